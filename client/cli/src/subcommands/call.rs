@@ -87,7 +87,7 @@ impl Call {
             &contract_address,
         )?;
 
-        let bytes_ret: Vec<u8> = provider.call_transaction(call_request).await?.0;
+        let bytes_ret: Vec<u8> = provider.call_transaction(call_request, None, None).await?.0;
         let u64_ret = bytes_to_u64s(bytes_ret.clone());
         let decoded = abi
             .decode_output_from_slice(func.signature().as_str(), &u64_ret)
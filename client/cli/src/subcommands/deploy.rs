@@ -2,14 +2,11 @@ use std::{fs::File, path::PathBuf};
 
 use anyhow::Result;
 use clap::Parser;
-use ethereum_types::{H256, U256};
+use ethereum_types::H256;
 use ola_lang::codegen::core::ir::function::print;
 use ola_lang_abi::{Abi, FixedArray4, Value};
 use ola_types::{L2ChainId, Nonce};
-use ola_utils::{
-    convert::{h256_to_u64_array, u64s_to_bytes},
-    hash::hash_bytes,
-};
+use ola_utils::convert::h256_to_u64_array;
 use ola_wallet_sdk::{
     abi::create_calldata,
     key_store::OlaKeyPair,
@@ -17,7 +14,7 @@ use ola_wallet_sdk::{
     program_meta::ProgramMeta,
     provider::ProviderParams,
     signer::Signer,
-    utils::{h256_from_hex_be, is_h256_a_valid_ola_hash},
+    utils::{compute_contract_address, h256_from_hex_be, is_h256_a_valid_ola_hash},
     wallet::Wallet,
 };
 use ola_web3_decl::jsonrpsee::http_client::HttpClientBuilder;
@@ -32,6 +29,11 @@ pub struct Deploy {
     aa: Option<String>,
     #[clap(long, help = "Provide transaction nonce manually")]
     nonce: Option<u32>,
+    #[clap(
+        long,
+        help = "Salt for the deterministic deployment address, random if not provided"
+    )]
+    salt: Option<String>,
     #[clap(long, env = "OLA_KEYSTORE", help = "Path to keystore config JSON file")]
     keystore: String,
     #[clap(
@@ -71,13 +73,22 @@ impl Deploy {
             .find(|func| func.name == "create2")
             .expect("create2 function not found in abi file");
 
-        let salt = Self::random_salt();
+        let salt = match self.salt {
+            Some(salt) => {
+                let salt = h256_from_hex_be(salt.as_str())?;
+                if !is_h256_a_valid_ola_hash(salt) {
+                    anyhow::bail!("salt limbs must be lower than the field order");
+                }
+                salt
+            }
+            None => Self::random_salt(),
+        };
         let prog_hash = prog_meta.program_hash;
         let bytecode_hash = prog_meta.bytecode_hash;
         let code = prog_meta.instructions;
 
         let params = [
-            Value::Hash(FixedArray4(salt.0)),
+            Value::Hash(FixedArray4(h256_to_u64_array(&salt))),
             Value::Hash(FixedArray4(h256_to_u64_array(&prog_hash))),
             Value::Hash(FixedArray4(h256_to_u64_array(&bytecode_hash))),
         ];
@@ -124,29 +135,19 @@ impl Deploy {
             .raw_code(prog_meta.bytes)
             .send()
             .await?;
-        let new_address = Self::get_new_deployed_address(&from, &salt, &bytecode_hash);
+        let new_address = compute_contract_address(&from, &salt, &bytecode_hash);
         println!("New Deployed Address: 0x{}", hex::encode(&new_address));
+        println!("salt: 0x{}", hex::encode(&salt));
         let tx_hash = hex::encode(&handle.hash());
         println!("tx_hash: 0x{}", tx_hash);
         Ok(())
     }
 
-    fn random_salt() -> U256 {
+    fn random_salt() -> H256 {
         let mut salt = H256::random();
         while !is_h256_a_valid_ola_hash(salt) {
             salt = H256::random();
         }
-        U256(h256_to_u64_array(&salt))
-    }
-
-    fn get_new_deployed_address(creator: &H256, salt: &U256, bytecode_hash: &H256) -> H256 {
-        let mut input = Vec::new();
-        let u64_prefix = "OlaCreate2".chars().map(|c| c as u64).collect::<Vec<_>>();
-        input.extend_from_slice(&hash_bytes(&u64s_to_bytes(&u64_prefix)).to_fixed_bytes());
-        input.extend_from_slice(&creator.to_fixed_bytes());
-        input.extend_from_slice(&u64s_to_bytes(&salt.0));
-        input.extend_from_slice(&bytecode_hash.to_fixed_bytes());
-
-        hash_bytes(&input)
+        salt
     }
 }
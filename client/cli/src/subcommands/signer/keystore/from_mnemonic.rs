@@ -0,0 +1,66 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+use ola_wallet_sdk::{key_store::OlaKeyPair, mnemonic::derivation_path};
+
+use crate::{path::ExpandedPathbufParser, subcommands::signer::mnemonic::read_mnemonic};
+
+#[derive(Debug, Parser)]
+pub struct FromMnemonic {
+    #[clap(long, help = "Overwrite the file if it already exists")]
+    force: bool,
+    #[clap(long, default_value_t = 0, help = "Index of the account to derive")]
+    account_index: u32,
+    #[clap(long, help = "Take the mnemonic from stdin instead of prompt")]
+    mnemonic_stdin: bool,
+    #[clap(long, help = "Prompt for the BIP39 passphrase protecting the mnemonic")]
+    with_passphrase: bool,
+    #[clap(
+        long,
+        help = "Supply password from command line option instead of prompt"
+    )]
+    password: Option<String>,
+    #[clap(
+        value_parser = ExpandedPathbufParser,
+        help = "Path to save the JSON keystore"
+    )]
+    file: PathBuf,
+}
+
+impl FromMnemonic {
+    pub fn run(self) -> Result<()> {
+        if self.password.is_some() {
+            eprintln!(
+                "{}",
+                "WARNING: setting passwords via --password is generally considered insecure, \
+                as they will be stored in your shell history or other log files."
+                    .bright_magenta()
+            );
+        }
+
+        if self.file.exists() && !self.force {
+            anyhow::bail!("keystore file already exists");
+        }
+
+        let (phrase, passphrase) = read_mnemonic(self.mnemonic_stdin, self.with_passphrase)?;
+        let key = OlaKeyPair::from_mnemonic(&phrase, &passphrase, self.account_index)?;
+
+        let password = if let Some(password) = self.password {
+            password
+        } else {
+            rpassword::prompt_password("Enter password: ")?
+        };
+        key.save_as_keystore(&self.file, &password)?;
+
+        println!(
+            "Created new encrypted keystore file: {}",
+            std::fs::canonicalize(self.file)?.display()
+        );
+        println!("Derivation path: {}", derivation_path(self.account_index));
+        println!("Public key: 0x{}", key.public_key_str());
+        println!("Address: 0x{}", key.address_str());
+        Ok(())
+    }
+}
@@ -13,6 +13,9 @@ use inspect::Inspect;
 mod from_key;
 use from_key::FromKey;
 
+mod from_mnemonic;
+use from_mnemonic::FromMnemonic;
+
 #[derive(Debug, Parser)]
 pub struct Keystore {
     #[clap(subcommand)]
@@ -29,6 +32,8 @@ enum Subcommands {
     Inspect(Inspect),
     #[clap(about = "Create a keystore file from an existing private key")]
     FromKey(FromKey),
+    #[clap(about = "Create a keystore file from an account derived from a mnemonic")]
+    FromMnemonic(FromMnemonic),
 }
 
 impl Keystore {
@@ -38,6 +43,7 @@ impl Keystore {
             Subcommands::InspectPrivate(cmd) => cmd.run(),
             Subcommands::Inspect(cmd) => cmd.run(),
             Subcommands::FromKey(cmd) => cmd.run(),
+            Subcommands::FromMnemonic(cmd) => cmd.run(),
         }
     }
 }
@@ -0,0 +1,32 @@
+use anyhow::Result;
+use clap::Parser;
+use ola_wallet_sdk::{key_store::OlaKeyPair, mnemonic::derivation_path};
+
+use super::read_mnemonic;
+
+#[derive(Debug, Parser)]
+pub struct Derive {
+    #[clap(long, default_value_t = 0, help = "Index of the account to derive")]
+    account_index: u32,
+    #[clap(long, help = "Take the mnemonic from stdin instead of prompt")]
+    mnemonic_stdin: bool,
+    #[clap(long, help = "Prompt for the BIP39 passphrase protecting the mnemonic")]
+    with_passphrase: bool,
+    #[clap(long, help = "Print the private key as well")]
+    private: bool,
+}
+
+impl Derive {
+    pub fn run(self) -> Result<()> {
+        let (phrase, passphrase) = read_mnemonic(self.mnemonic_stdin, self.with_passphrase)?;
+        let key = OlaKeyPair::from_mnemonic(&phrase, &passphrase, self.account_index)?;
+
+        println!("Derivation path: {}", derivation_path(self.account_index));
+        if self.private {
+            println!("Private key: 0x{}", key.private_key_str());
+        }
+        println!("Public key: 0x{}", key.public_key_str());
+        println!("Address: 0x{}", key.address_str());
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use std::io::Read;
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+
+mod new;
+use new::New;
+
+mod derive;
+use derive::Derive;
+
+#[derive(Debug, Parser)]
+pub struct Mnemonic {
+    #[clap(subcommand)]
+    command: Subcommands,
+}
+
+#[derive(Debug, Subcommand)]
+enum Subcommands {
+    #[clap(about = "Randomly generate a new mnemonic")]
+    New(New),
+    #[clap(about = "Derive an account key from an existing mnemonic")]
+    Derive(Derive),
+}
+
+impl Mnemonic {
+    pub fn run(self) -> Result<()> {
+        match self.command {
+            Subcommands::New(cmd) => cmd.run(),
+            Subcommands::Derive(cmd) => cmd.run(),
+        }
+    }
+}
+
+/// Reads a mnemonic phrase and an optional BIP39 passphrase (empty if not requested).
+pub(crate) fn read_mnemonic(from_stdin: bool, with_passphrase: bool) -> Result<(String, String)> {
+    let phrase = if from_stdin {
+        let mut buffer = String::new();
+        std::io::stdin().read_to_string(&mut buffer)?;
+        buffer
+    } else {
+        rpassword::prompt_password("Enter mnemonic: ")?
+    };
+    let passphrase = if with_passphrase {
+        rpassword::prompt_password("Enter mnemonic passphrase: ")?
+    } else {
+        String::new()
+    };
+    Ok((phrase, passphrase))
+}
@@ -0,0 +1,36 @@
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+use ola_wallet_sdk::{
+    key_store::OlaKeyPair,
+    mnemonic::{derivation_path, generate_mnemonic},
+};
+
+#[derive(Debug, Parser)]
+pub struct New {
+    #[clap(
+        long,
+        default_value_t = 12,
+        help = "Number of words in the mnemonic: 12, 15, 18, 21 or 24"
+    )]
+    words: usize,
+}
+
+impl New {
+    pub fn run(self) -> Result<()> {
+        let phrase = generate_mnemonic(self.words)?;
+        let key = OlaKeyPair::from_mnemonic(&phrase, "", 0)?;
+
+        eprintln!(
+            "{}",
+            "WARNING: anyone with this mnemonic controls all accounts derived from it. \
+            Write it down and keep it offline."
+                .bright_magenta()
+        );
+        println!("Mnemonic: {phrase}");
+        println!("Derivation path: {}", derivation_path(0));
+        println!("Public key: 0x{}", key.public_key_str());
+        println!("Address: 0x{}", key.address_str());
+        Ok(())
+    }
+}
@@ -4,6 +4,9 @@ use clap::{Parser, Subcommand};
 mod keystore;
 use keystore::Keystore;
 
+pub(crate) mod mnemonic;
+use mnemonic::Mnemonic;
+
 #[derive(Debug, Parser)]
 pub struct Signer {
     #[clap(subcommand)]
@@ -14,12 +17,15 @@ pub struct Signer {
 enum Subcommands {
     #[clap(about = "Keystore management commands")]
     Keystore(Keystore),
+    #[clap(about = "BIP39 mnemonic commands")]
+    Mnemonic(Mnemonic),
 }
 
 impl Signer {
     pub fn run(self) -> Result<()> {
         match self.command {
             Subcommands::Keystore(cmd) => cmd.run(),
+            Subcommands::Mnemonic(cmd) => cmd.run(),
         }
     }
 }
@@ -1,17 +1,230 @@
+use std::{fs::File, path::PathBuf};
+
 use anyhow::{bail, Ok, Result};
 use clap::Parser;
-use ola_wallet_sdk::provider::{ExtendProvider, ProviderParams};
+use ola_lang_abi::{Abi, DecodedParams, FixedArray4};
+use ola_types::api::{Log, TransactionDetails, TransactionReceipt};
+use ola_utils::convert::{bytes_to_u64s, h256_to_u64_array};
+use ola_wallet_sdk::{
+    abi::decode_entry_point_calldata,
+    parser::FromValue,
+    provider::{ExtendProvider, ProviderParams},
+};
+use serde::Serialize;
 
-use crate::utils::from_hex_be;
+use crate::{path::ExpandedPathbufParser, utils::from_hex_be};
 
 #[derive(Debug, Parser)]
 pub struct Transaction {
     #[clap(long, help = "network name, can be local or alpha")]
     network: Option<String>,
+    #[clap(
+        long,
+        value_parser = ExpandedPathbufParser,
+        help = "Path to the ABI file of the called contract, used to decode calldata and events"
+    )]
+    abi: Option<PathBuf>,
+    #[clap(long, help = "Print the output as JSON")]
+    json: bool,
     #[clap(help = "Transaction hash")]
     hash: String,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionReport {
+    hash: String,
+    details: TransactionDetails,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    call: Option<CallReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    receipt: Option<ReceiptReport>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CallReport {
+    from: String,
+    to: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    function: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    params: Option<Vec<ParamReport>>,
+    raw_calldata: Vec<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReceiptReport {
+    status: Option<u64>,
+    block_number: Option<u64>,
+    l1_batch_number: Option<u64>,
+    contract_address: Option<String>,
+    events: Vec<EventReport>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EventReport {
+    address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    params: Option<Vec<ParamReport>>,
+    topics: Vec<String>,
+    data: Vec<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct ParamReport {
+    name: String,
+    #[serde(rename = "type")]
+    type_: String,
+    value: String,
+}
+
+fn param_reports(params: &DecodedParams) -> Vec<ParamReport> {
+    params
+        .iter()
+        .map(|param| ParamReport {
+            name: param.param.name.clone(),
+            type_: param.param.type_.to_string(),
+            value: FromValue::parse_input(param.value.clone()),
+        })
+        .collect()
+}
+
+fn call_report(calldata: &[u8], abi: Option<&Abi>) -> Option<CallReport> {
+    let call = decode_entry_point_calldata(calldata).ok()?;
+    let decoded = abi.and_then(|abi| {
+        if call.data.len() < 2 {
+            return None;
+        }
+        abi.decode_input_from_slice(&call.data).ok()
+    });
+    let (function, params) = match decoded {
+        Some((func, params)) => (Some(func.signature()), Some(param_reports(&params))),
+        None => (None, None),
+    };
+    Some(CallReport {
+        from: format!("{:?}", call.from),
+        to: format!("{:?}", call.to),
+        function,
+        params,
+        raw_calldata: call.data,
+    })
+}
+
+fn event_report(log: &Log, abi: Option<&Abi>) -> EventReport {
+    let data = if log.data.0.len() % 8 == 0 {
+        bytes_to_u64s(log.data.0.clone())
+    } else {
+        vec![]
+    };
+    let topics: Vec<FixedArray4> = log
+        .topics
+        .iter()
+        .map(|topic| FixedArray4(h256_to_u64_array(topic)))
+        .collect();
+    let decoded = abi.and_then(|abi| abi.decode_log_from_slice(&topics, &data).ok());
+    let (name, params) = match decoded {
+        Some((event, params)) => (Some(event.name.clone()), Some(param_reports(&params))),
+        None => (None, None),
+    };
+    EventReport {
+        address: format!("{:?}", log.address),
+        name,
+        params,
+        topics: log
+            .topics
+            .iter()
+            .map(|topic| format!("{topic:?}"))
+            .collect(),
+        data,
+    }
+}
+
+fn receipt_report(receipt: &TransactionReceipt, abi: Option<&Abi>) -> ReceiptReport {
+    ReceiptReport {
+        status: receipt.status.map(|status| status.as_u64()),
+        block_number: receipt.block_number.map(|number| number.as_u64()),
+        l1_batch_number: receipt.l1_batch_number.map(|number| number.as_u64()),
+        contract_address: receipt
+            .contract_address
+            .map(|address| format!("{address:?}")),
+        events: receipt
+            .logs
+            .iter()
+            .map(|log| event_report(log, abi))
+            .collect(),
+    }
+}
+
+fn format_params(params: &[ParamReport]) -> String {
+    params
+        .iter()
+        .map(|param| format!("{} {} = {}", param.type_, param.name, param.value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn print_report(report: &TransactionReport) {
+    let details = &report.details;
+    println!("Transaction {}", report.hash);
+    println!("  status:      {:?}", details.status);
+    println!("  initiator:   {:?}", details.initiator_address);
+    println!("  received at: {}", details.received_at);
+    if let Some(revert_reason) = &details.revert_reason {
+        println!("  revert reason: {revert_reason}");
+    }
+
+    if let Some(call) = &report.call {
+        println!("Call:");
+        println!("  from: {}", call.from);
+        println!("  to:   {}", call.to);
+        match (&call.function, &call.params) {
+            (Some(function), Some(params)) => {
+                println!("  function: {function}");
+                println!("  params:   {}", format_params(params));
+            }
+            _ => println!("  calldata: {:?}", call.raw_calldata),
+        }
+    }
+
+    if let Some(receipt) = &report.receipt {
+        println!("Receipt:");
+        if let Some(status) = receipt.status {
+            let status = if status == 1 { "success" } else { "failure" };
+            println!("  status:          {status}");
+        }
+        if let Some(block_number) = receipt.block_number {
+            println!("  block number:    {block_number}");
+        }
+        if let Some(l1_batch_number) = receipt.l1_batch_number {
+            println!("  l1 batch number: {l1_batch_number}");
+        }
+        if let Some(contract_address) = &receipt.contract_address {
+            println!("  contract:        {contract_address}");
+        }
+        for (index, event) in receipt.events.iter().enumerate() {
+            match (&event.name, &event.params) {
+                (Some(name), Some(params)) => println!(
+                    "  event #{index} {}: {name}({})",
+                    event.address,
+                    format_params(params)
+                ),
+                _ => {
+                    println!("  event #{index} {}:", event.address);
+                    for topic in &event.topics {
+                        println!("    topic: {topic}");
+                    }
+                    println!("    data:  {:?}", event.data);
+                }
+            }
+        }
+    }
+}
+
 impl Transaction {
     pub async fn run(self) -> Result<()> {
         let network = if let Some(network) = self.network {
@@ -25,17 +238,32 @@ impl Transaction {
         } else {
             ProviderParams::alpha()
         };
+        let abi: Option<Abi> = match self.abi {
+            Some(path) => {
+                let abi_file = File::open(path).expect("failed to open ABI file");
+                Some(serde_json::from_reader(abi_file)?)
+            }
+            None => None,
+        };
         let hash = from_hex_be(self.hash.as_str()).expect("invalid transaction hash");
         let provider = ExtendProvider::with_http_client(network.http_endpoint.as_str()).unwrap();
-        let tx_detail = provider.get_transaction_detail(hash).await?;
-        match tx_detail {
-            Some(tx) => {
-                let tx_json = serde_json::to_string(&tx).unwrap();
-                println!("Transaction Details:\n{}", tx_json);
-            }
-            None => {
-                println!("No transaction found by tx_hash: {}", self.hash)
-            }
+        let Some(details) = provider.get_transaction_detail(hash).await? else {
+            println!("No transaction found by tx_hash: {}", self.hash);
+            return Ok(());
+        };
+        let tx = provider.get_transaction_by_hash(hash).await?;
+        let receipt = provider.get_transaction_receipt(hash).await?;
+
+        let report = TransactionReport {
+            hash: format!("{hash:?}"),
+            details,
+            call: tx.and_then(|tx| call_report(&tx.input.0, abi.as_ref())),
+            receipt: receipt.map(|receipt| receipt_report(&receipt, abi.as_ref())),
+        };
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            print_report(&report);
         }
         Ok(())
     }
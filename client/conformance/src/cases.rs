@@ -0,0 +1,1038 @@
+//! Conformance cases: JSON-RPC requests and the responses they must produce.
+//!
+//! Successful results are checked by deserializing them into the canonical types of the methods
+//! declared in `ola_web3_decl`. Methods that execute the VM or change the node state are only
+//! checked for rejecting malformed params, so that the suite can be run against any node
+//! without deployed contracts or side effects.
+
+use std::{any, fmt};
+
+use ola_config::dump::ConfigDump;
+use ola_types::{
+    api::{
+        proof_offchain_verification::{
+            L1BatchAttestations, L1BatchDetailsWithOffchainVerification,
+        },
+        AccountInfo, Block, BlockDetails, CalldataScreeningRules, ContractResourceUsage,
+        FeeHistory, L1BatchDetails, Log, MempoolStatus, NodeInfo, Proof, ProtocolVersion,
+        ReorgedOutBlock, ReorgedOutTransaction, StorageSample, SyncState, SystemContract,
+        TokenBalance, TokenDetails, Transaction, TransactionInclusionProof, TransactionReceipt,
+        TransactionStatusInfo, TransactionVariant, TxpoolContent, VmConcurrencyStatus,
+    },
+    Address, Bytes, L1BatchNumber, H256, U256, U64,
+};
+use ola_web3_decl::types::{
+    AuditLogPage, BlockTransactionsPage, L1BatchesPage, LogsPage, PageCursor, StorageHistoryPage,
+    StorageRangePage, TransactionsPage,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+
+/// JSON-RPC 2.0 error code for unknown methods.
+pub const METHOD_NOT_FOUND_CODE: i32 = -32601;
+/// JSON-RPC 2.0 error code for params not matching the method signature.
+pub const INVALID_PARAMS_CODE: i32 = -32602;
+/// EIP-1898 error code for requests referencing an unknown block hash.
+pub const RESOURCE_NOT_FOUND_CODE: i32 = -32001;
+/// Error code for transactions that can't be deserialized or submitted.
+pub const TRANSACTION_ERROR_CODE: i32 = 3;
+
+/// Group of methods served together, following API namespaces of the node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Suite {
+    Eth,
+    Net,
+    Web3,
+    Ola,
+    /// Debugging methods of the `ola` namespace served only if the admin namespace is enabled.
+    Admin,
+    Txpool,
+    Verifier,
+}
+
+impl Suite {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Eth => "eth",
+            Self::Net => "net",
+            Self::Web3 => "web3",
+            Self::Ola => "ola",
+            Self::Admin => "admin",
+            Self::Txpool => "txpool",
+            Self::Verifier => "verifier",
+        }
+    }
+
+    /// Whether the suite is served only over WebSocket, i.e., by the offchain verifier server
+    /// that has no HTTP transport.
+    pub fn is_ws_only(self) -> bool {
+        matches!(self, Self::Verifier)
+    }
+}
+
+impl fmt::Display for Suite {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.pad(self.as_str())
+    }
+}
+
+/// Check of a successful result.
+#[derive(Clone, Copy)]
+pub struct ResultCheck {
+    /// Name of the expected result type, reported on failures.
+    pub type_name: &'static str,
+    check: fn(&Value) -> Result<(), String>,
+}
+
+impl fmt::Debug for ResultCheck {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("ResultCheck")
+            .field("type_name", &self.type_name)
+            .finish()
+    }
+}
+
+impl ResultCheck {
+    /// Result deserializable into `T`.
+    fn of<T: DeserializeOwned>() -> Self {
+        Self {
+            type_name: any::type_name::<T>(),
+            check: |value| {
+                serde_json::from_value::<T>(value.clone())
+                    .map(drop)
+                    .map_err(|err| err.to_string())
+            },
+        }
+    }
+
+    /// Non-null result deserializable into `T`.
+    fn present<T: DeserializeOwned>() -> Self {
+        Self {
+            type_name: any::type_name::<T>(),
+            check: |value| {
+                if value.is_null() {
+                    return Err("result is null".to_owned());
+                }
+                serde_json::from_value::<T>(value.clone())
+                    .map(drop)
+                    .map_err(|err| err.to_string())
+            },
+        }
+    }
+
+    /// `null` result, e.g. for a non-existing entity.
+    fn null() -> Self {
+        Self {
+            type_name: "null",
+            check: |value| {
+                if value.is_null() {
+                    Ok(())
+                } else {
+                    Err(format!("expected null, got {value}"))
+                }
+            },
+        }
+    }
+
+    pub fn check(&self, value: &Value) -> Result<(), String> {
+        (self.check)(value)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Expected {
+    /// Successful response passing the check.
+    Result(ResultCheck),
+    /// Error response with the specified code.
+    Error(i32),
+}
+
+impl Expected {
+    fn result<T: DeserializeOwned>() -> Self {
+        Self::Result(ResultCheck::of::<T>())
+    }
+
+    fn present<T: DeserializeOwned>() -> Self {
+        Self::Result(ResultCheck::present::<T>())
+    }
+
+    fn null() -> Self {
+        Self::Result(ResultCheck::null())
+    }
+
+    /// Non-null `T` if the referenced entity exists, otherwise `null`.
+    fn present_if<T: DeserializeOwned>(exists: bool) -> Self {
+        if exists {
+            Self::present::<T>()
+        } else {
+            Self::null()
+        }
+    }
+}
+
+/// Single request to the node and its expected response.
+#[derive(Debug)]
+pub struct Case {
+    pub suite: Suite,
+    pub method: &'static str,
+    /// What the case checks, e.g. "block referenced by EIP-1898 hash object".
+    pub description: &'static str,
+    pub params: Vec<Value>,
+    pub expected: Expected,
+    /// Whether the method is served only if an optional node component runs (e.g., the Merkle
+    /// tree or the mempool). Errors of such cases are reported as skipped rather than failed.
+    pub optional: bool,
+}
+
+impl Case {
+    fn new(
+        suite: Suite,
+        method: &'static str,
+        description: &'static str,
+        params: Vec<Value>,
+        expected: Expected,
+    ) -> Self {
+        Self {
+            suite,
+            method,
+            description,
+            params,
+            expected,
+            optional: false,
+        }
+    }
+
+    fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+}
+
+/// Chain data referenced by cases, discovered from the node before running them.
+#[derive(Debug, Clone, Default)]
+pub struct ChainContext {
+    pub block_number: U64,
+    pub block_hash: H256,
+    pub l1_batch_number: U64,
+    /// Hash and initiator of a transaction in a recent block, if any.
+    pub transaction: Option<(H256, Address)>,
+}
+
+/// Returns cases for the specified suites, in the order they should be run.
+pub fn cases(context: &ChainContext, suites: &[Suite]) -> Vec<Case> {
+    let mut cases = vec![];
+    for &suite in suites {
+        cases.extend(match suite {
+            Suite::Eth => eth_cases(context),
+            Suite::Net => net_cases(),
+            Suite::Web3 => web3_cases(),
+            Suite::Ola => ola_cases(context),
+            Suite::Admin => admin_cases(),
+            Suite::Txpool => txpool_cases(),
+            Suite::Verifier => verifier_cases(context),
+        });
+    }
+    cases
+}
+
+/// Hash that doesn't reference any block or transaction.
+fn unknown_hash() -> H256 {
+    H256::repeat_byte(0xee)
+}
+
+fn eth_cases(context: &ChainContext) -> Vec<Case> {
+    use Suite::Eth;
+
+    let block_number = context.block_number;
+    let block_hash = context.block_hash;
+    let has_tx = context.transaction.is_some();
+    let (tx_hash, account) = context.transaction.unwrap_or_default();
+    vec![
+        Case::new(
+            Eth,
+            "eth_nonexistentMethod",
+            "unknown method",
+            vec![],
+            Expected::Error(METHOD_NOT_FOUND_CODE),
+        ),
+        Case::new(
+            Eth,
+            "eth_blockNumber",
+            "latest block number",
+            vec![],
+            Expected::result::<U64>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_chainId",
+            "chain ID",
+            vec![],
+            Expected::result::<U64>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getBlockByNumber",
+            "latest block with transaction hashes",
+            vec![json!("latest"), json!(false)],
+            Expected::present::<Block<TransactionVariant>>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getBlockByNumber",
+            "block by number with full transactions",
+            vec![json!(block_number), json!(true)],
+            Expected::present::<Block<TransactionVariant>>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getBlockByNumber",
+            "non-existing block",
+            vec![json!(U64::from(u32::MAX)), json!(false)],
+            Expected::null(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getBlockByNumber",
+            "malformed block number",
+            vec![json!("notABlock"), json!(false)],
+            Expected::Error(INVALID_PARAMS_CODE),
+        ),
+        Case::new(
+            Eth,
+            "eth_getBlockByHash",
+            "block by hash",
+            vec![json!(block_hash), json!(false)],
+            Expected::present::<Block<TransactionVariant>>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getBlockByHash",
+            "unknown block hash",
+            vec![json!(unknown_hash()), json!(false)],
+            Expected::null(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getBlockTransactionCountByNumber",
+            "latest block",
+            vec![json!("latest")],
+            Expected::present::<U256>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getBlockTransactionCountByHash",
+            "block by hash",
+            vec![json!(block_hash)],
+            Expected::present::<U256>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getBlockReceipts",
+            "block by number",
+            vec![json!(block_number)],
+            Expected::result::<Vec<TransactionReceipt>>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getBlockReceipts",
+            "block by hash",
+            vec![json!(block_hash)],
+            Expected::result::<Vec<TransactionReceipt>>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getTransactionCount",
+            "omitted block",
+            vec![json!(account)],
+            Expected::result::<u32>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getTransactionCount",
+            "block tag",
+            vec![json!(account), json!("latest")],
+            Expected::result::<u32>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getTransactionCount",
+            "EIP-1898 block number object",
+            vec![json!(account), json!({ "blockNumber": block_number })],
+            Expected::result::<u32>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getTransactionCount",
+            "EIP-1898 block hash object",
+            vec![json!(account), json!({ "blockHash": block_hash })],
+            Expected::result::<u32>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getTransactionCount",
+            "EIP-1898 canonical block hash object",
+            vec![
+                json!(account),
+                json!({ "blockHash": block_hash, "requireCanonical": true }),
+            ],
+            Expected::result::<u32>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getTransactionCount",
+            "EIP-1898 unknown block hash",
+            vec![json!(account), json!({ "blockHash": unknown_hash() })],
+            Expected::Error(RESOURCE_NOT_FOUND_CODE),
+        ),
+        Case::new(
+            Eth,
+            "eth_getStorageAt",
+            "block tag",
+            vec![json!(account), json!(U256::zero()), json!("latest")],
+            Expected::result::<H256>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getStorageAt",
+            "EIP-1898 block hash object",
+            vec![
+                json!(account),
+                json!(U256::zero()),
+                json!({ "blockHash": block_hash, "requireCanonical": true }),
+            ],
+            Expected::result::<H256>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getStorageAt",
+            "EIP-1898 unknown block hash",
+            vec![
+                json!(account),
+                json!(U256::zero()),
+                json!({ "blockHash": unknown_hash(), "requireCanonical": true }),
+            ],
+            Expected::Error(RESOURCE_NOT_FOUND_CODE),
+        ),
+        Case::new(
+            Eth,
+            "eth_getLogs",
+            "block range",
+            vec![json!({ "fromBlock": block_number, "toBlock": "latest" })],
+            Expected::result::<Vec<Log>>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getLogs",
+            "block hash with address and topics",
+            vec![json!({
+                "blockHash": block_hash,
+                "address": [account],
+                "topics": [null, [unknown_hash()]],
+            })],
+            Expected::result::<Vec<Log>>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getLogs",
+            "unknown block hash",
+            vec![json!({ "blockHash": unknown_hash() })],
+            Expected::Error(RESOURCE_NOT_FOUND_CODE),
+        ),
+        Case::new(
+            Eth,
+            "eth_getLogs",
+            "block hash together with a block range",
+            vec![json!({ "blockHash": block_hash, "fromBlock": block_number })],
+            Expected::Error(INVALID_PARAMS_CODE),
+        ),
+        Case::new(
+            Eth,
+            "eth_getTransactionByHash",
+            "recent transaction",
+            vec![json!(tx_hash)],
+            Expected::present_if::<Transaction>(has_tx),
+        ),
+        Case::new(
+            Eth,
+            "eth_getTransactionByHash",
+            "unknown transaction",
+            vec![json!(unknown_hash())],
+            Expected::null(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getTransactionByBlockHashAndIndex",
+            "first transaction of a block",
+            vec![json!(block_hash), json!(U64::zero())],
+            Expected::result::<Option<Transaction>>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getTransactionByBlockNumberAndIndex",
+            "first transaction of a block",
+            vec![json!(block_number), json!(U64::zero())],
+            Expected::result::<Option<Transaction>>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_getTransactionReceipt",
+            "recent transaction",
+            vec![json!(tx_hash)],
+            Expected::present_if::<TransactionReceipt>(has_tx),
+        ),
+        Case::new(
+            Eth,
+            "eth_protocolVersion",
+            "protocol version",
+            vec![],
+            Expected::result::<String>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_syncing",
+            "sync state",
+            vec![],
+            Expected::result::<SyncState>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_accounts",
+            "accounts",
+            vec![],
+            Expected::result::<Vec<Address>>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_gasPrice",
+            "gas price",
+            vec![],
+            Expected::result::<U256>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_feeHistory",
+            "latest blocks with reward percentiles",
+            vec![json!(U64::from(4)), json!("latest"), json!([25.0, 75.0])],
+            Expected::result::<FeeHistory>(),
+        ),
+        Case::new(
+            Eth,
+            "eth_feeHistory",
+            "decreasing reward percentiles",
+            vec![json!(U64::from(4)), json!("latest"), json!([75.0, 25.0])],
+            Expected::Error(INVALID_PARAMS_CODE),
+        ),
+    ]
+}
+
+fn net_cases() -> Vec<Case> {
+    use Suite::Net;
+
+    vec![
+        Case::new(
+            Net,
+            "net_version",
+            "network ID",
+            vec![],
+            Expected::result::<String>(),
+        ),
+        Case::new(
+            Net,
+            "net_peerCount",
+            "peer count",
+            vec![],
+            Expected::result::<U256>(),
+        ),
+        Case::new(
+            Net,
+            "net_listening",
+            "listening flag",
+            vec![],
+            Expected::result::<bool>(),
+        ),
+    ]
+}
+
+fn web3_cases() -> Vec<Case> {
+    vec![Case::new(
+        Suite::Web3,
+        "web3_clientVersion",
+        "client version",
+        vec![],
+        Expected::result::<String>(),
+    )]
+}
+
+fn ola_cases(context: &ChainContext) -> Vec<Case> {
+    use Suite::Ola;
+
+    let block_number = context.block_number.as_u32();
+    let block_hash = context.block_hash;
+    let l1_batch_number = context.l1_batch_number.as_u32();
+    let has_tx = context.transaction.is_some();
+    let (tx_hash, account) = context.transaction.unwrap_or_default();
+    vec![
+        Case::new(
+            Ola,
+            "ola_sendRawTransaction",
+            "malformed transaction bytes",
+            vec![json!(Bytes(vec![0]))],
+            Expected::Error(TRANSACTION_ERROR_CODE),
+        ),
+        Case::new(
+            Ola,
+            "ola_sendRawTransactionWithSimulation",
+            "malformed transaction bytes",
+            vec![json!(Bytes(vec![0]))],
+            Expected::Error(TRANSACTION_ERROR_CODE),
+        ),
+        Case::new(
+            Ola,
+            "ola_sendScheduledTransaction",
+            "malformed transaction bytes",
+            vec![json!(Bytes(vec![0])), json!(0)],
+            Expected::Error(TRANSACTION_ERROR_CODE),
+        ),
+        Case::new(
+            Ola,
+            "ola_sendScheduledTransaction",
+            "missing timestamp",
+            vec![json!(Bytes(vec![0]))],
+            Expected::Error(INVALID_PARAMS_CODE),
+        ),
+        Case::new(
+            Ola,
+            "ola_callTransaction",
+            "malformed call request",
+            vec![json!("notARequest")],
+            Expected::Error(INVALID_PARAMS_CODE),
+        ),
+        Case::new(
+            Ola,
+            "ola_callTransactionWithStats",
+            "malformed call request",
+            vec![json!("notARequest")],
+            Expected::Error(INVALID_PARAMS_CODE),
+        ),
+        Case::new(
+            Ola,
+            "ola_getCallOutput",
+            "unknown output hash",
+            vec![json!(unknown_hash())],
+            Expected::null(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getTransactionReceipt",
+            "recent transaction",
+            vec![json!(tx_hash)],
+            Expected::present_if::<TransactionReceipt>(has_tx),
+        ),
+        Case::new(
+            Ola,
+            "ola_postVerificationRes",
+            "malformed verification result",
+            vec![json!({ "isPassed": true })],
+            Expected::Error(INVALID_PARAMS_CODE),
+        ),
+        Case::new(
+            Ola,
+            "ola_L1ChainId",
+            "L1 chain ID",
+            vec![],
+            Expected::result::<U64>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_gasPrice",
+            "gas price",
+            vec![],
+            Expected::result::<U256>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getL1BatchNumber",
+            "latest L1 batch number",
+            vec![],
+            Expected::result::<U64>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getL1BatchBlockRange",
+            "latest L1 batch",
+            vec![json!(l1_batch_number)],
+            Expected::result::<Option<(U64, U64)>>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getBlockDetails",
+            "block by number",
+            vec![json!(block_number)],
+            Expected::present::<BlockDetails>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getTransactionDetails",
+            "recent transaction",
+            vec![json!(tx_hash)],
+            Expected::present_if::<ola_types::api::TransactionDetails>(has_tx),
+        ),
+        Case::new(
+            Ola,
+            "ola_getRawBlockTransactions",
+            "block by number",
+            vec![json!(block_number)],
+            Expected::result::<Vec<ola_types::Transaction>>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getBlockTransactions",
+            "first page of the latest block",
+            vec![json!("latest"), json!(null), json!(U64::from(16))],
+            Expected::present::<BlockTransactionsPage>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getL1BatchDetails",
+            "latest L1 batch",
+            vec![json!(l1_batch_number)],
+            Expected::result::<Option<L1BatchDetails>>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getProtocolVersion",
+            "latest protocol version",
+            vec![],
+            Expected::present::<ProtocolVersion>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getL1BatchDetailsWithOffchainVerification",
+            "latest L1 batch",
+            vec![json!(l1_batch_number)],
+            Expected::result::<Option<L1BatchDetailsWithOffchainVerification>>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getProof",
+            "single storage slot",
+            vec![json!(account), json!([H256::zero()])],
+            Expected::result::<Proof>(),
+        )
+        .optional(),
+        Case::new(
+            Ola,
+            "ola_nodeInfo",
+            "node info",
+            vec![],
+            Expected::result::<NodeInfo>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getAccountsInfo",
+            "single account",
+            vec![json!([account])],
+            Expected::result::<Vec<AccountInfo>>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getTransactionStatus",
+            "recent transaction",
+            vec![json!(tx_hash)],
+            Expected::present_if::<TransactionStatusInfo>(has_tx),
+        ),
+        Case::new(
+            Ola,
+            "ola_getSystemContracts",
+            "latest protocol version",
+            vec![],
+            Expected::result::<Vec<SystemContract>>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getTokenInfo",
+            "non-token address",
+            vec![json!(account)],
+            Expected::result::<Option<TokenDetails>>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getTokenBalances",
+            "account balances",
+            vec![json!(account)],
+            Expected::result::<Vec<TokenBalance>>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getTransactionInclusionProof",
+            "recent transaction",
+            vec![json!(tx_hash)],
+            Expected::result::<Option<TransactionInclusionProof>>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getStorageHistory",
+            "storage slot since genesis",
+            vec![
+                json!(account),
+                json!(H256::zero()),
+                json!(0),
+                json!(null),
+                json!(null),
+                json!(16),
+            ],
+            Expected::result::<StorageHistoryPage>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getStorageHistory",
+            "inverted block range",
+            vec![json!(account), json!(H256::zero()), json!(2), json!(1)],
+            Expected::Error(INVALID_PARAMS_CODE),
+        ),
+        Case::new(
+            Ola,
+            "ola_getReorgedOutBlocks",
+            "latest block number",
+            vec![json!(block_number)],
+            Expected::result::<Vec<ReorgedOutBlock>>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getReorgedOutTransaction",
+            "recent transaction",
+            vec![json!(tx_hash)],
+            Expected::result::<Vec<ReorgedOutTransaction>>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getLogsPage",
+            "first page",
+            vec![json!(null), json!(null), json!(16)],
+            Expected::result::<LogsPage>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getLogsPage",
+            "malformed cursor",
+            vec![json!(null), json!(Bytes(vec![0])), json!(16)],
+            Expected::Error(INVALID_PARAMS_CODE),
+        ),
+        Case::new(
+            Ola,
+            "ola_getTransactionsByAddress",
+            "first page",
+            vec![json!(account), json!(null), json!(16)],
+            Expected::result::<TransactionsPage>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getL1Batches",
+            "first page",
+            vec![json!(null), json!(16)],
+            Expected::result::<L1BatchesPage>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getAccountStorageRange",
+            "first page at the latest block",
+            vec![json!(account), json!(null), json!(null), json!(16)],
+            Expected::result::<StorageRangePage>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getAccountStorageRange",
+            "EIP-1898 block hash object",
+            vec![
+                json!(account),
+                json!({ "blockHash": block_hash, "requireCanonical": true }),
+                json!(null),
+                json!(16),
+            ],
+            Expected::result::<StorageRangePage>(),
+        ),
+        Case::new(
+            Ola,
+            "ola_getAccountStorageRange",
+            "EIP-1898 unknown block hash",
+            vec![json!(account), json!({ "blockHash": unknown_hash() })],
+            Expected::Error(RESOURCE_NOT_FOUND_CODE),
+        ),
+        Case::new(
+            Ola,
+            "ola_getAccountStorageRange",
+            "cursor of another list",
+            vec![
+                json!(account),
+                json!("latest"),
+                json!(PageCursor::new(L1BatchNumber(1))),
+            ],
+            Expected::Error(INVALID_PARAMS_CODE),
+        ),
+    ]
+}
+
+fn admin_cases() -> Vec<Case> {
+    use Suite::Admin;
+
+    vec![
+        Case::new(
+            Admin,
+            "ola_getMempoolStatus",
+            "next transactions",
+            vec![json!(16)],
+            Expected::result::<MempoolStatus>(),
+        )
+        .optional(),
+        Case::new(
+            Admin,
+            "ola_getVmConcurrency",
+            "VM concurrency status",
+            vec![],
+            Expected::result::<VmConcurrencyStatus>(),
+        )
+        .optional(),
+        Case::new(
+            Admin,
+            "ola_getConfig",
+            "configuration dump",
+            vec![],
+            Expected::result::<ConfigDump>(),
+        ),
+        Case::new(
+            Admin,
+            "ola_getTopContracts",
+            "time range",
+            vec![json!(0), json!(1)],
+            Expected::result::<Vec<ContractResourceUsage>>(),
+        ),
+        Case::new(
+            Admin,
+            "ola_getTopContracts",
+            "inverted time range",
+            vec![json!(2), json!(1)],
+            Expected::Error(INVALID_PARAMS_CODE),
+        ),
+        Case::new(
+            Admin,
+            "ola_getAuditLog",
+            "first page",
+            vec![json!(null), json!(null), json!(16)],
+            Expected::result::<AuditLogPage>(),
+        ),
+        Case::new(
+            Admin,
+            "ola_setNamespaceEnabled",
+            "unknown namespace",
+            vec![json!("nonexistent"), json!(true)],
+            Expected::Error(INVALID_PARAMS_CODE),
+        ),
+        Case::new(
+            Admin,
+            "ola_sampleStorageProofs",
+            "single key",
+            vec![json!(42), json!(1)],
+            Expected::result::<StorageSample>(),
+        )
+        .optional(),
+        Case::new(
+            Admin,
+            "ola_getCalldataScreeningRules",
+            "current rules",
+            vec![],
+            Expected::result::<CalldataScreeningRules>(),
+        )
+        .optional(),
+        Case::new(
+            Admin,
+            "ola_setCalldataScreeningRules",
+            "malformed rules",
+            vec![json!("notRules")],
+            Expected::Error(INVALID_PARAMS_CODE),
+        ),
+    ]
+}
+
+fn txpool_cases() -> Vec<Case> {
+    vec![Case::new(
+        Suite::Txpool,
+        "txpool_content",
+        "mempool content",
+        vec![],
+        Expected::result::<TxpoolContent>(),
+    )
+    .optional()]
+}
+
+fn verifier_cases(context: &ChainContext) -> Vec<Case> {
+    use Suite::Verifier;
+
+    vec![
+        Case::new(
+            Verifier,
+            "verifier_submitAttestation",
+            "malformed attestation",
+            vec![json!({ "isValid": true })],
+            Expected::Error(INVALID_PARAMS_CODE),
+        ),
+        Case::new(
+            Verifier,
+            "verifier_getL1BatchAttestations",
+            "latest L1 batch",
+            vec![json!(context.l1_batch_number.as_u32())],
+            Expected::result::<Option<L1BatchAttestations>>(),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn result_checks() {
+        let check = ResultCheck::of::<Option<U64>>();
+        check.check(&json!("0x1")).unwrap();
+        check.check(&Value::Null).unwrap();
+        check.check(&json!(1)).unwrap_err();
+
+        let check = ResultCheck::present::<U64>();
+        check.check(&json!("0x1")).unwrap();
+        assert_eq!(check.check(&Value::Null).unwrap_err(), "result is null");
+
+        let check = ResultCheck::null();
+        check.check(&Value::Null).unwrap();
+        check.check(&json!("0x1")).unwrap_err();
+    }
+
+    #[test]
+    fn cases_are_unique() {
+        let context = ChainContext::default();
+        let all_suites = [
+            Suite::Eth,
+            Suite::Net,
+            Suite::Web3,
+            Suite::Ola,
+            Suite::Admin,
+            Suite::Txpool,
+            Suite::Verifier,
+        ];
+        let cases = cases(&context, &all_suites);
+        let mut keys = HashSet::new();
+        for case in &cases {
+            assert!(
+                keys.insert((case.method, case.description)),
+                "duplicate case {} ({})",
+                case.method,
+                case.description
+            );
+        }
+        for suite in all_suites {
+            assert!(cases.iter().any(|case| case.suite == suite), "{suite}");
+        }
+    }
+}
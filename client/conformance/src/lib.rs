@@ -0,0 +1,11 @@
+//! JSON-RPC conformance test suite runnable against any Ola node.
+//!
+//! Each implemented RPC method is called with valid and invalid params; successful results are
+//! checked against the canonical result types, and errors against the expected error codes
+//! (including EIP-1898 block references). The suite doesn't change the node state, so it can be
+//! run against production nodes. Suites served only over WebSocket (e.g., the `verifier` one
+//! served by the offchain verifier server) are run over a separate WS client.
+
+pub mod cases;
+pub mod report;
+pub mod runner;
@@ -0,0 +1,89 @@
+//! Command-line runner of the conformance suite; prints the compatibility report of a node.
+
+use anyhow::Context as _;
+use clap::Parser;
+use conformance::{cases::Suite, runner::run_suites};
+use ola_web3_decl::jsonrpsee::{http_client::HttpClientBuilder, ws_client::WsClientBuilder};
+
+#[derive(Debug, Parser)]
+#[clap(about = "JSON-RPC conformance test suite for Ola nodes")]
+struct Args {
+    #[clap(
+        long,
+        default_value = "http://localhost:13000",
+        help = "HTTP API endpoint"
+    )]
+    http_endpoint: String,
+    #[clap(
+        long,
+        default_value = "ws://localhost:13003",
+        help = "WS endpoint of the offchain verifier server; only used by WS-only suites"
+    )]
+    ws_endpoint: String,
+    #[clap(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_value = "eth,net,web3,ola",
+        help = "Comma-separated method suites to run"
+    )]
+    suites: Vec<Suite>,
+    #[clap(long, help = "Print the report as JSON")]
+    json: bool,
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(err) = run(Args::parse()).await {
+        eprintln!("Error: {err:#}");
+        std::process::exit(1);
+    }
+}
+
+async fn run(args: Args) -> anyhow::Result<()> {
+    let client = HttpClientBuilder::default()
+        .build(&args.http_endpoint)
+        .with_context(|| format!("failed building client for {}", args.http_endpoint))?;
+    let ws_client = if args.suites.iter().any(|suite| suite.is_ws_only()) {
+        let ws_client = WsClientBuilder::default()
+            .build(&args.ws_endpoint)
+            .await
+            .with_context(|| format!("failed connecting to {}", args.ws_endpoint))?;
+        Some(ws_client)
+    } else {
+        None
+    };
+    let report = run_suites(&client, ws_client.as_ref(), &args.suites).await?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{report}");
+    }
+
+    let failed_count = report.failed_count();
+    anyhow::ensure!(failed_count == 0, "{failed_count} conformance cases failed");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_args() {
+        let args = Args::try_parse_from(["conformance"]).unwrap();
+        assert_eq!(
+            args.suites,
+            [Suite::Eth, Suite::Net, Suite::Web3, Suite::Ola]
+        );
+        assert_eq!(args.ws_endpoint, "ws://localhost:13003");
+        assert!(!args.json);
+
+        let args =
+            Args::try_parse_from(["conformance", "--suites", "admin,txpool", "--json"]).unwrap();
+        assert_eq!(args.suites, [Suite::Admin, Suite::Txpool]);
+        assert!(args.json);
+
+        assert!(Args::try_parse_from(["conformance", "--suites", "debug"]).is_err());
+    }
+}
@@ -0,0 +1,138 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::Serialize;
+
+use crate::cases::Suite;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", content = "reason", rename_all = "camelCase")]
+pub enum Outcome {
+    Passed,
+    Failed(String),
+    /// Optional case which the node hasn't served, e.g. because the component serving it
+    /// isn't running.
+    Skipped(String),
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaseReport {
+    pub suite: Suite,
+    pub method: &'static str,
+    pub description: &'static str,
+    pub outcome: Outcome,
+}
+
+/// Compatibility report of a node, with results of all cases in the order they were run.
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    pub cases: Vec<CaseReport>,
+}
+
+impl Report {
+    pub fn failed_count(&self) -> usize {
+        self.count(|outcome| matches!(outcome, Outcome::Failed(_)))
+    }
+
+    fn count(&self, predicate: impl Fn(&Outcome) -> bool) -> usize {
+        self.cases
+            .iter()
+            .filter(|case| predicate(&case.outcome))
+            .count()
+    }
+
+    /// Returns whether each tested method is compatible, i.e. has no failed cases, grouped
+    /// by suite.
+    fn method_compatibility(&self) -> BTreeMap<(Suite, &'static str), bool> {
+        let mut methods = BTreeMap::new();
+        for case in &self.cases {
+            let is_compatible = methods.entry((case.suite, case.method)).or_insert(true);
+            *is_compatible &= !matches!(case.outcome, Outcome::Failed(_));
+        }
+        methods
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut current_suite = None;
+        for case in &self.cases {
+            if current_suite != Some(case.suite) {
+                writeln!(formatter, "[{}]", case.suite)?;
+                current_suite = Some(case.suite);
+            }
+            let (status, reason) = match &case.outcome {
+                Outcome::Passed => ("ok", None),
+                Outcome::Failed(reason) => ("FAIL", Some(reason)),
+                Outcome::Skipped(reason) => ("skip", Some(reason)),
+            };
+            writeln!(
+                formatter,
+                "  {status:<4} {} ({})",
+                case.method, case.description
+            )?;
+            if let Some(reason) = reason {
+                writeln!(formatter, "       {reason}")?;
+            }
+        }
+
+        let methods = self.method_compatibility();
+        let compatible_methods = methods.values().filter(|&&is_compatible| is_compatible);
+        write!(
+            formatter,
+            "Cases: {} passed, {} failed, {} skipped; methods: {}/{} compatible",
+            self.count(|outcome| *outcome == Outcome::Passed),
+            self.failed_count(),
+            self.count(|outcome| matches!(outcome, Outcome::Skipped(_))),
+            compatible_methods.count(),
+            methods.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn case_report(method: &'static str, outcome: Outcome) -> CaseReport {
+        CaseReport {
+            suite: Suite::Eth,
+            method,
+            description: "test",
+            outcome,
+        }
+    }
+
+    #[test]
+    fn summarizing_report() {
+        let report = Report {
+            cases: vec![
+                case_report("eth_blockNumber", Outcome::Passed),
+                case_report("eth_getStorageAt", Outcome::Passed),
+                case_report(
+                    "eth_getStorageAt",
+                    Outcome::Failed("code -32602".to_owned()),
+                ),
+                case_report("eth_syncing", Outcome::Skipped("unavailable".to_owned())),
+            ],
+        };
+        assert_eq!(report.failed_count(), 1);
+        let summary = report.to_string();
+        let summary = summary.lines().last().unwrap();
+        assert_eq!(
+            summary,
+            "Cases: 2 passed, 1 failed, 1 skipped; methods: 2/3 compatible"
+        );
+
+        let json = serde_json::to_value(&report.cases[2]).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "suite": "eth",
+                "method": "eth_getStorageAt",
+                "description": "test",
+                "outcome": { "status": "failed", "reason": "code -32602" },
+            })
+        );
+    }
+}
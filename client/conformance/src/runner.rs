@@ -0,0 +1,142 @@
+use anyhow::Context as _;
+use ola_types::{
+    api::{BlockNumber, TransactionVariant},
+    U64,
+};
+use ola_web3_decl::{
+    jsonrpsee::{
+        core::{client::ClientT, params::ArrayParams, ClientError},
+        http_client::HttpClient,
+        ws_client::WsClient,
+    },
+    namespaces::{eth::EthNamespaceClient, ola::OlaNamespaceClient},
+};
+use serde_json::Value;
+
+use crate::{
+    cases::{cases, Case, ChainContext, Expected, Suite},
+    report::{CaseReport, Outcome, Report},
+};
+
+/// Number of blocks before the latest one searched for a transaction referenced by cases.
+const TRANSACTION_SEARCH_DEPTH: u64 = 16;
+
+/// Discovers chain data referenced by cases using the typed client.
+pub async fn discover_context(client: &HttpClient) -> anyhow::Result<ChainContext> {
+    let latest_block = client
+        .get_block_number()
+        .await
+        .context("failed getting latest block number")?;
+    let l1_batch_number = client
+        .get_l1_batch_number()
+        .await
+        .context("failed getting latest L1 batch number")?;
+
+    let mut context = ChainContext {
+        l1_batch_number,
+        ..ChainContext::default()
+    };
+    let earliest_block = latest_block
+        .as_u64()
+        .saturating_sub(TRANSACTION_SEARCH_DEPTH);
+    for number in (earliest_block..=latest_block.as_u64()).rev() {
+        let number = U64::from(number);
+        let block = client
+            .get_block_by_number(BlockNumber::Number(number), true)
+            .await
+            .with_context(|| format!("failed getting block #{number}"))?
+            .with_context(|| format!("block #{number} is missing"))?;
+        if number == latest_block {
+            context.block_number = number;
+            context.block_hash = block.hash;
+        }
+        let transaction = block.transactions.into_iter().find_map(|tx| match tx {
+            TransactionVariant::Full(tx) => Some((tx.hash, tx.from.unwrap_or_default())),
+            TransactionVariant::Hash(_) => None,
+        });
+        if transaction.is_some() {
+            // Block-scoped cases should reference the block with the transaction.
+            context.block_number = number;
+            context.block_hash = block.hash;
+            context.transaction = transaction;
+            break;
+        }
+    }
+    Ok(context)
+}
+
+/// Runs cases of `suites` against a node. WS-only suites are run over `ws_client`, which is
+/// required if any of them is requested; other suites are run over `http_client`.
+pub async fn run_suites(
+    http_client: &HttpClient,
+    ws_client: Option<&WsClient>,
+    suites: &[Suite],
+) -> anyhow::Result<Report> {
+    let context = discover_context(http_client).await?;
+    let (ws_suites, http_suites): (Vec<Suite>, Vec<Suite>) =
+        suites.iter().partition(|suite| suite.is_ws_only());
+
+    let mut report = run_cases(http_client, cases(&context, &http_suites)).await;
+    if !ws_suites.is_empty() {
+        let ws_client = ws_client
+            .with_context(|| format!("WS client is required to run {ws_suites:?} suites"))?;
+        let ws_report = run_cases(ws_client, cases(&context, &ws_suites)).await;
+        report.cases.extend(ws_report.cases);
+    }
+    Ok(report)
+}
+
+pub async fn run_cases<C: ClientT + Sync>(client: &C, cases: Vec<Case>) -> Report {
+    let mut report = Report::default();
+    for case in cases {
+        let outcome = run_case(client, &case).await;
+        report.cases.push(CaseReport {
+            suite: case.suite,
+            method: case.method,
+            description: case.description,
+            outcome,
+        });
+    }
+    report
+}
+
+async fn run_case<C: ClientT + Sync>(client: &C, case: &Case) -> Outcome {
+    let mut params = ArrayParams::new();
+    for param in &case.params {
+        if let Err(err) = params.insert(param) {
+            return Outcome::Failed(format!("failed serializing params: {err}"));
+        }
+    }
+    let response = client.request::<Value, _>(case.method, params).await;
+    let outcome = check_response(case.expected, response);
+    match outcome {
+        Outcome::Failed(reason) if case.optional => Outcome::Skipped(reason),
+        outcome => outcome,
+    }
+}
+
+fn check_response(expected: Expected, response: Result<Value, ClientError>) -> Outcome {
+    match (expected, response) {
+        (Expected::Result(check), Ok(value)) => match check.check(&value) {
+            Ok(()) => Outcome::Passed,
+            Err(err) => Outcome::Failed(format!("result is not `{}`: {err}", check.type_name)),
+        },
+        (Expected::Result(_), Err(ClientError::Call(err))) => Outcome::Failed(format!(
+            "unexpected error {}: {}",
+            err.code(),
+            err.message()
+        )),
+        (Expected::Error(code), Err(ClientError::Call(err))) if err.code() == code => {
+            Outcome::Passed
+        }
+        (Expected::Error(code), Err(ClientError::Call(err))) => Outcome::Failed(format!(
+            "expected error {code}, got {}: {}",
+            err.code(),
+            err.message()
+        )),
+        (Expected::Error(code), Ok(value)) => {
+            Outcome::Failed(format!("expected error {code}, got result {value}"))
+        }
+        (_, Err(err)) => Outcome::Failed(format!("transport error: {err}")),
+    }
+}
@@ -0,0 +1,199 @@
+use std::{fs::File, path::Path, time::Duration};
+
+use anyhow::Context as _;
+use futures::future;
+use ola_lang_abi::{Abi, FixedArray4, Value};
+use ola_types::{request::CallRequest, Address, L2ChainId, Nonce, H256};
+use ola_utils::convert::h256_to_u64_array;
+use ola_wallet_sdk::{
+    abi::{build_call_request, create_calldata, create_set_public_key_calldata},
+    errors::ClientError,
+    key_store::OlaKeyPair,
+    private_key_signer::PrivateKeySigner,
+    signer::Signer,
+    wallet::Wallet,
+};
+use ola_web3_decl::jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
+
+use crate::executor::wait_for_inclusion;
+
+pub type LoadtestWallet = Wallet<PrivateKeySigner, HttpClient>;
+
+/// Account sending load test transactions. The nonce is tracked locally, so that an account
+/// can have several transactions in the mempool.
+pub struct LoadtestAccount {
+    pub key_pair: OlaKeyPair,
+    pub wallet: LoadtestWallet,
+    nonce: u32,
+}
+
+impl LoadtestAccount {
+    pub async fn new(
+        key_pair: OlaKeyPair,
+        http_endpoint: &str,
+        chain_id: L2ChainId,
+    ) -> anyhow::Result<Self> {
+        let client = HttpClientBuilder::default()
+            .build(http_endpoint)
+            .context("failed creating HTTP client")?;
+        let pk_signer = PrivateKeySigner::new(key_pair.clone());
+        let signer = Signer::new(pk_signer, key_pair.address, chain_id);
+        let wallet = Wallet::new(client, signer);
+        let nonce = wallet.get_nonce().await?;
+        Ok(Self {
+            key_pair,
+            wallet,
+            nonce,
+        })
+    }
+
+    pub fn address(&self) -> Address {
+        self.key_pair.address
+    }
+
+    /// Re-reads the nonce from the node, e.g. after a transaction was rejected.
+    pub async fn resync_nonce(&mut self) -> Result<(), ClientError> {
+        self.nonce = self.wallet.get_nonce().await?;
+        Ok(())
+    }
+
+    pub async fn execute(&mut self, calldata: Vec<u8>) -> Result<H256, ClientError> {
+        let handle = self
+            .wallet
+            .start_execute_contract(Some(self.address()), None)
+            .calldata(calldata)
+            .nonce(Nonce(self.nonce))
+            .send()
+            .await?;
+        self.nonce += 1;
+        Ok(handle.hash())
+    }
+
+    pub async fn deploy(
+        &mut self,
+        calldata: Vec<u8>,
+        raw_code: Vec<u8>,
+    ) -> Result<H256, ClientError> {
+        let handle = self
+            .wallet
+            .start_deploy_contract(Some(self.address()))
+            .calldata(calldata)
+            .raw_code(raw_code)
+            .nonce(Nonce(self.nonce))
+            .send()
+            .await?;
+        self.nonce += 1;
+        Ok(handle.hash())
+    }
+}
+
+/// Token contract used for transfers, funding and balance reads. Its ABI must have
+/// `transfer(address,u32)` and `balanceOf(address)` functions.
+#[derive(Debug, Clone)]
+pub struct TokenContract {
+    pub address: Address,
+    abi: Abi,
+    transfer_signature: String,
+    balance_signature: String,
+}
+
+impl TokenContract {
+    pub fn from_abi_file(address: Address, abi_path: &Path) -> anyhow::Result<Self> {
+        let abi_file = File::open(abi_path)
+            .with_context(|| format!("failed opening token ABI at {}", abi_path.display()))?;
+        let abi: Abi = serde_json::from_reader(abi_file).context("failed parsing token ABI")?;
+        let signature = |name: &str| {
+            abi.functions
+                .iter()
+                .find(|func| func.name == name)
+                .map(|func| func.signature())
+                .with_context(|| format!("token ABI has no `{name}` function"))
+        };
+        let transfer_signature = signature("transfer")?;
+        let balance_signature = signature("balanceOf")?;
+        Ok(Self {
+            address,
+            abi,
+            transfer_signature,
+            balance_signature,
+        })
+    }
+
+    pub fn transfer_calldata(
+        &self,
+        from: &Address,
+        to: &Address,
+        amount: u64,
+    ) -> anyhow::Result<Vec<u8>> {
+        let params = vec![address_value(to), Value::U32(amount)];
+        create_calldata(
+            &self.abi,
+            &self.transfer_signature,
+            params,
+            from,
+            &self.address,
+            None,
+        )
+    }
+
+    pub fn balance_call(&self, from: &Address, owner: &Address) -> anyhow::Result<CallRequest> {
+        build_call_request(
+            &self.abi,
+            &self.balance_signature,
+            vec![address_value(owner)],
+            from,
+            &self.address,
+        )
+    }
+}
+
+fn address_value(address: &Address) -> Value {
+    Value::Address(FixedArray4(h256_to_u64_array(address)))
+}
+
+/// Creates `count` random accounts, funds them with tokens from `master` and registers their
+/// public keys in the default account contract. Waits until all setup transactions are included.
+pub async fn prepare_accounts(
+    master: &mut LoadtestAccount,
+    token: &TokenContract,
+    count: usize,
+    funding_amount: u64,
+    http_endpoint: &str,
+    inclusion_timeout: Duration,
+) -> anyhow::Result<Vec<LoadtestAccount>> {
+    let chain_id = L2ChainId(master.wallet.get_chain_id());
+    let mut accounts = Vec::with_capacity(count);
+    let mut setup_txs = Vec::with_capacity(count * 2);
+    for _ in 0..count {
+        let mut account =
+            LoadtestAccount::new(OlaKeyPair::from_random(), http_endpoint, chain_id).await?;
+        let funding_calldata =
+            token.transfer_calldata(&master.address(), &account.address(), funding_amount)?;
+        let funding_tx = master
+            .execute(funding_calldata)
+            .await
+            .with_context(|| format!("failed funding account {:?}", account.address()))?;
+        setup_txs.push(funding_tx);
+
+        let pub_key_calldata =
+            create_set_public_key_calldata(&account.address(), account.key_pair.public)?;
+        let pub_key_tx = account.execute(pub_key_calldata).await.with_context(|| {
+            format!(
+                "failed setting public key of account {:?}",
+                account.address()
+            )
+        })?;
+        setup_txs.push(pub_key_tx);
+        accounts.push(account);
+    }
+
+    let provider = &master.wallet.provider;
+    future::try_join_all(
+        setup_txs
+            .into_iter()
+            .map(|tx_hash| wait_for_inclusion(provider, tx_hash, inclusion_timeout)),
+    )
+    .await
+    .context("account setup transaction failed")?;
+    Ok(accounts)
+}
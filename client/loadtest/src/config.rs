@@ -0,0 +1,198 @@
+use std::{fmt, str::FromStr, time::Duration};
+
+use anyhow::Context as _;
+use rand::Rng;
+
+/// Operation executed by a load test worker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum OperationKind {
+    /// Token transfer to another load test account.
+    Transfer,
+    /// Deployment of the configured contract with a random salt.
+    Deploy,
+    /// Read-only token balance call plus a nonce query.
+    Read,
+}
+
+impl OperationKind {
+    pub const ALL: [Self; 3] = [Self::Transfer, Self::Deploy, Self::Read];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Transfer => "transfer",
+            Self::Deploy => "deploy",
+            Self::Read => "read",
+        }
+    }
+}
+
+impl fmt::Display for OperationKind {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.pad(self.as_str())
+    }
+}
+
+/// Relative frequencies of operations in a workload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OperationWeights {
+    pub transfer: u32,
+    pub deploy: u32,
+    pub read: u32,
+}
+
+impl OperationWeights {
+    fn weight(&self, kind: OperationKind) -> u32 {
+        match kind {
+            OperationKind::Transfer => self.transfer,
+            OperationKind::Deploy => self.deploy,
+            OperationKind::Read => self.read,
+        }
+    }
+
+    fn total(&self) -> u32 {
+        self.transfer + self.deploy + self.read
+    }
+
+    /// Picks an operation with a probability proportional to its weight.
+    pub fn pick(&self, rng: &mut impl Rng) -> OperationKind {
+        let mut point = rng.gen_range(0..self.total());
+        for kind in OperationKind::ALL {
+            let weight = self.weight(kind);
+            if point < weight {
+                return kind;
+            }
+            point -= weight;
+        }
+        unreachable!("point is lower than the total weight")
+    }
+}
+
+/// Predefined workload profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Profile {
+    /// Mostly token transfers, stressing the mempool and the sequencer.
+    TransferHeavy,
+    /// Mostly contract deployments, stressing factory deps handling and state growth.
+    DeployHeavy,
+    /// Mostly read-only calls, stressing the API and the rate limiter.
+    ReadHeavy,
+}
+
+impl Profile {
+    pub fn weights(self) -> OperationWeights {
+        match self {
+            Self::TransferHeavy => OperationWeights {
+                transfer: 85,
+                deploy: 0,
+                read: 15,
+            },
+            Self::DeployHeavy => OperationWeights {
+                transfer: 20,
+                deploy: 70,
+                read: 10,
+            },
+            Self::ReadHeavy => OperationWeights {
+                transfer: 15,
+                deploy: 0,
+                read: 85,
+            },
+        }
+    }
+}
+
+/// Stage of the concurrency ramp: `concurrency` workers send operations for `duration`.
+/// Parsed from `<concurrency>@<duration>`, e.g. `50@30s`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RampStage {
+    pub concurrency: usize,
+    pub duration: Duration,
+}
+
+impl FromStr for RampStage {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (concurrency, duration) = s
+            .split_once('@')
+            .with_context(|| format!("ramp stage `{s}` must be `<concurrency>@<duration>`"))?;
+        let concurrency: usize = concurrency
+            .trim()
+            .parse()
+            .with_context(|| format!("invalid concurrency in ramp stage `{s}`"))?;
+        anyhow::ensure!(
+            concurrency > 0,
+            "concurrency in ramp stage `{s}` must be positive"
+        );
+        let duration = parse_duration(duration.trim())
+            .with_context(|| format!("invalid duration in ramp stage `{s}`"))?;
+        Ok(Self {
+            concurrency,
+            duration,
+        })
+    }
+}
+
+/// Parses durations like `500ms`, `30s` or `2m`.
+pub fn parse_duration(s: &str) -> anyhow::Result<Duration> {
+    let unit_start = s
+        .find(|c: char| !c.is_ascii_digit())
+        .context("duration must have a unit (`ms`, `s` or `m`)")?;
+    let (value, unit) = s.split_at(unit_start);
+    let value: u64 = value.parse()?;
+    let duration = match unit {
+        "ms" => Duration::from_millis(value),
+        "s" => Duration::from_secs(value),
+        "m" => Duration::from_secs(value * 60),
+        _ => anyhow::bail!("unknown duration unit `{unit}`"),
+    };
+    anyhow::ensure!(!duration.is_zero(), "duration must be positive");
+    Ok(duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn parsing_ramp_stages() {
+        assert_eq!(
+            "50@30s".parse::<RampStage>().unwrap(),
+            RampStage {
+                concurrency: 50,
+                duration: Duration::from_secs(30),
+            }
+        );
+        assert_eq!(
+            "1@2m".parse::<RampStage>().unwrap().duration,
+            Duration::from_secs(120)
+        );
+        assert_eq!(
+            "1@500ms".parse::<RampStage>().unwrap().duration,
+            Duration::from_millis(500)
+        );
+
+        assert!("50".parse::<RampStage>().is_err());
+        assert!("0@30s".parse::<RampStage>().is_err());
+        assert!("10@30".parse::<RampStage>().is_err());
+        assert!("10@0s".parse::<RampStage>().is_err());
+        assert!("10@30h".parse::<RampStage>().is_err());
+    }
+
+    #[test]
+    fn picking_operations_respects_weights() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let weights = Profile::ReadHeavy.weights();
+        let mut counts = [0_u32; 3];
+        for _ in 0..10_000 {
+            match weights.pick(&mut rng) {
+                OperationKind::Transfer => counts[0] += 1,
+                OperationKind::Deploy => counts[1] += 1,
+                OperationKind::Read => counts[2] += 1,
+            }
+        }
+        assert_eq!(counts[1], 0);
+        assert!(counts[2] > counts[0] * 4, "{counts:?}");
+    }
+}
@@ -0,0 +1,271 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context as _;
+use futures::future;
+use ola_lang_abi::{Abi, FixedArray4, Value};
+use ola_types::{Address, H256};
+use ola_utils::convert::h256_to_u64_array;
+use ola_wallet_sdk::{
+    abi::create_calldata,
+    errors::{ClientError, RpcError},
+    program_meta::ProgramMeta,
+    utils::is_h256_a_valid_ola_hash,
+};
+use ola_web3_decl::{jsonrpsee::http_client::HttpClient, namespaces::ola::OlaNamespaceClient};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::{
+    accounts::{LoadtestAccount, TokenContract},
+    config::{OperationKind, OperationWeights, RampStage},
+    report::{Report, StageReport, StageStats},
+};
+
+const DEPLOYER_ADDRESS: Address = H256([
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x05,
+]);
+const INCLUSION_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, thiserror::Error)]
+pub enum InclusionError {
+    #[error("failed polling receipt: {0}")]
+    Rpc(#[from] ClientError),
+    #[error("transaction {0:?} reverted")]
+    Reverted(H256),
+    #[error("transaction {0:?} is not included in time")]
+    Timeout(H256),
+}
+
+/// Waits until the transaction with `tx_hash` is included in a miniblock and checks
+/// that it succeeded.
+pub async fn wait_for_inclusion(
+    provider: &HttpClient,
+    tx_hash: H256,
+    timeout: Duration,
+) -> Result<(), InclusionError> {
+    let started_at = Instant::now();
+    loop {
+        let receipt = provider
+            .get_transaction_receipt(tx_hash)
+            .await
+            .map_err(ClientError::from)?;
+        match receipt.and_then(|receipt| receipt.status) {
+            Some(status) if status.as_u64() == 1 => return Ok(()),
+            Some(_) => return Err(InclusionError::Reverted(tx_hash)),
+            None if started_at.elapsed() >= timeout => {
+                return Err(InclusionError::Timeout(tx_hash))
+            }
+            None => tokio::time::sleep(INCLUSION_POLL_INTERVAL).await,
+        }
+    }
+}
+
+/// Groups errors into a bounded set of categories for the report.
+fn error_category(err: &anyhow::Error) -> String {
+    if let Some(err) = err.downcast_ref::<InclusionError>() {
+        return match err {
+            InclusionError::Rpc(err) => client_error_category(err),
+            InclusionError::Reverted(_) => "reverted".to_owned(),
+            InclusionError::Timeout(_) => "inclusion timeout".to_owned(),
+        };
+    }
+    if let Some(err) = err.downcast_ref::<ClientError>() {
+        return client_error_category(err);
+    }
+    format!("other: {err}")
+}
+
+fn client_error_category(err: &ClientError) -> String {
+    match err {
+        ClientError::RpcError(RpcError::Call(err)) => {
+            format!("rpc error {}: {}", err.code(), err.message())
+        }
+        ClientError::RpcError(RpcError::RequestTimeout) => "request timeout".to_owned(),
+        ClientError::RpcError(_) => "transport error".to_owned(),
+        err => format!("client error: {err}"),
+    }
+}
+
+/// Shared state of load test workers.
+#[derive(Debug)]
+pub struct LoadContext {
+    pub token: TokenContract,
+    /// Contract deployed by deploy operations; required if deploys have a non-zero weight.
+    pub program: Option<ProgramMeta>,
+    pub weights: OperationWeights,
+    pub transfer_amount: u64,
+    /// If set, write operations are measured until inclusion rather than until submission.
+    pub inclusion_timeout: Option<Duration>,
+    /// Addresses of all load test accounts, used as transfer recipients.
+    pub recipients: Vec<Address>,
+    deployer_abi: Abi,
+}
+
+impl LoadContext {
+    pub fn new(
+        token: TokenContract,
+        program: Option<ProgramMeta>,
+        weights: OperationWeights,
+        transfer_amount: u64,
+        inclusion_timeout: Option<Duration>,
+        recipients: Vec<Address>,
+    ) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            weights.deploy == 0 || program.is_some(),
+            "the profile includes deployments, but no contract to deploy is provided"
+        );
+        let deployer_abi = serde_json::from_str(include_str!("abi/ContractDeployer.json"))
+            .context("failed parsing deployer ABI")?;
+        Ok(Self {
+            token,
+            program,
+            weights,
+            transfer_amount,
+            inclusion_timeout,
+            recipients,
+            deployer_abi,
+        })
+    }
+
+    async fn execute(
+        &self,
+        account: &mut LoadtestAccount,
+        kind: OperationKind,
+        rng: &mut StdRng,
+    ) -> anyhow::Result<()> {
+        let recipient = *self
+            .recipients
+            .choose(rng)
+            .context("no load test accounts")?;
+        let tx_hash = match kind {
+            OperationKind::Transfer => {
+                let calldata = self.token.transfer_calldata(
+                    &account.address(),
+                    &recipient,
+                    self.transfer_amount,
+                )?;
+                account.execute(calldata).await?
+            }
+            OperationKind::Deploy => {
+                let program = self.program.as_ref().context("no contract to deploy")?;
+                let calldata = self.deploy_calldata(&account.address(), program, rng)?;
+                account.deploy(calldata, program.bytes.clone()).await?
+            }
+            OperationKind::Read => {
+                let call = self.token.balance_call(&account.address(), &recipient)?;
+                account
+                    .wallet
+                    .provider
+                    .call_transaction(call, None, None)
+                    .await
+                    .map_err(ClientError::from)?;
+                account.wallet.get_addr_nonce(recipient).await?;
+                return Ok(());
+            }
+        };
+
+        if let Some(timeout) = self.inclusion_timeout {
+            wait_for_inclusion(&account.wallet.provider, tx_hash, timeout).await?;
+        }
+        Ok(())
+    }
+
+    fn deploy_calldata(
+        &self,
+        from: &Address,
+        program: &ProgramMeta,
+        rng: &mut StdRng,
+    ) -> anyhow::Result<Vec<u8>> {
+        let salt = loop {
+            let salt = H256(rng.gen());
+            if is_h256_a_valid_ola_hash(salt) {
+                break salt;
+            }
+        };
+        let params = vec![
+            Value::Hash(FixedArray4(h256_to_u64_array(&salt))),
+            Value::Hash(FixedArray4(h256_to_u64_array(&program.program_hash))),
+            Value::Hash(FixedArray4(h256_to_u64_array(&program.bytecode_hash))),
+        ];
+        let func = self
+            .deployer_abi
+            .functions
+            .iter()
+            .find(|func| func.name == "create2")
+            .context("create2 function not found in deployer ABI")?;
+        create_calldata(
+            &self.deployer_abi,
+            func.signature().as_str(),
+            params,
+            from,
+            &DEPLOYER_ADDRESS,
+            Some(program.instructions.clone()),
+        )
+    }
+}
+
+async fn run_worker(
+    context: Arc<LoadContext>,
+    mut account: LoadtestAccount,
+    deadline: Instant,
+) -> (LoadtestAccount, StageStats) {
+    let mut rng = StdRng::from_entropy();
+    let mut stats = StageStats::default();
+    while Instant::now() < deadline {
+        let kind = context.weights.pick(&mut rng);
+        let started_at = Instant::now();
+        let result = context.execute(&mut account, kind, &mut rng).await;
+        let result = match result {
+            Ok(()) => Ok(started_at.elapsed()),
+            Err(err) => {
+                if kind != OperationKind::Read {
+                    // The transaction may or may not have been accepted.
+                    account.resync_nonce().await.ok();
+                }
+                Err(error_category(&err))
+            }
+        };
+        stats.record(kind, result);
+    }
+    (account, stats)
+}
+
+/// Runs the ramp `stages` one after another. Each worker of a stage uses its own account,
+/// so `accounts` must cover the highest concurrency.
+pub async fn run_stages(
+    context: Arc<LoadContext>,
+    mut accounts: Vec<LoadtestAccount>,
+    stages: &[RampStage],
+) -> Report {
+    let mut report = Report::default();
+    for (i, stage) in stages.iter().enumerate() {
+        println!(
+            "Starting stage {} with {} workers for {:?}",
+            i + 1,
+            stage.concurrency,
+            stage.duration
+        );
+        let started_at = Instant::now();
+        let deadline = started_at + stage.duration;
+        let workers = accounts
+            .drain(..stage.concurrency)
+            .map(|account| tokio::spawn(run_worker(context.clone(), account, deadline)));
+        let results = future::join_all(workers).await;
+
+        let mut stats = StageStats::default();
+        for result in results {
+            let (account, worker_stats) = result.expect("load test worker panicked");
+            accounts.push(account);
+            stats.merge(worker_stats);
+        }
+        report.stages.push(StageReport {
+            stage: *stage,
+            elapsed: started_at.elapsed(),
+            stats,
+        });
+    }
+    report
+}
@@ -0,0 +1,208 @@
+//! Load test harness driving the HTTP API of an Ola node.
+//!
+//! The master account funds freshly generated accounts with tokens and registers their public
+//! keys; then workers send operations picked according to the workload profile, with
+//! the number of concurrent workers following the configured ramp. Latencies and errors
+//! are reported per stage and operation.
+
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use anyhow::Context as _;
+use clap::Parser;
+use ola_types::L2ChainId;
+use ola_wallet_sdk::{key_store::OlaKeyPair, program_meta::ProgramMeta, utils::h256_from_hex_be};
+
+use crate::{
+    accounts::{prepare_accounts, LoadtestAccount, TokenContract},
+    config::{parse_duration, Profile, RampStage},
+    executor::{run_stages, LoadContext},
+};
+
+mod accounts;
+mod config;
+mod executor;
+mod report;
+
+#[derive(Debug, Parser)]
+#[clap(about = "Load test harness for the Ola node API")]
+struct Args {
+    #[clap(
+        long,
+        default_value = "http://localhost:13000",
+        help = "HTTP API endpoint"
+    )]
+    http_endpoint: String,
+    #[clap(long, default_value_t = 1027, help = "L2 chain ID")]
+    chain_id: u16,
+    #[clap(
+        long,
+        env = "OLA_LOADTEST_MASTER_KEY",
+        hide_env_values = true,
+        help = "Private key of the account funding load test accounts"
+    )]
+    master_private_key: String,
+    #[clap(
+        long,
+        help = "Address of the token contract used for transfers and reads"
+    )]
+    token: String,
+    #[clap(long, help = "Path to the ABI of the token contract")]
+    token_abi: PathBuf,
+    #[clap(
+        long,
+        help = "Path to the contract binary deployed by deploy operations"
+    )]
+    contract: Option<PathBuf>,
+    #[clap(
+        long,
+        value_enum,
+        default_value = "transfer-heavy",
+        help = "Workload profile"
+    )]
+    profile: Profile,
+    #[clap(
+        long,
+        value_delimiter = ',',
+        default_value = "10@30s",
+        help = "Concurrency ramp as comma-separated `<workers>@<duration>` stages, e.g. `10@30s,50@1m`"
+    )]
+    ramp: Vec<RampStage>,
+    #[clap(
+        long,
+        help = "Number of load test accounts; defaults to the highest ramp concurrency"
+    )]
+    accounts: Option<usize>,
+    #[clap(
+        long,
+        default_value_t = 1_000_000,
+        help = "Tokens sent to each account"
+    )]
+    funding_amount: u64,
+    #[clap(long, default_value_t = 1, help = "Tokens sent by each transfer")]
+    transfer_amount: u64,
+    #[clap(
+        long,
+        help = "Measure write operations until inclusion rather than until submission"
+    )]
+    await_inclusion: bool,
+    #[clap(
+        long,
+        value_parser = parse_duration,
+        default_value = "2m",
+        help = "Timeout for transactions to be included"
+    )]
+    inclusion_timeout: Duration,
+    #[clap(
+        long,
+        default_value_t = 0.01,
+        help = "Fail if the share of failed operations exceeds this value"
+    )]
+    max_error_rate: f64,
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(err) = run(Args::parse()).await {
+        eprintln!("Error: {err:#}");
+        std::process::exit(1);
+    }
+}
+
+async fn run(args: Args) -> anyhow::Result<()> {
+    let max_concurrency = args
+        .ramp
+        .iter()
+        .map(|stage| stage.concurrency)
+        .max()
+        .context("ramp must have at least one stage")?;
+    let account_count = args.accounts.unwrap_or(max_concurrency);
+    anyhow::ensure!(
+        account_count >= max_concurrency,
+        "{account_count} accounts can't serve {max_concurrency} concurrent workers"
+    );
+
+    let chain_id = L2ChainId(args.chain_id);
+    let master_key = h256_from_hex_be(&args.master_private_key)?;
+    let master_key_pair =
+        OlaKeyPair::new(master_key).context("invalid master account private key")?;
+    let mut master = LoadtestAccount::new(master_key_pair, &args.http_endpoint, chain_id).await?;
+    let token = TokenContract::from_abi_file(h256_from_hex_be(&args.token)?, &args.token_abi)?;
+    let program = args
+        .contract
+        .map(ProgramMeta::from_file)
+        .transpose()
+        .context("failed loading contract to deploy")?;
+
+    println!("Preparing {account_count} accounts");
+    let accounts = prepare_accounts(
+        &mut master,
+        &token,
+        account_count,
+        args.funding_amount,
+        &args.http_endpoint,
+        args.inclusion_timeout,
+    )
+    .await?;
+
+    let recipients = accounts.iter().map(LoadtestAccount::address).collect();
+    let context = LoadContext::new(
+        token,
+        program,
+        args.profile.weights(),
+        args.transfer_amount,
+        args.await_inclusion.then_some(args.inclusion_timeout),
+        recipients,
+    )?;
+    let report = run_stages(Arc::new(context), accounts, &args.ramp).await;
+    println!("{report}");
+
+    let error_rate = report.error_rate();
+    anyhow::ensure!(
+        error_rate <= args.max_error_rate,
+        "error rate {:.2}% exceeds the allowed {:.2}%",
+        error_rate * 100.0,
+        args.max_error_rate * 100.0
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_args() {
+        let args = Args::try_parse_from([
+            "loadtest",
+            "--master-private-key",
+            "0x01",
+            "--token",
+            "0x02",
+            "--token-abi",
+            "token.json",
+            "--profile",
+            "read-heavy",
+            "--ramp",
+            "10@30s,50@1m",
+        ])
+        .unwrap();
+        assert_eq!(args.profile, Profile::ReadHeavy);
+        assert_eq!(
+            args.ramp,
+            [
+                RampStage {
+                    concurrency: 10,
+                    duration: Duration::from_secs(30),
+                },
+                RampStage {
+                    concurrency: 50,
+                    duration: Duration::from_secs(60),
+                },
+            ]
+        );
+        assert_eq!(args.inclusion_timeout, Duration::from_secs(120));
+        assert!(!args.await_inclusion);
+
+        assert!(Args::try_parse_from(["loadtest", "--token", "0x02"]).is_err());
+    }
+}
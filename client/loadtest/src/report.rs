@@ -0,0 +1,196 @@
+use std::{collections::BTreeMap, fmt, time::Duration};
+
+use crate::config::{OperationKind, RampStage};
+
+/// Latencies and errors of a single operation kind.
+#[derive(Debug, Clone, Default)]
+pub struct OperationStats {
+    latencies: Vec<Duration>,
+    errors: BTreeMap<String, u64>,
+}
+
+impl OperationStats {
+    pub fn record_success(&mut self, latency: Duration) {
+        self.latencies.push(latency);
+    }
+
+    pub fn record_error(&mut self, category: String) {
+        *self.errors.entry(category).or_default() += 1;
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        self.latencies.extend(other.latencies);
+        for (category, count) in other.errors {
+            *self.errors.entry(category).or_default() += count;
+        }
+    }
+
+    pub fn successes(&self) -> u64 {
+        self.latencies.len() as u64
+    }
+
+    pub fn error_count(&self) -> u64 {
+        self.errors.values().sum()
+    }
+
+    /// Returns the latency below which `quantile` of successful operations completed.
+    pub fn latency_quantile(&self, quantile: f64) -> Option<Duration> {
+        if self.latencies.is_empty() {
+            return None;
+        }
+        let mut latencies = self.latencies.clone();
+        latencies.sort_unstable();
+        let rank = (quantile * latencies.len() as f64).ceil() as usize;
+        Some(latencies[rank.clamp(1, latencies.len()) - 1])
+    }
+}
+
+/// Statistics collected by workers during a ramp stage.
+#[derive(Debug, Clone, Default)]
+pub struct StageStats {
+    pub operations: BTreeMap<OperationKind, OperationStats>,
+}
+
+impl StageStats {
+    pub fn record(&mut self, kind: OperationKind, result: Result<Duration, String>) {
+        let stats = self.operations.entry(kind).or_default();
+        match result {
+            Ok(latency) => stats.record_success(latency),
+            Err(category) => stats.record_error(category),
+        }
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        for (kind, stats) in other.operations {
+            self.operations.entry(kind).or_default().merge(stats);
+        }
+    }
+
+    fn totals(&self) -> (u64, u64) {
+        self.operations.values().fold((0, 0), |(ok, err), stats| {
+            (ok + stats.successes(), err + stats.error_count())
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct StageReport {
+    pub stage: RampStage,
+    pub elapsed: Duration,
+    pub stats: StageStats,
+}
+
+/// Results of a load test run, printed once all stages are completed.
+#[derive(Debug, Default)]
+pub struct Report {
+    pub stages: Vec<StageReport>,
+}
+
+impl Report {
+    /// Share of failed operations over all stages.
+    pub fn error_rate(&self) -> f64 {
+        let (successes, errors) = self
+            .stages
+            .iter()
+            .map(|stage| stage.stats.totals())
+            .fold((0, 0), |(ok, err), (stage_ok, stage_err)| {
+                (ok + stage_ok, err + stage_err)
+            });
+        if successes + errors == 0 {
+            return 0.0;
+        }
+        errors as f64 / (successes + errors) as f64
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, stage) in self.stages.iter().enumerate() {
+            let (successes, errors) = stage.stats.totals();
+            let throughput = successes as f64 / stage.elapsed.as_secs_f64();
+            writeln!(
+                formatter,
+                "Stage {} ({} workers, {:?}): {successes} ok, {errors} failed, {throughput:.1} ops/s",
+                i + 1,
+                stage.stage.concurrency,
+                stage.elapsed
+            )?;
+            for (kind, stats) in &stage.stats.operations {
+                let quantile = |q| {
+                    stats
+                        .latency_quantile(q)
+                        .map_or_else(|| "-".to_owned(), |latency| format!("{latency:?}"))
+                };
+                writeln!(
+                    formatter,
+                    "  {kind:<8} ok={:<7} failed={:<7} p50={:<12} p95={:<12} p99={:<12} max={}",
+                    stats.successes(),
+                    stats.error_count(),
+                    quantile(0.5),
+                    quantile(0.95),
+                    quantile(0.99),
+                    quantile(1.0)
+                )?;
+                for (category, count) in &stats.errors {
+                    writeln!(formatter, "    {count} x {category}")?;
+                }
+            }
+        }
+        write!(
+            formatter,
+            "Overall error rate: {:.2}%",
+            self.error_rate() * 100.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latency_quantiles() {
+        let mut stats = OperationStats::default();
+        assert_eq!(stats.latency_quantile(0.5), None);
+        for millis in (1..=100).rev() {
+            stats.record_success(Duration::from_millis(millis));
+        }
+
+        assert_eq!(stats.latency_quantile(0.5), Some(Duration::from_millis(50)));
+        assert_eq!(
+            stats.latency_quantile(0.99),
+            Some(Duration::from_millis(99))
+        );
+        assert_eq!(
+            stats.latency_quantile(1.0),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(stats.latency_quantile(0.0), Some(Duration::from_millis(1)));
+    }
+
+    #[test]
+    fn merging_stage_stats() {
+        let mut stats = StageStats::default();
+        stats.record(OperationKind::Read, Ok(Duration::from_millis(5)));
+        stats.record(OperationKind::Transfer, Err("rpc -32000".to_owned()));
+
+        let mut other = StageStats::default();
+        other.record(OperationKind::Transfer, Err("rpc -32000".to_owned()));
+        other.record(OperationKind::Transfer, Ok(Duration::from_millis(20)));
+        stats.merge(other);
+
+        let transfers = &stats.operations[&OperationKind::Transfer];
+        assert_eq!(transfers.successes(), 1);
+        assert_eq!(transfers.error_count(), 2);
+        assert_eq!(stats.totals(), (2, 2));
+
+        let report = Report {
+            stages: vec![StageReport {
+                stage: "1@1s".parse().unwrap(),
+                elapsed: Duration::from_secs(1),
+                stats,
+            }],
+        };
+        assert_eq!(report.error_rate(), 0.5);
+    }
+}
@@ -1,7 +1,10 @@
 use ethereum_types::{H256, H512};
 use ola_lang_abi::{Abi, FixedArray4, Value};
 use ola_types::{l2::L2Tx, request::CallRequest, request::PaymasterParams, Address, Bytes, Nonce};
-use ola_utils::{h256_to_string, h256_to_u64_array, u64s_to_bytes};
+use ola_utils::{
+    convert::{bytes_to_u64s, u64_array_to_h256},
+    h256_to_string, h256_to_u64_array, u64s_to_bytes,
+};
 
 use crate::{errors::ClientError, utils::h512_to_u64_array};
 
@@ -83,6 +86,47 @@ fn build_entry_point_calldata(
     Ok(input)
 }
 
+/// The business call wrapped into the `system_entrance` calldata of a transaction.
+#[derive(Debug, Clone)]
+pub struct EntryPointCall {
+    pub from: Address,
+    pub to: Address,
+    pub data: Vec<u64>,
+}
+
+/// Decodes the calldata built by [`create_calldata`] back into the wrapped call.
+pub fn decode_entry_point_calldata(calldata: &[u8]) -> anyhow::Result<EntryPointCall> {
+    if calldata.len() % 8 != 0 || calldata.len() < 16 {
+        anyhow::bail!("calldata is not a valid entry point calldata");
+    }
+    let entry_point_abi_str = include_str!("abi/EntryPointAbi.json");
+    let abi: Abi =
+        serde_json::from_str(entry_point_abi_str).map_err(|_| ClientError::AbiParseError)?;
+    let input = bytes_to_u64s(calldata.to_vec());
+    let (_, decoded) = abi.decode_input_from_slice(&input)?;
+
+    let fields = match decoded.first().map(|param| &param.value) {
+        Some(Value::Tuple(fields)) => fields,
+        _ => anyhow::bail!("entry point calldata doesn't start with a transaction tuple"),
+    };
+    let field = |name: &str| {
+        fields
+            .iter()
+            .find(|(field_name, _)| field_name == name)
+            .map(|(_, value)| value)
+            .ok_or_else(|| anyhow::anyhow!("entry point calldata has no `{name}` field"))
+    };
+    let (from, to, data) = match (field("from")?, field("to")?, field("data")?) {
+        (Value::Address(from), Value::Address(to), Value::Fields(data)) => (
+            u64_array_to_h256(&from.0),
+            u64_array_to_h256(&to.0),
+            data.clone(),
+        ),
+        _ => anyhow::bail!("unexpected entry point calldata layout"),
+    };
+    Ok(EntryPointCall { from, to, data })
+}
+
 pub fn build_call_request(
     abi: &Abi,
     function_sig: &str,
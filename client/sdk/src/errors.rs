@@ -30,6 +30,11 @@ pub enum KeystoreError {
     InvalidPath,
     #[error("invalid decrypted secret scalar")]
     InvalidScalar,
+    #[error("invalid mnemonic: {0}")]
+    InvalidMnemonic(String),
+    #[error("key derivation failed: {0}")]
+    Derivation(String),
+    #[cfg(feature = "native")]
     #[error(transparent)]
     Inner(eth_keystore::KeystoreError),
 }
@@ -48,4 +53,8 @@ pub enum ClientError {
     NumberConvertError(#[from] NumberConvertError),
     #[error("Keystore error: {0}")]
     KeystoreError(#[from] KeystoreError),
+    #[error("Event {0} not found in ABI")]
+    EventNotFound(String),
+    #[error("Event decoding error: {0}")]
+    EventDecodeError(String),
 }
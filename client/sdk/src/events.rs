@@ -0,0 +1,246 @@
+//! Subscriptions to contract events over WebSocket.
+//!
+//! [`subscribe_events()`] subscribes to the logs of a contract, decodes logs of the requested event
+//! with the contract ABI and yields them as an async [`Stream`]. If the connection drops, the
+//! subscription is re-established with exponential backoff and asks the node to replay logs
+//! starting from the last received block, so that logs emitted while disconnected aren't lost.
+//! Nodes with replay disabled, or a gap wider than their replay window, reject the request; in this
+//! case the subscription continues with new logs only.
+
+use std::{marker::PhantomData, pin::Pin, time::Duration};
+
+use futures::{stream, Stream};
+use ola_lang_abi::{Abi, DecodedParams, FixedArray4, Value};
+use ola_types::{api::Log, Address, U256, U64};
+use ola_utils::convert::{bytes_to_u64s, h256_to_u64_array, u64_array_to_h256};
+use ola_web3_decl::{
+    jsonrpsee::core::client::Subscription,
+    namespaces::eth::EthPubSubClient,
+    types::{PubSubFilter, PubSubResult},
+};
+
+use crate::{
+    errors::ClientError,
+    parser::FromValue,
+    transport::{self, PubSubClient},
+};
+
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Converts decoded event parameters into a typed value.
+pub trait FromEventParams: Sized {
+    fn from_event_params(params: DecodedParams) -> Result<Self, ClientError>;
+}
+
+impl FromEventParams for DecodedParams {
+    fn from_event_params(params: DecodedParams) -> Result<Self, ClientError> {
+        Ok(params)
+    }
+}
+
+impl FromEventParams for Vec<Value> {
+    fn from_event_params(params: DecodedParams) -> Result<Self, ClientError> {
+        Ok(params.iter().map(|param| param.value.clone()).collect())
+    }
+}
+
+/// Parameter values formatted like `olatte` prints them.
+impl FromEventParams for Vec<String> {
+    fn from_event_params(params: DecodedParams) -> Result<Self, ClientError> {
+        Ok(params
+            .iter()
+            .map(|param| FromValue::parse_input(param.value.clone()))
+            .collect())
+    }
+}
+
+/// Event decoded from a log, together with the log itself.
+#[derive(Debug, Clone)]
+pub struct DecodedEvent<T> {
+    pub log: Log,
+    pub value: T,
+}
+
+pub type EventStream<T> = Pin<Box<dyn Stream<Item = Result<DecodedEvent<T>, ClientError>> + Send>>;
+
+/// Subscribes to `event_signature` events (e.g. `Transfer(address,address,u32)`) emitted by
+/// `contract`. Logs of other events of the contract are filtered out by the node.
+pub async fn subscribe_events<T>(
+    ws_url: &str,
+    contract: Address,
+    abi: Abi,
+    event_signature: &str,
+) -> Result<EventStream<T>, ClientError>
+where
+    T: FromEventParams + Send + 'static,
+{
+    let event_topic = abi
+        .events
+        .iter()
+        .find(|event| event.signature() == event_signature)
+        .ok_or_else(|| ClientError::EventNotFound(event_signature.to_owned()))?
+        .topic();
+    let mut subscription = EventSubscription {
+        ws_url: ws_url.to_owned(),
+        filter: event_filter(contract, &event_topic),
+        abi,
+        event_topic,
+        connection: None,
+        last_position: None,
+        _item: PhantomData::<T>,
+    };
+    // Fail fast if the node is unreachable, rather than retrying forever.
+    subscription.connection = Some(subscription.connect().await?);
+
+    let events = stream::unfold(subscription, |mut subscription| async move {
+        let event = subscription.next_event().await;
+        Some((event, subscription))
+    });
+    Ok(Box::pin(events))
+}
+
+/// Returns a filter of logs of `contract` with `event_topic` as the first topic, so that the node
+/// doesn't send logs of other events.
+fn event_filter(contract: Address, event_topic: &FixedArray4) -> PubSubFilter {
+    let event_topic = u64_array_to_h256(&event_topic.0);
+    PubSubFilter {
+        address: Some(contract.into()),
+        topics: Some(vec![Some(event_topic.into())]),
+        from_block: None,
+    }
+}
+
+struct EventSubscription<T> {
+    ws_url: String,
+    filter: PubSubFilter,
+    abi: Abi,
+    /// First topic of logs of the subscribed event.
+    event_topic: FixedArray4,
+    /// The client is kept alongside the subscription since dropping it closes the connection.
+    connection: Option<(PubSubClient, Subscription<PubSubResult>)>,
+    /// Block number and index in the block of the last received log.
+    last_position: Option<(U64, U256)>,
+    _item: PhantomData<T>,
+}
+
+impl<T: FromEventParams> EventSubscription<T> {
+    async fn connect(&self) -> Result<(PubSubClient, Subscription<PubSubResult>), ClientError> {
+        let client = transport::build_pubsub_client(&self.ws_url).await?;
+        let mut filter = self.filter.clone();
+        // Replay the whole last block, since it may have been received partially;
+        // already received logs are skipped in `next_event()`.
+        filter.from_block = self.last_position.map(|(block_number, _)| block_number);
+        let replayed =
+            EthPubSubClient::subscribe(&client, "logs".to_owned(), Some(filter.clone())).await;
+        let subscription = match replayed {
+            Ok(subscription) => subscription,
+            Err(_) if filter.from_block.is_some() => {
+                filter.from_block = None;
+                EthPubSubClient::subscribe(&client, "logs".to_owned(), Some(filter)).await?
+            }
+            Err(err) => return Err(err.into()),
+        };
+        Ok((client, subscription))
+    }
+
+    async fn reconnect(&mut self) {
+        let mut delay = INITIAL_RECONNECT_DELAY;
+        loop {
+            transport::sleep(delay).await;
+            if let Ok(connection) = self.connect().await {
+                self.connection = Some(connection);
+                return;
+            }
+            delay = next_reconnect_delay(delay);
+        }
+    }
+
+    async fn next_event(&mut self) -> Result<DecodedEvent<T>, ClientError> {
+        loop {
+            let Some((_, subscription)) = &mut self.connection else {
+                self.reconnect().await;
+                continue;
+            };
+            match subscription.next().await {
+                Some(Ok(PubSubResult::Log(log))) => {
+                    let position = log.block_number.zip(log.log_index);
+                    if position.is_some() && position <= self.last_position {
+                        continue; // Replayed log that was already received
+                    }
+                    self.last_position = position.or(self.last_position);
+                    if let Some(event) = self.decode(log) {
+                        return event;
+                    }
+                }
+                Some(Ok(_)) => {}
+                Some(Err(_)) | None => self.connection = None,
+            }
+        }
+    }
+
+    /// Returns `None` if the log doesn't belong to the subscribed event. Logs of the subscribed
+    /// event which cannot be decoded are reported as errors rather than skipped.
+    fn decode(&self, log: Log) -> Option<Result<DecodedEvent<T>, ClientError>> {
+        let topics: Vec<_> = log
+            .topics
+            .iter()
+            .map(|topic| FixedArray4(h256_to_u64_array(topic)))
+            .collect();
+        if topics.first() != Some(&self.event_topic) {
+            return None;
+        }
+        if log.data.0.len() % 8 != 0 {
+            return Some(Err(ClientError::EventDecodeError(format!(
+                "data of log {:?} is not a sequence of field elements",
+                log.transaction_hash
+            ))));
+        }
+        let data = bytes_to_u64s(log.data.0.clone());
+        let params = match self.abi.decode_log_from_slice(&topics, &data) {
+            Ok((_, params)) => params,
+            Err(err) => {
+                return Some(Err(ClientError::EventDecodeError(format!(
+                    "cannot decode log {:?}: {err}",
+                    log.transaction_hash
+                ))))
+            }
+        };
+        Some(T::from_event_params(params).map(|value| DecodedEvent { log, value }))
+    }
+}
+
+fn next_reconnect_delay(delay: Duration) -> Duration {
+    (delay * 2).min(MAX_RECONNECT_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use ola_types::H256;
+
+    use super::*;
+
+    #[test]
+    fn event_filter_selects_event_topic() {
+        let contract = Address::repeat_byte(1);
+        let event_topic = H256::repeat_byte(0xee);
+        let filter = event_filter(contract, &FixedArray4(h256_to_u64_array(&event_topic)));
+        assert_eq!(filter.address.unwrap().0, [contract]);
+        let topics = filter.topics.unwrap();
+        assert_eq!(topics.len(), 1);
+        assert_eq!(topics[0].as_ref().unwrap().0, [event_topic]);
+    }
+
+    #[test]
+    fn reconnect_delay_is_capped() {
+        let mut delay = INITIAL_RECONNECT_DELAY;
+        for _ in 0..10 {
+            delay = next_reconnect_delay(delay);
+        }
+        assert_eq!(delay, MAX_RECONNECT_DELAY);
+        assert_eq!(
+            next_reconnect_delay(INITIAL_RECONNECT_DELAY),
+            Duration::from_secs(2)
+        );
+    }
+}
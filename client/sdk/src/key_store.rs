@@ -84,6 +84,7 @@ impl OlaKeyPair {
         }
     }
 
+    #[cfg(feature = "native")]
     pub fn from_keystore<P>(path: P, password: &str) -> Result<Self, ClientError>
     where
         P: AsRef<std::path::Path>,
@@ -100,6 +101,7 @@ impl OlaKeyPair {
         Self::new(H256(key))
     }
 
+    #[cfg(feature = "native")]
     pub fn save_as_keystore<P>(&self, path: P, password: &str) -> Result<(), KeystoreError>
     where
         P: AsRef<std::path::Path>,
@@ -4,13 +4,16 @@ use ola_types::{tx::primitives::PackedEthSignature, Address};
 
 pub mod abi;
 pub mod errors;
+pub mod events;
 pub mod key_store;
+pub mod mnemonic;
 pub mod operation;
 pub mod parser;
 pub mod private_key_signer;
 pub mod program_meta;
 pub mod provider;
 pub mod signer;
+pub mod transport;
 pub mod utils;
 pub mod wallet;
 
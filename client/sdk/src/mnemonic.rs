@@ -0,0 +1,95 @@
+//! BIP39 mnemonics and derivation of Ola keys from them.
+//!
+//! Keys are derived along the BIP44 path `m/44'/8888'/0'/0/{account_index}`. Since not every
+//! secp256k1 key is a valid Ola key, the derived key is mapped to an Ola key the same way
+//! as Ethereum private keys are (see [`OlaKeyPair::from_etherum_private_key()`]).
+
+use bip32::{DerivationPath, XPrv};
+use bip39::{Language, Mnemonic};
+use ethereum_types::H256;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    errors::{ClientError, KeystoreError},
+    key_store::OlaKeyPair,
+};
+
+/// Coin type of Ola keys in BIP44 derivation paths.
+pub const OLA_COIN_TYPE: u32 = 8_888;
+
+/// Returns the derivation path of the account with the specified index.
+pub fn derivation_path(account_index: u32) -> String {
+    format!("m/44'/{OLA_COIN_TYPE}'/0'/0/{account_index}")
+}
+
+/// Generates a random English mnemonic with the specified number of words
+/// (12, 15, 18, 21 or 24).
+pub fn generate_mnemonic(word_count: usize) -> Result<String, KeystoreError> {
+    if !matches!(word_count, 12 | 15 | 18 | 21 | 24) {
+        return Err(KeystoreError::InvalidMnemonic(format!(
+            "unsupported word count {word_count}; expected 12, 15, 18, 21 or 24"
+        )));
+    }
+    // Each word encodes 11 bits, 1 bit out of every 33 being the checksum.
+    let mut entropy = [0_u8; 32];
+    let entropy = &mut entropy[..word_count * 4 / 3];
+    StdRng::from_entropy().fill(entropy);
+    let mnemonic = Mnemonic::from_entropy_in(Language::English, entropy)
+        .map_err(|err| KeystoreError::InvalidMnemonic(err.to_string()))?;
+    Ok(mnemonic.to_string())
+}
+
+impl OlaKeyPair {
+    /// Derives the key of the account with `account_index` from an English `phrase`
+    /// and an optional BIP39 `passphrase` (empty if not used).
+    pub fn from_mnemonic(
+        phrase: &str,
+        passphrase: &str,
+        account_index: u32,
+    ) -> Result<Self, ClientError> {
+        let mnemonic = Mnemonic::parse_in_normalized(Language::English, phrase.trim())
+            .map_err(|err| KeystoreError::InvalidMnemonic(err.to_string()))?;
+        let seed = mnemonic.to_seed_normalized(passphrase);
+        let path: DerivationPath = derivation_path(account_index)
+            .parse()
+            .map_err(|err: bip32::Error| KeystoreError::Derivation(err.to_string()))?;
+        let key = XPrv::derive_from_path(seed, &path)
+            .map_err(|err| KeystoreError::Derivation(err.to_string()))?;
+        let private_key = H256::from_slice(&key.private_key().to_bytes());
+        Self::from_etherum_private_key(private_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon \
+                          abandon abandon abandon about";
+
+    #[test]
+    fn generating_mnemonics() {
+        for word_count in [12, 24] {
+            let phrase = generate_mnemonic(word_count).unwrap();
+            assert_eq!(phrase.split_whitespace().count(), word_count);
+            Mnemonic::parse_in_normalized(Language::English, &phrase).unwrap();
+        }
+        assert!(generate_mnemonic(13).is_err());
+    }
+
+    #[test]
+    fn deriving_keys() {
+        assert_eq!(derivation_path(3), "m/44'/8888'/0'/0/3");
+
+        let first = OlaKeyPair::from_mnemonic(PHRASE, "", 0).unwrap();
+        let same = OlaKeyPair::from_mnemonic(&format!("  {PHRASE}\n"), "", 0).unwrap();
+        assert_eq!(first.secret, same.secret);
+        let second = OlaKeyPair::from_mnemonic(PHRASE, "", 1).unwrap();
+        assert_ne!(first.secret, second.secret);
+        let with_passphrase = OlaKeyPair::from_mnemonic(PHRASE, "secret", 0).unwrap();
+        assert_ne!(first.secret, with_passphrase.secret);
+
+        let invalid_phrase = PHRASE.replace("about", "abandon");
+        assert!(OlaKeyPair::from_mnemonic(&invalid_phrase, "", 0).is_err());
+    }
+}
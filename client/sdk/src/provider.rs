@@ -1,13 +1,16 @@
 use crate::{
     errors::ClientError,
     operation::{execute_contract::ExecuteContractBuilder, SyncTransactionHandle},
+    transport::{self, RpcClient},
 };
 use ethereum_types::H256;
-use ola_types::{api::TransactionDetails, l2::L2Tx, request::CallRequest, Address, Bytes};
-use ola_web3_decl::{
-    jsonrpsee::http_client::{HttpClient, HttpClientBuilder},
-    namespaces::{eth::EthNamespaceClient, ola::OlaNamespaceClient},
+use ola_types::{
+    api::{TokenBalance, TokenDetails, Transaction, TransactionDetails, TransactionReceipt},
+    l2::L2Tx,
+    request::CallRequest,
+    Address, Bytes,
 };
+use ola_web3_decl::namespaces::{eth::EthNamespaceClient, ola::OlaNamespaceClient};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ProviderParams {
@@ -40,18 +43,30 @@ impl ProviderParams {
 
 #[derive(Debug, Clone)]
 pub struct ExtendProvider {
-    pub provider: HttpClient,
+    pub provider: RpcClient,
 }
 
 impl ExtendProvider {
+    #[cfg(feature = "native")]
     pub fn with_http_client(rpc_address: &str) -> Result<ExtendProvider, ClientError> {
-        let provider = HttpClientBuilder::default().build(rpc_address)?;
+        let provider = ola_web3_decl::jsonrpsee::http_client::HttpClientBuilder::default()
+            .build(rpc_address)?;
+
+        Ok(ExtendProvider { provider })
+    }
+
+    /// Connects to `rpc_address` with the transport of the target; see [`transport`].
+    pub async fn connect(rpc_address: &str) -> Result<ExtendProvider, ClientError> {
+        let provider = transport::build_rpc_client(rpc_address).await?;
 
         Ok(ExtendProvider { provider })
     }
 
     pub async fn call_transaction(&self, call_request: CallRequest) -> Result<Bytes, ClientError> {
-        let ret = self.provider.call_transaction(call_request).await?;
+        let ret = self
+            .provider
+            .call_transaction(call_request, None, None)
+            .await?;
         Ok(ret)
     }
 
@@ -62,4 +77,36 @@ impl ExtendProvider {
         let ret = self.provider.get_transaction_details(hash).await?;
         Ok(ret)
     }
+
+    pub async fn get_transaction_by_hash(
+        &self,
+        hash: H256,
+    ) -> Result<Option<Transaction>, ClientError> {
+        let ret = EthNamespaceClient::get_transaction_by_hash(&self.provider, hash).await?;
+        Ok(ret)
+    }
+
+    pub async fn get_transaction_receipt(
+        &self,
+        hash: H256,
+    ) -> Result<Option<TransactionReceipt>, ClientError> {
+        let ret = OlaNamespaceClient::get_transaction_receipt(&self.provider, hash).await?;
+        Ok(ret)
+    }
+
+    pub async fn get_token_info(
+        &self,
+        address: Address,
+    ) -> Result<Option<TokenDetails>, ClientError> {
+        let ret = self.provider.get_token_info(address).await?;
+        Ok(ret)
+    }
+
+    pub async fn get_token_balances(
+        &self,
+        account: Address,
+    ) -> Result<Vec<TokenBalance>, ClientError> {
+        let ret = self.provider.get_token_balances(account).await?;
+        Ok(ret)
+    }
 }
@@ -0,0 +1,53 @@
+//! JSON-RPC transports of the SDK, selected by the target.
+//!
+//! Native builds (the `native` feature) use HTTP for requests and WebSocket for subscriptions.
+//! Browser builds (the `wasm` feature) use the browser WebSocket for both, since browsers don't
+//! expose raw sockets. If both features are enabled, the native transports are used.
+
+use std::time::Duration;
+
+#[cfg(not(any(feature = "native", feature = "wasm")))]
+compile_error!("either the `native` or the `wasm` feature must be enabled");
+
+#[cfg(all(feature = "wasm", not(feature = "native")))]
+pub use ola_web3_decl::jsonrpsee::wasm_client::Client as RpcClient;
+#[cfg(feature = "native")]
+pub use ola_web3_decl::jsonrpsee::{
+    http_client::HttpClient as RpcClient, ws_client::WsClient as PubSubClient,
+};
+#[cfg(all(feature = "wasm", not(feature = "native")))]
+pub use RpcClient as PubSubClient;
+
+use crate::errors::ClientError;
+
+/// Builds a client for requests to `url`: an HTTP endpoint for native builds, or
+/// a WebSocket endpoint for browser builds.
+pub async fn build_rpc_client(url: &str) -> Result<RpcClient, ClientError> {
+    #[cfg(feature = "native")]
+    let client = ola_web3_decl::jsonrpsee::http_client::HttpClientBuilder::default().build(url)?;
+    #[cfg(all(feature = "wasm", not(feature = "native")))]
+    let client = ola_web3_decl::jsonrpsee::wasm_client::WasmClientBuilder::default()
+        .build(url)
+        .await?;
+    Ok(client)
+}
+
+/// Connects to the WebSocket endpoint at `url` for subscriptions.
+pub async fn build_pubsub_client(url: &str) -> Result<PubSubClient, ClientError> {
+    #[cfg(feature = "native")]
+    let client = ola_web3_decl::jsonrpsee::ws_client::WsClientBuilder::default()
+        .build(url)
+        .await?;
+    #[cfg(all(feature = "wasm", not(feature = "native")))]
+    let client = ola_web3_decl::jsonrpsee::wasm_client::WasmClientBuilder::default()
+        .build(url)
+        .await?;
+    Ok(client)
+}
+
+pub(crate) async fn sleep(delay: Duration) {
+    #[cfg(feature = "native")]
+    tokio::time::sleep(delay).await;
+    #[cfg(all(feature = "wasm", not(feature = "native")))]
+    futures_timer::Delay::new(delay).await;
+}
@@ -1,4 +1,5 @@
 use ethereum_types::{H256, H512, U256};
+use ola_types::{utils::compute_create2_address, Address};
 use ola_utils::convert::h256_to_u64_array;
 use sha2::{Digest, Sha256};
 
@@ -83,6 +84,12 @@ pub fn is_u64_under_felt_order(num: u64) -> bool {
     num < OLA_FIELD_ORDER
 }
 
+/// Returns the address a contract with `bytecode_hash` gets when deployed by `deployer` with
+/// `salt` through the universal deployer.
+pub fn compute_contract_address(deployer: &Address, salt: &H256, bytecode_hash: &H256) -> Address {
+    compute_create2_address(deployer, salt, bytecode_hash)
+}
+
 pub fn is_h256_a_valid_ola_hash(h: H256) -> bool {
     h256_to_u64_array(&h)
         .iter()
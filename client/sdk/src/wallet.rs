@@ -1,12 +1,15 @@
 use crate::{
     errors::ClientError,
+    events::{self, EventStream, FromEventParams},
     operation::{
         deploy_contract::DeployContractBuilder, execute_contract::ExecuteContractBuilder,
         SyncTransactionHandle,
     },
     signer::Signer,
+    transport::{self, RpcClient},
     OlaTxSigner,
 };
+use ola_lang_abi::Abi;
 use ola_types::{
     api::{BlockIdVariant, BlockNumber},
     l2::L2Tx,
@@ -14,30 +17,46 @@ use ola_types::{
     tx::primitives::PackedEthSignature,
     Address, Bytes,
 };
-use ola_web3_decl::{
-    jsonrpsee::http_client::{HttpClient, HttpClientBuilder},
-    namespaces::{eth::EthNamespaceClient, ola::OlaNamespaceClient},
-};
+use ola_web3_decl::namespaces::{eth::EthNamespaceClient, ola::OlaNamespaceClient};
 
 #[derive(Debug)]
 pub struct Wallet<S, P> {
     pub provider: P,
     pub signer: Signer<S>,
+    /// WebSocket endpoint used for subscriptions.
+    pub ws_endpoint: Option<String>,
 }
 
-impl<S> Wallet<S, HttpClient>
+impl<S> Wallet<S, RpcClient>
 where
     S: OlaTxSigner,
 {
+    #[cfg(feature = "native")]
     pub fn with_http_client(
         rpc_address: &str,
         signer: Signer<S>,
-    ) -> Result<Wallet<S, HttpClient>, ClientError> {
-        let client = HttpClientBuilder::default().build(rpc_address)?;
+    ) -> Result<Wallet<S, RpcClient>, ClientError> {
+        let client = ola_web3_decl::jsonrpsee::http_client::HttpClientBuilder::default()
+            .build(rpc_address)?;
 
         Ok(Wallet {
             provider: client,
             signer,
+            ws_endpoint: None,
+        })
+    }
+
+    /// Connects to `rpc_address` with the transport of the target; see [`transport`].
+    pub async fn connect(
+        rpc_address: &str,
+        signer: Signer<S>,
+    ) -> Result<Wallet<S, RpcClient>, ClientError> {
+        let client = transport::build_rpc_client(rpc_address).await?;
+
+        Ok(Wallet {
+            provider: client,
+            signer,
+            ws_endpoint: None,
         })
     }
 }
@@ -48,7 +67,16 @@ where
     P: EthNamespaceClient + OlaNamespaceClient + Sync,
 {
     pub fn new(provider: P, signer: Signer<S>) -> Self {
-        Self { provider, signer }
+        Self {
+            provider,
+            signer,
+            ws_endpoint: None,
+        }
+    }
+
+    pub fn with_ws_endpoint(mut self, ws_endpoint: &str) -> Self {
+        self.ws_endpoint = Some(ws_endpoint.to_owned());
+        self
     }
 
     pub fn address(&self) -> Address {
@@ -122,9 +150,27 @@ where
         let tx_hash = self.provider.send_raw_transaction(bytes).await?;
         Ok(SyncTransactionHandle::new(tx_hash, &self.provider))
     }
+
+    /// Subscribes to `event_signature` events emitted by `contract`, decoding them with `abi`.
+    /// See [`events::subscribe_events()`] for details.
+    pub async fn subscribe_events<T>(
+        &self,
+        contract: Address,
+        abi: Abi,
+        event_signature: &str,
+    ) -> Result<EventStream<T>, ClientError>
+    where
+        T: FromEventParams + Send + 'static,
+    {
+        let ws_endpoint = self
+            .ws_endpoint
+            .as_deref()
+            .ok_or_else(|| ClientError::MissingRequiredField("ws_endpoint".to_owned()))?;
+        events::subscribe_events(ws_endpoint, contract, abi, event_signature).await
+    }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "native"))]
 mod tests {
     use std::fs::File;
 
@@ -1,10 +1,17 @@
-use std::{net::SocketAddr, time::Duration};
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::{envy_load, load_config, BYTES_IN_MB};
+use crate::{
+    envy_load, load_config,
+    units::{DurationMs, DurationSecs, SizeMb},
+    validation::{ConfigValidator, MIN_DURATION},
+};
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ApiConfig {
     pub web3_json_rpc: Web3JsonRpcConfig,
     pub healthcheck: HealthCheckConfig,
@@ -19,7 +26,17 @@ impl ApiConfig {
     }
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+/// What happens to a WS subscription whose notification buffer is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SubscriptionOverflowPolicy {
+    /// Drop the oldest buffered notifications to make room for the new ones.
+    #[default]
+    DropOldest,
+    /// Close the subscription.
+    Disconnect,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Web3JsonRpcConfig {
     pub http_port: u16,
     pub http_url: String,
@@ -30,17 +47,135 @@ pub struct Web3JsonRpcConfig {
     pub max_nonce_ahead: u32,
     pub transactions_per_sec_limit: Option<u32>,
     pub max_tx_size: usize,
+    /// Maximum size of the transaction calldata in bytes. Larger transactions are rejected
+    /// before reaching the mempool.
+    pub max_calldata_size: Option<usize>,
     pub vm_execution_cache_misses_limit: Option<usize>,
     pub vm_concurrency_limit: Option<usize>,
+    /// Lower bound of the VM concurrency limit. If set, the effective limit is autotuned between
+    /// this bound and `vm_concurrency_limit`; otherwise, `vm_concurrency_limit` is used as is.
+    pub vm_concurrency_min_limit: Option<usize>,
+    /// p95 VM execution time in milliseconds above which the autotuner lowers the limit.
+    pub vm_concurrency_target_p95_ms: Option<DurationMs>,
+    /// Share of VM storage reads missing the call storage cache above which the autotuner lowers
+    /// the limit, so that concurrent VMs don't thrash the state database.
+    pub vm_concurrency_max_cache_miss_ratio: Option<f64>,
+    /// Interval between autotuner adjustments, in milliseconds.
+    pub vm_concurrency_autotune_interval_ms: Option<DurationMs>,
+    /// Wall-clock budget of a single `eth_call` VM invocation, in milliseconds.
+    pub call_execution_budget_ms: Option<DurationMs>,
+    /// Wall-clock budget of validating a single submitted transaction, in milliseconds.
+    pub validation_execution_budget_ms: Option<DurationMs>,
+    /// Maximum number of VM cycles an `eth_call` may take.
+    pub call_max_cycles: Option<u32>,
+    /// Maximum number of VM cycles a simulated transaction may take.
+    pub validation_max_cycles: Option<u32>,
+    /// Maximum number of blocks returned by a single `eth_feeHistory` call.
+    pub fee_history_limit: Option<u64>,
+    /// Maximum number of reward percentiles accepted by `eth_feeHistory`.
+    pub fee_history_max_reward_percentiles: Option<usize>,
     pub http_threads: Option<u32>,
     pub ws_threads: Option<u32>,
     pub max_batch_request_size: Option<usize>,
-    pub max_response_body_size_mb: Option<usize>,
-    pub factory_deps_cache_size_mb: Option<usize>,
-    pub initial_writes_cache_size_mb: Option<usize>,
-    pub latest_values_cache_size_mb: Option<usize>,
+    pub max_response_body_size_mb: Option<SizeMb>,
+    pub factory_deps_cache_size_mb: Option<SizeMb>,
+    pub initial_writes_cache_size_mb: Option<SizeMb>,
+    pub latest_values_cache_size_mb: Option<SizeMb>,
     pub subscriptions_limit: Option<u32>,
-    pub pubsub_polling_interval: Option<u64>,
+    /// Interval between polling Postgres for new notifications, in milliseconds.
+    pub pubsub_polling_interval: Option<DurationMs>,
+    /// Maximum number of notifications buffered for a single WS subscription, so that
+    /// a slow subscriber doesn't hold back the others.
+    pub subscription_buffer_size: Option<usize>,
+    /// What happens to a WS subscription whose buffer is full.
+    pub subscription_overflow_policy: Option<SubscriptionOverflowPolicy>,
+    /// Maximum number of subscriptions of a single WS connection.
+    pub max_subscriptions_per_connection: Option<u32>,
+    /// Number of last miniblocks whose `newHeads` and `logs` notifications can be replayed to
+    /// subscriptions specifying `fromBlock`. 0 disables replay.
+    pub subscription_replay_window: Option<u32>,
+    /// Whether to serve admin methods (e.g. `ola_getMempoolStatus` and `txpool_content`)
+    /// on the HTTP API.
+    pub admin_namespace_enabled: Option<bool>,
+    /// Whether `pending` storage reads see writes of the batch currently executed by the sequencer.
+    /// Only has effect if the sequencer runs in the same process as the HTTP API.
+    pub pending_state_overlay_enabled: Option<bool>,
+    /// Whether the node only serves the API (and the tree reader) without running the sequencer.
+    /// Transaction submissions are proxied to `main_node_url` if it's set, and rejected otherwise.
+    pub read_only: Option<bool>,
+    /// URL of the main node JSON-RPC API to proxy transactions to. Only used in read-only mode.
+    pub main_node_url: Option<String>,
+    /// Path to the Unix domain socket (or the name of the Windows named pipe) serving the HTTP API
+    /// over IPC. IPC is disabled if not set.
+    pub ipc_path: Option<String>,
+    /// Permissions of the IPC socket file as an octal string, e.g. `"600"`.
+    pub ipc_permissions: Option<String>,
+    /// Comma-separated names of the only JSON-RPC methods served, e.g. `eth_call,eth_chainId`.
+    /// All methods of enabled namespaces are served if not set.
+    pub methods_allowlist: Option<String>,
+    /// Comma-separated names of JSON-RPC methods that are not served even if allowlisted.
+    pub methods_denylist: Option<String>,
+    /// Share of successful JSON-RPC calls written to the access log, from 0 to 1. The access log
+    /// is disabled if not set.
+    pub access_log_sample_rate: Option<f64>,
+    /// Share of failed JSON-RPC calls written to the access log, from 0 to 1. Defaults to
+    /// `access_log_sample_rate`.
+    pub access_log_error_sample_rate: Option<f64>,
+    /// Path to the access log file (JSON lines). Entries are logged via `olaos_logs` if not set.
+    pub access_log_path: Option<String>,
+    /// Size of the access log file after which it's rotated. Only the previous file is kept.
+    pub access_log_max_file_size_mb: Option<SizeMb>,
+    /// Whether to mask the host part of caller IPs in the access log.
+    pub access_log_redact_ips: Option<bool>,
+    /// Comma-separated IP addresses of reverse proxies whose `x-forwarded-for` entries are
+    /// trusted. Caller IPs are not recorded if not set.
+    pub access_log_trusted_proxies: Option<String>,
+    /// For how long account nonces read during transaction prechecks are cached. If the sequencer
+    /// runs in the same process, cached nonces are also dropped once a miniblock is sealed.
+    /// Set to 0 to disable the cache.
+    pub nonce_cache_ttl_ms: Option<DurationMs>,
+    /// For how long zero nonces (i.e., accounts without sent transactions) are cached.
+    pub nonce_cache_negative_ttl_ms: Option<DurationMs>,
+    /// Number of call storages (i.e., sequencer RocksDB instances) opened in advance for `eth_call`.
+    /// Disabled by default, in which case a storage is opened on each call.
+    pub call_storage_pool_size: Option<usize>,
+    /// Maximum size of the output returned by `ola_callTransaction`. Larger outputs are truncated
+    /// and marked as such in the response.
+    pub max_call_output_size_mb: Option<SizeMb>,
+    /// Whether to store full truncated call outputs in the object store, so that they can be
+    /// fetched by hash via `ola_getCallOutput`.
+    pub spill_call_outputs: Option<bool>,
+    /// Maximum total size of call outputs stored in the object store. The oldest outputs are
+    /// removed to fit new ones.
+    pub call_output_quota_mb: Option<SizeMb>,
+    /// How long call outputs are kept in the object store.
+    pub call_output_retention_sec: Option<DurationSecs>,
+    /// Maximum number of full transactions returned inline by `eth_getBlockBy*`. Larger blocks
+    /// must be fetched in pages of at most this size via `ola_getBlockTransactions`.
+    pub max_inline_block_txs: Option<usize>,
+    /// Path to a JSON file with calldata screening rules (forbidden selectors, exploit signatures
+    /// and a calldata size cap) checked before mempool admission. Rules can be replaced at runtime
+    /// via `ola_setCalldataScreeningRules`; such rules are persisted in Postgres and take precedence
+    /// over the file. Screening is disabled if not set.
+    pub calldata_screening_rules_path: Option<String>,
+    /// Number of threads dedicated to VM invocations for `eth_call`, gas estimation and transaction
+    /// validation.
+    pub sandbox_threads: Option<usize>,
+    /// Maximum number of VM invocations waiting for a sandbox thread. Further invocations are rejected
+    /// with a "node is overloaded" error.
+    pub sandbox_queue_capacity: Option<usize>,
+    /// Number of threads dedicated to Merkle tree reads (e.g., `eth_getProof`).
+    pub tree_read_threads: Option<usize>,
+    /// Maximum number of Merkle tree reads waiting for a thread. Further reads are rejected.
+    pub tree_read_queue_capacity: Option<usize>,
+    /// Maximum delay of transactions submitted via `ola_sendScheduledTransaction`, counted from
+    /// the submission time.
+    pub max_scheduled_tx_delay_sec: Option<DurationSecs>,
+    /// How long simulations recorded for transactions submitted via
+    /// `ola_sendRawTransactionWithSimulation` are kept.
+    pub simulation_retention_sec: Option<DurationSecs>,
+    /// Maximum number of miniblocks in the block range queried by `eth_getLogs`.
+    pub max_logs_block_range: Option<u32>,
 }
 
 impl Web3JsonRpcConfig {
@@ -56,8 +191,165 @@ impl Web3JsonRpcConfig {
         self.subscriptions_limit.unwrap_or(10000) as usize
     }
 
+    pub fn subscription_buffer_size(&self) -> usize {
+        self.subscription_buffer_size.unwrap_or(1_024)
+    }
+
+    pub fn subscription_overflow_policy(&self) -> SubscriptionOverflowPolicy {
+        self.subscription_overflow_policy.unwrap_or_default()
+    }
+
+    pub fn max_subscriptions_per_connection(&self) -> u32 {
+        self.max_subscriptions_per_connection.unwrap_or(64)
+    }
+
+    pub fn subscription_replay_window(&self) -> u32 {
+        self.subscription_replay_window.unwrap_or(1_000)
+    }
+
+    pub fn max_calldata_size(&self) -> usize {
+        self.max_calldata_size.unwrap_or(128 * 1024)
+    }
+
+    pub fn admin_namespace_enabled(&self) -> bool {
+        self.admin_namespace_enabled.unwrap_or(false)
+    }
+
+    pub fn pending_state_overlay_enabled(&self) -> bool {
+        self.pending_state_overlay_enabled.unwrap_or(false)
+    }
+
+    pub fn read_only(&self) -> bool {
+        self.read_only.unwrap_or(false)
+    }
+
+    /// Returns permissions of the IPC socket file; only the node user has access by default.
+    pub fn ipc_permissions(&self) -> Option<u32> {
+        match &self.ipc_permissions {
+            Some(permissions) => u32::from_str_radix(permissions, 8)
+                .ok()
+                .filter(|&permissions| permissions <= 0o777),
+            None => Some(0o600),
+        }
+    }
+
+    pub fn methods_allowlist(&self) -> Option<Vec<String>> {
+        self.methods_allowlist.as_deref().map(split_list)
+    }
+
+    pub fn methods_denylist(&self) -> Vec<String> {
+        self.methods_denylist
+            .as_deref()
+            .map(split_list)
+            .unwrap_or_default()
+    }
+
+    pub fn access_log_max_file_size(&self) -> u64 {
+        self.access_log_max_file_size_mb
+            .unwrap_or(SizeMb::from_mb(100))
+            .bytes()
+    }
+
+    pub fn access_log_redact_ips(&self) -> bool {
+        self.access_log_redact_ips.unwrap_or(false)
+    }
+
+    pub fn access_log_trusted_proxies(&self) -> Vec<IpAddr> {
+        self.access_log_trusted_proxies
+            .as_deref()
+            .map(split_list)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|ip| ip.parse().ok())
+            .collect()
+    }
+
+    pub fn nonce_cache_ttl(&self) -> Duration {
+        self.nonce_cache_ttl_ms
+            .unwrap_or(DurationMs::from_millis(1_000))
+            .get()
+    }
+
+    pub fn call_max_cycles(&self) -> u32 {
+        self.call_max_cycles.unwrap_or(1 << 24)
+    }
+
+    pub fn validation_max_cycles(&self) -> u32 {
+        self.validation_max_cycles.unwrap_or(1 << 22)
+    }
+
+    pub fn nonce_cache_negative_ttl(&self) -> Duration {
+        self.nonce_cache_negative_ttl_ms
+            .unwrap_or(DurationMs::from_millis(10_000))
+            .get()
+    }
+
+    pub fn call_storage_pool_size(&self) -> usize {
+        self.call_storage_pool_size.unwrap_or(0)
+    }
+
+    pub fn sandbox_threads(&self) -> usize {
+        self.sandbox_threads.unwrap_or(64)
+    }
+
+    pub fn sandbox_queue_capacity(&self) -> usize {
+        self.sandbox_queue_capacity.unwrap_or(1_024)
+    }
+
+    pub fn tree_read_threads(&self) -> usize {
+        self.tree_read_threads.unwrap_or(8)
+    }
+
+    pub fn tree_read_queue_capacity(&self) -> usize {
+        self.tree_read_queue_capacity.unwrap_or(256)
+    }
+
+    pub fn max_scheduled_tx_delay(&self) -> Duration {
+        self.max_scheduled_tx_delay_sec
+            .unwrap_or(DurationSecs::from_secs(7 * 24 * 3_600))
+            .get()
+    }
+
+    pub fn max_call_output_size(&self) -> usize {
+        self.max_call_output_size_mb
+            .unwrap_or(SizeMb::from_mb(1))
+            .bytes() as usize
+    }
+
+    pub fn spill_call_outputs(&self) -> bool {
+        self.spill_call_outputs.unwrap_or(false)
+    }
+
+    pub fn call_output_quota(&self) -> u64 {
+        self.call_output_quota_mb
+            .unwrap_or(SizeMb::from_mb(1_024))
+            .bytes()
+    }
+
+    pub fn call_output_retention(&self) -> Duration {
+        self.call_output_retention_sec
+            .unwrap_or(DurationSecs::from_secs(86_400))
+            .get()
+    }
+
+    pub fn simulation_retention(&self) -> Duration {
+        self.simulation_retention_sec
+            .unwrap_or(DurationSecs::from_secs(7 * 86_400))
+            .get()
+    }
+
+    pub fn max_inline_block_txs(&self) -> usize {
+        self.max_inline_block_txs.unwrap_or(1_000)
+    }
+
+    pub fn max_logs_block_range(&self) -> u32 {
+        self.max_logs_block_range.unwrap_or(10_000)
+    }
+
     pub fn pubsub_interval(&self) -> Duration {
-        Duration::from_millis(self.pubsub_polling_interval.unwrap_or(200))
+        self.pubsub_polling_interval
+            .unwrap_or(DurationMs::from_millis(200))
+            .get()
     }
 
     pub fn http_server_threads(&self) -> usize {
@@ -73,27 +365,269 @@ impl Web3JsonRpcConfig {
     }
 
     pub fn max_response_body_size(&self) -> usize {
-        self.max_response_body_size_mb.unwrap_or(10) * BYTES_IN_MB
+        self.max_response_body_size_mb
+            .unwrap_or(SizeMb::from_mb(10))
+            .bytes() as usize
     }
 
     pub fn vm_concurrency_limit(&self) -> usize {
         self.vm_concurrency_limit.unwrap_or(2048)
     }
 
+    pub fn vm_concurrency_target_p95(&self) -> Duration {
+        self.vm_concurrency_target_p95_ms
+            .unwrap_or(DurationMs::from_millis(1_000))
+            .get()
+    }
+
+    pub fn vm_concurrency_max_cache_miss_ratio(&self) -> f64 {
+        self.vm_concurrency_max_cache_miss_ratio.unwrap_or(0.5)
+    }
+
+    pub fn vm_concurrency_autotune_interval(&self) -> Duration {
+        self.vm_concurrency_autotune_interval_ms
+            .unwrap_or(DurationMs::from_millis(10_000))
+            .get()
+    }
+
+    pub fn call_execution_budget(&self) -> Duration {
+        self.call_execution_budget_ms
+            .unwrap_or(DurationMs::from_millis(10_000))
+            .get()
+    }
+
+    pub fn validation_execution_budget(&self) -> Duration {
+        self.validation_execution_budget_ms
+            .unwrap_or(DurationMs::from_millis(1_000))
+            .get()
+    }
+
+    pub fn fee_history_limit(&self) -> u64 {
+        self.fee_history_limit.unwrap_or(1024)
+    }
+
+    pub fn fee_history_max_reward_percentiles(&self) -> usize {
+        self.fee_history_max_reward_percentiles.unwrap_or(100)
+    }
+
     pub fn factory_deps_cache_size(&self) -> usize {
-        self.factory_deps_cache_size_mb.unwrap_or(128) * BYTES_IN_MB
+        self.factory_deps_cache_size_mb
+            .unwrap_or(SizeMb::from_mb(128))
+            .bytes() as usize
     }
 
     pub fn initial_writes_cache_size(&self) -> usize {
-        self.initial_writes_cache_size_mb.unwrap_or(32) * BYTES_IN_MB
+        self.initial_writes_cache_size_mb
+            .unwrap_or(SizeMb::from_mb(32))
+            .bytes() as usize
     }
 
     pub fn latest_values_cache_size(&self) -> usize {
-        self.latest_values_cache_size_mb.unwrap_or(128) * BYTES_IN_MB
+        self.latest_values_cache_size_mb
+            .unwrap_or(SizeMb::from_mb(128))
+            .bytes() as usize
     }
+
+    pub fn validate(&self, validator: &mut ConfigValidator) {
+        validator.check(
+            self.http_port != self.ws_port,
+            "web3_json_rpc.ws_port",
+            format!("must differ from http_port ({})", self.http_port),
+        );
+        validator.check(
+            self.http_server_threads() > 0 && self.ws_server_threads() > 0,
+            "web3_json_rpc.threads_per_server",
+            "HTTP and WS servers must have at least one thread",
+        );
+        validator.check(
+            self.vm_concurrency_limit() > 0,
+            "web3_json_rpc.vm_concurrency_limit",
+            "must be positive",
+        );
+        if let Some(min_limit) = self.vm_concurrency_min_limit {
+            validator.check(
+                min_limit > 0 && min_limit <= self.vm_concurrency_limit(),
+                "web3_json_rpc.vm_concurrency_min_limit",
+                format!(
+                    "must be positive and not exceed vm_concurrency_limit ({})",
+                    self.vm_concurrency_limit()
+                ),
+            );
+        }
+        validator.check_range(
+            self.vm_concurrency_target_p95(),
+            MIN_DURATION..,
+            "web3_json_rpc.vm_concurrency_target_p95_ms",
+        );
+        let max_cache_miss_ratio = self.vm_concurrency_max_cache_miss_ratio();
+        validator.check(
+            max_cache_miss_ratio > 0.0 && max_cache_miss_ratio <= 1.0,
+            "web3_json_rpc.vm_concurrency_max_cache_miss_ratio",
+            "must be in (0, 1]",
+        );
+        validator.check_range(
+            self.vm_concurrency_autotune_interval(),
+            MIN_DURATION..,
+            "web3_json_rpc.vm_concurrency_autotune_interval_ms",
+        );
+        validator.check_range(
+            self.call_execution_budget(),
+            MIN_DURATION..,
+            "web3_json_rpc.call_execution_budget_ms",
+        );
+        validator.check_range(
+            self.validation_execution_budget(),
+            MIN_DURATION..,
+            "web3_json_rpc.validation_execution_budget_ms",
+        );
+        validator.check(
+            self.call_max_cycles() > 0,
+            "web3_json_rpc.call_max_cycles",
+            "must be positive",
+        );
+        validator.check(
+            self.validation_max_cycles() > 0,
+            "web3_json_rpc.validation_max_cycles",
+            "must be positive",
+        );
+        validator.check_range(
+            self.pubsub_interval(),
+            MIN_DURATION..,
+            "web3_json_rpc.pubsub_polling_interval",
+        );
+        validator.check_range(
+            self.max_response_body_size(),
+            1..,
+            "web3_json_rpc.max_response_body_size_mb",
+        );
+        validator.check_range(
+            self.max_call_output_size(),
+            1..=self.max_response_body_size(),
+            "web3_json_rpc.max_call_output_size_mb",
+        );
+        if self.spill_call_outputs() {
+            validator.check(
+                self.call_output_quota() >= self.max_call_output_size() as u64,
+                "web3_json_rpc.call_output_quota_mb",
+                "must not be less than `max_call_output_size_mb`",
+            );
+            validator.check_range(
+                self.call_output_retention(),
+                MIN_DURATION..,
+                "web3_json_rpc.call_output_retention_sec",
+            );
+        }
+        validator.check_range(
+            self.simulation_retention(),
+            MIN_DURATION..,
+            "web3_json_rpc.simulation_retention_sec",
+        );
+        validator.check(
+            self.sandbox_threads() > 0 && self.sandbox_queue_capacity() > 0,
+            "web3_json_rpc.sandbox_threads",
+            "sandbox thread pool must have positive thread count and queue capacity",
+        );
+        validator.check(
+            self.tree_read_threads() > 0 && self.tree_read_queue_capacity() > 0,
+            "web3_json_rpc.tree_read_threads",
+            "tree read thread pool must have positive thread count and queue capacity",
+        );
+        validator.check_range(
+            self.max_scheduled_tx_delay(),
+            MIN_DURATION..,
+            "web3_json_rpc.max_scheduled_tx_delay_sec",
+        );
+        validator.check(
+            self.max_inline_block_txs() > 0,
+            "web3_json_rpc.max_inline_block_txs",
+            "must be positive",
+        );
+        validator.check(
+            self.max_logs_block_range() > 0,
+            "web3_json_rpc.max_logs_block_range",
+            "must be positive",
+        );
+        validator.check(
+            self.fee_history_limit() > 0,
+            "web3_json_rpc.fee_history_limit",
+            "must be positive",
+        );
+        validator.check(
+            self.max_calldata_size() % 8 == 0,
+            "web3_json_rpc.max_calldata_size",
+            "must be a multiple of 8 bytes",
+        );
+        validator.check(
+            self.max_batch_request_size() > 0,
+            "web3_json_rpc.max_batch_request_size",
+            "must be positive",
+        );
+        validator.check(
+            self.subscription_buffer_size() > 0,
+            "web3_json_rpc.subscription_buffer_size",
+            "must be positive",
+        );
+        validator.check(
+            self.max_subscriptions_per_connection() > 0,
+            "web3_json_rpc.max_subscriptions_per_connection",
+            "must be positive",
+        );
+        validator.check(
+            self.main_node_url.is_none() || self.read_only(),
+            "web3_json_rpc.main_node_url",
+            "is only used if read_only is enabled",
+        );
+        validator.check(
+            self.ipc_permissions().is_some(),
+            "web3_json_rpc.ipc_permissions",
+            "must be an octal mode not exceeding 777",
+        );
+        validator.check(
+            self.methods_allowlist()
+                .map_or(true, |methods| !methods.is_empty()),
+            "web3_json_rpc.methods_allowlist",
+            "must list at least one method if set",
+        );
+        if let Some(sample_rate) = self.access_log_sample_rate {
+            validator.check(
+                (0.0..=1.0).contains(&sample_rate),
+                "web3_json_rpc.access_log_sample_rate",
+                "must be in [0, 1]",
+            );
+        }
+        if let Some(sample_rate) = self.access_log_error_sample_rate {
+            validator.check(
+                (0.0..=1.0).contains(&sample_rate),
+                "web3_json_rpc.access_log_error_sample_rate",
+                "must be in [0, 1]",
+            );
+        }
+        if let Some(proxies) = self.access_log_trusted_proxies.as_deref() {
+            validator.check(
+                split_list(proxies)
+                    .iter()
+                    .all(|ip| ip.parse::<IpAddr>().is_ok()),
+                "web3_json_rpc.access_log_trusted_proxies",
+                "must be a comma-separated list of IP addresses",
+            );
+        }
+        validator.check(
+            self.access_log_max_file_size() > 0,
+            "web3_json_rpc.access_log_max_file_size_mb",
+            "must be positive",
+        );
+    }
+}
+
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_owned)
+        .collect()
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct HealthCheckConfig {
     /// Port to which the REST server is listening.
     pub port: u16,
@@ -127,7 +661,8 @@ pub fn load_healthcheck_config() -> Result<HealthCheckConfig, config::ConfigErro
 #[cfg(test)]
 mod tests {
 
-    use super::{ApiConfig, HealthCheckConfig, Web3JsonRpcConfig};
+    use super::{ApiConfig, HealthCheckConfig, SubscriptionOverflowPolicy, Web3JsonRpcConfig};
+    use crate::units::{DurationMs, DurationSecs, SizeMb};
 
     use crate::{api::load_api_config, utils::tests::EnvMutex};
 
@@ -141,21 +676,66 @@ mod tests {
                 ws_port: 1002,
                 ws_url: "ws://127.0.0.1:1002".to_string(),
                 max_tx_size: 1_000_000,
+                max_calldata_size: Some(65536),
                 vm_execution_cache_misses_limit: None,
                 vm_concurrency_limit: Some(2048),
+                vm_concurrency_min_limit: Some(64),
+                vm_concurrency_target_p95_ms: Some(DurationMs::from_millis(500)),
+                vm_concurrency_max_cache_miss_ratio: Some(0.25),
+                vm_concurrency_autotune_interval_ms: Some(DurationMs::from_millis(5_000)),
+                call_execution_budget_ms: Some(DurationMs::from_millis(5_000)),
+                validation_execution_budget_ms: Some(DurationMs::from_millis(500)),
+                call_max_cycles: Some(8_000_000),
+                validation_max_cycles: Some(1_000_000),
+                fee_history_limit: Some(256),
+                fee_history_max_reward_percentiles: Some(50),
                 filters_limit: Some(10_000),
                 threads_per_server: 128,
                 http_threads: Some(128),
                 ws_threads: Some(256),
                 max_batch_request_size: Some(200),
-                max_response_body_size_mb: Some(10),
+                max_response_body_size_mb: Some(SizeMb::from_mb(10)),
                 max_nonce_ahead: 5,
                 transactions_per_sec_limit: Some(1000),
-                factory_deps_cache_size_mb: Some(128),
-                initial_writes_cache_size_mb: Some(32),
-                latest_values_cache_size_mb: Some(128),
+                factory_deps_cache_size_mb: Some(SizeMb::from_mb(128)),
+                initial_writes_cache_size_mb: Some(SizeMb::from_mb(32)),
+                latest_values_cache_size_mb: Some(SizeMb::from_mb(128)),
                 subscriptions_limit: Some(10000),
-                pubsub_polling_interval: Some(200),
+                pubsub_polling_interval: Some(DurationMs::from_millis(200)),
+                subscription_buffer_size: Some(256),
+                subscription_overflow_policy: Some(SubscriptionOverflowPolicy::Disconnect),
+                max_subscriptions_per_connection: Some(16),
+                subscription_replay_window: Some(500),
+                admin_namespace_enabled: Some(true),
+                pending_state_overlay_enabled: Some(true),
+                read_only: Some(true),
+                main_node_url: Some("http://127.0.0.1:13000".to_string()),
+                ipc_path: Some("/tmp/olaos.ipc".to_string()),
+                ipc_permissions: Some("660".to_string()),
+                methods_allowlist: None,
+                methods_denylist: Some("debug_traceCall, ola_getTopContracts".to_string()),
+                access_log_sample_rate: Some(0.01),
+                access_log_error_sample_rate: Some(0.1),
+                access_log_path: Some("/var/log/olaos/rpc_access.log".to_string()),
+                access_log_max_file_size_mb: Some(SizeMb::from_mb(512)),
+                access_log_redact_ips: Some(true),
+                access_log_trusted_proxies: Some("10.0.0.1, 10.0.0.2".to_string()),
+                nonce_cache_ttl_ms: Some(DurationMs::from_millis(500)),
+                nonce_cache_negative_ttl_ms: Some(DurationMs::from_millis(30_000)),
+                call_storage_pool_size: Some(8),
+                max_call_output_size_mb: Some(SizeMb::from_mb(2)),
+                spill_call_outputs: Some(true),
+                call_output_quota_mb: Some(SizeMb::from_mb(4_096)),
+                call_output_retention_sec: Some(DurationSecs::from_secs(3_600)),
+                max_inline_block_txs: Some(500),
+                calldata_screening_rules_path: Some("/etc/olaos/calldata_rules.json".to_string()),
+                sandbox_threads: Some(32),
+                sandbox_queue_capacity: Some(512),
+                tree_read_threads: Some(4),
+                tree_read_queue_capacity: Some(128),
+                max_scheduled_tx_delay_sec: Some(DurationSecs::from_secs(86_400)),
+                simulation_retention_sec: Some(DurationSecs::from_secs(2 * 86_400)),
+                max_logs_block_range: Some(5_000),
             },
             healthcheck: HealthCheckConfig { port: 8081 },
         }
@@ -170,11 +750,60 @@ mod tests {
             OLAOS_WEB3_JSON_RPC_WS_PORT="1002"
             OLAOS_WEB3_JSON_RPC_WS_URL="ws://127.0.0.1:1002"
             OLAOS_WEB3_JSON_RPC_MAX_NONCE_AHEAD=5
+            OLAOS_WEB3_JSON_RPC_MAX_CALLDATA_SIZE=65536
+            OLAOS_WEB3_JSON_RPC_VM_CONCURRENCY_MIN_LIMIT=64
+            OLAOS_WEB3_JSON_RPC_VM_CONCURRENCY_TARGET_P95_MS=500
+            OLAOS_WEB3_JSON_RPC_VM_CONCURRENCY_MAX_CACHE_MISS_RATIO=0.25
+            OLAOS_WEB3_JSON_RPC_VM_CONCURRENCY_AUTOTUNE_INTERVAL_MS=5000
+            OLAOS_WEB3_JSON_RPC_CALL_EXECUTION_BUDGET_MS=5s
+            OLAOS_WEB3_JSON_RPC_VALIDATION_EXECUTION_BUDGET_MS=500
+            OLAOS_WEB3_JSON_RPC_CALL_MAX_CYCLES=8000000
+            OLAOS_WEB3_JSON_RPC_VALIDATION_MAX_CYCLES=1000000
+            OLAOS_WEB3_JSON_RPC_FEE_HISTORY_LIMIT=256
+            OLAOS_WEB3_JSON_RPC_FEE_HISTORY_MAX_REWARD_PERCENTILES=50
+            OLAOS_WEB3_JSON_RPC_SUBSCRIPTION_BUFFER_SIZE=256
+            OLAOS_WEB3_JSON_RPC_SUBSCRIPTION_OVERFLOW_POLICY="Disconnect"
+            OLAOS_WEB3_JSON_RPC_MAX_SUBSCRIPTIONS_PER_CONNECTION=16
+            OLAOS_WEB3_JSON_RPC_SUBSCRIPTION_REPLAY_WINDOW=500
+            OLAOS_WEB3_JSON_RPC_ADMIN_NAMESPACE_ENABLED=true
+            OLAOS_WEB3_JSON_RPC_PENDING_STATE_OVERLAY_ENABLED=true
+            OLAOS_WEB3_JSON_RPC_READ_ONLY=true
+            OLAOS_WEB3_JSON_RPC_MAIN_NODE_URL="http://127.0.0.1:13000"
+            OLAOS_WEB3_JSON_RPC_IPC_PATH="/tmp/olaos.ipc"
+            OLAOS_WEB3_JSON_RPC_IPC_PERMISSIONS="660"
+            OLAOS_WEB3_JSON_RPC_METHODS_DENYLIST="debug_traceCall, ola_getTopContracts"
+            OLAOS_WEB3_JSON_RPC_ACCESS_LOG_SAMPLE_RATE=0.01
+            OLAOS_WEB3_JSON_RPC_ACCESS_LOG_ERROR_SAMPLE_RATE=0.1
+            OLAOS_WEB3_JSON_RPC_ACCESS_LOG_PATH="/var/log/olaos/rpc_access.log"
+            OLAOS_WEB3_JSON_RPC_ACCESS_LOG_MAX_FILE_SIZE_MB=512MiB
+            OLAOS_WEB3_JSON_RPC_ACCESS_LOG_REDACT_IPS=true
+            OLAOS_WEB3_JSON_RPC_ACCESS_LOG_TRUSTED_PROXIES="10.0.0.1, 10.0.0.2"
+            OLAOS_WEB3_JSON_RPC_NONCE_CACHE_TTL_MS=500
+            OLAOS_WEB3_JSON_RPC_NONCE_CACHE_NEGATIVE_TTL_MS=30000
+            OLAOS_WEB3_JSON_RPC_CALL_STORAGE_POOL_SIZE=8
+            OLAOS_WEB3_JSON_RPC_MAX_CALL_OUTPUT_SIZE_MB=2MiB
+            OLAOS_WEB3_JSON_RPC_SPILL_CALL_OUTPUTS=true
+            OLAOS_WEB3_JSON_RPC_CALL_OUTPUT_QUOTA_MB=4GiB
+            OLAOS_WEB3_JSON_RPC_CALL_OUTPUT_RETENTION_SEC=1h
+            OLAOS_WEB3_JSON_RPC_MAX_INLINE_BLOCK_TXS=500
+            OLAOS_WEB3_JSON_RPC_CALLDATA_SCREENING_RULES_PATH="/etc/olaos/calldata_rules.json"
+            OLAOS_WEB3_JSON_RPC_SANDBOX_THREADS=32
+            OLAOS_WEB3_JSON_RPC_SANDBOX_QUEUE_CAPACITY=512
+            OLAOS_WEB3_JSON_RPC_TREE_READ_THREADS=4
+            OLAOS_WEB3_JSON_RPC_TREE_READ_QUEUE_CAPACITY=128
+            OLAOS_WEB3_JSON_RPC_MAX_SCHEDULED_TX_DELAY_SEC=86400
+            OLAOS_WEB3_JSON_RPC_SIMULATION_RETENTION_SEC=48h
+            OLAOS_WEB3_JSON_RPC_MAX_LOGS_BLOCK_RANGE=5000
             OLAOS_HEALTHCHECK_PORT=8081
         "#;
         lock.set_env(config);
 
         let api_config = load_api_config().expect("failed to load api config");
         assert_eq!(api_config, default_config());
+        assert_eq!(api_config.web3_json_rpc.methods_allowlist(), None);
+        assert_eq!(
+            api_config.web3_json_rpc.methods_denylist(),
+            ["debug_traceCall", "ola_getTopContracts"]
+        );
     }
 }
@@ -1,41 +1,81 @@
 use std::time::Duration;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::{envy_load, load_config};
+use crate::{
+    envy_load, load_config,
+    units::{DurationMs, DurationSecs},
+    validation::{ConfigValidator, MIN_DURATION},
+};
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct MempoolConfig {
-    pub sync_interval_ms: u64,
+    pub sync_interval_ms: DurationMs,
     pub sync_batch_size: usize,
     pub capacity: u64,
-    pub stuck_tx_timeout: u64,
+    pub stuck_tx_timeout: DurationSecs,
     pub remove_stuck_txs: bool,
-    pub delay_interval: u64,
+    pub delay_interval: DurationMs,
+    /// Maximum number of scheduled transactions, i.e. ones not executable before their
+    /// `execute_after` timestamp, held in the mempool, so that they can't crowd out executable
+    /// transactions. Defaults to a tenth of the capacity.
+    pub max_scheduled_transactions: Option<u64>,
 }
 
 impl MempoolConfig {
     pub fn sync_interval(&self) -> Duration {
-        Duration::from_millis(self.sync_interval_ms)
+        self.sync_interval_ms.get()
     }
 
     pub fn stuck_tx_timeout(&self) -> Duration {
-        Duration::from_secs(self.stuck_tx_timeout)
+        self.stuck_tx_timeout.get()
     }
 
     pub fn delay_interval(&self) -> Duration {
-        Duration::from_millis(self.delay_interval)
+        self.delay_interval.get()
+    }
+
+    pub fn max_scheduled_transactions(&self) -> u64 {
+        self.max_scheduled_transactions
+            .unwrap_or(self.capacity / 10)
     }
 
     pub fn from_env() -> Self {
         envy_load("mempool", "OLAOS_MEMPOOL_")
     }
+
+    pub fn validate(&self, validator: &mut ConfigValidator) {
+        validator.check(self.capacity > 0, "mempool.capacity", "must be positive");
+        validator.check(
+            self.sync_batch_size > 0 && self.sync_batch_size as u64 <= self.capacity,
+            "mempool.sync_batch_size",
+            format!(
+                "must be positive and not exceed mempool capacity ({})",
+                self.capacity
+            ),
+        );
+        validator.check_range(
+            self.sync_interval(),
+            MIN_DURATION..,
+            "mempool.sync_interval_ms",
+        );
+        validator.check_range(
+            self.delay_interval(),
+            MIN_DURATION..,
+            "mempool.delay_interval",
+        );
+        validator.check(
+            self.max_scheduled_transactions() <= self.capacity,
+            "mempool.max_scheduled_transactions",
+            format!("must not exceed mempool capacity ({})", self.capacity),
+        );
+    }
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct OperationsManagerConfig {
     /// Sleep time in ms when there is no new input data
-    pub delay_interval: u64,
+    pub delay_interval: DurationMs,
 }
 
 impl OperationsManagerConfig {
@@ -44,7 +84,7 @@ impl OperationsManagerConfig {
     }
 
     pub fn delay_interval(&self) -> Duration {
-        Duration::from_millis(self.delay_interval)
+        self.delay_interval.get()
     }
 }
 
@@ -63,6 +103,7 @@ pub fn load_operation_manager_config() -> Result<OperationsManagerConfig, config
 mod tests {
     use crate::{
         chain::{load_mempool_config, load_operation_manager_config},
+        units::{DurationMs, DurationSecs},
         utils::tests::EnvMutex,
     };
 
@@ -72,18 +113,19 @@ mod tests {
 
     fn default_mempool_config() -> MempoolConfig {
         MempoolConfig {
-            sync_interval_ms: 10,
+            sync_interval_ms: DurationMs::from_millis(10),
             sync_batch_size: 1000,
             capacity: 10000,
-            stuck_tx_timeout: 50,
+            stuck_tx_timeout: DurationSecs::from_secs(50),
             remove_stuck_txs: true,
-            delay_interval: 200,
+            delay_interval: DurationMs::from_millis(200),
+            max_scheduled_transactions: Some(500),
         }
     }
 
     fn default_operation_manager_config() -> OperationsManagerConfig {
         OperationsManagerConfig {
-            delay_interval: 100,
+            delay_interval: DurationMs::from_millis(100),
         }
     }
 
@@ -92,8 +134,9 @@ mod tests {
         let mut lock = MUTEX.lock();
         let config = r#"
             OLAOS_MEMPOOL_CAPACITY=10000
-            OLAOS_MEMPOOL_STUCK_TX_TIMEOUT=50
+            OLAOS_MEMPOOL_STUCK_TX_TIMEOUT=50s
             OLAOS_MEMPOOL_DELAY_INTERVAL=200
+            OLAOS_MEMPOOL_MAX_SCHEDULED_TRANSACTIONS=500
         "#;
         lock.set_env(config);
 
@@ -2,9 +2,11 @@ use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
-use crate::{envy_load, load_config};
-
-const BYTES_IN_MEGABYTE: usize = 1_024 * 1_024;
+use crate::{
+    envy_load, load_config,
+    units::{DurationMs, DurationSecs, SizeMb},
+    validation::{ConfigValidator, MIN_DURATION},
+};
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MerkleTreeMode {
@@ -13,7 +15,7 @@ pub enum MerkleTreeMode {
     Lightweight,
 }
 
-#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MerkleTreeConfig {
     #[serde(default = "MerkleTreeConfig::default_path")]
     pub path: String,
@@ -22,13 +24,19 @@ pub struct MerkleTreeConfig {
     #[serde(default = "MerkleTreeConfig::default_multi_get_chunk_size")]
     pub multi_get_chunk_size: usize,
     #[serde(default = "MerkleTreeConfig::default_block_cache_size_mb")]
-    pub block_cache_size_mb: usize,
+    pub block_cache_size_mb: SizeMb,
     #[serde(default = "MerkleTreeConfig::default_memtable_capacity_mb")]
-    pub memtable_capacity_mb: usize,
+    pub memtable_capacity_mb: SizeMb,
     #[serde(default = "MerkleTreeConfig::default_stalled_writes_timeout_sec")]
-    pub stalled_writes_timeout_sec: u64,
+    pub stalled_writes_timeout_sec: DurationSecs,
     #[serde(default = "MerkleTreeConfig::default_max_l1_batches_per_iter")]
     pub max_l1_batches_per_iter: usize,
+    /// Interval between reports of RocksDB IO statistics of the tree. If not set,
+    /// the statistics are not collected.
+    pub io_stats_interval_sec: Option<DurationSecs>,
+    /// Whether to read the tree nodes touched by the next L1 batch into the RocksDB block cache
+    /// while the previous batch is processed. Disabled by default.
+    pub prefetch_enabled: Option<bool>,
 }
 
 impl Default for MerkleTreeConfig {
@@ -41,6 +49,8 @@ impl Default for MerkleTreeConfig {
             memtable_capacity_mb: Self::default_memtable_capacity_mb(),
             stalled_writes_timeout_sec: Self::default_stalled_writes_timeout_sec(),
             max_l1_batches_per_iter: Self::default_max_l1_batches_per_iter(),
+            io_stats_interval_sec: None,
+            prefetch_enabled: None,
         }
     }
 }
@@ -54,16 +64,16 @@ impl MerkleTreeConfig {
         500
     }
 
-    const fn default_block_cache_size_mb() -> usize {
-        128
+    const fn default_block_cache_size_mb() -> SizeMb {
+        SizeMb::from_mb(128)
     }
 
-    const fn default_memtable_capacity_mb() -> usize {
-        256
+    const fn default_memtable_capacity_mb() -> SizeMb {
+        SizeMb::from_mb(256)
     }
 
-    const fn default_stalled_writes_timeout_sec() -> u64 {
-        30
+    const fn default_stalled_writes_timeout_sec() -> DurationSecs {
+        DurationSecs::from_secs(30)
     }
 
     const fn default_max_l1_batches_per_iter() -> usize {
@@ -72,23 +82,56 @@ impl MerkleTreeConfig {
 
     /// Returns the size of block cache size for Merkle tree in bytes.
     pub fn block_cache_size(&self) -> usize {
-        self.block_cache_size_mb * BYTES_IN_MEGABYTE
+        self.block_cache_size_mb.bytes() as usize
     }
 
     /// Returns the memtable capacity in bytes.
     pub fn memtable_capacity(&self) -> usize {
-        self.memtable_capacity_mb * BYTES_IN_MEGABYTE
+        self.memtable_capacity_mb.bytes() as usize
     }
 
     /// Returns the timeout to wait for the Merkle tree database to run compaction on stalled writes.
     pub fn stalled_writes_timeout(&self) -> Duration {
-        Duration::from_secs(self.stalled_writes_timeout_sec)
+        self.stalled_writes_timeout_sec.get()
+    }
+
+    pub fn io_stats_interval(&self) -> Option<Duration> {
+        self.io_stats_interval_sec.map(DurationSecs::get)
+    }
+
+    pub fn prefetch_enabled(&self) -> bool {
+        self.prefetch_enabled.unwrap_or(false)
+    }
+
+    pub fn validate(&self, validator: &mut ConfigValidator) {
+        validator.check(
+            self.multi_get_chunk_size > 0,
+            "merkle_tree.multi_get_chunk_size",
+            "must be positive",
+        );
+        validator.check(
+            self.max_l1_batches_per_iter > 0,
+            "merkle_tree.max_l1_batches_per_iter",
+            "must be positive",
+        );
+        if let Some(interval) = self.io_stats_interval() {
+            validator.check_range(
+                interval,
+                MIN_DURATION..,
+                "merkle_tree.io_stats_interval_sec",
+            );
+        }
+        validator.check_range(
+            self.block_cache_size(),
+            1..,
+            "merkle_tree.block_cache_size_mb",
+        );
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DBConfig {
-    pub statement_timeout_sec: Option<u64>,
+    pub statement_timeout_sec: Option<DurationSecs>,
     #[serde(default = "DBConfig::default_sequencer_db_path")]
     pub sequencer_db_path: String,
     #[serde(skip)]
@@ -96,7 +139,31 @@ pub struct DBConfig {
     #[serde(default = "DBConfig::default_backup_count")]
     pub backup_count: usize,
     #[serde(default = "DBConfig::default_backup_interval_ms")]
-    pub backup_interval_ms: u64,
+    pub backup_interval_ms: DurationMs,
+    /// Number of latest L1 batches whose transactions are kept in Postgres; transactions of older
+    /// batches are moved to the object store. If not set, transactions are never archived.
+    pub tx_archive_retention_l1_batches: Option<u32>,
+    #[serde(default = "DBConfig::default_tx_archive_interval_sec")]
+    pub tx_archive_interval_sec: DurationSecs,
+    /// Interval between reports of RocksDB IO statistics of the sequencer DB. If not set,
+    /// the statistics are not collected.
+    pub sequencer_io_stats_interval_sec: Option<DurationSecs>,
+    /// L1 batch of the state snapshot in the object store used to initialize an empty sequencer
+    /// DB. If not set, the sequencer DB is populated by replaying all L1 batches from Postgres.
+    pub sequencer_snapshot_l1_batch: Option<u32>,
+    /// File to which audit log entries are appended as JSON lines, in addition to Postgres.
+    pub audit_log_path: Option<String>,
+    /// Interval between checks of invariants across Postgres tables. The first check is performed
+    /// on startup.
+    #[serde(default = "DBConfig::default_consistency_check_interval_sec")]
+    pub consistency_check_interval_sec: DurationSecs,
+    /// Whether the consistency checker repairs data derived from other tables (e.g., transaction
+    /// counts of miniblocks). Disabled by default: repairs update whole tables on every check.
+    /// Violations of core invariants are only reported.
+    pub consistency_repair_enabled: Option<bool>,
+    /// Whether to compare the Postgres schema with the one expected by the binary on startup,
+    /// and refuse to start if they differ.
+    pub schema_check_enabled: Option<bool>,
 }
 
 impl DBConfig {
@@ -108,8 +175,16 @@ impl DBConfig {
         5
     }
 
-    const fn default_backup_interval_ms() -> u64 {
-        60_000
+    const fn default_backup_interval_ms() -> DurationMs {
+        DurationMs::from_millis(60_000)
+    }
+
+    const fn default_tx_archive_interval_sec() -> DurationSecs {
+        DurationSecs::from_secs(60)
+    }
+
+    const fn default_consistency_check_interval_sec() -> DurationSecs {
+        DurationSecs::from_secs(3_600)
     }
 
     pub fn from_env() -> Self {
@@ -120,11 +195,68 @@ impl DBConfig {
     }
 
     pub fn statement_timeout(&self) -> Option<Duration> {
-        self.statement_timeout_sec.map(Duration::from_secs)
+        self.statement_timeout_sec.map(DurationSecs::get)
     }
 
     pub fn backup_interval(&self) -> Duration {
-        Duration::from_millis(self.backup_interval_ms)
+        self.backup_interval_ms.get()
+    }
+
+    pub fn sequencer_io_stats_interval(&self) -> Option<Duration> {
+        self.sequencer_io_stats_interval_sec.map(DurationSecs::get)
+    }
+
+    /// Returns the interval between checks for L1 batches to archive.
+    pub fn tx_archive_interval(&self) -> Duration {
+        self.tx_archive_interval_sec.get()
+    }
+
+    pub fn consistency_check_interval(&self) -> Duration {
+        self.consistency_check_interval_sec.get()
+    }
+
+    pub fn consistency_repair_enabled(&self) -> bool {
+        self.consistency_repair_enabled.unwrap_or(false)
+    }
+
+    pub fn schema_check_enabled(&self) -> bool {
+        self.schema_check_enabled.unwrap_or(true)
+    }
+
+    pub fn validate(&self, validator: &mut ConfigValidator) {
+        validator.check(
+            self.sequencer_db_path != self.merkle_tree.path,
+            "database.sequencer_db_path",
+            "must differ from merkle_tree.path",
+        );
+        validator.check(
+            self.backup_count > 0,
+            "database.backup_count",
+            "must be positive",
+        );
+        validator.check(
+            self.tx_archive_retention_l1_batches != Some(0),
+            "database.tx_archive_retention_l1_batches",
+            "must be positive if set",
+        );
+        validator.check_range(
+            self.tx_archive_interval(),
+            MIN_DURATION..,
+            "database.tx_archive_interval_sec",
+        );
+        validator.check_range(
+            self.consistency_check_interval(),
+            MIN_DURATION..,
+            "database.consistency_check_interval_sec",
+        );
+        if let Some(interval) = self.sequencer_io_stats_interval() {
+            validator.check_range(
+                interval,
+                MIN_DURATION..,
+                "database.sequencer_io_stats_interval_sec",
+            );
+        }
+        self.merkle_tree.validate(validator);
     }
 }
 
@@ -142,7 +274,10 @@ pub fn load_merkle_tree_config() -> Result<MerkleTreeConfig, config::ConfigError
 
 #[cfg(test)]
 mod tests {
-    use crate::utils::tests::EnvMutex;
+    use crate::{
+        units::{DurationMs, DurationSecs, SizeMb},
+        utils::tests::EnvMutex,
+    };
 
     use super::{load_db_config, DBConfig, MerkleTreeConfig};
 
@@ -150,19 +285,29 @@ mod tests {
 
     fn default_db_config() -> DBConfig {
         DBConfig {
-            statement_timeout_sec: Some(300),
+            statement_timeout_sec: Some(DurationSecs::from_secs(300)),
             sequencer_db_path: "./db/main/sequencer".to_string(),
             merkle_tree: MerkleTreeConfig {
                 path: "./db/main/tree".to_string(),
                 mode: Default::default(),
                 multi_get_chunk_size: 500,
-                block_cache_size_mb: 128,
-                memtable_capacity_mb: 256,
-                stalled_writes_timeout_sec: 30,
+                block_cache_size_mb: SizeMb::from_mb(128),
+                memtable_capacity_mb: SizeMb::from_mb(256),
+                stalled_writes_timeout_sec: DurationSecs::from_secs(30),
                 max_l1_batches_per_iter: 50,
+                io_stats_interval_sec: None,
+                prefetch_enabled: None,
             },
             backup_count: 5,
-            backup_interval_ms: 60000,
+            backup_interval_ms: DurationMs::from_millis(60_000),
+            tx_archive_retention_l1_batches: Some(10000),
+            tx_archive_interval_sec: DurationSecs::from_secs(60),
+            sequencer_io_stats_interval_sec: Some(DurationSecs::from_secs(30)),
+            sequencer_snapshot_l1_batch: Some(1000),
+            audit_log_path: Some("./db/main/audit.log".to_string()),
+            consistency_check_interval_sec: DurationSecs::from_secs(600),
+            consistency_repair_enabled: Some(false),
+            schema_check_enabled: Some(false),
         }
     }
 
@@ -173,7 +318,14 @@ mod tests {
             OLAOS_DATABASE_STATEMENT_TIMEOUT_SEC=300
             OLAOS_DATABASE_SEQUENCER_DB_PATH=./db/main/sequencer
             OLAOS_DATABASE_BACKUP_COUNT=5
-            OLAOS_DATABASE_BACKUP_INTERVAL_MS=60000
+            OLAOS_DATABASE_BACKUP_INTERVAL_MS=1m
+            OLAOS_DATABASE_TX_ARCHIVE_RETENTION_L1_BATCHES=10000
+            OLAOS_DATABASE_SEQUENCER_IO_STATS_INTERVAL_SEC=30
+            OLAOS_DATABASE_SEQUENCER_SNAPSHOT_L1_BATCH=1000
+            OLAOS_DATABASE_AUDIT_LOG_PATH=./db/main/audit.log
+            OLAOS_DATABASE_CONSISTENCY_CHECK_INTERVAL_SEC=10m
+            OLAOS_DATABASE_CONSISTENCY_REPAIR_ENABLED=false
+            OLAOS_DATABASE_SCHEMA_CHECK_ENABLED=false
         "#;
         lock.set_env(config);
 
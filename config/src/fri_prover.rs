@@ -2,13 +2,13 @@ use std::time::Duration;
 
 use serde::Deserialize;
 
-use crate::load_config;
+use crate::{load_config, units::DurationSecs};
 
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct FriProverConfig {
     // pub setup_data_path: String,
     pub max_attempts: u32,
-    pub generation_timeout_in_secs: u16,
+    pub generation_timeout_in_secs: DurationSecs,
     // pub base_layer_circuit_ids_to_be_verified: Vec<u8>,
     // pub recursive_layer_circuit_ids_to_be_verified: Vec<u8>,
     // pub setup_load_mode: SetupLoadMode,
@@ -19,11 +19,17 @@ pub struct FriProverConfig {
 
     // whether to write to public GCS bucket for https://github.com/matter-labs/era-boojum-validator-cli
     pub shall_save_to_public_bucket: bool,
+    /// Number of upcoming jobs whose circuits are fetched in the background. 0 disables prefetching.
+    pub prefetch_queue_capacity: Option<usize>,
 }
 
 impl FriProverConfig {
     pub fn proof_generation_timeout(&self) -> Duration {
-        Duration::from_secs(self.generation_timeout_in_secs as u64)
+        self.generation_timeout_in_secs.get()
+    }
+
+    pub fn prefetch_queue_capacity(&self) -> usize {
+        self.prefetch_queue_capacity.unwrap_or(1)
     }
 }
 
@@ -33,7 +39,7 @@ pub fn load_prover_fri_config() -> Result<FriProverConfig, config::ConfigError>
 
 #[cfg(test)]
 mod tests {
-    use crate::{fri_prover::load_prover_fri_config, utils::tests::EnvMutex};
+    use crate::{fri_prover::load_prover_fri_config, units::DurationSecs, utils::tests::EnvMutex};
 
     use super::FriProverConfig;
 
@@ -42,11 +48,30 @@ mod tests {
     fn default_fri_prover_config() -> FriProverConfig {
         FriProverConfig {
             max_attempts: 10,
-            generation_timeout_in_secs: 300,
+            generation_timeout_in_secs: DurationSecs::from_secs(300),
             shall_save_to_public_bucket: true,
+            prefetch_queue_capacity: Some(2),
         }
     }
 
+    #[test]
+    fn base_config_file_is_loaded() {
+        let base_path = concat!(env!("CARGO_MANIFEST_DIR"), "/configuration/fri_prover/base");
+        let config: FriProverConfig = config::Config::default()
+            .merge(config::File::with_name(base_path))
+            .unwrap()
+            .clone()
+            .try_into()
+            .unwrap();
+        assert_eq!(
+            config,
+            FriProverConfig {
+                prefetch_queue_capacity: Some(1),
+                ..default_fri_prover_config()
+            }
+        );
+    }
+
     #[test]
     fn test_load_fri_prover_gateway_config() {
         let mut lock = MUTEX.lock();
@@ -54,6 +79,7 @@ mod tests {
         OLAOS_FRI_PROVER_GENERATION_TIMEOUT_IN_SECS=300
         OLAOS_FRI_PROVER_MAX_ATTEMPTS=10
         OLAOS_FRI_PROVER_SHALL_SAVE_TO_PUBLIC_BUCKET=true
+        OLAOS_FRI_PROVER_PREFETCH_QUEUE_CAPACITY=2
         "#;
         lock.set_env(config);
 
@@ -2,17 +2,40 @@ use std::time::Duration;
 
 use serde::Deserialize;
 
-use crate::load_config;
+use crate::{load_config, units::DurationSecs};
 
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct FriProverGatewayConfig {
     pub api_url: String,
-    pub api_poll_duration_secs: u16,
+    pub api_poll_duration_secs: DurationSecs,
+    /// How long jobs in a terminal state are kept in `prover_jobs_fri` before being
+    /// moved to the archive table.
+    #[serde(default = "FriProverGatewayConfig::default_job_history_retention_hours")]
+    pub job_history_retention_hours: u32,
+    /// Interval between archival runs, which also refresh the prover job metrics.
+    #[serde(default = "FriProverGatewayConfig::default_job_history_pruning_interval_secs")]
+    pub job_history_pruning_interval_secs: DurationSecs,
 }
 
 impl FriProverGatewayConfig {
+    const fn default_job_history_retention_hours() -> u32 {
+        72
+    }
+
+    const fn default_job_history_pruning_interval_secs() -> DurationSecs {
+        DurationSecs::from_secs(600)
+    }
+
     pub fn api_poll_duration(&self) -> Duration {
-        Duration::from_secs(self.api_poll_duration_secs as u64)
+        self.api_poll_duration_secs.get()
+    }
+
+    pub fn job_history_retention(&self) -> Duration {
+        Duration::from_secs(self.job_history_retention_hours as u64 * 3600)
+    }
+
+    pub fn job_history_pruning_interval(&self) -> Duration {
+        self.job_history_pruning_interval_secs.get()
     }
 }
 
@@ -25,7 +48,10 @@ pub fn load_prover_fri_gateway_config() -> Result<FriProverGatewayConfig, config
 
 #[cfg(test)]
 mod tests {
-    use crate::{fri_prover_gateway::load_prover_fri_gateway_config, utils::tests::EnvMutex};
+    use crate::{
+        fri_prover_gateway::load_prover_fri_gateway_config, units::DurationSecs,
+        utils::tests::EnvMutex,
+    };
 
     use super::FriProverGatewayConfig;
 
@@ -34,7 +60,9 @@ mod tests {
     fn default_fri_prover_gateway_config() -> FriProverGatewayConfig {
         FriProverGatewayConfig {
             api_url: "http://private-dns-for-server".to_string(),
-            api_poll_duration_secs: 100,
+            api_poll_duration_secs: DurationSecs::from_secs(100),
+            job_history_retention_hours: 48,
+            job_history_pruning_interval_secs: DurationSecs::from_secs(300),
         }
     }
 
@@ -44,6 +72,8 @@ mod tests {
         let config = r#"
             OLAOS_FRI_PROVER_GATEWAY_API_URL="http://private-dns-for-server"
             OLAOS_FRI_PROVER_GATEWAY_API_POLL_DURATION_SECS="100"
+            OLAOS_FRI_PROVER_GATEWAY_JOB_HISTORY_RETENTION_HOURS="48"
+            OLAOS_FRI_PROVER_GATEWAY_JOB_HISTORY_PRUNING_INTERVAL_SECS="5m"
         "#;
         lock.set_env(config);
 
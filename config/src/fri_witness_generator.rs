@@ -4,13 +4,16 @@ use std::{string, time::Duration};
 // External uses
 use serde::{Deserialize, Deserializer};
 
-use crate::load_config;
+use crate::{
+    load_config,
+    units::{DurationSecs, SizeMb},
+};
 
 /// Configuration for the fri witness generation
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct FriWitnessGeneratorConfig {
     /// Max time for witness to be generated
-    pub generation_timeout_in_secs: u16,
+    pub generation_timeout_in_secs: DurationSecs,
     /// Max attempts for generating witness
     pub max_attempts: u32,
     // Percentage of the blocks that gets proven in the range [0.0, 1.0]
@@ -28,15 +31,44 @@ pub struct FriWitnessGeneratorConfig {
 
     // whether to write to public GCS bucket for https://github.com/matter-labs/era-boojum-validator-cli
     pub shall_save_to_public_bucket: bool,
+
+    /// Max number of L1 batches processed concurrently. Defaults to 1.
+    pub max_concurrent_jobs: Option<usize>,
+    /// Total memory budget of concurrently processed jobs, in MB. Jobs which don't fit
+    /// wait for the running ones to finish. No limit is applied if not set.
+    pub memory_budget_mb: Option<SizeMb>,
+    /// Estimated memory used per storage log of the processed L1 batch, in bytes.
+    pub memory_per_storage_log_bytes: Option<u64>,
 }
 impl FriWitnessGeneratorConfig {
     pub fn witness_generation_timeout(&self) -> Duration {
-        Duration::from_secs(self.generation_timeout_in_secs as u64)
+        self.generation_timeout_in_secs.get()
     }
 
     pub fn last_l1_batch_to_process(&self) -> u32 {
         self.last_l1_batch_to_process.unwrap_or(u32::MAX)
     }
+
+    pub fn max_concurrent_jobs(&self) -> usize {
+        self.max_concurrent_jobs.unwrap_or(1).max(1)
+    }
+
+    pub fn memory_budget_bytes(&self) -> Option<u64> {
+        self.memory_budget_mb.map(SizeMb::bytes)
+    }
+
+    /// Estimates memory required to generate witness for an L1 batch with
+    /// `storage_log_count` storage logs.
+    pub fn estimated_job_memory_bytes(&self, storage_log_count: u64) -> u64 {
+        const BASE_JOB_MEMORY_BYTES: u64 = 64 << 20;
+        // Each log carries a Merkle path of 256 hashes plus its VM trace.
+        const DEFAULT_MEMORY_PER_STORAGE_LOG_BYTES: u64 = 16 << 10;
+
+        let per_log = self
+            .memory_per_storage_log_bytes
+            .unwrap_or(DEFAULT_MEMORY_PER_STORAGE_LOG_BYTES);
+        BASE_JOB_MEMORY_BYTES.saturating_add(storage_log_count.saturating_mul(per_log))
+    }
 }
 
 pub fn load_fri_witness_generator_config() -> Result<FriWitnessGeneratorConfig, config::ConfigError>
@@ -60,7 +92,11 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::{fri_witness_generator::load_fri_witness_generator_config, utils::tests::EnvMutex};
+    use crate::{
+        fri_witness_generator::load_fri_witness_generator_config,
+        units::{DurationSecs, SizeMb},
+        utils::tests::EnvMutex,
+    };
 
     use super::FriWitnessGeneratorConfig;
 
@@ -68,13 +104,16 @@ mod tests {
 
     fn default_fri_witness_generator_config() -> FriWitnessGeneratorConfig {
         FriWitnessGeneratorConfig {
-            generation_timeout_in_secs: 900u16,
+            generation_timeout_in_secs: DurationSecs::from_secs(900),
             max_attempts: 4,
             blocks_proving_percentage: Some(30),
             dump_arguments_for_blocks: vec![2, 3],
             last_l1_batch_to_process: None,
             force_process_block: Some(1),
             shall_save_to_public_bucket: true,
+            max_concurrent_jobs: Some(4),
+            memory_budget_mb: Some(SizeMb::from_mb(16384)),
+            memory_per_storage_log_bytes: None,
         }
     }
 
@@ -88,11 +127,19 @@ mod tests {
         OLAOS_FRI_WITNESS_BLOCKS_PROVING_PERCENTAGE="30"
         OLAOS_FRI_WITNESS_FORCE_PROCESS_BLOCK="1"
         OLAOS_FRI_WITNESS_SHALL_SAVE_TO_PUBLIC_BUCKET=true
+        OLAOS_FRI_WITNESS_MAX_CONCURRENT_JOBS=4
+        OLAOS_FRI_WITNESS_MEMORY_BUDGET_MB=16GiB
         "#;
         lock.set_env(config);
 
         let config =
             load_fri_witness_generator_config().expect("failed to load fri prover gateway config");
         assert_eq!(config, default_fri_witness_generator_config());
+        assert_eq!(config.max_concurrent_jobs(), 4);
+        assert_eq!(config.memory_budget_bytes(), Some(16 << 30));
+        assert_eq!(
+            config.estimated_job_memory_bytes(1_000),
+            (64 << 20) + 1_000 * (16 << 10)
+        );
     }
 }
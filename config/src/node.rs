@@ -0,0 +1,158 @@
+//! Aggregated configuration of the main node, used to validate configs against each other.
+
+use std::collections::HashMap;
+
+use crate::{
+    api::{load_api_config, ApiConfig},
+    bootstrap::applied_fingerprint,
+    chain::{
+        load_mempool_config, load_operation_manager_config, MempoolConfig, OperationsManagerConfig,
+    },
+    database::{load_db_config, DBConfig},
+    dump::ConfigDump,
+    offchain_verifier::{load_offchain_verifier_config, OffChainVerifierConfig},
+    proof_data_handler::{load_proof_data_handler_config, ProofDataHandlerConfig},
+    sequencer::{load_network_config, load_sequencer_config, NetworkConfig, SequencerConfig},
+    validation::{ConfigValidationError, ConfigValidator},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub api: ApiConfig,
+    pub sequencer: SequencerConfig,
+    pub network: NetworkConfig,
+    pub mempool: MempoolConfig,
+    pub db: DBConfig,
+    pub operations_manager: OperationsManagerConfig,
+    pub proof_data_handler: ProofDataHandlerConfig,
+    pub offchain_verifier: OffChainVerifierConfig,
+}
+
+impl Config {
+    pub fn load() -> Result<Self, config::ConfigError> {
+        Ok(Self {
+            api: load_api_config()?,
+            sequencer: load_sequencer_config()?,
+            network: load_network_config()?,
+            mempool: load_mempool_config()?,
+            db: load_db_config()?,
+            operations_manager: load_operation_manager_config()?,
+            proof_data_handler: load_proof_data_handler_config()?,
+            offchain_verifier: load_offchain_verifier_config()?,
+        })
+    }
+
+    /// Dumps values of all configs together with their sources, redacting secrets.
+    pub fn dump(&self) -> Result<ConfigDump, config::ConfigError> {
+        let mut dump = ConfigDump::new();
+        dump.add_component(
+            "web3_json_rpc",
+            "configuration/web3_json_rpc",
+            "OLAOS_WEB3_JSON_RPC",
+            &self.api.web3_json_rpc,
+        )?;
+        dump.add_component(
+            "healthcheck",
+            "configuration/health_check",
+            "OLAOS_HEALTHCHECK",
+            &self.api.healthcheck,
+        )?;
+        dump.add_component(
+            "sequencer",
+            "configuration/sequencer",
+            "OLAOS_SEQUENCER",
+            &self.sequencer,
+        )?;
+        dump.add_component(
+            "network",
+            "configuration/network",
+            "OLAOS_NETWORK",
+            &self.network,
+        )?;
+        dump.add_component(
+            "mempool",
+            "configuration/mempool",
+            "OLAOS_MEMPOOL",
+            &self.mempool,
+        )?;
+        dump.add_component(
+            "database",
+            "configuration/database",
+            "OLAOS_DATABASE",
+            &self.db,
+        )?;
+        dump.add_component(
+            "merkle_tree",
+            "configuration/merkle_tree",
+            "OLAOS_MERKLE_TREE",
+            &self.db.merkle_tree,
+        )?;
+        dump.add_component(
+            "operations_manager",
+            "configuration/operation_manager",
+            "OLAOS_OPERATIONS_MANAGER",
+            &self.operations_manager,
+        )?;
+        dump.add_component(
+            "proof_data_handler",
+            "configuration/proof_data_handler",
+            "OLAOS_PROOF_DATA_HANDLER",
+            &self.proof_data_handler,
+        )?;
+        dump.add_component(
+            "offchain_verifier",
+            "configuration/offchain_verifier",
+            "OLAOS_OFFCHAIN_VERIFIER",
+            &self.offchain_verifier,
+        )?;
+        dump.bootstrap = applied_fingerprint();
+        Ok(dump)
+    }
+
+    /// Checks all configs and their combinations, returning every violation found.
+    pub fn validate(&self) -> Result<(), ConfigValidationError> {
+        let mut validator = ConfigValidator::new();
+        self.validate_into(&mut validator);
+        validator.finish()
+    }
+
+    /// Same as [`Self::validate()`], but records violations into an existing `validator`,
+    /// so that callers can add their own checks (e.g., depending on the enabled components).
+    pub fn validate_into(&self, validator: &mut ConfigValidator) {
+        self.api.web3_json_rpc.validate(validator);
+        self.sequencer.validate(validator);
+        self.mempool.validate(validator);
+        self.db.validate(validator);
+        self.proof_data_handler.validate(validator);
+        self.offchain_verifier.validate(validator);
+
+        validator.check(
+            self.operations_manager.delay_interval > 0,
+            "operations_manager.delay_interval",
+            "must be positive",
+        );
+
+        let ports = [
+            ("web3_json_rpc.http_port", self.api.web3_json_rpc.http_port),
+            ("healthcheck.port", self.api.healthcheck.port),
+            (
+                "proof_data_handler.http_port",
+                self.proof_data_handler.http_port,
+            ),
+            ("offchain_verifier.port", self.offchain_verifier.port),
+        ];
+        let mut port_owners = HashMap::with_capacity(ports.len());
+        for (path, port) in ports {
+            match port_owners.get(&port) {
+                Some(owner) => validator.check(
+                    false,
+                    path,
+                    format!("port {port} is already used by {owner}"),
+                ),
+                None => {
+                    port_owners.insert(port, path);
+                }
+            }
+        }
+    }
+}
@@ -1,18 +1,85 @@
-use std::net::SocketAddr;
+use std::{net::SocketAddr, str::FromStr};
 
-use serde::Deserialize;
+use ola_basic_types::Address;
+use serde::{Deserialize, Serialize};
 
-use crate::load_config;
+use crate::{load_config, validation::ConfigValidator};
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct OffChainVerifierConfig {
     pub port: u16,
+    /// Comma-separated addresses of the verifier committee members whose attestations are
+    /// counted towards the quorum.
+    pub verifier_addresses: Option<String>,
+    /// Number of matching attestations required to mark an L1 batch as verified. Defaults to
+    /// a simple majority of the committee; must be a majority, so that the committee can't reach
+    /// the quorum on both outcomes.
+    pub quorum_threshold: Option<usize>,
+    /// Whether the pubsub API streams verification tasks and accepts attestations via
+    /// the `verifier` namespace. Disabled by default.
+    pub attestations_enabled: Option<bool>,
 }
 
 impl OffChainVerifierConfig {
     pub fn bind_addr(&self) -> SocketAddr {
         SocketAddr::new("0.0.0.0".parse().unwrap(), self.port)
     }
+
+    /// Returns the committee members. Unparsable addresses are reported by [`Self::validate()`].
+    pub fn verifier_addresses(&self) -> Vec<Address> {
+        self.verifier_addresses
+            .as_deref()
+            .map(|addresses| {
+                split_addresses(addresses)
+                    .filter_map(|address| Address::from_str(address).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn quorum_threshold(&self) -> usize {
+        self.quorum_threshold
+            .unwrap_or_else(|| self.verifier_addresses().len() / 2 + 1)
+    }
+
+    pub fn attestations_enabled(&self) -> bool {
+        self.attestations_enabled.unwrap_or(false)
+    }
+
+    pub fn validate(&self, validator: &mut ConfigValidator) {
+        let addresses = self.verifier_addresses.as_deref().unwrap_or_default();
+        for address in split_addresses(addresses) {
+            validator.check(
+                Address::from_str(address).is_ok(),
+                "offchain_verifier.verifier_addresses",
+                format!("`{address}` is not a valid address"),
+            );
+        }
+        let committee_size = self.verifier_addresses().len();
+        if self.attestations_enabled() {
+            validator.check(
+                committee_size > 0,
+                "offchain_verifier.verifier_addresses",
+                "must not be empty if attestations are enabled",
+            );
+        }
+        if committee_size > 0 {
+            validator.check(
+                (committee_size / 2 + 1..=committee_size).contains(&self.quorum_threshold()),
+                "offchain_verifier.quorum_threshold",
+                format!(
+                    "must be a majority of the committee and not exceed its size ({committee_size})"
+                ),
+            );
+        }
+    }
+}
+
+fn split_addresses(addresses: &str) -> impl Iterator<Item = &str> {
+    addresses
+        .split(',')
+        .map(str::trim)
+        .filter(|address| !address.is_empty())
 }
 
 pub fn load_offchain_verifier_config() -> Result<OffChainVerifierConfig, config::ConfigError> {
@@ -23,12 +90,22 @@ pub fn load_offchain_verifier_config() -> Result<OffChainVerifierConfig, config:
 mod tests {
     use crate::utils::tests::EnvMutex;
 
-    use super::{load_offchain_verifier_config, OffChainVerifierConfig};
+    use super::*;
 
     static MUTEX: EnvMutex = EnvMutex::new();
 
     fn default_offchain_verifier_config() -> OffChainVerifierConfig {
-        OffChainVerifierConfig { port: 13003 }
+        OffChainVerifierConfig {
+            port: 13003,
+            verifier_addresses: Some(
+                "0x0000000000000000000000000000000000000000000000000000000000000001, \
+                 0x0000000000000000000000000000000000000000000000000000000000000002, \
+                 0x0000000000000000000000000000000000000000000000000000000000000003"
+                    .to_string(),
+            ),
+            quorum_threshold: None,
+            attestations_enabled: Some(true),
+        }
     }
 
     #[test]
@@ -36,11 +113,51 @@ mod tests {
         let mut lock = MUTEX.lock();
         let config = r#"
             OLAOS_OFFCHAIN_VERIFIER_PORT="13003"
+            OLAOS_OFFCHAIN_VERIFIER_VERIFIER_ADDRESSES="0x0000000000000000000000000000000000000000000000000000000000000001, 0x0000000000000000000000000000000000000000000000000000000000000002, 0x0000000000000000000000000000000000000000000000000000000000000003"
+            OLAOS_OFFCHAIN_VERIFIER_ATTESTATIONS_ENABLED=true
         "#;
         lock.set_env(config);
 
         let config =
             load_offchain_verifier_config().expect("failed to load offchain verifier config");
         assert_eq!(config, default_offchain_verifier_config());
+        assert_eq!(config.verifier_addresses().len(), 3);
+        assert_eq!(config.quorum_threshold(), 2);
+    }
+
+    #[test]
+    fn validating_committee() {
+        let mut config = default_offchain_verifier_config();
+        let mut validator = ConfigValidator::new();
+        config.validate(&mut validator);
+        validator.finish().unwrap();
+
+        // A minority of the committee could reach the quorum on both outcomes.
+        config.quorum_threshold = Some(1);
+        let mut validator = ConfigValidator::new();
+        config.validate(&mut validator);
+        let err = validator.finish().unwrap_err().to_string();
+        assert!(err.contains("offchain_verifier.quorum_threshold"), "{err}");
+
+        config.verifier_addresses = None;
+        config.quorum_threshold = None;
+        let mut validator = ConfigValidator::new();
+        config.validate(&mut validator);
+        let err = validator.finish().unwrap_err().to_string();
+        assert!(
+            err.contains("offchain_verifier.verifier_addresses"),
+            "{err}"
+        );
+
+        config.quorum_threshold = Some(4);
+        config.verifier_addresses = Some(
+            "0x0000000000000000000000000000000000000000000000000000000000000001, not_an_address"
+                .to_string(),
+        );
+        let mut validator = ConfigValidator::new();
+        config.validate(&mut validator);
+        let err = validator.finish().unwrap_err().to_string();
+        assert!(err.contains("not_an_address"), "{err}");
+        assert!(err.contains("offchain_verifier.quorum_threshold"), "{err}");
     }
 }
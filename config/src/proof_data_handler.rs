@@ -1,25 +1,69 @@
 use std::time::Duration;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::load_config;
+use crate::{
+    load_config,
+    units::DurationSecs,
+    validation::{ConfigValidator, MIN_DURATION},
+};
 
-#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub enum ProtocolVersionLoadingMode {
     FromDb,
     FromEnvVar,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ProofDataHandlerConfig {
     pub http_port: u16,
-    pub proof_generation_timeout_in_secs: u16,
+    pub proof_generation_timeout_in_secs: DurationSecs,
     pub protocol_version_loading_mode: ProtocolVersionLoadingMode,
     pub fri_protocol_version_id: u16,
+    /// Whether to generate and verify a reduced-size proof of each sealed L1 batch in-process.
+    /// Intended for devnets; not allowed on mainnet.
+    pub proof_smoke_test_enabled: Option<bool>,
+    /// Interval between checks for newly sealed L1 batches by the proof smoke test.
+    #[serde(default = "ProofDataHandlerConfig::default_proof_smoke_test_interval_sec")]
+    pub proof_smoke_test_interval_sec: DurationSecs,
+    /// Number of FRI query rounds in proofs generated by the smoke test. Proofs with fewer rounds
+    /// are faster to generate, but aren't sound enough to be used outside of tests.
+    #[serde(default = "ProofDataHandlerConfig::default_proof_smoke_test_fri_query_rounds")]
+    pub proof_smoke_test_fri_query_rounds: usize,
 }
+
 impl ProofDataHandlerConfig {
+    const fn default_proof_smoke_test_interval_sec() -> DurationSecs {
+        DurationSecs::from_secs(5)
+    }
+
+    const fn default_proof_smoke_test_fri_query_rounds() -> usize {
+        2
+    }
+
     pub fn proof_generation_timeout(&self) -> Duration {
-        Duration::from_secs(self.proof_generation_timeout_in_secs as u64)
+        self.proof_generation_timeout_in_secs.get()
+    }
+
+    pub fn proof_smoke_test_enabled(&self) -> bool {
+        self.proof_smoke_test_enabled.unwrap_or(false)
+    }
+
+    pub fn proof_smoke_test_interval(&self) -> Duration {
+        self.proof_smoke_test_interval_sec.get()
+    }
+
+    pub fn validate(&self, validator: &mut ConfigValidator) {
+        validator.check_range(
+            self.proof_smoke_test_interval(),
+            MIN_DURATION..,
+            "proof_data_handler.proof_smoke_test_interval_sec",
+        );
+        validator.check(
+            self.proof_smoke_test_fri_query_rounds > 0,
+            "proof_data_handler.proof_smoke_test_fri_query_rounds",
+            "must be positive",
+        );
     }
 }
 
@@ -32,7 +76,7 @@ pub fn load_proof_data_handler_config() -> Result<ProofDataHandlerConfig, config
 
 #[cfg(test)]
 mod tests {
-    use crate::utils::tests::EnvMutex;
+    use crate::{units::DurationSecs, utils::tests::EnvMutex};
 
     use super::{
         load_proof_data_handler_config, ProofDataHandlerConfig, ProtocolVersionLoadingMode,
@@ -43,9 +87,12 @@ mod tests {
     fn default_proof_data_handler_config() -> ProofDataHandlerConfig {
         ProofDataHandlerConfig {
             http_port: 13320,
-            proof_generation_timeout_in_secs: 18000,
+            proof_generation_timeout_in_secs: DurationSecs::from_secs(18000),
             protocol_version_loading_mode: ProtocolVersionLoadingMode::FromEnvVar,
             fri_protocol_version_id: 2,
+            proof_smoke_test_enabled: Some(true),
+            proof_smoke_test_interval_sec: DurationSecs::from_secs(10),
+            proof_smoke_test_fri_query_rounds: 2,
         }
     }
 
@@ -53,10 +100,12 @@ mod tests {
     fn test_load_object_store_config() {
         let mut lock = MUTEX.lock();
         let config = r#"
-            OLAOS_PROOF_DATA_HANDLER_PROOF_GENERATION_TIMEOUT_IN_SECS="18000"
+            OLAOS_PROOF_DATA_HANDLER_PROOF_GENERATION_TIMEOUT_IN_SECS="5h"
             OLAOS_PROOF_DATA_HANDLER_HTTP_PORT="13320"
             OLAOS_PROOF_DATA_HANDLER_PROTOCOL_VERSION_LOADING_MODE="FromEnvVar"
             OLAOS_PROOF_DATA_HANDLER_FRI_PROTOCOL_VERSION_ID="2"
+            OLAOS_PROOF_DATA_HANDLER_PROOF_SMOKE_TEST_ENABLED="true"
+            OLAOS_PROOF_DATA_HANDLER_PROOF_SMOKE_TEST_INTERVAL_SEC="10s"
         "#;
         lock.set_env(config);
 
@@ -1,12 +1,28 @@
-use crate::{envy_load, load_config};
+use std::time::Duration;
+
+use crate::{
+    envy_load, load_config,
+    units::{DurationMs, DurationSecs, SizeMb},
+    validation::{ConfigValidator, MIN_DURATION},
+};
 use ola_basic_types::{network::Network, Address, H256};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+/// Order in which the sequencer includes transactions of different accounts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TxOrderingPolicy {
+    /// In the order the transactions are received.
+    #[default]
+    Fifo,
+    /// In a pseudorandom order derived from `tx_ordering_seed`.
+    SeededShuffle,
+}
 
-#[derive(Debug, Deserialize, Clone, PartialEq, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct SequencerConfig {
     pub miniblock_seal_queue_capacity: usize,
-    pub miniblock_commit_deadline_ms: u64,
-    pub block_commit_deadline_ms: u64,
+    pub miniblock_commit_deadline_ms: DurationMs,
+    pub block_commit_deadline_ms: DurationMs,
     pub reject_tx_at_geometry_percentage: f64,
     pub close_block_at_geometry_percentage: f64,
     pub fee_account_addr: Address,
@@ -14,19 +30,167 @@ pub struct SequencerConfig {
     pub default_aa_hash: H256,
     pub transaction_slots: usize,
     pub save_call_traces: bool,
+    /// Time without a sealed miniblock while the mempool has transactions, after which
+    /// the sequencer is considered stalled.
+    pub watchdog_miniblock_stall_threshold_sec: Option<DurationSecs>,
+    /// Time without a sealed L1 batch while there are miniblocks outside sealed batches,
+    /// after which the sequencer is considered stalled.
+    pub watchdog_l1_batch_stall_threshold_sec: Option<DurationSecs>,
+    /// Whether to restart the sequencer once it's considered stalled.
+    pub watchdog_restart_on_stall: Option<bool>,
+    /// Whether the sequencer must hold the leadership lease in Postgres to run. With the election
+    /// enabled, several instances can be deployed; all but the leader stay on warm standby.
+    pub leader_election_enabled: Option<bool>,
+    /// Time after which the lease of an unresponsive leader expires and a standby takes over.
+    pub leader_lease_ttl_ms: Option<DurationMs>,
+    /// Identifier of this instance in the leadership lease. Defaults to the host name
+    /// and the process ID.
+    pub instance_id: Option<String>,
+    /// Order in which transactions of different accounts are pulled from the mempool.
+    pub tx_ordering_policy: Option<TxOrderingPolicy>,
+    /// Secret seed of the `SeededShuffle` ordering policy.
+    pub tx_ordering_seed: Option<u64>,
+    /// Maximum size of a single call output stored in call traces. Larger outputs are truncated
+    /// before being persisted.
+    pub max_stored_call_output_size_mb: Option<SizeMb>,
 }
 
 impl SequencerConfig {
     pub fn from_env() -> Self {
         envy_load("ola_sequencer", "OLAOS_SEQUENCER_")
     }
+
+    pub fn miniblock_commit_deadline(&self) -> Duration {
+        self.miniblock_commit_deadline_ms.get()
+    }
+
+    pub fn block_commit_deadline(&self) -> Duration {
+        self.block_commit_deadline_ms.get()
+    }
+
+    pub fn watchdog_miniblock_stall_threshold(&self) -> Duration {
+        self.watchdog_miniblock_stall_threshold_sec
+            .unwrap_or(DurationSecs::from_secs(60))
+            .get()
+    }
+
+    pub fn watchdog_l1_batch_stall_threshold(&self) -> Duration {
+        self.watchdog_l1_batch_stall_threshold_sec
+            .unwrap_or(DurationSecs::from_secs(600))
+            .get()
+    }
+
+    pub fn watchdog_restart_on_stall(&self) -> bool {
+        self.watchdog_restart_on_stall.unwrap_or(false)
+    }
+
+    pub fn leader_election_enabled(&self) -> bool {
+        self.leader_election_enabled.unwrap_or(false)
+    }
+
+    pub fn leader_lease_ttl(&self) -> Duration {
+        self.leader_lease_ttl_ms
+            .unwrap_or(DurationMs::from_millis(10_000))
+            .get()
+    }
+
+    /// Interval between lease renewals by the leader and lease acquisition attempts by a standby.
+    pub fn leader_lease_renew_interval(&self) -> Duration {
+        self.leader_lease_ttl() / 3
+    }
+
+    pub fn instance_id(&self) -> String {
+        self.instance_id.clone().unwrap_or_else(|| {
+            let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "localhost".to_owned());
+            format!("{host}-{}", std::process::id())
+        })
+    }
+
+    pub fn tx_ordering_policy(&self) -> TxOrderingPolicy {
+        self.tx_ordering_policy.unwrap_or_default()
+    }
+
+    pub fn max_stored_call_output_size(&self) -> usize {
+        self.max_stored_call_output_size_mb
+            .unwrap_or(SizeMb::from_mb(1))
+            .bytes() as usize
+    }
+
+    pub fn validate(&self, validator: &mut ConfigValidator) {
+        validator.check(
+            self.miniblock_seal_queue_capacity > 0,
+            "sequencer.miniblock_seal_queue_capacity",
+            "must be positive; the sequencer relies on the async miniblock sealer",
+        );
+        validator.check_range(
+            self.miniblock_commit_deadline(),
+            MIN_DURATION..,
+            "sequencer.miniblock_commit_deadline_ms",
+        );
+        validator.check_range(
+            self.block_commit_deadline(),
+            self.miniblock_commit_deadline()..,
+            "sequencer.block_commit_deadline_ms",
+        );
+        for (path, percentage) in [
+            (
+                "sequencer.reject_tx_at_geometry_percentage",
+                self.reject_tx_at_geometry_percentage,
+            ),
+            (
+                "sequencer.close_block_at_geometry_percentage",
+                self.close_block_at_geometry_percentage,
+            ),
+        ] {
+            validator.check(
+                percentage > 0.0 && percentage <= 1.0,
+                path,
+                format!("must be in (0, 1], got {percentage}"),
+            );
+        }
+        validator.check(
+            self.transaction_slots > 0,
+            "sequencer.transaction_slots",
+            "must be positive",
+        );
+        validator.check(
+            self.watchdog_miniblock_stall_threshold() > self.miniblock_commit_deadline(),
+            "sequencer.watchdog_miniblock_stall_threshold_sec",
+            format!(
+                "must be greater than miniblock_commit_deadline_ms ({})",
+                self.miniblock_commit_deadline_ms
+            ),
+        );
+        validator.check_range(
+            self.leader_lease_ttl(),
+            Duration::from_millis(300)..,
+            "sequencer.leader_lease_ttl_ms",
+        );
+        validator.check(
+            self.tx_ordering_policy() != TxOrderingPolicy::SeededShuffle
+                || self.tx_ordering_seed.is_some(),
+            "sequencer.tx_ordering_seed",
+            "must be set for the SeededShuffle ordering policy",
+        );
+        validator.check(
+            self.watchdog_l1_batch_stall_threshold() > self.block_commit_deadline(),
+            "sequencer.watchdog_l1_batch_stall_threshold_sec",
+            format!(
+                "must be greater than block_commit_deadline_ms ({})",
+                self.block_commit_deadline_ms
+            ),
+        );
+    }
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct NetworkConfig {
     pub network: Network,
     pub ola_network_id: u16,
     pub ola_network_name: String,
+    /// Path to a genesis bundle exported with `ola_node export-genesis`. If set, the genesis
+    /// L1 batch is created from the bundle state, so that the chain forks the exported one.
+    pub fork_genesis_path: Option<String>,
 }
 
 impl NetworkConfig {
@@ -51,18 +215,19 @@ mod tests {
 
     use crate::{
         sequencer::{load_network_config, load_sequencer_config},
+        units::{DurationMs, DurationSecs, SizeMb},
         utils::tests::EnvMutex,
     };
 
-    use super::{NetworkConfig, SequencerConfig};
+    use super::{NetworkConfig, SequencerConfig, TxOrderingPolicy};
 
     static MUTEX: EnvMutex = EnvMutex::new();
 
     fn default_sequencer_config() -> SequencerConfig {
         SequencerConfig {
             miniblock_seal_queue_capacity: 10,
-            miniblock_commit_deadline_ms: 1000,
-            block_commit_deadline_ms: 2500,
+            miniblock_commit_deadline_ms: DurationMs::from_millis(1000),
+            block_commit_deadline_ms: DurationMs::from_millis(2500),
             reject_tx_at_geometry_percentage: 0.3,
             close_block_at_geometry_percentage: 0.5,
             fee_account_addr: Address::from_str(
@@ -79,6 +244,15 @@ mod tests {
             .unwrap(),
             transaction_slots: 250,
             save_call_traces: true,
+            watchdog_miniblock_stall_threshold_sec: Some(DurationSecs::from_secs(120)),
+            watchdog_l1_batch_stall_threshold_sec: Some(DurationSecs::from_secs(600)),
+            watchdog_restart_on_stall: Some(false),
+            leader_election_enabled: Some(true),
+            leader_lease_ttl_ms: Some(DurationMs::from_millis(5000)),
+            instance_id: None,
+            tx_ordering_policy: Some(TxOrderingPolicy::SeededShuffle),
+            tx_ordering_seed: Some(42),
+            max_stored_call_output_size_mb: Some(SizeMb::from_mb(4)),
         }
     }
 
@@ -87,6 +261,7 @@ mod tests {
             network: Network::Localhost,
             ola_network_id: 1027,
             ola_network_name: "localhost".to_string(),
+            fork_genesis_path: Some("/etc/olaos/genesis_bundle.json".to_string()),
         }
     }
     #[test]
@@ -98,6 +273,12 @@ mod tests {
             OLAOS_SEQUENCER_DEFAULT_AA_HASH=0x0100038dc66b69be75ec31653c64cb931678299b9b659472772b2550b703f41c
             OLAOS_SEQUENCER_REJECT_TX_AT_GEOMETRY_PERCENTAGE=0.3
             OLAOS_SEQUENCER_CLOSE_BLOCK_AT_GEOMETRY_PERCENTAGE=0.5
+            OLAOS_SEQUENCER_WATCHDOG_MINIBLOCK_STALL_THRESHOLD_SEC=2m
+            OLAOS_SEQUENCER_LEADER_ELECTION_ENABLED=true
+            OLAOS_SEQUENCER_LEADER_LEASE_TTL_MS=5000
+            OLAOS_SEQUENCER_TX_ORDERING_POLICY="SeededShuffle"
+            OLAOS_SEQUENCER_TX_ORDERING_SEED=42
+            OLAOS_SEQUENCER_MAX_STORED_CALL_OUTPUT_SIZE_MB=4
         "#;
         lock.set_env(config);
 
@@ -110,6 +291,7 @@ mod tests {
         let mut lock = MUTEX.lock();
         let config = r#"
             OLAOS_NETWORK_OLA_NETWORK_ID=1027
+            OLAOS_NETWORK_FORK_GENESIS_PATH="/etc/olaos/genesis_bundle.json"
         "#;
         lock.set_env(config);
 
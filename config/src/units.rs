@@ -0,0 +1,293 @@
+//! Typed durations and byte sizes of config values.
+//!
+//! Values are parsed either from bare numbers or from human-readable strings with a unit suffix,
+//! e.g. `"500ms"`, `"2s"`, `"5m"` or `"512MiB"`. Bare numbers are interpreted in the unit implied
+//! by the name of the config field (e.g., milliseconds for `*_ms` fields, which are
+//! [`DurationMs`]), so numeric env values and config files keep working as is.
+
+use std::{fmt, str::FromStr, time::Duration};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+const BYTES_IN_KIB: u64 = 1_024;
+const BYTES_IN_MIB: u64 = 1_024 * BYTES_IN_KIB;
+const BYTES_IN_GIB: u64 = 1_024 * BYTES_IN_MIB;
+
+/// Error parsing a config value with a unit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitParseError(String);
+
+impl fmt::Display for UnitParseError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for UnitParseError {}
+
+/// Splits `value` into the integer amount and the (possibly empty) unit suffix.
+fn split_unit(value: &str) -> Result<(u64, &str), UnitParseError> {
+    let value = value.trim();
+    let unit_start = value
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(unit_start);
+    let amount = amount
+        .parse()
+        .map_err(|_| UnitParseError(format!("`{value}` doesn't start with an integer")))?;
+    Ok((amount, unit.trim()))
+}
+
+fn checked_scale(amount: u64, multiplier: u64, value: &str) -> Result<u64, UnitParseError> {
+    amount
+        .checked_mul(multiplier)
+        .ok_or_else(|| UnitParseError(format!("`{value}` is too large")))
+}
+
+/// Parses a duration; a bare number is a number of `default_unit`s, which must be
+/// a whole number of milliseconds.
+fn parse_duration(value: &str, default_unit: Duration) -> Result<Duration, UnitParseError> {
+    let (amount, unit) = split_unit(value)?;
+    let millis_in_unit = match unit {
+        "" => default_unit.as_millis() as u64,
+        "ms" => 1,
+        "s" | "sec" | "secs" => 1_000,
+        "m" | "min" | "mins" => 60_000,
+        "h" => 3_600_000,
+        _ => {
+            return Err(UnitParseError(format!(
+                "unknown duration unit in `{value}`; expected one of ms, s, m, h"
+            )))
+        }
+    };
+    Ok(Duration::from_millis(checked_scale(
+        amount,
+        millis_in_unit,
+        value,
+    )?))
+}
+
+/// Formats a duration with the largest unit representing it exactly.
+fn format_duration(duration: Duration, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let millis = duration.as_millis();
+    match millis {
+        0 => write!(formatter, "0s"),
+        _ if millis % 3_600_000 == 0 => write!(formatter, "{}h", millis / 3_600_000),
+        _ if millis % 60_000 == 0 => write!(formatter, "{}m", millis / 60_000),
+        _ if millis % 1_000 == 0 => write!(formatter, "{}s", millis / 1_000),
+        _ => write!(formatter, "{millis}ms"),
+    }
+}
+
+/// Visitor accepting both numbers (in the default unit) and strings with units.
+struct UnitVisitor<T>(std::marker::PhantomData<T>);
+
+impl<'de, T> de::Visitor<'de> for UnitVisitor<T>
+where
+    T: FromStr<Err = UnitParseError>,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a non-negative integer or a string with a unit, e.g. \"2s\"")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<T, E> {
+        value.to_string().parse().map_err(E::custom)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<T, E> {
+        let value = u64::try_from(value)
+            .map_err(|_| E::custom(format!("value {value} must not be negative")))?;
+        self.visit_u64(value)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<T, E> {
+        value.parse().map_err(E::custom)
+    }
+}
+
+macro_rules! duration_unit {
+    ($(#[$meta:meta])* $name:ident, $default_unit:expr, $constructor:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(Duration);
+
+        impl $name {
+            pub const fn $constructor(amount: u64) -> Self {
+                Self($default_unit(amount))
+            }
+
+            pub const fn get(self) -> Duration {
+                self.0
+            }
+        }
+
+        impl From<Duration> for $name {
+            fn from(duration: Duration) -> Self {
+                Self(duration)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = UnitParseError;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                parse_duration(value, $default_unit(1)).map(Self)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                format_duration(self.0, formatter)
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserializer.deserialize_any(UnitVisitor(std::marker::PhantomData))
+            }
+        }
+    };
+}
+
+duration_unit!(
+    /// Duration parsed from a bare number of milliseconds or a string with a unit.
+    DurationMs,
+    Duration::from_millis,
+    from_millis
+);
+
+duration_unit!(
+    /// Duration parsed from a bare number of seconds or a string with a unit.
+    DurationSecs,
+    Duration::from_secs,
+    from_secs
+);
+
+/// Byte size parsed from a bare number of mebibytes or a string with a unit, e.g. `"512MiB"`
+/// or `"2GiB"`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SizeMb(u64);
+
+impl SizeMb {
+    pub const fn from_mb(mb: u64) -> Self {
+        Self(mb * BYTES_IN_MIB)
+    }
+
+    /// Returns the size in bytes.
+    pub const fn bytes(self) -> u64 {
+        self.0
+    }
+}
+
+impl FromStr for SizeMb {
+    type Err = UnitParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (amount, unit) = split_unit(value)?;
+        let bytes_in_unit = match unit {
+            "" | "MiB" => BYTES_IN_MIB,
+            "B" => 1,
+            "KiB" => BYTES_IN_KIB,
+            "GiB" => BYTES_IN_GIB,
+            "KB" => 1_000,
+            "MB" => 1_000_000,
+            "GB" => 1_000_000_000,
+            _ => {
+                return Err(UnitParseError(format!(
+                    "unknown size unit in `{value}`; expected one of B, KiB, MiB, GiB, KB, MB, GB"
+                )))
+            }
+        };
+        checked_scale(amount, bytes_in_unit, value).map(Self)
+    }
+}
+
+impl fmt::Display for SizeMb {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes = self.0;
+        match bytes {
+            0 => write!(formatter, "0B"),
+            _ if bytes % BYTES_IN_GIB == 0 => write!(formatter, "{}GiB", bytes / BYTES_IN_GIB),
+            _ if bytes % BYTES_IN_MIB == 0 => write!(formatter, "{}MiB", bytes / BYTES_IN_MIB),
+            _ if bytes % BYTES_IN_KIB == 0 => write!(formatter, "{}KiB", bytes / BYTES_IN_KIB),
+            _ => write!(formatter, "{bytes}B"),
+        }
+    }
+}
+
+impl Serialize for SizeMb {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for SizeMb {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(UnitVisitor(std::marker::PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_durations() {
+        assert_eq!("250".parse(), Ok(DurationMs::from_millis(250)));
+        assert_eq!("250".parse(), Ok(DurationSecs::from_secs(250)));
+        assert_eq!("2s".parse(), Ok(DurationMs::from_millis(2_000)));
+        assert_eq!(
+            " 1500 ms ".parse(),
+            Ok(DurationSecs::from(Duration::from_millis(1_500)))
+        );
+        assert_eq!("5m".parse(), Ok(DurationSecs::from_secs(300)));
+        assert_eq!("1h".parse(), Ok(DurationMs::from_millis(3_600_000)));
+
+        assert!("2 days".parse::<DurationMs>().is_err());
+        assert!("-1s".parse::<DurationMs>().is_err());
+        assert!("1.5s".parse::<DurationMs>().is_err());
+        assert!("18446744073709551615h".parse::<DurationMs>().is_err());
+    }
+
+    #[test]
+    fn parsing_sizes() {
+        assert_eq!("128".parse(), Ok(SizeMb::from_mb(128)));
+        assert_eq!("512MiB".parse(), Ok(SizeMb::from_mb(512)));
+        assert_eq!("2GiB".parse(), Ok(SizeMb::from_mb(2_048)));
+        assert_eq!("64KiB".parse::<SizeMb>().unwrap().bytes(), 65_536);
+        assert_eq!("10MB".parse::<SizeMb>().unwrap().bytes(), 10_000_000);
+        assert!("10 mebibytes".parse::<SizeMb>().is_err());
+    }
+
+    #[test]
+    fn display_roundtrip() {
+        for value in ["0s", "250ms", "2s", "5m", "1h", "1500ms"] {
+            let duration: DurationMs = value.parse().unwrap();
+            assert_eq!(duration.to_string(), value);
+        }
+        for value in ["0B", "100B", "64KiB", "512MiB", "2GiB"] {
+            let size: SizeMb = value.parse().unwrap();
+            assert_eq!(size.to_string(), value);
+        }
+    }
+
+    #[test]
+    fn deserializing_numbers_and_strings() {
+        let duration: DurationSecs = serde_json::from_str("30").unwrap();
+        assert_eq!(duration.get(), Duration::from_secs(30));
+        let duration: DurationSecs = serde_json::from_str("\"30s\"").unwrap();
+        assert_eq!(duration.get(), Duration::from_secs(30));
+        assert!(serde_json::from_str::<DurationSecs>("-30").is_err());
+
+        let size: SizeMb = serde_json::from_str("\"1GiB\"").unwrap();
+        assert_eq!(serde_json::to_string(&size).unwrap(), "\"1GiB\"");
+    }
+}
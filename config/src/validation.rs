@@ -0,0 +1,184 @@
+//! Validation of loaded configuration values.
+//!
+//! Configs are deserialized independently, so checks are collected with a [`ConfigValidator`]
+//! and reported all at once instead of failing on the first violation.
+
+use std::{
+    error, fmt,
+    ops::{Bound, RangeBounds},
+    time::Duration,
+};
+
+/// Smallest duration accepted for intervals and timeouts that must be positive; config durations
+/// have millisecond precision.
+pub const MIN_DURATION: Duration = Duration::from_millis(1);
+
+/// A single invalid configuration value or inconsistent combination of values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigViolation {
+    /// Path to the offending value, e.g. `sequencer.transaction_slots`.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigViolation {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Error returned if the configuration has at least one violation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigValidationError {
+    pub violations: Vec<ConfigViolation>,
+}
+
+impl fmt::Display for ConfigValidationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "configuration has {} violation(s)",
+            self.violations.len()
+        )?;
+        for violation in &self.violations {
+            write!(formatter, "\n  - {violation}")?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for ConfigValidationError {}
+
+/// Accumulates violations found by config checks.
+#[derive(Debug, Default)]
+pub struct ConfigValidator {
+    violations: Vec<ConfigViolation>,
+}
+
+impl ConfigValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a violation at `path` unless `condition` holds.
+    pub fn check(&mut self, condition: bool, path: &str, message: impl Into<String>) {
+        if !condition {
+            self.violations.push(ConfigViolation {
+                path: path.to_owned(),
+                message: message.into(),
+            });
+        }
+    }
+
+    /// Records a violation at `path` unless `value` is within `range`. Used for values with units,
+    /// so that their bounds are reported uniformly, e.g. "must be at least 1ms".
+    pub fn check_range<T>(&mut self, value: T, range: impl RangeBounds<T>, path: &str)
+    where
+        T: PartialOrd + fmt::Debug,
+    {
+        if range.contains(&value) {
+            return;
+        }
+        let lower = match range.start_bound() {
+            Bound::Included(start) => Some(format!("at least {start:?}")),
+            Bound::Excluded(start) => Some(format!("greater than {start:?}")),
+            Bound::Unbounded => None,
+        };
+        let upper = match range.end_bound() {
+            Bound::Included(end) => Some(format!("at most {end:?}")),
+            Bound::Excluded(end) => Some(format!("less than {end:?}")),
+            Bound::Unbounded => None,
+        };
+        let bounds: Vec<_> = lower.into_iter().chain(upper).collect();
+        let message = format!("must be {}, got {value:?}", bounds.join(" and "));
+        self.check(false, path, message);
+    }
+
+    pub fn violations(&self) -> &[ConfigViolation] {
+        &self.violations
+    }
+
+    pub fn finish(self) -> Result<(), ConfigValidationError> {
+        if self.violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigValidationError {
+                violations: self.violations,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConfigValidator, MIN_DURATION};
+    use crate::{
+        chain::MempoolConfig,
+        sequencer::SequencerConfig,
+        units::{DurationMs, DurationSecs},
+    };
+
+    fn mempool_config() -> MempoolConfig {
+        MempoolConfig {
+            sync_interval_ms: DurationMs::from_millis(10),
+            sync_batch_size: 1000,
+            capacity: 10000,
+            stuck_tx_timeout: DurationSecs::from_secs(60),
+            remove_stuck_txs: true,
+            delay_interval: DurationMs::from_millis(100),
+            max_scheduled_transactions: None,
+        }
+    }
+
+    #[test]
+    fn valid_config_has_no_violations() {
+        let mut validator = ConfigValidator::new();
+        mempool_config().validate(&mut validator);
+        assert!(validator.finish().is_ok());
+    }
+
+    #[test]
+    fn all_violations_are_reported() {
+        let mut validator = ConfigValidator::new();
+        let mempool_config = MempoolConfig {
+            capacity: 0,
+            ..mempool_config()
+        };
+        mempool_config.validate(&mut validator);
+        SequencerConfig::default().validate(&mut validator);
+
+        let err = validator.finish().unwrap_err();
+        let paths: Vec<_> = err
+            .violations
+            .iter()
+            .map(|violation| violation.path.as_str())
+            .collect();
+        assert!(paths.contains(&"mempool.capacity"));
+        assert!(paths.contains(&"mempool.sync_batch_size"));
+        assert!(paths.contains(&"sequencer.miniblock_seal_queue_capacity"));
+        assert!(paths.contains(&"sequencer.transaction_slots"));
+    }
+
+    #[test]
+    fn range_violations_mention_bounds() {
+        let mut validator = ConfigValidator::new();
+        let mempool_config = MempoolConfig {
+            delay_interval: DurationMs::from_millis(0),
+            ..mempool_config()
+        };
+        mempool_config.validate(&mut validator);
+        validator.check_range(5_u32, 1..=3, "test.value");
+
+        let err = validator.finish().unwrap_err();
+        assert_eq!(err.violations.len(), 2);
+        assert_eq!(err.violations[0].path, "mempool.delay_interval");
+        assert_eq!(
+            err.violations[0].message,
+            format!("must be at least {MIN_DURATION:?}, got 0ns")
+        );
+        assert_eq!(
+            err.violations[1].message,
+            "must be at least 1 and at most 3, got 5"
+        );
+    }
+}
@@ -0,0 +1,41 @@
+//! Embeds system contract artifacts into the crate, together with a manifest of their SHA-256
+//! hashes, so that the node doesn't depend on artifacts present on disk at runtime.
+
+use std::{env, fmt::Write as _, fs, path::Path};
+
+use sha2::{Digest, Sha256};
+
+const ARTIFACTS_DIR: &str = "../etc/system-contracts/contracts";
+
+fn main() {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let artifacts_dir = Path::new(&manifest_dir).join(ARTIFACTS_DIR);
+    println!("cargo:rerun-if-changed={}", artifacts_dir.display());
+
+    let mut artifacts: Vec<_> = fs::read_dir(&artifacts_dir)
+        .unwrap_or_else(|err| panic!("cannot read `{}`: {err}", artifacts_dir.display()))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+        .collect();
+    artifacts.sort();
+
+    let mut code =
+        String::from("pub(crate) const EMBEDDED_SYSTEM_CONTRACTS: &[EmbeddedArtifact] = &[\n");
+    for path in &artifacts {
+        println!("cargo:rerun-if-changed={}", path.display());
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let contents = fs::read(path).unwrap();
+        let sha256 = Sha256::digest(&contents);
+        let sha256: String = sha256.iter().map(|byte| format!("{byte:02x}")).collect();
+        let path = path.canonicalize().unwrap();
+        writeln!(
+            code,
+            "    EmbeddedArtifact {{ name: {name:?}, sha256: {sha256:?}, contents: include_bytes!({path:?}) }},"
+        )
+        .unwrap();
+    }
+    code.push_str("];\n");
+
+    let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("system_contracts.rs");
+    fs::write(out_path, code).unwrap();
+}
@@ -2,11 +2,33 @@ use ola_basic_types::H256;
 use ola_utils::{bytecode::hash_bytecode, program_bytecode_to_bytes};
 use olavm_core::program::binary_program::BinaryProgram;
 use serde::{Deserialize, Serialize};
-use std::{
-    fs::File,
-    io::{BufReader, Read},
-    path::Path,
-};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// Env variable that makes system contracts load from `$OLAOS_HOME/etc/system-contracts`
+/// instead of the artifacts embedded into the binary. Only meant for developing system contracts.
+pub const DEV_SYSTEM_CONTRACTS_FROM_DISK_VAR: &str = "OLAOS_DEV_SYSTEM_CONTRACTS_FROM_DISK";
+
+/// System contract artifact embedded into the binary at build time.
+#[derive(Debug, Clone, Copy)]
+pub struct EmbeddedArtifact {
+    pub name: &'static str,
+    /// Hex-encoded SHA-256 of the artifact recorded in the build-time manifest.
+    pub sha256: &'static str,
+    pub contents: &'static [u8],
+}
+
+include!(concat!(env!("OUT_DIR"), "/system_contracts.rs"));
+
+/// Returns the manifest of system contract artifacts embedded into the binary.
+pub fn embedded_system_contracts() -> &'static [EmbeddedArtifact] {
+    EMBEDDED_SYSTEM_CONTRACTS
+}
+
+/// Whether system contracts are loaded from disk rather than from the embedded artifacts.
+pub fn system_contracts_from_disk() -> bool {
+    std::env::var(DEV_SYSTEM_CONTRACTS_FROM_DISK_VAR).map_or(false, |value| value == "true")
+}
 
 #[derive(Debug, Clone)]
 pub struct SystemContractCode {
@@ -51,7 +73,8 @@ impl BaseSystemContracts {
         BaseSystemContracts::load_with_entrypoint(entrypoint)
     }
 
-    // used for execute txs in Ola
+    // used for execute txs in Ola. Loads the artifacts embedded into the binary, unless
+    // `OLAOS_DEV_SYSTEM_CONTRACTS_FROM_DISK` is set.
     pub fn load_from_disk() -> Self {
         let (entrypoint, _) = read_proved_block_entrypoint_bytecode();
         BaseSystemContracts::load_with_entrypoint(entrypoint)
@@ -65,24 +88,58 @@ impl BaseSystemContracts {
     }
 }
 
+/// Reads a program artifact at `json_path` relative to `$OLAOS_HOME`.
 pub fn read_json_program(json_path: impl AsRef<Path>) -> (Vec<u8>, Vec<u8>) {
-    // dbg!(json_path.as_ref().to_str());
     let ola_home = std::env::var("OLAOS_HOME").unwrap_or_else(|_| ".".into());
     let bytecode_path = Path::new(&ola_home).join(json_path);
-    let file = File::open(bytecode_path).unwrap();
-    let reader = BufReader::new(file);
-    let program: BinaryProgram = serde_json::from_reader(reader).unwrap();
+    let contents = std::fs::read(&bytecode_path)
+        .unwrap_or_else(|err| panic!("failed to read `{}`: {err}", bytecode_path.display()));
+    parse_json_program(&contents, &bytecode_path.display().to_string())
+}
+
+fn parse_json_program(contents: &[u8], name: &str) -> (Vec<u8>, Vec<u8>) {
+    let program: BinaryProgram = serde_json::from_slice(contents)
+        .unwrap_or_else(|err| panic!("failed to parse contract `{name}`: {err}"));
     let json_bytes = bincode::serialize(&program).expect("failed to read system contracts");
-    let bytecode_bytes =
-        program_bytecode_to_bytes(&program.bytecode).expect("failed to load contract: {json_path}");
+    let bytecode_bytes = program_bytecode_to_bytes(&program.bytecode)
+        .unwrap_or_else(|err| panic!("failed to load contract `{name}`: {err}"));
     (json_bytes, bytecode_bytes)
 }
 
+/// Reads a system contract artifact. Artifacts are embedded into the binary, unless
+/// [`DEV_SYSTEM_CONTRACTS_FROM_DISK_VAR`] is set.
+fn read_system_contract(name: &str) -> (Vec<u8>, Vec<u8>) {
+    let embedded = EMBEDDED_SYSTEM_CONTRACTS
+        .iter()
+        .find(|artifact| artifact.name == name);
+    if system_contracts_from_disk() {
+        let path = format!("etc/system-contracts/contracts/{name}.json");
+        let ola_home = std::env::var("OLAOS_HOME").unwrap_or_else(|_| ".".into());
+        let contents = std::fs::read(Path::new(&ola_home).join(&path))
+            .unwrap_or_else(|err| panic!("failed to read `{path}`: {err}"));
+        let sha256 = hex_sha256(&contents);
+        if embedded.map_or(true, |artifact| artifact.sha256 != sha256) {
+            olaos_logs::warn!(
+                "System contract `{name}` loaded from disk differs from the embedded one \
+                 (sha256 {sha256})"
+            );
+        }
+        return parse_json_program(&contents, name);
+    }
+
+    let artifact = embedded.unwrap_or_else(|| panic!("system contract `{name}` is not embedded"));
+    parse_json_program(artifact.contents, name)
+}
+
+fn hex_sha256(contents: &[u8]) -> String {
+    Sha256::digest(contents)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
 pub fn read_entrypoint_code(entrypoint_type: &str) -> (Vec<u8>, Vec<u8>) {
-    read_json_program(format!(
-        "etc/system-contracts/contracts/{}.json",
-        entrypoint_type
-    ))
+    read_system_contract(entrypoint_type)
 }
 
 pub fn read_proved_block_entrypoint_bytecode() -> (Vec<u8>, Vec<u8>) {
@@ -90,10 +147,7 @@ pub fn read_proved_block_entrypoint_bytecode() -> (Vec<u8>, Vec<u8>) {
 }
 
 pub fn read_sys_contract_bytecode(directory: &str, name: &str) -> (Vec<u8>, Vec<u8>) {
-    read_json_program(format!(
-        "etc/system-contracts/contracts/{0}{1}.json",
-        directory, name
-    ))
+    read_system_contract(&format!("{directory}{name}"))
 }
 
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
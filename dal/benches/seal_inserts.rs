@@ -0,0 +1,266 @@
+//! Benchmarks bulk inserts performed when sealing a miniblock against row-by-row inserts.
+//!
+//! Requires a migrated Postgres database at the master database URL. Each iteration runs
+//! in a transaction that is rolled back, so the database is left intact. Run with
+//! `cargo bench -p ola_dal --bench seal_inserts`.
+
+use std::time::{Duration, Instant};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ola_dal::StorageProcessor;
+use ola_types::{
+    block::MiniblockHeader, events::VmEvent, log::StorageLog, protocol_version::ProtocolVersionId,
+    tx::IncludedTxLocation, AccountTreeId, Address, MiniblockNumber, StorageKey, H256,
+};
+use tokio::runtime::Runtime;
+
+/// Number of rows written per iteration, matching a large miniblock.
+const ROW_COUNT: usize = 100_000;
+/// Number of rows attributed to a single transaction.
+const ROWS_PER_TX: usize = 100;
+
+#[derive(Debug, Clone, Copy)]
+enum InsertMode {
+    RowByRow,
+    Bulk,
+}
+
+impl InsertMode {
+    const ALL: [Self; 2] = [Self::RowByRow, Self::Bulk];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::RowByRow => "row_by_row",
+            Self::Bulk => "bulk",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Table {
+    StorageLogs,
+    Events,
+}
+
+impl Table {
+    const ALL: [Self; 2] = [Self::StorageLogs, Self::Events];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::StorageLogs => "storage_logs",
+            Self::Events => "events",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SealData {
+    write_logs: Vec<(H256, Vec<StorageLog>)>,
+    events: Vec<(IncludedTxLocation, Vec<VmEvent>)>,
+}
+
+fn write_logs() -> Vec<(H256, Vec<StorageLog>)> {
+    (0..ROW_COUNT / ROWS_PER_TX)
+        .map(|tx_index| {
+            let logs = (0..ROWS_PER_TX).map(|i| {
+                let slot = (tx_index * ROWS_PER_TX + i) as u64;
+                let key = StorageKey::new(
+                    AccountTreeId::new(Address::repeat_byte(1)),
+                    H256::from_low_u64_be(slot),
+                );
+                StorageLog::new_write_log(key, H256::from_low_u64_be(slot + 1))
+            });
+            (H256::from_low_u64_be(tx_index as u64), logs.collect())
+        })
+        .collect()
+}
+
+fn events() -> Vec<(IncludedTxLocation, Vec<VmEvent>)> {
+    (0..ROW_COUNT / ROWS_PER_TX)
+        .map(|tx_index| {
+            let location = IncludedTxLocation {
+                tx_hash: H256::from_low_u64_be(tx_index as u64),
+                tx_index_in_miniblock: tx_index as u32,
+                tx_initiator_address: Address::repeat_byte(2),
+            };
+            let events = (0..ROWS_PER_TX).map(|i| VmEvent {
+                address: Address::repeat_byte(3),
+                indexed_topics: vec![H256::from_low_u64_be(i as u64)],
+                value: vec![0xff; 64],
+                ..VmEvent::default()
+            });
+            (location, events.collect())
+        })
+        .collect()
+}
+
+/// Starts a transaction with a fresh miniblock header the benchmarked rows can reference.
+async fn prepare_miniblock(storage: &mut StorageProcessor<'_>) -> MiniblockNumber {
+    let number = storage
+        .blocks_dal()
+        .get_sealed_miniblock_number()
+        .await
+        .unwrap()
+        + 1;
+    let header = MiniblockHeader {
+        number,
+        timestamp: 1_700_000_000 + u64::from(number.0),
+        hash: H256::from_low_u64_be(number.0.into()),
+        l1_tx_count: 0,
+        l2_tx_count: 0,
+        base_system_contracts_hashes: Default::default(),
+        protocol_version: Some(ProtocolVersionId::latest()),
+    };
+    storage
+        .blocks_dal()
+        .insert_miniblock(&header)
+        .await
+        .unwrap();
+    number
+}
+
+async fn insert_storage_logs(
+    storage: &mut StorageProcessor<'_>,
+    number: MiniblockNumber,
+    logs: &[(H256, Vec<StorageLog>)],
+    mode: InsertMode,
+) {
+    match mode {
+        InsertMode::RowByRow => {
+            let logs = logs
+                .iter()
+                .flat_map(|(tx_hash, logs)| logs.iter().map(move |log| (tx_hash, log)));
+            for (operation_number, (tx_hash, log)) in logs.enumerate() {
+                sqlx::query(
+                    "INSERT INTO storage_logs ( \
+                        hashed_key, address, key, value, operation_number, tx_hash, \
+                        miniblock_number, created_at, updated_at \
+                    ) \
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, now(), now())",
+                )
+                .bind(log.key.hashed_key().as_bytes())
+                .bind(log.key.address().as_bytes())
+                .bind(log.key.key().as_bytes())
+                .bind(log.value.as_bytes())
+                .bind(operation_number as i32)
+                .bind(tx_hash.as_bytes())
+                .bind(i64::from(number.0))
+                .execute(storage.conn())
+                .await
+                .unwrap();
+            }
+        }
+        InsertMode::Bulk => {
+            storage
+                .storage_logs_dal()
+                .insert_storage_logs(number, logs)
+                .await
+                .unwrap();
+        }
+    }
+}
+
+async fn insert_events(
+    storage: &mut StorageProcessor<'_>,
+    number: MiniblockNumber,
+    events: &[(IncludedTxLocation, Vec<VmEvent>)],
+    mode: InsertMode,
+) {
+    match mode {
+        InsertMode::RowByRow => {
+            let events = events.iter().flat_map(|(location, events)| {
+                events.iter().enumerate().map(move |e| (location, e))
+            });
+            for (index_in_block, (location, (index_in_tx, event))) in events.enumerate() {
+                sqlx::query(
+                    "INSERT INTO events ( \
+                        miniblock_number, tx_hash, tx_index_in_block, address, \
+                        event_index_in_block, event_index_in_tx, \
+                        topic1, topic2, topic3, topic4, value, tx_initiator_address, \
+                        created_at, updated_at \
+                    ) \
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, '', '', '', $8, $9, now(), now())",
+                )
+                .bind(i64::from(number.0))
+                .bind(location.tx_hash.as_bytes())
+                .bind(location.tx_index_in_miniblock as i32)
+                .bind(event.address.as_bytes())
+                .bind(index_in_block as i32)
+                .bind(index_in_tx as i32)
+                .bind(event.indexed_topics[0].as_bytes())
+                .bind(event.value.as_slice())
+                .bind(location.tx_initiator_address.as_bytes())
+                .execute(storage.conn())
+                .await
+                .unwrap();
+            }
+        }
+        InsertMode::Bulk => {
+            let events: Vec<_> = events
+                .iter()
+                .map(|(location, events)| (location.clone(), events.iter().collect()))
+                .collect();
+            storage
+                .events_dal()
+                .save_events(number, &events)
+                .await
+                .unwrap();
+        }
+    }
+}
+
+/// Measures `iters` inserts into `table`, each in a transaction which is rolled back afterwards.
+fn measure(
+    runtime: &Runtime,
+    iters: u64,
+    table: Table,
+    mode: InsertMode,
+    data: &SealData,
+) -> Duration {
+    runtime.block_on(async {
+        let mut storage = StorageProcessor::establish_connection(true).await;
+        let mut elapsed = Duration::ZERO;
+        for _ in 0..iters {
+            let mut transaction = storage.start_transaction().await;
+            let number = prepare_miniblock(&mut transaction).await;
+            let started_at = Instant::now();
+            match table {
+                Table::StorageLogs => {
+                    insert_storage_logs(&mut transaction, number, &data.write_logs, mode).await;
+                }
+                Table::Events => {
+                    insert_events(&mut transaction, number, &data.events, mode).await;
+                }
+            }
+            elapsed += started_at.elapsed();
+            // The transaction is dropped without committing, which rolls it back.
+        }
+        elapsed
+    })
+}
+
+fn bench_seal_inserts(criterion: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let data = SealData {
+        write_logs: write_logs(),
+        events: events(),
+    };
+
+    let mut group = criterion.benchmark_group("seal_inserts");
+    group
+        .sample_size(10)
+        .measurement_time(Duration::from_secs(60))
+        .throughput(Throughput::Elements(ROW_COUNT as u64));
+    for table in Table::ALL {
+        for mode in InsertMode::ALL {
+            let id = BenchmarkId::new(table.name(), mode.name());
+            group.bench_function(id, |bencher| {
+                bencher.iter_custom(|iters| measure(&runtime, iters, table, mode, &data));
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_seal_inserts);
+criterion_main!(benches);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP VIEW IF EXISTS prover_jobs_fri_stats;
+DROP INDEX IF EXISTS idx_prover_jobs_fri_archive_archived_at;
+DROP INDEX IF EXISTS idx_prover_jobs_fri_archive_l1_batch_number;
+DROP TABLE IF EXISTS prover_jobs_fri_archive;
@@ -0,0 +1,48 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS prover_jobs_fri_archive
+(
+    id BIGINT PRIMARY KEY,
+    l1_batch_number BIGINT NOT NULL,
+    circuit_id SMALLINT NOT NULL,
+    circuit_blob_url TEXT NOT NULL,
+    aggregation_round SMALLINT NOT NULL,
+    sequence_number INT NOT NULL,
+    proof_blob_url TEXT,
+    status TEXT NOT NULL,
+    depth INT NOT NULL DEFAULT 0,
+    error TEXT,
+    attempts SMALLINT NOT NULL DEFAULT 0,
+    processing_started_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL,
+    time_taken TIME,
+    is_blob_cleaned BOOLEAN,
+    is_node_final_proof BOOLEAN NOT NULL DEFAULT FALSE,
+    protocol_version INT,
+    picked_by TEXT,
+    archived_at TIMESTAMP NOT NULL
+    );
+
+CREATE INDEX IF NOT EXISTS idx_prover_jobs_fri_archive_l1_batch_number
+    ON prover_jobs_fri_archive (l1_batch_number);
+
+CREATE INDEX IF NOT EXISTS idx_prover_jobs_fri_archive_archived_at
+    ON prover_jobs_fri_archive (archived_at);
+
+CREATE OR REPLACE VIEW prover_jobs_fri_stats AS
+SELECT
+    circuit_id,
+    aggregation_round,
+    COUNT(*) AS total_jobs,
+    COUNT(*) FILTER (WHERE status IN ('successful', 'sent_to_server')) AS successful_jobs,
+    COUNT(*) FILTER (WHERE status = 'failed') AS failed_jobs,
+    AVG(EXTRACT(EPOCH FROM time_taken)) FILTER (WHERE time_taken IS NOT NULL) AS avg_proving_time_secs
+FROM
+    (
+        SELECT circuit_id, aggregation_round, status, time_taken FROM prover_jobs_fri
+        UNION ALL
+        SELECT circuit_id, aggregation_round, status, time_taken FROM prover_jobs_fri_archive
+    ) AS jobs
+GROUP BY
+    circuit_id,
+    aggregation_round;
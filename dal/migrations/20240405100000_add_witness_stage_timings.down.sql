@@ -0,0 +1 @@
+ALTER TABLE witness_inputs_fri DROP COLUMN IF EXISTS stage_timings;
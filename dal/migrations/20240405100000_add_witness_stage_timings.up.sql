@@ -0,0 +1 @@
+ALTER TABLE witness_inputs_fri ADD COLUMN IF NOT EXISTS stage_timings JSONB;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS transactions_archive_index;
+DROP TABLE IF EXISTS transactions_archive_segments;
@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS transactions_archive_segments
+(
+    l1_batch_number BIGINT PRIMARY KEY,
+    object_key TEXT NOT NULL,
+    tx_count INT NOT NULL,
+    event_count INT NOT NULL,
+    archived_at TIMESTAMP NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS transactions_archive_index
+(
+    hash BYTEA PRIMARY KEY,
+    l1_batch_number BIGINT NOT NULL
+        REFERENCES transactions_archive_segments (l1_batch_number) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_transactions_archive_index_l1_batch_number
+    ON transactions_archive_index (l1_batch_number);
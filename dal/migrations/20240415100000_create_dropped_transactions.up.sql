@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS dropped_transactions
+(
+    hash BYTEA PRIMARY KEY,
+    initiator_address BYTEA NOT NULL,
+    nonce BIGINT,
+    reason TEXT NOT NULL,
+    replaced_by BYTEA,
+    dropped_at TIMESTAMP NOT NULL
+);
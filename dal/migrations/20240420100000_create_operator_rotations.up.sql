@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS operator_rotations
+(
+    id BIGSERIAL PRIMARY KEY,
+    operator_address BYTEA NOT NULL,
+    -- First L1 batch of the operator; NULL until the sequencer opens the next L1 batch.
+    effective_l1_batch_number BIGINT UNIQUE,
+    requested_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS contract_execution_metrics
+(
+    l1_batch_number BIGINT NOT NULL REFERENCES l1_batches (number) ON DELETE CASCADE,
+    address BYTEA NOT NULL,
+    tx_count BIGINT NOT NULL,
+    cycles_used BIGINT NOT NULL,
+    storage_reads BIGINT NOT NULL,
+    storage_writes BIGINT NOT NULL,
+    events BIGINT NOT NULL,
+    PRIMARY KEY (l1_batch_number, address)
+);
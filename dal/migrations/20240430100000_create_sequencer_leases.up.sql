@@ -0,0 +1,9 @@
+-- Leadership lease of the sequencer; the table holds at most one row.
+CREATE TABLE IF NOT EXISTS sequencer_leases
+(
+    id SMALLINT PRIMARY KEY CHECK (id = 1),
+    holder TEXT NOT NULL,
+    -- Incremented on each change of the leader, so that a former leader can't write.
+    fencing_token BIGINT NOT NULL,
+    expires_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS tokens
+(
+    address BYTEA PRIMARY KEY,
+    name TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    decimals INT NOT NULL,
+    deployed_in_miniblock BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS token_balances
+(
+    account_address BYTEA NOT NULL,
+    token_address BYTEA NOT NULL REFERENCES tokens (address) ON DELETE CASCADE,
+    balance NUMERIC(80) NOT NULL,
+    updated_at_miniblock BIGINT NOT NULL,
+    PRIMARY KEY (account_address, token_address)
+);
@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS audit_log
+(
+    id BIGSERIAL PRIMARY KEY,
+    action TEXT NOT NULL,
+    -- Who triggered the action, e.g. `cli:<user>` or `system` for background tasks.
+    actor TEXT NOT NULL,
+    parameters JSONB NOT NULL,
+    created_at TIMESTAMP NOT NULL
+);
+CREATE INDEX IF NOT EXISTS audit_log_action_idx ON audit_log (action, id);
+
+-- The log is append-only: entries can't be modified or removed.
+CREATE OR REPLACE FUNCTION audit_log_forbid_changes() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_log_append_only
+    BEFORE UPDATE OR DELETE ON audit_log
+    FOR EACH ROW EXECUTE FUNCTION audit_log_forbid_changes();
@@ -0,0 +1 @@
+DROP INDEX IF EXISTS transactions_pending_received_at_idx;
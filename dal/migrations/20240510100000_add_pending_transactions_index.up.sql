@@ -0,0 +1,3 @@
+-- Serves the mempool sync query, which only scans pending transactions received after its cursor.
+CREATE INDEX IF NOT EXISTS transactions_pending_received_at_idx ON transactions (received_at)
+    WHERE miniblock_number IS NULL AND in_mempool = FALSE AND error IS NULL;
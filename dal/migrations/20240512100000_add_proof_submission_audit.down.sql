@@ -0,0 +1,4 @@
+ALTER TABLE proof_generation_details
+    DROP COLUMN IF EXISTS proof_hash,
+    DROP COLUMN IF EXISTS proof_submitter,
+    DROP COLUMN IF EXISTS proof_submitted_at;
@@ -0,0 +1,4 @@
+ALTER TABLE proof_generation_details
+    ADD COLUMN IF NOT EXISTS proof_hash BYTEA,
+    ADD COLUMN IF NOT EXISTS proof_submitter TEXT,
+    ADD COLUMN IF NOT EXISTS proof_submitted_at TIMESTAMP;
@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS reverted_events;
+DROP TABLE IF EXISTS reverted_transactions;
+DROP TABLE IF EXISTS reverted_miniblocks;
+DROP TABLE IF EXISTS reverted_l1_batches;
+DROP TABLE IF EXISTS reverts;
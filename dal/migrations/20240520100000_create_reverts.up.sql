@@ -0,0 +1,52 @@
+CREATE TABLE IF NOT EXISTS reverts
+(
+    id BIGSERIAL PRIMARY KEY,
+    last_kept_l1_batch BIGINT NOT NULL,
+    last_kept_miniblock BIGINT NOT NULL,
+    reason TEXT NOT NULL,
+    reverted_at TIMESTAMP NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS reverted_l1_batches
+(
+    revert_id BIGINT NOT NULL REFERENCES reverts (id) ON DELETE CASCADE,
+    number BIGINT NOT NULL,
+    data JSONB NOT NULL,
+    PRIMARY KEY (revert_id, number)
+);
+
+CREATE TABLE IF NOT EXISTS reverted_miniblocks
+(
+    revert_id BIGINT NOT NULL REFERENCES reverts (id) ON DELETE CASCADE,
+    number BIGINT NOT NULL,
+    hash BYTEA NOT NULL,
+    data JSONB NOT NULL,
+    PRIMARY KEY (revert_id, number)
+);
+
+CREATE TABLE IF NOT EXISTS reverted_transactions
+(
+    revert_id BIGINT NOT NULL REFERENCES reverts (id) ON DELETE CASCADE,
+    hash BYTEA NOT NULL,
+    miniblock_number BIGINT NOT NULL,
+    data JSONB NOT NULL,
+    PRIMARY KEY (revert_id, hash)
+);
+
+CREATE TABLE IF NOT EXISTS reverted_events
+(
+    revert_id BIGINT NOT NULL REFERENCES reverts (id) ON DELETE CASCADE,
+    miniblock_number BIGINT NOT NULL,
+    event_index_in_block INT NOT NULL,
+    tx_hash BYTEA NOT NULL,
+    data JSONB NOT NULL,
+    PRIMARY KEY (revert_id, miniblock_number, event_index_in_block)
+);
+
+CREATE INDEX IF NOT EXISTS reverted_l1_batches_number_idx ON reverted_l1_batches (number);
+CREATE INDEX IF NOT EXISTS reverted_miniblocks_number_idx ON reverted_miniblocks (number);
+CREATE INDEX IF NOT EXISTS reverted_miniblocks_hash_idx ON reverted_miniblocks USING hash (hash);
+CREATE INDEX IF NOT EXISTS reverted_transactions_hash_idx ON reverted_transactions USING hash (hash);
+CREATE INDEX IF NOT EXISTS reverted_transactions_miniblock_number_idx
+    ON reverted_transactions (revert_id, miniblock_number);
+CREATE INDEX IF NOT EXISTS reverted_events_tx_hash_idx ON reverted_events USING hash (tx_hash);
@@ -0,0 +1,6 @@
+-- no-transaction
+-- The index is built concurrently, so that the migration doesn't block writes to `storage_logs`.
+-- If the build fails, the invalid index must be dropped manually before rerunning the migration.
+-- Serves storage slot history, which reads the last write of the slot in each miniblock.
+CREATE INDEX CONCURRENTLY IF NOT EXISTS storage_logs_hashed_key_history_idx
+    ON storage_logs (hashed_key, miniblock_number, operation_number DESC) INCLUDE (value, tx_hash);
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS offchain_verifier_attestations;
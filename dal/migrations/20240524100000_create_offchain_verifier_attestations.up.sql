@@ -0,0 +1,12 @@
+-- Signed verdicts of the offchain verifier committee members; one per member and L1 batch.
+CREATE TABLE IF NOT EXISTS offchain_verifier_attestations
+(
+    l1_batch_number  BIGINT    NOT NULL REFERENCES proof_offchain_verification_details (l1_batch_number) ON DELETE CASCADE,
+    verifier_address BYTEA     NOT NULL,
+    commitment       BYTEA     NOT NULL,
+    is_valid         BOOLEAN   NOT NULL,
+    signature        BYTEA     NOT NULL,
+    created_at       TIMESTAMP NOT NULL,
+    updated_at       TIMESTAMP NOT NULL,
+    PRIMARY KEY (l1_batch_number, verifier_address)
+);
@@ -0,0 +1,12 @@
+-- Effect digests of transactions submitted with simulation recording; the actual digests
+-- are filled in when the miniblock including the transaction is sealed.
+CREATE TABLE IF NOT EXISTS transaction_simulations
+(
+    tx_hash                  BYTEA     NOT NULL PRIMARY KEY,
+    simulated_write_set_hash BYTEA     NOT NULL,
+    simulated_events_hash    BYTEA     NOT NULL,
+    actual_write_set_hash    BYTEA,
+    actual_events_hash       BYTEA,
+    created_at               TIMESTAMP NOT NULL,
+    updated_at               TIMESTAMP NOT NULL
+);
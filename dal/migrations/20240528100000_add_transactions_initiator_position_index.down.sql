@@ -0,0 +1 @@
+DROP INDEX IF EXISTS transactions_initiator_address_position_idx;
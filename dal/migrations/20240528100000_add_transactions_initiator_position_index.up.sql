@@ -0,0 +1,4 @@
+-- Keyset pagination of sealed transactions by initiator (`ola_getTransactionsByAddress`).
+CREATE INDEX IF NOT EXISTS transactions_initiator_address_position_idx
+    ON transactions (initiator_address, miniblock_number, index_in_block)
+    WHERE miniblock_number IS NOT NULL;
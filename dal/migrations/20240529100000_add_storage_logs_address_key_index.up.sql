@@ -0,0 +1,7 @@
+-- no-transaction
+-- The index is built concurrently, so that the migration doesn't block writes to `storage_logs`.
+-- If the build fails, the invalid index must be dropped manually before rerunning the migration.
+-- Serves storage range iteration of an account (`ola_getAccountStorageRange`), which reads
+-- the last write of each slot of the account as of a miniblock.
+CREATE INDEX CONCURRENTLY IF NOT EXISTS storage_logs_address_key_idx
+    ON storage_logs (address, key, miniblock_number DESC, operation_number DESC) INCLUDE (value);
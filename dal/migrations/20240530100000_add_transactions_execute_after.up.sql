@@ -0,0 +1,2 @@
+-- Scheduled transactions are held in the mempool until the chain timestamp passes this value.
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS execute_after TIMESTAMP;
@@ -0,0 +1,2 @@
+-- Call traces written before the version was introduced use format version 0.
+ALTER TABLE call_traces ADD COLUMN IF NOT EXISTS format_version SMALLINT NOT NULL DEFAULT 0;
@@ -0,0 +1,8 @@
+-- Index of call outputs spilled to the object store, used to enforce their quota and retention.
+CREATE TABLE IF NOT EXISTS call_outputs (
+    hash BYTEA PRIMARY KEY,
+    size BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS call_outputs_created_at_idx ON call_outputs (created_at);
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS calldata_screening_rules;
@@ -0,0 +1,7 @@
+-- Calldata screening rules set via the admin API, so that they survive restarts and are shared
+-- by all API servers. The table has at most one row.
+CREATE TABLE IF NOT EXISTS calldata_screening_rules (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    rules JSONB NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
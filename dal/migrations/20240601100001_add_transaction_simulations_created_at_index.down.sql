@@ -0,0 +1 @@
+DROP INDEX IF EXISTS transaction_simulations_created_at_idx;
@@ -0,0 +1,2 @@
+-- Used to prune simulations older than the retention.
+CREATE INDEX IF NOT EXISTS transaction_simulations_created_at_idx ON transaction_simulations (created_at);
@@ -0,0 +1 @@
+ALTER TABLE basic_witness_input_producer_jobs DROP COLUMN IF EXISTS stage_timings;
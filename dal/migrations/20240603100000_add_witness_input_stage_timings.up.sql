@@ -0,0 +1 @@
+ALTER TABLE basic_witness_input_producer_jobs ADD COLUMN IF NOT EXISTS stage_timings JSONB;
@@ -0,0 +1,86 @@
+use ola_types::api::{AuditAction, AuditLogEntry};
+use sqlx::types::chrono::{DateTime, NaiveDateTime, Utc};
+
+use crate::{DalResult, StorageProcessor};
+
+/// Append-only log of operator-affecting actions. The table rejects updates and deletions.
+#[derive(Debug)]
+pub struct AuditLogDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl AuditLogDal<'_, '_> {
+    /// Appends an entry and returns it with the assigned ID and timestamp.
+    pub async fn insert_entry(
+        &mut self,
+        action: AuditAction,
+        actor: &str,
+        parameters: serde_json::Value,
+    ) -> DalResult<AuditLogEntry> {
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO audit_log (action, actor, parameters, created_at)
+            VALUES ($1, $2, $3, NOW())
+            RETURNING id, created_at
+            "#,
+            action.to_string(),
+            actor,
+            &parameters
+        )
+        .fetch_one(self.storage.conn())
+        .await?;
+
+        Ok(AuditLogEntry {
+            id: row.id as u64,
+            timestamp: to_utc(row.created_at),
+            action,
+            actor: actor.to_owned(),
+            parameters,
+        })
+    }
+
+    /// Returns up to `limit` entries with IDs greater than `after_id`, optionally filtered
+    /// by `action`, in the order they were recorded.
+    pub async fn get_entries(
+        &mut self,
+        action: Option<AuditAction>,
+        after_id: Option<u64>,
+        limit: usize,
+    ) -> DalResult<Vec<AuditLogEntry>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, action, actor, parameters, created_at
+            FROM audit_log
+            WHERE
+                ($1::TEXT IS NULL OR action = $1)
+                AND id > $2
+            ORDER BY id
+            LIMIT $3
+            "#,
+            action.map(|action| action.to_string()),
+            after_id.map_or(0, |id| id as i64),
+            limit as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(AuditLogEntry {
+                    id: row.id as u64,
+                    timestamp: to_utc(row.created_at),
+                    action: row
+                        .action
+                        .parse()
+                        .map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+                    actor: row.actor,
+                    parameters: row.parameters,
+                })
+            })
+            .collect()
+    }
+}
+
+fn to_utc(timestamp: NaiveDateTime) -> DateTime<Utc> {
+    DateTime::<Utc>::from_naive_utc_and_offset(timestamp, Utc)
+}
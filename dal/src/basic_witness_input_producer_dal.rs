@@ -1,11 +1,11 @@
 use std::time::{Duration, Instant};
 
-use ola_types::L1BatchNumber;
+use ola_types::{proofs::WitnessInputStageTimings, L1BatchNumber};
 use sqlx::postgres::types::PgInterval;
 
 use crate::{
     time_utils::{duration_to_naive_time, pg_interval_from_duration},
-    StorageProcessor,
+    DalResult, StorageProcessor,
 };
 
 #[derive(Debug)]
@@ -48,7 +48,7 @@ impl BasicWitnessInputProducerDal<'_, '_> {
     pub async fn create_basic_witness_input_producer_job(
         &mut self,
         l1_batch_number: L1BatchNumber,
-    ) -> sqlx::Result<()> {
+    ) -> DalResult<()> {
         sqlx::query!(
             r#"
             INSERT INTO
@@ -65,11 +65,152 @@ impl BasicWitnessInputProducerDal<'_, '_> {
 
         Ok(())
     }
+
+    /// Picks the queued job with the lowest L1 batch number, a job whose processing timed out,
+    /// or a failed job with attempts left, and marks it as in progress.
+    pub async fn get_next_basic_witness_input_producer_job(
+        &mut self,
+    ) -> DalResult<Option<L1BatchNumber>> {
+        let l1_batch_number = sqlx::query!(
+            r#"
+            UPDATE basic_witness_input_producer_jobs
+            SET
+                status = $1,
+                attempts = attempts + 1,
+                updated_at = NOW(),
+                processing_started_at = NOW()
+            WHERE
+                l1_batch_number = (
+                    SELECT
+                        l1_batch_number
+                    FROM
+                        basic_witness_input_producer_jobs
+                    WHERE
+                        status = $2
+                        OR (
+                            status = $1
+                            AND processing_started_at < NOW() - $4::INTERVAL
+                        )
+                        OR (
+                            status = $3
+                            AND attempts < $5
+                        )
+                    ORDER BY
+                        l1_batch_number ASC
+                    LIMIT
+                        1
+                    FOR UPDATE
+                        SKIP LOCKED
+                )
+            RETURNING
+                basic_witness_input_producer_jobs.l1_batch_number
+            "#,
+            BasicWitnessInputProducerJobStatus::InProgress as BasicWitnessInputProducerJobStatus,
+            BasicWitnessInputProducerJobStatus::Queued as BasicWitnessInputProducerJobStatus,
+            BasicWitnessInputProducerJobStatus::Failed as BasicWitnessInputProducerJobStatus,
+            &JOB_PROCESSING_TIMEOUT,
+            JOB_MAX_ATTEMPT,
+        )
+        .fetch_optional(self.storage.conn())
+        .await?
+        .map(|job| L1BatchNumber(job.l1_batch_number as u32));
+
+        Ok(l1_batch_number)
+    }
+
+    pub async fn get_basic_witness_input_producer_job_attempts(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> DalResult<Option<u32>> {
+        let attempts = sqlx::query!(
+            r#"
+            SELECT
+                attempts
+            FROM
+                basic_witness_input_producer_jobs
+            WHERE
+                l1_batch_number = $1
+            "#,
+            l1_batch_number.0 as i64,
+        )
+        .fetch_optional(self.storage.conn())
+        .await?
+        .map(|job| job.attempts as u32);
+
+        Ok(attempts)
+    }
+
+    pub async fn mark_job_as_successful(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        started_at: Instant,
+        object_path: &str,
+        stage_timings: &WitnessInputStageTimings,
+    ) -> DalResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE basic_witness_input_producer_jobs
+            SET
+                status = $1,
+                updated_at = NOW(),
+                time_taken = $3,
+                input_blob_url = $4,
+                stage_timings = $5,
+                error = NULL
+            WHERE
+                l1_batch_number = $2
+            "#,
+            BasicWitnessInputProducerJobStatus::Successful as BasicWitnessInputProducerJobStatus,
+            l1_batch_number.0 as i64,
+            duration_to_naive_time(started_at.elapsed()),
+            object_path,
+            serde_json::to_value(stage_timings).expect("failed serializing stage timings"),
+        )
+        .execute(self.storage.conn())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Marks the job as failed. Returns the number of attempts made, or `None` if the job
+    /// is not in progress, e.g. its processing timed out and it was picked again.
+    pub async fn mark_job_as_failed(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        started_at: Instant,
+        error: String,
+    ) -> DalResult<Option<u32>> {
+        let attempts = sqlx::query!(
+            r#"
+            UPDATE basic_witness_input_producer_jobs
+            SET
+                status = $1,
+                updated_at = NOW(),
+                time_taken = $3,
+                error = $4
+            WHERE
+                l1_batch_number = $2
+                AND status != $5
+            RETURNING
+                basic_witness_input_producer_jobs.attempts
+            "#,
+            BasicWitnessInputProducerJobStatus::Failed as BasicWitnessInputProducerJobStatus,
+            l1_batch_number.0 as i64,
+            duration_to_naive_time(started_at.elapsed()),
+            error,
+            BasicWitnessInputProducerJobStatus::Successful as BasicWitnessInputProducerJobStatus,
+        )
+        .fetch_optional(self.storage.conn())
+        .await?
+        .map(|job| job.attempts as u32);
+
+        Ok(attempts)
+    }
 }
 
 /// These functions should only be used for tests.
 impl BasicWitnessInputProducerDal<'_, '_> {
-    pub async fn delete_all_jobs(&mut self) -> sqlx::Result<()> {
+    pub async fn delete_all_jobs(&mut self) -> DalResult<()> {
         sqlx::query!(
             r#"
             DELETE FROM basic_witness_input_producer_jobs
@@ -0,0 +1,83 @@
+//! Generates `dal/schema_manifest.json` from the schema of the master database, which must have
+//! all migrations applied. Also checks that the fields of DAL models reading whole table rows are
+//! columns of the corresponding tables.
+//!
+//! `schema_manifest --check` compares the database with the checked-in manifest instead
+//! of overwriting it.
+
+use std::{fs, path::Path};
+
+use anyhow::Context as _;
+use ola_dal::{
+    connection::{ConnectionPool, DbVariant},
+    schema::{model_fields, SchemaManifest, MODEL_TABLES},
+};
+
+const USAGE: &str = "usage: schema_manifest [--check]";
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let check = match args.as_slice() {
+        [] => false,
+        [arg] if arg == "--check" => true,
+        _ => anyhow::bail!(USAGE),
+    };
+
+    let pool = ConnectionPool::singleton(DbVariant::Master).build().await;
+    let mut storage = pool.access_storage_tagged("schema_manifest").await;
+    let live = SchemaManifest::load(&mut storage)
+        .await
+        .context("failed loading database schema")?;
+    check_models(&live)?;
+
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let manifest_path = crate_dir.join("schema_manifest.json");
+    if check {
+        let diff = SchemaManifest::expected().diff(&live);
+        anyhow::ensure!(
+            diff.is_empty(),
+            "database schema differs from {}:\n{diff}",
+            manifest_path.display()
+        );
+        println!("Database schema matches {}", manifest_path.display());
+    } else {
+        fs::write(&manifest_path, live.to_json())
+            .with_context(|| format!("cannot write {}", manifest_path.display()))?;
+        println!(
+            "Wrote schema of {} tables to {}",
+            live.tables.len(),
+            manifest_path.display()
+        );
+    }
+    Ok(())
+}
+
+fn check_models(live: &SchemaManifest) -> anyhow::Result<()> {
+    let models_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/models");
+    let mut errors = vec![];
+    for &(file, model, table) in MODEL_TABLES {
+        let path = models_dir.join(file);
+        let source =
+            fs::read_to_string(&path).with_context(|| format!("cannot read {}", path.display()))?;
+        let fields = model_fields(&source, model)
+            .with_context(|| format!("model `{model}` is not defined in {}", path.display()))?;
+        let Some(table_schema) = live.tables.get(table) else {
+            errors.push(format!("- table `{table}` of model `{model}` is missing"));
+            continue;
+        };
+        for field in fields {
+            if !table_schema.columns.contains_key(&field) {
+                errors.push(format!(
+                    "- field `{model}.{field}` is not a column of `{table}`"
+                ));
+            }
+        }
+    }
+    anyhow::ensure!(
+        errors.is_empty(),
+        "DAL models don't match the database schema:\n{}",
+        errors.join("\n")
+    );
+    Ok(())
+}
@@ -10,7 +10,7 @@ use ola_types::{
 
 use crate::{
     models::storage_block::{StorageL1Batch, StorageL1BatchHeader, StorageMiniblockHeader},
-    StorageProcessor,
+    DalError, DalResult, StorageProcessor,
 };
 
 #[derive(Debug)]
@@ -19,37 +19,39 @@ pub struct BlocksDal<'a, 'c> {
 }
 
 impl BlocksDal<'_, '_> {
-    pub async fn is_genesis_needed(&mut self) -> bool {
+    pub async fn is_genesis_needed(&mut self) -> DalResult<bool> {
         let count = sqlx::query!("SELECT COUNT(*) as \"count!\" FROM l1_batches")
             .fetch_one(self.storage.conn())
-            .await
-            .unwrap()
+            .await?
             .count;
         olaos_logs::info!("is_genesis_needed count = {}", count);
-        count == 0
+        Ok(count == 0)
     }
 
-    pub async fn get_l1_batch_state_root(&mut self, number: L1BatchNumber) -> Option<H256> {
-        sqlx::query!(
+    pub async fn get_l1_batch_state_root(
+        &mut self,
+        number: L1BatchNumber,
+    ) -> DalResult<Option<H256>> {
+        let row = sqlx::query!(
             "SELECT hash FROM l1_batches WHERE number = $1",
             number.0 as i64
         )
         .fetch_optional(self.storage.conn())
-        .await
-        .unwrap()
-        .and_then(|row| row.hash)
-        .map(|hash| H256::from_slice(&hash))
+        .await?;
+        Ok(row
+            .and_then(|row| row.hash)
+            .map(|hash| H256::from_slice(&hash)))
     }
 
     pub async fn insert_l1_batch(
         &mut self,
         header: &L1BatchHeader,
         initial_bootloader_contents: &[(usize, U256)],
-    ) {
-        let initial_bootloader_contents = serde_json::to_value(initial_bootloader_contents)
-            .expect("failed to serialize initial_bootloader_contents to JSON value");
-        let used_contract_hashes = serde_json::to_value(&header.used_contract_hashes)
-            .expect("failed to serialize used_contract_hashes to JSON value");
+    ) -> DalResult<()> {
+        let initial_bootloader_contents =
+            serde_json::to_value(initial_bootloader_contents).map_err(DalError::encode)?;
+        let used_contract_hashes =
+            serde_json::to_value(&header.used_contract_hashes).map_err(DalError::encode)?;
 
         sqlx::query!(
             "INSERT INTO l1_batches (\
@@ -71,11 +73,58 @@ impl BlocksDal<'_, '_> {
             header.protocol_version.map(|v| v as i32),
         )
         .execute(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
+        Ok(())
+    }
+
+    /// Saves the root of the receipts tree of an L1 batch, computed when the batch is sealed.
+    pub async fn set_l1_batch_receipts_root(
+        &mut self,
+        number: L1BatchNumber,
+        receipts_root: H256,
+    ) -> DalResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE l1_batches
+            SET
+                receipts_root = $2,
+                updated_at = NOW()
+            WHERE
+                number = $1
+            "#,
+            number.0 as i64,
+            receipts_root.as_bytes()
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
     }
 
-    pub async fn insert_miniblock(&mut self, miniblock_header: &MiniblockHeader) {
+    /// Returns the root of the receipts tree of an L1 batch, or `None` if the batch is not sealed
+    /// or was sealed before receipts roots were introduced.
+    pub async fn get_l1_batch_receipts_root(
+        &mut self,
+        number: L1BatchNumber,
+    ) -> DalResult<Option<H256>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                receipts_root
+            FROM
+                l1_batches
+            WHERE
+                number = $1
+            "#,
+            number.0 as i64
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+        Ok(row
+            .and_then(|row| row.receipts_root)
+            .map(|root| H256::from_slice(&root)))
+    }
+
+    pub async fn insert_miniblock(&mut self, miniblock_header: &MiniblockHeader) -> DalResult<()> {
         sqlx::query!(
             "INSERT INTO miniblocks ( \
                 number, timestamp, hash, l1_tx_count, l2_tx_count, \
@@ -98,14 +147,14 @@ impl BlocksDal<'_, '_> {
             miniblock_header.protocol_version.map(|v| v as i32),
         )
         .execute(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
+        Ok(())
     }
 
     pub async fn mark_miniblocks_as_executed_in_l1_batch(
         &mut self,
         l1_batch_number: L1BatchNumber,
-    ) {
+    ) -> DalResult<()> {
         sqlx::query!(
             "UPDATE miniblocks \
             SET l1_batch_number = $1 \
@@ -113,23 +162,45 @@ impl BlocksDal<'_, '_> {
             l1_batch_number.0 as i32,
         )
         .execute(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
+        Ok(())
+    }
+
+    /// Assigns a single miniblock to `l1_batch_number`. Used for miniblocks imported without
+    /// sealing their L1 batch locally, e.g. by the backfill.
+    pub async fn set_miniblock_l1_batch_number(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+        l1_batch_number: L1BatchNumber,
+    ) -> DalResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE miniblocks
+            SET
+                l1_batch_number = $1
+            WHERE
+                number = $2
+            "#,
+            l1_batch_number.0 as i64,
+            miniblock_number.0 as i64
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
     }
 
     #[olaos_logs::instrument(name = "get_sealed_miniblock_number", skip_all)]
-    pub async fn get_sealed_miniblock_number(&mut self) -> MiniblockNumber {
+    pub async fn get_sealed_miniblock_number(&mut self) -> DalResult<MiniblockNumber> {
         let number: i64 = sqlx::query!("SELECT MAX(number) as \"number\" FROM miniblocks")
             .fetch_one(self.storage.conn())
-            .await
-            .unwrap()
+            .await?
             .number
             .unwrap_or(0);
-        MiniblockNumber(number as u32)
+        Ok(MiniblockNumber(number as u32))
     }
 
     #[olaos_logs::instrument(name = "get_newest_l1_batch_header", skip_all)]
-    pub async fn get_newest_l1_batch_header(&mut self) -> L1BatchHeader {
+    pub async fn get_newest_l1_batch_header(&mut self) -> DalResult<L1BatchHeader> {
         let last_l1_batch = sqlx::query_as!(
             StorageL1BatchHeader,
             "SELECT number, l1_tx_count, l2_tx_count, \
@@ -140,10 +211,9 @@ impl BlocksDal<'_, '_> {
             LIMIT 1"
         )
         .fetch_one(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
 
-        last_l1_batch.into()
+        Ok(last_l1_batch.into())
     }
 
     pub async fn get_l1_batch_metadata(
@@ -168,7 +238,7 @@ impl BlocksDal<'_, '_> {
     ) -> anyhow::Result<Option<L1BatchWithMetadata>> {
         let unsorted_factory_deps = self
             .get_l1_batch_factory_deps(L1BatchNumber(storage_batch.number as u32))
-            .await;
+            .await?;
         let header = storage_batch.clone().into();
         let Ok(metadata) = storage_batch.try_into() else {
             return Ok(None);
@@ -184,8 +254,8 @@ impl BlocksDal<'_, '_> {
     pub async fn get_storage_l1_batch(
         &mut self,
         number: L1BatchNumber,
-    ) -> sqlx::Result<Option<StorageL1Batch>> {
-        sqlx::query_as!(
+    ) -> DalResult<Option<StorageL1Batch>> {
+        let row = sqlx::query_as!(
             StorageL1Batch,
             r#"
             SELECT
@@ -220,45 +290,46 @@ impl BlocksDal<'_, '_> {
             number.0 as i64
         )
         .fetch_optional(self.storage.conn())
-        .await
+        .await?;
+        Ok(row)
     }
 
     // #[olaos_logs::instrument(name = "get_sealed_l1_batch_number", skip_all)]
-    pub async fn get_sealed_l1_batch_number(&mut self) -> L1BatchNumber {
+    /// Returns the number of the last sealed L1 batch, or [`DalError::NotFound`] before genesis.
+    pub async fn get_sealed_l1_batch_number(&mut self) -> DalResult<L1BatchNumber> {
         let number = sqlx::query!(
             "SELECT MAX(number) as \"number\" FROM l1_batches WHERE is_finished = TRUE"
         )
         .fetch_one(self.storage.conn())
-        .await
-        .unwrap()
+        .await?
         .number
-        .expect("DAL invocation before genesis");
+        .ok_or(DalError::NotFound)?;
 
-        L1BatchNumber(number as u32)
+        Ok(L1BatchNumber(number as u32))
     }
 
     pub async fn get_l1_batch_factory_deps(
         &mut self,
         l1_batch_number: L1BatchNumber,
-    ) -> HashMap<H256, Vec<u8>> {
-        sqlx::query!(
+    ) -> DalResult<HashMap<H256, Vec<u8>>> {
+        let rows = sqlx::query!(
             "SELECT bytecode_hash, bytecode FROM factory_deps \
             INNER JOIN miniblocks ON miniblocks.number = factory_deps.miniblock_number \
             WHERE miniblocks.l1_batch_number = $1",
             l1_batch_number.0 as i64
         )
         .fetch_all(self.storage.conn())
-        .await
-        .unwrap()
-        .into_iter()
-        .map(|row| (H256::from_slice(&row.bytecode_hash), row.bytecode))
-        .collect()
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| (H256::from_slice(&row.bytecode_hash), row.bytecode))
+            .collect())
     }
 
     pub async fn get_miniblock_range_of_l1_batch(
         &mut self,
         l1_batch_number: L1BatchNumber,
-    ) -> Option<(MiniblockNumber, MiniblockNumber)> {
+    ) -> DalResult<Option<(MiniblockNumber, MiniblockNumber)>> {
         let row = sqlx::query!(
             "SELECT MIN(miniblocks.number) as \"min?\", MAX(miniblocks.number) as \"max?\" \
             FROM miniblocks \
@@ -266,20 +337,19 @@ impl BlocksDal<'_, '_> {
             l1_batch_number.0 as i64
         )
         .fetch_one(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
 
-        Some((
-            MiniblockNumber(row.min? as u32),
-            MiniblockNumber(row.max? as u32),
-        ))
+        Ok(row
+            .min
+            .zip(row.max)
+            .map(|(min, max)| (MiniblockNumber(min as u32), MiniblockNumber(max as u32))))
     }
 
     pub async fn get_miniblock_header(
         &mut self,
         miniblock_number: MiniblockNumber,
-    ) -> Option<MiniblockHeader> {
-        sqlx::query_as!(
+    ) -> DalResult<Option<MiniblockHeader>> {
+        let header = sqlx::query_as!(
             StorageMiniblockHeader,
             "SELECT number, timestamp, hash, l1_tx_count, l2_tx_count, \
                 bootloader_code_hash, default_aa_code_hash, protocol_version \
@@ -288,59 +358,58 @@ impl BlocksDal<'_, '_> {
             miniblock_number.0 as i64,
         )
         .fetch_optional(self.storage.conn())
-        .await
-        .unwrap()
-        .map(Into::into)
+        .await?;
+        Ok(header.map(Into::into))
     }
 
     pub async fn get_l1_batch_state_root_and_timestamp(
         &mut self,
         number: L1BatchNumber,
-    ) -> Option<(H256, u64)> {
+    ) -> DalResult<Option<(H256, u64)>> {
         let row = sqlx::query!(
             "SELECT timestamp, hash FROM l1_batches WHERE number = $1",
             number.0 as i64
         )
         .fetch_optional(self.storage.conn())
-        .await
-        .unwrap()?;
+        .await?;
 
-        Some((H256::from_slice(&row.hash?), row.timestamp as u64))
+        Ok(row.and_then(|row| Some((H256::from_slice(&row.hash?), row.timestamp as u64))))
     }
 
     pub async fn get_miniblock_timestamp(
         &mut self,
         miniblock_number: MiniblockNumber,
-    ) -> Option<u64> {
-        sqlx::query!(
+    ) -> DalResult<Option<u64>> {
+        let row = sqlx::query!(
             "SELECT timestamp FROM miniblocks WHERE number = $1",
             miniblock_number.0 as i64,
         )
         .fetch_optional(self.storage.conn())
-        .await
-        .unwrap()
-        .map(|row| row.timestamp as u64)
+        .await?;
+        Ok(row.map(|row| row.timestamp as u64))
     }
 
     pub async fn get_batch_protocol_version_id(
         &mut self,
         l1_batch_number: L1BatchNumber,
-    ) -> Option<ProtocolVersionId> {
-        {
-            let row = sqlx::query!(
-                "SELECT protocol_version FROM l1_batches WHERE number = $1",
-                l1_batch_number.0 as i64
-            )
-            .fetch_optional(self.storage.conn())
-            .await
-            .unwrap()?;
-            row.protocol_version.map(|v| (v as u16).try_into().unwrap())
-        }
+    ) -> DalResult<Option<ProtocolVersionId>> {
+        let row = sqlx::query!(
+            "SELECT protocol_version FROM l1_batches WHERE number = $1",
+            l1_batch_number.0 as i64
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+        row.and_then(|row| row.protocol_version)
+            .map(|version| ProtocolVersionId::try_from(version as u16).map_err(DalError::decode))
+            .transpose()
     }
 
     #[olaos_logs::instrument(name = "get_l1_batch_header", skip_all)]
-    pub async fn get_l1_batch_header(&mut self, number: L1BatchNumber) -> Option<L1BatchHeader> {
-        sqlx::query_as!(
+    pub async fn get_l1_batch_header(
+        &mut self,
+        number: L1BatchNumber,
+    ) -> DalResult<Option<L1BatchHeader>> {
+        let header = sqlx::query_as!(
             StorageL1BatchHeader,
             "SELECT number, l1_tx_count, l2_tx_count, \
                 timestamp, is_finished, fee_account_address, used_contract_hashes, \
@@ -350,12 +419,14 @@ impl BlocksDal<'_, '_> {
             number.0 as i64
         )
         .fetch_optional(self.storage.conn())
-        .await
-        .unwrap()
-        .map(Into::into)
+        .await?;
+        Ok(header.map(Into::into))
     }
 
-    pub async fn save_genesis_l1_batch_metadata(&mut self, metadata: &L1BatchMetadata) {
+    pub async fn save_genesis_l1_batch_metadata(
+        &mut self,
+        metadata: &L1BatchMetadata,
+    ) -> DalResult<()> {
         sqlx::query!(
             "UPDATE l1_batches \
             SET hash = $1, merkle_root_hash = $2, commitment = $3, default_aa_code_hash = $4, \
@@ -378,20 +449,21 @@ impl BlocksDal<'_, '_> {
             0,
         )
         .execute(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
+        Ok(())
     }
 
-    pub async fn get_last_l1_batch_number_with_metadata(&mut self) -> L1BatchNumber {
+    /// Returns the number of the last L1 batch with metadata, or [`DalError::NotFound`]
+    /// before genesis.
+    pub async fn get_last_l1_batch_number_with_metadata(&mut self) -> DalResult<L1BatchNumber> {
         let number =
             sqlx::query!("SELECT MAX(number) as \"number\" FROM l1_batches WHERE hash IS NOT NULL")
                 .fetch_one(self.storage.conn())
-                .await
-                .unwrap()
+                .await?
                 .number
-                .expect("DAL invocation before genesis");
+                .ok_or(DalError::NotFound)?;
 
-        L1BatchNumber(number as u32)
+        Ok(L1BatchNumber(number as u32))
     }
 
     pub async fn save_l1_batch_metadata(
@@ -460,7 +532,7 @@ impl BlocksDal<'_, '_> {
     }
 
     /// Returns the number of the earliest L1 batch present in the DB, or `None` if there are no L1 batches.
-    pub async fn get_earliest_l1_batch_number(&mut self) -> sqlx::Result<Option<L1BatchNumber>> {
+    pub async fn get_earliest_l1_batch_number(&mut self) -> DalResult<Option<L1BatchNumber>> {
         let row = sqlx::query!(
             r#"
             SELECT
@@ -479,7 +551,7 @@ impl BlocksDal<'_, '_> {
         &mut self,
         fee_address: &mut Address,
         miniblock_number: MiniblockNumber,
-    ) -> sqlx::Result<()> {
+    ) -> DalResult<()> {
         if *fee_address != Address::default() {
             return Ok(());
         }
@@ -509,3 +581,4 @@ impl BlocksDal<'_, '_> {
         Ok(())
     }
 }
+
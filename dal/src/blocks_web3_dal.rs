@@ -1,5 +1,7 @@
 use bigdecimal::BigDecimal;
-use ola_types::{api, L1BatchNumber, L2ChainId, MiniblockNumber, H256, U256, U64};
+use ola_types::{
+    api, receipts_tree::ReceiptLeaf, L1BatchNumber, L2ChainId, MiniblockNumber, H256, U256, U64,
+};
 use ola_utils::bigdecimal_to_u256;
 use sqlx::Row;
 
@@ -9,7 +11,7 @@ use crate::models::storage_block::{
 use crate::models::storage_transaction::{extract_web3_transaction, web3_transaction_select_sql};
 use crate::{
     models::storage_block::{bind_block_where_sql_params, web3_block_number_to_sql},
-    SqlxError, StorageProcessor,
+    DalResult, StorageProcessor,
 };
 
 use ola_constants::blocks::EMPTY_UNCLES_HASH;
@@ -27,12 +29,45 @@ impl BlocksWeb3Dal<'_, '_> {
         block_id: api::BlockId,
         include_full_transactions: bool,
         chain_id: L2ChainId,
-    ) -> sqlx::Result<Option<api::Block<api::TransactionVariant>>> {
+    ) -> DalResult<Option<api::Block<api::TransactionVariant>>> {
+        let block = self
+            .get_block_with_tx_count(block_id, include_full_transactions, None, chain_id)
+            .await?;
+        Ok(block.map(|(block, _)| block))
+    }
+
+    /// Same as [`Self::get_block_by_web3_block_id()`] with full transactions, but transactions are
+    /// only loaded if the block has at most `max_transactions` of them. The block is returned
+    /// together with its transaction count; if the count exceeds `max_transactions`, the block
+    /// has no transactions. The block is resolved and checked in a single query, so that the check
+    /// cannot go stale (e.g., if `block_id` is `latest`).
+    pub async fn get_block_with_limited_transactions(
+        &mut self,
+        block_id: api::BlockId,
+        max_transactions: u32,
+        chain_id: L2ChainId,
+    ) -> DalResult<Option<(api::Block<api::TransactionVariant>, u64)>> {
+        self.get_block_with_tx_count(block_id, true, Some(max_transactions), chain_id)
+            .await
+    }
+
+    async fn get_block_with_tx_count(
+        &mut self,
+        block_id: api::BlockId,
+        include_full_transactions: bool,
+        max_transactions: Option<u32>,
+        chain_id: L2ChainId,
+    ) -> DalResult<Option<(api::Block<api::TransactionVariant>, u64)>> {
         let transactions_sql = if include_full_transactions {
             web3_transaction_select_sql()
         } else {
             "transactions.hash as tx_hash"
         };
+        // The limit is a plain integer, so it's inlined to keep parameter indices of the block ID
+        // intact.
+        let transactions_condition = max_transactions.map_or_else(String::new, |max| {
+            format!(" AND miniblocks.l1_tx_count + miniblocks.l2_tx_count <= {max}")
+        });
 
         let query = format!(
             "SELECT
@@ -42,6 +77,7 @@ impl BlocksWeb3Dal<'_, '_> {
                 miniblocks.timestamp,
                 prev_miniblock.hash as parent_hash,
                 l1_batches.timestamp as l1_batch_timestamp,
+                miniblocks.l1_tx_count + miniblocks.l2_tx_count AS tx_count,
                 {}
             FROM miniblocks
             LEFT JOIN miniblocks prev_miniblock
@@ -49,10 +85,11 @@ impl BlocksWeb3Dal<'_, '_> {
             LEFT JOIN l1_batches
                 ON l1_batches.number = miniblocks.l1_batch_number
             LEFT JOIN transactions
-                ON transactions.miniblock_number = miniblocks.number
+                ON transactions.miniblock_number = miniblocks.number{}
             WHERE {}
             ORDER BY transactions.index_in_block ASC",
             transactions_sql,
+            transactions_condition,
             web3_block_where_sql(block_id, 1)
         );
 
@@ -60,7 +97,7 @@ impl BlocksWeb3Dal<'_, '_> {
         let rows = query.fetch_all(self.storage.conn()).await?.into_iter();
 
         let block = rows.fold(None, |prev_block, db_row| {
-            let mut block = prev_block.unwrap_or_else(|| {
+            let (mut block, tx_count) = prev_block.unwrap_or_else(|| {
                 // This code will be only executed for the first row in the DB response.
                 // All other rows will only be used to extract relevant transactions.
                 let hash = db_row
@@ -79,7 +116,9 @@ impl BlocksWeb3Dal<'_, '_> {
                     .try_get("parent_hash")
                     .map_or_else(|_| H256::zero(), H256::from_slice);
 
-                api::Block {
+                let tx_count = db_row.get::<i32, &str>("tx_count") as u64;
+
+                let block = api::Block {
                     hash,
                     parent_hash,
                     uncles_hash: EMPTY_UNCLES_HASH,
@@ -89,7 +128,8 @@ impl BlocksWeb3Dal<'_, '_> {
                     l1_batch_timestamp,
                     // TODO: include logs
                     ..api::Block::default()
-                }
+                };
+                (block, tx_count)
             });
             if db_row.try_get::<&[u8], &str>("tx_hash").is_ok() {
                 let tx = if include_full_transactions {
@@ -100,15 +140,63 @@ impl BlocksWeb3Dal<'_, '_> {
                 };
                 block.transactions.push(tx);
             }
-            Some(block)
+            Some((block, tx_count))
         });
         Ok(block)
     }
 
+    /// Returns headers of miniblocks in the inclusive range `from..=to`, ordered by number.
+    pub async fn get_block_headers(
+        &mut self,
+        from: MiniblockNumber,
+        to: MiniblockNumber,
+    ) -> DalResult<Vec<api::Block<H256>>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                miniblocks.hash,
+                miniblocks.number,
+                miniblocks.l1_batch_number,
+                miniblocks.timestamp,
+                prev_miniblock.hash AS "parent_hash?",
+                l1_batches.timestamp AS "l1_batch_timestamp?"
+            FROM
+                miniblocks
+                LEFT JOIN miniblocks prev_miniblock ON prev_miniblock.number = miniblocks.number - 1
+                LEFT JOIN l1_batches ON l1_batches.number = miniblocks.l1_batch_number
+            WHERE
+                miniblocks.number BETWEEN $1 AND $2
+            ORDER BY
+                miniblocks.number
+            "#,
+            from.0 as i64,
+            to.0 as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let headers = rows
+            .into_iter()
+            .map(|row| api::Block {
+                hash: H256::from_slice(&row.hash),
+                parent_hash: row
+                    .parent_hash
+                    .map_or_else(H256::zero, |hash| H256::from_slice(&hash)),
+                uncles_hash: EMPTY_UNCLES_HASH,
+                number: U64::from(row.number),
+                l1_batch_number: row.l1_batch_number.map(U64::from),
+                timestamp: row.timestamp.into(),
+                l1_batch_timestamp: row.l1_batch_timestamp.map(U256::from),
+                ..api::Block::default()
+            })
+            .collect();
+        Ok(headers)
+    }
+
     pub async fn get_block_tx_count(
         &mut self,
         block_id: api::BlockId,
-    ) -> sqlx::Result<Option<(MiniblockNumber, U256)>> {
+    ) -> DalResult<Option<(MiniblockNumber, U256)>> {
         let query = format!(
             "SELECT number, l1_tx_count + l2_tx_count AS tx_count FROM miniblocks WHERE {}",
             web3_block_where_sql(block_id, 1)
@@ -122,10 +210,48 @@ impl BlocksWeb3Dal<'_, '_> {
         }))
     }
 
+    /// Returns up to `limit` transactions of the block with positions greater than `after`,
+    /// ordered by the position, together with the block number and the total number
+    /// of transactions in the block.
+    pub async fn get_block_transactions_page(
+        &mut self,
+        block_id: api::BlockId,
+        after: Option<u32>,
+        limit: u32,
+        chain_id: L2ChainId,
+    ) -> DalResult<Option<(MiniblockNumber, U256, Vec<api::Transaction>)>> {
+        let Some((block_number, tx_count)) = self.get_block_tx_count(block_id).await? else {
+            return Ok(None);
+        };
+
+        let query = format!(
+            "SELECT {}
+            FROM transactions
+            INNER JOIN miniblocks
+                ON miniblocks.number = transactions.miniblock_number
+            WHERE transactions.miniblock_number = $1
+                AND transactions.index_in_block > $2
+            ORDER BY transactions.index_in_block ASC
+            LIMIT $3",
+            web3_transaction_select_sql()
+        );
+        let rows = sqlx::query(&query)
+            .bind(block_number.0 as i64)
+            .bind(after.map_or(-1, i64::from))
+            .bind(limit as i64)
+            .fetch_all(self.storage.conn())
+            .await?;
+        let transactions = rows
+            .into_iter()
+            .map(|row| extract_web3_transaction(row, chain_id))
+            .collect();
+        Ok(Some((block_number, tx_count, transactions)))
+    }
+
     pub async fn get_miniblock_hash(
         &mut self,
         block_number: MiniblockNumber,
-    ) -> sqlx::Result<Option<H256>> {
+    ) -> DalResult<Option<H256>> {
         let hash = sqlx::query!(
             r#"
             SELECT
@@ -146,7 +272,7 @@ impl BlocksWeb3Dal<'_, '_> {
     pub async fn get_l1_batch_number_of_miniblock(
         &mut self,
         miniblock_number: MiniblockNumber,
-    ) -> sqlx::Result<Option<L1BatchNumber>> {
+    ) -> DalResult<Option<L1BatchNumber>> {
         let number: Option<i64> = sqlx::query!(
             r#"
             SELECT
@@ -165,10 +291,45 @@ impl BlocksWeb3Dal<'_, '_> {
         Ok(number.map(|number| L1BatchNumber(number as u32)))
     }
 
+    /// Returns leaves of the receipts tree of an L1 batch, ordered by the index of transactions
+    /// in the batch.
+    pub async fn get_l1_batch_receipt_leaves(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> DalResult<Vec<ReceiptLeaf>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                hash,
+                l1_batch_tx_index AS "l1_batch_tx_index!",
+                error IS NULL AS "succeeded!"
+            FROM
+                transactions
+            WHERE
+                l1_batch_number = $1
+                AND l1_batch_tx_index IS NOT NULL
+            ORDER BY
+                l1_batch_tx_index
+            "#,
+            l1_batch_number.0 as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ReceiptLeaf {
+                tx_hash: H256::from_slice(&row.hash),
+                index_in_batch: row.l1_batch_tx_index as u32,
+                succeeded: row.succeeded,
+            })
+            .collect())
+    }
+
     pub async fn get_miniblock_range_of_l1_batch(
         &mut self,
         l1_batch_number: L1BatchNumber,
-    ) -> sqlx::Result<Option<(MiniblockNumber, MiniblockNumber)>> {
+    ) -> DalResult<Option<(MiniblockNumber, MiniblockNumber)>> {
         let row = sqlx::query!(
             r#"
             SELECT
@@ -196,7 +357,7 @@ impl BlocksWeb3Dal<'_, '_> {
     pub async fn resolve_block_id(
         &mut self,
         block_id: api::BlockId,
-    ) -> Result<Option<MiniblockNumber>, SqlxError> {
+    ) -> DalResult<Option<MiniblockNumber>> {
         let query_string = match block_id {
             api::BlockId::Hash(_) => "SELECT number FROM miniblocks WHERE hash = $1".to_owned(),
             api::BlockId::Number(api::BlockNumber::Number(_)) => {
@@ -224,7 +385,7 @@ impl BlocksWeb3Dal<'_, '_> {
     pub async fn get_block_details(
         &mut self,
         block_number: MiniblockNumber,
-    ) -> sqlx::Result<Option<api::BlockDetails>> {
+    ) -> DalResult<Option<api::BlockDetails>> {
         let res = sqlx::query_as!(
             StorageBlockDetails,
             r#"
@@ -245,8 +406,7 @@ impl BlocksWeb3Dal<'_, '_> {
             block_number.0 as i64,
         )
         .fetch_optional(self.storage.conn())
-        .await
-        .unwrap()
+        .await?
         .map(Into::into);
 
         Ok(res)
@@ -255,30 +415,250 @@ impl BlocksWeb3Dal<'_, '_> {
     pub async fn get_l1_batch_details(
         &mut self,
         l1_batch_number: L1BatchNumber,
-    ) -> sqlx::Result<Option<api::L1BatchDetails>> {
+    ) -> DalResult<Option<api::L1BatchDetails>> {
         let res = sqlx::query_as!(
             StorageL1BatchDetails,
             r#"
             SELECT
-                number,
-                timestamp,      
-                l1_tx_count,
-                l2_tx_count,
-                hash,
-                bootloader_code_hash,
-                default_aa_code_hash
-            FROM 
+                l1_batches.number,
+                l1_batches.timestamp,
+                l1_batches.l1_tx_count,
+                l1_batches.l2_tx_count,
+                l1_batches.hash,
+                l1_batches.bootloader_code_hash,
+                l1_batches.default_aa_code_hash,
+                basic_witness_input_producer_jobs.stage_timings AS witness_input_stage_timings,
+                witness_inputs_fri.stage_timings AS witness_stage_timings
+            FROM
                 l1_batches
-            WHERE 
-                number = $1
+                LEFT JOIN basic_witness_input_producer_jobs ON basic_witness_input_producer_jobs.l1_batch_number = l1_batches.number
+                LEFT JOIN witness_inputs_fri ON witness_inputs_fri.l1_batch_number = l1_batches.number
+            WHERE
+                l1_batches.number = $1
             "#,
             l1_batch_number.0 as i64
         )
         .fetch_optional(self.storage.conn())
-        .await
-        .unwrap()
+        .await?
         .map(Into::into);
 
         Ok(res)
     }
+
+    /// Returns numbers of L1 batches that have reached `status`, in ascending order, starting
+    /// from `from` and skipping `exclude`.
+    pub async fn get_l1_batch_numbers_with_status(
+        &mut self,
+        status: api::L1BatchStatus,
+        from: L1BatchNumber,
+        exclude: &[L1BatchNumber],
+        limit: usize,
+    ) -> DalResult<Vec<L1BatchNumber>> {
+        let from = i64::from(from.0);
+        let exclude: Vec<_> = exclude.iter().map(|number| i64::from(number.0)).collect();
+        let limit = limit as i64;
+        let numbers: Vec<i64> = match status {
+            api::L1BatchStatus::Sealed => {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT
+                        number
+                    FROM
+                        l1_batches
+                    WHERE
+                        number >= $1
+                        AND NOT (number = ANY ($2))
+                    ORDER BY
+                        number
+                    LIMIT
+                        $3
+                    "#,
+                    from,
+                    &exclude,
+                    limit
+                )
+                .fetch_all(self.storage.conn())
+                .await?
+            }
+            api::L1BatchStatus::Committed => {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT
+                        number
+                    FROM
+                        l1_batches
+                    WHERE
+                        number >= $1
+                        AND NOT (number = ANY ($2))
+                        AND hash IS NOT NULL
+                        AND commitment IS NOT NULL
+                    ORDER BY
+                        number
+                    LIMIT
+                        $3
+                    "#,
+                    from,
+                    &exclude,
+                    limit
+                )
+                .fetch_all(self.storage.conn())
+                .await?
+            }
+            api::L1BatchStatus::Proven => {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT
+                        l1_batch_number
+                    FROM
+                        proof_generation_details
+                    WHERE
+                        l1_batch_number >= $1
+                        AND NOT (l1_batch_number = ANY ($2))
+                        AND status = 'generated'
+                    ORDER BY
+                        l1_batch_number
+                    LIMIT
+                        $3
+                    "#,
+                    from,
+                    &exclude,
+                    limit
+                )
+                .fetch_all(self.storage.conn())
+                .await?
+            }
+            api::L1BatchStatus::Executed => {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT
+                        l1_batch_number
+                    FROM
+                        proof_offchain_verification_details
+                    WHERE
+                        l1_batch_number >= $1
+                        AND NOT (l1_batch_number = ANY ($2))
+                        AND status = 'offchain_verify_passed'
+                    ORDER BY
+                        l1_batch_number
+                    LIMIT
+                        $3
+                    "#,
+                    from,
+                    &exclude,
+                    limit
+                )
+                .fetch_all(self.storage.conn())
+                .await?
+            }
+        };
+        Ok(numbers
+            .into_iter()
+            .map(|number| L1BatchNumber(number as u32))
+            .collect())
+    }
+
+    /// Returns details of up to `limit` sealed L1 batches with numbers greater than `after`,
+    /// ordered by the number.
+    pub async fn get_l1_batch_details_page(
+        &mut self,
+        after: Option<L1BatchNumber>,
+        limit: usize,
+    ) -> DalResult<Vec<api::L1BatchDetails>> {
+        let after = after.map_or(-1, |number| i64::from(number.0));
+        let rows = sqlx::query_as!(
+            StorageL1BatchDetails,
+            r#"
+            SELECT
+                l1_batches.number,
+                l1_batches.timestamp,
+                l1_batches.l1_tx_count,
+                l1_batches.l2_tx_count,
+                l1_batches.hash,
+                l1_batches.bootloader_code_hash,
+                l1_batches.default_aa_code_hash,
+                basic_witness_input_producer_jobs.stage_timings AS witness_input_stage_timings,
+                witness_inputs_fri.stage_timings AS witness_stage_timings
+            FROM
+                l1_batches
+                LEFT JOIN basic_witness_input_producer_jobs ON basic_witness_input_producer_jobs.l1_batch_number = l1_batches.number
+                LEFT JOIN witness_inputs_fri ON witness_inputs_fri.l1_batch_number = l1_batches.number
+            WHERE
+                l1_batches.number > $1
+                AND l1_batches.is_finished
+            ORDER BY
+                l1_batches.number ASC
+            LIMIT
+                $2
+            "#,
+            after,
+            limit as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get_last_l1_batch_number_with_status(
+        &mut self,
+        status: api::L1BatchStatus,
+    ) -> DalResult<Option<L1BatchNumber>> {
+        let number = match status {
+            api::L1BatchStatus::Sealed => {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT
+                        MAX(number)
+                    FROM
+                        l1_batches
+                    "#
+                )
+                .fetch_one(self.storage.conn())
+                .await?
+            }
+            api::L1BatchStatus::Committed => {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT
+                        MAX(number)
+                    FROM
+                        l1_batches
+                    WHERE
+                        hash IS NOT NULL
+                        AND commitment IS NOT NULL
+                    "#
+                )
+                .fetch_one(self.storage.conn())
+                .await?
+            }
+            api::L1BatchStatus::Proven => {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT
+                        MAX(l1_batch_number)
+                    FROM
+                        proof_generation_details
+                    WHERE
+                        status = 'generated'
+                    "#
+                )
+                .fetch_one(self.storage.conn())
+                .await?
+            }
+            api::L1BatchStatus::Executed => {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT
+                        MAX(l1_batch_number)
+                    FROM
+                        proof_offchain_verification_details
+                    WHERE
+                        status = 'offchain_verify_passed'
+                    "#
+                )
+                .fetch_one(self.storage.conn())
+                .await?
+            }
+        };
+        Ok(number.map(|number| L1BatchNumber(number as u32)))
+    }
 }
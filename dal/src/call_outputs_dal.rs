@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use ola_types::H256;
+
+use crate::{time_utils::pg_interval_from_duration, DalResult, StorageProcessor};
+
+/// Index of call outputs spilled to the object store. The objects themselves are managed
+/// by the caller; the index is used to enforce the total size quota and retention of outputs.
+#[derive(Debug)]
+pub struct CallOutputsDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl CallOutputsDal<'_, '_> {
+    /// Records a spilled output. Returns `false` if the output is already recorded.
+    pub async fn insert_output(&mut self, hash: H256, size: u64) -> DalResult<bool> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO call_outputs (hash, size, created_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (hash) DO NOTHING
+            "#,
+            hash.as_bytes(),
+            size as i64
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Returns hashes of the oldest outputs which don't fit into `quota_bytes` together with
+    /// the newer outputs.
+    pub async fn get_outputs_over_quota(&mut self, quota_bytes: u64) -> DalResult<Vec<H256>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT hash AS "hash!"
+            FROM (
+                SELECT hash, SUM(size) OVER (ORDER BY created_at DESC, hash) AS total_size
+                FROM call_outputs
+            ) AS outputs
+            WHERE total_size > $1
+            "#,
+            quota_bytes as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| H256::from_slice(&row.hash))
+            .collect())
+    }
+
+    /// Returns hashes of up to `limit` outputs recorded more than `retention` ago, oldest first.
+    pub async fn get_expired_outputs(
+        &mut self,
+        retention: Duration,
+        limit: usize,
+    ) -> DalResult<Vec<H256>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT hash
+            FROM call_outputs
+            WHERE created_at < NOW() - $1::INTERVAL
+            ORDER BY created_at
+            LIMIT $2
+            "#,
+            pg_interval_from_duration(retention),
+            limit as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| H256::from_slice(&row.hash))
+            .collect())
+    }
+
+    pub async fn remove_outputs(&mut self, hashes: &[H256]) -> DalResult<()> {
+        let hashes: Vec<_> = hashes.iter().map(H256::as_bytes).collect();
+        sqlx::query!(
+            "DELETE FROM call_outputs WHERE hash = ANY($1)",
+            &hashes as &[&[u8]]
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+}
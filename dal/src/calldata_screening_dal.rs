@@ -0,0 +1,49 @@
+use ola_types::api::CalldataScreeningRules;
+
+use crate::{DalResult, StorageProcessor};
+
+/// Calldata screening rules set at runtime. Persisted rules take precedence over the rules file
+/// from the node configuration.
+#[derive(Debug)]
+pub struct CalldataScreeningDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl CalldataScreeningDal<'_, '_> {
+    /// Returns the persisted rules, or `None` if rules were never set at runtime.
+    pub async fn get_rules(&mut self) -> DalResult<Option<CalldataScreeningRules>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT rules
+            FROM calldata_screening_rules
+            "#
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        row.map(|row| {
+            serde_json::from_value(row.rules).map_err(|err| sqlx::Error::Decode(Box::new(err)))
+        })
+        .transpose()
+        .map_err(Into::into)
+    }
+
+    /// Persists `rules`, replacing the previously persisted ones.
+    pub async fn set_rules(&mut self, rules: &CalldataScreeningRules) -> DalResult<()> {
+        let rules = serde_json::to_value(rules).expect("failed serializing screening rules");
+        sqlx::query!(
+            r#"
+            INSERT INTO calldata_screening_rules (rules, updated_at)
+            VALUES ($1, NOW())
+            ON CONFLICT (id) DO UPDATE
+            SET
+                rules = excluded.rules,
+                updated_at = excluded.updated_at
+            "#,
+            &rules
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use ola_types::{block::MiniblockHeader, Address, L1BatchNumber, MiniblockNumber, U256};
+
+use crate::{facade::SealFuture, DalError, DalResult};
+
+/// In-memory stand-in for Postgres, served by [`ConnectionPool::Mock`](super::ConnectionPool).
+///
+/// Only queries exposed via [`DalFacade`](crate::facade::DalFacade) are supported; they return
+/// canned results after the configured latency, or fail with a [`DalError::Connection`] with
+/// the configured probability. Failures are pseudo-random, but deterministic for a given seed.
+/// Sealing a miniblock runs the provided seal future, which is expected to record the miniblock
+/// header using [`Self::insert_miniblock()`], and updates the sealed miniblock number.
+#[derive(Debug)]
+pub struct MockDb {
+    latency: Duration,
+    error_rate: f64,
+    rng_state: AtomicU64,
+    calls: AtomicUsize,
+    sealed_miniblock_number: Mutex<MiniblockNumber>,
+    sealed_l1_batch_number: Mutex<L1BatchNumber>,
+    nonces: Mutex<HashMap<Address, U256>>,
+    miniblocks: Mutex<BTreeMap<MiniblockNumber, MiniblockHeader>>,
+}
+
+impl Default for MockDb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockDb {
+    pub fn new() -> Self {
+        Self {
+            latency: Duration::ZERO,
+            error_rate: 0.0,
+            rng_state: AtomicU64::new(0x2545_f491_4f6c_dd1d),
+            calls: AtomicUsize::new(0),
+            sealed_miniblock_number: Mutex::new(MiniblockNumber(0)),
+            sealed_l1_batch_number: Mutex::new(L1BatchNumber(0)),
+            nonces: Mutex::new(HashMap::new()),
+            miniblocks: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Delays every query by `latency`.
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Fails queries with the given probability in `[0, 1]`.
+    pub fn with_error_rate(mut self, error_rate: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&error_rate),
+            "error rate must be in [0, 1], got {error_rate}"
+        );
+        self.error_rate = error_rate;
+        self
+    }
+
+    /// Sets the seed used to decide which queries fail.
+    pub fn with_seed(self, seed: u64) -> Self {
+        // Xorshift gets stuck at zero.
+        self.rng_state.store(seed.max(1), Ordering::Relaxed);
+        self
+    }
+
+    pub fn set_sealed_miniblock_number(&self, number: MiniblockNumber) {
+        *self.sealed_miniblock_number.lock().unwrap() = number;
+    }
+
+    pub fn set_sealed_l1_batch_number(&self, number: L1BatchNumber) {
+        *self.sealed_l1_batch_number.lock().unwrap() = number;
+    }
+
+    pub fn set_nonce(&self, address: Address, nonce: U256) {
+        self.nonces.lock().unwrap().insert(address, nonce);
+    }
+
+    /// Stores a miniblock header without simulating a query. Used by seal futures run
+    /// against the mock instead of the Postgres queries.
+    pub fn insert_miniblock(&self, header: MiniblockHeader) {
+        self.miniblocks
+            .lock()
+            .unwrap()
+            .insert(header.number, header);
+    }
+
+    /// Returns the last sealed miniblock without simulating a query.
+    pub fn current_sealed_miniblock_number(&self) -> MiniblockNumber {
+        *self.sealed_miniblock_number.lock().unwrap()
+    }
+
+    /// Returns the number of queries issued so far, including failed ones.
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::Relaxed)
+    }
+
+    async fn simulate_query(&self) -> DalResult<()> {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        if !self.latency.is_zero() {
+            tokio::time::sleep(self.latency).await;
+        }
+        if self.error_rate > 0.0 && self.next_random() < self.error_rate {
+            return Err(DalError::Connection(sqlx::Error::PoolTimedOut));
+        }
+        Ok(())
+    }
+
+    /// Returns a pseudo-random number in `[0, 1)`.
+    fn next_random(&self) -> f64 {
+        let mut state = self.rng_state.load(Ordering::Relaxed);
+        loop {
+            let mut next = state;
+            next ^= next << 13;
+            next ^= next >> 7;
+            next ^= next << 17;
+            match self.rng_state.compare_exchange_weak(
+                state,
+                next,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return (next >> 11) as f64 / (1_u64 << 53) as f64,
+                Err(actual) => state = actual,
+            }
+        }
+    }
+
+    pub(crate) async fn sealed_miniblock_number(&self) -> DalResult<MiniblockNumber> {
+        self.simulate_query().await?;
+        Ok(*self.sealed_miniblock_number.lock().unwrap())
+    }
+
+    pub(crate) async fn seal_miniblock(
+        &self,
+        number: MiniblockNumber,
+        seal: SealFuture<'_>,
+    ) -> DalResult<()> {
+        self.simulate_query().await?;
+        seal.await?;
+        *self.sealed_miniblock_number.lock().unwrap() = number;
+        Ok(())
+    }
+
+    pub(crate) async fn miniblock_header(
+        &self,
+        number: MiniblockNumber,
+    ) -> DalResult<Option<MiniblockHeader>> {
+        self.simulate_query().await?;
+        Ok(self.miniblocks.lock().unwrap().get(&number).cloned())
+    }
+
+    pub(crate) async fn sealed_l1_batch_number(&self) -> DalResult<L1BatchNumber> {
+        self.simulate_query().await?;
+        Ok(*self.sealed_l1_batch_number.lock().unwrap())
+    }
+
+    pub(crate) async fn address_nonce(&self, address: Address) -> DalResult<U256> {
+        self.simulate_query().await?;
+        Ok(self
+            .nonces
+            .lock()
+            .unwrap()
+            .get(&address)
+            .copied()
+            .unwrap_or_default())
+    }
+}
@@ -1,6 +1,9 @@
-use std::time::Duration;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use ola_utils::env_tools::parse_env;
+use ola_utils::{env_tools::parse_env, retry::RetryPolicy};
 use sqlx::{
     pool::PoolConnection,
     postgres::{PgConnectOptions, PgPoolOptions},
@@ -11,7 +14,10 @@ use crate::{
     get_master_database_url, get_prover_database_url, get_replica_database_url, StorageProcessor,
 };
 
+use self::mock::MockDb;
+
 pub mod holder;
+pub mod mock;
 
 const OLAOS_DATABASE_POOL_SIZE: u32 = 50;
 
@@ -73,6 +79,8 @@ impl ConnectionPoolBuilder {
 pub enum ConnectionPool {
     Real(PgPool),
     Test(PgPool),
+    /// In-memory pool for tests; only serves queries via [`DalFacade`](crate::facade::DalFacade).
+    Mock(Arc<MockDb>),
 }
 
 impl ConnectionPool {
@@ -100,43 +108,54 @@ impl ConnectionPool {
         self.access_storage_inner(Some(requester)).await
     }
 
-    async fn access_storage_inner(&self, _requester: Option<&'static str>) -> StorageProcessor<'_> {
+    async fn access_storage_inner(&self, requester: Option<&'static str>) -> StorageProcessor<'_> {
         match self {
             ConnectionPool::Real(real_pool) => {
+                let started_at = Instant::now();
                 let conn = Self::acquire_connection_retried(real_pool).await;
+                // Emitted within the caller's span, so e.g. API server logs it with the request's
+                // correlation ID attached.
+                tracing::debug!(
+                    requester = requester.unwrap_or("unknown"),
+                    elapsed = ?started_at.elapsed(),
+                    "Acquired DB connection"
+                );
                 StorageProcessor::from_pool(conn)
             }
             ConnectionPool::Test(_test_pool) => {
                 panic!("test pool not supported!")
             }
+            ConnectionPool::Mock(_) => {
+                panic!("mock pool only supports queries via `DalFacade`")
+            }
         }
     }
 
     async fn acquire_connection_retried(pool: &PgPool) -> PoolConnection<Postgres> {
-        const DB_CONNECTION_RETRIES: u32 = 3;
+        const DB_CONNECTION_ATTEMPTS: u32 = 4;
         const BACKOFF_INTERVAL: Duration = Duration::from_secs(1);
 
-        let mut retry_count = 0;
-        while retry_count < DB_CONNECTION_RETRIES {
-            let connection = pool.acquire().await;
-            match connection {
-                Ok(connection) => return connection,
-                Err(_) => {
-                    retry_count += 1;
-                }
-            };
-
-            tokio::time::sleep(BACKOFF_INTERVAL).await;
-        }
-        pool.acquire()
+        RetryPolicy::new(BACKOFF_INTERVAL)
+            .with_jitter(0.2)
+            .with_max_attempts(DB_CONNECTION_ATTEMPTS)
+            .retry("db_connection", || pool.acquire())
             .await
             .unwrap_or_else(|err| panic!("Failed getting a DB connection: {}", err))
     }
 
+    /// Closes the pool, waiting until all acquired connections are returned to it.
+    pub async fn close(&self) {
+        match self {
+            ConnectionPool::Real(pool) | ConnectionPool::Test(pool) => pool.close().await,
+            ConnectionPool::Mock(_) => {}
+        }
+    }
+
     pub fn max_size(&self) -> u32 {
         match self {
             ConnectionPool::Real(pool) => pool.options().get_max_connections(),
             ConnectionPool::Test(pool) => pool.options().get_max_connections(),
+            ConnectionPool::Mock(_) => 0,
         }
     }
 }
@@ -0,0 +1,218 @@
+use ola_types::{L1BatchNumber, MiniblockNumber};
+
+use crate::{DalResult, StorageProcessor};
+
+/// Maximum number of violations of a single invariant returned by a check.
+const MAX_REPORTED_VIOLATIONS: i64 = 100;
+
+/// Checks of invariants across DAL tables, and repairs of data derived from other tables.
+#[derive(Debug)]
+pub struct ConsistencyDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl ConsistencyDal<'_, '_> {
+    /// Returns pairs of consecutive miniblock numbers that aren't contiguous.
+    pub async fn get_miniblock_gaps(
+        &mut self,
+    ) -> DalResult<Vec<(MiniblockNumber, MiniblockNumber)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT number AS "number!", next_number AS "next_number!"
+            FROM (
+                SELECT number, LEAD(number) OVER (ORDER BY number) AS next_number
+                FROM miniblocks
+            ) AS numbers
+            WHERE next_number <> number + 1
+            ORDER BY number
+            LIMIT $1
+            "#,
+            MAX_REPORTED_VIOLATIONS
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                (
+                    MiniblockNumber(row.number as u32),
+                    MiniblockNumber(row.next_number as u32),
+                )
+            })
+            .collect())
+    }
+
+    /// Returns positions in miniblocks occupied by more than one transaction.
+    pub async fn get_duplicate_tx_indices(&mut self) -> DalResult<Vec<(MiniblockNumber, u32)>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT miniblock_number AS "miniblock_number!", index_in_block AS "index_in_block!"
+            FROM transactions
+            WHERE miniblock_number IS NOT NULL
+            GROUP BY miniblock_number, index_in_block
+            HAVING COUNT(*) > 1
+            ORDER BY miniblock_number, index_in_block
+            LIMIT $1
+            "#,
+            MAX_REPORTED_VIOLATIONS
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                (
+                    MiniblockNumber(row.miniblock_number as u32),
+                    row.index_in_block as u32,
+                )
+            })
+            .collect())
+    }
+
+    /// Returns the number of events referencing transactions that don't exist. Events
+    /// of archived transactions are archived together with them, so they aren't counted.
+    pub async fn count_orphaned_events(&mut self) -> DalResult<u64> {
+        let row = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM events
+            WHERE NOT EXISTS (
+                SELECT 1 FROM transactions WHERE transactions.hash = events.tx_hash
+            )
+            "#
+        )
+        .fetch_one(self.storage.conn())
+        .await?;
+        Ok(row.count as u64)
+    }
+
+    /// Returns L1 batches whose miniblocks don't directly follow the miniblocks of the previous
+    /// L1 batch, or aren't contiguous themselves.
+    pub async fn get_misaligned_l1_batches(&mut self) -> DalResult<Vec<L1BatchNumber>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT l1_batch_number AS "l1_batch_number!"
+            FROM (
+                SELECT
+                    l1_batch_number,
+                    MIN(number) AS first_miniblock,
+                    MAX(number) AS last_miniblock,
+                    COUNT(*) AS miniblock_count,
+                    LAG(MAX(number)) OVER (ORDER BY l1_batch_number) AS prev_last_miniblock,
+                    LAG(l1_batch_number) OVER (ORDER BY l1_batch_number) AS prev_l1_batch_number
+                FROM miniblocks
+                WHERE l1_batch_number IS NOT NULL
+                GROUP BY l1_batch_number
+            ) AS ranges
+            WHERE
+                last_miniblock - first_miniblock + 1 <> miniblock_count
+                OR (
+                    prev_l1_batch_number IS NOT NULL
+                    AND (
+                        prev_l1_batch_number <> l1_batch_number - 1
+                        OR prev_last_miniblock <> first_miniblock - 1
+                    )
+                )
+            ORDER BY l1_batch_number
+            LIMIT $1
+            "#,
+            MAX_REPORTED_VIOLATIONS
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| L1BatchNumber(row.l1_batch_number as u32))
+            .collect())
+    }
+
+    /// Recomputes transaction counts of miniblocks in L1 batches after `last_archived_l1_batch`
+    /// (transactions of earlier batches are no longer in Postgres) and of pending miniblocks.
+    /// Returns the number of repaired miniblocks.
+    pub async fn repair_miniblock_tx_counts(
+        &mut self,
+        last_archived_l1_batch: Option<L1BatchNumber>,
+    ) -> DalResult<u64> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE miniblocks
+            SET
+                l1_tx_count = counts.l1_tx_count,
+                l2_tx_count = counts.l2_tx_count,
+                updated_at = NOW()
+            FROM (
+                SELECT
+                    miniblocks.number,
+                    COUNT(transactions.hash) FILTER (WHERE transactions.is_priority)::INT AS l1_tx_count,
+                    COUNT(transactions.hash) FILTER (WHERE NOT transactions.is_priority)::INT AS l2_tx_count
+                FROM
+                    miniblocks
+                    LEFT JOIN transactions ON transactions.miniblock_number = miniblocks.number
+                WHERE
+                    miniblocks.l1_batch_number IS NULL
+                    OR miniblocks.l1_batch_number > $1
+                GROUP BY miniblocks.number
+            ) AS counts
+            WHERE
+                miniblocks.number = counts.number
+                AND (
+                    miniblocks.l1_tx_count <> counts.l1_tx_count
+                    OR miniblocks.l2_tx_count <> counts.l2_tx_count
+                )
+            "#,
+            last_archived_l1_batch.map_or(-1, |number| i64::from(number.0))
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Copies transaction indices and initiators denormalized into events from the transactions.
+    /// Returns the number of repaired events.
+    pub async fn repair_event_tx_fields(&mut self) -> DalResult<u64> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE events
+            SET
+                tx_index_in_block = transactions.index_in_block,
+                tx_initiator_address = transactions.initiator_address,
+                updated_at = NOW()
+            FROM transactions
+            WHERE
+                events.tx_hash = transactions.hash
+                AND transactions.index_in_block IS NOT NULL
+                AND (
+                    events.tx_index_in_block <> transactions.index_in_block
+                    OR events.tx_initiator_address <> transactions.initiator_address
+                )
+            "#
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Sets L1 batch numbers of transactions to the L1 batches of their miniblocks.
+    /// Returns the number of repaired transactions.
+    pub async fn repair_tx_l1_batch_numbers(&mut self) -> DalResult<u64> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE transactions
+            SET
+                l1_batch_number = miniblocks.l1_batch_number,
+                updated_at = NOW()
+            FROM miniblocks
+            WHERE
+                transactions.miniblock_number = miniblocks.number
+                AND miniblocks.l1_batch_number IS NOT NULL
+                AND transactions.l1_batch_number IS DISTINCT FROM miniblocks.l1_batch_number
+            "#
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,113 @@
+use ola_types::{
+    api::{ContractResourceKind, ContractResourceUsage},
+    tx::tx_execution_info::ContractExecutionMetrics,
+    Address, L1BatchNumber,
+};
+
+use crate::{DalResult, StorageProcessor};
+
+/// Per-contract resource consumption aggregated per L1 batch.
+#[derive(Debug)]
+pub struct ContractMetricsDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl ContractMetricsDal<'_, '_> {
+    pub async fn insert_l1_batch_metrics(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        metrics: &[ContractExecutionMetrics],
+    ) -> DalResult<()> {
+        let mut addresses = Vec::with_capacity(metrics.len());
+        let mut tx_counts = Vec::with_capacity(metrics.len());
+        let mut cycles = Vec::with_capacity(metrics.len());
+        let mut storage_reads = Vec::with_capacity(metrics.len());
+        let mut storage_writes = Vec::with_capacity(metrics.len());
+        let mut events = Vec::with_capacity(metrics.len());
+        for contract in metrics {
+            addresses.push(contract.address.as_bytes());
+            tx_counts.push(i64::from(contract.tx_count));
+            cycles.push(contract.cycles_used as i64);
+            storage_reads.push(i64::from(contract.storage_reads));
+            storage_writes.push(i64::from(contract.storage_writes));
+            events.push(i64::from(contract.events));
+        }
+
+        sqlx::query!(
+            r#"
+            INSERT INTO contract_execution_metrics (
+                l1_batch_number, address, tx_count, cycles_used, storage_reads, storage_writes, events
+            )
+            SELECT $1, u.address, u.tx_count, u.cycles_used, u.storage_reads, u.storage_writes, u.events
+            FROM UNNEST($2::bytea[], $3::bigint[], $4::bigint[], $5::bigint[], $6::bigint[], $7::bigint[])
+                AS u (address, tx_count, cycles_used, storage_reads, storage_writes, events)
+            ON CONFLICT (l1_batch_number, address) DO NOTHING
+            "#,
+            l1_batch_number.0 as i64,
+            &addresses as &[&[u8]],
+            &tx_counts,
+            &cycles,
+            &storage_reads,
+            &storage_writes,
+            &events,
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Returns contracts with the highest consumption of `resource` in L1 batches with timestamps
+    /// in `from_timestamp..=to_timestamp` (in seconds).
+    pub async fn get_top_contracts(
+        &mut self,
+        from_timestamp: u64,
+        to_timestamp: u64,
+        resource: ContractResourceKind,
+        limit: usize,
+    ) -> DalResult<Vec<ContractResourceUsage>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                m.address,
+                SUM(m.tx_count)::BIGINT AS "tx_count!",
+                SUM(m.cycles_used)::BIGINT AS "cycles_used!",
+                SUM(m.storage_reads)::BIGINT AS "storage_reads!",
+                SUM(m.storage_writes)::BIGINT AS "storage_writes!",
+                SUM(m.events)::BIGINT AS "events!"
+            FROM
+                contract_execution_metrics m
+                INNER JOIN l1_batches ON l1_batches.number = m.l1_batch_number
+            WHERE
+                l1_batches.timestamp BETWEEN $1 AND $2
+            GROUP BY
+                m.address
+            ORDER BY
+                CASE $3
+                    WHEN 'transactions' THEN SUM(m.tx_count)
+                    WHEN 'cycles' THEN SUM(m.cycles_used)
+                    WHEN 'storage_ops' THEN SUM(m.storage_reads + m.storage_writes)
+                    ELSE SUM(m.events)
+                END DESC,
+                m.address
+            LIMIT
+                $4
+            "#,
+            from_timestamp as i64,
+            to_timestamp as i64,
+            resource.as_str(),
+            limit as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let rows = rows.into_iter().map(|row| ContractResourceUsage {
+            address: Address::from_slice(&row.address),
+            tx_count: row.tx_count as u64,
+            cycles_used: row.cycles_used as u64,
+            storage_reads: row.storage_reads as u64,
+            storage_writes: row.storage_writes as u64,
+            events: row.events as u64,
+        });
+        Ok(rows.collect())
+    }
+}
@@ -0,0 +1,106 @@
+//! Errors returned by DAL methods.
+
+use thiserror::Error;
+
+/// Postgres error classes (the first two characters of SQLSTATE codes).
+const INTEGRITY_CONSTRAINT_VIOLATION_CLASS: &str = "23";
+const CONNECTION_EXCEPTION_CLASS: &str = "08";
+const OPERATOR_INTERVENTION_CLASS: &str = "57";
+const SERIALIZATION_FAILURE_CODE: &str = "40001";
+const DEADLOCK_DETECTED_CODE: &str = "40P01";
+
+/// Error of a DAL method, classified so that callers can decide whether to retry the call
+/// and how to report it.
+#[derive(Debug, Error)]
+pub enum DalError {
+    /// The database is unreachable, or the connection was lost or timed out.
+    #[error("database connection error: {0}")]
+    Connection(#[source] sqlx::Error),
+    /// The query violates a constraint, e.g. inserts a duplicate of a unique key.
+    #[error("constraint {} violated: {source}", constraint.as_deref().unwrap_or("<unknown>"))]
+    ConstraintViolation {
+        constraint: Option<String>,
+        #[source]
+        source: sqlx::Error,
+    },
+    /// The transaction conflicted with a concurrent one (a serialization failure or deadlock).
+    #[error("transaction serialization failure: {0}")]
+    Serialization(#[source] sqlx::Error),
+    /// The requested row doesn't exist.
+    #[error("requested row is not found")]
+    NotFound,
+    /// Any other error, e.g. a malformed query or a value that cannot be decoded.
+    #[error("database query failed: {0}")]
+    Query(#[source] sqlx::Error),
+}
+
+impl DalError {
+    /// Returns `true` if the call may succeed when repeated as is.
+    pub fn is_retriable(&self) -> bool {
+        matches!(self, Self::Connection(_) | Self::Serialization(_))
+    }
+
+    /// Wraps an error converting a value to a query argument, e.g. serializing it to JSON.
+    pub(crate) fn encode(err: impl Into<sqlx::error::BoxDynError>) -> Self {
+        Self::Query(sqlx::Error::Encode(err.into()))
+    }
+
+    /// Wraps an error converting a returned row to a domain value.
+    pub(crate) fn decode(err: impl Into<sqlx::error::BoxDynError>) -> Self {
+        Self::Query(sqlx::Error::Decode(err.into()))
+    }
+}
+
+impl From<sqlx::Error> for DalError {
+    fn from(err: sqlx::Error) -> Self {
+        match &err {
+            sqlx::Error::RowNotFound => Self::NotFound,
+            sqlx::Error::Io(_)
+            | sqlx::Error::Tls(_)
+            | sqlx::Error::PoolTimedOut
+            | sqlx::Error::PoolClosed
+            | sqlx::Error::WorkerCrashed => Self::Connection(err),
+            sqlx::Error::Database(db_err) => {
+                let code = db_err.code().unwrap_or_default();
+                if code.starts_with(INTEGRITY_CONSTRAINT_VIOLATION_CLASS) {
+                    Self::ConstraintViolation {
+                        constraint: db_err.constraint().map(str::to_owned),
+                        source: err,
+                    }
+                } else if code == SERIALIZATION_FAILURE_CODE || code == DEADLOCK_DETECTED_CODE {
+                    Self::Serialization(err)
+                } else if code.starts_with(CONNECTION_EXCEPTION_CLASS)
+                    || code.starts_with(OPERATOR_INTERVENTION_CLASS)
+                {
+                    Self::Connection(err)
+                } else {
+                    Self::Query(err)
+                }
+            }
+            _ => Self::Query(err),
+        }
+    }
+}
+
+pub type DalResult<T> = Result<T, DalError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifying_errors() {
+        assert!(matches!(
+            DalError::from(sqlx::Error::RowNotFound),
+            DalError::NotFound
+        ));
+        let err = DalError::from(sqlx::Error::PoolTimedOut);
+        assert!(matches!(err, DalError::Connection(_)));
+        assert!(err.is_retriable());
+
+        let err = DalError::from(sqlx::Error::ColumnNotFound("hash".to_owned()));
+        assert!(matches!(err, DalError::Query(_)));
+        assert!(!err.is_retriable());
+        assert!(!DalError::NotFound.is_retriable());
+    }
+}
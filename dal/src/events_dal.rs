@@ -1,11 +1,12 @@
 use std::collections::HashMap;
 use std::fmt;
 
-use ola_types::{api, events::VmEvent, tx::IncludedTxLocation, MiniblockNumber, H256};
+use ola_types::{api, events::VmEvent, tx::IncludedTxLocation, Address, MiniblockNumber, H256};
 use sqlx::types::chrono::Utc;
 
+use crate::macro_utils::COPY_FLUSH_THRESHOLD_BYTES;
 use crate::models::storage_event::StorageWeb3Log;
-use crate::{SqlxError, StorageProcessor};
+use crate::{DalResult, StorageProcessor};
 #[derive(Debug)]
 struct EventTopic<'a>(Option<&'a H256>);
 
@@ -30,7 +31,11 @@ impl EventsDal<'_, '_> {
         &mut self,
         block_number: MiniblockNumber,
         all_block_events: &[(IncludedTxLocation, Vec<&VmEvent>)],
-    ) {
+    ) -> DalResult<()> {
+        if all_block_events.iter().all(|(_, events)| events.is_empty()) {
+            return Ok(()); // Shortcut to avoid opening a `COPY` stream with no data
+        }
+
         let mut copy = self
             .storage
             .conn()
@@ -44,8 +49,7 @@ impl EventsDal<'_, '_> {
                 )
                 FROM STDIN WITH (DELIMITER '|')",
             )
-            .await
-            .unwrap();
+            .await?;
 
         let mut buffer = String::new();
         let now = Utc::now().naive_utc().to_string();
@@ -79,27 +83,208 @@ impl EventsDal<'_, '_> {
                 );
 
                 event_index_in_block += 1;
+                if buffer.len() >= COPY_FLUSH_THRESHOLD_BYTES {
+                    copy.send(buffer.as_bytes()).await?;
+                    buffer.clear();
+                }
             }
         }
-        copy.send(buffer.as_bytes()).await.unwrap();
+        if !buffer.is_empty() {
+            copy.send(buffer.as_bytes()).await?;
+        }
         // note: all the time spent in this function is spent in `copy.finish()`
-        copy.finish().await.unwrap();
+        copy.finish().await?;
+        Ok(())
     }
 
     /// Removes events with a block number strictly greater than the specified `block_number`.
-    pub async fn rollback_events(&mut self, block_number: MiniblockNumber) {
+    pub async fn rollback_events(&mut self, block_number: MiniblockNumber) -> DalResult<()> {
         sqlx::query!(
             "DELETE FROM events WHERE miniblock_number > $1",
             block_number.0 as i64
         )
         .execute(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
+        Ok(())
     }
+
+    /// Returns logs emitted in miniblocks in the inclusive range `from..=to`, ordered by
+    /// the miniblock number and the index in the miniblock.
+    pub async fn get_logs_in_range(
+        &mut self,
+        from: MiniblockNumber,
+        to: MiniblockNumber,
+    ) -> DalResult<Vec<api::Log>> {
+        let logs: Vec<_> = sqlx::query_as!(
+            StorageWeb3Log,
+            r#"
+            SELECT
+                events.address,
+                events.topic1,
+                events.topic2,
+                events.topic3,
+                events.topic4,
+                events.value,
+                miniblocks.hash AS "block_hash?",
+                miniblocks.l1_batch_number AS "l1_batch_number?",
+                events.miniblock_number,
+                events.tx_hash,
+                events.tx_index_in_block,
+                events.event_index_in_block,
+                events.event_index_in_tx
+            FROM
+                events
+                INNER JOIN miniblocks ON miniblocks.number = events.miniblock_number
+            WHERE
+                events.miniblock_number BETWEEN $1 AND $2
+            ORDER BY
+                events.miniblock_number ASC,
+                events.event_index_in_block ASC
+            "#,
+            from.0 as i64,
+            to.0 as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+        Ok(logs.into_iter().map(api::Log::from).collect())
+    }
+
+    /// Returns up to `limit` logs emitted in miniblocks in the inclusive range `from..=to` which
+    /// match the filter, ordered by the miniblock number and the index in the miniblock. A log
+    /// matches if it's emitted by one of `addresses` and, for each `(position, topics)` pair,
+    /// its topic at the 1-based `position` is one of `topics`; empty `addresses` match any address.
+    pub async fn get_filtered_logs(
+        &mut self,
+        from: MiniblockNumber,
+        to: MiniblockNumber,
+        addresses: &[Address],
+        topics: &[(u32, Vec<H256>)],
+        limit: usize,
+    ) -> DalResult<Vec<api::Log>> {
+        let addresses: Vec<_> = addresses.iter().map(|addr| addr.0.to_vec()).collect();
+        let mut topics_by_position: [Vec<Vec<u8>>; 4] = Default::default();
+        for (position, topics) in topics {
+            let idx = *position as usize - 1;
+            topics_by_position[idx].extend(topics.iter().map(|topic| topic.0.to_vec()));
+        }
+        let [topics1, topics2, topics3, topics4] = topics_by_position;
+
+        let logs: Vec<_> = sqlx::query_as!(
+            StorageWeb3Log,
+            r#"
+            SELECT
+                events.address,
+                events.topic1,
+                events.topic2,
+                events.topic3,
+                events.topic4,
+                events.value,
+                miniblocks.hash AS "block_hash?",
+                miniblocks.l1_batch_number AS "l1_batch_number?",
+                events.miniblock_number,
+                events.tx_hash,
+                events.tx_index_in_block,
+                events.event_index_in_block,
+                events.event_index_in_tx
+            FROM
+                events
+                INNER JOIN miniblocks ON miniblocks.number = events.miniblock_number
+            WHERE
+                events.miniblock_number BETWEEN $1 AND $2
+                AND (
+                    CARDINALITY($3::bytea[]) = 0
+                    OR events.address = ANY ($3)
+                )
+                AND (
+                    CARDINALITY($4::bytea[]) = 0
+                    OR events.topic1 = ANY ($4)
+                )
+                AND (
+                    CARDINALITY($5::bytea[]) = 0
+                    OR events.topic2 = ANY ($5)
+                )
+                AND (
+                    CARDINALITY($6::bytea[]) = 0
+                    OR events.topic3 = ANY ($6)
+                )
+                AND (
+                    CARDINALITY($7::bytea[]) = 0
+                    OR events.topic4 = ANY ($7)
+                )
+            ORDER BY
+                events.miniblock_number ASC,
+                events.event_index_in_block ASC
+            LIMIT
+                $8
+            "#,
+            from.0 as i64,
+            to.0 as i64,
+            &addresses[..],
+            &topics1[..],
+            &topics2[..],
+            &topics3[..],
+            &topics4[..],
+            limit as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+        Ok(logs.into_iter().map(api::Log::from).collect())
+    }
+
+    /// Returns up to `limit` logs, optionally only emitted by `address`, ordered by the miniblock
+    /// number and the index in the miniblock and starting strictly after the `after` position.
+    /// Only logs of sealed miniblocks are returned.
+    pub async fn get_logs_page(
+        &mut self,
+        address: Option<Address>,
+        after: Option<(MiniblockNumber, u32)>,
+        limit: usize,
+    ) -> DalResult<Vec<api::Log>> {
+        let (after_miniblock, after_index) =
+            after.map_or((-1, -1), |(number, index)| (number.0 as i64, index as i32));
+        let logs: Vec<_> = sqlx::query_as!(
+            StorageWeb3Log,
+            r#"
+            SELECT
+                events.address,
+                events.topic1,
+                events.topic2,
+                events.topic3,
+                events.topic4,
+                events.value,
+                miniblocks.hash AS "block_hash?",
+                miniblocks.l1_batch_number AS "l1_batch_number?",
+                events.miniblock_number,
+                events.tx_hash,
+                events.tx_index_in_block,
+                events.event_index_in_block,
+                events.event_index_in_tx
+            FROM
+                events
+                INNER JOIN miniblocks ON miniblocks.number = events.miniblock_number
+            WHERE
+                ($1::bytea IS NULL OR events.address = $1)
+                AND (events.miniblock_number, events.event_index_in_block) > ($2, $3)
+            ORDER BY
+                events.miniblock_number ASC,
+                events.event_index_in_block ASC
+            LIMIT
+                $4
+            "#,
+            address.as_ref().map(Address::as_bytes),
+            after_miniblock,
+            after_index,
+            limit as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+        Ok(logs.into_iter().map(api::Log::from).collect())
+    }
+
     pub(crate) async fn get_logs_by_tx_hashes(
         &mut self,
         hashes: &[H256],
-    ) -> Result<HashMap<H256, Vec<api::Log>>, SqlxError> {
+    ) -> DalResult<HashMap<H256, Vec<api::Log>>> {
         let hashes = hashes
             .iter()
             .map(|hash| hash.as_bytes().to_vec())
@@ -137,9 +322,11 @@ impl EventsDal<'_, '_> {
         let mut result = HashMap::<H256, Vec<api::Log>>::new();
 
         for storage_log in logs {
-            let current_log = api::Log::from(storage_log);
-            let tx_hash = current_log.transaction_hash.unwrap();
-            result.entry(tx_hash).or_default().push(current_log);
+            let tx_hash = H256::from_slice(&storage_log.tx_hash);
+            result
+                .entry(tx_hash)
+                .or_default()
+                .push(api::Log::from(storage_log));
         }
 
         Ok(result)
@@ -0,0 +1,206 @@
+//! Narrow, trait-based view of the DAL used by components which should be testable without
+//! Postgres. [`ConnectionPool`] implements it for both real and mock pools.
+
+use std::{future::Future, pin::Pin};
+
+use async_trait::async_trait;
+use ola_types::{block::MiniblockHeader, Address, L1BatchNumber, MiniblockNumber, U256};
+
+use crate::{connection::ConnectionPool, DalResult};
+
+/// Future persisting a miniblock to a real database; see [`DalFacade::seal_miniblock()`].
+pub type SealFuture<'a> = Pin<Box<dyn Future<Output = DalResult<()>> + Send + 'a>>;
+
+#[async_trait]
+pub trait DalFacade: Send + Sync {
+    async fn sealed_miniblock_number(&self) -> DalResult<MiniblockNumber>;
+
+    async fn sealed_l1_batch_number(&self) -> DalResult<L1BatchNumber>;
+
+    /// Returns the nonce of `address` as of `block_number`. Mock pools ignore `block_number`.
+    async fn address_historical_nonce(
+        &self,
+        address: Address,
+        block_number: MiniblockNumber,
+    ) -> DalResult<U256>;
+
+    /// Returns the header of a sealed miniblock, or `None` if it isn't sealed.
+    async fn miniblock_header(&self, number: MiniblockNumber)
+        -> DalResult<Option<MiniblockHeader>>;
+
+    /// Persists the miniblock `number` by running `seal`. Mock pools run `seal` as well (it's
+    /// expected to record the miniblock in the mock), then mark `number` as the last sealed
+    /// miniblock.
+    async fn seal_miniblock(&self, number: MiniblockNumber, seal: SealFuture<'_>) -> DalResult<()>;
+}
+
+#[async_trait]
+impl DalFacade for ConnectionPool {
+    async fn sealed_miniblock_number(&self) -> DalResult<MiniblockNumber> {
+        if let ConnectionPool::Mock(db) = self {
+            return db.sealed_miniblock_number().await;
+        }
+        let mut storage = self.access_storage_tagged("dal_facade").await;
+        storage.blocks_dal().get_sealed_miniblock_number().await
+    }
+
+    async fn sealed_l1_batch_number(&self) -> DalResult<L1BatchNumber> {
+        if let ConnectionPool::Mock(db) = self {
+            return db.sealed_l1_batch_number().await;
+        }
+        let mut storage = self.access_storage_tagged("dal_facade").await;
+        storage.blocks_dal().get_sealed_l1_batch_number().await
+    }
+
+    async fn address_historical_nonce(
+        &self,
+        address: Address,
+        block_number: MiniblockNumber,
+    ) -> DalResult<U256> {
+        if let ConnectionPool::Mock(db) = self {
+            return db.address_nonce(address).await;
+        }
+        let mut storage = self.access_storage_tagged("dal_facade").await;
+        storage
+            .storage_web3_dal()
+            .get_address_historical_nonce(address, block_number)
+            .await
+    }
+
+    async fn miniblock_header(
+        &self,
+        number: MiniblockNumber,
+    ) -> DalResult<Option<MiniblockHeader>> {
+        if let ConnectionPool::Mock(db) = self {
+            return db.miniblock_header(number).await;
+        }
+        let mut storage = self.access_storage_tagged("dal_facade").await;
+        storage.blocks_dal().get_miniblock_header(number).await
+    }
+
+    async fn seal_miniblock(&self, number: MiniblockNumber, seal: SealFuture<'_>) -> DalResult<()> {
+        if let ConnectionPool::Mock(db) = self {
+            return db.seal_miniblock(number, seal).await;
+        }
+        seal.await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        time::{Duration, Instant},
+    };
+
+    use ola_types::H256;
+
+    use super::*;
+    use crate::{connection::mock::MockDb, DalError};
+
+    #[tokio::test]
+    async fn mock_pool_returns_canned_results() {
+        let db = Arc::new(MockDb::new());
+        db.set_sealed_miniblock_number(MiniblockNumber(42));
+        db.set_nonce(Address::repeat_byte(1), 5.into());
+        let pool = ConnectionPool::Mock(db.clone());
+
+        assert_eq!(
+            pool.sealed_miniblock_number().await.unwrap(),
+            MiniblockNumber(42)
+        );
+        let nonce = pool
+            .address_historical_nonce(Address::repeat_byte(1), MiniblockNumber(42))
+            .await
+            .unwrap();
+        assert_eq!(nonce, 5.into());
+        let nonce = pool
+            .address_historical_nonce(Address::repeat_byte(2), MiniblockNumber(42))
+            .await
+            .unwrap();
+        assert_eq!(nonce, U256::zero());
+        assert_eq!(db.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn mock_pool_injects_errors() {
+        let pool = ConnectionPool::Mock(Arc::new(MockDb::new().with_error_rate(1.0)));
+        let err = pool.sealed_l1_batch_number().await.unwrap_err();
+        assert!(matches!(
+            err,
+            DalError::Connection(sqlx::Error::PoolTimedOut)
+        ));
+        assert!(err.is_retriable());
+
+        let db = Arc::new(MockDb::new().with_error_rate(0.5).with_seed(7));
+        let pool = ConnectionPool::Mock(db.clone());
+        let mut failures = 0;
+        for _ in 0..200 {
+            if pool.sealed_miniblock_number().await.is_err() {
+                failures += 1;
+            }
+        }
+        assert!((50..150).contains(&failures), "failures: {failures}");
+        assert_eq!(db.calls(), 200);
+    }
+
+    fn miniblock_header(number: u32) -> MiniblockHeader {
+        MiniblockHeader {
+            number: MiniblockNumber(number),
+            timestamp: u64::from(number),
+            hash: H256::from_low_u64_be(number.into()),
+            l1_tx_count: 0,
+            l2_tx_count: 1,
+            base_system_contracts_hashes: Default::default(),
+            protocol_version: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn mock_pool_seals_miniblocks() {
+        let db = Arc::new(MockDb::new());
+        let pool = ConnectionPool::Mock(db.clone());
+        let polled = AtomicBool::new(false);
+        let seal = Box::pin(async {
+            polled.store(true, Ordering::Relaxed);
+            db.insert_miniblock(miniblock_header(3));
+            Ok::<_, DalError>(())
+        });
+        pool.seal_miniblock(MiniblockNumber(3), seal).await.unwrap();
+        assert!(polled.load(Ordering::Relaxed));
+        assert_eq!(db.current_sealed_miniblock_number(), MiniblockNumber(3));
+        assert_eq!(
+            pool.sealed_miniblock_number().await.unwrap(),
+            MiniblockNumber(3)
+        );
+        let header = pool.miniblock_header(MiniblockNumber(3)).await.unwrap();
+        assert_eq!(header, Some(miniblock_header(3)));
+        let header = pool.miniblock_header(MiniblockNumber(4)).await.unwrap();
+        assert_eq!(header, None);
+    }
+
+    #[tokio::test]
+    async fn mock_pool_propagates_seal_errors() {
+        let db = Arc::new(MockDb::new());
+        let pool = ConnectionPool::Mock(db.clone());
+        let seal = Box::pin(async { Err(DalError::Connection(sqlx::Error::PoolClosed)) });
+        let err = pool
+            .seal_miniblock(MiniblockNumber(1), seal)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DalError::Connection(sqlx::Error::PoolClosed)));
+        assert_eq!(db.current_sealed_miniblock_number(), MiniblockNumber(0));
+    }
+
+    #[tokio::test]
+    async fn mock_pool_simulates_latency() {
+        let latency = Duration::from_millis(20);
+        let pool = ConnectionPool::Mock(Arc::new(MockDb::new().with_latency(latency)));
+        let started_at = Instant::now();
+        pool.sealed_miniblock_number().await.unwrap();
+        assert!(started_at.elapsed() >= latency);
+    }
+}
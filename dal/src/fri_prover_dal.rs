@@ -8,7 +8,10 @@ use ola_types::{
 };
 use strum::{Display, EnumString};
 
-use crate::{time_utils::duration_to_naive_time, StorageProcessor};
+use crate::{
+    time_utils::{duration_to_naive_time, pg_interval_from_duration},
+    DalError, DalResult, StorageProcessor,
+};
 
 #[derive(Debug, EnumString, Display)]
 pub enum FriProofJobStatus {
@@ -26,6 +29,32 @@ pub enum FriProofJobStatus {
     Skipped,
 }
 
+/// Aggregated proving statistics for a single `(circuit_id, aggregation_round)` pair,
+/// computed over both live and archived prover jobs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FriProverJobsStats {
+    pub circuit_id: u8,
+    pub aggregation_round: AggregationRound,
+    pub total_jobs: u64,
+    pub successful_jobs: u64,
+    pub failed_jobs: u64,
+    pub avg_proving_time: Option<Duration>,
+}
+
+impl FriProverJobsStats {
+    pub fn success_rate(&self) -> f64 {
+        if self.total_jobs == 0 {
+            return 0.0;
+        }
+        self.successful_jobs as f64 / self.total_jobs as f64
+    }
+}
+
+fn aggregation_round(round: i32) -> DalResult<AggregationRound> {
+    AggregationRound::try_from(round)
+        .map_err(|()| DalError::decode(format!("unknown aggregation round {round}")))
+}
+
 #[derive(Debug)]
 pub struct FriProverDal<'a, 'c> {
     pub(crate) storage: &'a mut StorageProcessor<'c>,
@@ -39,7 +68,7 @@ impl FriProverDal<'_, '_> {
         aggregation_round: AggregationRound,
         depth: u16,
         protocol_version_id: FriProtocolVersionId,
-    ) {
+    ) -> DalResult<()> {
         // let latency = MethodLatency::new("save_fri_prover_jobs");
         for (sequence_number, (circuit_id, circuit_blob_url)) in
             circuit_ids_and_urls.iter().enumerate()
@@ -54,9 +83,10 @@ impl FriProverDal<'_, '_> {
                 false,
                 protocol_version_id,
             )
-            .await;
+            .await?;
         }
         // drop(latency);
+        Ok(())
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -70,7 +100,7 @@ impl FriProverDal<'_, '_> {
         circuit_blob_url: &str,
         is_node_final_proof: bool,
         protocol_version_id: FriProtocolVersionId,
-    ) {
+    ) -> DalResult<()> {
         sqlx::query!(
                     r#"
                     INSERT INTO
@@ -105,8 +135,8 @@ impl FriProverDal<'_, '_> {
             FriProofJobStatus::Queued.to_string(),
         )
             .execute(self.storage.conn())
-            .await
-            .unwrap();
+            .await?;
+        Ok(())
     }
 
     pub async fn save_proof(
@@ -114,8 +144,8 @@ impl FriProverDal<'_, '_> {
         id: u32,
         time_taken: Duration,
         blob_url: &str,
-    ) -> FriProverJobMetadata {
-        sqlx::query!(
+    ) -> DalResult<FriProverJobMetadata> {
+        let row = sqlx::query!(
             r#"
             UPDATE prover_jobs_fri
             SET
@@ -139,22 +169,24 @@ impl FriProverDal<'_, '_> {
             blob_url,
             id as i64,
         )
-        .fetch_optional(self.storage.conn())
-        .await
-        .unwrap()
-        .map(|row| FriProverJobMetadata {
+        .fetch_one(self.storage.conn())
+        .await?;
+
+        Ok(FriProverJobMetadata {
             id: row.id as u32,
             block_number: L1BatchNumber(row.l1_batch_number as u32),
             circuit_id: row.circuit_id as u8,
-            aggregation_round: AggregationRound::try_from(row.aggregation_round as i32).unwrap(),
+            aggregation_round: aggregation_round(row.aggregation_round as i32)?,
             sequence_number: row.sequence_number as usize,
             depth: row.depth as u16,
             is_node_final_proof: row.is_node_final_proof,
         })
-        .unwrap()
     }
 
-    pub async fn mark_proof_sent_to_server(&mut self, block_number: L1BatchNumber) {
+    pub async fn mark_proof_sent_to_server(
+        &mut self,
+        block_number: L1BatchNumber,
+    ) -> DalResult<()> {
         sqlx::query!(
             r#"
             UPDATE prover_jobs_fri
@@ -168,37 +200,35 @@ impl FriProverDal<'_, '_> {
             block_number.0 as i64
         )
         .execute(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
+        Ok(())
     }
 
-    pub async fn save_proof_error(&mut self, id: u32, error: String) {
-        {
-            sqlx::query!(
-                r#"
-                UPDATE prover_jobs_fri
-                SET
-                    status = $1,
-                    error = $2,
-                    updated_at = NOW()
-                WHERE
-                    id = $3
-                "#,
-                FriProofJobStatus::Failed.to_string(),
-                error,
-                id as i64,
-            )
-            .execute(self.storage.conn())
-            .await
-            .unwrap();
-        }
+    pub async fn save_proof_error(&mut self, id: u32, error: String) -> DalResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE prover_jobs_fri
+            SET
+                status = $1,
+                error = $2,
+                updated_at = NOW()
+            WHERE
+                id = $3
+            "#,
+            FriProofJobStatus::Failed.to_string(),
+            error,
+            id as i64,
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
     }
 
     pub async fn get_next_job(
         &mut self,
         protocol_versions: &[FriProtocolVersionId],
         picked_by: &str,
-    ) -> Option<FriProverJobMetadata> {
+    ) -> DalResult<Option<FriProverJobMetadata>> {
         let protocol_versions: Vec<i32> = protocol_versions.iter().map(|&id| id as i32).collect();
         sqlx::query!(
             r#"
@@ -242,20 +272,73 @@ impl FriProverDal<'_, '_> {
             FriProofJobStatus::Queued.to_string(),
         )
         .fetch_optional(self.storage.conn())
-        .await
-        .unwrap()
-        .map(|row| FriProverJobMetadata {
-            id: row.id as u32,
-            block_number: L1BatchNumber(row.l1_batch_number as u32),
-            circuit_id: row.circuit_id as u8,
-            aggregation_round: AggregationRound::try_from(row.aggregation_round as i32).unwrap(),
-            sequence_number: row.sequence_number as usize,
-            depth: row.depth as u16,
-            is_node_final_proof: row.is_node_final_proof,
+        .await?
+        .map(|row| {
+            Ok(FriProverJobMetadata {
+                id: row.id as u32,
+                block_number: L1BatchNumber(row.l1_batch_number as u32),
+                circuit_id: row.circuit_id as u8,
+                aggregation_round: aggregation_round(row.aggregation_round as i32)?,
+                sequence_number: row.sequence_number as usize,
+                depth: row.depth as u16,
+                is_node_final_proof: row.is_node_final_proof,
+            })
         })
+        .transpose()
     }
 
-    pub async fn get_prover_job_attempts(&mut self, id: u32) -> sqlx::Result<Option<u32>> {
+    /// Returns up to `limit` queued jobs in the order they would be picked by [`Self::get_next_job()`],
+    /// without claiming them. Used to prefetch circuit blobs for upcoming jobs.
+    pub async fn peek_next_jobs(
+        &mut self,
+        protocol_versions: &[FriProtocolVersionId],
+        limit: usize,
+    ) -> DalResult<Vec<FriProverJobMetadata>> {
+        let protocol_versions: Vec<i32> = protocol_versions.iter().map(|&id| id as i32).collect();
+        sqlx::query!(
+            r#"
+            SELECT
+                id,
+                l1_batch_number,
+                circuit_id,
+                aggregation_round,
+                sequence_number,
+                depth,
+                is_node_final_proof
+            FROM
+                prover_jobs_fri
+            WHERE
+                status = $2
+                AND protocol_version = ANY ($1)
+            ORDER BY
+                aggregation_round DESC,
+                l1_batch_number ASC,
+                id ASC
+            LIMIT
+                $3
+            "#,
+            &protocol_versions[..],
+            FriProofJobStatus::Queued.to_string(),
+            limit as i64,
+        )
+        .fetch_all(self.storage.conn())
+        .await?
+        .into_iter()
+        .map(|row| {
+            Ok(FriProverJobMetadata {
+                id: row.id as u32,
+                block_number: L1BatchNumber(row.l1_batch_number as u32),
+                circuit_id: row.circuit_id as u8,
+                aggregation_round: aggregation_round(row.aggregation_round as i32)?,
+                sequence_number: row.sequence_number as usize,
+                depth: row.depth as u16,
+                is_node_final_proof: row.is_node_final_proof,
+            })
+        })
+        .collect()
+    }
+
+    pub async fn get_prover_job_attempts(&mut self, id: u32) -> DalResult<Option<u32>> {
         let attempts = sqlx::query!(
             r#"
             SELECT
@@ -279,7 +362,7 @@ impl FriProverDal<'_, '_> {
         circuits_to_pick: &[CircuitIdRoundTuple],
         protocol_versions: &[FriProtocolVersionId],
         picked_by: &str,
-    ) -> Option<FriProverJobMetadata> {
+    ) -> DalResult<Option<FriProverJobMetadata>> {
         let circuit_ids: Vec<_> = circuits_to_pick
             .iter()
             .map(|tuple| tuple.circuit_id as i16)
@@ -351,22 +434,24 @@ impl FriProverDal<'_, '_> {
             FriProofJobStatus::Queued.to_string(),
         )
         .fetch_optional(self.storage.conn())
-        .await
-        .unwrap()
-        .map(|row| FriProverJobMetadata {
-            id: row.id as u32,
-            block_number: L1BatchNumber(row.l1_batch_number as u32),
-            circuit_id: row.circuit_id as u8,
-            aggregation_round: AggregationRound::try_from(row.aggregation_round as i32).unwrap(),
-            sequence_number: row.sequence_number as usize,
-            depth: row.depth as u16,
-            is_node_final_proof: row.is_node_final_proof,
+        .await?
+        .map(|row| {
+            Ok(FriProverJobMetadata {
+                id: row.id as u32,
+                block_number: L1BatchNumber(row.l1_batch_number as u32),
+                circuit_id: row.circuit_id as u8,
+                aggregation_round: aggregation_round(row.aggregation_round as i32)?,
+                sequence_number: row.sequence_number as usize,
+                depth: row.depth as u16,
+                is_node_final_proof: row.is_node_final_proof,
+            })
         })
+        .transpose()
     }
 
     pub async fn get_least_proven_block_number_not_sent_to_server(
         &mut self,
-    ) -> Option<(L1BatchNumber, FriProofJobStatus)> {
+    ) -> DalResult<Option<(L1BatchNumber, FriProofJobStatus)>> {
         let row = sqlx::query!(
             r#"
             SELECT
@@ -389,14 +474,118 @@ impl FriProverDal<'_, '_> {
             FriProofJobStatus::Skipped.to_string()
         )
         .fetch_optional(self.storage.conn())
-        .await
-        .ok()?;
-        match row {
-            Some(row) => Some((
-                L1BatchNumber(row.l1_batch_number as u32),
-                FriProofJobStatus::from_str(&row.status).unwrap(),
-            )),
-            None => None,
-        }
+        .await?;
+        row.map(|row| {
+            let status = FriProofJobStatus::from_str(&row.status).map_err(DalError::decode)?;
+            Ok((L1BatchNumber(row.l1_batch_number as u32), status))
+        })
+        .transpose()
+    }
+
+    /// Moves jobs in a terminal state that were last updated more than `retention` ago
+    /// into `prover_jobs_fri_archive`. Returns the number of archived jobs.
+    pub async fn archive_old_jobs(&mut self, retention: Duration) -> DalResult<usize> {
+        let retention = pg_interval_from_duration(retention);
+        let result = sqlx::query!(
+            r#"
+            WITH
+                moved AS (
+                    DELETE FROM prover_jobs_fri
+                    WHERE
+                        status = ANY ($1)
+                        AND updated_at < NOW() - $2::INTERVAL
+                    RETURNING
+                        *
+                )
+            INSERT INTO
+                prover_jobs_fri_archive (
+                    id,
+                    l1_batch_number,
+                    circuit_id,
+                    circuit_blob_url,
+                    aggregation_round,
+                    sequence_number,
+                    proof_blob_url,
+                    status,
+                    depth,
+                    error,
+                    attempts,
+                    processing_started_at,
+                    created_at,
+                    updated_at,
+                    time_taken,
+                    is_blob_cleaned,
+                    is_node_final_proof,
+                    protocol_version,
+                    picked_by,
+                    archived_at
+                )
+            SELECT
+                id,
+                l1_batch_number,
+                circuit_id,
+                circuit_blob_url,
+                aggregation_round,
+                sequence_number,
+                proof_blob_url,
+                status,
+                depth,
+                error,
+                attempts,
+                processing_started_at,
+                created_at,
+                updated_at,
+                time_taken,
+                is_blob_cleaned,
+                is_node_final_proof,
+                protocol_version,
+                picked_by,
+                NOW()
+            FROM
+                moved
+            ON CONFLICT (id) DO NOTHING
+            "#,
+            &[
+                FriProofJobStatus::SentToServer.to_string(),
+                FriProofJobStatus::Failed.to_string(),
+            ],
+            retention,
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(result.rows_affected() as usize)
+    }
+
+    pub async fn get_prover_jobs_stats(&mut self) -> DalResult<Vec<FriProverJobsStats>> {
+        sqlx::query!(
+            r#"
+            SELECT
+                circuit_id AS "circuit_id!",
+                aggregation_round AS "aggregation_round!",
+                total_jobs AS "total_jobs!",
+                successful_jobs AS "successful_jobs!",
+                failed_jobs AS "failed_jobs!",
+                avg_proving_time_secs::FLOAT8 AS avg_proving_time_secs
+            FROM
+                prover_jobs_fri_stats
+            ORDER BY
+                circuit_id,
+                aggregation_round
+            "#
+        )
+        .fetch_all(self.storage.conn())
+        .await?
+        .into_iter()
+        .map(|row| {
+            Ok(FriProverJobsStats {
+                circuit_id: row.circuit_id as u8,
+                aggregation_round: aggregation_round(row.aggregation_round as i32)?,
+                total_jobs: row.total_jobs as u64,
+                successful_jobs: row.successful_jobs as u64,
+                failed_jobs: row.failed_jobs as u64,
+                avg_proving_time: row.avg_proving_time_secs.map(Duration::from_secs_f64),
+            })
+        })
+        .collect()
     }
 }
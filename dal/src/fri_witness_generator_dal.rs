@@ -1,8 +1,10 @@
 use std::time::Duration;
 
-use ola_types::{protocol_version::FriProtocolVersionId, L1BatchNumber};
+use ola_types::{
+    proofs::WitnessStageTimings, protocol_version::FriProtocolVersionId, L1BatchNumber,
+};
 
-use crate::{time_utils::duration_to_naive_time, StorageProcessor};
+use crate::{time_utils::duration_to_naive_time, DalError, DalResult, StorageProcessor};
 
 #[derive(Debug)]
 pub struct FriWitnessGeneratorDal<'a, 'c> {
@@ -29,7 +31,7 @@ impl FriWitnessGeneratorDal<'_, '_> {
         block_number: L1BatchNumber,
         object_key: &str,
         protocol_version_id: FriProtocolVersionId,
-    ) {
+    ) -> DalResult<()> {
         sqlx::query!(
             r#"
             INSERT INTO
@@ -49,9 +51,9 @@ impl FriWitnessGeneratorDal<'_, '_> {
             object_key,
             protocol_version_id as i32,
         )
-        .fetch_optional(self.storage.conn())
-        .await
-        .unwrap();
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
     }
 
     pub async fn get_next_basic_circuit_witness_job(
@@ -59,9 +61,9 @@ impl FriWitnessGeneratorDal<'_, '_> {
         last_l1_batch_to_process: u32,
         protocol_versions: &[FriProtocolVersionId],
         picked_by: &str,
-    ) -> Option<L1BatchNumber> {
+    ) -> DalResult<Option<L1BatchNumber>> {
         let protocol_versions: Vec<i32> = protocol_versions.iter().map(|&id| id as i32).collect();
-        sqlx::query!(
+        let row = sqlx::query!(
             r#"
             UPDATE witness_inputs_fri
             SET
@@ -95,15 +97,15 @@ impl FriWitnessGeneratorDal<'_, '_> {
             picked_by,
         )
         .fetch_optional(self.storage.conn())
-        .await
-        .unwrap()
-        .map(|row| L1BatchNumber(row.l1_batch_number as u32))
+        .await?;
+
+        Ok(row.map(|row| L1BatchNumber(row.l1_batch_number as u32)))
     }
 
     pub async fn get_basic_circuit_witness_job_attempts(
         &mut self,
         l1_batch_number: L1BatchNumber,
-    ) -> sqlx::Result<Option<u32>> {
+    ) -> DalResult<Option<u32>> {
         let attempts = sqlx::query!(
             r#"
             SELECT
@@ -126,7 +128,7 @@ impl FriWitnessGeneratorDal<'_, '_> {
         &mut self,
         status: FriWitnessJobStatus,
         block_number: L1BatchNumber,
-    ) {
+    ) -> DalResult<()> {
         sqlx::query!(
             r#"
             UPDATE witness_inputs_fri
@@ -140,34 +142,42 @@ impl FriWitnessGeneratorDal<'_, '_> {
             block_number.0 as i64
         )
         .execute(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
+        Ok(())
     }
 
     pub async fn mark_witness_job_as_successful(
         &mut self,
         block_number: L1BatchNumber,
         time_taken: Duration,
-    ) {
+        stage_timings: &WitnessStageTimings,
+    ) -> DalResult<()> {
+        let stage_timings = serde_json::to_value(stage_timings).map_err(DalError::encode)?;
         sqlx::query!(
             r#"
             UPDATE witness_inputs_fri
             SET
                 status = 'successful',
                 updated_at = NOW(),
-                time_taken = $1
+                time_taken = $1,
+                stage_timings = $2
             WHERE
-                l1_batch_number = $2
+                l1_batch_number = $3
             "#,
             duration_to_naive_time(time_taken),
+            stage_timings,
             block_number.0 as i64
         )
         .execute(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
+        Ok(())
     }
 
-    pub async fn mark_witness_job_failed(&mut self, error: &str, block_number: L1BatchNumber) {
+    pub async fn mark_witness_job_failed(
+        &mut self,
+        error: &str,
+        block_number: L1BatchNumber,
+    ) -> DalResult<()> {
         sqlx::query!(
             r#"
             UPDATE witness_inputs_fri
@@ -182,15 +192,15 @@ impl FriWitnessGeneratorDal<'_, '_> {
             block_number.0 as i64
         )
         .execute(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
+        Ok(())
     }
 
     pub async fn protocol_version_for_l1_batch(
         &mut self,
         l1_batch_number: L1BatchNumber,
-    ) -> FriProtocolVersionId {
-        sqlx::query!(
+    ) -> DalResult<FriProtocolVersionId> {
+        let row = sqlx::query!(
             r#"
             SELECT
                 protocol_version
@@ -202,10 +212,12 @@ impl FriWitnessGeneratorDal<'_, '_> {
             l1_batch_number.0 as i64,
         )
         .fetch_one(self.storage.conn())
-        .await
-        .unwrap()
-        .protocol_version
-        .map(|id| FriProtocolVersionId::try_from(id as u16).unwrap())
-        .unwrap()
+        .await?;
+        let id = row.protocol_version.ok_or_else(|| {
+            DalError::decode(format!(
+                "L1 batch #{l1_batch_number} has no protocol version"
+            ))
+        })?;
+        FriProtocolVersionId::try_from(id as u16).map_err(DalError::decode)
     }
 }
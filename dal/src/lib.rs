@@ -1,19 +1,27 @@
 use std::env;
 
 use crate::protocol_versions_web3_dal::ProtocolVersionsWeb3Dal;
+use audit_log_dal::AuditLogDal;
 use basic_witness_input_producer_dal::BasicWitnessInputProducerDal;
 use blocks_dal::BlocksDal;
 use blocks_web3_dal::BlocksWeb3Dal;
+use call_outputs_dal::CallOutputsDal;
+use calldata_screening_dal::CalldataScreeningDal;
 use connection::holder::ConnectionHolder;
+use consistency_dal::ConsistencyDal;
+use contract_metrics_dal::ContractMetricsDal;
+pub use error::{DalError, DalResult};
 use events_dal::EventsDal;
 use fri_protocol_versions_dal::FriProtocolVersionsDal;
 use fri_prover_dal::FriProverDal;
 use fri_witness_generator_dal::FriWitnessGeneratorDal;
+use operators_dal::OperatorsDal;
 use proof_generation_dal::ProofGenerationDal;
 use proof_offchain_verification_dal::ProofVerificationDal;
 use protocol_version_dal::ProtocolVersionsDal;
+use reverts_dal::RevertsDal;
+use sequencer_leases_dal::SequencerLeasesDal;
 use snapshot_recovery_dal::SnapshotRecoveryDal;
-pub use sqlx::Error as SqlxError;
 use sqlx::{pool::PoolConnection, Connection, PgConnection, Postgres, Transaction};
 use storage_dal::StorageDal;
 use storage_logs_dal::StorageLogsDal;
@@ -21,24 +29,36 @@ use storage_logs_dedup_dal::StorageLogsDedupDal;
 use storage_web3_dal::StorageWeb3Dal;
 use token_dal::TokensDal;
 use transaction_web3_dal::TransactionsWeb3Dal;
+use transactions_archive_dal::TransactionsArchiveDal;
 use transactions_dal::TransactionsDal;
 
 #[macro_use]
 mod macro_utils;
+pub mod audit_log_dal;
 pub mod basic_witness_input_producer_dal;
 pub mod blocks_dal;
 pub mod blocks_web3_dal;
+pub mod call_outputs_dal;
+pub mod calldata_screening_dal;
 pub mod connection;
+pub mod consistency_dal;
+pub mod contract_metrics_dal;
+pub mod error;
 pub mod events_dal;
+pub mod facade;
 pub mod fri_protocol_versions_dal;
 pub mod fri_prover_dal;
 pub mod fri_witness_generator_dal;
 pub mod healthcheck;
 pub mod models;
+pub mod operators_dal;
 pub mod proof_generation_dal;
 pub mod proof_offchain_verification_dal;
 pub mod protocol_version_dal;
 pub mod protocol_versions_web3_dal;
+pub mod reverts_dal;
+pub mod schema;
+pub mod sequencer_leases_dal;
 pub mod snapshot_recovery_dal;
 pub mod storage_dal;
 pub mod storage_logs_dal;
@@ -47,6 +67,7 @@ pub mod storage_web3_dal;
 pub mod time_utils;
 pub mod token_dal;
 pub mod transaction_web3_dal;
+pub mod transactions_archive_dal;
 pub mod transactions_dal;
 
 pub fn get_master_database_url() -> String {
@@ -159,6 +180,38 @@ impl<'a> StorageProcessor<'a> {
         TransactionsWeb3Dal { storage: self }
     }
 
+    pub fn transactions_archive_dal(&mut self) -> TransactionsArchiveDal<'_, 'a> {
+        TransactionsArchiveDal { storage: self }
+    }
+
+    pub fn reverts_dal(&mut self) -> RevertsDal<'_, 'a> {
+        RevertsDal { storage: self }
+    }
+
+    pub fn audit_log_dal(&mut self) -> AuditLogDal<'_, 'a> {
+        AuditLogDal { storage: self }
+    }
+
+    pub fn call_outputs_dal(&mut self) -> CallOutputsDal<'_, 'a> {
+        CallOutputsDal { storage: self }
+    }
+
+    pub fn calldata_screening_dal(&mut self) -> CalldataScreeningDal<'_, 'a> {
+        CalldataScreeningDal { storage: self }
+    }
+
+    pub fn consistency_dal(&mut self) -> ConsistencyDal<'_, 'a> {
+        ConsistencyDal { storage: self }
+    }
+
+    pub fn operators_dal(&mut self) -> OperatorsDal<'_, 'a> {
+        OperatorsDal { storage: self }
+    }
+
+    pub fn sequencer_leases_dal(&mut self) -> SequencerLeasesDal<'_, 'a> {
+        SequencerLeasesDal { storage: self }
+    }
+
     pub fn storage_dal(&mut self) -> StorageDal<'_, 'a> {
         StorageDal { storage: self }
     }
@@ -175,6 +228,10 @@ impl<'a> StorageProcessor<'a> {
         EventsDal { storage: self }
     }
 
+    pub fn contract_metrics_dal(&mut self) -> ContractMetricsDal<'_, 'a> {
+        ContractMetricsDal { storage: self }
+    }
+
     pub fn protocol_versions_dal(&mut self) -> ProtocolVersionsDal<'_, 'a> {
         ProtocolVersionsDal { storage: self }
     }
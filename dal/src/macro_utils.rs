@@ -16,3 +16,7 @@ macro_rules! writeln_str {
         std::writeln!(__buffer, $($args)+).unwrap(); // Writing to a string cannot result in an error
     }};
 }
+
+/// Maximum size of a buffer accumulated before it is flushed into a `COPY ... FROM STDIN` stream.
+/// Flushing in chunks keeps memory usage bounded when sealing miniblocks with 100k+ rows.
+pub(crate) const COPY_FLUSH_THRESHOLD_BYTES: usize = 4 * 1_024 * 1_024;
@@ -280,6 +280,7 @@ impl TryInto<L1BatchMetadata> for StorageL1Batch {
                         .default_aa_code_hash
                         .ok_or(StorageL1BatchConvertError::Incomplete)?,
                 ),
+                operator_address: Address::from_slice(&self.fee_account_address),
             },
             events_queue_commitment: self.events_queue_commitment.map(|v| H256::from_slice(&v)),
         })
@@ -303,6 +304,8 @@ pub struct StorageL1BatchDetails {
     // pub l2_fair_gas_price: i64,
     pub bootloader_code_hash: Option<Vec<u8>>,
     pub default_aa_code_hash: Option<Vec<u8>>,
+    pub witness_input_stage_timings: Option<serde_json::Value>,
+    pub witness_stage_timings: Option<serde_json::Value>,
 }
 
 impl From<StorageL1BatchDetails> for api::L1BatchDetails {
@@ -339,6 +342,12 @@ impl From<StorageL1BatchDetails> for api::L1BatchDetails {
         api::L1BatchDetails {
             base,
             number: L1BatchNumber(details.number as u32),
+            witness_input_stage_timings: details
+                .witness_input_stage_timings
+                .and_then(|timings| serde_json::from_value(timings).ok()),
+            witness_stage_timings: details
+                .witness_stage_timings
+                .and_then(|timings| serde_json::from_value(timings).ok()),
         }
     }
 }
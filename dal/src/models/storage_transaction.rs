@@ -29,6 +29,7 @@ pub struct StorageTransaction {
     pub data: serde_json::Value,
     pub received_at: NaiveDateTime,
     pub in_mempool: bool,
+    pub execute_after: Option<NaiveDateTime>,
 
     pub l1_block_number: Option<i32>,
     pub l1_batch_number: Option<i64>,
@@ -53,6 +54,7 @@ impl From<StorageTransaction> for L2TxCommonData {
             signature,
             hash,
             input,
+            execute_after,
             ..
         } = tx;
 
@@ -63,7 +65,7 @@ impl From<StorageTransaction> for L2TxCommonData {
             Some(_) => unreachable!("Unsupported tx type"),
         };
 
-        L2TxCommonData::new(
+        let mut common_data = L2TxCommonData::new(
             nonce,
             Address::from_slice(&initiator_address),
             signature.unwrap_or_else(|| {
@@ -72,7 +74,10 @@ impl From<StorageTransaction> for L2TxCommonData {
             tx_format,
             input.expect("input data is mandatory for l2 transactions"),
             H256::from_slice(&hash),
-        )
+        );
+        common_data.execute_after =
+            execute_after.map(|execute_after| execute_after.timestamp() as u64);
+        common_data
     }
 }
 
@@ -118,6 +123,7 @@ pub struct StorageTransactionDetails {
     pub received_at: NaiveDateTime,
     pub miniblock_number: Option<i64>,
     pub error: Option<String>,
+    pub execute_after: Option<NaiveDateTime>,
 }
 
 impl StorageTransactionDetails {
@@ -126,6 +132,10 @@ impl StorageTransactionDetails {
             TransactionStatus::Failed
         } else if self.miniblock_number.is_some() {
             TransactionStatus::Included
+        } else if self.execute_after.map_or(false, |execute_after| {
+            execute_after > Utc::now().naive_utc()
+        }) {
+            TransactionStatus::Scheduled
         } else {
             TransactionStatus::Pending
         }
@@ -149,6 +159,7 @@ impl From<StorageTransactionDetails> for TransactionDetails {
             eth_commit_tx_hash: None,
             eth_prove_tx_hash: None,
             eth_execute_tx_hash: None,
+            revert_reason: tx_details.error,
         }
     }
 }
@@ -198,8 +209,9 @@ impl From<StorageTransactionReceipt> for TransactionReceipt {
                 })
                 // For better compatibility with various clients, we never return null.
                 .or_else(|| Some(Address::default())),
-            gas_used: None,
-            effective_gas_price: None,
+            // Ola doesn't charge fees for transactions yet.
+            gas_used: Some(U256::zero()),
+            effective_gas_price: Some(U256::zero()),
             logs_bloom: H2048::default(),
             cumulative_gas_used: U256::default(),
 
@@ -207,6 +219,8 @@ impl From<StorageTransactionReceipt> for TransactionReceipt {
                 .contract_address
                 .map(|addr| h256_to_account_address(&H256::from_slice(&addr))),
             logs: vec![],
+            // L2 -> L1 logs are not persisted yet.
+            l2_to_l1_logs: vec![],
             status: Some(status),
             root: Some(block_hash),
             // Even though the Rust SDK recommends us to supply "None" for legacy transactions
@@ -0,0 +1,86 @@
+use ola_types::{Address, L1BatchNumber};
+
+use crate::{DalResult, StorageProcessor};
+
+/// Rotations of the operator address. A requested rotation is pending until the sequencer opens
+/// the next L1 batch, which becomes the first L1 batch of the new operator.
+#[derive(Debug)]
+pub struct OperatorsDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl OperatorsDal<'_, '_> {
+    /// Requests rotation to `operator_address`, replacing the previously requested rotation
+    /// if it isn't applied yet.
+    pub async fn request_rotation(&mut self, operator_address: Address) -> DalResult<()> {
+        let mut transaction = self.storage.start_transaction().await;
+        sqlx::query!("DELETE FROM operator_rotations WHERE effective_l1_batch_number IS NULL")
+            .execute(transaction.conn())
+            .await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO operator_rotations (operator_address, requested_at)
+            VALUES ($1, NOW())
+            "#,
+            operator_address.as_bytes()
+        )
+        .execute(transaction.conn())
+        .await?;
+        transaction.commit().await;
+        Ok(())
+    }
+
+    pub async fn get_pending_rotation(&mut self) -> DalResult<Option<Address>> {
+        let row = sqlx::query!(
+            "SELECT operator_address FROM operator_rotations WHERE effective_l1_batch_number IS NULL"
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+        Ok(row.map(|row| Address::from_slice(&row.operator_address)))
+    }
+
+    /// Applies the pending rotation, if any, starting from `l1_batch_number`. The rotation is kept
+    /// pending if a rotation already took effect at this or a later L1 batch, e.g. if the L1 batch
+    /// is reopened after a restart. Returns the new operator address if the rotation is applied.
+    pub async fn apply_pending_rotation(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> DalResult<Option<Address>> {
+        let row = sqlx::query!(
+            r#"
+            UPDATE operator_rotations
+            SET effective_l1_batch_number = $1
+            WHERE effective_l1_batch_number IS NULL
+                AND NOT EXISTS (
+                    SELECT 1 FROM operator_rotations WHERE effective_l1_batch_number >= $1
+                )
+            RETURNING operator_address
+            "#,
+            l1_batch_number.0 as i64
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+        Ok(row.map(|row| Address::from_slice(&row.operator_address)))
+    }
+
+    /// Returns the operator of the L1 batch, or `None` if the operator was never rotated,
+    /// i.e. the operator is the one from the sequencer config.
+    pub async fn get_operator_for_l1_batch(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> DalResult<Option<Address>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT operator_address
+            FROM operator_rotations
+            WHERE effective_l1_batch_number <= $1
+            ORDER BY effective_l1_batch_number DESC
+            LIMIT 1
+            "#,
+            l1_batch_number.0 as i64
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+        Ok(row.map(|row| Address::from_slice(&row.operator_address)))
+    }
+}
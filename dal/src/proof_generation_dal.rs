@@ -1,9 +1,9 @@
 use std::time::Duration;
 
-use ola_types::L1BatchNumber;
+use ola_types::{L1BatchNumber, H256};
 use strum::{Display, EnumString};
 
-use crate::{time_utils::pg_interval_from_duration, SqlxError, StorageProcessor};
+use crate::{time_utils::pg_interval_from_duration, DalError, DalResult, StorageProcessor};
 
 #[derive(Debug)]
 pub struct ProofGenerationDal<'a, 'c> {
@@ -22,13 +22,27 @@ enum ProofGenerationJobStatus {
     Skipped,
 }
 
+/// Proof submitted for an L1 batch, or the lack thereof.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProofSubmission {
+    /// No proof was submitted and the proof generation wasn't skipped.
+    Pending,
+    /// A proof was submitted. Its hash is unknown for proofs submitted before hashes were recorded.
+    Generated {
+        proof_hash: Option<H256>,
+        submitter: Option<String>,
+    },
+    /// The proof generation was skipped.
+    Skipped { submitter: Option<String> },
+}
+
 impl ProofGenerationDal<'_, '_> {
     pub async fn get_next_block_to_be_proven(
         &mut self,
         processing_timeout: Duration,
-    ) -> Option<L1BatchNumber> {
+    ) -> DalResult<Option<L1BatchNumber>> {
         let processing_timeout = pg_interval_from_duration(processing_timeout);
-        let result: Option<L1BatchNumber> = sqlx::query!(
+        let row = sqlx::query!(
             r#"
             UPDATE proof_generation_details
             SET
@@ -60,18 +74,16 @@ impl ProofGenerationDal<'_, '_> {
             &processing_timeout,
         )
         .fetch_optional(self.storage.conn())
-        .await
-        .unwrap()
-        .map(|row| L1BatchNumber(row.l1_batch_number as u32));
+        .await?;
 
-        result
+        Ok(row.map(|row| L1BatchNumber(row.l1_batch_number as u32)))
     }
 
     pub async fn insert_proof_generation_details(
         &mut self,
         block_number: L1BatchNumber,
         proof_gen_data_blob_url: &str,
-    ) {
+    ) -> DalResult<()> {
         sqlx::query!(
             r#"
             INSERT INTO
@@ -84,45 +96,118 @@ impl ProofGenerationDal<'_, '_> {
             proof_gen_data_blob_url,
         )
         .execute(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
+        Ok(())
+    }
+
+    /// Returns the proof submitted for the L1 batch, locking the row until the end of the current
+    /// DB transaction so that concurrent submissions are serialized. Returns
+    /// [`DalError::NotFound`] if the L1 batch isn't known.
+    pub async fn lock_proof_submission(
+        &mut self,
+        block_number: L1BatchNumber,
+    ) -> DalResult<ProofSubmission> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                status,
+                proof_hash,
+                proof_submitter
+            FROM
+                proof_generation_details
+            WHERE
+                l1_batch_number = $1
+            FOR UPDATE
+            "#,
+            block_number.0 as i64,
+        )
+        .fetch_one(self.storage.conn())
+        .await?;
+
+        let status = row.status.parse::<ProofGenerationJobStatus>().ok();
+        Ok(match status {
+            Some(ProofGenerationJobStatus::Generated) => ProofSubmission::Generated {
+                proof_hash: row.proof_hash.as_deref().map(H256::from_slice),
+                submitter: row.proof_submitter,
+            },
+            Some(ProofGenerationJobStatus::Skipped) => ProofSubmission::Skipped {
+                submitter: row.proof_submitter,
+            },
+            _ => ProofSubmission::Pending,
+        })
+    }
+
+    /// Checks whether the proof generation of the L1 batch is finished, i.e. a proof was submitted
+    /// or the generation was skipped. L1 batches without proof generation details aren't finished.
+    pub async fn is_proof_generation_finished(
+        &mut self,
+        block_number: L1BatchNumber,
+    ) -> DalResult<bool> {
+        let status = sqlx::query!(
+            r#"
+            SELECT
+                status
+            FROM
+                proof_generation_details
+            WHERE
+                l1_batch_number = $1
+            "#,
+            block_number.0 as i64,
+        )
+        .fetch_optional(self.storage.conn())
+        .await?
+        .and_then(|row| row.status.parse::<ProofGenerationJobStatus>().ok());
+
+        Ok(matches!(
+            status,
+            Some(ProofGenerationJobStatus::Generated | ProofGenerationJobStatus::Skipped)
+        ))
     }
 
     pub async fn mark_proof_generation_job_as_skipped(
         &mut self,
         block_number: L1BatchNumber,
-    ) -> Result<(), SqlxError> {
+        submitter: Option<&str>,
+    ) -> DalResult<()> {
         sqlx::query!(
             r#"
             UPDATE proof_generation_details
             SET
                 status = $1,
+                proof_submitter = $3,
+                proof_submitted_at = NOW(),
                 updated_at = NOW()
             WHERE
                 l1_batch_number = $2
             "#,
             ProofGenerationJobStatus::Skipped.to_string(),
             block_number.0 as i64,
+            submitter,
         )
         .execute(self.storage.conn())
         .await?
         .rows_affected()
         .eq(&1)
         .then_some(())
-        .ok_or(sqlx::Error::RowNotFound)
+        .ok_or(DalError::NotFound)
     }
 
     pub async fn save_proof_artifacts_metadata(
         &mut self,
         block_number: L1BatchNumber,
         proof_blob_url: &str,
-    ) -> Result<(), SqlxError> {
+        proof_hash: H256,
+        submitter: Option<&str>,
+    ) -> DalResult<()> {
         sqlx::query!(
             r#"
             UPDATE proof_generation_details
             SET
                 status = $1,
                 proof_blob_url = $2,
+                proof_hash = $4,
+                proof_submitter = $5,
+                proof_submitted_at = NOW(),
                 updated_at = NOW()
             WHERE
                 l1_batch_number = $3
@@ -130,12 +215,14 @@ impl ProofGenerationDal<'_, '_> {
             ProofGenerationJobStatus::Generated.to_string(),
             proof_blob_url,
             block_number.0 as i64,
+            proof_hash.as_bytes(),
+            submitter,
         )
         .execute(self.storage.conn())
         .await?
         .rows_affected()
         .eq(&1)
         .then_some(())
-        .ok_or(sqlx::Error::RowNotFound)
+        .ok_or(DalError::NotFound)
     }
 }
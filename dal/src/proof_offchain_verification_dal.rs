@@ -1,14 +1,21 @@
 use std::str::FromStr;
 
-use ola_types::L1BatchNumber;
+use ola_types::{
+    api::proof_offchain_verification::{
+        AttestationInfo, L1BatchVerificationTask, VerifierAttestation,
+    },
+    Address, L1BatchNumber, H256,
+};
+use sqlx::types::chrono::{DateTime, Utc};
 use strum::{Display, EnumString};
 use tracing::Instrument;
 
 use crate::{
-    models::storage_verification::StorageOffChainVerifyDetails, SqlxError, StorageProcessor,
+    models::storage_verification::StorageOffChainVerifyDetails, DalError, DalResult,
+    StorageProcessor,
 };
 
-#[derive(Debug, EnumString, Display)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, Display)]
 pub enum ProofVerificationStatus {
     #[strum(serialize = "not_ready")]
     NotReady,
@@ -22,6 +29,16 @@ pub enum ProofVerificationStatus {
     OffChainVerifyFailed,
 }
 
+impl ProofVerificationStatus {
+    /// Returns `true` if the verification outcome is decided.
+    pub fn is_final(self) -> bool {
+        matches!(
+            self,
+            Self::OffChainVerifyPassed | Self::OffChainVerifyFailed
+        )
+    }
+}
+
 #[derive(Debug)]
 pub struct ProofVerificationDal<'a, 'c> {
     pub(crate) storage: &'a mut StorageProcessor<'c>,
@@ -31,7 +48,7 @@ impl ProofVerificationDal<'_, '_> {
     pub async fn insert_l1_batch_to_be_verified(
         &mut self,
         block_number: L1BatchNumber,
-    ) -> Result<(), SqlxError> {
+    ) -> DalResult<()> {
         sqlx::query!(
             r#"
             INSERT INTO
@@ -48,14 +65,14 @@ impl ProofVerificationDal<'_, '_> {
         .rows_affected()
         .eq(&1)
         .then_some(())
-        .ok_or(sqlx::Error::RowNotFound)
+        .ok_or(DalError::NotFound)
     }
 
     pub async fn mark_l1_batch_as_verified(
         &mut self,
         block_number: L1BatchNumber,
         is_passed: bool,
-    ) -> Result<(), SqlxError> {
+    ) -> DalResult<()> {
         let status = if is_passed {
             ProofVerificationStatus::OffChainVerifyPassed.to_string()
         } else {
@@ -79,13 +96,10 @@ impl ProofVerificationDal<'_, '_> {
         .rows_affected()
         .eq(&1)
         .then_some(())
-        .ok_or(sqlx::Error::RowNotFound)
+        .ok_or(DalError::NotFound)
     }
 
-    pub async fn mark_l1_batch_as_picked(
-        &mut self,
-        block_number: L1BatchNumber,
-    ) -> Result<(), SqlxError> {
+    pub async fn mark_l1_batch_as_picked(&mut self, block_number: L1BatchNumber) -> DalResult<()> {
         sqlx::query!(
             r#"
             UPDATE proof_offchain_verification_details
@@ -104,10 +118,10 @@ impl ProofVerificationDal<'_, '_> {
         .rows_affected()
         .eq(&1)
         .then_some(())
-        .ok_or(sqlx::Error::RowNotFound)
+        .ok_or(DalError::NotFound)
     }
 
-    pub async fn get_last_l1_batch_verified(&mut self) -> sqlx::Result<L1BatchNumber> {
+    pub async fn get_last_l1_batch_verified(&mut self) -> DalResult<L1BatchNumber> {
         let row = sqlx::query!(
             r#"
             SELECT
@@ -129,7 +143,7 @@ impl ProofVerificationDal<'_, '_> {
     pub async fn get_l1_batch_verification_status(
         &mut self,
         l1_batch_number: L1BatchNumber,
-    ) -> sqlx::Result<ProofVerificationStatus> {
+    ) -> DalResult<ProofVerificationStatus> {
         let row = sqlx::query!(
             r#"
             SELECT
@@ -152,8 +166,8 @@ impl ProofVerificationDal<'_, '_> {
     pub async fn get_l1_batch_verification_details(
         &mut self,
         l1_batch_number: L1BatchNumber,
-    ) -> sqlx::Result<Option<StorageOffChainVerifyDetails>> {
-        sqlx::query_as!(
+    ) -> DalResult<Option<StorageOffChainVerifyDetails>> {
+        let row = sqlx::query_as!(
             StorageOffChainVerifyDetails,
             r#"
             SELECT
@@ -169,6 +183,153 @@ impl ProofVerificationDal<'_, '_> {
             l1_batch_number.0 as i64,
         )
         .fetch_optional(self.storage.conn())
-        .await
+        .await?;
+        Ok(row)
+    }
+
+    /// Returns L1 batches waiting for offchain verification after `after`, with their commitments.
+    /// Proofs aren't loaded; they are stored in the object store.
+    pub async fn get_l1_batches_to_be_verified(
+        &mut self,
+        after: Option<L1BatchNumber>,
+        limit: usize,
+    ) -> DalResult<Vec<L1BatchVerificationTask>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                provd.l1_batch_number,
+                l1_batches.commitment AS "commitment!",
+                l1_batches.hash AS "root_hash!"
+            FROM
+                proof_offchain_verification_details provd
+                JOIN l1_batches ON l1_batches.number = provd.l1_batch_number
+            WHERE
+                provd.status IN ($1, $2)
+                AND provd.l1_batch_number > $3
+                AND l1_batches.commitment IS NOT NULL
+                AND l1_batches.hash IS NOT NULL
+            ORDER BY
+                provd.l1_batch_number
+            LIMIT
+                $4
+            "#,
+            ProofVerificationStatus::ReadyToBeVerified.to_string(),
+            ProofVerificationStatus::PickedByOffChainVerifier.to_string(),
+            after.map_or(-1, |number| i64::from(number.0)),
+            limit as i64,
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| L1BatchVerificationTask {
+                l1_batch_number: L1BatchNumber(row.l1_batch_number as u32),
+                commitment: H256::from_slice(&row.commitment),
+                root_hash: H256::from_slice(&row.root_hash),
+                proof: None,
+            })
+            .collect())
+    }
+
+    /// Locks verification details of an L1 batch until the end of the transaction, so that
+    /// concurrent attestations are counted towards the quorum one at a time. Returns the status
+    /// and the commitment of the batch, or `None` if the batch isn't registered for verification.
+    pub async fn lock_l1_batch_for_attestation(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> DalResult<Option<(ProofVerificationStatus, Option<H256>)>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                provd.status,
+                l1_batches.commitment
+            FROM
+                proof_offchain_verification_details provd
+                JOIN l1_batches ON l1_batches.number = provd.l1_batch_number
+            WHERE
+                provd.l1_batch_number = $1
+            FOR UPDATE OF
+                provd
+            "#,
+            l1_batch_number.0 as i64,
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        Ok(row.map(|row| {
+            let status = ProofVerificationStatus::from_str(&row.status).unwrap();
+            let commitment = row
+                .commitment
+                .map(|commitment| H256::from_slice(&commitment));
+            (status, commitment)
+        }))
+    }
+
+    /// Saves an attestation, replacing the previous one of the same verifier for the batch.
+    pub async fn insert_attestation(&mut self, attestation: &VerifierAttestation) -> DalResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                offchain_verifier_attestations (
+                    l1_batch_number,
+                    verifier_address,
+                    commitment,
+                    is_valid,
+                    signature,
+                    created_at,
+                    updated_at
+                )
+            VALUES
+                ($1, $2, $3, $4, $5, NOW(), NOW())
+            ON CONFLICT (l1_batch_number, verifier_address) DO
+            UPDATE
+            SET
+                commitment = excluded.commitment,
+                is_valid = excluded.is_valid,
+                signature = excluded.signature,
+                updated_at = NOW()
+            "#,
+            attestation.l1_batch_number.0 as i64,
+            attestation.verifier.as_bytes(),
+            attestation.commitment.as_bytes(),
+            attestation.is_valid,
+            &attestation.signature.0,
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_attestations(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> DalResult<Vec<AttestationInfo>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                verifier_address,
+                is_valid,
+                updated_at
+            FROM
+                offchain_verifier_attestations
+            WHERE
+                l1_batch_number = $1
+            ORDER BY
+                verifier_address
+            "#,
+            l1_batch_number.0 as i64,
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| AttestationInfo {
+                verifier: Address::from_slice(&row.verifier_address),
+                is_valid: row.is_valid,
+                attested_at: DateTime::<Utc>::from_naive_utc_and_offset(row.updated_at, Utc),
+            })
+            .collect())
     }
 }
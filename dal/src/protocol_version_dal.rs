@@ -1,9 +1,9 @@
 use crate::models::storage_protocol_version::StorageProtocolVersion;
-use ola_contracts::BaseSystemContracts;
+use ola_contracts::{BaseSystemContracts, BaseSystemContractsHashes};
 use ola_types::protocol_version::{ProtocolUpgradeTx, ProtocolVersion, ProtocolVersionId};
 use ola_types::H256;
 
-use crate::StorageProcessor;
+use crate::{DalError, DalResult, StorageProcessor};
 
 #[derive(Debug)]
 pub struct ProtocolVersionsDal<'a, 'c> {
@@ -11,7 +11,7 @@ pub struct ProtocolVersionsDal<'a, 'c> {
 }
 
 impl ProtocolVersionsDal<'_, '_> {
-    pub async fn save_protocol_version(&mut self, version: ProtocolVersion) {
+    pub async fn save_protocol_version(&mut self, version: ProtocolVersion) -> DalResult<()> {
         let tx_hash = version
             .tx
             .as_ref()
@@ -22,7 +22,7 @@ impl ProtocolVersionsDal<'_, '_> {
             db_transaction
                 .transactions_dal()
                 .insert_system_transaction(tx)
-                .await;
+                .await?;
         }
 
         sqlx::query!(
@@ -38,16 +38,16 @@ impl ProtocolVersionsDal<'_, '_> {
             tx_hash
         )
         .execute(db_transaction.conn())
-        .await
-        .unwrap();
+        .await?;
 
         db_transaction.commit().await;
+        Ok(())
     }
 
     pub async fn base_system_contracts_by_timestamp(
         &mut self,
         current_timestamp: i64,
-    ) -> (BaseSystemContracts, ProtocolVersionId) {
+    ) -> DalResult<(BaseSystemContracts, ProtocolVersionId)> {
         let row = sqlx::query!(
             "SELECT bootloader_code_hash, default_account_code_hash, id FROM protocol_versions
                 WHERE timestamp <= $1
@@ -57,8 +57,7 @@ impl ProtocolVersionsDal<'_, '_> {
             current_timestamp as i64
         )
         .fetch_one(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
         let contracts = self
             .storage
             .storage_dal()
@@ -66,14 +65,40 @@ impl ProtocolVersionsDal<'_, '_> {
                 H256::from_slice(&row.bootloader_code_hash),
                 H256::from_slice(&row.default_account_code_hash),
             )
-            .await;
-        (contracts, (row.id as u16).try_into().unwrap())
+            .await?;
+        let version_id = ProtocolVersionId::try_from(row.id as u16).map_err(DalError::decode)?;
+        Ok((contracts, version_id))
+    }
+
+    /// Returns base system contract hashes of all registered protocol versions,
+    /// from the latest version to the earliest one.
+    pub async fn all_base_system_contracts_hashes(
+        &mut self,
+    ) -> DalResult<Vec<(ProtocolVersionId, BaseSystemContractsHashes)>> {
+        let rows = sqlx::query!(
+            "SELECT id, bootloader_code_hash, default_account_code_hash FROM protocol_versions
+                ORDER BY id DESC
+            "
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+        rows.into_iter()
+            .map(|row| {
+                let hashes = BaseSystemContractsHashes {
+                    entrypoint: H256::from_slice(&row.bootloader_code_hash),
+                    default_aa: H256::from_slice(&row.default_account_code_hash),
+                };
+                let version_id =
+                    ProtocolVersionId::try_from(row.id as u16).map_err(DalError::decode)?;
+                Ok((version_id, hashes))
+            })
+            .collect()
     }
 
     pub async fn get_protocol_upgrade_tx(
         &mut self,
         protocol_version_id: ProtocolVersionId,
-    ) -> Option<ProtocolUpgradeTx> {
+    ) -> DalResult<Option<ProtocolUpgradeTx>> {
         let row = sqlx::query!(
             "
                 SELECT upgrade_tx_hash FROM protocol_versions
@@ -82,25 +107,17 @@ impl ProtocolVersionsDal<'_, '_> {
             protocol_version_id as i32
         )
         .fetch_optional(self.storage.conn())
-        .await
-        .unwrap()?;
-        if let Some(hash) = row.upgrade_tx_hash {
-            Some(
-                self.storage
-                    .transactions_dal()
-                    .get_tx_by_hash(H256::from_slice(&hash))
-                    .await
-                    .unwrap_or_else(|| {
-                        panic!(
-                            "Missing upgrade tx for protocol version {}",
-                            protocol_version_id as u16
-                        );
-                    })
-                    .try_into()
-                    .unwrap(),
-            )
-        } else {
-            None
-        }
+        .await?;
+        let Some(hash) = row.and_then(|row| row.upgrade_tx_hash) else {
+            return Ok(None);
+        };
+        // The upgrade transaction is saved together with the protocol version.
+        let tx = self
+            .storage
+            .transactions_dal()
+            .get_tx_by_hash(H256::from_slice(&hash))
+            .await?
+            .ok_or(DalError::NotFound)?;
+        tx.try_into().map(Some).map_err(DalError::decode)
     }
 }
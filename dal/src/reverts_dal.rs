@@ -0,0 +1,633 @@
+use ola_types::{
+    api,
+    events::VmEvent,
+    tokens::{decode_token_transfer, TokenTransfer, TRANSFER_EVENT_SIGNATURE},
+    Address, L1BatchNumber, MiniblockNumber, H256,
+};
+use sqlx::types::chrono::{DateTime, NaiveDateTime, Utc};
+
+use crate::{DalResult, StorageProcessor};
+
+/// Numbers of entities removed from the chain by a revert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RevertedEntities {
+    pub revert_id: u64,
+    pub l1_batches: u64,
+    pub miniblocks: u64,
+    pub transactions: u64,
+    pub events: u64,
+    /// Number of storage slots restored to their values as of the last kept miniblock.
+    pub storage_slots: u64,
+    /// Number of tokens deployed in reverted miniblocks.
+    pub tokens: u64,
+}
+
+/// Reverts of the chain. Instead of deleting reverted L1 batches, miniblocks, transactions and
+/// events for good, their rows are moved to the revert log (`reverted_*` tables). Queries over
+/// the chain tables therefore never see reorged-out data, while the explorer can still display it.
+#[derive(Debug)]
+pub struct RevertsDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl RevertsDal<'_, '_> {
+    /// Reverts the chain to `last_kept_l1_batch`: L1 batches after it and miniblocks not included
+    /// in kept batches (including pending ones) are moved to the revert log together with their
+    /// transactions and events. Reverted transactions are returned to the pending state, so that
+    /// they are picked up by the mempool on the next restart. The latest storage values and token
+    /// balances are rolled back to the last kept miniblock, and tokens deployed after it are removed.
+    ///
+    /// Only Postgres data is reverted; the Merkle tree and the sequencer RocksDB must be rebuilt
+    /// separately. Batches whose transactions are archived must not be reverted, since archived
+    /// transactions are not in Postgres.
+    pub async fn revert_to_l1_batch(
+        &mut self,
+        last_kept_l1_batch: L1BatchNumber,
+        reason: &str,
+    ) -> DalResult<RevertedEntities> {
+        let mut transaction = self.storage.start_transaction().await;
+
+        let last_kept_miniblock = sqlx::query!(
+            r#"
+            SELECT COALESCE(MAX(number), 0) AS "number!"
+            FROM miniblocks
+            WHERE l1_batch_number <= $1
+            "#,
+            last_kept_l1_batch.0 as i64
+        )
+        .fetch_one(transaction.conn())
+        .await?
+        .number;
+        let revert_id = sqlx::query!(
+            r#"
+            INSERT INTO reverts (last_kept_l1_batch, last_kept_miniblock, reason, reverted_at)
+            VALUES ($1, $2, $3, NOW())
+            RETURNING id
+            "#,
+            last_kept_l1_batch.0 as i64,
+            last_kept_miniblock,
+            reason
+        )
+        .fetch_one(transaction.conn())
+        .await?
+        .id;
+
+        let l1_batches = sqlx::query!(
+            r#"
+            INSERT INTO reverted_l1_batches (revert_id, number, data)
+            SELECT $1, number, to_jsonb(l1_batches)
+            FROM l1_batches
+            WHERE number > $2
+            "#,
+            revert_id,
+            last_kept_l1_batch.0 as i64
+        )
+        .execute(transaction.conn())
+        .await?
+        .rows_affected();
+        let miniblocks = sqlx::query!(
+            r#"
+            INSERT INTO reverted_miniblocks (revert_id, number, hash, data)
+            SELECT $1, number, hash, to_jsonb(miniblocks)
+            FROM miniblocks
+            WHERE number > $2
+            "#,
+            revert_id,
+            last_kept_miniblock
+        )
+        .execute(transaction.conn())
+        .await?
+        .rows_affected();
+        let transactions = sqlx::query!(
+            r#"
+            INSERT INTO reverted_transactions (revert_id, hash, miniblock_number, data)
+            SELECT $1, hash, miniblock_number, to_jsonb(transactions)
+            FROM transactions
+            WHERE miniblock_number > $2
+            "#,
+            revert_id,
+            last_kept_miniblock
+        )
+        .execute(transaction.conn())
+        .await?
+        .rows_affected();
+        let events = sqlx::query!(
+            r#"
+            INSERT INTO reverted_events
+                (revert_id, miniblock_number, event_index_in_block, tx_hash, data)
+            SELECT $1, miniblock_number, event_index_in_block, tx_hash, to_jsonb(events)
+            FROM events
+            WHERE miniblock_number > $2
+            "#,
+            revert_id,
+            last_kept_miniblock
+        )
+        .execute(transaction.conn())
+        .await?
+        .rows_affected();
+
+        let reverted_transfers = transaction
+            .reverts_dal()
+            .get_transfers_after(last_kept_miniblock)
+            .await?;
+        transaction
+            .tokens_dal()
+            .apply_transfers(
+                MiniblockNumber(last_kept_miniblock as u32),
+                &reverted_transfers,
+            )
+            .await?;
+        // Balances of removed tokens are removed by the cascade.
+        let tokens = sqlx::query!(
+            "DELETE FROM tokens WHERE deployed_in_miniblock > $1",
+            last_kept_miniblock
+        )
+        .execute(transaction.conn())
+        .await?
+        .rows_affected();
+
+        // Slots written in reverted miniblocks are restored from the last storage log before
+        // the revert, or removed if they were first written after it.
+        let restored_slots = sqlx::query!(
+            r#"
+            UPDATE storage
+            SET
+                value = prev.value,
+                tx_hash = prev.tx_hash,
+                updated_at = NOW()
+            FROM
+                (
+                    SELECT DISTINCT
+                        ON (hashed_key) hashed_key,
+                        value,
+                        tx_hash
+                    FROM
+                        storage_logs
+                    WHERE
+                        miniblock_number <= $1
+                        AND hashed_key IN (
+                            SELECT hashed_key
+                            FROM storage_logs
+                            WHERE miniblock_number > $1
+                        )
+                    ORDER BY
+                        hashed_key,
+                        miniblock_number DESC,
+                        operation_number DESC
+                ) AS prev
+            WHERE
+                storage.hashed_key = prev.hashed_key
+            "#,
+            last_kept_miniblock
+        )
+        .execute(transaction.conn())
+        .await?
+        .rows_affected();
+        let removed_slots = sqlx::query!(
+            r#"
+            DELETE FROM storage
+            WHERE
+                hashed_key IN (
+                    SELECT hashed_key
+                    FROM storage_logs
+                    WHERE miniblock_number > $1
+                )
+                AND NOT EXISTS (
+                    SELECT 1
+                    FROM storage_logs
+                    WHERE
+                        storage_logs.hashed_key = storage.hashed_key
+                        AND storage_logs.miniblock_number <= $1
+                )
+            "#,
+            last_kept_miniblock
+        )
+        .execute(transaction.conn())
+        .await?
+        .rows_affected();
+
+        // Transactions reference miniblocks without a cascade, so they are detached first.
+        // Events, storage logs and factory deps are removed together with their miniblocks.
+        sqlx::query!(
+            r#"
+            UPDATE transactions
+            SET
+                in_mempool = FALSE,
+                l1_batch_number = NULL,
+                miniblock_number = NULL,
+                index_in_block = NULL,
+                l1_batch_tx_index = NULL,
+                error = NULL,
+                execution_info = '{}',
+                updated_at = NOW()
+            WHERE miniblock_number > $1
+            "#,
+            last_kept_miniblock
+        )
+        .execute(transaction.conn())
+        .await?;
+        sqlx::query!(
+            "DELETE FROM miniblocks WHERE number > $1",
+            last_kept_miniblock
+        )
+        .execute(transaction.conn())
+        .await?;
+        sqlx::query!(
+            "DELETE FROM l1_batches WHERE number > $1",
+            last_kept_l1_batch.0 as i64
+        )
+        .execute(transaction.conn())
+        .await?;
+
+        transaction.commit().await;
+        Ok(RevertedEntities {
+            revert_id: revert_id as u64,
+            l1_batches,
+            miniblocks,
+            transactions,
+            events,
+            storage_slots: restored_slots + removed_slots,
+            tokens,
+        })
+    }
+
+    /// Returns token transfers made in miniblocks after `last_kept_miniblock`, inverted so that
+    /// applying them undoes the original transfers.
+    async fn get_transfers_after(
+        &mut self,
+        last_kept_miniblock: i64,
+    ) -> DalResult<Vec<TokenTransfer>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                address,
+                topic1,
+                topic2,
+                topic3,
+                topic4,
+                value
+            FROM
+                events
+            WHERE
+                miniblock_number > $1
+                AND topic1 = $2
+            "#,
+            last_kept_miniblock,
+            TRANSFER_EVENT_SIGNATURE.as_bytes()
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                // Missing topics are stored as empty byte strings.
+                let indexed_topics = [row.topic1, row.topic2, row.topic3, row.topic4]
+                    .into_iter()
+                    .filter(|topic| !topic.is_empty())
+                    .map(|topic| H256::from_slice(&topic))
+                    .collect();
+                let event = VmEvent {
+                    address: Address::from_slice(&row.address),
+                    indexed_topics,
+                    value: row.value,
+                    ..VmEvent::default()
+                };
+                let transfer = decode_token_transfer(&event)?;
+                Some(TokenTransfer {
+                    from: transfer.to,
+                    to: transfer.from,
+                    ..transfer
+                })
+            })
+            .collect())
+    }
+
+    /// Returns all reorged-out versions of the miniblock with the specified number, oldest first.
+    pub async fn get_reorged_out_blocks(
+        &mut self,
+        number: MiniblockNumber,
+    ) -> DalResult<Vec<api::ReorgedOutBlock>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                m.number,
+                m.hash,
+                (m.data ->> 'l1_batch_number')::BIGINT AS "l1_batch_number?",
+                (m.data ->> 'timestamp')::BIGINT AS "timestamp!",
+                ARRAY(
+                    SELECT t.hash
+                    FROM reverted_transactions t
+                    WHERE t.revert_id = m.revert_id AND t.miniblock_number = m.number
+                    ORDER BY (t.data ->> 'index_in_block')::INT
+                ) AS "transactions!",
+                r.id AS revert_id,
+                r.last_kept_l1_batch,
+                r.last_kept_miniblock,
+                r.reason,
+                r.reverted_at
+            FROM reverted_miniblocks m
+            JOIN reverts r ON r.id = m.revert_id
+            WHERE m.number = $1
+            ORDER BY r.id
+            "#,
+            number.0 as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| api::ReorgedOutBlock {
+                number: MiniblockNumber(row.number as u32),
+                hash: H256::from_slice(&row.hash),
+                l1_batch_number: row
+                    .l1_batch_number
+                    .map(|number| L1BatchNumber(number as u32)),
+                timestamp: row.timestamp as u64,
+                transactions: row
+                    .transactions
+                    .iter()
+                    .map(|hash| H256::from_slice(hash))
+                    .collect(),
+                revert: revert_info(
+                    row.revert_id,
+                    row.last_kept_l1_batch,
+                    row.last_kept_miniblock,
+                    row.reason,
+                    row.reverted_at,
+                ),
+            })
+            .collect())
+    }
+
+    /// Returns all reorged-out inclusions of the transaction, oldest first.
+    pub async fn get_reorged_out_transaction(
+        &mut self,
+        hash: H256,
+    ) -> DalResult<Vec<api::ReorgedOutTransaction>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                t.miniblock_number,
+                (t.data ->> 'l1_batch_number')::BIGINT AS "l1_batch_number?",
+                (t.data ->> 'index_in_block')::INT AS "index_in_block?",
+                t.data ->> 'error' AS "error?",
+                r.id AS revert_id,
+                r.last_kept_l1_batch,
+                r.last_kept_miniblock,
+                r.reason,
+                r.reverted_at
+            FROM reverted_transactions t
+            JOIN reverts r ON r.id = t.revert_id
+            WHERE t.hash = $1
+            ORDER BY r.id
+            "#,
+            hash.as_bytes()
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| api::ReorgedOutTransaction {
+                hash,
+                miniblock_number: MiniblockNumber(row.miniblock_number as u32),
+                l1_batch_number: row
+                    .l1_batch_number
+                    .map(|number| L1BatchNumber(number as u32)),
+                index_in_block: row.index_in_block.map(|index| index as u32),
+                error: row.error,
+                revert: revert_info(
+                    row.revert_id,
+                    row.last_kept_l1_batch,
+                    row.last_kept_miniblock,
+                    row.reason,
+                    row.reverted_at,
+                ),
+            })
+            .collect())
+    }
+}
+
+fn revert_info(
+    id: i64,
+    last_kept_l1_batch: i64,
+    last_kept_miniblock: i64,
+    reason: String,
+    reverted_at: NaiveDateTime,
+) -> api::RevertInfo {
+    api::RevertInfo {
+        id: id as u64,
+        last_kept_l1_batch: L1BatchNumber(last_kept_l1_batch as u32),
+        last_kept_miniblock: MiniblockNumber(last_kept_miniblock as u32),
+        reason,
+        reverted_at: DateTime::<Utc>::from_naive_utc_and_offset(reverted_at, Utc),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ola_types::{
+        block::MiniblockHeader, log::StorageLog, protocol_version::ProtocolVersionId,
+        tokens::TokenMetadata, tx::IncludedTxLocation, AccountTreeId, StorageKey, U256,
+    };
+    use ola_utils::convert::u64s_to_bytes;
+
+    use super::*;
+
+    fn miniblock_header(number: MiniblockNumber) -> MiniblockHeader {
+        MiniblockHeader {
+            number,
+            timestamp: 1_700_000_000 + u64::from(number.0),
+            hash: H256::from_low_u64_be(number.0.into()),
+            l1_tx_count: 0,
+            l2_tx_count: 0,
+            base_system_contracts_hashes: Default::default(),
+            protocol_version: Some(ProtocolVersionId::latest()),
+        }
+    }
+
+    fn token_metadata() -> TokenMetadata {
+        TokenMetadata {
+            name: "Test".to_owned(),
+            symbol: "TST".to_owned(),
+            decimals: 18,
+        }
+    }
+
+    async fn storage_value(storage: &mut StorageProcessor<'_>, key: &StorageKey) -> Option<H256> {
+        sqlx::query!(
+            "SELECT value FROM storage WHERE hashed_key = $1",
+            key.hashed_key().as_bytes()
+        )
+        .fetch_optional(storage.conn())
+        .await
+        .unwrap()
+        .map(|row| H256::from_slice(&row.value))
+    }
+
+    async fn write_slots(
+        storage: &mut StorageProcessor<'_>,
+        miniblock_number: MiniblockNumber,
+        logs: Vec<StorageLog>,
+    ) {
+        let logs = [(H256::repeat_byte(0xee), logs)];
+        storage
+            .storage_logs_dal()
+            .insert_storage_logs(miniblock_number, &logs)
+            .await
+            .unwrap();
+        storage
+            .storage_dal()
+            .apply_storage_logs(&logs)
+            .await
+            .unwrap();
+    }
+
+    #[ignore]
+    #[tokio::test]
+    async fn reverting_rolls_back_storage_and_tokens() {
+        let mut storage = StorageProcessor::establish_connection(true).await;
+        // Nothing is committed, so the test leaves the database intact.
+        let mut transaction = storage.start_transaction().await;
+        let last_kept_l1_batch = transaction
+            .blocks_dal()
+            .get_sealed_l1_batch_number()
+            .await
+            .unwrap();
+        let kept_miniblock = transaction
+            .blocks_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .unwrap()
+            + 1;
+        let reverted_miniblock = kept_miniblock + 1;
+
+        let account = AccountTreeId::new(Address::repeat_byte(1));
+        let overwritten_slot = StorageKey::new(account, H256::from_low_u64_be(1));
+        let created_slot = StorageKey::new(account, H256::from_low_u64_be(2));
+        let token = Address::repeat_byte(2);
+        let reverted_token = Address::repeat_byte(3);
+        let (alice, bob) = (Address::repeat_byte(4), Address::repeat_byte(5));
+
+        transaction
+            .blocks_dal()
+            .insert_miniblock(&miniblock_header(kept_miniblock))
+            .await
+            .unwrap();
+        transaction
+            .blocks_dal()
+            .set_miniblock_l1_batch_number(kept_miniblock, last_kept_l1_batch)
+            .await
+            .unwrap();
+        write_slots(
+            &mut transaction,
+            kept_miniblock,
+            vec![StorageLog::new_write_log(
+                overwritten_slot,
+                H256::repeat_byte(0xaa),
+            )],
+        )
+        .await;
+        transaction
+            .tokens_dal()
+            .add_tokens(kept_miniblock, &[(token, token_metadata())])
+            .await
+            .unwrap();
+        let mint = TokenTransfer {
+            token,
+            from: Address::zero(),
+            to: alice,
+            amount: 100.into(),
+        };
+        transaction
+            .tokens_dal()
+            .apply_transfers(kept_miniblock, &[mint])
+            .await
+            .unwrap();
+
+        // The reverted miniblock is pending, i.e. not included in any L1 batch.
+        transaction
+            .blocks_dal()
+            .insert_miniblock(&miniblock_header(reverted_miniblock))
+            .await
+            .unwrap();
+        write_slots(
+            &mut transaction,
+            reverted_miniblock,
+            vec![
+                StorageLog::new_write_log(overwritten_slot, H256::repeat_byte(0xbb)),
+                StorageLog::new_write_log(created_slot, H256::repeat_byte(0xcc)),
+            ],
+        )
+        .await;
+        transaction
+            .tokens_dal()
+            .add_tokens(reverted_miniblock, &[(reverted_token, token_metadata())])
+            .await
+            .unwrap();
+        let transfer = TokenTransfer {
+            token,
+            from: alice,
+            to: bob,
+            amount: 40.into(),
+        };
+        let transfer_event = VmEvent {
+            address: token,
+            indexed_topics: vec![*TRANSFER_EVENT_SIGNATURE, alice, bob],
+            value: u64s_to_bytes(&[40]),
+            ..VmEvent::default()
+        };
+        let tx_location = IncludedTxLocation {
+            tx_hash: H256::repeat_byte(0xee),
+            tx_index_in_miniblock: 0,
+            tx_initiator_address: alice,
+        };
+        transaction
+            .events_dal()
+            .save_events(reverted_miniblock, &[(tx_location, vec![&transfer_event])])
+            .await
+            .unwrap();
+        transaction
+            .tokens_dal()
+            .apply_transfers(reverted_miniblock, &[transfer])
+            .await
+            .unwrap();
+
+        let reverted = transaction
+            .reverts_dal()
+            .revert_to_l1_batch(last_kept_l1_batch, "test")
+            .await
+            .unwrap();
+        assert!(reverted.miniblocks >= 1);
+        assert!(reverted.storage_slots >= 2);
+        assert!(reverted.tokens >= 1);
+
+        assert_eq!(
+            storage_value(&mut transaction, &overwritten_slot).await,
+            Some(H256::repeat_byte(0xaa))
+        );
+        assert_eq!(storage_value(&mut transaction, &created_slot).await, None);
+
+        let mut tokens_dal = transaction.tokens_dal();
+        let balances = tokens_dal.get_token_balances(alice).await.unwrap();
+        assert_eq!(balances.len(), 1);
+        assert_eq!(balances[0].balance, U256::from(100));
+        assert!(tokens_dal.get_token_balances(bob).await.unwrap().is_empty());
+        assert!(tokens_dal
+            .get_token_details(reverted_token)
+            .await
+            .unwrap()
+            .is_none());
+
+        let reorged_out = transaction
+            .reverts_dal()
+            .get_reorged_out_blocks(reverted_miniblock)
+            .await
+            .unwrap();
+        let reorged_out = reorged_out.last().unwrap();
+        assert_eq!(reorged_out.hash, miniblock_header(reverted_miniblock).hash);
+        assert_eq!(reorged_out.revert.id, reverted.revert_id);
+    }
+}
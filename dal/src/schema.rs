@@ -0,0 +1,270 @@
+//! Manifest of the database schema expected by the binary.
+//!
+//! The manifest (`dal/schema_manifest.json`) lists the tables and columns of a database with all
+//! migrations applied. It's generated by the `schema_manifest` tool and embedded into the binary.
+//! On startup, [`check_schema()`] compares it with the live schema, so that a database with
+//! missing or diverged migrations is reported with the full list of differences instead of failing
+//! on the first mismatched query. Tables and columns absent from the manifest are ignored, so that
+//! migrations applied by a newer binary don't prevent an older one from starting.
+
+use std::{collections::BTreeMap, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{connection::ConnectionPool, DalResult, StorageProcessor};
+
+const MANIFEST_JSON: &str = include_str!("../schema_manifest.json");
+
+/// Models read with `sqlx::FromRow` from a single table: `(file in dal/src/models, model, table)`.
+/// The `schema_manifest` tool checks that every field of these models is a column of the table.
+pub const MODEL_TABLES: &[(&str, &str, &str)] = &[
+    ("storage_block.rs", "StorageL1BatchHeader", "l1_batches"),
+    ("storage_block.rs", "StorageMiniblockHeader", "miniblocks"),
+    (
+        "storage_transaction.rs",
+        "StorageTransaction",
+        "transactions",
+    ),
+    (
+        "storage_protocol_version.rs",
+        "StorageProtocolVersion",
+        "protocol_versions",
+    ),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnSchema {
+    /// Type as reported by `information_schema.columns.data_type`, e.g. `bigint` or `ARRAY`.
+    pub data_type: String,
+    pub nullable: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableSchema {
+    pub columns: BTreeMap<String, ColumnSchema>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaManifest {
+    pub tables: BTreeMap<String, TableSchema>,
+}
+
+impl SchemaManifest {
+    /// Returns the manifest embedded into the binary.
+    pub fn expected() -> Self {
+        serde_json::from_str(MANIFEST_JSON).expect("embedded schema manifest is malformed")
+    }
+
+    /// Reads the schema of base tables in the `public` schema, except for the migrations table.
+    pub async fn load(storage: &mut StorageProcessor<'_>) -> DalResult<Self> {
+        let rows: Vec<(String, String, String, String)> = sqlx::query_as(
+            "SELECT c.table_name::TEXT, c.column_name::TEXT, c.data_type::TEXT, \
+                c.is_nullable::TEXT \
+            FROM information_schema.columns c \
+            JOIN information_schema.tables t \
+                ON t.table_schema = c.table_schema AND t.table_name = c.table_name \
+            WHERE c.table_schema = 'public' AND t.table_type = 'BASE TABLE' \
+                AND c.table_name <> '_sqlx_migrations'",
+        )
+        .fetch_all(storage.conn())
+        .await?;
+
+        let mut manifest = Self::default();
+        for (table, column, data_type, is_nullable) in rows {
+            let column_schema = ColumnSchema {
+                data_type,
+                nullable: is_nullable == "YES",
+            };
+            manifest
+                .tables
+                .entry(table)
+                .or_default()
+                .columns
+                .insert(column, column_schema);
+        }
+        Ok(manifest)
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).expect("failed serializing manifest");
+        json.push('\n');
+        json
+    }
+
+    /// Lists the differences of `live` from this manifest.
+    pub fn diff(&self, live: &Self) -> SchemaDiff {
+        let mut drifts = vec![];
+        for (table, expected) in &self.tables {
+            let Some(live_table) = live.tables.get(table) else {
+                drifts.push(SchemaDrift::MissingTable(table.clone()));
+                continue;
+            };
+            for (column, expected) in &expected.columns {
+                match live_table.columns.get(column) {
+                    None => drifts.push(SchemaDrift::MissingColumn {
+                        table: table.clone(),
+                        column: column.clone(),
+                    }),
+                    Some(actual) if actual != expected => {
+                        drifts.push(SchemaDrift::ColumnMismatch {
+                            table: table.clone(),
+                            column: column.clone(),
+                            expected: expected.clone(),
+                            actual: actual.clone(),
+                        });
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        SchemaDiff(drifts)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaDrift {
+    MissingTable(String),
+    MissingColumn {
+        table: String,
+        column: String,
+    },
+    ColumnMismatch {
+        table: String,
+        column: String,
+        expected: ColumnSchema,
+        actual: ColumnSchema,
+    },
+}
+
+impl fmt::Display for SchemaDrift {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let describe = |column: &ColumnSchema| {
+            let null = if column.nullable { "NULL" } else { "NOT NULL" };
+            format!("{} {null}", column.data_type)
+        };
+        match self {
+            Self::MissingTable(table) => write!(formatter, "table `{table}` is missing"),
+            Self::MissingColumn { table, column } => {
+                write!(formatter, "column `{table}.{column}` is missing")
+            }
+            Self::ColumnMismatch {
+                table,
+                column,
+                expected,
+                actual,
+            } => write!(
+                formatter,
+                "column `{table}.{column}` is `{}`, expected `{}`",
+                describe(actual),
+                describe(expected)
+            ),
+        }
+    }
+}
+
+/// Differences of the live schema from the expected one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaDiff(pub Vec<SchemaDrift>);
+
+impl SchemaDiff {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for SchemaDiff {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for drift in &self.0 {
+            writeln!(formatter, "- {drift}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the names of fields of the struct `model` defined in `source`.
+pub fn model_fields(source: &str, model: &str) -> Option<Vec<String>> {
+    let header = format!("pub struct {model} {{");
+    let start = source.find(&header)? + header.len();
+    let body = &source[start..start + source[start..].find("\n}")?];
+    let fields = body
+        .lines()
+        .map(str::trim)
+        .filter_map(|line| line.strip_prefix("pub "))
+        .filter_map(|line| Some(line.split_once(':')?.0.trim().to_owned()))
+        .collect();
+    Some(fields)
+}
+
+/// Checks that the database schema matches the manifest embedded into the binary.
+pub async fn check_schema(pool: &ConnectionPool) -> anyhow::Result<()> {
+    if matches!(pool, ConnectionPool::Mock(_)) {
+        return Ok(());
+    }
+    let mut storage = pool.access_storage_tagged("schema_check").await;
+    let live = SchemaManifest::load(&mut storage).await?;
+    let diff = SchemaManifest::expected().diff(&live);
+    anyhow::ensure!(
+        diff.is_empty(),
+        "database schema differs from the one expected by this binary (are migrations applied?):\n{diff}"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(data_type: &str, nullable: bool) -> ColumnSchema {
+        ColumnSchema {
+            data_type: data_type.to_owned(),
+            nullable,
+        }
+    }
+
+    #[test]
+    fn embedded_manifest_covers_models() {
+        let manifest = SchemaManifest::expected();
+        assert!(manifest.tables.contains_key("l1_batches"));
+        let source = include_str!("models/storage_block.rs");
+        let fields = model_fields(source, "StorageL1BatchHeader").unwrap();
+        assert!(fields.contains(&"fee_account_address".to_owned()));
+        let columns = &manifest.tables["l1_batches"].columns;
+        for field in fields {
+            assert!(columns.contains_key(&field), "{field}");
+        }
+    }
+
+    #[test]
+    fn diffing_schemas() {
+        let mut expected = SchemaManifest::default();
+        let table = expected.tables.entry("miniblocks".to_owned()).or_default();
+        table
+            .columns
+            .insert("number".to_owned(), column("bigint", false));
+        table
+            .columns
+            .insert("hash".to_owned(), column("bytea", false));
+        expected
+            .tables
+            .insert("events".to_owned(), TableSchema::default());
+
+        let mut live = SchemaManifest::default();
+        let table = live.tables.entry("miniblocks".to_owned()).or_default();
+        table
+            .columns
+            .insert("number".to_owned(), column("integer", false));
+        table
+            .columns
+            .insert("extra".to_owned(), column("text", true));
+
+        let diff = expected.diff(&live);
+        assert_eq!(diff.0.len(), 3, "{diff}");
+        assert_eq!(diff.0[0], SchemaDrift::MissingTable("events".to_owned()));
+        assert_eq!(diff.0[1].to_string(), "column `miniblocks.hash` is missing");
+        assert_eq!(
+            diff.0[2].to_string(),
+            "column `miniblocks.number` is `integer NOT NULL`, expected `bigint NOT NULL`"
+        );
+        assert!(expected.diff(&expected).is_empty());
+    }
+}
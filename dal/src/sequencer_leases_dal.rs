@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use crate::{time_utils::pg_interval_from_duration, DalResult, StorageProcessor};
+
+/// Leadership lease held by the active sequencer instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequencerLease {
+    pub holder: String,
+    pub fencing_token: i64,
+}
+
+#[derive(Debug)]
+pub struct SequencerLeasesDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl SequencerLeasesDal<'_, '_> {
+    /// Acquires the lease for `holder` if it's not held or has expired. Each acquisition
+    /// increments the fencing token, invalidating the lease of the previous holder.
+    pub async fn try_acquire(
+        &mut self,
+        holder: &str,
+        ttl: Duration,
+    ) -> DalResult<Option<SequencerLease>> {
+        let ttl = pg_interval_from_duration(ttl);
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO sequencer_leases (id, holder, fencing_token, expires_at)
+            VALUES (1, $1, 1, NOW() + $2::INTERVAL)
+            ON CONFLICT (id) DO UPDATE
+            SET
+                holder = EXCLUDED.holder,
+                fencing_token = sequencer_leases.fencing_token + 1,
+                expires_at = EXCLUDED.expires_at
+            WHERE
+                sequencer_leases.expires_at < NOW()
+            RETURNING
+                fencing_token
+            "#,
+            holder,
+            &ttl
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        Ok(row.map(|row| SequencerLease {
+            holder: holder.to_owned(),
+            fencing_token: row.fencing_token,
+        }))
+    }
+
+    /// Extends the lease. Returns `false` if the lease is lost, i.e. has expired
+    /// and possibly was acquired by another instance.
+    pub async fn renew(&mut self, lease: &SequencerLease, ttl: Duration) -> DalResult<bool> {
+        let ttl = pg_interval_from_duration(ttl);
+        let result = sqlx::query!(
+            r#"
+            UPDATE sequencer_leases
+            SET
+                expires_at = NOW() + $3::INTERVAL
+            WHERE
+                holder = $1
+                AND fencing_token = $2
+                AND expires_at >= NOW()
+            "#,
+            lease.holder,
+            lease.fencing_token,
+            &ttl
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Checks that the lease is still valid. If called within a transaction, the lease row is
+    /// locked until the end of the transaction, so that the lease can't be taken over
+    /// while the transaction writes data.
+    pub async fn check(&mut self, lease: &SequencerLease) -> DalResult<bool> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                expires_at >= NOW() AS "is_valid!"
+            FROM
+                sequencer_leases
+            WHERE
+                holder = $1
+                AND fencing_token = $2
+            FOR SHARE
+            "#,
+            lease.holder,
+            lease.fencing_token
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+        Ok(row.map_or(false, |row| row.is_valid))
+    }
+
+    /// Releases the lease, so that a standby can take over without waiting for it to expire.
+    pub async fn release(&mut self, lease: &SequencerLease) -> DalResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE sequencer_leases
+            SET
+                expires_at = NOW() - INTERVAL '1 second'
+            WHERE
+                holder = $1
+                AND fencing_token = $2
+            "#,
+            lease.holder,
+            lease.fencing_token
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+}
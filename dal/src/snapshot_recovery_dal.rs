@@ -1,6 +1,6 @@
 use ola_types::{snapshots::SnapshotRecoveryStatus, L1BatchNumber, MiniblockNumber, H256};
 
-use crate::StorageProcessor;
+use crate::{DalResult, StorageProcessor};
 
 #[derive(Debug)]
 pub struct SnapshotRecoveryDal<'a, 'c> {
@@ -11,7 +11,7 @@ impl SnapshotRecoveryDal<'_, '_> {
     pub async fn set_applied_snapshot_status(
         &mut self,
         status: &SnapshotRecoveryStatus,
-    ) -> sqlx::Result<()> {
+    ) -> DalResult<()> {
         sqlx::query!(
             r#"
             INSERT INTO
@@ -52,7 +52,7 @@ impl SnapshotRecoveryDal<'_, '_> {
 
     pub async fn get_applied_snapshot_status(
         &mut self,
-    ) -> sqlx::Result<Option<SnapshotRecoveryStatus>> {
+    ) -> DalResult<Option<SnapshotRecoveryStatus>> {
         let record = sqlx::query!(
             r#"
             SELECT
@@ -4,9 +4,12 @@ use ola_utils::bytes_to_chunks;
 
 use std::collections::{HashMap, HashSet};
 
-use ola_types::{log::StorageLog, MiniblockNumber, StorageKey, StorageValue, H256, U256};
+use ola_types::{
+    log::StorageLog, snapshots::SnapshotFactoryDependency, MiniblockNumber, StorageKey,
+    StorageValue, H256, U256,
+};
 
-use crate::StorageProcessor;
+use crate::{DalError, DalResult, StorageProcessor};
 
 #[derive(Debug)]
 pub struct StorageDal<'a, 'c> {
@@ -20,7 +23,7 @@ impl StorageDal<'_, '_> {
         &mut self,
         block_number: MiniblockNumber,
         factory_deps: &HashMap<H256, Vec<u8>>,
-    ) {
+    ) -> DalResult<()> {
         let (bytecode_hashes, bytecodes): (Vec<_>, Vec<_>) = factory_deps
             .iter()
             .map(|dep| (dep.0.as_bytes(), dep.1.as_slice()))
@@ -39,14 +42,14 @@ impl StorageDal<'_, '_> {
             block_number.0 as i64,
         )
         .execute(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
+        Ok(())
     }
 
     pub async fn apply_storage_logs(
         &mut self,
         updates: &[(H256, Vec<StorageLog>)],
-    ) -> HashMap<StorageKey, (H256, StorageValue)> {
+    ) -> DalResult<HashMap<StorageKey, (H256, StorageValue)>> {
         let unique_updates: HashMap<_, _> = updates
             .iter()
             .flat_map(|(tx_hash, storage_logs)| {
@@ -56,15 +59,24 @@ impl StorageDal<'_, '_> {
             })
             .collect();
 
-        let query_parts = unique_updates.iter().map(|(key, (tx_hash, value))| {
-            (
-                key.hashed_key().0.to_vec(),
-                key.address().0.as_slice(),
-                key.key().0.as_slice(),
-                value.as_bytes(),
-                tx_hash.0.as_slice(),
-            )
-        });
+        if unique_updates.is_empty() {
+            return Ok(unique_updates);
+        }
+
+        // Rows are sorted by the hashed key so that concurrent upserts touch index pages
+        // in the same order, which avoids deadlocks and improves locality for large miniblocks.
+        let query_parts = unique_updates
+            .iter()
+            .sorted_by_key(|(key, _)| key.hashed_key())
+            .map(|(key, (tx_hash, value))| {
+                (
+                    key.hashed_key().0.to_vec(),
+                    key.address().0.as_slice(),
+                    key.key().0.as_slice(),
+                    value.as_bytes(),
+                    tx_hash.0.as_slice(),
+                )
+            });
         let (hashed_keys, addresses, keys, values, tx_hashes): (
             Vec<_>,
             Vec<_>,
@@ -88,21 +100,20 @@ impl StorageDal<'_, '_> {
             &tx_hashes as &[&[u8]],
         )
         .execute(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
 
-        unique_updates
+        Ok(unique_updates)
     }
 
     pub async fn get_base_system_contracts(
         &mut self,
         entrypoint_hash: H256,
         default_aa_hash: H256,
-    ) -> BaseSystemContracts {
+    ) -> DalResult<BaseSystemContracts> {
         let entrypoint_bytecode = self
             .get_factory_dep(entrypoint_hash)
-            .await
-            .expect("Bootloader code should be present in the database");
+            .await?
+            .ok_or(DalError::NotFound)?;
         let entrypoint_code = SystemContractCode {
             code: entrypoint_bytecode,
             hash: entrypoint_hash,
@@ -110,38 +121,37 @@ impl StorageDal<'_, '_> {
 
         let default_aa_bytecode = self
             .get_factory_dep(default_aa_hash)
-            .await
-            .expect("Default account code should be present in the database");
+            .await?
+            .ok_or(DalError::NotFound)?;
 
         let default_aa_code = SystemContractCode {
             code: default_aa_bytecode,
             hash: default_aa_hash,
         };
-        BaseSystemContracts {
+        Ok(BaseSystemContracts {
             entrypoint: entrypoint_code,
             default_aa: default_aa_code,
-        }
+        })
     }
 
-    pub async fn get_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
-        sqlx::query!(
+    pub async fn get_factory_dep(&mut self, hash: H256) -> DalResult<Option<Vec<u8>>> {
+        let row = sqlx::query!(
             "SELECT bytecode FROM factory_deps WHERE bytecode_hash = $1",
             hash.as_bytes(),
         )
         .fetch_optional(self.storage.conn())
-        .await
-        .unwrap()
-        .map(|row| row.bytecode)
+        .await?;
+        Ok(row.map(|row| row.bytecode))
     }
 
     /// Returns bytecodes for factory deps with the specified `hashes`.
     pub async fn get_factory_deps(
         &mut self,
         hashes: &HashSet<H256>,
-    ) -> HashMap<U256, Vec<[u8; 32]>> {
+    ) -> DalResult<HashMap<U256, Vec<[u8; 32]>>> {
         let hashes_as_bytes: Vec<_> = hashes.iter().map(H256::as_bytes).collect();
 
-        sqlx::query!(
+        let rows = sqlx::query!(
             r#"
             SELECT
                 bytecode,
@@ -154,15 +164,70 @@ impl StorageDal<'_, '_> {
             &hashes_as_bytes as &[&[u8]],
         )
         .fetch_all(self.storage.conn())
-        .await
-        .unwrap()
-        .into_iter()
-        .map(|row| {
-            (
-                U256::from_big_endian(&row.bytecode_hash),
-                bytes_to_chunks(&row.bytecode),
-            )
-        })
-        .collect()
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                (
+                    U256::from_big_endian(&row.bytecode_hash),
+                    bytes_to_chunks(&row.bytecode),
+                )
+            })
+            .collect())
+    }
+
+    /// Returns raw bytecodes for factory deps with the specified `hashes`, keyed by the hash.
+    pub async fn get_factory_dep_bytecodes(
+        &mut self,
+        hashes: &HashSet<H256>,
+    ) -> DalResult<HashMap<H256, Vec<u8>>> {
+        let hashes_as_bytes: Vec<_> = hashes.iter().map(H256::as_bytes).collect();
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                bytecode,
+                bytecode_hash
+            FROM
+                factory_deps
+            WHERE
+                bytecode_hash = ANY ($1)
+            "#,
+            &hashes_as_bytes as &[&[u8]],
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (H256::from_slice(&row.bytecode_hash), row.bytecode))
+            .collect())
+    }
+
+    /// Returns all factory deps inserted up to and including the specified miniblock.
+    pub async fn get_snapshot_factory_deps(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+    ) -> DalResult<Vec<SnapshotFactoryDependency>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                bytecode_hash,
+                bytecode
+            FROM
+                factory_deps
+            WHERE
+                miniblock_number <= $1
+            "#,
+            miniblock_number.0 as i64,
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let rows = rows.into_iter().map(|row| SnapshotFactoryDependency {
+            bytecode_hash: H256::from_slice(&row.bytecode_hash),
+            bytecode: row.bytecode,
+        });
+        Ok(rows.collect())
     }
 }
@@ -1,11 +1,17 @@
 use std::collections::HashMap;
 
 use ola_types::{
-    log::StorageLog, AccountTreeId, Address, L1BatchNumber, MiniblockNumber, StorageKey, H256, U256,
+    log::StorageLog,
+    snapshots::{GenesisStorageEntry, SnapshotStorageLog},
+    storage::diff::StorageValueDiff,
+    AccountTreeId, Address, L1BatchNumber, MiniblockNumber, StorageKey, H256, U256,
 };
 use sqlx::types::chrono::Utc;
 
-use crate::{models::storage_log::StorageTreeEntry, StorageProcessor};
+use crate::{
+    macro_utils::COPY_FLUSH_THRESHOLD_BYTES, models::storage_log::StorageTreeEntry, DalError,
+    DalResult, StorageProcessor,
+};
 
 #[derive(Debug)]
 pub struct StorageLogsDal<'a, 'c> {
@@ -19,8 +25,8 @@ impl StorageLogsDal<'_, '_> {
         &mut self,
         block_number: MiniblockNumber,
         logs: &[(H256, Vec<StorageLog>)],
-    ) {
-        self.insert_storage_logs_inner(block_number, logs, 0).await;
+    ) -> DalResult<()> {
+        self.insert_storage_logs_inner(block_number, logs, 0).await
     }
 
     async fn insert_storage_logs_inner(
@@ -28,7 +34,11 @@ impl StorageLogsDal<'_, '_> {
         block_number: MiniblockNumber,
         logs: &[(H256, Vec<StorageLog>)],
         mut operation_number: u32,
-    ) {
+    ) -> DalResult<()> {
+        if logs.iter().all(|(_, logs)| logs.is_empty()) {
+            return Ok(()); // Shortcut to avoid opening a `COPY` stream with no data
+        }
+
         let mut copy = self
             .storage
             .conn()
@@ -39,8 +49,7 @@ impl StorageLogsDal<'_, '_> {
                 )
                 FROM STDIN WITH (DELIMITER '|')",
             )
-            .await
-            .unwrap();
+            .await?;
 
         let mut buffer = String::new();
         let now = Utc::now().naive_utc().to_string();
@@ -60,16 +69,23 @@ impl StorageLogsDal<'_, '_> {
                 );
 
                 operation_number += 1;
+                if buffer.len() >= COPY_FLUSH_THRESHOLD_BYTES {
+                    copy.send(buffer.as_bytes()).await?;
+                    buffer.clear();
+                }
             }
         }
-        copy.send(buffer.as_bytes()).await.unwrap();
-        copy.finish().await.unwrap();
+        if !buffer.is_empty() {
+            copy.send(buffer.as_bytes()).await?;
+        }
+        copy.finish().await?;
+        Ok(())
     }
 
     pub async fn get_touched_slots_for_l1_batch(
         &mut self,
         l1_batch_number: L1BatchNumber,
-    ) -> HashMap<StorageKey, H256> {
+    ) -> DalResult<HashMap<StorageKey, H256>> {
         let rows = sqlx::query!(
             "SELECT address, key, value \
             FROM storage_logs \
@@ -80,8 +96,7 @@ impl StorageLogsDal<'_, '_> {
             l1_batch_number.0 as i64
         )
         .fetch_all(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
 
         let touched_slots = rows.into_iter().map(|row| {
             let key = StorageKey::new(
@@ -90,15 +105,15 @@ impl StorageLogsDal<'_, '_> {
             );
             (key, H256::from_slice(&row.value))
         });
-        touched_slots.collect()
+        Ok(touched_slots.collect())
     }
 
     pub async fn get_l1_batches_and_indices_for_initial_writes(
         &mut self,
         hashed_keys: &[H256],
-    ) -> HashMap<H256, (L1BatchNumber, u64)> {
+    ) -> DalResult<HashMap<H256, (L1BatchNumber, u64)>> {
         if hashed_keys.is_empty() {
-            return HashMap::new(); // Shortcut to save time on communication with DB in the common case
+            return Ok(HashMap::new()); // Shortcut to save time on communication with DB in the common case
         }
 
         let hashed_keys: Vec<_> = hashed_keys.iter().map(H256::as_bytes).collect();
@@ -116,10 +131,10 @@ impl StorageLogsDal<'_, '_> {
             &hashed_keys as &[&[u8]],
         )
         .fetch_all(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
 
-        rows.into_iter()
+        Ok(rows
+            .into_iter()
             .map(|row| {
                 (
                     H256::from_slice(&row.hashed_key),
@@ -129,12 +144,17 @@ impl StorageLogsDal<'_, '_> {
                     ),
                 )
             })
-            .collect()
+            .collect())
     }
 
-    pub async fn resolve_hashed_keys(&mut self, hashed_keys: &[H256]) -> Vec<StorageKey> {
+    /// Resolves hashed keys to storage keys. Returns [`DalError::NotFound`] if a key was never
+    /// written to.
+    pub async fn resolve_hashed_keys(
+        &mut self,
+        hashed_keys: &[H256],
+    ) -> DalResult<Vec<StorageKey>> {
         let hashed_keys: Vec<_> = hashed_keys.iter().map(H256::as_bytes).collect();
-        sqlx::query!(
+        let rows = sqlx::query!(
             "SELECT \
                 (SELECT ARRAY[address,key] FROM storage_logs \
                 WHERE hashed_key = u.hashed_key \
@@ -144,24 +164,24 @@ impl StorageLogsDal<'_, '_> {
             &hashed_keys as &[&[u8]],
         )
         .fetch_all(self.storage.conn())
-        .await
-        .unwrap()
-        .into_iter()
-        .map(|row| {
-            let address_and_key = row.address_and_key.unwrap();
-            StorageKey::new(
-                AccountTreeId::new(Address::from_slice(&address_and_key[0])),
-                H256::from_slice(&address_and_key[1]),
-            )
-        })
-        .collect()
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let address_and_key = row.address_and_key.ok_or(DalError::NotFound)?;
+                Ok(StorageKey::new(
+                    AccountTreeId::new(Address::from_slice(&address_and_key[0])),
+                    H256::from_slice(&address_and_key[1]),
+                ))
+            })
+            .collect()
     }
 
     async fn get_storage_values(
         &mut self,
         hashed_keys: &[H256],
         miniblock_number: MiniblockNumber,
-    ) -> HashMap<H256, Option<H256>> {
+    ) -> DalResult<HashMap<H256, Option<H256>>> {
         let hashed_keys: Vec<_> = hashed_keys.iter().map(H256::as_bytes).collect();
 
         let rows = sqlx::query!(
@@ -174,44 +194,73 @@ impl StorageLogsDal<'_, '_> {
             miniblock_number.0 as i64
         )
         .fetch_all(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
 
-        rows.into_iter()
+        Ok(rows
+            .into_iter()
             .map(|row| {
                 let key = H256::from_slice(&row.hashed_key);
                 let value = row.value.map(|value| H256::from_slice(&value));
                 (key, value)
             })
-            .collect()
+            .collect())
     }
 
     pub async fn get_previous_storage_values(
         &mut self,
         hashed_keys: &[H256],
         next_l1_batch: L1BatchNumber,
-    ) -> HashMap<H256, Option<H256>> {
+    ) -> DalResult<HashMap<H256, Option<H256>>> {
         let (miniblock_number, _) = self
             .storage
             .blocks_dal()
             .get_miniblock_range_of_l1_batch(next_l1_batch)
-            .await
-            .unwrap();
+            .await?
+            .ok_or(DalError::NotFound)?;
 
         if miniblock_number == MiniblockNumber(0) {
-            hashed_keys.iter().copied().map(|key| (key, None)).collect()
+            Ok(hashed_keys.iter().copied().map(|key| (key, None)).collect())
         } else {
             self.get_storage_values(hashed_keys, miniblock_number - 1)
                 .await
         }
     }
 
+    /// Returns values of all slots modified in miniblocks `(from, to]`, as of `from` and `to`.
+    pub async fn get_storage_diff(
+        &mut self,
+        from: MiniblockNumber,
+        to: MiniblockNumber,
+    ) -> DalResult<Vec<StorageValueDiff>> {
+        if from >= to {
+            return Ok(vec![]);
+        }
+        let hashed_keys = self
+            .storage
+            .storage_web3_dal()
+            .modified_keys_in_miniblocks(from + 1..=to)
+            .await;
+        let keys = self.resolve_hashed_keys(&hashed_keys).await?;
+        let before = self.get_storage_values(&hashed_keys, from).await?;
+        let after = self.get_storage_values(&hashed_keys, to).await?;
+
+        Ok(hashed_keys
+            .iter()
+            .zip(keys)
+            .map(|(hashed_key, key)| StorageValueDiff {
+                key,
+                before: before.get(hashed_key).copied().flatten(),
+                after: after.get(hashed_key).copied().flatten(),
+            })
+            .collect())
+    }
+
     /// Counts the total number of storage logs in the specified miniblock,
     // TODO(PLA-596): add storage log count to snapshot metadata instead?
     pub async fn count_miniblock_storage_logs(
         &mut self,
         miniblock_number: MiniblockNumber,
-    ) -> sqlx::Result<u64> {
+    ) -> DalResult<u64> {
         let count = sqlx::query_scalar!(
             "SELECT COUNT(*) FROM storage_logs WHERE miniblock_number = $1",
             miniblock_number.0 as i32
@@ -221,13 +270,34 @@ impl StorageLogsDal<'_, '_> {
         Ok(count.unwrap_or(0) as u64)
     }
 
+    /// Counts storage logs written in all miniblocks of the specified L1 batch.
+    pub async fn count_l1_batch_storage_logs(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> DalResult<u64> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*)
+            FROM
+                storage_logs
+                INNER JOIN miniblocks ON miniblocks.number = storage_logs.miniblock_number
+            WHERE
+                miniblocks.l1_batch_number = $1
+            "#,
+            l1_batch_number.0 as i64
+        )
+        .fetch_one(self.storage.conn())
+        .await?;
+        Ok(count.unwrap_or(0) as u64)
+    }
+
     /// Gets a starting tree entry for each of the supplied `key_ranges` for the specified
     /// `miniblock_number`. This method is used during Merkle tree recovery.
     pub async fn get_chunk_starts_for_miniblock(
         &mut self,
         miniblock_number: MiniblockNumber,
         key_ranges: &[core::ops::RangeInclusive<H256>],
-    ) -> sqlx::Result<Vec<Option<StorageTreeEntry>>> {
+    ) -> DalResult<Vec<Option<StorageTreeEntry>>> {
         let (start_keys, end_keys): (Vec<_>, Vec<_>) = key_ranges
             .iter()
             .map(|range| (range.start().as_bytes(), range.end().as_bytes()))
@@ -285,7 +355,7 @@ impl StorageLogsDal<'_, '_> {
         &mut self,
         miniblock_number: MiniblockNumber,
         key_range: core::ops::RangeInclusive<H256>,
-    ) -> sqlx::Result<Vec<StorageTreeEntry>> {
+    ) -> DalResult<Vec<StorageTreeEntry>> {
         let rows = sqlx::query!(
             r#"
             SELECT
@@ -316,4 +386,84 @@ impl StorageLogsDal<'_, '_> {
         });
         Ok(rows.collect())
     }
+
+    /// Fetches the latest values of storage slots with hashed keys in `key_range` as of
+    /// the specified `miniblock_number`. This is used to create state snapshots.
+    pub async fn get_snapshot_storage_logs(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+        key_range: core::ops::RangeInclusive<H256>,
+    ) -> DalResult<Vec<SnapshotStorageLog>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT DISTINCT
+                ON (hashed_key) hashed_key,
+                value
+            FROM
+                storage_logs
+            WHERE
+                miniblock_number <= $1
+                AND hashed_key >= $2::bytea
+                AND hashed_key <= $3::bytea
+            ORDER BY
+                hashed_key,
+                miniblock_number DESC,
+                operation_number DESC
+            "#,
+            miniblock_number.0 as i64,
+            key_range.start().as_bytes(),
+            key_range.end().as_bytes()
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let rows = rows.into_iter().map(|row| SnapshotStorageLog {
+            hashed_key: H256::from_slice(&row.hashed_key),
+            value: H256::from_slice(&row.value),
+        });
+        Ok(rows.collect())
+    }
+
+    /// Same as [`Self::get_snapshot_storage_logs()`], but returns full storage keys and skips
+    /// zeroed slots, which don't need to be recreated in a genesis state.
+    pub async fn get_genesis_storage_entries(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+        key_range: core::ops::RangeInclusive<H256>,
+    ) -> DalResult<Vec<GenesisStorageEntry>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT DISTINCT
+                ON (hashed_key) hashed_key,
+                address,
+                key,
+                value
+            FROM
+                storage_logs
+            WHERE
+                miniblock_number <= $1
+                AND hashed_key >= $2::bytea
+                AND hashed_key <= $3::bytea
+            ORDER BY
+                hashed_key,
+                miniblock_number DESC,
+                operation_number DESC
+            "#,
+            miniblock_number.0 as i64,
+            key_range.start().as_bytes(),
+            key_range.end().as_bytes()
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let rows = rows.into_iter().filter_map(|row| {
+            let value = H256::from_slice(&row.value);
+            (!value.is_zero()).then(|| GenesisStorageEntry {
+                address: Address::from_slice(&row.address),
+                key: H256::from_slice(&row.key),
+                value,
+            })
+        });
+        Ok(rows.collect())
+    }
 }
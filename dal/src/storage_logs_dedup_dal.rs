@@ -4,7 +4,7 @@ use ola_types::{log::LogQuery, AccountTreeId, Address, L1BatchNumber, StorageKey
 use ola_utils::u256_to_h256;
 use sqlx::types::chrono::Utc;
 
-use crate::StorageProcessor;
+use crate::{macro_utils::COPY_FLUSH_THRESHOLD_BYTES, DalResult, StorageProcessor};
 
 #[derive(Debug)]
 pub struct StorageLogsDedupDal<'a, 'c> {
@@ -16,7 +16,7 @@ impl StorageLogsDedupDal<'_, '_> {
         &mut self,
         l1_batch_number: L1BatchNumber,
         read_logs: &[LogQuery],
-    ) {
+    ) -> DalResult<()> {
         let mut copy = self
             .storage
             .conn()
@@ -24,29 +24,34 @@ impl StorageLogsDedupDal<'_, '_> {
                 "COPY protective_reads (l1_batch_number, address, key, created_at, updated_at) \
                 FROM STDIN WITH (DELIMITER '|')",
             )
-            .await
-            .unwrap();
+            .await?;
 
-        let mut bytes: Vec<u8> = Vec::new();
+        let mut buffer = String::new();
         let now = Utc::now().naive_utc().to_string();
         for log in read_logs.iter() {
-            let address_str = format!("\\\\x{}", hex::encode(log.address.0));
-            let key_str = format!("\\\\x{}", hex::encode(u256_to_h256(log.key).0));
-            let row = format!(
-                "{}|{}|{}|{}|{}\n",
-                l1_batch_number, address_str, key_str, now, now
+            writeln_str!(
+                &mut buffer,
+                r"{l1_batch_number}|\\x{address:x}|\\x{key:x}|{now}|{now}",
+                address = log.address,
+                key = u256_to_h256(log.key)
             );
-            bytes.extend_from_slice(row.as_bytes());
+            if buffer.len() >= COPY_FLUSH_THRESHOLD_BYTES {
+                copy.send(buffer.as_bytes()).await?;
+                buffer.clear();
+            }
         }
-        copy.send(bytes).await.unwrap();
-        copy.finish().await.unwrap();
+        if !buffer.is_empty() {
+            copy.send(buffer.as_bytes()).await?;
+        }
+        copy.finish().await?;
+        Ok(())
     }
 
     pub async fn insert_initial_writes(
         &mut self,
         l1_batch_number: L1BatchNumber,
         written_storage_keys: &[StorageKey],
-    ) {
+    ) -> DalResult<()> {
         let hashed_keys: Vec<_> = written_storage_keys
             .iter()
             .map(|key| StorageKey::raw_hashed_key(key.address(), key.key()).to_vec())
@@ -54,7 +59,7 @@ impl StorageLogsDedupDal<'_, '_> {
 
         let last_index = self
             .max_set_enumeration_index()
-            .await
+            .await?
             .map(|(last_index, _)| last_index)
             .unwrap_or(0);
         let indices: Vec<_> = ((last_index + 1)..=(last_index + hashed_keys.len() as u64))
@@ -70,87 +75,126 @@ impl StorageLogsDedupDal<'_, '_> {
             l1_batch_number.0 as i64,
         )
         .execute(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
+        Ok(())
     }
 
     pub async fn get_protective_reads_for_l1_batch(
         &mut self,
         l1_batch_number: L1BatchNumber,
-    ) -> HashSet<StorageKey> {
-        sqlx::query!(
+    ) -> DalResult<HashSet<StorageKey>> {
+        let rows = sqlx::query!(
             "SELECT address, key FROM protective_reads WHERE l1_batch_number = $1",
             l1_batch_number.0 as i64
         )
         .fetch_all(self.storage.conn())
-        .await
-        .unwrap()
-        .into_iter()
-        .map(|row| {
-            StorageKey::new(
-                AccountTreeId::new(Address::from_slice(&row.address)),
-                H256::from_slice(&row.key),
-            )
-        })
-        .collect()
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                StorageKey::new(
+                    AccountTreeId::new(Address::from_slice(&row.address)),
+                    H256::from_slice(&row.key),
+                )
+            })
+            .collect())
     }
 
-    pub async fn max_set_enumeration_index(&mut self) -> Option<(u64, L1BatchNumber)> {
-        sqlx::query!(
+    pub async fn max_set_enumeration_index(&mut self) -> DalResult<Option<(u64, L1BatchNumber)>> {
+        let row = sqlx::query!(
             "SELECT index, l1_batch_number FROM initial_writes \
             WHERE index IS NOT NULL \
             ORDER BY index DESC LIMIT 1",
         )
         .fetch_optional(self.storage.conn())
-        .await
-        .unwrap()
-        .map(|row| {
-            (
-                row.index.unwrap() as u64,
-                L1BatchNumber(row.l1_batch_number as u32),
-            )
-        })
+        .await?;
+        // The query only selects rows with a set index.
+        Ok(
+            row.and_then(|row| {
+                Some((row.index? as u64, L1BatchNumber(row.l1_batch_number as u32)))
+            }),
+        )
+    }
+
+    /// Returns the greatest leaf index assigned to keys first written in L1 batches
+    /// before `l1_batch_number`, or `None` if there are no such keys.
+    pub async fn max_enumeration_index_before(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> DalResult<Option<u64>> {
+        let row = sqlx::query!(
+            "SELECT MAX(index) AS \"max_index\" FROM initial_writes \
+            WHERE l1_batch_number < $1",
+            l1_batch_number.0 as i64,
+        )
+        .fetch_one(self.storage.conn())
+        .await?;
+        Ok(row.max_index.map(|index| index as u64))
     }
 
-    pub async fn filter_written_slots(&mut self, hashed_keys: &[H256]) -> HashSet<H256> {
+    /// Returns hashed keys with the specified leaf indices together with their indices,
+    /// ordered by index. Indices not assigned to any key are skipped.
+    pub async fn get_hashed_keys_by_indices(
+        &mut self,
+        indices: &[u64],
+    ) -> DalResult<Vec<(H256, u64)>> {
+        let indices: Vec<_> = indices.iter().map(|&index| index as i64).collect();
+        let rows = sqlx::query!(
+            "SELECT hashed_key, index FROM initial_writes \
+            WHERE index = ANY($1) \
+            ORDER BY index",
+            &indices,
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| Some((H256::from_slice(&row.hashed_key), row.index? as u64)))
+            .collect())
+    }
+
+    pub async fn filter_written_slots(&mut self, hashed_keys: &[H256]) -> DalResult<HashSet<H256>> {
         let hashed_keys: Vec<_> = hashed_keys.iter().map(H256::as_bytes).collect();
-        sqlx::query!(
+        let rows = sqlx::query!(
             "SELECT hashed_key FROM initial_writes \
             WHERE hashed_key = ANY($1)",
             &hashed_keys as &[&[u8]],
         )
         .fetch_all(self.storage.conn())
-        .await
-        .unwrap()
-        .into_iter()
-        .map(|row| H256::from_slice(&row.hashed_key))
-        .collect()
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| H256::from_slice(&row.hashed_key))
+            .collect())
     }
 
     pub async fn initial_writes_for_batch(
         &mut self,
         l1_batch_number: L1BatchNumber,
-    ) -> Vec<(H256, Option<u64>)> {
-        sqlx::query!(
+    ) -> DalResult<Vec<(H256, Option<u64>)>> {
+        let rows = sqlx::query!(
             "SELECT hashed_key, index FROM initial_writes \
             WHERE l1_batch_number = $1 \
             ORDER BY index",
             l1_batch_number.0 as i64
         )
         .fetch_all(self.storage.conn())
-        .await
-        .unwrap()
-        .into_iter()
-        .map(|row| {
-            (
-                H256::from_slice(&row.hashed_key),
-                row.index.map(|i| i as u64),
-            )
-        })
-        .collect()
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                (
+                    H256::from_slice(&row.hashed_key),
+                    row.index.map(|i| i as u64),
+                )
+            })
+            .collect())
     }
 
-    pub async fn set_indices_for_initial_writes(&mut self, indexed_keys: &[(H256, u64)]) {
+    pub async fn set_indices_for_initial_writes(
+        &mut self,
+        indexed_keys: &[(H256, u64)],
+    ) -> DalResult<()> {
         let (hashed_keys, indices): (Vec<_>, Vec<_>) = indexed_keys
             .iter()
             .map(|(hashed_key, index)| (hashed_key.as_bytes(), *index as i64))
@@ -167,7 +211,7 @@ impl StorageLogsDedupDal<'_, '_> {
             &indices,
         )
         .execute(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
+        Ok(())
     }
 }
@@ -1,11 +1,14 @@
-use std::ops;
+use std::{collections::HashMap, ops};
 
 use ola_types::{
-    get_nonce_key, storage::StorageKey, Address, L1BatchNumber, MiniblockNumber, H256, U256,
+    api::{StorageEntry, StorageValueChange},
+    get_nonce_key,
+    storage::StorageKey,
+    Address, L1BatchNumber, MiniblockNumber, H256, U256,
 };
 use ola_utils::convert::h256_to_u256;
 
-use crate::{models::storage_block::ResolvedL1BatchForMiniblock, SqlxError, StorageProcessor};
+use crate::{models::storage_block::ResolvedL1BatchForMiniblock, DalResult, StorageProcessor};
 
 #[derive(Debug)]
 pub struct StorageWeb3Dal<'a, 'c> {
@@ -17,7 +20,7 @@ impl StorageWeb3Dal<'_, '_> {
         &mut self,
         address: Address,
         block_number: MiniblockNumber,
-    ) -> Result<U256, SqlxError> {
+    ) -> DalResult<U256> {
         let nonce_key = get_nonce_key(&address);
         let nonce_value = self
             .get_historical_value_unchecked(&nonce_key, block_number)
@@ -31,14 +34,14 @@ impl StorageWeb3Dal<'_, '_> {
         &mut self,
         key: &StorageKey,
         block_number: MiniblockNumber,
-    ) -> Result<H256, SqlxError> {
+    ) -> DalResult<H256> {
         {
             // We need to proper distinguish if the value is zero or None
             // for the VM to correctly determine initial writes.
             // So, we accept that the value is None if it's zero and it wasn't initially written at the moment.
             let hashed_key = key.hashed_key();
 
-            sqlx::query!(
+            let value = sqlx::query!(
                 r#"
                 SELECT value
                 FROM storage_logs
@@ -50,15 +53,130 @@ impl StorageWeb3Dal<'_, '_> {
                 block_number.0 as i64
             )
             .fetch_optional(self.storage.conn())
-            .await
-            .map(|option_row| {
-                option_row
-                    .map(|row| H256::from_slice(&row.value))
-                    .unwrap_or_else(H256::zero)
-            })
+            .await?
+            .map(|row| H256::from_slice(&row.value))
+            .unwrap_or_else(H256::zero);
+            Ok(value)
         }
     }
 
+    /// Returns values of the storage slot at the end of each miniblock in `range` that wrote it,
+    /// ordered by miniblock number. At most `limit` changes are returned.
+    pub async fn get_storage_history(
+        &mut self,
+        key: &StorageKey,
+        range: ops::RangeInclusive<MiniblockNumber>,
+        limit: usize,
+    ) -> DalResult<Vec<StorageValueChange>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT DISTINCT ON (miniblock_number) miniblock_number, tx_hash, value
+            FROM storage_logs
+            WHERE hashed_key = $1 AND miniblock_number BETWEEN $2 AND $3
+            ORDER BY miniblock_number, operation_number DESC
+            LIMIT $4
+            "#,
+            key.hashed_key().as_bytes(),
+            range.start().0 as i64,
+            range.end().0 as i64,
+            limit as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| StorageValueChange {
+                miniblock_number: MiniblockNumber(row.miniblock_number as u32),
+                tx_hash: H256::from_slice(&row.tx_hash),
+                value: H256::from_slice(&row.value),
+            })
+            .collect())
+    }
+
+    /// Returns non-zero storage slots of `address` as of the end of `block_number` with keys
+    /// greater than `after`, ordered by the key. At most `limit` slots are returned.
+    pub async fn get_account_storage_range(
+        &mut self,
+        address: Address,
+        block_number: MiniblockNumber,
+        after: Option<H256>,
+        limit: usize,
+    ) -> DalResult<Vec<StorageEntry>> {
+        // Slots are filtered by value after picking their latest write; the outer `LIMIT` still
+        // stops the index scan early since `DISTINCT ON` preserves the key order.
+        let rows = sqlx::query!(
+            r#"
+            SELECT key, value
+            FROM (
+                SELECT DISTINCT ON (key) key, value
+                FROM storage_logs
+                WHERE address = $1 AND miniblock_number <= $2 AND ($3::bytea IS NULL OR key > $3)
+                ORDER BY key, miniblock_number DESC, operation_number DESC
+            ) AS latest_values
+            WHERE value != $4
+            ORDER BY key
+            LIMIT $5
+            "#,
+            address.as_bytes(),
+            block_number.0 as i64,
+            after.as_ref().map(H256::as_bytes),
+            H256::zero().as_bytes(),
+            limit as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| StorageEntry {
+                key: H256::from_slice(&row.key),
+                value: H256::from_slice(&row.value),
+            })
+            .collect())
+    }
+
+    /// Batched version of [`Self::get_historical_value_unchecked()`] reading all `keys` with a single query.
+    /// Keys not written as of `block_number` are mapped to zero.
+    pub async fn get_historical_values_unchecked(
+        &mut self,
+        keys: &[StorageKey],
+        block_number: MiniblockNumber,
+    ) -> DalResult<HashMap<StorageKey, H256>> {
+        let hashed_keys: Vec<_> = keys.iter().map(StorageKey::hashed_key).collect();
+        let hashed_keys_bytes: Vec<_> = hashed_keys.iter().map(|key| key.0.to_vec()).collect();
+        let rows = sqlx::query!(
+            r#"
+            SELECT DISTINCT ON (hashed_key) hashed_key, value
+            FROM storage_logs
+            WHERE hashed_key = ANY($1) AND miniblock_number <= $2
+            ORDER BY hashed_key, miniblock_number DESC, operation_number DESC
+            "#,
+            &hashed_keys_bytes,
+            block_number.0 as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let values: HashMap<_, _> = rows
+            .into_iter()
+            .map(|row| {
+                (
+                    H256::from_slice(&row.hashed_key),
+                    H256::from_slice(&row.value),
+                )
+            })
+            .collect();
+        Ok(keys
+            .iter()
+            .zip(&hashed_keys)
+            .map(|(key, hashed_key)| {
+                let value = values.get(hashed_key).copied().unwrap_or_else(H256::zero);
+                (*key, value)
+            })
+            .collect())
+    }
+
     pub async fn modified_keys_in_miniblocks(
         &mut self,
         miniblock_numbers: ops::RangeInclusive<MiniblockNumber>,
@@ -82,7 +200,7 @@ impl StorageWeb3Dal<'_, '_> {
     pub async fn resolve_l1_batch_number_of_miniblock(
         &mut self,
         miniblock_number: MiniblockNumber,
-    ) -> Result<ResolvedL1BatchForMiniblock, SqlxError> {
+    ) -> DalResult<ResolvedL1BatchForMiniblock> {
         let row = sqlx::query!(
             "SELECT \
                 (SELECT l1_batch_number FROM miniblocks WHERE number = $1) as \"block_batch?\", \
@@ -1,60 +1,165 @@
-use crate::StorageProcessor;
+use std::collections::HashMap;
 
+use bigdecimal::BigDecimal;
+use ola_types::{
+    api::{TokenBalance, TokenDetails},
+    tokens::{TokenMetadata, TokenTransfer},
+    Address, MiniblockNumber,
+};
+use ola_utils::{bigdecimal_to_u256, u256_to_big_decimal};
+
+use crate::{DalResult, StorageProcessor};
+
+/// Tokens registered on deployment and their balances, maintained from transfer events.
 #[derive(Debug)]
 pub struct TokensDal<'a, 'c> {
     pub(crate) storage: &'a mut StorageProcessor<'c>,
 }
 
-// impl TokensDal<'_, '_> {
-//     pub async fn get_all_l2_token_addresses(&mut self) -> Vec<Address> {
-//         {
-//             let records = sqlx::query!("SELECT l2_address FROM tokens")
-//                 .fetch_all(self.storage.conn())
-//                 .await
-//                 .unwrap();
-//             let addresses: Vec<Address> = records
-//                 .into_iter()
-//                 .map(|record| Address::from_slice(&record.l2_address))
-//                 .collect();
-//             addresses
-//         }
-//     }
-
-//     pub async fn add_tokens(&mut self, tokens: Vec<TokenInfo>) {
-//         {
-//             let mut copy = self
-//             .storage
-//             .conn()
-//             .copy_in_raw(
-//                 "COPY tokens (l1_address, l2_address, name, symbol, decimals, well_known, created_at, updated_at)
-//                 FROM STDIN WITH (DELIMITER '|')",
-//             )
-//             .await
-//             .unwrap();
-
-//             let mut bytes: Vec<u8> = Vec::new();
-//             let now = Utc::now().naive_utc().to_string();
-//             for TokenInfo {
-//                 l1_address,
-//                 l2_address,
-//                 metadata:
-//                     TokenMetadata {
-//                         name,
-//                         symbol,
-//                         decimals,
-//                     },
-//             } in tokens
-//             {
-//                 let l1_address_str = format!("\\\\x{}", hex::encode(l1_address.0));
-//                 let l2_address_str = format!("\\\\x{}", hex::encode(l2_address.0));
-//                 let row = format!(
-//                     "{}|{}|{}|{}|{}|FALSE|{}|{}\n",
-//                     l1_address_str, l2_address_str, name, symbol, decimals, now, now
-//                 );
-//                 bytes.extend_from_slice(row.as_bytes());
-//             }
-//             copy.send(bytes).await.unwrap();
-//             copy.finish().await.unwrap();
-//         }
-//     }
-// }
+impl TokensDal<'_, '_> {
+    pub async fn add_tokens(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+        tokens: &[(Address, TokenMetadata)],
+    ) -> DalResult<()> {
+        let mut addresses = Vec::with_capacity(tokens.len());
+        let mut names = Vec::with_capacity(tokens.len());
+        let mut symbols = Vec::with_capacity(tokens.len());
+        let mut decimals = Vec::with_capacity(tokens.len());
+        for (address, metadata) in tokens {
+            addresses.push(address.as_bytes());
+            names.push(metadata.name.clone());
+            symbols.push(metadata.symbol.clone());
+            decimals.push(i32::from(metadata.decimals));
+        }
+
+        sqlx::query!(
+            r#"
+            INSERT INTO tokens (
+                address, name, symbol, decimals, deployed_in_miniblock, created_at, updated_at
+            )
+            SELECT u.address, u.name, u.symbol, u.decimals, $5, NOW(), NOW()
+            FROM UNNEST($1::bytea[], $2::text[], $3::text[], $4::int[])
+                AS u (address, name, symbol, decimals)
+            ON CONFLICT (address) DO NOTHING
+            "#,
+            &addresses as &[&[u8]],
+            &names,
+            &symbols,
+            &decimals,
+            miniblock_number.0 as i64,
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Applies `transfers` to balances. Transfers of contracts not registered as tokens
+    /// are ignored.
+    pub async fn apply_transfers(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+        transfers: &[TokenTransfer],
+    ) -> DalResult<()> {
+        let mut deltas: HashMap<(Address, Address), BigDecimal> = HashMap::new();
+        for transfer in transfers {
+            let amount = u256_to_big_decimal(transfer.amount).with_scale(0);
+            if !transfer.from.is_zero() {
+                *deltas.entry((transfer.from, transfer.token)).or_default() -= amount.clone();
+            }
+            if !transfer.to.is_zero() {
+                *deltas.entry((transfer.to, transfer.token)).or_default() += amount;
+            }
+        }
+        if deltas.is_empty() {
+            return Ok(());
+        }
+
+        let mut accounts = Vec::with_capacity(deltas.len());
+        let mut tokens = Vec::with_capacity(deltas.len());
+        let mut amounts = Vec::with_capacity(deltas.len());
+        for ((account, token), delta) in deltas {
+            accounts.push(account.as_bytes().to_vec());
+            tokens.push(token.as_bytes().to_vec());
+            amounts.push(delta);
+        }
+
+        sqlx::query!(
+            r#"
+            INSERT INTO token_balances (account_address, token_address, balance, updated_at_miniblock)
+            SELECT u.account_address, u.token_address, u.delta, $4
+            FROM UNNEST($1::bytea[], $2::bytea[], $3::numeric[])
+                AS u (account_address, token_address, delta)
+                INNER JOIN tokens ON tokens.address = u.token_address
+            ON CONFLICT (account_address, token_address) DO UPDATE
+            SET
+                balance = token_balances.balance + EXCLUDED.balance,
+                updated_at_miniblock = EXCLUDED.updated_at_miniblock
+            "#,
+            &accounts,
+            &tokens,
+            &amounts,
+            miniblock_number.0 as i64,
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_token_details(&mut self, address: Address) -> DalResult<Option<TokenDetails>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT name, symbol, decimals, deployed_in_miniblock
+            FROM tokens
+            WHERE address = $1
+            "#,
+            address.as_bytes()
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        Ok(row.map(|row| TokenDetails {
+            address,
+            name: row.name,
+            symbol: row.symbol,
+            decimals: row.decimals as u8,
+            deployed_in_block: MiniblockNumber(row.deployed_in_miniblock as u32),
+        }))
+    }
+
+    /// Returns non-zero balances of `account` in all registered tokens.
+    pub async fn get_token_balances(&mut self, account: Address) -> DalResult<Vec<TokenBalance>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                tokens.address,
+                tokens.name,
+                tokens.symbol,
+                tokens.decimals,
+                token_balances.balance
+            FROM
+                token_balances
+                INNER JOIN tokens ON tokens.address = token_balances.token_address
+            WHERE
+                token_balances.account_address = $1
+                AND token_balances.balance > 0
+            ORDER BY
+                tokens.address
+            "#,
+            account.as_bytes()
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| TokenBalance {
+                token: Address::from_slice(&row.address),
+                name: row.name,
+                symbol: row.symbol,
+                decimals: row.decimals as u8,
+                balance: bigdecimal_to_u256(row.balance),
+            })
+            .collect())
+    }
+}
@@ -3,9 +3,11 @@ use ola_config::constants::contracts::{
 };
 use ola_types::{
     api::{self, BlockId, BlockNumber, TransactionDetails},
-    Address, L2ChainId, MiniblockNumber, H2048, H256, U256, U64,
+    tx::execution_digest::ExecutionDigest,
+    Address, L1BatchNumber, L2ChainId, MiniblockNumber, H2048, H256, U256, U64,
 };
 use ola_utils::h256_to_account_address;
+use sqlx::types::chrono::{DateTime, Utc};
 
 use crate::models::storage_block::{bind_block_where_sql_params, web3_block_where_sql};
 use crate::models::storage_transaction::{extract_web3_transaction, web3_transaction_select_sql};
@@ -16,7 +18,7 @@ use crate::{
             StorageTransaction, StorageTransactionDetails, StorageTransactionReceipt,
         },
     },
-    SqlxError, StorageProcessor,
+    DalResult, StorageProcessor,
 };
 
 #[derive(Debug)]
@@ -28,7 +30,7 @@ impl TransactionsWeb3Dal<'_, '_> {
     pub async fn next_nonce_by_initiator_account(
         &mut self,
         initiator_address: Address,
-    ) -> Result<u32, SqlxError> {
+    ) -> DalResult<u32> {
         let latest_block_number = self
             .storage
             .blocks_web3_dal()
@@ -90,7 +92,7 @@ impl TransactionsWeb3Dal<'_, '_> {
         &mut self,
         transaction_id: api::TransactionId,
         chain_id: L2ChainId,
-    ) -> Result<Option<api::Transaction>, SqlxError> {
+    ) -> DalResult<Option<api::Transaction>> {
         let where_sql = match transaction_id {
             api::TransactionId::Hash(_) => "transactions.hash = $1".to_owned(),
             api::TransactionId::Block(block_id, _) => {
@@ -131,7 +133,7 @@ impl TransactionsWeb3Dal<'_, '_> {
     pub async fn get_transaction_receipt(
         &mut self,
         hash: H256,
-    ) -> Result<Option<api::TransactionReceipt>, SqlxError> {
+    ) -> DalResult<Option<api::TransactionReceipt>> {
         {
             // TODO: check transactions.data->'to' as "transfer_to?",
             // and transactions.data->'contractAddress' as "execute_contract_address?",
@@ -198,14 +200,17 @@ impl TransactionsWeb3Dal<'_, '_> {
                         })
                         // For better compatibility with various clients, we never return null.
                         .or_else(|| Some(Address::default())),
-                    gas_used: None,
-                    effective_gas_price: None,
+                    // Ola doesn't charge fees for transactions yet.
+                    gas_used: Some(U256::zero()),
+                    effective_gas_price: Some(U256::zero()),
                     logs_bloom: H2048::default(),
                     cumulative_gas_used: U256::default(),
                     contract_address: db_row
                         .contract_address
                         .map(|addr| h256_to_account_address(&H256::from_slice(&addr))),
                     logs: vec![],
+                    // L2 -> L1 logs are not persisted yet.
+                    l2_to_l1_logs: vec![],
                     status,
                     root: block_hash,
                     // Even though the Rust SDK recommends us to supply "None" for legacy transactions
@@ -240,7 +245,7 @@ impl TransactionsWeb3Dal<'_, '_> {
                     })
                     .collect();
 
-                    receipt.logs = logs;
+                    receipt.set_logs(logs);
 
                     Ok(Some(receipt))
                 }
@@ -254,7 +259,7 @@ impl TransactionsWeb3Dal<'_, '_> {
     pub async fn get_transaction_receipts(
         &mut self,
         hashes: &[H256],
-    ) -> Result<Vec<api::TransactionReceipt>, SqlxError> {
+    ) -> DalResult<Vec<api::TransactionReceipt>> {
         let mut receipts: Vec<api::TransactionReceipt> = sqlx::query_as!(
             StorageTransactionReceipt,
             r#"
@@ -316,7 +321,7 @@ impl TransactionsWeb3Dal<'_, '_> {
             let logs_for_tx = logs.remove(&receipt.transaction_hash);
 
             if let Some(logs) = logs_for_tx {
-                receipt.logs = logs
+                let logs = logs
                     .into_iter()
                     .map(|mut log| {
                         log.block_hash = receipt.block_hash;
@@ -324,16 +329,57 @@ impl TransactionsWeb3Dal<'_, '_> {
                         log
                     })
                     .collect();
+                receipt.set_logs(logs);
             }
         }
 
         Ok(receipts)
     }
 
+    /// Returns hashes of up to `limit` sealed transactions initiated by `address`, ordered by
+    /// the miniblock number and the index in the miniblock and starting strictly after
+    /// the `after` position.
+    pub async fn get_sealed_tx_hashes_by_initiator(
+        &mut self,
+        address: Address,
+        after: Option<(MiniblockNumber, u32)>,
+        limit: usize,
+    ) -> DalResult<Vec<H256>> {
+        let (after_miniblock, after_index) =
+            after.map_or((-1, -1), |(number, index)| (number.0 as i64, index as i32));
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                hash
+            FROM
+                transactions
+            WHERE
+                initiator_address = $1
+                AND miniblock_number IS NOT NULL
+                AND (miniblock_number, index_in_block) > ($2, $3)
+            ORDER BY
+                miniblock_number ASC,
+                index_in_block ASC
+            LIMIT
+                $4
+            "#,
+            address.as_bytes(),
+            after_miniblock,
+            after_index,
+            limit as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| H256::from_slice(&row.hash))
+            .collect())
+    }
+
     pub async fn get_transaction_details(
         &mut self,
         hash: H256,
-    ) -> Result<Option<TransactionDetails>, SqlxError> {
+    ) -> DalResult<Option<TransactionDetails>> {
         {
             let storage_tx_details: Option<StorageTransactionDetails> = sqlx::query_as!(
                 StorageTransactionDetails,
@@ -342,7 +388,8 @@ impl TransactionsWeb3Dal<'_, '_> {
                         transactions.initiator_address,
                         transactions.received_at,
                         transactions.miniblock_number,
-                        transactions.error
+                        transactions.error,
+                        transactions.execute_after
                     FROM transactions
                     LEFT JOIN miniblocks ON miniblocks.number = transactions.miniblock_number
                     LEFT JOIN l1_batches ON l1_batches.number = miniblocks.l1_batch_number
@@ -359,12 +406,93 @@ impl TransactionsWeb3Dal<'_, '_> {
         }
     }
 
+    /// Returns the number of the sealed L1 batch that includes the transaction.
+    pub async fn get_transaction_l1_batch_number(
+        &mut self,
+        hash: H256,
+    ) -> DalResult<Option<L1BatchNumber>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                l1_batch_number
+            FROM
+                transactions
+            WHERE
+                hash = $1
+            "#,
+            hash.as_bytes()
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+        Ok(row
+            .and_then(|row| row.l1_batch_number)
+            .map(|number| L1BatchNumber(number as u32)))
+    }
+
+    /// Returns information on the dropped transaction, if the transaction was dropped.
+    pub async fn get_dropped_transaction(
+        &mut self,
+        hash: H256,
+    ) -> DalResult<Option<api::DroppedTransaction>> {
+        let row = sqlx::query!(
+            "SELECT reason, replaced_by, dropped_at FROM dropped_transactions WHERE hash = $1",
+            hash.as_bytes()
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+        Ok(row.map(|row| api::DroppedTransaction {
+            reason: row
+                .reason
+                .parse()
+                .unwrap_or_else(|err| panic!("invalid drop reason for tx {hash:?}: {err}")),
+            replaced_by: row.replaced_by.map(|hash| H256::from_slice(&hash)),
+            dropped_at: DateTime::<Utc>::from_naive_utc_and_offset(row.dropped_at, Utc),
+        }))
+    }
+
+    /// Returns the simulated and (if the transaction is sealed) actual execution digests
+    /// of a transaction submitted with simulation recording.
+    pub async fn get_transaction_simulation(
+        &mut self,
+        hash: H256,
+    ) -> DalResult<Option<api::TransactionSimulationDiff>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                simulated_write_set_hash,
+                simulated_events_hash,
+                actual_write_set_hash,
+                actual_events_hash
+            FROM
+                transaction_simulations
+            WHERE
+                tx_hash = $1
+            "#,
+            hash.as_bytes()
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+        Ok(row.map(|row| {
+            let simulated = ExecutionDigest {
+                write_set_hash: H256::from_slice(&row.simulated_write_set_hash),
+                events_hash: H256::from_slice(&row.simulated_events_hash),
+            };
+            let actual = row.actual_write_set_hash.zip(row.actual_events_hash).map(
+                |(write_set_hash, events_hash)| ExecutionDigest {
+                    write_set_hash: H256::from_slice(&write_set_hash),
+                    events_hash: H256::from_slice(&events_hash),
+                },
+            );
+            api::TransactionSimulationDiff::new(simulated, actual)
+        }))
+    }
+
     /// Returns the server transactions (not API ones) from a certain miniblock.
     /// Returns an empty list if the miniblock doesn't exist.
     pub async fn get_raw_miniblock_transactions(
         &mut self,
         miniblock: MiniblockNumber,
-    ) -> sqlx::Result<Vec<ola_types::Transaction>> {
+    ) -> DalResult<Vec<ola_types::Transaction>> {
         let rows = sqlx::query_as!(
             StorageTransaction,
             r#"
@@ -0,0 +1,230 @@
+use std::ops;
+
+use ola_types::{L1BatchNumber, H256};
+
+use crate::{DalResult, StorageProcessor};
+
+/// Rows of archived transactions, in the Postgres JSON representation.
+#[derive(Debug, Clone, Default)]
+pub struct ArchivedRows {
+    pub transactions: Vec<serde_json::Value>,
+    pub events: Vec<serde_json::Value>,
+    pub call_traces: Vec<serde_json::Value>,
+}
+
+#[derive(Debug)]
+pub struct TransactionsArchiveDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl TransactionsArchiveDal<'_, '_> {
+    pub async fn get_last_archived_l1_batch(&mut self) -> DalResult<Option<L1BatchNumber>> {
+        let row = sqlx::query!(
+            r#"SELECT MAX(l1_batch_number) AS "number" FROM transactions_archive_segments"#
+        )
+        .fetch_one(self.storage.conn())
+        .await?;
+        Ok(row.number.map(|number| L1BatchNumber(number as u32)))
+    }
+
+    /// Returns hashes of the transactions of the L1 batch that can be archived. Protocol upgrade
+    /// transactions are referenced by `protocol_versions`, so they are always kept in Postgres.
+    pub async fn get_archivable_tx_hashes(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> DalResult<Vec<H256>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT hash
+            FROM transactions
+            WHERE l1_batch_number = $1
+                AND NOT EXISTS (
+                    SELECT 1 FROM protocol_versions WHERE upgrade_tx_hash = transactions.hash
+                )
+            ORDER BY miniblock_number, index_in_block
+            "#,
+            l1_batch_number.0 as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| H256::from_slice(&row.hash))
+            .collect())
+    }
+
+    /// Reads rows of the `hashes` transactions, together with their events and call traces.
+    pub async fn get_rows(&mut self, hashes: &[H256]) -> DalResult<ArchivedRows> {
+        let hashes: Vec<_> = hashes.iter().map(|hash| hash.0.to_vec()).collect();
+        let transactions = sqlx::query!(
+            r#"
+            SELECT row_to_json(transactions) AS "row!"
+            FROM transactions
+            WHERE hash = ANY($1)
+            "#,
+            &hashes
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+        let events = sqlx::query!(
+            r#"
+            SELECT row_to_json(events) AS "row!"
+            FROM events
+            WHERE tx_hash = ANY($1)
+            "#,
+            &hashes
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+        let call_traces = sqlx::query!(
+            r#"
+            SELECT row_to_json(call_traces) AS "row!"
+            FROM call_traces
+            WHERE tx_hash = ANY($1)
+            "#,
+            &hashes
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(ArchivedRows {
+            transactions: transactions.into_iter().map(|row| row.row).collect(),
+            events: events.into_iter().map(|row| row.row).collect(),
+            call_traces: call_traces.into_iter().map(|row| row.row).collect(),
+        })
+    }
+
+    /// Records that the `hashes` transactions of the L1 batch are archived at `object_key`,
+    /// and removes them together with their events and call traces.
+    pub async fn mark_l1_batch_archived(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        object_key: &str,
+        hashes: &[H256],
+        event_count: usize,
+    ) -> DalResult<()> {
+        let hashes: Vec<_> = hashes.iter().map(|hash| hash.0.to_vec()).collect();
+        let mut transaction = self.storage.start_transaction().await;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO transactions_archive_segments
+                (l1_batch_number, object_key, tx_count, event_count, archived_at)
+            VALUES ($1, $2, $3, $4, NOW())
+            "#,
+            l1_batch_number.0 as i64,
+            object_key,
+            hashes.len() as i32,
+            event_count as i32
+        )
+        .execute(transaction.conn())
+        .await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO transactions_archive_index (hash, l1_batch_number)
+            SELECT u.hash, $2 FROM UNNEST($1::bytea[]) AS u(hash)
+            "#,
+            &hashes,
+            l1_batch_number.0 as i64
+        )
+        .execute(transaction.conn())
+        .await?;
+        sqlx::query!("DELETE FROM events WHERE tx_hash = ANY($1)", &hashes)
+            .execute(transaction.conn())
+            .await?;
+        sqlx::query!("DELETE FROM call_traces WHERE tx_hash = ANY($1)", &hashes)
+            .execute(transaction.conn())
+            .await?;
+        sqlx::query!("DELETE FROM transactions WHERE hash = ANY($1)", &hashes)
+            .execute(transaction.conn())
+            .await?;
+
+        transaction.commit().await;
+        Ok(())
+    }
+
+    /// Returns the L1 batch whose archive segment contains the transaction, if it's archived.
+    pub async fn get_archived_l1_batch(&mut self, hash: H256) -> DalResult<Option<L1BatchNumber>> {
+        let row = sqlx::query!(
+            "SELECT l1_batch_number FROM transactions_archive_index WHERE hash = $1",
+            hash.as_bytes()
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+        Ok(row.map(|row| L1BatchNumber(row.l1_batch_number as u32)))
+    }
+
+    pub async fn get_archived_l1_batches(
+        &mut self,
+        range: ops::RangeInclusive<L1BatchNumber>,
+    ) -> DalResult<Vec<L1BatchNumber>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT l1_batch_number
+            FROM transactions_archive_segments
+            WHERE l1_batch_number BETWEEN $1 AND $2
+            ORDER BY l1_batch_number
+            "#,
+            range.start().0 as i64,
+            range.end().0 as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| L1BatchNumber(row.l1_batch_number as u32))
+            .collect())
+    }
+
+    /// Inserts archived `rows` of the L1 batch back and removes the batch from the archive index.
+    pub async fn restore_l1_batch(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        rows: ArchivedRows,
+    ) -> DalResult<()> {
+        let mut transaction = self.storage.start_transaction().await;
+
+        // Transactions go first, since call traces reference them.
+        sqlx::query!(
+            r#"
+            INSERT INTO transactions
+            SELECT * FROM json_populate_recordset(NULL::transactions, $1)
+            ON CONFLICT (hash) DO NOTHING
+            "#,
+            serde_json::Value::Array(rows.transactions)
+        )
+        .execute(transaction.conn())
+        .await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO events
+            SELECT * FROM json_populate_recordset(NULL::events, $1)
+            ON CONFLICT (miniblock_number, event_index_in_block) DO NOTHING
+            "#,
+            serde_json::Value::Array(rows.events)
+        )
+        .execute(transaction.conn())
+        .await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO call_traces (tx_hash, call_trace, format_version)
+            -- Segments archived before call traces were versioned have no `format_version`.
+            SELECT tx_hash, call_trace, COALESCE(format_version, 0)
+            FROM json_populate_recordset(NULL::call_traces, $1)
+            ON CONFLICT (tx_hash) DO NOTHING
+            "#,
+            serde_json::Value::Array(rows.call_traces)
+        )
+        .execute(transaction.conn())
+        .await?;
+        sqlx::query!(
+            "DELETE FROM transactions_archive_segments WHERE l1_batch_number = $1",
+            l1_batch_number.0 as i64
+        )
+        .execute(transaction.conn())
+        .await?;
+
+        transaction.commit().await;
+        Ok(())
+    }
+}
@@ -1,26 +1,27 @@
-use std::{
-    collections::HashMap,
-    fmt,
-    time::{Duration, Instant},
-};
+use std::{collections::HashMap, fmt, time::Duration};
 
 use itertools::Itertools;
 
 use ola_types::{
+    api::DropReason,
     block::MiniblockReexecuteData,
     fee::TransactionExecutionMetrics,
     get_nonce_key,
     l2::L2Tx,
     protocol_version::ProtocolUpgradeTx,
-    tx::{tx_execution_info::TxExecutionStatus, TransactionExecutionResult},
+    tx::{
+        execution_digest::ExecutionDigest, tx_execution_info::TxExecutionStatus,
+        TransactionExecutionResult,
+    },
+    vm_trace::CALL_TRACE_FORMAT_VERSION,
     Address, ExecuteTransactionCommon, L1BatchNumber, MiniblockNumber, Nonce, PriorityOpId,
     Transaction, H256, PROTOCOL_UPGRADE_TX_TYPE,
 };
 use ola_utils::h256_to_u32;
 
 use crate::{
-    models::storage_transaction::StorageTransaction, time_utils::pg_interval_from_duration,
-    StorageProcessor,
+    macro_utils::COPY_FLUSH_THRESHOLD_BYTES, models::storage_transaction::StorageTransaction,
+    time_utils::pg_interval_from_duration, DalError, DalResult, StorageProcessor,
 };
 use sqlx::{error, types::chrono::NaiveDateTime};
 
@@ -45,19 +46,16 @@ pub struct TransactionsDal<'c, 'a> {
 }
 
 impl TransactionsDal<'_, '_> {
-    pub async fn insert_system_transaction(&mut self, tx: ProtocolUpgradeTx) {
+    pub async fn insert_system_transaction(&mut self, tx: ProtocolUpgradeTx) -> DalResult<()> {
         let contract_address = tx.execute.contract_address.as_bytes().to_vec();
         let tx_hash = tx.common_data.hash().0.to_vec();
-        let json_data = serde_json::to_value(&tx.execute)
-            .unwrap_or_else(|_| panic!("cannot serialize tx {:?} to json", tx.common_data.hash()));
+        let json_data = serde_json::to_value(&tx.execute).map_err(DalError::encode)?;
         let upgrade_id = tx.common_data.upgrade_id as i32;
         let sender = tx.common_data.sender.0.to_vec();
         let tx_format = tx.common_data.tx_format() as i32;
         let l1_block_number = tx.common_data.eth_block as i32;
 
-        let secs = (tx.received_timestamp_ms / 1000) as i64;
-        let nanosecs = ((tx.received_timestamp_ms % 1000) * 1_000_000) as u32;
-        let received_at = NaiveDateTime::from_timestamp_opt(secs, nanosecs).unwrap();
+        let received_at = received_at(tx.received_timestamp_ms)?;
 
         sqlx::query!(
             "
@@ -93,28 +91,38 @@ impl TransactionsDal<'_, '_> {
             received_at,
         )
         .fetch_optional(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
+        Ok(())
     }
 
     pub async fn insert_transaction_l2(
         &mut self,
         tx: L2Tx,
         exec_info: TransactionExecutionMetrics,
-    ) -> L2TxSubmissionResult {
+    ) -> DalResult<L2TxSubmissionResult> {
         {
             let tx_hash = tx.hash();
             let initiator_address = tx.initiator_account();
             let contract_address = tx.execute.contract_address.as_bytes();
-            let json_data = serde_json::to_value(&tx.execute)
-                .unwrap_or_else(|_| panic!("cannot serialize tx {:?} to json", tx.hash()));
+            let json_data = serde_json::to_value(&tx.execute).map_err(DalError::encode)?;
             let tx_format = tx.common_data.transaction_type as i32;
             let signature = tx.common_data.signature;
             let nonce = tx.common_data.nonce.0 as i64;
-            let input_data = tx.common_data.input.expect("Data is mandatory").data;
-            let secs = (tx.received_timestamp_ms / 1000) as i64;
-            let nanosecs = ((tx.received_timestamp_ms % 1000) * 1_000_000) as u32;
-            let received_at = NaiveDateTime::from_timestamp_opt(secs, nanosecs).unwrap();
+            let input_data = tx
+                .common_data
+                .input
+                .ok_or_else(|| DalError::encode("L2 transaction has no input data"))?
+                .data;
+            let received_at = received_at(tx.received_timestamp_ms)?;
+            let execute_after = tx
+                .common_data
+                .execute_after
+                .map(|execute_after| {
+                    NaiveDateTime::from_timestamp_opt(execute_after as i64, 0).ok_or_else(|| {
+                        DalError::encode(format!("invalid execute_after timestamp {execute_after}"))
+                    })
+                })
+                .transpose()?;
             // Besides just adding or updating(on conflict) the record, we want to extract some info
             // from the query below, to indicate what actually happened:
             // 1) transaction is added
@@ -125,6 +133,9 @@ impl TransactionsDal<'_, '_> {
             // Otherwise, if the subquery won't return NULL it means that there is already tx with such nonce and initiator_address in DB
             // and we can replace it WHERE clause conditions are met.
             // It is worth mentioning that if WHERE clause conditions are not met, None will be returned.
+            // `execute_after` isn't covered by the transaction signature, so resubmitting the same
+            // transaction (i.e., with the same hash) must not change it; only a replacement signed by
+            // the initiator can.
             let query_result = sqlx::query!(
                 r#"
                 INSERT INTO transactions
@@ -140,6 +151,7 @@ impl TransactionsDal<'_, '_> {
                     contract_address,
                     execution_info,
                     received_at,
+                    execute_after,
                     created_at,
                     updated_at
                 )
@@ -147,7 +159,7 @@ impl TransactionsDal<'_, '_> {
                     (
                         $1, FALSE, $2, $3, $4, $5, $6, $7, $8,
                         jsonb_build_object('storage_writes', $9::int, 'contracts_used', $10::int),
-                        $11, now(), now()
+                        $11, $12, now(), now()
                     )
                 ON CONFLICT
                     (initiator_address, nonce)
@@ -161,11 +173,13 @@ impl TransactionsDal<'_, '_> {
                         execution_info=jsonb_build_object('storage_writes', $9::int, 'contracts_used', $10::int),
                         in_mempool=FALSE,
                         received_at=$11,
+                        execute_after=$12,
                         created_at=now(),
                         updated_at=now(),
                         error = NULL
                     WHERE transactions.is_priority = FALSE AND transactions.miniblock_number IS NULL
-                    RETURNING (SELECT hash FROM transactions WHERE transactions.initiator_address = $2 AND transactions.nonce = $3) IS NOT NULL as "is_replaced!"
+                        AND (transactions.hash != $1 OR transactions.execute_after IS NOT DISTINCT FROM $12)
+                    RETURNING (SELECT hash FROM transactions WHERE transactions.initiator_address = $2 AND transactions.nonce = $3) AS "replaced_hash?"
                 "#,
                 tx_hash.as_bytes(),
                 initiator_address.as_bytes(),
@@ -177,17 +191,46 @@ impl TransactionsDal<'_, '_> {
                 contract_address,
                 (exec_info.initial_storage_writes + exec_info.repeated_storage_writes) as i32,
                 exec_info.contracts_used as i32,
-                received_at
+                received_at,
+                execute_after
             )
                 .fetch_optional(self.storage.conn())
                 .await
-                .map(|option_record| option_record.map(|record| record.is_replaced));
+                .map(|option_record| option_record.map(|record| record.replaced_hash));
 
             let l2_tx_insertion_result = match query_result {
                 Ok(option_query_result) => match option_query_result {
-                    Some(true) => L2TxSubmissionResult::Replaced,
-                    Some(false) => L2TxSubmissionResult::Added,
-                    None => L2TxSubmissionResult::AlreadyExecuted,
+                    Some(Some(replaced_hash)) => {
+                        self.record_replaced_tx(&replaced_hash, tx_hash, initiator_address, nonce)
+                            .await?;
+                        L2TxSubmissionResult::Replaced
+                    }
+                    Some(None) => {
+                        // The transaction may be resubmitted after being dropped.
+                        sqlx::query!(
+                            "DELETE FROM dropped_transactions WHERE hash = $1",
+                            tx_hash.as_bytes()
+                        )
+                        .execute(self.storage.conn())
+                        .await?;
+                        L2TxSubmissionResult::Added
+                    }
+                    None => {
+                        // A pending transaction with the same hash is only kept if the resubmission
+                        // changes its schedule.
+                        let is_pending = sqlx::query!(
+                            r#"SELECT 1 AS "exists!" FROM transactions WHERE hash = $1 AND miniblock_number IS NULL"#,
+                            tx_hash.as_bytes()
+                        )
+                        .fetch_optional(self.storage.conn())
+                        .await?
+                        .is_some();
+                        if is_pending {
+                            L2TxSubmissionResult::Duplicate
+                        } else {
+                            L2TxSubmissionResult::AlreadyExecuted
+                        }
+                    }
                 },
                 Err(err) => {
                     // So, we consider a tx hash to be a primary key of the transaction
@@ -200,11 +243,11 @@ impl TransactionsDal<'_, '_> {
                     if let error::Error::Database(ref error) = err {
                         if let Some(constraint) = error.constraint() {
                             if constraint == "transactions_pkey" {
-                                return L2TxSubmissionResult::Duplicate;
+                                return Ok(L2TxSubmissionResult::Duplicate);
                             }
                         }
                     }
-                    panic!("{}", err);
+                    return Err(err.into());
                 }
             };
             olaos_logs::info!(
@@ -216,29 +259,63 @@ impl TransactionsDal<'_, '_> {
                 l2_tx_insertion_result
             );
 
-            l2_tx_insertion_result
+            Ok(l2_tx_insertion_result)
         }
     }
 
-    pub async fn next_priority_id(&mut self) -> PriorityOpId {
-        {
-            sqlx::query!(
-                r#"SELECT MAX(priority_op_id) as "op_id" from transactions where is_priority = true AND miniblock_number IS NOT NULL"#
-            )
-                .fetch_optional(self.storage.conn())
-                .await
-                .unwrap()
-                .and_then(|row| row.op_id)
-                .map(|value| PriorityOpId((value + 1) as u64))
-                .unwrap_or_default()
-        }
+    /// Records that the transaction with `replaced_hash` was replaced by `tx_hash`.
+    async fn record_replaced_tx(
+        &mut self,
+        replaced_hash: &[u8],
+        tx_hash: H256,
+        initiator_address: Address,
+        nonce: i64,
+    ) -> DalResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO dropped_transactions
+                (hash, initiator_address, nonce, reason, replaced_by, dropped_at)
+            VALUES ($1, $2, $3, $4, $5, now())
+            ON CONFLICT (hash) DO UPDATE
+                SET reason = $4, replaced_by = $5, dropped_at = now()
+            "#,
+            replaced_hash,
+            initiator_address.as_bytes(),
+            nonce,
+            DropReason::Replaced.as_str(),
+            tx_hash.as_bytes()
+        )
+        .execute(self.storage.conn())
+        .await?;
+        sqlx::query!(
+            "DELETE FROM dropped_transactions WHERE hash = $1",
+            tx_hash.as_bytes()
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    pub async fn next_priority_id(&mut self) -> DalResult<PriorityOpId> {
+        let row = sqlx::query!(
+            r#"SELECT MAX(priority_op_id) as "op_id" from transactions where is_priority = true AND miniblock_number IS NOT NULL"#
+        )
+        .fetch_optional(self.storage.conn())
+        .await?;
+        Ok(row
+            .and_then(|row| row.op_id)
+            .map(|value| PriorityOpId((value + 1) as u64))
+            .unwrap_or_default())
     }
 
+    /// Marks `transactions` as executed in the miniblock. Outputs in their call traces are
+    /// truncated to `max_call_output_size` bytes.
     pub async fn mark_txs_as_executed_in_miniblock(
         &mut self,
         miniblock_number: MiniblockNumber,
         transactions: &[TransactionExecutionResult],
-    ) {
+        max_call_output_size: usize,
+    ) -> DalResult<()> {
         {
             let mut transaction = self.storage.start_transaction().await;
 
@@ -267,60 +344,59 @@ impl TransactionsDal<'_, '_> {
             let mut l2_inputs = Vec::with_capacity(transactions.len());
             let mut l2_datas = Vec::with_capacity(transactions.len());
 
-            let mut call_traces_tx_hashes = Vec::with_capacity(transactions.len());
-            let mut bytea_call_traces = Vec::with_capacity(transactions.len());
-            transactions
-                .iter()
-                .enumerate()
-                .for_each(|(index_in_block, tx_res)| {
-                    let TransactionExecutionResult {
-                        hash,
-                        execution_info,
-                        transaction,
-                        execution_status,
-                        ..
-                    } = tx_res;
-
-                    // Entrypoint currently doesn't return detailed errors.
-                    let error = match execution_status {
-                        TxExecutionStatus::Success => None,
-                        // The string error used here is copied from the previous version.
-                        // It is applied to every failed transaction -
-                        // currently detailed errors are not supported.
-                        TxExecutionStatus::Failure => Some("Entrypoint-based tx failed".to_owned()),
-                    };
-
-                    if let Some(call_trace) = tx_res.call_trace() {
-                        bytea_call_traces.push(bincode::serialize(&call_trace).unwrap());
-                        call_traces_tx_hashes.push(hash.0.to_vec());
-                    }
+            let mut call_traces = Vec::with_capacity(transactions.len());
+            for (index_in_block, tx_res) in transactions.iter().enumerate() {
+                let TransactionExecutionResult {
+                    hash,
+                    execution_info,
+                    transaction,
+                    execution_status,
+                    ..
+                } = tx_res;
 
-                    match &transaction.common_data {
-                        ExecuteTransactionCommon::L2(common_data) => {
-                            let data = serde_json::to_value(&transaction.execute).unwrap();
-                            l2_contract_addresses
-                                .push(transaction.execute.contract_address.as_bytes().to_vec());
-                            l2_hashes.push(hash.0.to_vec());
-                            l2_indices_in_block.push(index_in_block as i32);
-                            l2_initiators.push(transaction.initiator_account().0.to_vec());
-                            l2_nonces.push(common_data.nonce.0 as i32);
-                            l2_signatures.push(common_data.signature.clone());
-                            l2_tx_formats.push(common_data.transaction_type as i32);
-                            l2_errors.push(error.unwrap_or_default());
-                            l2_execution_infos.push(serde_json::to_value(execution_info).unwrap());
-                            // Normally input data is mandatory
-                            l2_inputs.push(common_data.input_data().unwrap_or_default());
-                            l2_datas.push(data);
-                        }
-                        ExecuteTransactionCommon::ProtocolUpgrade(_common_data) => {
-                            upgrade_hashes.push(hash.0.to_vec());
-                            upgrade_indices_in_block.push(index_in_block as i32);
-                            upgrade_errors.push(error.unwrap_or_default());
-                            upgrade_execution_infos
-                                .push(serde_json::to_value(execution_info).unwrap());
-                        }
+                // Entrypoint currently doesn't return detailed errors.
+                let error = match execution_status {
+                    TxExecutionStatus::Success => None,
+                    // The string error used here is copied from the previous version.
+                    // It is applied to every failed transaction -
+                    // currently detailed errors are not supported.
+                    TxExecutionStatus::Failure => Some("Entrypoint-based tx failed".to_owned()),
+                };
+
+                if let Some(mut call_trace) = tx_res.call_trace() {
+                    call_trace.truncate_outputs(max_call_output_size);
+                    let call_trace = call_trace.encode_stored().map_err(DalError::encode)?;
+                    call_traces.push((*hash, call_trace));
+                }
+
+                match &transaction.common_data {
+                    ExecuteTransactionCommon::L2(common_data) => {
+                        let data =
+                            serde_json::to_value(&transaction.execute).map_err(DalError::encode)?;
+                        l2_contract_addresses
+                            .push(transaction.execute.contract_address.as_bytes().to_vec());
+                        l2_hashes.push(hash.0.to_vec());
+                        l2_indices_in_block.push(index_in_block as i32);
+                        l2_initiators.push(transaction.initiator_account().0.to_vec());
+                        l2_nonces.push(common_data.nonce.0 as i32);
+                        l2_signatures.push(common_data.signature.clone());
+                        l2_tx_formats.push(common_data.transaction_type as i32);
+                        l2_errors.push(error.unwrap_or_default());
+                        l2_execution_infos
+                            .push(serde_json::to_value(execution_info).map_err(DalError::encode)?);
+                        // Normally input data is mandatory
+                        l2_inputs.push(common_data.input_data().unwrap_or_default());
+                        l2_datas.push(data);
+                    }
+                    ExecuteTransactionCommon::ProtocolUpgrade(_common_data) => {
+                        upgrade_hashes.push(hash.0.to_vec());
+                        upgrade_indices_in_block.push(index_in_block as i32);
+                        upgrade_errors.push(error.unwrap_or_default());
+                        upgrade_execution_infos
+                            .push(serde_json::to_value(execution_info).map_err(DalError::encode)?);
                     }
-                });
+                }
+            }
 
             if !l2_hashes.is_empty() {
                 // Update l2 txs
@@ -382,8 +458,7 @@ impl TransactionsDal<'_, '_> {
                     miniblock_number.0 as i32
                 )
                 .execute(transaction.conn())
-                .await
-                .unwrap();
+                .await?;
             }
 
             // TODO: l1 transaction is not supported yet. 23/10/23
@@ -454,51 +529,81 @@ impl TransactionsDal<'_, '_> {
                     &upgrade_execution_infos,
                 )
                 .execute(transaction.conn())
-                .await
-                .unwrap();
+                .await?;
             }
 
-            if !bytea_call_traces.is_empty() {
-                let _started_at = Instant::now();
-                sqlx::query!(
-                    r#"
-                        INSERT INTO call_traces (tx_hash, call_trace)
-                        SELECT u.tx_hash, u.call_trace
-                        FROM UNNEST($1::bytea[], $2::bytea[])
-                        AS u(tx_hash, call_trace)
-                        "#,
-                    &call_traces_tx_hashes,
-                    &bytea_call_traces
-                )
-                .execute(transaction.conn())
-                .await
-                .unwrap();
+            if !call_traces.is_empty() {
+                // Call traces make up most of the persisted tx results, so they are streamed
+                // with `COPY` instead of being bound as a single huge array parameter.
+                let mut copy = transaction
+                    .conn()
+                    .copy_in_raw(
+                        "COPY call_traces(tx_hash, call_trace, format_version) \
+                        FROM STDIN WITH (DELIMITER '|')",
+                    )
+                    .await?;
+                let mut buffer = String::new();
+                for (tx_hash, call_trace) in &call_traces {
+                    writeln_str!(
+                        &mut buffer,
+                        r"\\x{tx_hash:x}|\\x{call_trace}|{CALL_TRACE_FORMAT_VERSION}",
+                        call_trace = hex::encode(call_trace)
+                    );
+                    if buffer.len() >= COPY_FLUSH_THRESHOLD_BYTES {
+                        copy.send(buffer.as_bytes()).await?;
+                        buffer.clear();
+                    }
+                }
+                if !buffer.is_empty() {
+                    copy.send(buffer.as_bytes()).await?;
+                }
+                copy.finish().await?;
             }
             transaction.commit().await;
+            Ok(())
         }
     }
 
-    pub async fn mark_tx_as_rejected(&mut self, transaction_hash: H256, error: &str) {
-        // If the rejected tx has been replaced, it means that this tx hash does not exist in the database
-        // and we will update nothing.
+    /// Records errors of transactions rejected by the sequencer in a single query.
+    pub async fn mark_txs_as_rejected(&mut self, rejected: &[(H256, String)]) -> DalResult<()> {
+        if rejected.is_empty() {
+            return Ok(());
+        }
+        // Transactions are updated in order of their hashes to avoid deadlocks with other UPDATE queries.
+        let mut rejected: Vec<_> = rejected.iter().collect();
+        rejected.sort_unstable_by_key(|(hash, _)| *hash);
+        let (hashes, errors): (Vec<_>, Vec<_>) = rejected
+            .into_iter()
+            .map(|(hash, error)| (hash.as_bytes().to_vec(), error.clone()))
+            .unzip();
+
+        // If a rejected tx has been replaced, it means that its hash does not exist in the database
+        // and we will update nothing for it.
         // These txs don't affect the state, so we can just easily skip this update.
         sqlx::query!(
-            "UPDATE transactions
-                    SET error = $1, updated_at = now()
-                    WHERE hash = $2",
-            error,
-            transaction_hash.0.to_vec()
+            "
+                UPDATE transactions
+                SET error = data_table.error, updated_at = now()
+                FROM
+                    (SELECT
+                            UNNEST($1::bytea[]) AS hash,
+                            UNNEST($2::varchar[]) AS error
+                    ) AS data_table
+                WHERE transactions.hash = data_table.hash
+            ",
+            &hashes,
+            &errors
         )
         .execute(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
+        Ok(())
     }
 
     pub async fn mark_txs_as_executed_in_l1_batch(
         &mut self,
         block_number: L1BatchNumber,
         transactions: &[TransactionExecutionResult],
-    ) {
+    ) -> DalResult<()> {
         let hashes: Vec<Vec<u8>> = transactions
             .iter()
             .map(|tx| tx.hash.as_bytes().to_vec())
@@ -523,43 +628,66 @@ impl TransactionsDal<'_, '_> {
             block_number.0 as i64
         )
         .execute(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
+        Ok(())
     }
 
-    pub async fn remove_stuck_txs(&mut self, stuck_tx_timeout: Duration) -> usize {
+    /// Removes stuck transactions, recording them as dropped. Scheduled transactions are only
+    /// considered stuck once the timeout passes after their `execute_after` timestamp.
+    pub async fn remove_stuck_txs(&mut self, stuck_tx_timeout: Duration) -> DalResult<usize> {
         {
             let stuck_tx_timeout = pg_interval_from_duration(stuck_tx_timeout);
-            sqlx::query!(
-                "DELETE FROM transactions \
-                 WHERE miniblock_number IS NULL AND received_at < now() - $1::interval \
-                 AND is_priority=false AND error IS NULL \
-                 RETURNING hash",
-                stuck_tx_timeout
+            let row = sqlx::query!(
+                r#"
+                WITH removed AS (
+                    DELETE FROM transactions
+                    WHERE miniblock_number IS NULL
+                        AND GREATEST(received_at, execute_after) < now() - $1::interval
+                        AND is_priority = FALSE AND error IS NULL
+                    RETURNING hash, initiator_address, nonce
+                ),
+                recorded AS (
+                    INSERT INTO dropped_transactions
+                        (hash, initiator_address, nonce, reason, dropped_at)
+                    SELECT hash, initiator_address, nonce, $2, now() FROM removed
+                    ON CONFLICT (hash) DO UPDATE
+                        SET reason = $2, replaced_by = NULL, dropped_at = now()
+                )
+                SELECT COUNT(*) AS "count!" FROM removed
+                "#,
+                stuck_tx_timeout,
+                DropReason::Stuck.as_str()
             )
-            .fetch_all(self.storage.conn())
-            .await
-            .unwrap()
-            .len()
+            .fetch_one(self.storage.conn())
+            .await?;
+            Ok(row.count as usize)
         }
     }
 
     #[olaos_logs::instrument(skip(self))]
-    pub async fn reset_mempool(&mut self) {
-        {
-            sqlx::query!("UPDATE transactions SET in_mempool = FALSE WHERE in_mempool = TRUE")
-                .execute(self.storage.conn())
-                .await
-                .unwrap();
-        }
+    pub async fn reset_mempool(&mut self) -> DalResult<()> {
+        sqlx::query!("UPDATE transactions SET in_mempool = FALSE WHERE in_mempool = TRUE")
+            .execute(self.storage.conn())
+            .await?;
+        Ok(())
     }
 
+    /// Loads up to `limit` pending transactions into the mempool. L2 transactions received before
+    /// `received_after` are skipped, so that only the index entries of new transactions are scanned.
+    /// Returns the loaded transactions, nonces of their initiators and the latest receipt time
+    /// of the loaded L2 transactions.
     pub async fn sync_mempool(
         &mut self,
         stashed_accounts: Vec<Address>,
         purged_accounts: Vec<Address>,
         limit: usize,
-    ) -> (Vec<Transaction>, HashMap<Address, Nonce>) {
+        scheduled_limit: usize,
+        received_after: Option<NaiveDateTime>,
+    ) -> DalResult<(
+        Vec<Transaction>,
+        HashMap<Address, Nonce>,
+        Option<NaiveDateTime>,
+    )> {
         let stashed_addresses: Vec<_> =
             stashed_accounts.into_iter().map(|a| a.0.to_vec()).collect();
         sqlx::query!(
@@ -569,8 +697,7 @@ impl TransactionsDal<'_, '_> {
                 &stashed_addresses,
             )
             .execute(self.storage.conn())
-            .await
-            .unwrap();
+            .await?;
 
         let purged_addresses: Vec<_> = purged_accounts.into_iter().map(|a| a.0.to_vec()).collect();
         sqlx::query!(
@@ -579,10 +706,11 @@ impl TransactionsDal<'_, '_> {
             &purged_addresses[..]
         )
         .execute(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
 
         // Note, that transactions are updated in order of their hashes to avoid deadlocks with other UPDATE queries.
+        // At most `scheduled_limit` transactions which are not due yet are loaded; the ones skipped
+        // because of the limit are picked up by later syncs ignoring `received_after`.
         // TODO: if gas is added, update where condition to filter valid txs
         let transactions = sqlx::query_as!(
             StorageTransaction,
@@ -594,6 +722,18 @@ impl TransactionsDal<'_, '_> {
                         FROM transactions
                         WHERE miniblock_number IS NULL AND in_mempool = FALSE AND error IS NULL
                         AND tx_format != $2
+                        AND (is_priority OR $3::timestamp IS NULL OR received_at >= $3)
+                        AND (
+                            execute_after IS NULL OR execute_after <= now()
+                            OR hash IN (
+                                SELECT hash
+                                FROM transactions
+                                WHERE miniblock_number IS NULL AND in_mempool = FALSE AND error IS NULL
+                                AND execute_after > now()
+                                ORDER BY received_at
+                                LIMIT $4
+                            )
+                        )
                         ORDER BY is_priority DESC, priority_op_id, received_at
                         LIMIT $1
                     ) as subquery1
@@ -603,10 +743,16 @@ impl TransactionsDal<'_, '_> {
                 RETURNING transactions.*",
             limit as i32,
             PROTOCOL_UPGRADE_TX_TYPE as i32,
+            received_after,
+            scheduled_limit as i64,
         )
         .fetch_all(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
+        let last_received_at = transactions
+            .iter()
+            .filter(|tx| !tx.is_priority)
+            .map(|tx| tx.received_at)
+            .max();
 
         let nonce_keys: HashMap<_, _> = transactions
             .iter()
@@ -623,59 +769,84 @@ impl TransactionsDal<'_, '_> {
             &storage_keys,
         )
         .fetch_all(self.storage.conn())
-        .await
-        .unwrap()
+        .await?
         .into_iter()
-        .map(|row| {
+        .filter_map(|row| {
             let nonce_key = H256::from_slice(&row.hashed_key);
             let nonce = Nonce(h256_to_u32(H256::from_slice(&row.value)));
-
-            (*nonce_keys.get(&nonce_key).unwrap(), nonce)
+            // Only keys from `nonce_keys` are queried.
+            Some((*nonce_keys.get(&nonce_key)?, nonce))
         })
         .collect();
 
-        (
+        Ok((
             transactions.into_iter().map(|tx| tx.into()).collect(),
             nonces,
-        )
+            last_received_at,
+        ))
     }
 
-    pub async fn get_miniblocks_to_reexecute(&mut self) -> Vec<MiniblockReexecuteData> {
-        let transactions_by_miniblock: Vec<(MiniblockNumber, Vec<Transaction>)> = sqlx::query_as!(
+    pub async fn get_miniblocks_to_reexecute(&mut self) -> DalResult<Vec<MiniblockReexecuteData>> {
+        let transactions = sqlx::query_as!(
             StorageTransaction,
             "SELECT * FROM transactions \
             WHERE miniblock_number IS NOT NULL AND l1_batch_number IS NULL \
             ORDER BY miniblock_number, index_in_block",
         )
         .fetch_all(self.storage.conn())
-        .await
-        .unwrap()
-        .into_iter()
-        .group_by(|tx| tx.miniblock_number.unwrap())
-        .into_iter()
-        .map(|(miniblock_number, txs)| {
-            (
-                MiniblockNumber(miniblock_number as u32),
-                txs.map(Transaction::from).collect::<Vec<_>>(),
-            )
-        })
-        .collect();
-        if transactions_by_miniblock.is_empty() {
-            return Vec::new();
-        }
+        .await?;
+        self.group_by_miniblocks(transactions).await
+    }
 
-        let from_miniblock = transactions_by_miniblock.first().unwrap().0;
-        let to_miniblock = transactions_by_miniblock.last().unwrap().0;
+    /// Returns transactions of a sealed L1 batch grouped by miniblock, in the execution order.
+    pub async fn get_l1_batch_miniblocks_to_reexecute(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> DalResult<Vec<MiniblockReexecuteData>> {
+        let transactions = sqlx::query_as!(
+            StorageTransaction,
+            "SELECT * FROM transactions \
+            WHERE l1_batch_number = $1 \
+            ORDER BY miniblock_number, index_in_block",
+            l1_batch_number.0 as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+        self.group_by_miniblocks(transactions).await
+    }
+
+    /// Groups transactions ordered by miniblock and fetches the miniblock timestamps.
+    async fn group_by_miniblocks(
+        &mut self,
+        transactions: Vec<StorageTransaction>,
+    ) -> DalResult<Vec<MiniblockReexecuteData>> {
+        // Transactions are queried by their miniblock or L1 batch, so they always have a miniblock.
+        let transactions_by_miniblock: Vec<(MiniblockNumber, Vec<Transaction>)> = transactions
+            .into_iter()
+            .group_by(|tx| tx.miniblock_number.unwrap_or_default())
+            .into_iter()
+            .map(|(miniblock_number, txs)| {
+                (
+                    MiniblockNumber(miniblock_number as u32),
+                    txs.map(Transaction::from).collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        let (Some((from_miniblock, _)), Some((to_miniblock, _))) = (
+            transactions_by_miniblock.first(),
+            transactions_by_miniblock.last(),
+        ) else {
+            return Ok(Vec::new());
+        };
         let timestamps = sqlx::query!(
             "SELECT timestamp FROM miniblocks WHERE number BETWEEN $1 AND $2 ORDER BY number",
             from_miniblock.0 as i64,
             to_miniblock.0 as i64,
         )
         .fetch_all(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
 
-        transactions_by_miniblock
+        Ok(transactions_by_miniblock
             .into_iter()
             .zip(timestamps)
             .map(|((number, txs), row)| MiniblockReexecuteData {
@@ -683,11 +854,11 @@ impl TransactionsDal<'_, '_> {
                 timestamp: row.timestamp as u64,
                 txs,
             })
-            .collect()
+            .collect())
     }
 
-    pub(crate) async fn get_tx_by_hash(&mut self, hash: H256) -> Option<Transaction> {
-        sqlx::query_as!(
+    pub(crate) async fn get_tx_by_hash(&mut self, hash: H256) -> DalResult<Option<Transaction>> {
+        let tx = sqlx::query_as!(
             StorageTransaction,
             r#"
                 SELECT * FROM transactions
@@ -696,8 +867,312 @@ impl TransactionsDal<'_, '_> {
             hash.as_bytes()
         )
         .fetch_optional(self.storage.conn())
+        .await?;
+        Ok(tx.map(|tx| tx.into()))
+    }
+
+    /// Returns initiators of all transactions included up to and including the specified
+    /// miniblock, sorted by address.
+    pub async fn get_initiator_addresses(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+    ) -> DalResult<Vec<Address>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT DISTINCT
+                initiator_address
+            FROM
+                transactions
+            WHERE
+                miniblock_number <= $1
+            ORDER BY
+                initiator_address
+            "#,
+            miniblock_number.0 as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Address::from_slice(&row.initiator_address))
+            .collect())
+    }
+
+    /// Records the digest of the transaction simulated at submission, so that it can be
+    /// compared with the actual execution once the transaction is sealed.
+    pub async fn insert_transaction_simulation(
+        &mut self,
+        tx_hash: H256,
+        simulated: &ExecutionDigest,
+    ) -> DalResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO
+                transaction_simulations (
+                    tx_hash,
+                    simulated_write_set_hash,
+                    simulated_events_hash,
+                    created_at,
+                    updated_at
+                )
+            VALUES
+                ($1, $2, $3, NOW(), NOW())
+            ON CONFLICT (tx_hash) DO
+            UPDATE
+            SET
+                simulated_write_set_hash = excluded.simulated_write_set_hash,
+                simulated_events_hash = excluded.simulated_events_hash,
+                actual_write_set_hash = NULL,
+                actual_events_hash = NULL,
+                updated_at = NOW()
+            "#,
+            tx_hash.as_bytes(),
+            simulated.write_set_hash.as_bytes(),
+            simulated.events_hash.as_bytes()
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Returns hashes of the specified transactions that have a recorded simulation.
+    pub async fn get_simulated_tx_hashes(&mut self, tx_hashes: &[H256]) -> DalResult<Vec<H256>> {
+        let hashes: Vec<_> = tx_hashes.iter().map(H256::as_bytes).collect();
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                tx_hash
+            FROM
+                transaction_simulations
+            WHERE
+                tx_hash = ANY ($1)
+            "#,
+            &hashes as &[&[u8]]
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| H256::from_slice(&row.tx_hash))
+            .collect())
+    }
+
+    pub async fn set_actual_execution_digests(
+        &mut self,
+        digests: &[(H256, ExecutionDigest)],
+    ) -> DalResult<()> {
+        let mut tx_hashes = Vec::with_capacity(digests.len());
+        let mut write_set_hashes = Vec::with_capacity(digests.len());
+        let mut events_hashes = Vec::with_capacity(digests.len());
+        for (tx_hash, digest) in digests {
+            tx_hashes.push(tx_hash.as_bytes());
+            write_set_hashes.push(digest.write_set_hash.as_bytes());
+            events_hashes.push(digest.events_hash.as_bytes());
+        }
+        sqlx::query!(
+            r#"
+            UPDATE transaction_simulations
+            SET
+                actual_write_set_hash = data_table.write_set_hash,
+                actual_events_hash = data_table.events_hash,
+                updated_at = NOW()
+            FROM
+                (
+                    SELECT
+                        UNNEST($1::bytea[]) AS tx_hash,
+                        UNNEST($2::bytea[]) AS write_set_hash,
+                        UNNEST($3::bytea[]) AS events_hash
+                ) AS data_table
+            WHERE
+                transaction_simulations.tx_hash = data_table.tx_hash
+            "#,
+            &tx_hashes as &[&[u8]],
+            &write_set_hashes as &[&[u8]],
+            &events_hashes as &[&[u8]]
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Removes up to `limit` simulations recorded more than `retention` ago. Returns the number
+    /// of removed simulations.
+    pub async fn prune_transaction_simulations(
+        &mut self,
+        retention: Duration,
+        limit: usize,
+    ) -> DalResult<usize> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM transaction_simulations
+            WHERE
+                tx_hash IN (
+                    SELECT
+                        tx_hash
+                    FROM
+                        transaction_simulations
+                    WHERE
+                        created_at < NOW() - $1::INTERVAL
+                    ORDER BY
+                        created_at
+                    LIMIT
+                        $2
+                )
+            "#,
+            pg_interval_from_duration(retention),
+            limit as i64
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(result.rows_affected() as usize)
+    }
+}
+
+/// Converts the time a transaction was received at, in milliseconds since the epoch,
+/// to a Postgres timestamp.
+fn received_at(received_timestamp_ms: u64) -> DalResult<NaiveDateTime> {
+    let secs = (received_timestamp_ms / 1000) as i64;
+    let nanosecs = ((received_timestamp_ms % 1000) * 1_000_000) as u32;
+    NaiveDateTime::from_timestamp_opt(secs, nanosecs).ok_or_else(|| {
+        DalError::encode(format!(
+            "invalid received timestamp {received_timestamp_ms}"
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ola_types::request::PaymasterParams;
+    use ola_utils::time::seconds_since_epoch;
+
+    use super::*;
+
+    fn mock_l2_tx(nonce: u32) -> L2Tx {
+        let mut tx = L2Tx::new(
+            Address::repeat_byte(0xff),
+            vec![],
+            Nonce(nonce),
+            Address::repeat_byte(0xee),
+            None,
+            PaymasterParams::default(),
+        );
+        tx.set_input(vec![], H256::from_low_u64_be(0xee00 + u64::from(nonce)));
+        tx
+    }
+
+    #[ignore]
+    #[tokio::test]
+    async fn rejected_transactions_are_not_synced_to_mempool() {
+        let mut storage = StorageProcessor::establish_connection(true).await;
+        // Nothing is committed, so the test leaves the database intact.
+        let mut transaction = storage.start_transaction().await;
+        let (rejected_tx, pending_tx) = (mock_l2_tx(0), mock_l2_tx(1));
+        let (rejected_hash, pending_hash) = (rejected_tx.hash(), pending_tx.hash());
+        for tx in [rejected_tx, pending_tx] {
+            let result = transaction
+                .transactions_dal()
+                .insert_transaction_l2(tx, TransactionExecutionMetrics::default())
+                .await
+                .unwrap();
+            assert_eq!(result, L2TxSubmissionResult::Added);
+        }
+
+        transaction
+            .transactions_dal()
+            .mark_txs_as_rejected(&[(rejected_hash, "rejected: test".to_owned())])
+            .await
+            .unwrap();
+        let (synced, ..) = transaction
+            .transactions_dal()
+            .sync_mempool(vec![], vec![], 10_000, 10_000, None)
+            .await
+            .unwrap();
+        let synced_hashes: Vec<_> = synced.iter().map(Transaction::hash).collect();
+        assert!(synced_hashes.contains(&pending_hash));
+        assert!(!synced_hashes.contains(&rejected_hash));
+    }
+
+    fn mock_scheduled_l2_tx(nonce: u32, execute_after: u64) -> L2Tx {
+        let mut tx = mock_l2_tx(nonce);
+        tx.common_data.execute_after = Some(execute_after);
+        tx
+    }
+
+    #[ignore]
+    #[tokio::test]
+    async fn resubmission_cannot_change_schedule() {
+        let mut storage = StorageProcessor::establish_connection(true).await;
+        // Nothing is committed, so the test leaves the database intact.
+        let mut transaction = storage.start_transaction().await;
+        let execute_after = seconds_since_epoch() + 3_600;
+        let tx = mock_scheduled_l2_tx(0, execute_after);
+        let tx_hash = tx.hash();
+        let result = transaction
+            .transactions_dal()
+            .insert_transaction_l2(tx.clone(), TransactionExecutionMetrics::default())
+            .await
+            .unwrap();
+        assert_eq!(result, L2TxSubmissionResult::Added);
+
+        for execute_after in [None, Some(execute_after + 3_600)] {
+            let mut resubmitted_tx = tx.clone();
+            resubmitted_tx.common_data.execute_after = execute_after;
+            let result = transaction
+                .transactions_dal()
+                .insert_transaction_l2(resubmitted_tx, TransactionExecutionMetrics::default())
+                .await
+                .unwrap();
+            assert_eq!(result, L2TxSubmissionResult::Duplicate);
+        }
+
+        let stored_execute_after = sqlx::query!(
+            "SELECT execute_after FROM transactions WHERE hash = $1",
+            tx_hash.as_bytes()
+        )
+        .fetch_one(transaction.conn())
         .await
         .unwrap()
-        .map(|tx| tx.into())
+        .execute_after;
+        assert_eq!(
+            stored_execute_after.map(|timestamp| timestamp.timestamp() as u64),
+            Some(execute_after)
+        );
+    }
+
+    #[ignore]
+    #[tokio::test]
+    async fn scheduled_transactions_are_capped_in_mempool_sync() {
+        let mut storage = StorageProcessor::establish_connection(true).await;
+        // Nothing is committed, so the test leaves the database intact.
+        let mut transaction = storage.start_transaction().await;
+        let execute_after = seconds_since_epoch() + 3_600;
+        let txs = [
+            mock_scheduled_l2_tx(0, execute_after),
+            mock_scheduled_l2_tx(1, execute_after),
+            mock_l2_tx(2),
+        ];
+        let hashes: Vec<_> = txs.iter().map(L2Tx::hash).collect();
+        for tx in txs {
+            transaction
+                .transactions_dal()
+                .insert_transaction_l2(tx, TransactionExecutionMetrics::default())
+                .await
+                .unwrap();
+        }
+
+        let (synced, ..) = transaction
+            .transactions_dal()
+            .sync_mempool(vec![], vec![], 10_000, 1, None)
+            .await
+            .unwrap();
+        let synced_hashes: Vec<_> = synced.iter().map(Transaction::hash).collect();
+        assert!(synced_hashes.contains(&hashes[2]));
+        let synced_scheduled_count = hashes[..2]
+            .iter()
+            .filter(|hash| synced_hashes.contains(hash))
+            .count();
+        assert!(synced_scheduled_count <= 1);
     }
 }
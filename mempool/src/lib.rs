@@ -1,2 +1,3 @@
 pub mod mempool_store;
+pub mod policy;
 pub mod types;
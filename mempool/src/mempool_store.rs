@@ -1,10 +1,23 @@
-use std::collections::{hash_map::Entry, BTreeSet, HashMap, HashSet};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{hash_map::Entry, BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet},
+};
 
-use ola_types::{l2::L2Tx, Address, ExecuteTransactionCommon, Nonce, PriorityOpId, Transaction};
+use ola_types::{
+    api::{
+        MempoolAccountStatus, MempoolSkipReason, MempoolSkippedAccount, MempoolStatus,
+        MempoolTransactionInfo, TxpoolContent, TxpoolTransaction,
+    },
+    l2::L2Tx,
+    Address, ExecuteTransactionCommon, Nonce, PriorityOpId, Transaction, H256,
+};
 
-use crate::types::{AccountTransactions, MempoolScore};
+use crate::{
+    policy::{BlockBuilderPolicy, FifoPolicy},
+    types::{AccountTransactions, MempoolScore},
+};
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct MempoolStore {
     /// Pending L2 transactions grouped by initiator address
     l2_transactions_per_account: HashMap<Address, AccountTransactions>,
@@ -16,10 +29,23 @@ pub struct MempoolStore {
     /// number of l2 transactions in the mempool
     size: u64,
     capacity: u64,
+    /// Orders transactions of different accounts
+    policy: Box<dyn BlockBuilderPolicy>,
+    /// Hashes of rejected transactions whose rejection isn't persisted yet. Such transactions
+    /// are still loaded by the sync with Postgres, so they are skipped on insertion.
+    pending_rejections: HashSet<H256>,
 }
 
 impl MempoolStore {
     pub fn new(next_priority_id: PriorityOpId, capacity: u64) -> Self {
+        Self::with_policy(next_priority_id, capacity, Box::new(FifoPolicy))
+    }
+
+    pub fn with_policy(
+        next_priority_id: PriorityOpId,
+        capacity: u64,
+        policy: Box<dyn BlockBuilderPolicy>,
+    ) -> Self {
         Self {
             l2_transactions_per_account: HashMap::new(),
             l2_priority_queue: BTreeSet::new(),
@@ -27,19 +53,30 @@ impl MempoolStore {
             stashed_accounts: vec![],
             size: 0,
             capacity,
+            policy,
+            pending_rejections: HashSet::new(),
         }
     }
 
-    pub fn has_next(&self) -> bool {
-        // TODO: add filter latter
-        self.l2_priority_queue.iter().rfind(|_| true).is_some()
+    /// Checks whether the mempool has a transaction executable in a block with the specified
+    /// timestamp.
+    pub fn has_next(&self, timestamp: u64) -> bool {
+        self.l2_priority_queue
+            .iter()
+            .any(|pointer| self.is_due(pointer, timestamp))
     }
 
-    pub fn next_transaction(&mut self) -> Option<Transaction> {
+    /// Returns the next transaction executable in a block with the specified timestamp.
+    /// Scheduled transactions are skipped until they are due.
+    pub fn next_transaction(&mut self, timestamp: u64) -> Option<Transaction> {
         let mut removed = 0;
         // We want to fetch the next transaction that would match the fee requirements.
         // TODO: add filter
-        let tx_pointer = self.l2_priority_queue.iter().rfind(|_el| true)?.clone();
+        let tx_pointer = self
+            .l2_priority_queue
+            .iter()
+            .rfind(|pointer| self.is_due(pointer, timestamp))?
+            .clone();
 
         // Stash all observed transactions that don't meet criteria
         for stashed_pointer in self
@@ -48,6 +85,11 @@ impl MempoolStore {
             .into_iter()
             .skip(1)
         {
+            if !self.is_due(&stashed_pointer, timestamp) {
+                // Scheduled transactions stay in the queue until they are due.
+                self.l2_priority_queue.insert(stashed_pointer);
+                continue;
+            }
             removed += self
                 .l2_transactions_per_account
                 .remove(&stashed_pointer.account)
@@ -61,7 +103,7 @@ impl MempoolStore {
             .l2_transactions_per_account
             .get_mut(&tx_pointer.account)
             .expect("mempool: dangling pointer in priority queue")
-            .next();
+            .next(self.policy.as_ref());
 
         if let Some(score) = score {
             self.l2_priority_queue.insert(score);
@@ -78,12 +120,23 @@ impl MempoolStore {
         Some(transaction.into())
     }
 
+    fn is_due(&self, pointer: &MempoolScore, timestamp: u64) -> bool {
+        self.l2_transactions_per_account
+            .get(&pointer.account)
+            .and_then(AccountTransactions::next_pending)
+            .map_or(true, |tx| tx.common_data.is_executable_at(timestamp))
+    }
+
     pub fn insert(
         &mut self,
         transactions: Vec<Transaction>,
         initial_nonces: HashMap<Address, Nonce>,
     ) {
         for transaction in transactions {
+            if self.pending_rejections.contains(&transaction.hash()) {
+                olaos_logs::info!("skipping rejected tx hash {:?}", transaction.hash());
+                continue;
+            }
             let Transaction {
                 common_data,
                 execute,
@@ -125,7 +178,7 @@ impl MempoolStore {
         let account = transaction.initiator_account();
 
         let metadata = match self.l2_transactions_per_account.entry(account) {
-            Entry::Occupied(mut txs) => txs.get_mut().insert(transaction),
+            Entry::Occupied(mut txs) => txs.get_mut().insert(transaction, self.policy.as_ref()),
             Entry::Vacant(entry) => {
                 let account_nonce = initial_nonces.get(&account).cloned().unwrap_or(Nonce(0));
                 olaos_logs::info!(
@@ -135,7 +188,7 @@ impl MempoolStore {
                 );
                 entry
                     .insert(AccountTransactions::new(account_nonce))
-                    .insert(transaction)
+                    .insert(transaction, self.policy.as_ref())
             }
         };
         if let Some(score) = metadata.previous_score {
@@ -164,7 +217,7 @@ impl MempoolStore {
                     .l2_transactions_per_account
                     .get_mut(&tx.initiator_account())
                     .expect("account is not available in mempool")
-                    .reset(tx)
+                    .reset(tx, self.policy.as_ref())
                 {
                     self.l2_priority_queue.remove(&score);
                 }
@@ -175,6 +228,19 @@ impl MempoolStore {
         }
     }
 
+    /// Rolls back the nonces after a rejected transaction, which isn't inserted again until
+    /// [`Self::clear_persisted_rejections()`] is called.
+    pub fn reject(&mut self, tx: &Transaction) {
+        self.rollback(tx);
+        self.pending_rejections.insert(tx.hash());
+    }
+
+    /// Forgets rejected transactions once their rejections are persisted, so that they are
+    /// no longer loaded by the sync with Postgres.
+    pub fn clear_persisted_rejections(&mut self) {
+        self.pending_rejections.clear();
+    }
+
     pub fn get_mempool_info(&mut self) -> MempoolInfo {
         MempoolInfo {
             stashed_accounts: std::mem::take(&mut self.stashed_accounts),
@@ -182,6 +248,187 @@ impl MempoolStore {
         }
     }
 
+    /// Returns a snapshot of the mempool state for debugging, without modifying it.
+    /// At most `next_transactions_limit` transactions scheduled for inclusion are listed.
+    /// Per-account lists are capped at `accounts_limit` entries, so that the snapshot stays
+    /// cheap to build while the mempool is locked; accounts with the most pending
+    /// transactions are listed first.
+    pub fn status(
+        &self,
+        next_transactions_limit: usize,
+        accounts_limit: usize,
+        now_ms: u64,
+    ) -> MempoolStatus {
+        let accounts = self
+            .l2_transactions_per_account
+            .iter()
+            .map(|(address, txs)| MempoolAccountStatus {
+                address: *address,
+                next_nonce: txs.nonce(),
+                pending_transactions: txs.len(),
+            });
+        let (accounts, accounts_truncated) = take_smallest(accounts, accounts_limit, |account| {
+            (Reverse(account.pending_transactions), account.address)
+        });
+
+        let now = now_ms / 1_000;
+        // The priority queue is ordered so that the last element is fetched first.
+        let next_transactions = self
+            .l2_priority_queue
+            .iter()
+            .rev()
+            .filter(|pointer| self.is_due(pointer, now))
+            .take(next_transactions_limit)
+            .filter_map(|pointer| {
+                let tx = self
+                    .l2_transactions_per_account
+                    .get(&pointer.account)?
+                    .next_pending()?;
+                Some(MempoolTransactionInfo {
+                    hash: tx.hash(),
+                    initiator_address: pointer.account,
+                    nonce: tx.common_data.nonce,
+                    received_at_ms: tx.received_timestamp_ms,
+                })
+            })
+            .collect();
+
+        let scheduled_accounts: HashSet<_> = self
+            .l2_priority_queue
+            .iter()
+            .map(|pointer| pointer.account)
+            .collect();
+        let nonce_gaps = self
+            .l2_transactions_per_account
+            .iter()
+            .filter(|(address, txs)| txs.len() > 0 && !scheduled_accounts.contains(*address))
+            .map(|(address, txs)| MempoolSkippedAccount {
+                address: *address,
+                reason: MempoolSkipReason::NonceGap,
+                next_nonce: Some(txs.nonce()),
+                lowest_pending_nonce: txs.lowest_pending_nonce(),
+                execute_after: None,
+            });
+        let scheduled = self.l2_priority_queue.iter().filter_map(|pointer| {
+            let txs = self.l2_transactions_per_account.get(&pointer.account)?;
+            let tx = txs.next_pending()?;
+            (!tx.common_data.is_executable_at(now)).then(|| MempoolSkippedAccount {
+                address: pointer.account,
+                reason: MempoolSkipReason::Scheduled,
+                next_nonce: Some(txs.nonce()),
+                lowest_pending_nonce: txs.lowest_pending_nonce(),
+                execute_after: tx.common_data.execute_after,
+            })
+        });
+        let stashed = self
+            .stashed_accounts
+            .iter()
+            .map(|address| MempoolSkippedAccount {
+                address: *address,
+                reason: MempoolSkipReason::FilteredByPolicy,
+                next_nonce: None,
+                lowest_pending_nonce: None,
+                execute_after: None,
+            });
+        let (skipped_accounts, skipped_accounts_truncated) = take_smallest(
+            nonce_gaps.chain(scheduled).chain(stashed),
+            accounts_limit,
+            |account| account.address,
+        );
+
+        let oldest_received_at_ms = self
+            .l2_transactions_per_account
+            .values()
+            .filter_map(|txs| txs.oldest_received_at_ms())
+            .min();
+
+        MempoolStatus {
+            total_transactions: self.size,
+            scheduled_transactions: self.scheduled_len(now) as u64,
+            capacity: self.capacity,
+            oldest_transaction_age_ms: oldest_received_at_ms
+                .map(|received_at_ms| now_ms.saturating_sub(received_at_ms)),
+            accounts,
+            accounts_truncated,
+            next_transactions,
+            skipped_accounts,
+            skipped_accounts_truncated,
+        }
+    }
+
+    /// Returns the number of transactions which cannot be executed in a block with the specified
+    /// timestamp yet.
+    pub fn scheduled_len(&self, timestamp: u64) -> usize {
+        self.l2_transactions_per_account
+            .values()
+            .map(|txs| txs.scheduled_len(timestamp))
+            .sum()
+    }
+
+    /// Lists in-memory transactions grouped by initiator and nonce. Transactions of stashed
+    /// accounts are not held in memory and are not listed.
+    ///
+    /// At most `account_limit` transactions are listed per account (pending ones first) and
+    /// `total_limit` in total, taking accounts in address order, so that only the listed
+    /// transactions are copied while the mempool is locked. `truncated` is set if any
+    /// transactions were left out.
+    pub fn content(&self, account_limit: usize, total_limit: usize) -> TxpoolContent {
+        let to_txpool_transaction = |tx: &L2Tx| {
+            (
+                tx.common_data.nonce,
+                TxpoolTransaction {
+                    hash: tx.hash(),
+                    nonce: tx.common_data.nonce,
+                    from: tx.initiator_account(),
+                    to: tx.recipient_account(),
+                    input: tx.execute.calldata.clone().into(),
+                    received_at_ms: tx.received_timestamp_ms,
+                },
+            )
+        };
+
+        // Every listed account contributes at least one transaction, so no more than `total_limit`
+        // accounts can be listed.
+        let (addresses, mut truncated) = take_smallest(
+            self.l2_transactions_per_account.keys().copied(),
+            total_limit,
+            |address| *address,
+        );
+        let mut content = TxpoolContent::default();
+        let mut remaining = total_limit;
+        for address in addresses {
+            if remaining == 0 {
+                truncated = true;
+                break;
+            }
+            let txs = &self.l2_transactions_per_account[&address];
+            let limit = account_limit.min(remaining);
+            truncated |= txs.len() > limit;
+
+            let (pending, queued) = txs.pending_and_queued();
+            let queued_limit = limit.saturating_sub(pending.len());
+            let pending: BTreeMap<_, _> = pending
+                .into_iter()
+                .take(limit)
+                .map(to_txpool_transaction)
+                .collect();
+            let queued: BTreeMap<_, _> = queued
+                .into_iter()
+                .take(queued_limit)
+                .map(to_txpool_transaction)
+                .collect();
+            remaining -= pending.len() + queued.len();
+            if !pending.is_empty() {
+                content.pending.insert(address, pending);
+            }
+            if !queued.is_empty() {
+                content.queued.insert(address, queued);
+            }
+        }
+        content.truncated = truncated;
+        content
+    }
+
     fn gc(&mut self) -> Vec<Address> {
         if self.size >= self.capacity {
             let index: HashSet<_> = self
@@ -208,6 +455,50 @@ impl MempoolStore {
     }
 }
 
+/// Returns up to `limit` items with the smallest keys in ascending key order, and whether
+/// any items were left out. At most `limit + 1` items are held at a time.
+fn take_smallest<T, K: Ord>(
+    items: impl Iterator<Item = T>,
+    limit: usize,
+    key: impl Fn(&T) -> K,
+) -> (Vec<T>, bool) {
+    let mut heap = BinaryHeap::with_capacity(limit + 1);
+    let mut truncated = false;
+    for item in items {
+        heap.push(KeyedItem(key(&item), item));
+        if heap.len() > limit {
+            heap.pop(); // Removes the item with the greatest key
+            truncated = true;
+        }
+    }
+    let items = heap.into_sorted_vec().into_iter().map(|entry| entry.1);
+    (items.collect(), truncated)
+}
+
+/// Item ordered only by its key.
+#[derive(Debug)]
+struct KeyedItem<K, T>(K, T);
+
+impl<K: Ord, T> PartialEq for KeyedItem<K, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<K: Ord, T> Eq for KeyedItem<K, T> {}
+
+impl<K: Ord, T> PartialOrd for KeyedItem<K, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, T> Ord for KeyedItem<K, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
 #[derive(Debug)]
 pub struct MempoolInfo {
     pub stashed_accounts: Vec<Address>,
@@ -0,0 +1,66 @@
+//! Policies ordering transactions of different accounts in blocks.
+
+use std::fmt;
+
+use ola_types::l2::L2Tx;
+
+/// Orders transactions when the sequencer pulls the next transaction from the mempool.
+///
+/// Transactions of the same account are always pulled in the nonce order; the policy only orders
+/// the next transactions of different accounts.
+pub trait BlockBuilderPolicy: fmt::Debug + Send + Sync {
+    /// Returns the ordering key of the transaction. Transactions with lower keys are pulled first,
+    /// ties are broken by the initiator address. The key must only depend on the transaction,
+    /// since it's computed once the transaction becomes the next one of its account.
+    fn ordering_key(&self, transaction: &L2Tx) -> u64;
+}
+
+/// Pulls transactions in the order they were received by the node.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FifoPolicy;
+
+impl BlockBuilderPolicy for FifoPolicy {
+    fn ordering_key(&self, transaction: &L2Tx) -> u64 {
+        transaction.received_timestamp_ms
+    }
+}
+
+/// Pulls transactions in a pseudorandom order derived from a secret seed and transaction data
+/// that submitters cannot choose: the time the node received the transaction and its nonce.
+/// Unlike with [`FifoPolicy`], submitters cannot get ahead of other transactions by observing
+/// them and submitting their own faster, and unlike with hash-based keys, they cannot grind
+/// transaction contents for a better position. The order is still reproducible given the seed.
+#[derive(Clone, Copy)]
+pub struct SeededShufflePolicy {
+    seed: u64,
+}
+
+impl fmt::Debug for SeededShufflePolicy {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The seed is not logged, since knowing it allows to predict the order.
+        formatter
+            .debug_struct("SeededShufflePolicy")
+            .finish_non_exhaustive()
+    }
+}
+
+impl SeededShufflePolicy {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+}
+
+impl BlockBuilderPolicy for SeededShufflePolicy {
+    fn ordering_key(&self, transaction: &L2Tx) -> u64 {
+        let received_at = mix(transaction.received_timestamp_ms ^ self.seed);
+        mix(received_at ^ u64::from(transaction.nonce().0))
+    }
+}
+
+/// Finalizer of the SplitMix64 generator; a bijection that spreads every input bit
+/// over the whole output.
+fn mix(mut value: u64) -> u64 {
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
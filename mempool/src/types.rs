@@ -2,6 +2,8 @@ use std::{cmp::Ordering, collections::HashMap};
 
 use ola_types::{l2::L2Tx, Address, Nonce, Transaction};
 
+use crate::policy::BlockBuilderPolicy;
+
 #[derive(Debug)]
 pub(crate) struct AccountTransactions {
     /// transactions that belong to given account keyed by transaction nonce
@@ -19,18 +21,22 @@ impl AccountTransactions {
         }
     }
 
-    pub fn insert(&mut self, transaction: L2Tx) -> InsertionMetadata {
+    pub fn insert(
+        &mut self,
+        transaction: L2Tx,
+        policy: &dyn BlockBuilderPolicy,
+    ) -> InsertionMetadata {
         let mut metadata = InsertionMetadata::default();
         let nonce = transaction.common_data.nonce;
         // skip insertion if transaction is old
         if nonce < self.nonce {
             return metadata;
         }
-        let new_score = Self::score_for_transaction(&transaction);
+        let new_score = Self::score_for_transaction(&transaction, policy);
         let previous_score = self
             .transactions
             .insert(nonce, transaction)
-            .map(|tx| Self::score_for_transaction(&tx));
+            .map(|tx| Self::score_for_transaction(&tx, policy));
         metadata.is_new = previous_score.is_none();
         if nonce == self.nonce {
             metadata.new_score = Some(new_score);
@@ -39,15 +45,19 @@ impl AccountTransactions {
         metadata
     }
 
-    fn score_for_transaction(transaction: &L2Tx) -> MempoolScore {
+    fn score_for_transaction(transaction: &L2Tx, policy: &dyn BlockBuilderPolicy) -> MempoolScore {
         MempoolScore {
             account: transaction.initiator_account(),
-            received_at_ms: transaction.received_timestamp_ms,
+            ordering_key: policy.ordering_key(transaction),
         }
     }
 
     // Handles transaction rejection. Returns optional score of its successor
-    pub fn reset(&mut self, transaction: &Transaction) -> Option<MempoolScore> {
+    pub fn reset(
+        &mut self,
+        transaction: &Transaction,
+        policy: &dyn BlockBuilderPolicy,
+    ) -> Option<MempoolScore> {
         // current nonce for the group needs to be reset
         let tx_nonce = transaction
             .nonce()
@@ -55,14 +65,61 @@ impl AccountTransactions {
         self.nonce = self.nonce.min(tx_nonce);
         self.transactions
             .get(&(tx_nonce + 1))
-            .map(Self::score_for_transaction)
+            .map(|tx| Self::score_for_transaction(tx, policy))
     }
 
     pub fn len(&self) -> usize {
         self.transactions.len()
     }
 
-    pub fn next(&mut self) -> (L2Tx, Option<MempoolScore>) {
+    pub fn nonce(&self) -> Nonce {
+        self.nonce
+    }
+
+    /// Returns the transaction with the nonce expected next, if it is already in the mempool.
+    pub fn next_pending(&self) -> Option<&L2Tx> {
+        self.transactions.get(&self.nonce)
+    }
+
+    pub fn lowest_pending_nonce(&self) -> Option<Nonce> {
+        self.transactions.keys().min().copied()
+    }
+
+    /// Splits transactions into the ones executable one after another starting from
+    /// the expected nonce, and the ones after a nonce gap. Both are sorted by nonce.
+    pub fn pending_and_queued(&self) -> (Vec<&L2Tx>, Vec<&L2Tx>) {
+        let mut transactions: Vec<_> = self.transactions.values().collect();
+        transactions.sort_by_key(|tx| tx.common_data.nonce);
+
+        let mut next_nonce = self.nonce;
+        let (mut pending, mut queued) = (vec![], vec![]);
+        for tx in transactions {
+            if tx.common_data.nonce == next_nonce {
+                pending.push(tx);
+                next_nonce += 1;
+            } else {
+                queued.push(tx);
+            }
+        }
+        (pending, queued)
+    }
+
+    /// Returns the number of transactions which cannot be executed at the specified timestamp yet.
+    pub fn scheduled_len(&self, timestamp: u64) -> usize {
+        self.transactions
+            .values()
+            .filter(|tx| !tx.common_data.is_executable_at(timestamp))
+            .count()
+    }
+
+    pub fn oldest_received_at_ms(&self) -> Option<u64> {
+        self.transactions
+            .values()
+            .map(|tx| tx.received_timestamp_ms)
+            .min()
+    }
+
+    pub fn next(&mut self, policy: &dyn BlockBuilderPolicy) -> (L2Tx, Option<MempoolScore>) {
         let transaction = self
             .transactions
             .remove(&self.nonce)
@@ -71,7 +128,7 @@ impl AccountTransactions {
         let score = self
             .transactions
             .get(&self.nonce)
-            .map(Self::score_for_transaction);
+            .map(|tx| Self::score_for_transaction(tx, policy));
         (transaction, score)
     }
 }
@@ -79,12 +136,13 @@ impl AccountTransactions {
 #[derive(Eq, PartialEq, Clone, Debug, Hash)]
 pub struct MempoolScore {
     pub account: Address,
-    pub received_at_ms: u64,
+    /// Key assigned by the [`BlockBuilderPolicy`]; lower keys are pulled first.
+    pub ordering_key: u64,
 }
 
 impl Ord for MempoolScore {
     fn cmp(&self, other: &MempoolScore) -> Ordering {
-        match self.received_at_ms.cmp(&other.received_at_ms).reverse() {
+        match self.ordering_key.cmp(&other.ordering_key).reverse() {
             Ordering::Equal => {}
             ordering => return ordering,
         }
@@ -1,6 +1,7 @@
 pub mod iter_ext;
 pub mod macros;
 pub mod patch;
+pub mod reader;
 pub mod storage;
 pub mod tree;
 pub mod tree_config;
@@ -47,4 +48,7 @@ pub enum TreeError {
 
     #[error("Lock mutex error: {0}")]
     MutexLockError(String),
+
+    #[error("Tree node at key {0:?} is corrupted: {1}")]
+    CorruptedNode(Vec<u8>, storage::DeserializeError),
 }
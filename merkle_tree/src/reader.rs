@@ -0,0 +1,232 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    storage::{MerkleTreeColumnFamily, Storage, BLOCK_NUMBER_KEY},
+    tree_config::TreeConfig,
+    utils::idx_to_merkle_path,
+    TreeError,
+};
+use ola_types::merkle_tree::{tree_key_to_u256, u8_arr_to_tree_key, LevelIndex};
+use ola_utils::convert::{deserialize_block_number, deserialize_leaf_index, serialize_tree_leaf};
+use olaos_storage::{RocksDB, RocksDBSnapshot};
+use olavm_core::{
+    crypto::ZkHasher,
+    types::merkle_tree::{TreeKey, TreeValue, ZkHash},
+};
+
+/// Value of a tree leaf together with the Merkle path proving it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeEntryWithProof {
+    /// Value of the leaf; the empty leaf value if the key was never written.
+    pub value: TreeValue,
+    /// Index assigned to the leaf on its first write; 0 if the key was never written.
+    pub leaf_index: u64,
+    /// Hashes of the sibling nodes, ordered from the leaf level up to the root.
+    pub merkle_path: Vec<ZkHash>,
+}
+
+/// Entries read from a single consistent state of the tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeEntriesWithProofs {
+    /// Number of L1 batches processed by the tree at the time of reading.
+    pub block_number: u32,
+    pub root_hash: ZkHash,
+    pub entries: Vec<TreeEntryWithProof>,
+}
+
+/// Read-only access to the tree.
+///
+/// The reader shares the RocksDB instance with [`AccountTree`](crate::tree::AccountTree), but never
+/// writes to it, and every read goes through a RocksDB snapshot. Since the tree persists each L1 batch
+/// with a single write batch, readers observe either the state before or after the batch and don't
+/// need to synchronize with the updater.
+#[derive(Debug, Clone)]
+pub struct TreeReader {
+    db: RocksDB<MerkleTreeColumnFamily>,
+    config: TreeConfig<ZkHasher>,
+}
+
+impl TreeReader {
+    pub fn new(db: RocksDB<MerkleTreeColumnFamily>) -> Self {
+        let config = TreeConfig::new(ZkHasher::default()).expect("TreeConfig new failed");
+        Self { db, config }
+    }
+
+    /// Returns the root hash and the number of processed L1 batches.
+    pub fn metadata(&self) -> Result<(ZkHash, u32), TreeError> {
+        let snapshot = self.db.snapshot();
+        Ok((self.root_hash(&snapshot)?, Self::block_number(&snapshot)?))
+    }
+
+    /// Reads values of the specified `keys` together with Merkle paths to them.
+    pub fn entries_with_proofs(
+        &self,
+        keys: &[TreeKey],
+    ) -> Result<TreeEntriesWithProofs, TreeError> {
+        let snapshot = self.db.snapshot();
+        let root_hash = self.root_hash(&snapshot)?;
+        let block_number = Self::block_number(&snapshot)?;
+
+        let leaf_indices = snapshot.multi_get_cf(
+            MerkleTreeColumnFamily::LeafIndices,
+            keys.iter().map(|key| serialize_tree_leaf(*key)),
+        );
+        let entries = keys
+            .iter()
+            .zip(leaf_indices)
+            .map(|(key, leaf_index)| {
+                let leaf_index = leaf_index?
+                    .map(|bytes| deserialize_leaf_index(&bytes))
+                    .unwrap_or(0);
+                let idx = tree_key_to_u256(key);
+                let leaf = idx_to_merkle_path(idx, false)
+                    .last()
+                    .expect("Merkle path is empty");
+                let value = self.hashes(&snapshot, [leaf])?[0];
+                let siblings: Vec<_> = idx_to_merkle_path(idx, true).rev().collect();
+                let merkle_path = self.hashes(&snapshot, siblings)?;
+                Ok(TreeEntryWithProof {
+                    value,
+                    leaf_index,
+                    merkle_path,
+                })
+            })
+            .collect::<Result<_, TreeError>>()?;
+
+        Ok(TreeEntriesWithProofs {
+            block_number,
+            root_hash,
+            entries,
+        })
+    }
+
+    /// Reads the leaf indices of `keys` and the nodes on the paths to them (including the siblings),
+    /// so that they are in the RocksDB block cache once the tree is updated with `keys`.
+    /// Reads are issued in chunks of `chunk_size` DB keys. Returns the number of read DB keys.
+    pub fn prefetch(&self, keys: &[TreeKey], chunk_size: usize) -> Result<usize, TreeError> {
+        // Paths of different keys share the upper levels, so DB keys are deduplicated. Sorting them
+        // also makes reads of each chunk hit adjacent blocks.
+        let mut db_keys = BTreeSet::new();
+        for key in keys {
+            db_keys.insert(serialize_tree_leaf(*key));
+            let idx = tree_key_to_u256(key);
+            let path = idx_to_merkle_path(idx, false).chain(idx_to_merkle_path(idx, true));
+            db_keys.extend(path.map(|level_index| level_index.bin_key()));
+        }
+
+        let db_keys: Vec<_> = db_keys.into_iter().collect();
+        for chunk in db_keys.chunks(chunk_size.max(1)) {
+            let values = self
+                .db
+                .multi_get_cf(MerkleTreeColumnFamily::LeafIndices, chunk.iter().cloned());
+            for value in values {
+                value?;
+            }
+        }
+        Ok(db_keys.len())
+    }
+
+    fn root_hash(
+        &self,
+        snapshot: &RocksDBSnapshot<'_, MerkleTreeColumnFamily>,
+    ) -> Result<ZkHash, TreeError> {
+        // The root hash is represented by level index (0, 0).
+        let root: LevelIndex = (0, 0.into()).into();
+        Ok(self.hashes(snapshot, [root])?[0])
+    }
+
+    fn block_number(
+        snapshot: &RocksDBSnapshot<'_, MerkleTreeColumnFamily>,
+    ) -> Result<u32, TreeError> {
+        Ok(snapshot
+            .get_cf(MerkleTreeColumnFamily::Tree, BLOCK_NUMBER_KEY)?
+            .map(|bytes| deserialize_block_number(&bytes))
+            .unwrap_or(0))
+    }
+
+    /// Fetches hashes of the nodes at `level_indices`, falling back to the hashes of the empty tree.
+    fn hashes(
+        &self,
+        snapshot: &RocksDBSnapshot<'_, MerkleTreeColumnFamily>,
+        level_indices: impl IntoIterator<Item = LevelIndex>,
+    ) -> Result<Vec<ZkHash>, TreeError> {
+        let level_indices: Vec<_> = level_indices.into_iter().collect();
+        let empty_tree = self.config.empty_tree();
+        snapshot
+            .multi_get_cf(
+                MerkleTreeColumnFamily::LeafIndices,
+                level_indices.iter().map(LevelIndex::bin_key),
+            )
+            .into_iter()
+            .zip(&level_indices)
+            .map(|(bytes, level_index)| {
+                Ok(match bytes? {
+                    Some(bytes) => u8_arr_to_tree_key(&Storage::decode_node(&bytes)),
+                    None => *empty_tree[level_index.0 .0 as usize].hash(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ola_types::{
+        log::{StorageLog, WitnessStorageLog},
+        merkle_tree::h256_to_tree_key,
+        AccountTreeId, Address, StorageKey, H256,
+    };
+    use olavm_core::types::merkle_tree::constant::ROOT_TREE_DEPTH;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::tree::AccountTree;
+
+    fn write_log(key: u64) -> WitnessStorageLog {
+        let key = StorageKey::new(
+            AccountTreeId::new(Address::repeat_byte(1)),
+            H256::from_low_u64_be(key),
+        );
+        WitnessStorageLog {
+            storage_log: StorageLog::new_write_log(key, H256::repeat_byte(0xff)),
+            previous_value: H256::zero(),
+        }
+    }
+
+    fn tree_key(log: &WitnessStorageLog) -> TreeKey {
+        h256_to_tree_key(&log.storage_log.key.hashed_key())
+    }
+
+    #[test]
+    fn prefetching_tree_nodes() {
+        let dir = TempDir::new().unwrap();
+        let db = RocksDB::new(dir.path());
+        let logs: Vec<_> = (0..2).map(write_log).collect();
+        let mut tree = AccountTree::new_with_db(db.clone());
+        tree.process_block(&logs);
+        tree.save().unwrap();
+
+        let reader = TreeReader::new(db);
+        let metadata = reader.metadata().unwrap();
+        let keys: Vec<_> = logs.iter().map(tree_key).collect();
+
+        // The leaf key, and the nodes on the path to the leaf together with their siblings.
+        let single_key_reads = 2 * ROOT_TREE_DEPTH + 1;
+        assert_eq!(reader.prefetch(&keys[..1], 1).unwrap(), single_key_reads);
+        // Paths to different keys share the upper levels.
+        let read_count = reader.prefetch(&keys, 16).unwrap();
+        assert!(
+            read_count > single_key_reads && read_count < 2 * single_key_reads,
+            "{read_count}"
+        );
+        // Chunking doesn't affect which keys are read.
+        assert_eq!(reader.prefetch(&keys, 1).unwrap(), read_count);
+        assert_eq!(reader.prefetch(&keys, 0).unwrap(), read_count);
+
+        // Keys which are not in the tree yet are read as well, since the tree loads their paths
+        // on insertion.
+        let new_key = tree_key(&write_log(2));
+        assert_eq!(reader.prefetch(&[new_key], 8).unwrap(), single_key_reads);
+        assert_eq!(reader.metadata().unwrap(), metadata);
+    }
+}
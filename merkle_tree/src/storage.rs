@@ -1,3 +1,4 @@
+use self::serialization::{deserialize_node, is_node_key, serialize_node, FORMAT_VERSION_KEY};
 use crate::TreeError;
 use itertools::Itertools;
 use log::debug;
@@ -19,7 +20,11 @@ use std::{
     fmt::Debug,
 };
 
-const BLOCK_NUMBER_KEY: &[u8; 12] = b"block_number";
+pub use self::serialization::{DeserializeError, NodeFormat};
+
+mod serialization;
+
+pub(crate) const BLOCK_NUMBER_KEY: &[u8; 12] = b"block_number";
 const LEAF_INDEX_KEY: &[u8; 10] = b"leaf_index";
 
 /// RocksDB column families used by the tree.
@@ -52,17 +57,50 @@ pub struct Storage {
     db: RocksDB<MerkleTreeColumnFamily>,
     pub(crate) put_pending_patch: HashMap<Vec<u8>, Vec<u8>>,
     pub(crate) delete_pending_patch: Vec<Vec<u8>>,
+    /// Format of the oldest nodes which may still be present in the DB.
+    format: NodeFormat,
+    /// Key to resume node migration from.
+    migration_cursor: Vec<u8>,
 }
 
 impl Storage {
     pub fn new(db: RocksDB<MerkleTreeColumnFamily>) -> Self {
+        let tag = db
+            .get_cf(MerkleTreeColumnFamily::Tree, FORMAT_VERSION_KEY)
+            .expect("failed to fetch tree format version");
+        let is_new_db = tag.is_none()
+            && db
+                .get_cf(MerkleTreeColumnFamily::Tree, BLOCK_NUMBER_KEY)
+                .expect("failed to fetch tree metadata")
+                .is_none();
+        let format = if is_new_db {
+            let mut write_batch = db.new_write_batch();
+            write_batch.put_cf(
+                MerkleTreeColumnFamily::Tree,
+                FORMAT_VERSION_KEY,
+                &NodeFormat::CURRENT.to_tag(),
+            );
+            db.write(write_batch)
+                .expect("failed to write tree format version");
+            NodeFormat::CURRENT
+        } else {
+            NodeFormat::from_tag(tag.as_deref()).expect("failed to parse tree format version")
+        };
+
         Self {
             db,
             put_pending_patch: HashMap::new(),
             delete_pending_patch: vec![],
+            format,
+            migration_cursor: vec![],
         }
     }
 
+    /// Returns the format of the oldest nodes which may still be present in the DB.
+    pub fn format(&self) -> NodeFormat {
+        self.format
+    }
+
     /// Fetches hashes of merkle tree branches from db
     pub fn hashes<'a, I: 'a>(&'a self, keys: I) -> Vec<Option<Vec<u8>>>
     where
@@ -75,7 +113,7 @@ impl Storage {
             )
             .into_iter()
             .map(|result| match result {
-                Ok(Some(slice)) => Some(slice.as_ref().to_vec()),
+                Ok(Some(bytes)) => Some(Self::decode_node(&bytes)),
                 _ => None,
             })
             .collect()
@@ -88,6 +126,71 @@ impl Storage {
                 &LevelIndex::bin_key(key),
             )
             .unwrap()
+            .map(|bytes| Self::decode_node(&bytes))
+    }
+
+    pub(crate) fn decode_node(bytes: &[u8]) -> Vec<u8> {
+        let (_, hash) = deserialize_node(bytes).expect("failed to deserialize tree node");
+        hash.to_vec()
+    }
+
+    /// Rewrites up to `max_nodes` nodes stored in an older format into [`NodeFormat::CURRENT`],
+    /// resuming from where the previous call stopped. Once all nodes are rewritten, updates
+    /// the format version tag. Returns the number of rewritten nodes.
+    pub fn migrate_nodes(&mut self, max_nodes: usize) -> Result<usize, TreeError> {
+        if self.format == NodeFormat::CURRENT {
+            return Ok(0);
+        }
+
+        let mut migrated_nodes = Vec::new();
+        let mut next_cursor = None;
+        for (key, value) in self
+            .db
+            .from_iterator_cf(MerkleTreeColumnFamily::LeafIndices, &self.migration_cursor)
+        {
+            if migrated_nodes.len() == max_nodes {
+                next_cursor = Some(key.into_vec());
+                break;
+            }
+            // Leaf indices are stored in the same column family; they aren't versioned.
+            if !is_node_key(&key) {
+                continue;
+            }
+            let (format, hash) = deserialize_node(&value)
+                .map_err(|err| TreeError::CorruptedNode(key.to_vec(), err))?;
+            if format < NodeFormat::CURRENT {
+                migrated_nodes.push((key, serialize_node(hash)));
+            }
+        }
+
+        let migrated_count = migrated_nodes.len();
+        let mut write_batch = self.db.new_write_batch();
+        for (key, value) in &migrated_nodes {
+            write_batch.put_cf(MerkleTreeColumnFamily::LeafIndices, key, value);
+        }
+        if next_cursor.is_none() {
+            write_batch.put_cf(
+                MerkleTreeColumnFamily::Tree,
+                FORMAT_VERSION_KEY,
+                &NodeFormat::CURRENT.to_tag(),
+            );
+        }
+        self.db
+            .write(write_batch)
+            .map_err(TreeError::StorageIoError)?;
+
+        match next_cursor {
+            Some(cursor) => self.migration_cursor = cursor,
+            None => {
+                debug!(
+                    "Migrated all tree nodes to format {:?}",
+                    NodeFormat::CURRENT
+                );
+                self.format = NodeFormat::CURRENT;
+                self.migration_cursor.clear();
+            }
+        }
+        Ok(migrated_count)
     }
 
     /// Prepares db update
@@ -95,10 +198,10 @@ impl Storage {
         for (level_index, tree_key) in branches {
             match self.put_pending_patch.entry(level_index.bin_key()) {
                 Entry::Occupied(mut entry) => {
-                    *entry.get_mut() = tree_key_to_u8_arr(tree_key);
+                    *entry.get_mut() = serialize_node(&tree_key_to_u8_arr(tree_key));
                 }
                 Entry::Vacant(entry) => {
-                    entry.insert(tree_key_to_u8_arr(tree_key));
+                    entry.insert(serialize_node(&tree_key_to_u8_arr(tree_key)));
                 }
             }
         }
@@ -278,3 +381,121 @@ impl Storage {
 /// High level merkle tree metadata
 /// Includes root hash and current block number
 pub(crate) type StoredTreeMetadata = (Option<ZkHash>, u32);
+
+#[cfg(test)]
+mod tests {
+    use ola_types::U256;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn node_key(level: u16, index: U256) -> Vec<u8> {
+        LevelIndex((level, index)).bin_key()
+    }
+
+    /// Creates a DB with nodes written before the format was versioned.
+    fn create_v0_db(
+        dir: &TempDir,
+        nodes: &[(Vec<u8>, [u8; 32])],
+    ) -> RocksDB<MerkleTreeColumnFamily> {
+        let db = RocksDB::new(dir.path());
+        let mut write_batch = db.new_write_batch();
+        write_batch.put_cf(
+            MerkleTreeColumnFamily::Tree,
+            BLOCK_NUMBER_KEY,
+            &serialize_block_number(1),
+        );
+        for (key, hash) in nodes {
+            write_batch.put_cf(MerkleTreeColumnFamily::LeafIndices, key, hash);
+        }
+        db.write(write_batch).unwrap();
+        db
+    }
+
+    #[test]
+    fn node_keys_are_distinguished_from_leaf_keys() {
+        // A node key with a 20-digit hex index has the same length as a leaf key.
+        let long_node_key = node_key(3, U256::one() << 76);
+        assert_eq!(long_node_key.len(), 32);
+        assert!(is_node_key(&long_node_key));
+        assert!(is_node_key(&node_key(0, U256::zero())));
+
+        assert!(!is_node_key(&[0xab; 32]));
+        assert!(!is_node_key(LEAF_INDEX_KEY));
+        let mut non_canonical_key = node_key(1, U256::from(5));
+        non_canonical_key.push(0);
+        assert!(!is_node_key(&non_canonical_key));
+    }
+
+    #[test]
+    fn migrating_nodes_in_chunks() {
+        let dir = TempDir::new().unwrap();
+        let nodes: Vec<_> = (0..5_u8)
+            .map(|i| (node_key(u16::from(i), U256::one() << 76), [i + 1; 32]))
+            .collect();
+        let db = create_v0_db(&dir, &nodes);
+        let leaf_key = vec![0xab; 32];
+        let mut write_batch = db.new_write_batch();
+        write_batch.put_cf(
+            MerkleTreeColumnFamily::LeafIndices,
+            &leaf_key,
+            &serialize_leaf_index(7),
+        );
+        write_batch.put_cf(
+            MerkleTreeColumnFamily::LeafIndices,
+            LEAF_INDEX_KEY,
+            &serialize_leaf_index(8),
+        );
+        db.write(write_batch).unwrap();
+
+        let mut storage = Storage::new(db.clone());
+        assert_eq!(storage.format(), NodeFormat::V0);
+        let mut migrated_count = 0;
+        loop {
+            let count = storage.migrate_nodes(2).unwrap();
+            migrated_count += count;
+            if storage.format() == NodeFormat::CURRENT {
+                break;
+            }
+            assert_eq!(count, 2);
+        }
+        assert_eq!(migrated_count, nodes.len());
+
+        for (key, hash) in &nodes {
+            let raw_node = db
+                .get_cf(MerkleTreeColumnFamily::LeafIndices, key)
+                .unwrap()
+                .unwrap();
+            assert_eq!(raw_node.len(), 33);
+            assert_eq!(Storage::decode_node(&raw_node), hash);
+        }
+        let leaf_index = db
+            .get_cf(MerkleTreeColumnFamily::LeafIndices, &leaf_key)
+            .unwrap()
+            .unwrap();
+        assert_eq!(deserialize_leaf_index(&leaf_index), 7);
+
+        // The format tag is persisted, so nothing is migrated after a restart.
+        let mut storage = Storage::new(db);
+        assert_eq!(storage.format(), NodeFormat::CURRENT);
+        assert_eq!(storage.migrate_nodes(2).unwrap(), 0);
+    }
+
+    #[test]
+    fn migration_fails_on_corrupted_node() {
+        let dir = TempDir::new().unwrap();
+        let key = node_key(1, U256::from(5));
+        let db = create_v0_db(&dir, &[]);
+        let mut write_batch = db.new_write_batch();
+        write_batch.put_cf(MerkleTreeColumnFamily::LeafIndices, &key, &[1; 8]);
+        db.write(write_batch).unwrap();
+
+        let mut storage = Storage::new(db);
+        let err = storage.migrate_nodes(10).unwrap_err();
+        assert!(
+            matches!(&err, TreeError::CorruptedNode(err_key, _) if *err_key == key),
+            "{err:?}"
+        );
+        assert_eq!(storage.format(), NodeFormat::V0);
+    }
+}
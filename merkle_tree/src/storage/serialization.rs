@@ -0,0 +1,99 @@
+//! On-disk format of tree nodes.
+//!
+//! Nodes are stored in the `leaf_indices` column family keyed by [`LevelIndex::bin_key()`],
+//! next to leaf indices. The format version of the oldest node which may still be present in
+//! the DB is recorded under the [`FORMAT_VERSION_KEY`] tag in the `default` column family.
+//! Nodes are always written in [`NodeFormat::CURRENT`], while nodes in the previous format
+//! remain readable until they are rewritten by [`Storage::migrate_nodes()`].
+//!
+//! [`LevelIndex::bin_key()`]: ola_types::merkle_tree::LevelIndex::bin_key
+//! [`Storage::migrate_nodes()`]: super::Storage::migrate_nodes
+
+use std::fmt;
+
+use ola_types::{merkle_tree::LevelIndex, U256};
+
+pub(super) const FORMAT_VERSION_KEY: &[u8; 14] = b"format_version";
+
+const HASH_LEN: usize = 32;
+
+/// Version of the node serialization format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NodeFormat {
+    /// Raw 32-byte node hash without a version prefix.
+    V0 = 0,
+    /// Node hash prefixed with the version byte.
+    V1 = 1,
+}
+
+impl NodeFormat {
+    pub const CURRENT: Self = Self::V1;
+
+    pub(super) fn from_tag(bytes: Option<&[u8]>) -> Result<Self, DeserializeError> {
+        match bytes {
+            // DBs created before format versioning was introduced don't have the tag.
+            None => Ok(Self::V0),
+            Some([0]) => Ok(Self::V0),
+            Some([1]) => Ok(Self::V1),
+            Some(bytes) => Err(DeserializeError::UnknownFormatTag(bytes.to_vec())),
+        }
+    }
+
+    pub(super) fn to_tag(self) -> [u8; 1] {
+        [self as u8]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeserializeError {
+    UnknownFormatTag(Vec<u8>),
+    UnexpectedNodeLength(usize),
+    UnsupportedNodeVersion(u8),
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownFormatTag(tag) => write!(formatter, "unknown format version tag {tag:?}"),
+            Self::UnexpectedNodeLength(len) => {
+                write!(formatter, "unexpected serialized node length {len}")
+            }
+            Self::UnsupportedNodeVersion(version) => {
+                write!(formatter, "unsupported node format version {version}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DeserializeError {}
+
+/// Checks whether `key` from the `leaf_indices` column family is a node key, i.e. a canonically
+/// serialized [`LevelIndex`], rather than a leaf key or the leaf index counter. Leaf keys may have
+/// the same length as node keys, so keys are parsed rather than classified by their length.
+pub(super) fn is_node_key(key: &[u8]) -> bool {
+    let Ok(level_index) = bincode::deserialize::<(u16, U256)>(key) else {
+        return false;
+    };
+    LevelIndex(level_index).bin_key() == key
+}
+
+/// Serializes a node hash in [`NodeFormat::CURRENT`].
+pub(super) fn serialize_node(hash: &[u8]) -> Vec<u8> {
+    debug_assert_eq!(hash.len(), HASH_LEN);
+    let mut bytes = Vec::with_capacity(HASH_LEN + 1);
+    bytes.push(NodeFormat::CURRENT as u8);
+    bytes.extend_from_slice(hash);
+    bytes
+}
+
+/// Deserializes a node hash written in either the current or the previous format.
+pub(super) fn deserialize_node(bytes: &[u8]) -> Result<(NodeFormat, &[u8]), DeserializeError> {
+    match bytes.len() {
+        HASH_LEN => Ok((NodeFormat::V0, bytes)),
+        len if len == HASH_LEN + 1 => match bytes[0] {
+            1 => Ok((NodeFormat::V1, &bytes[1..])),
+            version => Err(DeserializeError::UnsupportedNodeVersion(version)),
+        },
+        len => Err(DeserializeError::UnexpectedNodeLength(len)),
+    }
+}
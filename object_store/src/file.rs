@@ -21,7 +21,13 @@ pub(crate) struct FileBackedObjectStore {
 
 impl FileBackedObjectStore {
     pub async fn new(base_dir: String) -> Self {
-        for bucket in &[Bucket::WitnessInput, Bucket::ProofsFri] {
+        for bucket in &[
+            Bucket::WitnessInput,
+            Bucket::ProofsFri,
+            Bucket::TransactionArchive,
+            Bucket::StorageSnapshots,
+            Bucket::CallOutputs,
+        ] {
             let bucket_path = format!("{base_dir}/{bucket}");
             fs::create_dir_all(&bucket_path)
                 .await
@@ -10,6 +10,6 @@ pub mod _reexports {
 }
 
 pub use self::{
-    objects::{FriCircuitKey, StoredObject},
+    objects::{CallOutput, FriCircuitKey, StoredObject},
     raw::{Bucket, ObjectStore, ObjectStoreError, ObjectStoreFactory},
 };
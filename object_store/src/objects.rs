@@ -1,6 +1,15 @@
+use std::io::Read;
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 use ola_types::{
+    archive::TransactionArchiveSegment,
     proofs::{AggregationRound, L1BatchProofForL1, PrepareBasicCircuitsJob},
-    L1BatchNumber,
+    snapshots::{
+        SnapshotFactoryDependencies, SnapshotHeader, SnapshotStorageLogsChunk,
+        SnapshotStorageLogsStorageKey,
+    },
+    witness_block_state::WitnessBlockState,
+    L1BatchNumber, H256,
 };
 
 use crate::{
@@ -99,6 +108,17 @@ impl StoredObject for PrepareBasicCircuitsJob {
     serialize_using_bincode!();
 }
 
+impl StoredObject for WitnessBlockState {
+    const BUCKET: Bucket = Bucket::WitnessInput;
+    type Key<'a> = L1BatchNumber;
+
+    fn encode_key(key: Self::Key<'_>) -> String {
+        format!("witness_block_state_for_l1_batch_{key}.bin")
+    }
+
+    serialize_using_bincode!();
+}
+
 /// Storage key for a [`CircuitWrapper`].
 #[derive(Debug, Clone, Copy)]
 pub struct FriCircuitKey {
@@ -119,3 +139,83 @@ impl StoredObject for L1BatchProofForL1 {
 
     serialize_using_bincode!();
 }
+
+impl StoredObject for SnapshotHeader {
+    const BUCKET: Bucket = Bucket::StorageSnapshots;
+    type Key<'a> = L1BatchNumber;
+
+    fn encode_key(key: Self::Key<'_>) -> String {
+        format!("snapshot_l1_batch_{key}_header.bin")
+    }
+
+    serialize_using_bincode!();
+}
+
+impl StoredObject for SnapshotStorageLogsChunk {
+    const BUCKET: Bucket = Bucket::StorageSnapshots;
+    type Key<'a> = SnapshotStorageLogsStorageKey;
+
+    fn encode_key(key: Self::Key<'_>) -> String {
+        format!(
+            "snapshot_l1_batch_{}_storage_logs_part_{:0>4}.bin",
+            key.l1_batch_number, key.chunk_id
+        )
+    }
+
+    serialize_using_bincode!();
+}
+
+impl StoredObject for SnapshotFactoryDependencies {
+    const BUCKET: Bucket = Bucket::StorageSnapshots;
+    type Key<'a> = L1BatchNumber;
+
+    fn encode_key(key: Self::Key<'_>) -> String {
+        format!("snapshot_l1_batch_{key}_factory_deps.bin")
+    }
+
+    serialize_using_bincode!();
+}
+
+/// Full output of a call truncated in an API response, keyed by the hash of the output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallOutput(pub Vec<u8>);
+
+impl StoredObject for CallOutput {
+    const BUCKET: Bucket = Bucket::CallOutputs;
+    type Key<'a> = H256;
+
+    fn encode_key(key: Self::Key<'_>) -> String {
+        format!("call_output_{key:x}.bin")
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>, BoxedError> {
+        Ok(self.0.clone())
+    }
+
+    fn deserialize(bytes: Vec<u8>) -> Result<Self, BoxedError> {
+        Ok(Self(bytes))
+    }
+}
+
+/// Archive segments are stored as gzipped JSON, since API types rely on serde attributes
+/// that `bincode` doesn't support.
+impl StoredObject for TransactionArchiveSegment {
+    const BUCKET: Bucket = Bucket::TransactionArchive;
+    type Key<'a> = L1BatchNumber;
+
+    fn encode_key(key: Self::Key<'_>) -> String {
+        format!("transactions_{key}.json.gz")
+    }
+
+    fn serialize(&self) -> Result<Vec<u8>, BoxedError> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        serde_json::to_writer(&mut encoder, self)?;
+        Ok(encoder.finish()?)
+    }
+
+    fn deserialize(bytes: Vec<u8>) -> Result<Self, BoxedError> {
+        let mut json = Vec::new();
+        GzDecoder::new(bytes.as_slice()).read_to_end(&mut json)?;
+        Ok(serde_json::from_slice(&json)?)
+    }
+}
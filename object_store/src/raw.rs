@@ -10,6 +10,9 @@ pub enum Bucket {
     WitnessInput,
     ProofsFri,
     ProverJobsFri,
+    TransactionArchive,
+    StorageSnapshots,
+    CallOutputs,
 }
 
 impl Bucket {
@@ -18,6 +21,9 @@ impl Bucket {
             Self::WitnessInput => "witness_inputs",
             Self::ProofsFri => "proofs_fri",
             Self::ProverJobsFri => "prover_jobs_fri",
+            Self::TransactionArchive => "transaction_archive",
+            Self::StorageSnapshots => "storage_logs_snapshots",
+            Self::CallOutputs => "call_outputs",
         }
     }
 }
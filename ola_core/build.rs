@@ -0,0 +1,39 @@
+//! Embeds build information reported by `ola_nodeInfo`.
+
+use std::{env, process::Command};
+
+fn main() {
+    // Builds from a source tree without `.git` (e.g., in Docker) can provide the commit explicitly.
+    println!("cargo:rerun-if-env-changed=OLAOS_GIT_COMMIT");
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/refs");
+    let git_commit = env::var("OLAOS_GIT_COMMIT").ok().or_else(|| {
+        let output = Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    });
+    println!(
+        "cargo:rustc-env=OLAOS_GIT_COMMIT={}",
+        git_commit.as_deref().unwrap_or("unknown")
+    );
+
+    let profile = env::var("PROFILE").unwrap_or_else(|_| "unknown".to_owned());
+    println!("cargo:rustc-env=OLAOS_BUILD_PROFILE={profile}");
+
+    let mut features: Vec<_> = env::vars()
+        .filter_map(|(name, _)| {
+            let feature = name.strip_prefix("CARGO_FEATURE_")?;
+            Some(feature.to_lowercase().replace('_', "-"))
+        })
+        .collect();
+    features.sort();
+    println!(
+        "cargo:rustc-env=OLAOS_BUILD_FEATURES={}",
+        features.join(",")
+    );
+}
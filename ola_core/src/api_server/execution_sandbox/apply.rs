@@ -1,14 +1,26 @@
-use ola_config::{database::load_db_config, sequencer::load_network_config};
-use ola_dal::{connection::ConnectionPool, SqlxError, StorageProcessor};
-use ola_executor::{tx_exe_manager::OlaTapeInitInfo, tx_pre_executor::TxPreExecutor};
+use ola_config::{
+    constants::contracts::ENTRYPOINT_ADDRESS, database::load_db_config,
+    sequencer::load_network_config,
+};
+use ola_dal::{connection::ConnectionPool, DalResult, StorageProcessor};
+use ola_executor::{
+    batch_exe_manager::BlockExeInfo,
+    config::ExecuteMode,
+    ola_storage::OlaCachedStorage,
+    tx_exe_manager::{OlaTapeInitInfo, TxExeManager},
+    tx_pre_executor::TxPreExecutor,
+};
 use ola_types::{
     api::{BlockId, BlockNumber},
-    ExecuteTransactionCommon, L1BatchNumber, MiniblockNumber, Transaction,
+    log::LogQuery,
+    tx::execution_digest::ExecutionDigest,
+    ExecuteTransactionCommon, L1BatchNumber, MiniblockNumber, StorageLog, Transaction,
 };
 use ola_utils::{bytes_to_u64s, h256_to_u64_array, time::seconds_since_epoch};
+use ola_vm::vm::{cycles_in_trace, VmPartialExecutionResult};
 use olavm_core::util::converts::u8_arr_to_address;
 
-use super::{BlockArgs, TxSharedArgs, VmPermit};
+use super::{vm_metrics::StorageAccessStats, BlockArgs, TxSharedArgs, VmPermit};
 
 #[allow(clippy::too_many_arguments)]
 pub(super) fn apply_vm_in_sandbox(
@@ -18,26 +30,130 @@ pub(super) fn apply_vm_in_sandbox(
     tx: Transaction,
     block_args: BlockArgs,
 ) -> anyhow::Result<()> {
-    let rt_handle = vm_permit.rt_handle();
-    let mut connection = rt_handle.block_on(connection_pool.access_storage_tagged("api"));
+    let block = SandboxBlock::resolve(&vm_permit, &shared_args, connection_pool, &block_args);
+    let mut pre_executor = TxPreExecutor::new(
+        block.db_path,
+        block.chain_id,
+        block.number,
+        block.timestamp,
+        u8_arr_to_address(&shared_args.operator_account.address().to_fixed_bytes()),
+    )?;
 
-    let (state_block_number, vm_block_number) = rt_handle
-        .block_on(block_args.resolve_block_numbers(&mut connection))
-        .expect("Failed resolving block numbers");
+    let result = pre_executor.invoke(tape_init_info(&tx));
+    drop(vm_permit);
+    result
+}
 
-    if block_args.resolves_to_latest_sealed_miniblock() {
-        shared_args
-            .caches
-            .schedule_values_update(state_block_number);
-    }
-    let block_timestamp = block_args.block_timestamp_seconds();
+/// Executes the transaction in the sandbox in the same way as the sequencer does, and returns
+/// the digest of its storage writes and events.
+///
+/// The transaction is executed on a single-use call storage, i.e. the API's own view of the state,
+/// so that its writes stay in the storage cache and never reach the state of the sequencer.
+pub(super) fn simulate_vm_in_sandbox(
+    vm_permit: VmPermit,
+    shared_args: TxSharedArgs,
+    connection_pool: &ConnectionPool,
+    tx: Transaction,
+    block_args: BlockArgs,
+) -> anyhow::Result<ExecutionDigest> {
+    let block = SandboxBlock::resolve(&vm_permit, &shared_args, connection_pool, &block_args);
+    let pooled_storage = shared_args
+        .call_storage_pool
+        .as_deref()
+        .and_then(|pool| pool.checkout(L1BatchNumber(block.number as u32)));
+    let (mut storage, timestamp) = match pooled_storage {
+        Some(pooled) => (pooled.storage, pooled.timestamp),
+        None => {
+            let storage = OlaCachedStorage::new(block.db_path, Some(block.timestamp))
+                .map_err(|err| anyhow::anyhow!("failed opening call storage: {err}"))?;
+            (storage, block.timestamp)
+        }
+    };
+    let block_info = BlockExeInfo {
+        block_number: block.number,
+        block_timestamp: timestamp,
+        sequencer_address: u8_arr_to_address(
+            &shared_args.operator_account.address().to_fixed_bytes(),
+        ),
+        chain_id: block.chain_id,
+    };
+    let mut tx_exe_manager = TxExeManager::new(
+        ExecuteMode::Invoke,
+        block_info,
+        tape_init_info(&tx),
+        &mut storage,
+        u8_arr_to_address(&ENTRYPOINT_ADDRESS.to_fixed_bytes()),
+        0,
+    );
+    let result = tx_exe_manager.invoke();
+    drop(tx_exe_manager);
+    drop(storage);
+    let result = result?;
+    let storage_accesses =
+        StorageAccessStats::new(result.storage_access_logs.iter().map(LogQuery::from), []);
+    vm_permit.record_storage_accesses(storage_accesses);
+    drop(vm_permit);
+    let cycles_used = cycles_in_trace(&result.trace);
+    shared_args.execution_budget.check_cycles(cycles_used)?;
+    let result = VmPartialExecutionResult::from_storage_events(
+        &result.storage_access_logs,
+        &result.events,
+        0,
+        cycles_used,
+    );
+    let writes: Vec<_> = result
+        .logs
+        .storage_logs
+        .iter()
+        .filter(|log| log.log_query.rw_flag)
+        .map(StorageLog::from_log_query)
+        .collect();
+    Ok(ExecutionDigest::new(&writes, &result.logs.events))
+}
 
-    let db_config = load_db_config().expect("failed to load database config");
-    let network = load_network_config().expect("failed to load network config");
+/// Block a sandboxed transaction is executed in.
+struct SandboxBlock {
+    db_path: String,
+    chain_id: u64,
+    number: u64,
+    timestamp: u64,
+}
+
+impl SandboxBlock {
+    fn resolve(
+        vm_permit: &VmPermit,
+        shared_args: &TxSharedArgs,
+        connection_pool: &ConnectionPool,
+        block_args: &BlockArgs,
+    ) -> Self {
+        let rt_handle = vm_permit.rt_handle();
+        let mut connection = rt_handle.block_on(connection_pool.access_storage_tagged("api"));
+
+        let (state_block_number, vm_block_number) = rt_handle
+            .block_on(block_args.resolve_block_numbers(&mut connection))
+            .expect("Failed resolving block numbers");
+
+        if block_args.resolves_to_latest_sealed_miniblock() {
+            shared_args
+                .caches
+                .schedule_values_update(state_block_number);
+        }
+
+        let db_config = load_db_config().expect("failed to load database config");
+        let network = load_network_config().expect("failed to load network config");
+        Self {
+            db_path: db_config.sequencer_db_path,
+            chain_id: network.ola_network_id as u64,
+            number: vm_block_number.0 as u64,
+            timestamp: block_args.block_timestamp_seconds(),
+        }
+    }
+}
 
+fn tape_init_info(tx: &Transaction) -> OlaTapeInitInfo {
     let hash = tx.hash();
-    let calldata = tx.execute.calldata;
-    let tx = match &tx.common_data {
+    let calldata = tx.execute.calldata.clone();
+    match &tx.common_data {
         ExecuteTransactionCommon::L2(tx) => {
             let to_u8_32 = |v: &Vec<u8>| {
                 let mut array = [0; 32];
@@ -70,18 +186,7 @@ pub(super) fn apply_vm_in_sandbox(
             }
         }
         ExecuteTransactionCommon::ProtocolUpgrade(_) => panic!("ProtocolUpgrade not supported"),
-    };
-
-    let mut pre_executor = TxPreExecutor::new(
-        db_config.sequencer_db_path,
-        network.ola_network_id as u64,
-        vm_block_number.0 as u64,
-        block_timestamp,
-        u8_arr_to_address(&shared_args.operator_account.address().to_fixed_bytes()),
-    )?;
-
-    drop(vm_permit);
-    pre_executor.invoke(tx)
+    }
 }
 
 impl BlockArgs {
@@ -99,11 +204,14 @@ impl BlockArgs {
     async fn resolve_block_numbers(
         &self,
         connection: &mut StorageProcessor<'_>,
-    ) -> Result<(MiniblockNumber, L1BatchNumber), SqlxError> {
+    ) -> DalResult<(MiniblockNumber, L1BatchNumber)> {
         Ok(if self.is_pending_miniblock() {
-            let sealed_l1_batch_number = connection.blocks_dal().get_sealed_l1_batch_number().await;
-            let sealed_miniblock_number =
-                connection.blocks_dal().get_sealed_miniblock_number().await;
+            let sealed_l1_batch_number =
+                connection.blocks_dal().get_sealed_l1_batch_number().await?;
+            let sealed_miniblock_number = connection
+                .blocks_dal()
+                .get_sealed_miniblock_number()
+                .await?;
             (sealed_miniblock_number, sealed_l1_batch_number + 1)
         } else {
             let l1_batch_number = connection
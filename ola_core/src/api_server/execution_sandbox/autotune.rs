@@ -0,0 +1,408 @@
+//! Autotuning of the VM concurrency limit.
+//!
+//! The autotuner periodically observes the p95 time VM invocations hold their permit, the share
+//! of their storage reads missing the call storage cache (i.e., served by the state database)
+//! and the number of invocations queued for a permit. It lowers the limit if invocations get slow
+//! or miss the cache too often (i.e., concurrent VMs start thrashing the state database), and
+//! raises it if invocations queue up while executing fast enough.
+//!
+//! Lowering the limit never waits for permits: permits that are available are forgotten
+//! immediately, and the rest are forgotten by running invocations once they finish. Thus,
+//! the autotuner never queues for permits in front of VM invocations.
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use ola_types::api::{VmConcurrencyAdjustment, VmConcurrencyAdjustmentReason, VmConcurrencyStatus};
+use ola_utils::time::seconds_since_epoch;
+use tokio::sync::{watch, Semaphore};
+
+use super::vm_metrics::StorageAccessStats;
+
+/// Maximum number of execution times collected between adjustments.
+const MAX_EXECUTION_SAMPLES: usize = 10_000;
+/// Number of adjustments kept for `ola_getVmConcurrency`.
+const HISTORY_LEN: usize = 64;
+
+/// Configuration of the VM concurrency autotuner.
+#[derive(Debug, Clone)]
+pub struct VmConcurrencyAutotuneConfig {
+    pub min_limit: usize,
+    pub max_limit: usize,
+    pub target_p95: Duration,
+    pub max_cache_miss_ratio: f64,
+    pub interval: Duration,
+}
+
+impl VmConcurrencyAutotuneConfig {
+    /// Returns the limit for the next interval, or `None` if it should stay the same.
+    fn next_limit(
+        &self,
+        current: usize,
+        observation: &Observation,
+    ) -> Option<(usize, VmConcurrencyAdjustmentReason)> {
+        let is_slow = observation.p95.is_some_and(|p95| p95 > self.target_p95);
+        let misses_cache = observation
+            .cache_miss_ratio
+            .is_some_and(|ratio| ratio > self.max_cache_miss_ratio);
+
+        let (next, reason) = if is_slow || misses_cache {
+            let reason = if is_slow {
+                VmConcurrencyAdjustmentReason::SlowExecution
+            } else {
+                VmConcurrencyAdjustmentReason::CacheMisses
+            };
+            let decrease = (current / 4).max(1);
+            (current.saturating_sub(decrease).max(self.min_limit), reason)
+        } else if observation.peak_queue_depth > 0 {
+            let increase = (current / 10).max(1);
+            let next = (current + increase).min(self.max_limit);
+            (next, VmConcurrencyAdjustmentReason::QueueBacklog)
+        } else {
+            return None;
+        };
+        (next != current).then_some((next, reason))
+    }
+}
+
+/// Signals observed by the autotuner over an interval.
+#[derive(Debug, Clone, Default)]
+struct Observation {
+    p95: Option<Duration>,
+    cache_miss_ratio: Option<f64>,
+    peak_queue_depth: usize,
+}
+
+/// State shared by the VM concurrency limiter, its barrier and the autotuner.
+#[derive(Debug)]
+pub(super) struct VmConcurrencyState {
+    /// Current effective limit. The semaphore may temporarily have more permits than that;
+    /// see `excess_permits`.
+    limit: AtomicUsize,
+    /// Number of permits that must be forgotten once released by running invocations.
+    excess_permits: AtomicUsize,
+    autotune: Option<VmConcurrencyAutotuneConfig>,
+    queue_depth: AtomicUsize,
+    peak_queue_depth: AtomicUsize,
+    execution_times: Mutex<Vec<Duration>>,
+    storage_reads: AtomicU64,
+    storage_misses: AtomicU64,
+    history: Mutex<VecDeque<VmConcurrencyAdjustment>>,
+}
+
+impl VmConcurrencyState {
+    pub(super) fn new(limit: usize, autotune: Option<VmConcurrencyAutotuneConfig>) -> Self {
+        metrics::gauge!("api.vm.concurrency_limit", limit as f64);
+        Self {
+            limit: AtomicUsize::new(limit),
+            excess_permits: AtomicUsize::new(0),
+            autotune,
+            queue_depth: AtomicUsize::new(0),
+            peak_queue_depth: AtomicUsize::new(0),
+            execution_times: Mutex::default(),
+            storage_reads: AtomicU64::new(0),
+            storage_misses: AtomicU64::new(0),
+            history: Mutex::default(),
+        }
+    }
+
+    pub(super) fn limit(&self) -> usize {
+        self.limit.load(Ordering::SeqCst)
+    }
+
+    /// Checks whether a released permit must be forgotten, since the limit was lowered while
+    /// it was held.
+    pub(super) fn take_excess_permit(&self) -> bool {
+        self.excess_permits
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |excess| {
+                excess.checked_sub(1)
+            })
+            .is_ok()
+    }
+
+    pub(super) fn enter_queue(&self) {
+        let depth = self.queue_depth.fetch_add(1, Ordering::Relaxed) + 1;
+        self.peak_queue_depth.fetch_max(depth, Ordering::Relaxed);
+    }
+
+    pub(super) fn leave_queue(&self) {
+        self.queue_depth.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub(super) fn record_execution(&self, elapsed: Duration) {
+        let mut execution_times = self.execution_times.lock().expect("poisoned");
+        if execution_times.len() < MAX_EXECUTION_SAMPLES {
+            execution_times.push(elapsed);
+        }
+    }
+
+    pub(super) fn record_storage_accesses(&self, stats: StorageAccessStats) {
+        self.storage_reads.fetch_add(stats.reads, Ordering::Relaxed);
+        self.storage_misses
+            .fetch_add(stats.misses, Ordering::Relaxed);
+    }
+
+    pub(super) fn status(&self) -> VmConcurrencyStatus {
+        let limit = self.limit();
+        let (min_limit, max_limit) = self.autotune.as_ref().map_or((limit, limit), |config| {
+            (config.min_limit, config.max_limit)
+        });
+        VmConcurrencyStatus {
+            limit,
+            min_limit,
+            max_limit,
+            autotune_enabled: self.autotune.is_some(),
+            queue_depth: self.queue_depth.load(Ordering::Relaxed),
+            history: self
+                .history
+                .lock()
+                .expect("poisoned")
+                .iter()
+                .cloned()
+                .collect(),
+        }
+    }
+
+    fn take_p95(&self) -> Option<Duration> {
+        let mut execution_times =
+            std::mem::take(&mut *self.execution_times.lock().expect("poisoned"));
+        if execution_times.is_empty() {
+            return None;
+        }
+        let index = (execution_times.len() * 95).div_ceil(100) - 1;
+        Some(*execution_times.select_nth_unstable(index).1)
+    }
+
+    /// Returns the share of storage reads that missed the cache since the previous call, or `None`
+    /// if there were no reads.
+    fn take_cache_miss_ratio(&self) -> Option<f64> {
+        let reads = self.storage_reads.swap(0, Ordering::Relaxed);
+        let misses = self.storage_misses.swap(0, Ordering::Relaxed);
+        (reads > 0).then(|| misses.min(reads) as f64 / reads as f64)
+    }
+}
+
+/// Task adjusting the VM concurrency limit; see the [module docs](self).
+#[derive(Debug)]
+pub struct VmConcurrencyAutotuner {
+    limiter: Arc<Semaphore>,
+    state: Arc<VmConcurrencyState>,
+    config: VmConcurrencyAutotuneConfig,
+}
+
+impl VmConcurrencyAutotuner {
+    pub(super) fn new(limiter: Arc<Semaphore>, state: Arc<VmConcurrencyState>) -> Option<Self> {
+        let config = state.autotune.clone()?;
+        Some(Self {
+            limiter,
+            state,
+            config,
+        })
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        loop {
+            if tokio::time::timeout(self.config.interval, stop_receiver.changed())
+                .await
+                .is_ok()
+            {
+                break;
+            }
+
+            let observation = Observation {
+                p95: self.state.take_p95(),
+                cache_miss_ratio: self.state.take_cache_miss_ratio(),
+                peak_queue_depth: self.state.peak_queue_depth.swap(
+                    self.state.queue_depth.load(Ordering::Relaxed),
+                    Ordering::Relaxed,
+                ),
+            };
+            metrics::gauge!(
+                "api.vm.concurrency_queue_depth",
+                observation.peak_queue_depth as f64
+            );
+
+            let current = self.state.limit();
+            if let Some((next, reason)) = self.config.next_limit(current, &observation) {
+                self.set_limit(current, next);
+                olaos_logs::info!(
+                    "Adjusted VM concurrency limit {current} -> {next} ({reason:?}): {observation:?}"
+                );
+                metrics::counter!("api.vm.concurrency_adjustments", 1, "reason" => reason.as_str());
+                self.record_adjustment(current, next, reason, &observation);
+            }
+        }
+        olaos_logs::info!("Stop signal received, VM concurrency autotuner is shutting down");
+        Ok(())
+    }
+
+    /// Changes the number of semaphore permits without waiting for running invocations.
+    fn set_limit(&self, current: usize, next: usize) {
+        if next > current {
+            // Cancel the pending decrease first, so that the semaphore doesn't overshoot the limit.
+            let mut increase = next - current;
+            let cancelled = self
+                .state
+                .excess_permits
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |excess| {
+                    Some(excess.saturating_sub(increase))
+                })
+                .unwrap();
+            increase -= cancelled.min(increase);
+            self.limiter.add_permits(increase);
+        } else {
+            let mut decrease = current - next;
+            while decrease > 0 {
+                let Ok(permit) = self.limiter.try_acquire() else {
+                    break;
+                };
+                permit.forget();
+                decrease -= 1;
+            }
+            // Remaining permits are held by running invocations.
+            self.state
+                .excess_permits
+                .fetch_add(decrease, Ordering::SeqCst);
+        }
+        self.state.limit.store(next, Ordering::SeqCst);
+        metrics::gauge!("api.vm.concurrency_limit", next as f64);
+    }
+
+    fn record_adjustment(
+        &self,
+        from: usize,
+        to: usize,
+        reason: VmConcurrencyAdjustmentReason,
+        observation: &Observation,
+    ) {
+        let mut history = self.state.history.lock().expect("poisoned");
+        if history.len() == HISTORY_LEN {
+            history.pop_front();
+        }
+        history.push_back(VmConcurrencyAdjustment {
+            timestamp: seconds_since_epoch(),
+            from,
+            to,
+            reason,
+            p95_execution_ms: observation.p95.map(|p95| p95.as_millis() as u64),
+            cache_miss_ratio: observation.cache_miss_ratio,
+            peak_queue_depth: observation.peak_queue_depth,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> VmConcurrencyAutotuneConfig {
+        VmConcurrencyAutotuneConfig {
+            min_limit: 8,
+            max_limit: 64,
+            target_p95: Duration::from_millis(100),
+            max_cache_miss_ratio: 0.5,
+            interval: Duration::from_secs(1),
+        }
+    }
+
+    #[test]
+    fn autotuning_vm_concurrency_limit() {
+        let config = config();
+        let fast_backlog = Observation {
+            p95: Some(Duration::from_millis(50)),
+            cache_miss_ratio: Some(0.1),
+            peak_queue_depth: 3,
+        };
+        assert_eq!(
+            config.next_limit(32, &fast_backlog),
+            Some((35, VmConcurrencyAdjustmentReason::QueueBacklog))
+        );
+        assert_eq!(config.next_limit(64, &fast_backlog), None);
+
+        let slow = Observation {
+            p95: Some(Duration::from_millis(150)),
+            ..fast_backlog.clone()
+        };
+        assert_eq!(
+            config.next_limit(32, &slow),
+            Some((24, VmConcurrencyAdjustmentReason::SlowExecution))
+        );
+        assert_eq!(
+            config.next_limit(9, &slow),
+            Some((8, VmConcurrencyAdjustmentReason::SlowExecution))
+        );
+        assert_eq!(config.next_limit(8, &slow), None);
+
+        let thrashing = Observation {
+            cache_miss_ratio: Some(0.8),
+            ..fast_backlog
+        };
+        assert_eq!(
+            config.next_limit(32, &thrashing),
+            Some((24, VmConcurrencyAdjustmentReason::CacheMisses))
+        );
+
+        assert_eq!(config.next_limit(32, &Observation::default()), None);
+    }
+
+    #[test]
+    fn lowering_limit_does_not_wait_for_running_invocations() {
+        let state = Arc::new(VmConcurrencyState::new(4, Some(config())));
+        let limiter = Arc::new(Semaphore::new(4));
+        let autotuner =
+            VmConcurrencyAutotuner::new(Arc::clone(&limiter), Arc::clone(&state)).unwrap();
+        let mut running: Vec<_> = (0..3).map(|_| limiter.try_acquire().unwrap()).collect();
+
+        autotuner.set_limit(4, 2);
+        assert_eq!(state.limit(), 2);
+        assert_eq!(limiter.available_permits(), 0);
+        // One of the running invocations must forget its permit once it finishes.
+        assert!(state.take_excess_permit());
+        assert!(!state.take_excess_permit());
+        running.pop().unwrap().forget();
+        drop(running);
+        assert_eq!(limiter.available_permits(), 2);
+
+        // Raising the limit cancels a pending decrease instead of adding permits.
+        let running = limiter.try_acquire_many(2).unwrap();
+        autotuner.set_limit(2, 1);
+        autotuner.set_limit(1, 3);
+        assert!(!state.take_excess_permit());
+        assert_eq!(limiter.available_permits(), 1);
+        drop(running);
+        assert_eq!(limiter.available_permits(), 3);
+    }
+
+    #[test]
+    fn cache_miss_ratio_of_storage_reads() {
+        let state = VmConcurrencyState::new(1, None);
+        assert_eq!(state.take_cache_miss_ratio(), None);
+        state.record_storage_accesses(StorageAccessStats {
+            reads: 6,
+            misses: 1,
+        });
+        state.record_storage_accesses(StorageAccessStats {
+            reads: 2,
+            misses: 1,
+        });
+        assert_eq!(state.take_cache_miss_ratio(), Some(0.25));
+        assert_eq!(state.take_cache_miss_ratio(), None);
+    }
+
+    #[test]
+    fn p95_of_execution_times() {
+        let state = VmConcurrencyState::new(1, None);
+        assert_eq!(state.take_p95(), None);
+        for millis in 1..=100 {
+            state.record_execution(Duration::from_millis(millis));
+        }
+        assert_eq!(state.take_p95(), Some(Duration::from_millis(95)));
+        assert_eq!(state.take_p95(), None);
+    }
+}
@@ -0,0 +1,198 @@
+//! Execution budgets of VM invocations in the API sandbox.
+//!
+//! Budgets bound the wall-clock time of an invocation and, for invocations returning an execution
+//! trace, the number of VM cycles. The VM doesn't expose a step limit, so the number of cycles
+//! is checked once the invocation completes. A VM invocation can't be interrupted either: on
+//! timeout, the request fails immediately, but the invocation keeps running on its blocking
+//! thread. Invocations hold their VM permit until they actually complete, so timed-out
+//! invocations still count towards the VM concurrency limit. Invocations run on the dedicated
+//! sandbox thread pool; the time spent waiting for a thread counts towards the budget.
+
+use std::{fmt, time::Duration};
+
+use ola_web3_decl::error::Web3Error;
+use thiserror::Error;
+
+use crate::utils::blocking_pool::{BlockingPool, PoolSaturated};
+
+/// Kind of a VM invocation; each kind has its own budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionKind {
+    /// `eth_call` / `ola_callTransaction`.
+    Call,
+    /// Validation of a submitted transaction.
+    Validation,
+}
+
+impl ExecutionKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Call => "call",
+            Self::Validation => "validation",
+        }
+    }
+}
+
+impl fmt::Display for ExecutionKind {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+/// Exhausted limit of an execution budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetLimit {
+    Time(Duration),
+    Cycles(u32),
+}
+
+impl fmt::Display for BudgetLimit {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Time(limit) => write!(formatter, "{}ms", limit.as_millis()),
+            Self::Cycles(limit) => write!(formatter, "{limit} cycles"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("execution ran out of the {kind} budget of {limit}")]
+pub struct OutOfBudget {
+    pub kind: ExecutionKind,
+    pub limit: BudgetLimit,
+}
+
+impl From<OutOfBudget> for Web3Error {
+    fn from(err: OutOfBudget) -> Self {
+        match err.limit {
+            BudgetLimit::Time(limit) => {
+                Self::ExecutionBudgetExceeded(err.kind.as_str(), limit.as_millis() as u64)
+            }
+            BudgetLimit::Cycles(limit) => {
+                Self::ExecutionCyclesExceeded(err.kind.as_str(), limit.into())
+            }
+        }
+    }
+}
+
+/// Error of a VM invocation run by [`ExecutionBudget::run()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum InvocationError {
+    #[error(transparent)]
+    OutOfBudget(#[from] OutOfBudget),
+    #[error(transparent)]
+    Rejected(#[from] PoolSaturated),
+}
+
+impl From<InvocationError> for Web3Error {
+    fn from(err: InvocationError) -> Self {
+        match err {
+            InvocationError::OutOfBudget(err) => err.into(),
+            InvocationError::Rejected(err) => err.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExecutionBudget {
+    kind: ExecutionKind,
+    limit: Duration,
+    max_cycles: Option<u32>,
+}
+
+impl ExecutionBudget {
+    pub fn new(kind: ExecutionKind, limit: Duration) -> Self {
+        Self {
+            kind,
+            limit,
+            max_cycles: None,
+        }
+    }
+
+    pub fn with_max_cycles(mut self, max_cycles: u32) -> Self {
+        self.max_cycles = Some(max_cycles);
+        self
+    }
+
+    /// Checks the number of VM cycles taken by a completed invocation against the budget.
+    pub fn check_cycles(&self, cycles: u32) -> Result<(), OutOfBudget> {
+        match self.max_cycles {
+            Some(max_cycles) if cycles > max_cycles => {
+                self.report_out_of_budget();
+                Err(OutOfBudget {
+                    kind: self.kind,
+                    limit: BudgetLimit::Cycles(max_cycles),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn report_out_of_budget(&self) {
+        metrics::increment_counter!(
+            "api.execution.out_of_budget",
+            "kind" => self.kind.as_str()
+        );
+    }
+
+    /// Runs a blocking VM `invocation` on a thread of `pool`, failing if the pool is saturated
+    /// or the invocation doesn't complete within the budget. The VM permit should be moved
+    /// into `invocation`.
+    pub async fn run<T: Send + 'static>(
+        self,
+        pool: &BlockingPool,
+        invocation: impl FnOnce() -> T + Send + 'static,
+    ) -> Result<T, InvocationError> {
+        match tokio::time::timeout(self.limit, pool.run(invocation)).await {
+            Ok(output) => Ok(output?),
+            Err(_) => {
+                self.report_out_of_budget();
+                Err(OutOfBudget {
+                    kind: self.kind,
+                    limit: BudgetLimit::Time(self.limit),
+                }
+                .into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn invocations_are_limited_by_budget() {
+        let pool = BlockingPool::new("sandbox", 2, 4).unwrap();
+        let budget = ExecutionBudget::new(ExecutionKind::Call, Duration::from_millis(50));
+        assert_eq!(budget.run(&pool, || 42).await, Ok(42));
+
+        let err = budget
+            .run(&pool, || std::thread::sleep(Duration::from_millis(500)))
+            .await
+            .unwrap_err();
+        let InvocationError::OutOfBudget(err) = err else {
+            panic!("unexpected error: {err:?}");
+        };
+        assert_eq!(err.kind, ExecutionKind::Call);
+        assert_eq!(
+            err.to_string(),
+            "execution ran out of the call budget of 50ms"
+        );
+    }
+
+    #[test]
+    fn cycles_are_limited_by_budget() {
+        let budget = ExecutionBudget::new(ExecutionKind::Validation, Duration::from_secs(1));
+        budget.check_cycles(u32::MAX).unwrap();
+
+        let budget = budget.with_max_cycles(1_000);
+        budget.check_cycles(1_000).unwrap();
+        let err = budget.check_cycles(1_001).unwrap_err();
+        assert_eq!(err.limit, BudgetLimit::Cycles(1_000));
+        assert_eq!(
+            err.to_string(),
+            "execution ran out of the validation budget of 1000 cycles"
+        );
+    }
+}
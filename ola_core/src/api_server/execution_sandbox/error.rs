@@ -1,6 +1,8 @@
 use ola_vm::errors::TxRevertReason;
 use thiserror::Error;
 
+use super::budget::InvocationError;
+
 #[derive(Debug, Error)]
 pub(crate) enum SandboxExecutionError {
     #[error("Account validation failed: {0}")]
@@ -28,6 +30,8 @@ pub(crate) enum SandboxExecutionError {
     UnexpectedVMBehavior(String),
     #[error("Transaction is unexecutable. Reason: {0}")]
     Unexecutable(String),
+    #[error(transparent)]
+    Invocation(#[from] InvocationError),
 }
 
 impl From<TxRevertReason> for SandboxExecutionError {
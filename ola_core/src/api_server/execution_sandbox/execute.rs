@@ -1,10 +1,20 @@
+use std::sync::Arc;
+
 use ola_dal::connection::ConnectionPool;
-use ola_types::{l2::L2Tx, Transaction};
+use ola_types::{l2::L2Tx, tx::execution_digest::ExecutionDigest, Transaction};
 use ola_types::{Nonce, U256};
 use ola_vm::errors::{TxRevertReason, VmRevertReason};
 use ola_vm::vm_with_bootloader::TxExecutionMode;
 
-use super::{apply, BlockArgs};
+use crate::api_server::web3::backend::correlation_id::{
+    current_correlation_id, with_correlation_id,
+};
+
+use super::{
+    apply,
+    budget::{InvocationError, OutOfBudget},
+    BlockArgs,
+};
 use super::{error::SandboxExecutionError, TxSharedArgs, VmPermit};
 
 #[derive(Debug)]
@@ -35,27 +45,72 @@ pub(crate) async fn execute_tx_with_pending_state(
     let block_args = BlockArgs::pending(&mut connection).await;
     drop(connection);
 
-    execute_tx_in_sandbox(vm_permit, shared_args, connection_pool, tx, block_args).await
+    execute_tx_in_sandbox(
+        vm_permit,
+        shared_args,
+        connection_pool,
+        tx,
+        block_args,
+        apply::apply_vm_in_sandbox,
+    )
+    .await
+}
+
+/// Executes the transaction on top of the pending state and returns the digest of its effects.
+#[olaos_logs::instrument(skip_all)]
+pub(crate) async fn simulate_tx_with_pending_state(
+    vm_permit: VmPermit,
+    shared_args: TxSharedArgs,
+    connection_pool: ConnectionPool,
+    tx: Transaction,
+) -> Result<ExecutionDigest, SandboxExecutionError> {
+    let mut connection = connection_pool.access_storage_tagged("api").await;
+    let block_args = BlockArgs::pending(&mut connection).await;
+    drop(connection);
+
+    execute_tx_in_sandbox(
+        vm_permit,
+        shared_args,
+        connection_pool,
+        tx,
+        block_args,
+        apply::simulate_vm_in_sandbox,
+    )
+    .await
 }
 
+type SandboxApplyFn<T> =
+    fn(VmPermit, TxSharedArgs, &ConnectionPool, Transaction, BlockArgs) -> anyhow::Result<T>;
+
 #[allow(clippy::too_many_arguments)]
 #[olaos_logs::instrument(skip_all)]
-async fn execute_tx_in_sandbox(
+async fn execute_tx_in_sandbox<T: Send + 'static>(
     vm_permit: VmPermit,
     shared_args: TxSharedArgs,
     connection_pool: ConnectionPool,
     tx: Transaction,
     block_args: BlockArgs,
-) -> Result<(), SandboxExecutionError> {
-    let execution_result = tokio::task::spawn_blocking(move || {
-        let result =
-            apply::apply_vm_in_sandbox(vm_permit, shared_args, &connection_pool, tx, block_args);
-        result
-    })
-    .await
-    .unwrap();
+    apply: SandboxApplyFn<T>,
+) -> Result<T, SandboxExecutionError> {
+    // Blocking tasks don't inherit the request span or task-locals, so carry them over explicitly
+    // for VM and DAL logs to stay correlated with the originating API request.
+    let span = tracing::Span::current();
+    let correlation_id = current_correlation_id();
+    let execution_budget = shared_args.execution_budget;
+    let sandbox_pool = Arc::clone(&shared_args.sandbox_pool);
+    let execution_result = execution_budget
+        .run(&sandbox_pool, move || {
+            let _entered = span.enter();
+            with_correlation_id(correlation_id, || {
+                apply(vm_permit, shared_args, &connection_pool, tx, block_args)
+            })
+        })
+        .await?;
 
     execution_result.map_err(|e| {
+        if let Some(err) = e.downcast_ref::<OutOfBudget>() {
+            return InvocationError::from(*err).into();
+        }
         let revert_reason = VmRevertReason::General {
             msg: e.to_string(),
             data: vec![],
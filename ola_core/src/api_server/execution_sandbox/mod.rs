@@ -4,17 +4,30 @@ use std::{
     time::{Duration, Instant},
 };
 
-use crate::web3::state::PruneQuery;
+use crate::{utils::blocking_pool::BlockingPool, web3::state::PruneQuery};
 use ola_contracts::BaseSystemContracts;
 use ola_dal::StorageProcessor;
 use ola_state::postgres::PostgresStorageCaches;
-use ola_types::{api, AccountTreeId, L1BatchNumber, MiniblockNumber};
+use ola_types::{
+    api::{self, VmConcurrencyStatus},
+    AccountTreeId, L1BatchNumber, MiniblockNumber,
+};
 use ola_web3_decl::error::Web3Error;
 use tokio::runtime::Handle;
 
+pub use self::autotune::{VmConcurrencyAutotuneConfig, VmConcurrencyAutotuner};
+use self::{
+    autotune::VmConcurrencyState, budget::ExecutionBudget, storage_pool::CallStoragePool,
+    vm_metrics::StorageAccessStats,
+};
+
 pub mod apply;
+mod autotune;
+pub mod budget;
 pub mod error;
 pub mod execute;
+pub(crate) mod overrides;
+pub(crate) mod storage_pool;
 pub mod validate;
 pub mod vm_metrics;
 
@@ -22,19 +35,60 @@ pub mod vm_metrics;
 pub struct VmPermit {
     /// A handle to the runtime that is used to query the VM storage.
     rt_handle: Handle,
-    _permit: Arc<tokio::sync::OwnedSemaphorePermit>,
+    permit: Arc<PermitGuard>,
+}
+
+/// Semaphore permit reporting how long it was held once released. If the concurrency limit was
+/// lowered while the permit was held, the permit is forgotten instead of being released.
+#[derive(Debug)]
+struct PermitGuard {
+    permit: Option<tokio::sync::OwnedSemaphorePermit>,
+    acquired_at: Instant,
+    state: Arc<VmConcurrencyState>,
+}
+
+impl Drop for PermitGuard {
+    fn drop(&mut self) {
+        self.state.record_execution(self.acquired_at.elapsed());
+        if self.state.take_excess_permit() {
+            if let Some(permit) = self.permit.take() {
+                permit.forget();
+            }
+        }
+    }
+}
+
+/// Tracks a VM invocation waiting for a permit, including if the waiting future is dropped.
+struct QueueGuard<'a>(&'a VmConcurrencyState);
+
+impl<'a> QueueGuard<'a> {
+    fn new(state: &'a VmConcurrencyState) -> Self {
+        state.enter_queue();
+        Self(state)
+    }
+}
+
+impl Drop for QueueGuard<'_> {
+    fn drop(&mut self) {
+        self.0.leave_queue();
+    }
 }
 
 impl VmPermit {
     fn rt_handle(&self) -> &Handle {
         &self.rt_handle
     }
+
+    /// Reports storage accesses of an invocation to the autotuner.
+    pub(crate) fn record_storage_accesses(&self, stats: StorageAccessStats) {
+        self.permit.state.record_storage_accesses(stats);
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct VmConcurrencyBarrier {
     limiter: Arc<tokio::sync::Semaphore>,
-    max_concurrency: usize,
+    state: Arc<VmConcurrencyState>,
 }
 
 impl VmConcurrencyBarrier {
@@ -52,7 +106,7 @@ impl VmConcurrencyBarrier {
 
         loop {
             let current_permits = self.limiter.available_permits();
-            if current_permits == self.max_concurrency {
+            if current_permits == self.state.limit() {
                 return;
             }
             tokio::time::sleep(POLL_INTERVAL).await;
@@ -64,27 +118,48 @@ impl VmConcurrencyBarrier {
 pub struct VmConcurrencyLimiter {
     limiter: Arc<tokio::sync::Semaphore>,
     rt_handle: Handle,
+    state: Arc<VmConcurrencyState>,
 }
 
 impl VmConcurrencyLimiter {
     pub fn new(max_concurrency: usize) -> (Self, VmConcurrencyBarrier) {
-        let limiter = Arc::new(tokio::sync::Semaphore::new(max_concurrency));
+        Self::with_state(VmConcurrencyState::new(max_concurrency, None))
+    }
+
+    /// Creates a limiter whose limit is adjusted by [`Self::autotuner()`]. The limit starts
+    /// at the upper bound.
+    pub fn autotuned(config: VmConcurrencyAutotuneConfig) -> (Self, VmConcurrencyBarrier) {
+        Self::with_state(VmConcurrencyState::new(config.max_limit, Some(config)))
+    }
+
+    fn with_state(state: VmConcurrencyState) -> (Self, VmConcurrencyBarrier) {
+        let limiter = Arc::new(tokio::sync::Semaphore::new(state.limit()));
+        let state = Arc::new(state);
         let this = Self {
             limiter: Arc::clone(&limiter),
             rt_handle: Handle::current(),
+            state: Arc::clone(&state),
         };
-        let barrier = VmConcurrencyBarrier {
-            limiter,
-            max_concurrency,
-        };
+        let barrier = VmConcurrencyBarrier { limiter, state };
         (this, barrier)
     }
 
+    /// Returns the task adjusting the limit, or `None` if the limiter isn't autotuned.
+    pub fn autotuner(&self) -> Option<VmConcurrencyAutotuner> {
+        VmConcurrencyAutotuner::new(Arc::clone(&self.limiter), Arc::clone(&self.state))
+    }
+
+    pub fn status(&self) -> VmConcurrencyStatus {
+        self.state.status()
+    }
+
     pub async fn acquire(&self) -> Option<VmPermit> {
         let available_permits = self.limiter.available_permits();
 
         let start = Instant::now();
+        let queue_guard = QueueGuard::new(&self.state);
         let permit = Arc::clone(&self.limiter).acquire_owned().await.ok()?;
+        drop(queue_guard);
         let elapsed = start.elapsed();
         // We don't want to emit too many logs.
         if elapsed > Duration::from_millis(10) {
@@ -94,7 +169,11 @@ impl VmConcurrencyLimiter {
         }
         Some(VmPermit {
             rt_handle: self.rt_handle.clone(),
-            _permit: Arc::new(permit),
+            permit: Arc::new(PermitGuard {
+                permit: Some(permit),
+                acquired_at: Instant::now(),
+                state: Arc::clone(&self.state),
+            }),
         })
     }
 }
@@ -104,6 +183,10 @@ pub(crate) struct TxSharedArgs {
     pub operator_account: AccountTreeId,
     pub base_system_contracts: BaseSystemContracts,
     pub caches: PostgresStorageCaches,
+    pub execution_budget: ExecutionBudget,
+    pub sandbox_pool: Arc<BlockingPool>,
+    /// Pool of call storages, which are also used to simulate transactions.
+    pub call_storage_pool: Option<Arc<CallStoragePool>>,
 }
 
 #[derive(Debug, Clone, Copy)]
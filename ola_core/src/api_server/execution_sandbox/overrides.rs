@@ -0,0 +1,167 @@
+//! Applying call state overrides to the call storage.
+//!
+//! The call executor reads state from `OlaCachedStorage` rather than a `ReadStorage`, so overrides
+//! can't be layered on top of it. Instead, the values of all overridden slots are resolved
+//! against Postgres and written to the storage cache before the call. Call storages are
+//! single-use (see [`storage_pool`](super::storage_pool)), so the writes never outlive the call.
+//! Calls on top of the `pending` block write the pending batch state the same way.
+
+use ola_dal::StorageProcessor;
+use ola_executor::ola_storage::OlaCachedStorage;
+use ola_state::overlay::StorageOverrides;
+use ola_types::{AccountTreeId, MiniblockNumber, StorageKey, StorageValue};
+use ola_utils::h256_to_u64_array;
+use olavm_core::util::converts::u8_arr_to_address;
+
+use crate::api_server::tx_sender::error::SubmitTxError;
+
+/// Maximum number of existing slots of an account which can be reset by a `state` override.
+const MAX_REPLACED_SLOTS: usize = 10_000;
+const REPLACED_SLOTS_PAGE_SIZE: usize = 1_000;
+
+/// Resolves slot writes applying `overrides` on top of the state as of `block_number`
+/// with `pending_writes` applied.
+pub(crate) async fn resolve_override_writes(
+    storage: &mut StorageProcessor<'_>,
+    overrides: &StorageOverrides,
+    block_number: MiniblockNumber,
+    pending_writes: &[(StorageKey, StorageValue)],
+) -> Result<Vec<(StorageKey, StorageValue)>, SubmitTxError> {
+    // The executor loads bytecodes from its own storage, so overridden code must already be known.
+    for hash in overrides.factory_dep_hashes() {
+        let bytecode = storage
+            .storage_dal()
+            .get_factory_dep(*hash)
+            .await
+            .map_err(|err| SubmitTxError::DatabaseUnavailable(err.to_string()))?;
+        if bytecode.is_none() {
+            return Err(SubmitTxError::InvalidStateOverride(format!(
+                "code override with bytecode hash {hash:?} must reference a deployed bytecode"
+            )));
+        }
+    }
+
+    let mut existing_slots = vec![];
+    for address in overrides.replaced_accounts() {
+        let account_id = AccountTreeId::new(*address);
+        let mut account_slots = 0;
+        let mut after = None;
+        loop {
+            let page = storage
+                .storage_web3_dal()
+                .get_account_storage_range(*address, block_number, after, REPLACED_SLOTS_PAGE_SIZE)
+                .await
+                .map_err(|err| SubmitTxError::DatabaseUnavailable(err.to_string()))?;
+            account_slots += page.len();
+            if account_slots > MAX_REPLACED_SLOTS {
+                return Err(SubmitTxError::InvalidStateOverride(format!(
+                    "`state` override of account {address:?} would reset more than \
+                     {MAX_REPLACED_SLOTS} slots; use `stateDiff` instead"
+                )));
+            }
+            after = page.last().map(|entry| entry.key);
+            let is_last_page = page.len() < REPLACED_SLOTS_PAGE_SIZE;
+            existing_slots.extend(
+                page.into_iter()
+                    .map(|entry| StorageKey::new(account_id, entry.key)),
+            );
+            if is_last_page {
+                break;
+            }
+        }
+    }
+    // Slots written in the pending batch must be reset by a `state` override as well.
+    existing_slots.extend(pending_writes.iter().map(|(key, _)| *key));
+    Ok(overrides.resolve_writes(existing_slots))
+}
+
+/// Writes resolved overrides or pending batch writes to the storage cache of a call storage.
+pub(crate) fn write_overrides(
+    storage: &mut OlaCachedStorage,
+    writes: &[(StorageKey, StorageValue)],
+) {
+    for (key, value) in writes {
+        storage.sstore(
+            u8_arr_to_address(&key.address().to_fixed_bytes()),
+            h256_to_u64_array(key.key()),
+            h256_to_u64_array(value),
+        );
+    }
+    // Move the writes from the transaction cache to the block cache, so that they are visible
+    // to the call as the pre-existing state.
+    storage.on_tx_success();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use ola_types::{
+        api::{OverrideAccount, StateOverride},
+        get_nonce_key, Address, H256, U256,
+    };
+
+    use super::*;
+
+    #[test]
+    fn resolving_storage_overrides() {
+        let patched = Address::repeat_byte(1);
+        let replaced = Address::repeat_byte(2);
+        let slot = |address, key: u64| {
+            StorageKey::new(AccountTreeId::new(address), H256::from_low_u64_be(key))
+        };
+        let state_override: StateOverride = HashMap::from([
+            (
+                patched,
+                OverrideAccount {
+                    nonce: Some(U256::from(5)),
+                    state_diff: Some(HashMap::from([(
+                        H256::from_low_u64_be(1),
+                        H256::repeat_byte(0xaa),
+                    )])),
+                    ..OverrideAccount::default()
+                },
+            ),
+            (
+                replaced,
+                OverrideAccount {
+                    state: Some(HashMap::from([(
+                        H256::from_low_u64_be(2),
+                        H256::repeat_byte(0xbb),
+                    )])),
+                    ..OverrideAccount::default()
+                },
+            ),
+        ]);
+        let overrides = StorageOverrides::new(&state_override).unwrap();
+
+        // Existing slots of accounts which are only patched must be left intact.
+        let existing_slots = [slot(replaced, 2), slot(replaced, 3), slot(patched, 4)];
+        let writes: HashMap<_, _> = overrides
+            .resolve_writes(existing_slots)
+            .into_iter()
+            .collect();
+        assert_eq!(
+            writes,
+            HashMap::from([
+                (get_nonce_key(&patched), H256::from_low_u64_be(5)),
+                (slot(patched, 1), H256::repeat_byte(0xaa)),
+                (slot(replaced, 2), H256::repeat_byte(0xbb)),
+                (slot(replaced, 3), H256::zero()),
+            ])
+        );
+    }
+
+    #[test]
+    fn balance_override_is_rejected() {
+        let state_override: StateOverride = HashMap::from([(
+            Address::repeat_byte(1),
+            OverrideAccount {
+                balance: Some(U256::from(1_000)),
+                ..OverrideAccount::default()
+            },
+        )]);
+        let err = StorageOverrides::new(&state_override).unwrap_err();
+        assert!(format!("{err:#}").contains("balance overrides"), "{err:#}");
+    }
+}
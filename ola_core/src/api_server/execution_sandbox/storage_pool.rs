@@ -0,0 +1,240 @@
+//! Pool of call storages opened in advance.
+//!
+//! Opening `OlaCachedStorage` (i.e., the sequencer RocksDB instance) dominates the setup cost of
+//! `eth_call`. The pool keeps several storages opened for the latest sealed miniblock, so that
+//! a call only has to check one out. Storages are single-use: a call may leave writes in
+//! the storage cache, so a checked-out storage is dropped after the call, and the pool is refilled
+//! in the background. Storages opened before the latest sealed miniblock are dropped once
+//! the miniblock is observed (immediately if the sequencer runs in the same process), and so are
+//! storages older than [`MAX_STORAGE_AGE`], since the block timestamp is fixed on opening.
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::Context as _;
+use ola_dal::{connection::ConnectionPool, DalResult};
+use ola_executor::ola_storage::OlaCachedStorage;
+use ola_types::{L1BatchNumber, MiniblockNumber};
+use ola_utils::time::seconds_since_epoch;
+use tokio::sync::{watch, Notify};
+
+/// Maximum age of a pooled storage; the block timestamp of calls is off by at most this much.
+const MAX_STORAGE_AGE: Duration = Duration::from_secs(10);
+/// Interval between polling Postgres for the latest sealed miniblock.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Block a pooled storage is opened for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct StorageKey {
+    sealed_miniblock: MiniblockNumber,
+    /// Pending L1 batch, i.e. the one calls are executed in.
+    l1_batch_number: L1BatchNumber,
+}
+
+/// Storage checked out of the [`CallStoragePool`].
+pub(crate) struct PooledStorage {
+    pub storage: OlaCachedStorage,
+    /// Block timestamp the storage is opened with.
+    pub timestamp: u64,
+    key: StorageKey,
+    opened_at: Instant,
+}
+
+impl fmt::Debug for PooledStorage {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("PooledStorage")
+            .field("timestamp", &self.timestamp)
+            .field("key", &self.key)
+            .field("opened_at", &self.opened_at)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PooledStorage {
+    fn is_usable(&self, latest: Option<StorageKey>, now: Instant) -> bool {
+        is_usable(self.key, self.opened_at, latest, now)
+    }
+}
+
+fn is_usable(
+    key: StorageKey,
+    opened_at: Instant,
+    latest: Option<StorageKey>,
+    now: Instant,
+) -> bool {
+    let is_fresh = now.saturating_duration_since(opened_at) < MAX_STORAGE_AGE;
+    is_fresh && latest.map_or(true, |latest| key == latest)
+}
+
+#[derive(Debug, Default)]
+struct PoolState {
+    /// Latest sealed miniblock observed by the refresher.
+    latest: Option<StorageKey>,
+    storages: VecDeque<PooledStorage>,
+}
+
+#[derive(Debug)]
+pub(crate) struct CallStoragePool {
+    db_path: String,
+    size: usize,
+    seal_events: Option<watch::Receiver<MiniblockNumber>>,
+    state: Mutex<PoolState>,
+    refill: Notify,
+}
+
+impl CallStoragePool {
+    pub(crate) fn new(
+        db_path: String,
+        size: usize,
+        seal_events: Option<watch::Receiver<MiniblockNumber>>,
+    ) -> Self {
+        Self {
+            db_path,
+            size,
+            seal_events,
+            state: Mutex::default(),
+            refill: Notify::new(),
+        }
+    }
+
+    /// Checks out a storage opened for `l1_batch_number`. Returns `None` if there are no usable
+    /// storages; the caller should open a storage itself in this case.
+    pub(crate) fn checkout(&self, l1_batch_number: L1BatchNumber) -> Option<PooledStorage> {
+        let now = Instant::now();
+        let mut state = self.state.lock().expect("poisoned");
+        let latest = state.latest;
+        state
+            .storages
+            .retain(|storage| storage.is_usable(latest, now));
+        let position = state
+            .storages
+            .iter()
+            .position(|storage| storage.key.l1_batch_number == l1_batch_number);
+        let storage = position.and_then(|position| state.storages.remove(position));
+        drop(state);
+
+        self.refill.notify_one();
+        let result = if storage.is_some() { "hit" } else { "miss" };
+        metrics::counter!("api.execution.call_storage_pool", 1, "result" => result);
+        storage
+    }
+
+    fn open(db_path: String, key: StorageKey) -> anyhow::Result<PooledStorage> {
+        let timestamp = seconds_since_epoch();
+        let storage = OlaCachedStorage::new(db_path, Some(timestamp))
+            .map_err(|err| anyhow::anyhow!("failed opening call storage: {err}"))?;
+        Ok(PooledStorage {
+            storage,
+            timestamp,
+            key,
+            opened_at: Instant::now(),
+        })
+    }
+
+    async fn latest_key(&self, connection_pool: &ConnectionPool) -> DalResult<StorageKey> {
+        let mut storage = connection_pool
+            .access_storage_tagged("call_storage_pool")
+            .await;
+        let mut blocks_dal = storage.blocks_dal();
+        Ok(StorageKey {
+            sealed_miniblock: blocks_dal.get_sealed_miniblock_number().await?,
+            l1_batch_number: blocks_dal.get_sealed_l1_batch_number().await? + 1,
+        })
+    }
+
+    /// Drops unusable storages and returns the number of storages to open.
+    fn update_latest(&self, latest: StorageKey) -> usize {
+        let now = Instant::now();
+        let mut state = self.state.lock().expect("poisoned");
+        state.latest = Some(latest);
+        state
+            .storages
+            .retain(|storage| storage.is_usable(Some(latest), now));
+        metrics::gauge!(
+            "api.execution.call_storage_pool_size",
+            state.storages.len() as f64
+        );
+        self.size.saturating_sub(state.storages.len())
+    }
+
+    /// Keeps the pool filled with storages for the latest sealed miniblock.
+    pub(crate) async fn run(
+        self: Arc<Self>,
+        connection_pool: ConnectionPool,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        let mut seal_events = self.seal_events.clone();
+        while !*stop_receiver.borrow() {
+            let latest = match self.latest_key(&connection_pool).await {
+                Ok(latest) => latest,
+                Err(err) => {
+                    olaos_logs::warn!("Failed loading the latest sealed miniblock: {err}");
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                    continue;
+                }
+            };
+            for _ in 0..self.update_latest(latest) {
+                let db_path = self.db_path.clone();
+                let storage = tokio::task::spawn_blocking(move || Self::open(db_path, latest))
+                    .await
+                    .context("opening call storage panicked")?;
+                match storage {
+                    Ok(storage) => self
+                        .state
+                        .lock()
+                        .expect("poisoned")
+                        .storages
+                        .push_back(storage),
+                    Err(err) => {
+                        olaos_logs::warn!("{err:#}");
+                        break;
+                    }
+                }
+            }
+
+            let sealed = async {
+                match &mut seal_events {
+                    Some(events) if events.changed().await.is_ok() => {}
+                    _ => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                _ = stop_receiver.changed() => break,
+                () = self.refill.notified() => {}
+                () = sealed => {}
+                () = tokio::time::sleep(POLL_INTERVAL) => {}
+            }
+        }
+        olaos_logs::info!("Stop signal received, call storage pool is shutting down");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checking_storage_usability() {
+        let key = StorageKey {
+            sealed_miniblock: MiniblockNumber(10),
+            l1_batch_number: L1BatchNumber(3),
+        };
+        let opened_at = Instant::now();
+        assert!(is_usable(key, opened_at, None, opened_at));
+        assert!(is_usable(key, opened_at, Some(key), opened_at));
+
+        let next_miniblock = StorageKey {
+            sealed_miniblock: MiniblockNumber(11),
+            ..key
+        };
+        assert!(!is_usable(key, opened_at, Some(next_miniblock), opened_at));
+        let stale_at = opened_at + MAX_STORAGE_AGE;
+        assert!(!is_usable(key, opened_at, Some(key), stale_at));
+    }
+}
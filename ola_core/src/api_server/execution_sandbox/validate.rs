@@ -1,4 +1,4 @@
-use std::{collections::HashSet, time::Instant};
+use std::{collections::HashSet, sync::Arc, time::Instant};
 
 use ola_dal::{connection::ConnectionPool, StorageProcessor};
 use ola_types::{l2::L2Tx, Address, Transaction, U256};
@@ -33,21 +33,32 @@ impl TxSharedArgs {
     ) -> Result<(), ValidationError> {
         let _stage_started_at = Instant::now();
         let mut connection = connection_pool.access_storage_tagged("api").await;
-        let _validation_params = get_validation_params(&mut connection, &tx).await;
+        let validation_params = get_validation_params(&mut connection, &tx).await;
         drop(connection);
+        // Any slot of a trusted address may be accessed during validation, so accounts at these
+        // addresses can't be validated in isolation.
+        if validation_params.is_trusted_address(&validation_params.user_address) {
+            return Err(format!(
+                "transactions can't be initiated by trusted address {:?}",
+                validation_params.user_address
+            ));
+        }
 
         let execution_args = TxExecutionArgs::for_validation(&tx);
         let _execution_mode = execution_args.execution_mode;
         let tx: Transaction = tx.into();
-        let validation_result = tokio::task::spawn_blocking(move || {
-            let span = tracing::debug_span!("validate_in_sandbox").entered();
-            let result =
-                apply::apply_vm_in_sandbox(vm_permit, self, &connection_pool, tx, block_args);
-            span.exit();
-            result
-        })
-        .await
-        .unwrap();
+        let execution_budget = self.execution_budget;
+        let sandbox_pool = Arc::clone(&self.sandbox_pool);
+        let validation_result = execution_budget
+            .run(&sandbox_pool, move || {
+                let span = tracing::debug_span!("validate_in_sandbox").entered();
+                let result =
+                    apply::apply_vm_in_sandbox(vm_permit, self, &connection_pool, tx, block_args);
+                span.exit();
+                result
+            })
+            .await
+            .map_err(|err| err.to_string())?;
 
         validation_result.map_err(|e| e.to_string())
     }
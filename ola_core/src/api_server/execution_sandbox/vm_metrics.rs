@@ -1,4 +1,7 @@
-use ola_types::fee::TransactionExecutionMetrics;
+use std::collections::HashSet;
+
+use ola_types::{fee::TransactionExecutionMetrics, log::LogQuery, AccountTreeId, StorageKey};
+use ola_utils::u256_to_h256;
 use ola_vm::vm::VmExecutionResult;
 
 pub(super) fn collect_tx_execution_metrics(
@@ -8,3 +11,86 @@ pub(super) fn collect_tx_execution_metrics(
     // TODO:
     TransactionExecutionMetrics::default()
 }
+
+/// Storage accesses of a VM invocation on a single-use call storage.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct StorageAccessStats {
+    pub reads: u64,
+    /// Reads which weren't served from the storage cache, i.e. first accesses to slots neither
+    /// written to the cache before the invocation nor written by the invocation itself.
+    pub misses: u64,
+}
+
+impl StorageAccessStats {
+    /// Counts accesses in the storage log of an invocation. `cached` are the slots written
+    /// to the cache before the invocation (e.g., state overrides).
+    pub(crate) fn new(
+        log: impl IntoIterator<Item = LogQuery>,
+        cached: impl IntoIterator<Item = StorageKey>,
+    ) -> Self {
+        let mut accessed: HashSet<_> = cached.into_iter().collect();
+        let mut stats = Self::default();
+        for query in log {
+            let key = StorageKey::new(AccountTreeId::new(query.address), u256_to_h256(query.key));
+            let is_first_access = accessed.insert(key);
+            if !query.rw_flag {
+                stats.reads += 1;
+                stats.misses += u64::from(is_first_access);
+            }
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ola_types::{log::Timestamp, Address, H256, U256};
+
+    use super::*;
+
+    #[test]
+    fn counting_storage_accesses() {
+        let address = Address::repeat_byte(1);
+        let query = |key: u64, rw_flag| LogQuery {
+            timestamp: Timestamp(0),
+            tx_number_in_block: 0,
+            aux_byte: 0,
+            shard_id: 0,
+            address,
+            key: U256::from(key),
+            read_value: U256::zero(),
+            written_value: U256::zero(),
+            rw_flag,
+            rollback: false,
+            is_service: false,
+        };
+        let log = [
+            query(1, false),
+            query(1, false),
+            query(2, true),
+            query(2, false),
+            query(3, false),
+        ];
+        let cached = [StorageKey::new(
+            AccountTreeId::new(address),
+            H256::from_low_u64_be(3),
+        )];
+
+        let stats = StorageAccessStats::new(log, cached);
+        assert_eq!(
+            stats,
+            StorageAccessStats {
+                reads: 4,
+                misses: 1
+            }
+        );
+        let stats = StorageAccessStats::new(log, []);
+        assert_eq!(
+            stats,
+            StorageAccessStats {
+                reads: 4,
+                misses: 2
+            }
+        );
+    }
+}
@@ -3,6 +3,8 @@ use ola_types::l2::error::TxCheckError;
 use ola_web3_decl::error::EnrichedClientError;
 use thiserror::Error;
 
+use crate::api_server::execution_sandbox::budget::InvocationError;
+
 #[derive(Debug, Error)]
 pub enum SubmitTxError {
     #[error("nonce too high. allowed nonce range: {0} - {1}, actual: {2}")]
@@ -21,6 +23,8 @@ pub enum SubmitTxError {
     RateLimitExceeded,
     #[error("server shutting down")]
     ServerShuttingDown,
+    #[error("failed to access the database: {0}")]
+    DatabaseUnavailable(String),
     #[error("failed to include transaction in the system. reason: {0}")]
     BootloaderFailure(String),
     #[error("failed to validate the transaction. reason: {0}")]
@@ -55,6 +59,20 @@ pub enum SubmitTxError {
     ProxyError(#[from] EnrichedClientError),
     #[error("tx call vm failed: {0}")]
     TxCallTxError(String),
+    #[error("invalid state override: {0}")]
+    InvalidStateOverride(String),
+    #[error("malformed calldata: length {0} is not a multiple of 8 bytes")]
+    MalformedCalldata(usize),
+    #[error("calldata is too large. max: {0} bytes, actual: {1} bytes")]
+    CalldataTooLarge(usize, usize),
+    #[error("calldata rejected by screening rules: {0}")]
+    CalldataRejected(String),
+    #[error("execute_after is too far in the future. max: {0}, actual: {1}")]
+    ExecuteAfterTooFar(u64, u64),
+    #[error("transaction submission is disabled on read-only nodes")]
+    ReadOnlyNode,
+    #[error("{0}")]
+    Invocation(#[from] InvocationError),
 }
 
 impl SubmitTxError {
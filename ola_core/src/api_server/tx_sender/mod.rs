@@ -1,4 +1,9 @@
-use std::{fmt::Debug, num::NonZeroU32, sync::Arc, time::Instant};
+use std::{
+    fmt::Debug,
+    num::NonZeroU32,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use governor::{
     clock::MonotonicClock,
@@ -13,26 +18,52 @@ use ola_config::{
     sequencer::{load_network_config, SequencerConfig},
 };
 use ola_contracts::BaseSystemContracts;
-use ola_dal::{connection::ConnectionPool, transactions_dal::L2TxSubmissionResult};
+use ola_dal::{
+    connection::ConnectionPool, facade::DalFacade, transactions_dal::L2TxSubmissionResult,
+};
 use ola_executor::{
     batch_exe_manager::BlockExeInfo,
     config::ExecuteMode,
     ola_storage::OlaCachedStorage,
     tx_exe_manager::{OlaTapeInitInfo, TxExeManager},
 };
-use ola_state::postgres::PostgresStorageCaches;
+use ola_state::{overlay::StorageOverrides, postgres::PostgresStorageCaches};
 use ola_types::{
-    fee::TransactionExecutionMetrics, l2::L2Tx, AccountTreeId, Address, Bytes, Nonce, H256,
+    api::{CallStats, StateOverride},
+    fee::TransactionExecutionMetrics,
+    l2::L2Tx,
+    log::LogQuery,
+    AccountTreeId, Address, Bytes, Nonce, StorageKey, StorageValue, H256,
+};
+use ola_utils::{
+    bytes_to_u64s,
+    time::{millis_since_epoch, seconds_since_epoch},
+    u64s_to_bytes,
 };
-use ola_utils::{bytes_to_u64s, time::millis_since_epoch, u64s_to_bytes};
+use ola_vm::vm::cycles_in_trace;
 use olavm_core::util::converts::u8_arr_to_address;
 
-use self::{error::SubmitTxError, proxy::TxProxy};
+use crate::{sequencer::types::MiniblockSealEvents, utils::blocking_pool::BlockingPool};
 
-use super::execution_sandbox::{TxSharedArgs, VmConcurrencyLimiter};
+use self::{
+    error::SubmitTxError, nonce_cache::NonceCache, proxy::TxProxy, screening::CalldataScreener,
+};
+
+use super::execution_sandbox::{
+    budget::{ExecutionBudget, ExecutionKind, InvocationError},
+    error::SandboxExecutionError,
+    execute::simulate_tx_with_pending_state,
+    overrides::{resolve_override_writes, write_overrides},
+    storage_pool::CallStoragePool,
+    vm_metrics::StorageAccessStats,
+    TxSharedArgs, VmConcurrencyAutotuner, VmConcurrencyLimiter,
+};
 
 pub mod error;
+mod nonce_cache;
 pub mod proxy;
+pub mod screening;
+pub mod simulations;
 
 pub struct ApiContracts {
     eth_call: BaseSystemContracts,
@@ -51,8 +82,19 @@ pub struct TxSenderConfig {
     pub fee_account_addr: Address,
     pub max_nonce_ahead: u32,
     pub vm_execution_cache_misses_limit: Option<usize>,
+    pub max_calldata_size: usize,
     pub default_aa: H256,
     pub entrypoint: H256,
+    pub call_execution_budget: Duration,
+    pub validation_execution_budget: Duration,
+    pub call_max_cycles: u32,
+    pub validation_max_cycles: u32,
+    pub nonce_cache_ttl: Duration,
+    pub nonce_cache_negative_ttl: Duration,
+    pub call_storage_pool_size: usize,
+    pub sandbox_threads: usize,
+    pub sandbox_queue_capacity: usize,
+    pub max_scheduled_tx_delay: Duration,
 }
 
 impl TxSenderConfig {
@@ -61,8 +103,19 @@ impl TxSenderConfig {
             fee_account_addr: sequencer_config.fee_account_addr,
             max_nonce_ahead: web3_json_config.max_nonce_ahead,
             vm_execution_cache_misses_limit: web3_json_config.vm_execution_cache_misses_limit,
+            max_calldata_size: web3_json_config.max_calldata_size(),
             default_aa: sequencer_config.default_aa_hash,
             entrypoint: sequencer_config.entrypoint_hash,
+            call_execution_budget: web3_json_config.call_execution_budget(),
+            validation_execution_budget: web3_json_config.validation_execution_budget(),
+            call_max_cycles: web3_json_config.call_max_cycles(),
+            validation_max_cycles: web3_json_config.validation_max_cycles(),
+            nonce_cache_ttl: web3_json_config.nonce_cache_ttl(),
+            nonce_cache_negative_ttl: web3_json_config.nonce_cache_negative_ttl(),
+            call_storage_pool_size: web3_json_config.call_storage_pool_size(),
+            sandbox_threads: web3_json_config.sandbox_threads(),
+            sandbox_queue_capacity: web3_json_config.sandbox_queue_capacity(),
+            max_scheduled_tx_delay: web3_json_config.max_scheduled_tx_delay(),
         }
     }
 }
@@ -84,6 +137,25 @@ impl Debug for TxSender {
 impl TxSender {
     #[olaos_logs::instrument(skip(self, tx))]
     pub async fn submit_tx(&self, tx: L2Tx) -> Result<L2TxSubmissionResult, SubmitTxError> {
+        self.submit_tx_inner(tx, false).await
+    }
+
+    /// Submits the transaction after simulating it on top of the pending state. The digest of
+    /// the simulated effects is recorded, so that it can be compared with the actual execution
+    /// once the transaction is sealed. Transactions failing the simulation are rejected.
+    #[olaos_logs::instrument(skip(self, tx))]
+    pub async fn submit_tx_with_simulation(
+        &self,
+        tx: L2Tx,
+    ) -> Result<L2TxSubmissionResult, SubmitTxError> {
+        self.submit_tx_inner(tx, true).await
+    }
+
+    async fn submit_tx_inner(
+        &self,
+        tx: L2Tx,
+        record_simulation: bool,
+    ) -> Result<L2TxSubmissionResult, SubmitTxError> {
         olaos_logs::info!("Start submit tx {:?}", tx.hash());
 
         if let Some(rate_limiter) = &self.0.rate_limiter {
@@ -104,20 +176,7 @@ impl TxSender {
 
         olaos_logs::info!("Acquired vm_permit");
 
-        // let res = execute_tx_with_pending_state(
-        //     vm_permit,
-        //     shared_args.clone(),
-        //     self.0.replica_connection_pool.clone(),
-        //     tx.clone().into(),
-        // )
-        // .await;
-
-        // if let Some(e) = res.err() {
-        //     return Err(SubmitTxError::PreExecutionReverted(e.to_string(), vec![]));
-        // }
-
         if let Some(proxy) = &self.0.proxy {
-            olaos_logs::error!("proxy not supported");
             // We're running an external node: we have to proxy the transaction to the main node.
             // But before we do that, save the tx to cache in case someone will request it
             // Before it reaches the main node.
@@ -128,16 +187,32 @@ impl TxSender {
             // from the mempool.
             proxy.forget_tx(tx.hash()).await;
             return Ok(L2TxSubmissionResult::Proxied);
-        } else {
-            assert!(
-                self.0.master_connection_pool.is_some(),
-                "TxSender is instantiated without both master connection pool and tx proxy"
-            );
+        } else if self.0.master_connection_pool.is_none() {
+            // Read-only node without a main node to proxy transactions to.
+            return Err(SubmitTxError::ReadOnlyNode);
         }
 
+        let simulated = if record_simulation {
+            let digest = simulate_tx_with_pending_state(
+                vm_permit.clone(),
+                self.shared_args(),
+                self.0.replica_connection_pool.clone(),
+                tx.clone().into(),
+            )
+            .await
+            .map_err(|err| match err {
+                SandboxExecutionError::Invocation(err) => SubmitTxError::Invocation(err),
+                err => SubmitTxError::PreExecutionReverted(err.to_string(), vec![]),
+            })?;
+            Some(digest)
+        } else {
+            None
+        };
+
         let nonce = tx.common_data.nonce.0;
         let hash = tx.hash();
-        let expected_nonce = self.get_expected_nonce(&tx).await;
+        let initiator = tx.initiator_account();
+        let expected_nonce = self.get_expected_nonce(&tx).await?;
 
         olaos_logs::info!(
             "Got nonce {:?} and expected nonce {:?}",
@@ -145,16 +220,34 @@ impl TxSender {
             expected_nonce
         );
 
-        let submission_res_handle = self
+        let mut storage = self
             .0
             .master_connection_pool
             .as_ref()
             .unwrap() // Checked above
             .access_storage_tagged("api")
-            .await
+            .await;
+        // The simulation is recorded in the same DB transaction as the transaction itself,
+        // so that the transaction can't be sealed before its simulation is recorded.
+        let mut transaction = storage.start_transaction().await;
+        let submission_res_handle = transaction
             .transactions_dal()
             .insert_transaction_l2(tx, TransactionExecutionMetrics::default())
-            .await;
+            .await
+            .map_err(|err| SubmitTxError::DatabaseUnavailable(err.to_string()))?;
+        let is_inserted = matches!(
+            submission_res_handle,
+            L2TxSubmissionResult::Added | L2TxSubmissionResult::Replaced
+        );
+        if let (true, Some(simulated)) = (is_inserted, &simulated) {
+            transaction
+                .transactions_dal()
+                .insert_transaction_simulation(hash, simulated)
+                .await
+                .map_err(|err| SubmitTxError::DatabaseUnavailable(err.to_string()))?;
+        }
+        transaction.commit().await;
+        drop(storage);
 
         olaos_logs::info!("Try to insert tx into db");
 
@@ -171,7 +264,10 @@ impl TxSender {
             L2TxSubmissionResult::Duplicate => Err(SubmitTxError::IncorrectTx(
                 ola_types::l2::error::TxCheckError::TxDuplication(hash),
             )),
-            _ => Ok(submission_res_handle),
+            _ => {
+                self.0.nonce_cache.invalidate(initiator);
+                Ok(submission_res_handle)
+            }
         };
 
         olaos_logs::info!("Insert tx into db result {:?}", res);
@@ -180,12 +276,32 @@ impl TxSender {
     }
 
     #[olaos_logs::instrument(skip(self, tx))]
-    pub async fn call_transaction_impl(&self, tx: L2Tx) -> Result<Bytes, SubmitTxError> {
+    pub async fn call_transaction_impl(
+        &self,
+        tx: L2Tx,
+        state_override: StateOverride,
+    ) -> Result<Bytes, SubmitTxError> {
+        let (output, _) = self
+            .call_transaction_with_stats_impl(tx, state_override, vec![])
+            .await?;
+        Ok(output)
+    }
+
+    /// Executes a call, also returning its statistics. `pending_writes` are applied to the state
+    /// of the latest sealed miniblock before `state_override`.
+    pub async fn call_transaction_with_stats_impl(
+        &self,
+        tx: L2Tx,
+        state_override: StateOverride,
+        pending_writes: Vec<(StorageKey, StorageValue)>,
+    ) -> Result<(Bytes, CallStats), SubmitTxError> {
         olaos_logs::info!(
             "Start call tx from {:?}, to {:?}",
             tx.initiator_account(),
             tx.recipient_account()
         );
+        let overrides = StorageOverrides::new(&state_override)
+            .map_err(|err| SubmitTxError::InvalidStateOverride(format!("{err:#}")))?;
 
         let vm_permit = self.0.vm_concurrency_limiter.acquire().await;
         let vm_permit = vm_permit.ok_or(SubmitTxError::ServerShuttingDown)?;
@@ -198,21 +314,32 @@ impl TxSender {
             .access_storage_tagged("api")
             .await;
 
-        let l1_batch_header = storage.blocks_dal().get_newest_l1_batch_header().await;
+        let l1_batch_header = storage
+            .blocks_dal()
+            .get_newest_l1_batch_header()
+            .await
+            .map_err(|err| SubmitTxError::DatabaseUnavailable(err.to_string()))?;
+        let override_writes = if state_override.is_empty() {
+            vec![]
+        } else {
+            // The call storage reflects the latest sealed miniblock.
+            let sealed_miniblock = storage
+                .blocks_dal()
+                .get_sealed_miniblock_number()
+                .await
+                .map_err(|err| SubmitTxError::DatabaseUnavailable(err.to_string()))?;
+            resolve_override_writes(&mut storage, &overrides, sealed_miniblock, &pending_writes)
+                .await?
+        };
 
         let db_config = load_db_config().expect("failed to load database config");
         let network = load_network_config().expect("failed to load network config");
 
         olaos_logs::info!("Start call in vm_manager");
 
-        let mut storage = OlaCachedStorage::new(
-            db_config.sequencer_db_path,
-            Some((millis_since_epoch() / 1_000) as u64),
-        )
-        .map_err(|e| SubmitTxError::TxCallTxError(e.to_string()))?;
-
-        let block_info = BlockExeInfo {
-            block_number: *l1_batch_header.number as u64 + 1,
+        let l1_batch_number = l1_batch_header.number + 1;
+        let mut block_info = BlockExeInfo {
+            block_number: l1_batch_number.0 as u64,
             block_timestamp: (millis_since_epoch() / 1_000) as u64,
             sequencer_address: u8_arr_to_address(
                 &self.0.sender_config.fee_account_addr.to_fixed_bytes(),
@@ -228,27 +355,78 @@ impl TxSender {
             signature_s: None,
             tx_hash: None,
         };
-        let mut tx_exe_manager: TxExeManager = TxExeManager::new(
-            ExecuteMode::Call,
-            block_info,
-            tape_init_info,
-            &mut storage,
-            u8_arr_to_address(&tx.execute.contract_address.to_fixed_bytes()),
-            0,
-        );
-        let call_res = tx_exe_manager
-            .call()
-            .map_err(|e| SubmitTxError::TxCallTxError(e.to_string()))?;
-        let ret = u64s_to_bytes(&call_res);
-
-        drop(vm_permit);
-
-        olaos_logs::info!("Drop vm_permit");
-
-        Ok(Bytes(ret))
+        let contract_address = u8_arr_to_address(&tx.execute.contract_address.to_fixed_bytes());
+        let execution_budget = ExecutionBudget::new(
+            ExecutionKind::Call,
+            self.0.sender_config.call_execution_budget,
+        )
+        .with_max_cycles(self.0.sender_config.call_max_cycles);
+        let call_storage_pool = self.call_storage_pool();
+        let (call_res, stats) = execution_budget
+            .run(&self.0.sandbox_pool, move || {
+                let started_at = Instant::now();
+                let pooled_storage = call_storage_pool
+                    .as_deref()
+                    .and_then(|pool| pool.checkout(l1_batch_number));
+                let mut storage = match pooled_storage {
+                    Some(pooled) => {
+                        block_info.block_timestamp = pooled.timestamp;
+                        pooled.storage
+                    }
+                    None => OlaCachedStorage::new(
+                        db_config.sequencer_db_path,
+                        Some(block_info.block_timestamp),
+                    )
+                    .map_err(|e| SubmitTxError::TxCallTxError(e.to_string()))?,
+                };
+                write_overrides(&mut storage, &pending_writes);
+                write_overrides(&mut storage, &override_writes);
+                let mut tx_exe_manager: TxExeManager = TxExeManager::new(
+                    ExecuteMode::Call,
+                    block_info,
+                    tape_init_info,
+                    &mut storage,
+                    contract_address,
+                    0,
+                );
+                // The call is traced, so that its cycles can be checked against the budget.
+                let call_res = tx_exe_manager
+                    .invoke()
+                    .map_err(|e| SubmitTxError::TxCallTxError(e.to_string()));
+                let time_spent = started_at.elapsed();
+                drop(tx_exe_manager);
+                let call_res = call_res?;
+
+                // Overridden and pending slots are written to the storage cache beforehand.
+                let cached_keys = pending_writes.iter().chain(&override_writes);
+                let storage_accesses = StorageAccessStats::new(
+                    call_res.storage_access_logs.iter().map(LogQuery::from),
+                    cached_keys.map(|(key, _)| *key),
+                );
+                vm_permit.record_storage_accesses(storage_accesses);
+                drop(vm_permit);
+                olaos_logs::info!("Drop vm_permit");
+
+                let cycles = cycles_in_trace(&call_res.trace);
+                execution_budget
+                    .check_cycles(cycles)
+                    .map_err(InvocationError::from)?;
+                let stats = CallStats {
+                    time_spent_ms: time_spent.as_millis() as u64,
+                    storage_reads: storage_accesses.reads,
+                    missed_storage_invocations: storage_accesses.misses,
+                    cycles: cycles.into(),
+                };
+                Ok::<_, SubmitTxError>((call_res.return_data, stats))
+            })
+            .await??;
+
+        Ok((Bytes(u64s_to_bytes(&call_res)), stats))
     }
 
     async fn validate_tx(&self, tx: &L2Tx) -> Result<(), SubmitTxError> {
+        self.validate_calldata(tx)?;
+        self.validate_execute_after(tx)?;
         if tx.execute.factory_deps_length() > MAX_NEW_FACTORY_DEPS {
             return Err(SubmitTxError::TooManyFactoryDependencies(
                 tx.execute.factory_deps_length(),
@@ -259,9 +437,63 @@ impl TxSender {
         Ok(())
     }
 
+    /// Structural checks of the calldata, so that garbage is rejected before touching the DB or VM.
+    fn validate_calldata(&self, tx: &L2Tx) -> Result<(), SubmitTxError> {
+        let calldata_len = tx.execute.calldata.len();
+        if calldata_len > self.0.sender_config.max_calldata_size {
+            return Err(SubmitTxError::CalldataTooLarge(
+                self.0.sender_config.max_calldata_size,
+                calldata_len,
+            ));
+        }
+        if calldata_len % 8 != 0 {
+            return Err(SubmitTxError::MalformedCalldata(calldata_len));
+        }
+        if let Some(screener) = &self.0.calldata_screener {
+            screener
+                .check(tx.execute.contract_address, &tx.execute.calldata)
+                .map_err(|violation| {
+                    olaos_logs::warn!(
+                        "Rejected transaction {:?} by calldata screening: {}",
+                        tx.hash(),
+                        violation.description()
+                    );
+                    SubmitTxError::CalldataRejected(violation.description())
+                })?;
+        }
+        Ok(())
+    }
+
+    fn validate_execute_after(&self, tx: &L2Tx) -> Result<(), SubmitTxError> {
+        let Some(execute_after) = tx.common_data.execute_after else {
+            return Ok(());
+        };
+        let max_execute_after =
+            seconds_since_epoch() + self.0.sender_config.max_scheduled_tx_delay.as_secs();
+        if execute_after > max_execute_after {
+            return Err(SubmitTxError::ExecuteAfterTooFar(
+                max_execute_after,
+                execute_after,
+            ));
+        }
+        Ok(())
+    }
+
     async fn validate_account_nonce(&self, tx: &L2Tx) -> Result<(), SubmitTxError> {
-        let expected_nonce = self.get_expected_nonce(tx).await;
+        let address = tx.initiator_account();
+        if let Some(expected_nonce) = self.0.nonce_cache.get(address) {
+            // The cached nonce may lag behind the actual one, so transactions too far ahead of it
+            // are only rejected after re-reading the nonce.
+            match self.check_nonce(tx, expected_nonce) {
+                Err(SubmitTxError::NonceIsTooHigh(..)) => {}
+                result => return result,
+            }
+        }
+        let expected_nonce = self.load_expected_nonce(address).await?;
+        self.check_nonce(tx, expected_nonce)
+    }
 
+    fn check_nonce(&self, tx: &L2Tx, expected_nonce: Nonce) -> Result<(), SubmitTxError> {
         if tx.common_data.nonce.0 < expected_nonce.0 {
             Err(SubmitTxError::NonceIsTooLow(
                 expected_nonce.0,
@@ -282,20 +514,28 @@ impl TxSender {
         }
     }
 
-    async fn get_expected_nonce(&self, tx: &L2Tx) -> Nonce {
-        let mut connection = self
-            .0
-            .replica_connection_pool
-            .access_storage_tagged("api")
-            .await;
+    async fn get_expected_nonce(&self, tx: &L2Tx) -> Result<Nonce, SubmitTxError> {
+        let address = tx.initiator_account();
+        match self.0.nonce_cache.get(address) {
+            Some(nonce) => Ok(nonce),
+            None => self.load_expected_nonce(address).await,
+        }
+    }
 
-        let latest_block_number = connection.blocks_dal().get_sealed_miniblock_number().await;
-        let nonce = connection
-            .storage_web3_dal()
-            .get_address_historical_nonce(tx.initiator_account(), latest_block_number)
+    /// Reads the nonce of `address` at the latest sealed miniblock and caches it.
+    async fn load_expected_nonce(&self, address: Address) -> Result<Nonce, SubmitTxError> {
+        let pool = &self.0.replica_connection_pool;
+        let latest_block_number = pool
+            .sealed_miniblock_number()
             .await
-            .unwrap();
-        Nonce(nonce.as_u32())
+            .map_err(|err| SubmitTxError::DatabaseUnavailable(err.to_string()))?;
+        let nonce = pool
+            .address_historical_nonce(address, latest_block_number)
+            .await
+            .map_err(|err| SubmitTxError::DatabaseUnavailable(err.to_string()))?;
+        let nonce = Nonce(nonce.as_u32());
+        self.0.nonce_cache.insert(address, nonce);
+        Ok(nonce)
     }
 
     fn shared_args(&self) -> TxSharedArgs {
@@ -303,12 +543,43 @@ impl TxSender {
             operator_account: AccountTreeId::new(self.0.sender_config.fee_account_addr),
             base_system_contracts: self.0.api_contracts.eth_call.clone(),
             caches: self.storage_caches(),
+            execution_budget: ExecutionBudget::new(
+                ExecutionKind::Validation,
+                self.0.sender_config.validation_execution_budget,
+            )
+            .with_max_cycles(self.0.sender_config.validation_max_cycles),
+            sandbox_pool: Arc::clone(&self.0.sandbox_pool),
+            call_storage_pool: self.call_storage_pool(),
         }
     }
 
     pub(crate) fn storage_caches(&self) -> PostgresStorageCaches {
         self.0.storage_caches.clone()
     }
+
+    pub(crate) fn vm_concurrency_limiter(&self) -> Arc<VmConcurrencyLimiter> {
+        Arc::clone(&self.0.vm_concurrency_limiter)
+    }
+
+    /// Returns the calldata screener if screening is enabled.
+    pub(crate) fn calldata_screener(&self) -> Option<Arc<CalldataScreener>> {
+        self.0.calldata_screener.clone()
+    }
+
+    /// Returns the proxy to the main node if this is an external node.
+    pub(crate) fn proxy(&self) -> Option<&TxProxy> {
+        self.0.proxy.as_ref()
+    }
+
+    /// Returns the task autotuning the VM concurrency limit, if autotuning is enabled.
+    pub(crate) fn vm_concurrency_autotuner(&self) -> Option<VmConcurrencyAutotuner> {
+        self.0.vm_concurrency_limiter.autotuner()
+    }
+
+    /// Returns the pool of call storages, which should be kept filled by [`CallStoragePool::run()`].
+    pub(crate) fn call_storage_pool(&self) -> Option<Arc<CallStoragePool>> {
+        self.0.call_storage_pool.clone()
+    }
 }
 
 type TxSenderRateLimiter =
@@ -324,6 +595,11 @@ pub struct TxSenderInner {
     sequencer_config: Option<SequencerConfig>,
     pub(super) vm_concurrency_limiter: Arc<VmConcurrencyLimiter>,
     storage_caches: PostgresStorageCaches,
+    nonce_cache: NonceCache,
+    call_storage_pool: Option<Arc<CallStoragePool>>,
+    calldata_screener: Option<Arc<CalldataScreener>>,
+    /// Threads running VM invocations of calls and transaction validation.
+    sandbox_pool: Arc<BlockingPool>,
 }
 
 #[derive(Debug)]
@@ -334,6 +610,9 @@ pub struct TxSenderBuilder {
     rate_limiter: Option<TxSenderRateLimiter>,
     proxy: Option<TxProxy>,
     sequencer_config: Option<SequencerConfig>,
+    seal_events: Option<MiniblockSealEvents>,
+    calldata_screener: Option<Arc<CalldataScreener>>,
+    call_storage_db_path: Option<String>,
 }
 
 impl TxSenderBuilder {
@@ -345,6 +624,9 @@ impl TxSenderBuilder {
             rate_limiter: None,
             proxy: None,
             sequencer_config: None,
+            seal_events: None,
+            calldata_screener: None,
+            call_storage_db_path: None,
         }
     }
 
@@ -374,12 +656,53 @@ impl TxSenderBuilder {
         self
     }
 
+    /// Sets seal events of the in-process sequencer used to invalidate cached nonces.
+    pub fn with_miniblock_seal_events(mut self, seal_events: Option<MiniblockSealEvents>) -> Self {
+        self.seal_events = seal_events;
+        self
+    }
+
+    /// Enables screening of calldata before mempool admission.
+    pub fn with_calldata_screener(mut self, screener: CalldataScreener) -> Self {
+        self.calldata_screener = Some(Arc::new(screener));
+        self
+    }
+
+    /// Enables the pool of call storages opened in advance from the sequencer RocksDB
+    /// at `sequencer_db_path`. The pool size is taken from [`TxSenderConfig`].
+    pub fn with_call_storage_pool(mut self, sequencer_db_path: String) -> Self {
+        self.call_storage_db_path = Some(sequencer_db_path);
+        self
+    }
+
     pub async fn build(
         self,
         vm_concurrency_limiter: Arc<VmConcurrencyLimiter>,
         api_contracts: ApiContracts,
         storage_caches: PostgresStorageCaches,
     ) -> TxSender {
+        let nonce_cache = NonceCache::new(
+            self.config.nonce_cache_ttl,
+            self.config.nonce_cache_negative_ttl,
+            self.seal_events
+                .as_ref()
+                .map(MiniblockSealEvents::subscribe),
+        );
+        let call_storage_pool = self.call_storage_db_path.map(|sequencer_db_path| {
+            Arc::new(CallStoragePool::new(
+                sequencer_db_path,
+                self.config.call_storage_pool_size,
+                self.seal_events
+                    .as_ref()
+                    .map(MiniblockSealEvents::subscribe),
+            ))
+        });
+        let sandbox_pool = BlockingPool::new(
+            "sandbox",
+            self.config.sandbox_threads,
+            self.config.sandbox_queue_capacity,
+        )
+        .expect("failed creating sandbox thread pool");
         TxSender(Arc::new(TxSenderInner {
             sender_config: self.config,
             master_connection_pool: self.master_connection_pool,
@@ -390,6 +713,10 @@ impl TxSenderBuilder {
             sequencer_config: self.sequencer_config,
             vm_concurrency_limiter,
             storage_caches,
+            nonce_cache,
+            call_storage_pool,
+            calldata_screener: self.calldata_screener,
+            sandbox_pool: Arc::new(sandbox_pool),
         }))
     }
 }
@@ -0,0 +1,138 @@
+//! Cache of account nonces used to precheck submitted transactions.
+//!
+//! Nonces are cached for a short time, and are dropped once a miniblock is persisted if
+//! the sequencer runs in the same process. Zero nonces (accounts that haven't sent transactions)
+//! are cached separately for longer, since they are looked up for spam from fresh accounts.
+//! A stale nonce lags behind the actual one. Transactions with nonces below the actual one may
+//! thus pass the precheck, in which case they are rejected when executed by the sequencer;
+//! transactions too far ahead of a cached nonce are rejected only after re-reading the nonce
+//! from Postgres.
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use ola_types::{Address, MiniblockNumber, Nonce};
+use tokio::sync::watch;
+
+/// Maximum number of cached accounts; the cache is reset once it's reached.
+const MAX_ENTRIES: usize = 100_000;
+
+#[derive(Debug, Clone, Copy)]
+struct CachedNonce {
+    nonce: Nonce,
+    /// Latest persisted miniblock when the nonce was read.
+    sealed_miniblock: MiniblockNumber,
+    cached_at: Instant,
+}
+
+#[derive(Debug)]
+pub(crate) struct NonceCache {
+    ttl: Duration,
+    negative_ttl: Duration,
+    seal_events: Option<watch::Receiver<MiniblockNumber>>,
+    entries: Mutex<HashMap<Address, CachedNonce>>,
+}
+
+impl NonceCache {
+    pub(crate) fn new(
+        ttl: Duration,
+        negative_ttl: Duration,
+        seal_events: Option<watch::Receiver<MiniblockNumber>>,
+    ) -> Self {
+        Self {
+            ttl,
+            negative_ttl,
+            seal_events,
+            entries: Mutex::default(),
+        }
+    }
+
+    fn sealed_miniblock(&self) -> MiniblockNumber {
+        self.seal_events
+            .as_ref()
+            .map_or(MiniblockNumber(0), |events| *events.borrow())
+    }
+
+    pub(crate) fn get(&self, address: Address) -> Option<Nonce> {
+        let entry = *self.entries.lock().expect("poisoned").get(&address)?;
+        let age = entry.cached_at.elapsed();
+        let is_fresh = if entry.nonce.0 == 0 {
+            // A zero nonce only changes once the first transaction of the account is executed,
+            // so it's not dropped on every seal; see `invalidate()`.
+            age < self.negative_ttl
+        } else {
+            age < self.ttl && entry.sealed_miniblock == self.sealed_miniblock()
+        };
+
+        let result = if is_fresh { "hit" } else { "miss" };
+        metrics::counter!("api.tx_sender.nonce_cache", 1, "result" => result);
+        is_fresh.then_some(entry.nonce)
+    }
+
+    pub(crate) fn insert(&self, address: Address, nonce: Nonce) {
+        let ttl = if nonce.0 == 0 {
+            self.negative_ttl
+        } else {
+            self.ttl
+        };
+        if ttl.is_zero() {
+            return;
+        }
+
+        let entry = CachedNonce {
+            nonce,
+            sealed_miniblock: self.sealed_miniblock(),
+            cached_at: Instant::now(),
+        };
+        let mut entries = self.entries.lock().expect("poisoned");
+        if entries.len() >= MAX_ENTRIES {
+            entries.clear();
+        }
+        entries.insert(address, entry);
+    }
+
+    /// Drops the cached nonce of `address`, e.g. once a transaction from it is accepted.
+    pub(crate) fn invalidate(&self, address: Address) {
+        self.entries.lock().expect("poisoned").remove(&address);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caching_nonces() {
+        let (seal_sender, seal_receiver) = watch::channel(MiniblockNumber(1));
+        let cache = NonceCache::new(
+            Duration::from_secs(60),
+            Duration::from_secs(60),
+            Some(seal_receiver),
+        );
+        let (account, fresh_account) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        assert_eq!(cache.get(account), None);
+
+        cache.insert(account, Nonce(5));
+        cache.insert(fresh_account, Nonce(0));
+        assert_eq!(cache.get(account), Some(Nonce(5)));
+        assert_eq!(cache.get(fresh_account), Some(Nonce(0)));
+
+        // Sealing a miniblock invalidates non-zero nonces only.
+        seal_sender.send_replace(MiniblockNumber(2));
+        assert_eq!(cache.get(account), None);
+        assert_eq!(cache.get(fresh_account), Some(Nonce(0)));
+
+        cache.invalidate(fresh_account);
+        assert_eq!(cache.get(fresh_account), None);
+    }
+
+    #[test]
+    fn disabled_nonce_cache() {
+        let cache = NonceCache::new(Duration::ZERO, Duration::ZERO, None);
+        cache.insert(Address::repeat_byte(1), Nonce(5));
+        assert_eq!(cache.get(Address::repeat_byte(1)), None);
+    }
+}
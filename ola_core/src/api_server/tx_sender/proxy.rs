@@ -1,16 +1,27 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
 
 use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
 use ola_types::l2::L2Tx;
-use ola_types::H256;
+use ola_types::{MiniblockNumber, H256};
 use ola_web3_decl::error::{ClientRpcContext, EnrichedClientResult};
+use ola_web3_decl::namespaces::eth::EthNamespaceClient;
 use ola_web3_decl::namespaces::ola::OlaNamespaceClient;
 use ola_web3_decl::namespaces::RpcResult;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
+
+/// How long the latest main node miniblock is cached for. Requests arriving in the meantime,
+/// e.g. `eth_syncing` polls by clients, don't reach the main node.
+const LATEST_MINIBLOCK_CACHE_TTL: Duration = Duration::from_secs(1);
 
 #[derive(Debug)]
 pub struct TxProxy {
     tx_cache: RwLock<HashMap<H256, L2Tx>>,
+    /// Latest main node miniblock with the time it was fetched at. The lock is held while
+    /// fetching, so concurrent requests share a single main node call.
+    latest_miniblock: Mutex<Option<(MiniblockNumber, Instant)>>,
     client: HttpClient,
 }
 
@@ -20,6 +31,7 @@ impl TxProxy {
         Self {
             client,
             tx_cache: RwLock::new(HashMap::new()),
+            latest_miniblock: Mutex::new(None),
         }
     }
 
@@ -32,6 +44,14 @@ impl TxProxy {
         let raw_tx = ola_types::Bytes(input_data.to_vec());
         let tx_hash = tx.hash();
         olaos_logs::info!("Proxying tx {}", tx_hash);
+        if let Some(execute_after) = tx.common_data.execute_after {
+            return self
+                .client
+                .send_scheduled_transaction(raw_tx, execute_after)
+                .rpc_context("send_scheduled_transaction")
+                .with_arg("tx_hash", &tx_hash)
+                .await;
+        }
         self.client
             .send_raw_transaction(raw_tx)
             .rpc_context("send_raw_transaction")
@@ -39,6 +59,24 @@ impl TxProxy {
             .await
     }
 
+    /// Returns the latest miniblock of the main node, cached for [`LATEST_MINIBLOCK_CACHE_TTL`].
+    pub async fn latest_miniblock(&self) -> EnrichedClientResult<MiniblockNumber> {
+        let mut cached = self.latest_miniblock.lock().await;
+        if let Some((number, fetched_at)) = *cached {
+            if fetched_at.elapsed() < LATEST_MINIBLOCK_CACHE_TTL {
+                return Ok(number);
+            }
+        }
+        let number = self
+            .client
+            .get_block_number()
+            .rpc_context("get_block_number")
+            .await?;
+        let number = MiniblockNumber(number.as_u32());
+        *cached = Some((number, Instant::now()));
+        Ok(number)
+    }
+
     pub async fn forget_tx(&self, tx_hash: H256) {
         self.tx_cache.write().await.remove(&tx_hash);
     }
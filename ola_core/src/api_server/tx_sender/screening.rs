@@ -0,0 +1,338 @@
+//! Screening of transaction calldata for known-bad patterns before mempool admission.
+//!
+//! Rules are loaded from the file at `web3_json_rpc.calldata_screening_rules_path` and can be
+//! replaced at runtime via `ola_setCalldataScreeningRules`, so that operators can block calls
+//! to a vulnerable contract or a known exploit payload while a fix is being rolled out. Rules set
+//! at runtime are persisted in Postgres; they take precedence over the file and are periodically
+//! reloaded, so that they survive restarts and apply to all API servers.
+
+use std::{
+    fs,
+    path::Path,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use anyhow::Context as _;
+use ola_dal::connection::ConnectionPool;
+use ola_types::{api::CalldataScreeningRules, Address};
+use tokio::sync::watch;
+
+const RULES_RELOAD_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Rule that caused calldata to be rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScreeningViolation {
+    TooLarge { max: usize, actual: usize },
+    ForbiddenSelector(u64),
+    ExploitSignature(String),
+}
+
+impl ScreeningViolation {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::TooLarge { .. } => "size",
+            Self::ForbiddenSelector(_) => "selector",
+            Self::ExploitSignature(_) => "signature",
+        }
+    }
+
+    pub(super) fn description(&self) -> String {
+        match self {
+            Self::TooLarge { max, actual } => {
+                format!("calldata size {actual} exceeds the screening limit of {max} bytes")
+            }
+            Self::ForbiddenSelector(selector) => format!("selector {selector:#x} is forbidden"),
+            Self::ExploitSignature(name) => format!("matches known exploit signature `{name}`"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CalldataScreener {
+    rules: RwLock<CalldataScreeningRules>,
+    /// Master pool used to persist rules set at runtime. If not set, rules are only kept in memory.
+    pool: Option<ConnectionPool>,
+}
+
+impl CalldataScreener {
+    pub fn new(rules: CalldataScreeningRules) -> Self {
+        Self {
+            rules: RwLock::new(rules),
+            pool: None,
+        }
+    }
+
+    /// Loads persisted rules, falling back to the rules file at `path` if rules were never
+    /// set at runtime. Rules set afterwards are persisted using `pool`.
+    pub async fn load(pool: ConnectionPool, path: &Path) -> anyhow::Result<Self> {
+        let mut storage = pool.access_storage_tagged("api").await;
+        let persisted_rules = storage
+            .calldata_screening_dal()
+            .get_rules()
+            .await
+            .context("failed loading persisted calldata screening rules")?;
+        drop(storage);
+
+        let rules = match persisted_rules {
+            Some(rules) => {
+                olaos_logs::info!(
+                    "Using persisted calldata screening rules instead of the ones from {path:?}"
+                );
+                rules
+            }
+            None => Self::read_file(path)?,
+        };
+        Ok(Self {
+            rules: RwLock::new(rules),
+            pool: Some(pool),
+        })
+    }
+
+    fn read_file(path: &Path) -> anyhow::Result<CalldataScreeningRules> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed reading calldata screening rules from {path:?}"))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("failed parsing calldata screening rules from {path:?}"))
+    }
+
+    pub fn rules(&self) -> CalldataScreeningRules {
+        self.rules.read().expect("poisoned").clone()
+    }
+
+    /// Replaces all rules; returns the previous ones. The rules are persisted before they are
+    /// applied, so that applied rules are never lost on restart.
+    pub async fn set_rules(
+        &self,
+        rules: CalldataScreeningRules,
+    ) -> anyhow::Result<CalldataScreeningRules> {
+        if let Some(pool) = &self.pool {
+            let mut storage = pool.access_storage_tagged("api").await;
+            storage
+                .calldata_screening_dal()
+                .set_rules(&rules)
+                .await
+                .context("failed persisting calldata screening rules")?;
+        }
+        Ok(std::mem::replace(
+            &mut *self.rules.write().expect("poisoned"),
+            rules,
+        ))
+    }
+
+    /// Periodically reloads persisted rules, so that rules set via another API server
+    /// are applied.
+    pub async fn run_reloading(
+        self: Arc<Self>,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        let Some(pool) = &self.pool else {
+            return Ok(());
+        };
+        loop {
+            if *stop_receiver.borrow_and_update() {
+                olaos_logs::info!("Stop signal received, calldata screening rules aren't reloaded");
+                break;
+            }
+
+            let mut storage = pool.access_storage_tagged("api").await;
+            match storage.calldata_screening_dal().get_rules().await {
+                Ok(Some(rules)) => {
+                    let mut current_rules = self.rules.write().expect("poisoned");
+                    if *current_rules != rules {
+                        olaos_logs::info!("Reloaded updated calldata screening rules");
+                        *current_rules = rules;
+                    }
+                }
+                Ok(None) => { /* Rules were never set at runtime */ }
+                // Failures aren't fatal; the current rules remain in effect.
+                Err(err) => olaos_logs::warn!("Failed reloading calldata screening rules: {err}"),
+            }
+            drop(storage);
+
+            tokio::select! {
+                _ = stop_receiver.changed() => {
+                    olaos_logs::info!("Stop signal received, calldata screening rules aren't reloaded");
+                    break;
+                }
+                () = tokio::time::sleep(RULES_RELOAD_INTERVAL) => { /* Continue reloading */ }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks calldata of a call to `contract`. The calldata must consist of whole words.
+    pub(super) fn check(
+        &self,
+        contract: Address,
+        calldata: &[u8],
+    ) -> Result<(), ScreeningViolation> {
+        let result = self.check_inner(contract, calldata);
+        if let Err(violation) = &result {
+            metrics::counter!(
+                "api.tx_sender.calldata_screening.rejected",
+                1,
+                "rule" => violation.kind()
+            );
+        }
+        result
+    }
+
+    fn check_inner(&self, contract: Address, calldata: &[u8]) -> Result<(), ScreeningViolation> {
+        let rules = self.rules.read().expect("poisoned");
+        if let Some(max) = rules.max_calldata_size {
+            if calldata.len() > max {
+                return Err(ScreeningViolation::TooLarge {
+                    max,
+                    actual: calldata.len(),
+                });
+            }
+        }
+
+        // The Ola ABI places the function selector into the last word of the calldata.
+        if let Some(last_word) = calldata.rchunks_exact(8).next() {
+            let selector = u64::from_be_bytes(last_word.try_into().unwrap());
+            let is_forbidden = rules.forbidden_selectors.iter().any(|rule| {
+                rule.selector.as_u64() == selector
+                    && rule.contract.map_or(true, |address| address == contract)
+            });
+            if is_forbidden {
+                return Err(ScreeningViolation::ForbiddenSelector(selector));
+            }
+        }
+
+        let signature = rules.exploit_signatures.iter().find(|signature| {
+            let pattern = &signature.pattern.0;
+            !pattern.is_empty()
+                && calldata
+                    .windows(pattern.len())
+                    .any(|window| window == pattern)
+        });
+        if let Some(signature) = signature {
+            return Err(ScreeningViolation::ExploitSignature(signature.name.clone()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ola_types::{
+        api::{ExploitSignature, ForbiddenSelector},
+        Bytes,
+    };
+    use ola_utils::u64s_to_bytes;
+
+    use super::*;
+
+    const SELECTOR: u64 = 0x1234_5678;
+
+    fn calldata(args: &[u64], selector: u64) -> Vec<u8> {
+        let mut words = args.to_vec();
+        words.push(args.len() as u64);
+        words.push(selector);
+        u64s_to_bytes(&words)
+    }
+
+    #[test]
+    fn empty_rules_pass_everything() {
+        let screener = CalldataScreener::default();
+        screener.check(Address::zero(), &[]).unwrap();
+        screener
+            .check(Address::repeat_byte(1), &calldata(&[1, 2, 3], SELECTOR))
+            .unwrap();
+    }
+
+    #[test]
+    fn size_limit() {
+        let screener = CalldataScreener::new(CalldataScreeningRules {
+            max_calldata_size: Some(32),
+            ..CalldataScreeningRules::default()
+        });
+        screener
+            .check(Address::zero(), &calldata(&[1, 2], SELECTOR))
+            .unwrap();
+        let err = screener
+            .check(Address::zero(), &calldata(&[1, 2, 3], SELECTOR))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ScreeningViolation::TooLarge {
+                max: 32,
+                actual: 40
+            }
+        );
+    }
+
+    #[test]
+    fn forbidden_selectors() {
+        let vulnerable = Address::repeat_byte(0xaa);
+        let screener = CalldataScreener::new(CalldataScreeningRules {
+            forbidden_selectors: vec![
+                ForbiddenSelector {
+                    selector: SELECTOR.into(),
+                    contract: Some(vulnerable),
+                },
+                ForbiddenSelector {
+                    selector: 0xdead.into(),
+                    contract: None,
+                },
+            ],
+            ..CalldataScreeningRules::default()
+        });
+
+        let data = calldata(&[1], SELECTOR);
+        screener.check(Address::repeat_byte(1), &data).unwrap();
+        let err = screener.check(vulnerable, &data).unwrap_err();
+        assert_eq!(err, ScreeningViolation::ForbiddenSelector(SELECTOR));
+
+        let data = calldata(&[1], 0xdead);
+        let err = screener.check(Address::repeat_byte(1), &data).unwrap_err();
+        assert_eq!(err, ScreeningViolation::ForbiddenSelector(0xdead));
+        // Selector values among arguments are not matched.
+        screener
+            .check(Address::repeat_byte(1), &calldata(&[0xdead], SELECTOR))
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn exploit_signatures_and_rule_updates() {
+        let screener = CalldataScreener::default();
+        let data = calldata(&[7, 0xbad_c0de, 7], SELECTOR);
+        screener.check(Address::zero(), &data).unwrap();
+
+        let rules = CalldataScreeningRules {
+            exploit_signatures: vec![ExploitSignature {
+                name: "reentrancy".to_owned(),
+                pattern: Bytes(u64s_to_bytes(&[0xbad_c0de, 7])),
+            }],
+            ..CalldataScreeningRules::default()
+        };
+        let previous = screener.set_rules(rules.clone()).await.unwrap();
+        assert_eq!(previous, CalldataScreeningRules::default());
+        assert_eq!(screener.rules(), rules);
+
+        let err = screener.check(Address::zero(), &data).unwrap_err();
+        assert_eq!(
+            err,
+            ScreeningViolation::ExploitSignature("reentrancy".to_owned())
+        );
+        screener
+            .check(Address::zero(), &calldata(&[0xbad_c0de], SELECTOR))
+            .unwrap();
+    }
+
+    #[test]
+    fn parsing_rules() {
+        let rules: CalldataScreeningRules = serde_json::from_value(serde_json::json!({
+            "maxCalldataSize": 1024,
+            "forbiddenSelectors": [{ "selector": "0x12345678" }],
+            "exploitSignatures": [{ "name": "drain", "pattern": "0x0102" }],
+        }))
+        .unwrap();
+        assert_eq!(rules.max_calldata_size, Some(1024));
+        assert_eq!(rules.forbidden_selectors[0].selector, SELECTOR.into());
+        assert_eq!(rules.forbidden_selectors[0].contract, None);
+        assert_eq!(rules.exploit_signatures[0].pattern, Bytes(vec![1, 2]));
+    }
+}
@@ -0,0 +1,60 @@
+//! Pruning of simulations recorded for transactions submitted with simulation recording.
+
+use std::time::Duration;
+
+use ola_dal::connection::ConnectionPool;
+use tokio::sync::watch;
+
+/// Maximum number of simulations removed per pruning iteration.
+const PRUNING_CHUNK_SIZE: usize = 10_000;
+const PRUNING_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Periodically removes simulations older than the retention.
+#[derive(Debug)]
+pub struct SimulationsPruner {
+    /// Master pool.
+    pool: ConnectionPool,
+    retention: Duration,
+}
+
+impl SimulationsPruner {
+    pub fn new(pool: ConnectionPool, retention: Duration) -> Self {
+        Self { pool, retention }
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        loop {
+            if *stop_receiver.borrow_and_update() {
+                olaos_logs::info!("Stop signal received, simulations pruner is shutting down");
+                break;
+            }
+
+            let mut storage = self.pool.access_storage_tagged("api").await;
+            let result = storage
+                .transactions_dal()
+                .prune_transaction_simulations(self.retention, PRUNING_CHUNK_SIZE)
+                .await;
+            drop(storage);
+            match result {
+                Ok(pruned) => {
+                    metrics::counter!("api.tx_sender.pruned_simulations", pruned as u64);
+                    // Simulations are removed in chunks, so that the stop signal is checked
+                    // in between.
+                    if pruned == PRUNING_CHUNK_SIZE {
+                        continue;
+                    }
+                }
+                // Failures aren't fatal; expired simulations are removed on the next iteration.
+                Err(err) => olaos_logs::warn!("Failed pruning transaction simulations: {err}"),
+            }
+            tokio::select! {
+                _ = stop_receiver.changed() => {
+                    olaos_logs::info!("Stop signal received, simulations pruner is shutting down");
+                    break;
+                }
+                () = tokio::time::sleep(PRUNING_INTERVAL) => { /* Continue pruning */ }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,404 @@
+//! Structured access log of the JSON-RPC server.
+//!
+//! Every sampled call is recorded as a JSON line with the method, a hash of its params
+//! (params themselves are never logged), the caller IP, the latency and the error code, so that
+//! operators can investigate abusive patterns without enabling debug logs. Successful and failed
+//! calls are sampled at separate rates. Entries are written to a rotated file if
+//! `web3_json_rpc.access_log_path` is set, and logged via `olaos_logs` otherwise.
+//!
+//! The caller IP is taken from the `x-forwarded-for` header: entries are walked from the right,
+//! and the first address which is not one of `web3_json_rpc.access_log_trusted_proxies` is
+//! the caller. The server doesn't expose the peer address to middleware, so this is only
+//! spoof-proof if the node is reachable through the trusted proxies alone. Caller IPs are not
+//! recorded if no proxies are trusted, and for WS calls.
+
+use std::{
+    collections::{hash_map::RandomState, HashSet},
+    fs::{File, OpenOptions},
+    future::Future,
+    hash::BuildHasher,
+    io::Write as _,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc,
+    },
+    task::{Context, Poll},
+    time::Instant,
+};
+
+use futures::future::BoxFuture;
+use jsonrpsee::{server::middleware::rpc::RpcServiceT, types::Request, MethodResponse};
+use ola_types::H256;
+use ola_utils::time::millis_since_epoch;
+use serde::Serialize;
+use tower::{Layer, Service};
+use web3::signing::keccak256;
+
+use super::correlation_id::current_correlation_id;
+
+/// Number of entries buffered for the file sink; entries are dropped if the writer lags behind.
+const FILE_SINK_BUFFER_SIZE: usize = 8_192;
+
+tokio::task_local! {
+    static CALLER_IP: Option<IpAddr>;
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AccessLogEntry {
+    timestamp_ms: u128,
+    transport: &'static str,
+    correlation_id: Option<String>,
+    method: String,
+    params_hash: Option<H256>,
+    caller_ip: Option<IpAddr>,
+    latency_us: u64,
+    /// JSON-RPC error code; `None` for successful calls.
+    error_code: Option<i32>,
+}
+
+#[derive(Debug)]
+enum AccessLogSink {
+    Logs,
+    File(mpsc::SyncSender<String>),
+}
+
+#[derive(Debug)]
+pub struct AccessLog {
+    sample_rate: f64,
+    error_sample_rate: f64,
+    trusted_proxies: Arc<HashSet<IpAddr>>,
+    redact_ips: bool,
+    sampler: RandomState,
+    call_counter: AtomicU64,
+    sink: AccessLogSink,
+}
+
+impl AccessLog {
+    /// Creates an access log recording the `sample_rate` share of successful calls and
+    /// the `error_sample_rate` share of failed ones. If `path` is specified, spawns a thread
+    /// writing entries to the file.
+    pub fn new(
+        sample_rate: f64,
+        error_sample_rate: f64,
+        trusted_proxies: Vec<IpAddr>,
+        path: Option<PathBuf>,
+        max_file_size: u64,
+        redact_ips: bool,
+    ) -> anyhow::Result<Self> {
+        let sink = match path {
+            Some(path) => {
+                let file_sink = FileSink::open(path, max_file_size)?;
+                let (sender, receiver) = mpsc::sync_channel(FILE_SINK_BUFFER_SIZE);
+                std::thread::Builder::new()
+                    .name("rpc-access-log".to_owned())
+                    .spawn(move || file_sink.run(receiver))?;
+                AccessLogSink::File(sender)
+            }
+            None => AccessLogSink::Logs,
+        };
+        Ok(Self {
+            sample_rate,
+            error_sample_rate,
+            trusted_proxies: Arc::new(trusted_proxies.into_iter().collect()),
+            redact_ips,
+            sampler: RandomState::new(),
+            call_counter: AtomicU64::new(0),
+            sink,
+        })
+    }
+
+    /// Returns a pseudo-random value in `[0, 1]` compared against the sample rates.
+    fn sample(&self) -> f64 {
+        let call_index = self.call_counter.fetch_add(1, Ordering::Relaxed);
+        self.sampler.hash_one(call_index) as f64 / u64::MAX as f64
+    }
+
+    fn should_record(&self, sample: f64, is_error: bool) -> bool {
+        let sample_rate = if is_error {
+            self.error_sample_rate
+        } else {
+            self.sample_rate
+        };
+        sample < sample_rate
+    }
+
+    fn record(&self, mut entry: AccessLogEntry) {
+        if self.redact_ips {
+            entry.caller_ip = entry.caller_ip.map(redact_ip);
+        }
+        let line = serde_json::to_string(&entry).expect("failed serializing access log entry");
+        match &self.sink {
+            AccessLogSink::Logs => olaos_logs::info!("RPC access: {line}"),
+            AccessLogSink::File(sender) => {
+                if sender.try_send(line).is_err() {
+                    metrics::counter!("api.web3.access_log.dropped_entries", 1);
+                }
+            }
+        }
+    }
+}
+
+/// Masks the host part of the address: the last octet for IPv4, and everything except
+/// the /48 prefix for IPv6.
+fn redact_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            IpAddr::from([a, b, c, 0])
+        }
+        IpAddr::V6(ip) => {
+            let mut segments = ip.segments();
+            segments[3..].fill(0);
+            IpAddr::from(segments)
+        }
+    }
+}
+
+#[derive(Debug)]
+struct FileSink {
+    path: PathBuf,
+    max_file_size: u64,
+    file: File,
+    file_size: u64,
+}
+
+impl FileSink {
+    fn open(path: PathBuf, max_file_size: u64) -> anyhow::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let file_size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            max_file_size,
+            file,
+            file_size,
+        })
+    }
+
+    fn run(mut self, receiver: mpsc::Receiver<String>) {
+        for mut line in receiver {
+            line.push('\n');
+            if let Err(err) = self.write(&line) {
+                olaos_logs::warn!(
+                    "Failed writing to the RPC access log `{}`: {err}",
+                    self.path.display()
+                );
+            }
+        }
+    }
+
+    fn write(&mut self, line: &str) -> std::io::Result<()> {
+        if self.file_size > 0 && self.file_size + line.len() as u64 > self.max_file_size {
+            self.rotate()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.file_size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Moves the current file to `{path}.1` (replacing the previously rotated file)
+    /// and starts a new one.
+    fn rotate(&mut self) -> std::io::Result<()> {
+        std::fs::rename(&self.path, rotated_path(&self.path))?;
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.file_size = 0;
+        Ok(())
+    }
+}
+
+fn rotated_path(path: &Path) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(".1");
+    rotated.into()
+}
+
+/// Returns the rightmost `x-forwarded-for` address not belonging to a trusted proxy. Entries
+/// to the left of it could've been set by the caller, so they are never used.
+fn parse_caller_ip<B>(
+    request: &hyper::Request<B>,
+    trusted_proxies: &HashSet<IpAddr>,
+) -> Option<IpAddr> {
+    if trusted_proxies.is_empty() {
+        return None;
+    }
+    let forwarded_for = request.headers().get("x-forwarded-for")?.to_str().ok()?;
+    for entry in forwarded_for.rsplit(',') {
+        let ip: IpAddr = entry.trim().parse().ok()?;
+        if !trusted_proxies.contains(&ip) {
+            return Some(ip);
+        }
+    }
+    None
+}
+
+/// HTTP middleware making the caller IP available to [`AccessLogMiddleware`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CallerIpLayer {
+    trusted_proxies: Arc<HashSet<IpAddr>>,
+}
+
+impl CallerIpLayer {
+    pub(crate) fn new(access_log: Option<&AccessLog>) -> Self {
+        Self {
+            trusted_proxies: access_log
+                .map(|access_log| access_log.trusted_proxies.clone())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl<S> Layer<S> for CallerIpLayer {
+    type Service = CallerIpService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CallerIpService {
+            inner,
+            trusted_proxies: self.trusted_proxies.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct CallerIpService<S> {
+    inner: S,
+    trusted_proxies: Arc<HashSet<IpAddr>>,
+}
+
+impl<S, B> Service<hyper::Request<B>> for CallerIpService<S>
+where
+    S: Service<hyper::Request<B>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: hyper::Request<B>) -> Self::Future {
+        let caller_ip = parse_caller_ip(&request, &self.trusted_proxies);
+        Box::pin(CALLER_IP.scope(caller_ip, self.inner.call(request)))
+    }
+}
+
+/// RPC middleware recording calls to the [`AccessLog`], if it's enabled.
+pub(crate) struct AccessLogMiddleware<S> {
+    inner: S,
+    access_log: Option<Arc<AccessLog>>,
+    transport: &'static str,
+}
+
+impl<S> AccessLogMiddleware<S> {
+    pub(crate) fn new(
+        inner: S,
+        access_log: Option<Arc<AccessLog>>,
+        transport: &'static str,
+    ) -> Self {
+        Self {
+            inner,
+            access_log,
+            transport,
+        }
+    }
+}
+
+impl<'a, S> RpcServiceT<'a> for AccessLogMiddleware<S>
+where
+    S: Send + Sync + RpcServiceT<'a>,
+    S::Future: 'a,
+{
+    type Future = BoxFuture<'a, MethodResponse>;
+
+    fn call(&self, request: Request<'a>) -> Self::Future {
+        let Some(access_log) = self.access_log.clone() else {
+            return Box::pin(self.inner.call(request));
+        };
+
+        let sample = access_log.sample();
+        let method = request.method_name().to_owned();
+        let params_hash = request
+            .params()
+            .as_str()
+            .map(|params| H256(keccak256(params.as_bytes())));
+        let transport = self.transport;
+        let started_at = Instant::now();
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            let response = response.await;
+            let error_code = response.as_error_code();
+            if access_log.should_record(sample, error_code.is_some()) {
+                access_log.record(AccessLogEntry {
+                    timestamp_ms: millis_since_epoch(),
+                    transport,
+                    correlation_id: current_correlation_id(),
+                    method,
+                    params_hash,
+                    caller_ip: CALLER_IP.try_with(|&ip| ip).ok().flatten(),
+                    latency_us: started_at.elapsed().as_micros() as u64,
+                    error_code,
+                });
+            }
+            response
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacting_ips() {
+        let ip: IpAddr = "192.168.10.42".parse().unwrap();
+        assert_eq!(redact_ip(ip), "192.168.10.0".parse::<IpAddr>().unwrap());
+        let ip: IpAddr = "2001:db8:85a3:8d3:1319:8a2e:370:7348".parse().unwrap();
+        assert_eq!(redact_ip(ip), "2001:db8:85a3::".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn parsing_caller_ip() {
+        let trusted_proxies = HashSet::from(["10.0.0.1".parse().unwrap()]);
+        let request_with = |forwarded_for: &str| {
+            hyper::Request::builder()
+                .header("x-forwarded-for", forwarded_for)
+                .body(())
+                .unwrap()
+        };
+
+        let request = request_with("203.0.113.7, 10.0.0.1");
+        assert_eq!(
+            parse_caller_ip(&request, &trusted_proxies),
+            Some("203.0.113.7".parse().unwrap())
+        );
+        // Entries prepended by the caller are ignored.
+        let request = request_with("1.2.3.4, 203.0.113.7, 10.0.0.1");
+        assert_eq!(
+            parse_caller_ip(&request, &trusted_proxies),
+            Some("203.0.113.7".parse().unwrap())
+        );
+        // The header is ignored if no proxies are trusted.
+        assert_eq!(parse_caller_ip(&request, &HashSet::new()), None);
+        let request = request_with("10.0.0.1");
+        assert_eq!(parse_caller_ip(&request, &trusted_proxies), None);
+        let request = hyper::Request::builder().body(()).unwrap();
+        assert_eq!(parse_caller_ip(&request, &trusted_proxies), None);
+    }
+
+    #[test]
+    fn sampling_calls() {
+        let access_log = AccessLog::new(0.0, 1.0, vec![], None, 0, false).unwrap();
+        assert!((0..1_000).all(|_| !access_log.should_record(access_log.sample(), false)));
+        assert!((0..1_000).all(|_| access_log.should_record(access_log.sample(), true)));
+        let access_log = AccessLog::new(1.0, 0.0, vec![], None, 0, false).unwrap();
+        assert!((0..1_000).all(|_| access_log.should_record(access_log.sample(), false)));
+        assert!((0..1_000).all(|_| !access_log.should_record(access_log.sample(), true)));
+    }
+}
@@ -0,0 +1,145 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        OnceLock,
+    },
+    task::{Context, Poll},
+};
+
+use hyper::header::HeaderValue;
+use jsonrpsee::{server::middleware::rpc::RpcServiceT, types::Request};
+use tokio::task::futures::TaskLocalFuture;
+use tower::{Layer, Service};
+use tracing::{instrument::Instrumented, Instrument};
+
+/// Header used to pass a correlation ID in and out of the HTTP API.
+pub(crate) const CORRELATION_ID_HEADER: &str = "x-request-id";
+
+/// Client-provided IDs longer than this are ignored and replaced with a generated one.
+const MAX_CORRELATION_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static CORRELATION_ID: String;
+}
+
+/// Returns the correlation ID of the request currently being processed, if any.
+pub(crate) fn current_correlation_id() -> Option<String> {
+    CORRELATION_ID.try_with(Clone::clone).ok()
+}
+
+/// Runs `f` with the correlation ID of the current request (if any) set.
+/// Used to carry the ID over to blocking tasks, e.g. VM execution in the sandbox.
+pub(crate) fn with_correlation_id<R>(correlation_id: Option<String>, f: impl FnOnce() -> R) -> R {
+    match correlation_id {
+        Some(correlation_id) => CORRELATION_ID.sync_scope(correlation_id, f),
+        None => f(),
+    }
+}
+
+fn generate_correlation_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    static PROCESS_TAG: OnceLock<u64> = OnceLock::new();
+
+    let process_tag = PROCESS_TAG.get_or_init(|| {
+        let since_epoch = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        since_epoch.as_millis() as u64 ^ u64::from(std::process::id())
+    });
+    let seq = COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{process_tag:x}-{seq:x}")
+}
+
+fn parse_correlation_id(value: &HeaderValue) -> Option<String> {
+    let value = value.to_str().ok()?;
+    let is_valid = !value.is_empty()
+        && value.len() <= MAX_CORRELATION_ID_LEN
+        && value.bytes().all(|byte| byte.is_ascii_graphic());
+    is_valid.then(|| value.to_owned())
+}
+
+/// HTTP middleware extracting the correlation ID from the `x-request-id` header (or generating
+/// a new one) and echoing it back in the response.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct CorrelationIdLayer;
+
+impl<S> Layer<S> for CorrelationIdLayer {
+    type Service = CorrelationIdService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CorrelationIdService { inner }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct CorrelationIdService<S> {
+    inner: S,
+}
+
+impl<S, B, R> Service<hyper::Request<B>> for CorrelationIdService<S>
+where
+    S: Service<hyper::Request<B>, Response = hyper::Response<R>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: hyper::Request<B>) -> Self::Future {
+        let correlation_id = request
+            .headers()
+            .get(CORRELATION_ID_HEADER)
+            .and_then(parse_correlation_id)
+            .unwrap_or_else(generate_correlation_id);
+        let header_value = HeaderValue::from_str(&correlation_id).ok();
+
+        let response = CORRELATION_ID.scope(correlation_id, self.inner.call(request));
+        Box::pin(async move {
+            let mut response = response.await?;
+            if let Some(header_value) = header_value {
+                response
+                    .headers_mut()
+                    .insert(CORRELATION_ID_HEADER, header_value);
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// RPC middleware wrapping every method call into a tracing span carrying the correlation ID,
+/// so that logs emitted by the DAL and the VM sandbox can be matched to the originating request.
+///
+/// For HTTP, the ID is taken from [`CorrelationIdLayer`]; WS calls get a fresh ID per call.
+#[derive(Debug, Clone)]
+pub(crate) struct CorrelationIdMiddleware<S> {
+    inner: S,
+}
+
+impl<S> CorrelationIdMiddleware<S> {
+    pub(crate) fn new(inner: S) -> Self {
+        Self { inner }
+    }
+}
+
+impl<'a, S> RpcServiceT<'a> for CorrelationIdMiddleware<S>
+where
+    S: Send + Sync + RpcServiceT<'a>,
+{
+    type Future = TaskLocalFuture<String, Instrumented<S::Future>>;
+
+    fn call(&self, request: Request<'a>) -> Self::Future {
+        let correlation_id = current_correlation_id().unwrap_or_else(generate_correlation_id);
+        let span = tracing::info_span!(
+            "rpc_call",
+            method = %request.method_name(),
+            correlation_id = %correlation_id
+        );
+        CORRELATION_ID.scope(correlation_id, self.inner.call(request).instrument(span))
+    }
+}
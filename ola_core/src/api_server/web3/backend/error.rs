@@ -1,5 +1,8 @@
+use ola_dal::DalError;
 use ola_web3_decl::error::Web3Error;
 
+use crate::utils::blocking_pool::PoolSaturated;
+
 pub fn internal_error(method_name: &str, error: impl ToString) -> Web3Error {
     olaos_logs::error!(
         "Internal error in method {}: {}",
@@ -9,3 +12,26 @@ pub fn internal_error(method_name: &str, error: impl ToString) -> Web3Error {
 
     Web3Error::InternalError
 }
+
+/// Converts a DAL error to an API error. Transient errors are reported as such, so that clients
+/// know the call can be retried.
+pub fn dal_error(method_name: &str, error: DalError) -> Web3Error {
+    if error.is_retriable() {
+        olaos_logs::warn!("Transient DB error in method {method_name}: {error}");
+        Web3Error::StorageUnavailable
+    } else {
+        internal_error(method_name, error)
+    }
+}
+
+/// Converts a Merkle tree read error to an API error. Reads rejected by a saturated thread pool
+/// are reported as such, so that clients know the call can be retried.
+pub fn tree_error(method_name: &str, error: anyhow::Error) -> Web3Error {
+    match error.downcast::<PoolSaturated>() {
+        Ok(err) => {
+            olaos_logs::warn!("Tree read rejected in method {method_name}: {err}");
+            err.into()
+        }
+        Err(error) => internal_error(method_name, error),
+    }
+}
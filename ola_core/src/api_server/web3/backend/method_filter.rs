@@ -0,0 +1,157 @@
+//! Filtering of served JSON-RPC methods.
+//!
+//! Methods can be allowlisted or denylisted in the config (`web3_json_rpc.methods_allowlist`
+//! and `methods_denylist`), and whole namespaces can be disabled at runtime via
+//! `ola_setNamespaceEnabled`, e.g. to shed expensive endpoints during an incident without
+//! restarting the node. Filtered requests are rejected by [`MethodFilterMiddleware`] before
+//! they are dispatched. The filter is shared by all servers of the node; IPC requests
+//! are not filtered.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, RwLock},
+};
+
+use jsonrpsee::{
+    server::middleware::rpc::{layer::ResponseFuture, RpcServiceT},
+    types::{ErrorObject, Request},
+    MethodResponse,
+};
+
+use super::METHOD_NOT_SUPPORTED_CODE;
+use crate::api_server::web3::Namespace;
+
+#[derive(Debug, Default)]
+pub struct MethodFilter {
+    allowlist: Option<HashSet<String>>,
+    denylist: HashSet<String>,
+    method_namespaces: RwLock<HashMap<&'static str, Namespace>>,
+    disabled_namespaces: RwLock<HashSet<Namespace>>,
+}
+
+impl MethodFilter {
+    pub fn new(allowlist: Option<Vec<String>>, denylist: Vec<String>) -> Self {
+        Self {
+            allowlist: allowlist.map(|methods| methods.into_iter().collect()),
+            denylist: denylist.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Records the namespace of methods merged into an RPC module.
+    pub(crate) fn register(
+        &self,
+        namespace: Namespace,
+        methods: impl Iterator<Item = &'static str>,
+    ) {
+        let mut method_namespaces = self.method_namespaces.write().expect("poisoned");
+        method_namespaces.extend(methods.map(|method| (method, namespace)));
+    }
+
+    /// Enables or disables all methods of `namespace`; returns the disabled namespaces.
+    pub fn set_namespace_enabled(&self, namespace: Namespace, enabled: bool) -> Vec<Namespace> {
+        let mut disabled = self.disabled_namespaces.write().expect("poisoned");
+        if enabled {
+            disabled.remove(&namespace);
+        } else {
+            disabled.insert(namespace);
+        }
+        let mut disabled: Vec<_> = disabled.iter().copied().collect();
+        disabled.sort_unstable_by_key(|namespace| namespace.as_str());
+        disabled
+    }
+
+    /// Returns an error message if `method` must not be served. Methods not served by the node
+    /// are passed through, so that they are rejected by `jsonrpsee` as unknown.
+    fn check(&self, method: &str) -> Result<(), String> {
+        let Some(namespace) = self
+            .method_namespaces
+            .read()
+            .expect("poisoned")
+            .get(method)
+            .copied()
+        else {
+            return Ok(());
+        };
+
+        let is_allowed = self
+            .allowlist
+            .as_ref()
+            .map_or(true, |allowlist| allowlist.contains(method));
+        if !is_allowed || self.denylist.contains(method) {
+            return Err(format!("Method `{method}` is disabled on this node"));
+        }
+        if self
+            .disabled_namespaces
+            .read()
+            .expect("poisoned")
+            .contains(&namespace)
+        {
+            return Err(format!(
+                "Namespace `{}` is temporarily disabled on this node",
+                namespace.as_str()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Middleware rejecting requests to methods filtered by [`MethodFilter`].
+pub(crate) struct MethodFilterMiddleware<S> {
+    inner: S,
+    filter: Arc<MethodFilter>,
+}
+
+impl<S> MethodFilterMiddleware<S> {
+    pub(crate) fn new(inner: S, filter: Arc<MethodFilter>) -> Self {
+        Self { inner, filter }
+    }
+}
+
+impl<'a, S> RpcServiceT<'a> for MethodFilterMiddleware<S>
+where
+    S: Send + Clone + Sync + RpcServiceT<'a>,
+{
+    type Future = ResponseFuture<S::Future>;
+
+    fn call(&self, request: Request<'a>) -> Self::Future {
+        if let Err(message) = self.filter.check(request.method_name()) {
+            metrics::counter!("api.web3.filtered_requests", 1);
+            let response = MethodResponse::error(
+                request.id,
+                ErrorObject::owned(METHOD_NOT_SUPPORTED_CODE, message, None::<()>),
+            );
+            return ResponseFuture::ready(response);
+        }
+        ResponseFuture::future(self.inner.call(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filtering_methods() {
+        let filter = MethodFilter::new(
+            Some(vec!["eth_call".to_owned(), "eth_chainId".to_owned()]),
+            vec!["eth_chainId".to_owned()],
+        );
+        filter.register(Namespace::Eth, ["eth_call", "eth_chainId"].into_iter());
+        filter.register(Namespace::Net, ["net_version"].into_iter());
+
+        assert!(filter.check("eth_call").is_ok());
+        assert!(filter.check("eth_chainId").is_err());
+        assert!(filter.check("net_version").is_err());
+        assert!(filter.check("eth_unknown").is_ok());
+
+        assert_eq!(
+            filter.set_namespace_enabled(Namespace::Eth, false),
+            [Namespace::Eth]
+        );
+        let err = filter.check("eth_call").unwrap_err();
+        assert!(err.contains("`eth`"), "{err}");
+        assert_eq!(filter.set_namespace_enabled(Namespace::Eth, true), []);
+        assert!(filter.check("eth_call").is_ok());
+    }
+}
@@ -1,15 +1,56 @@
 use jsonrpsee::types::{error::ErrorCode, ErrorObjectOwned};
 use ola_web3_decl::error::Web3Error;
+use serde_json::json;
 
+use self::correlation_id::current_correlation_id;
+
+pub mod access_log;
 pub mod batch_limiter_middleware;
+pub(crate) mod correlation_id;
 pub mod error;
+pub mod method_filter;
 pub mod namespaces;
 
+/// Error codes defined by EIP-1898 for requests referencing a block by hash.
+pub(crate) const RESOURCE_NOT_FOUND_CODE: i32 = -32001;
+const INVALID_INPUT_CODE: i32 = -32000;
+/// Error code defined by EIP-1474 for requests to temporarily unavailable resources.
+const RESOURCE_UNAVAILABLE_CODE: i32 = -32002;
+/// Error code defined by EIP-1474 for methods not supported by the node.
+const METHOD_NOT_SUPPORTED_CODE: i32 = -32004;
+/// Error code defined by EIP-1474 for requests exceeding a limit of the node.
+const LIMIT_EXCEEDED_CODE: i32 = -32005;
+
 pub fn into_rpc_error(err: Web3Error) -> ErrorObjectOwned {
     ErrorObjectOwned::owned(
         match err {
             Web3Error::InternalError => ErrorCode::InternalError.code(),
-            Web3Error::NoBlock | Web3Error::InvalidChainId(_) => ErrorCode::InvalidParams.code(),
+            Web3Error::UnknownBlockHash(_) => RESOURCE_NOT_FOUND_CODE,
+            Web3Error::NonCanonicalBlock(_) => INVALID_INPUT_CODE,
+            Web3Error::ReadOnlyNode | Web3Error::CalldataScreeningDisabled => {
+                METHOD_NOT_SUPPORTED_CODE
+            }
+            Web3Error::StorageUnavailable | Web3Error::ThreadPoolSaturated(_) => {
+                RESOURCE_UNAVAILABLE_CODE
+            }
+            Web3Error::ExecutionBudgetExceeded(..)
+            | Web3Error::ExecutionCyclesExceeded(..)
+            | Web3Error::CallOutputTooLarge(..)
+            | Web3Error::TooManyBlockTransactions(..)
+            | Web3Error::LogsLimitExceeded(..)
+            | Web3Error::BlockLogsLimitExceeded(..)
+            | Web3Error::LogsBlockRangeExceeded(..) => LIMIT_EXCEEDED_CODE,
+            Web3Error::NoBlock
+            | Web3Error::InvalidChainId(_)
+            | Web3Error::TooManyAddresses(_)
+            | Web3Error::InvalidTimeRange(..)
+            | Web3Error::InvalidBlockRange(..)
+            | Web3Error::ForeignPageCursor
+            | Web3Error::TooManyTopics
+            | Web3Error::InvalidFilterBlockHash
+            | Web3Error::InvalidNamespace(_)
+            | Web3Error::InvalidAttestation(_)
+            | Web3Error::InvalidRewardPercentiles(_) => ErrorCode::InvalidParams.code(),
             Web3Error::SerializationError(_) | Web3Error::SubmitTransactionError(_, _) => 3,
             _ => ErrorCode::InternalError.code(),
         },
@@ -18,8 +59,161 @@ pub fn into_rpc_error(err: Web3Error) -> ErrorObjectOwned {
             _ => err.to_string(),
         },
         match err {
-            Web3Error::SubmitTransactionError(_, data) => Some(format!("0x{}", hex::encode(data))),
-            _ => None,
+            Web3Error::SubmitTransactionError(_, data) => {
+                Some(json!(format!("0x{}", hex::encode(data))))
+            }
+            Web3Error::ExecutionBudgetExceeded(budget, limit_ms) => Some(json!({
+                "reason": "outOfBudget",
+                "budget": budget,
+                "limitMs": limit_ms,
+            })),
+            Web3Error::ExecutionCyclesExceeded(budget, limit_cycles) => Some(json!({
+                "reason": "outOfBudget",
+                "budget": budget,
+                "limitCycles": limit_cycles,
+            })),
+            Web3Error::CallOutputTooLarge(truncated, limit) => Some(json!({
+                "reason": "outputTooLarge",
+                "limit": limit,
+                "output": truncated,
+            })),
+            Web3Error::TooManyBlockTransactions(tx_count, limit) => Some(json!({
+                "reason": "tooManyTransactions",
+                "transactionCount": tx_count,
+                "limit": limit,
+                "method": "ola_getBlockTransactions",
+            })),
+            Web3Error::LogsLimitExceeded(limit, from, to) => Some(json!({
+                "reason": "tooManyLogs",
+                "limit": limit,
+                "suggestedRange": [format!("{from:#x}"), format!("{to:#x}")],
+            })),
+            Web3Error::BlockLogsLimitExceeded(limit, block) => Some(json!({
+                "reason": "tooManyLogs",
+                "limit": limit,
+                "block": format!("{block:#x}"),
+            })),
+            Web3Error::LogsBlockRangeExceeded(max_range, from, to) => Some(json!({
+                "reason": "blockRangeTooWide",
+                "limit": max_range,
+                "suggestedRange": [format!("{from:#x}"), format!("{to:#x}")],
+            })),
+            _ => current_correlation_id()
+                .map(|correlation_id| json!({ "correlationId": correlation_id })),
         },
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use ola_dal::DalError;
+    use ola_types::{api::TruncatedOutput, H256};
+
+    use super::*;
+
+    #[test]
+    fn block_hash_errors_have_eip1898_codes() {
+        let hash = H256::repeat_byte(1);
+        let err = into_rpc_error(Web3Error::UnknownBlockHash(hash));
+        assert_eq!(err.code(), RESOURCE_NOT_FOUND_CODE);
+        let err = into_rpc_error(Web3Error::NonCanonicalBlock(hash));
+        assert_eq!(err.code(), INVALID_INPUT_CODE);
+        let err = into_rpc_error(Web3Error::NoBlock);
+        assert_eq!(err.code(), ErrorCode::InvalidParams.code());
+    }
+
+    #[test]
+    fn read_only_node_error_has_dedicated_code() {
+        let err = into_rpc_error(Web3Error::ReadOnlyNode);
+        assert_eq!(err.code(), METHOD_NOT_SUPPORTED_CODE);
+        assert_eq!(
+            err.message(),
+            "Transaction submission is disabled on read-only nodes"
+        );
+    }
+
+    #[test]
+    fn dal_errors_have_dedicated_codes() {
+        let err = into_rpc_error(Web3Error::StorageUnavailable);
+        assert_eq!(err.code(), RESOURCE_UNAVAILABLE_CODE);
+        let err = error::dal_error("test", DalError::NotFound);
+        assert_eq!(into_rpc_error(err).code(), ErrorCode::InternalError.code());
+    }
+
+    #[test]
+    fn out_of_budget_error_is_structured() {
+        let err = into_rpc_error(Web3Error::ExecutionBudgetExceeded("call", 5_000));
+        assert_eq!(err.code(), LIMIT_EXCEEDED_CODE);
+        let data: serde_json::Value = serde_json::from_str(err.data().unwrap().get()).unwrap();
+        assert_eq!(
+            data,
+            json!({ "reason": "outOfBudget", "budget": "call", "limitMs": 5_000 })
+        );
+
+        let err = into_rpc_error(Web3Error::ExecutionCyclesExceeded("validation", 1_000));
+        assert_eq!(err.code(), LIMIT_EXCEEDED_CODE);
+        let data: serde_json::Value = serde_json::from_str(err.data().unwrap().get()).unwrap();
+        assert_eq!(
+            data,
+            json!({ "reason": "outOfBudget", "budget": "validation", "limitCycles": 1_000 })
+        );
+    }
+
+    #[test]
+    fn output_too_large_error_is_structured() {
+        let truncated = TruncatedOutput {
+            full_size: 2_048,
+            hash: H256::repeat_byte(1),
+            stored: true,
+        };
+        let err = into_rpc_error(Web3Error::CallOutputTooLarge(truncated, 1_024));
+        assert_eq!(err.code(), LIMIT_EXCEEDED_CODE);
+        let data: serde_json::Value = serde_json::from_str(err.data().unwrap().get()).unwrap();
+        assert_eq!(data["reason"], "outputTooLarge");
+        assert_eq!(data["limit"], 1_024);
+        assert_eq!(data["output"]["fullSize"], 2_048);
+        assert_eq!(data["output"]["stored"], true);
+    }
+
+    #[test]
+    fn too_many_block_transactions_error_points_to_pagination() {
+        let err = into_rpc_error(Web3Error::TooManyBlockTransactions(5_000, 1_000));
+        assert_eq!(err.code(), LIMIT_EXCEEDED_CODE);
+        assert!(err.message().contains("ola_getBlockTransactions"));
+        let data: serde_json::Value = serde_json::from_str(err.data().unwrap().get()).unwrap();
+        assert_eq!(data["reason"], "tooManyTransactions");
+        assert_eq!(data["transactionCount"], 5_000);
+        assert_eq!(data["method"], "ola_getBlockTransactions");
+    }
+
+    #[test]
+    fn logs_limit_error_suggests_block_range() {
+        let err = into_rpc_error(Web3Error::LogsLimitExceeded(10_000, 16, 31));
+        assert_eq!(err.code(), LIMIT_EXCEEDED_CODE);
+        assert!(err.message().contains("[0x10, 0x1f]"), "{}", err.message());
+        let data: serde_json::Value = serde_json::from_str(err.data().unwrap().get()).unwrap();
+        assert_eq!(
+            data,
+            json!({ "reason": "tooManyLogs", "limit": 10_000, "suggestedRange": ["0x10", "0x1f"] })
+        );
+
+        let err = into_rpc_error(Web3Error::BlockLogsLimitExceeded(10_000, 16));
+        assert_eq!(err.code(), LIMIT_EXCEEDED_CODE);
+        let data: serde_json::Value = serde_json::from_str(err.data().unwrap().get()).unwrap();
+        assert_eq!(
+            data,
+            json!({ "reason": "tooManyLogs", "limit": 10_000, "block": "0x10" })
+        );
+
+        let err = into_rpc_error(Web3Error::LogsBlockRangeExceeded(100, 16, 115));
+        assert_eq!(err.code(), LIMIT_EXCEEDED_CODE);
+        assert!(err.message().contains("[0x10, 0x73]"), "{}", err.message());
+        let data: serde_json::Value = serde_json::from_str(err.data().unwrap().get()).unwrap();
+        assert_eq!(data["reason"], "blockRangeTooWide");
+
+        let err = into_rpc_error(Web3Error::InvalidFilterBlockHash);
+        assert_eq!(err.code(), ErrorCode::InvalidParams.code());
+        let err = into_rpc_error(Web3Error::TooManyTopics);
+        assert_eq!(err.code(), ErrorCode::InvalidParams.code());
+    }
+}
@@ -0,0 +1,88 @@
+use jsonrpsee::core::{async_trait, RpcResult};
+use ola_config::dump::ConfigDump;
+use ola_types::api::{
+    AuditAction, CalldataScreeningRules, ContractResourceKind, ContractResourceUsage,
+    MempoolStatus, StorageSample, VmConcurrencyStatus,
+};
+use ola_web3_decl::{
+    namespaces::admin::AdminNamespaceServer,
+    types::{AuditLogPage, PageCursor},
+};
+
+use crate::api_server::web3::{backend::into_rpc_error, namespaces::admin::AdminNamespace};
+
+#[async_trait]
+impl AdminNamespaceServer for AdminNamespace {
+    async fn get_mempool_status(
+        &self,
+        next_transactions_limit: Option<usize>,
+        accounts_limit: Option<usize>,
+    ) -> RpcResult<MempoolStatus> {
+        self.get_mempool_status_impl(next_transactions_limit, accounts_limit)
+            .map_err(into_rpc_error)
+    }
+
+    async fn get_vm_concurrency(&self) -> RpcResult<VmConcurrencyStatus> {
+        self.get_vm_concurrency_impl().map_err(into_rpc_error)
+    }
+
+    async fn get_config(&self) -> RpcResult<ConfigDump> {
+        self.get_config_impl().map_err(into_rpc_error)
+    }
+
+    async fn get_top_contracts(
+        &self,
+        from_timestamp: u64,
+        to_timestamp: u64,
+        resource: Option<ContractResourceKind>,
+        limit: Option<usize>,
+    ) -> RpcResult<Vec<ContractResourceUsage>> {
+        self.get_top_contracts_impl(from_timestamp, to_timestamp, resource, limit)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn get_audit_log(
+        &self,
+        action: Option<AuditAction>,
+        cursor: Option<PageCursor<u64>>,
+        limit: Option<usize>,
+    ) -> RpcResult<AuditLogPage> {
+        self.get_audit_log_impl(action, cursor, limit)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn set_namespace_enabled(
+        &self,
+        namespace: String,
+        enabled: bool,
+    ) -> RpcResult<Vec<String>> {
+        self.set_namespace_enabled_impl(namespace, enabled)
+            .map_err(into_rpc_error)
+    }
+
+    async fn sample_storage_proofs(
+        &self,
+        seed: u64,
+        count: Option<usize>,
+    ) -> RpcResult<StorageSample> {
+        self.sample_storage_proofs_impl(seed, count)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn get_calldata_screening_rules(&self) -> RpcResult<CalldataScreeningRules> {
+        self.get_calldata_screening_rules_impl()
+            .map_err(into_rpc_error)
+    }
+
+    async fn set_calldata_screening_rules(
+        &self,
+        rules: CalldataScreeningRules,
+    ) -> RpcResult<CalldataScreeningRules> {
+        self.set_calldata_screening_rules_impl(rules)
+            .await
+            .map_err(into_rpc_error)
+    }
+}
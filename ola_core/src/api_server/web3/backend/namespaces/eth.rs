@@ -1,9 +1,10 @@
 use jsonrpsee::core::{async_trait, RpcResult};
 use ola_types::api::{
-    Block, BlockId, BlockNumber, Transaction, TransactionId, TransactionReceipt, TransactionVariant,
+    Block, BlockId, BlockNumber, FeeHistory, Log, SyncState, Transaction, TransactionId,
+    TransactionReceipt, TransactionVariant,
 };
 use ola_types::{api::BlockIdVariant, Address, H256, U256, U64};
-use ola_web3_decl::namespaces::eth::EthNamespaceServer;
+use ola_web3_decl::{namespaces::eth::EthNamespaceServer, types::Filter};
 use web3::types::Index;
 
 use crate::api_server::web3::{backend::into_rpc_error, namespaces::eth::EthNamespace};
@@ -67,11 +68,26 @@ impl EthNamespaceServer for EthNamespace {
         address: Address,
         block: Option<BlockIdVariant>,
     ) -> RpcResult<u32> {
-        self.get_transaction_count_impl(address, block.map(Into::into))
+        self.get_transaction_count_impl(address, block)
             .await
             .map_err(into_rpc_error)
     }
 
+    async fn get_storage_at(
+        &self,
+        address: Address,
+        idx: U256,
+        block: Option<BlockIdVariant>,
+    ) -> RpcResult<H256> {
+        self.get_storage_at_impl(address, idx, block)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn get_logs(&self, filter: Filter) -> RpcResult<Vec<Log>> {
+        self.get_logs_impl(filter).await.map_err(into_rpc_error)
+    }
+
     async fn get_transaction_by_hash(&self, hash: H256) -> RpcResult<Option<Transaction>> {
         self.get_transaction_impl(TransactionId::Hash(hash))
             .await
@@ -107,4 +123,27 @@ impl EthNamespaceServer for EthNamespace {
     async fn protocol_version(&self) -> RpcResult<String> {
         Ok(self.protocol_version())
     }
+
+    async fn syncing(&self) -> RpcResult<SyncState> {
+        self.syncing_impl().await.map_err(into_rpc_error)
+    }
+
+    async fn accounts(&self) -> RpcResult<Vec<Address>> {
+        Ok(self.accounts_impl())
+    }
+
+    async fn gas_price(&self) -> RpcResult<U256> {
+        Ok(self.gas_price_impl())
+    }
+
+    async fn fee_history(
+        &self,
+        block_count: U64,
+        newest_block: BlockNumber,
+        reward_percentiles: Vec<f32>,
+    ) -> RpcResult<FeeHistory> {
+        self.fee_history_impl(block_count, newest_block, reward_percentiles)
+            .await
+            .map_err(into_rpc_error)
+    }
 }
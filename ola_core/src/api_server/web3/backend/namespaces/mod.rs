@@ -1,3 +1,7 @@
+pub mod admin;
 pub mod eth;
 pub mod net;
 pub mod ola;
+pub mod txpool;
+pub mod verifier;
+pub mod web3;
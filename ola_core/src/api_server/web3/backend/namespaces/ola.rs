@@ -1,16 +1,24 @@
 use jsonrpsee::core::{async_trait, RpcResult};
 use ola_types::api::proof_offchain_verification::OffChainVerificationResult;
 use ola_types::api::{
-    proof_offchain_verification::L1BatchDetailsWithOffchainVerification, BlockDetails,
-    BridgeAddresses, L1BatchDetails, L2ToL1LogProof, Proof, ProtocolVersion,
+    proof_offchain_verification::L1BatchDetailsWithOffchainVerification, AccountInfo, BlockDetails,
+    BlockIdVariant, BlockNumber, BridgeAddresses, L1BatchDetails, L2ToL1LogProof, NodeInfo, Proof,
+    ProtocolVersion,
 };
 use ola_types::{
-    api::{TransactionDetails, TransactionReceipt},
+    api::{
+        CallResultWithStats, ReorgedOutBlock, ReorgedOutTransaction, StateOverride, SystemContract,
+        TokenBalance, TokenDetails, TransactionDetails, TransactionInclusionProof,
+        TransactionReceipt, TransactionStatusInfo,
+    },
     request::CallRequest,
     Address, Bytes, L1BatchNumber, MiniblockNumber, H256, U256, U64,
 };
 use ola_web3_decl::namespaces::ola::OlaNamespaceServer;
-use ola_web3_decl::types::Token;
+use ola_web3_decl::types::{
+    BlockTransactionsPage, L1BatchesPage, LogCursorKey, LogsPage, PageCursor, StorageHistoryPage,
+    StorageRangePage, Token, TransactionCursorKey, TransactionsPage,
+};
 use std::collections::HashMap;
 
 use crate::api_server::web3::{backend::into_rpc_error, namespaces::ola::OlaNamespace};
@@ -23,8 +31,48 @@ impl OlaNamespaceServer for OlaNamespace {
             .map_err(into_rpc_error)
     }
 
-    async fn call_transaction(&self, call_request: CallRequest) -> RpcResult<Bytes> {
-        self.call_impl(call_request).await.map_err(into_rpc_error)
+    async fn send_raw_transaction_with_simulation(&self, tx_bytes: Bytes) -> RpcResult<H256> {
+        self.send_raw_transaction_with_simulation_impl(tx_bytes)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn send_scheduled_transaction(
+        &self,
+        tx_bytes: Bytes,
+        execute_after: u64,
+    ) -> RpcResult<H256> {
+        self.send_scheduled_transaction_impl(tx_bytes, execute_after)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn call_transaction(
+        &self,
+        call_request: CallRequest,
+        state_override: Option<StateOverride>,
+        block: Option<BlockNumber>,
+    ) -> RpcResult<Bytes> {
+        self.call_impl(call_request, state_override, block)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn call_transaction_with_stats(
+        &self,
+        call_request: CallRequest,
+        state_override: Option<StateOverride>,
+        block: Option<BlockNumber>,
+    ) -> RpcResult<CallResultWithStats> {
+        self.call_with_stats_impl(call_request, state_override, block)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn get_call_output(&self, hash: H256) -> RpcResult<Option<Bytes>> {
+        self.get_call_output_impl(hash)
+            .await
+            .map_err(into_rpc_error)
     }
 
     async fn get_transaction_details(&self, hash: H256) -> RpcResult<Option<TransactionDetails>> {
@@ -52,6 +100,10 @@ impl OlaNamespaceServer for OlaNamespace {
         Ok(self.l1_chain_id_impl())
     }
 
+    async fn gas_price(&self) -> RpcResult<U256> {
+        Ok(self.gas_price_impl())
+    }
+
     async fn get_l1_batch_number(&self) -> RpcResult<U64> {
         self.get_l1_batch_number_impl()
             .await
@@ -82,6 +134,17 @@ impl OlaNamespaceServer for OlaNamespace {
             .map_err(into_rpc_error)
     }
 
+    async fn get_block_transactions(
+        &self,
+        block: BlockNumber,
+        cursor: Option<PageCursor<TransactionCursorKey>>,
+        limit: Option<U64>,
+    ) -> RpcResult<Option<BlockTransactionsPage>> {
+        self.get_block_transactions_impl(block, cursor, limit)
+            .await
+            .map_err(into_rpc_error)
+    }
+
     async fn get_l1_batch_details(
         &self,
         batch: L1BatchNumber,
@@ -106,4 +169,132 @@ impl OlaNamespaceServer for OlaNamespace {
     ) -> RpcResult<Option<L1BatchDetailsWithOffchainVerification>> {
         todo!()
     }
+
+    async fn get_proof(&self, address: Address, keys: Vec<H256>) -> RpcResult<Proof> {
+        self.get_proof_impl(address, keys)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn node_info(&self) -> RpcResult<NodeInfo> {
+        self.node_info_impl().await.map_err(into_rpc_error)
+    }
+
+    async fn get_accounts_info(&self, addresses: Vec<Address>) -> RpcResult<Vec<AccountInfo>> {
+        self.get_accounts_info_impl(addresses)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn get_transaction_status(&self, hash: H256) -> RpcResult<Option<TransactionStatusInfo>> {
+        self.get_transaction_status_impl(hash)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn get_system_contracts(
+        &self,
+        version_id: Option<u16>,
+    ) -> RpcResult<Vec<SystemContract>> {
+        self.get_system_contracts_impl(version_id)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn get_token_info(&self, address: Address) -> RpcResult<Option<TokenDetails>> {
+        self.get_token_info_impl(address)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn get_token_balances(&self, account: Address) -> RpcResult<Vec<TokenBalance>> {
+        self.get_token_balances_impl(account)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn get_transaction_inclusion_proof(
+        &self,
+        hash: H256,
+    ) -> RpcResult<Option<TransactionInclusionProof>> {
+        self.get_transaction_inclusion_proof_impl(hash)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn get_storage_history(
+        &self,
+        address: Address,
+        key: H256,
+        from_block: MiniblockNumber,
+        to_block: Option<MiniblockNumber>,
+        cursor: Option<PageCursor<MiniblockNumber>>,
+        limit: Option<usize>,
+    ) -> RpcResult<StorageHistoryPage> {
+        self.get_storage_history_impl(address, key, from_block, to_block, cursor, limit)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn get_reorged_out_blocks(
+        &self,
+        number: MiniblockNumber,
+    ) -> RpcResult<Vec<ReorgedOutBlock>> {
+        self.get_reorged_out_blocks_impl(number)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn get_reorged_out_transaction(
+        &self,
+        hash: H256,
+    ) -> RpcResult<Vec<ReorgedOutTransaction>> {
+        self.get_reorged_out_transaction_impl(hash)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn get_logs_page(
+        &self,
+        address: Option<Address>,
+        cursor: Option<PageCursor<LogCursorKey>>,
+        limit: Option<usize>,
+    ) -> RpcResult<LogsPage> {
+        self.get_logs_page_impl(address, cursor, limit)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn get_transactions_by_address(
+        &self,
+        address: Address,
+        cursor: Option<PageCursor<TransactionCursorKey>>,
+        limit: Option<usize>,
+    ) -> RpcResult<TransactionsPage> {
+        self.get_transactions_by_address_impl(address, cursor, limit)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn get_l1_batches(
+        &self,
+        cursor: Option<PageCursor<L1BatchNumber>>,
+        limit: Option<usize>,
+    ) -> RpcResult<L1BatchesPage> {
+        self.get_l1_batches_impl(cursor, limit)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn get_account_storage_range(
+        &self,
+        address: Address,
+        block: Option<BlockIdVariant>,
+        cursor: Option<PageCursor<H256>>,
+        limit: Option<usize>,
+    ) -> RpcResult<StorageRangePage> {
+        self.get_account_storage_range_impl(address, block, cursor, limit)
+            .await
+            .map_err(into_rpc_error)
+    }
 }
@@ -0,0 +1,12 @@
+use jsonrpsee::core::{async_trait, RpcResult};
+use ola_types::api::TxpoolContent;
+use ola_web3_decl::namespaces::txpool::TxpoolNamespaceServer;
+
+use crate::api_server::web3::{backend::into_rpc_error, namespaces::txpool::TxpoolNamespace};
+
+#[async_trait]
+impl TxpoolNamespaceServer for TxpoolNamespace {
+    async fn content(&self) -> RpcResult<TxpoolContent> {
+        self.content_impl().map_err(into_rpc_error)
+    }
+}
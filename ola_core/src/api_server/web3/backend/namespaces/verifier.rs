@@ -0,0 +1,29 @@
+use jsonrpsee::core::{async_trait, RpcResult};
+use ola_types::{
+    api::proof_offchain_verification::{L1BatchAttestations, VerifierAttestation},
+    L1BatchNumber,
+};
+use ola_web3_decl::namespaces::verifier::VerifierNamespaceServer;
+
+use crate::api_server::web3::{backend::into_rpc_error, namespaces::verifier::VerifierNamespace};
+
+#[async_trait]
+impl VerifierNamespaceServer for VerifierNamespace {
+    async fn submit_attestation(
+        &self,
+        attestation: VerifierAttestation,
+    ) -> RpcResult<L1BatchAttestations> {
+        self.submit_attestation_impl(attestation)
+            .await
+            .map_err(into_rpc_error)
+    }
+
+    async fn get_l1_batch_attestations(
+        &self,
+        l1_batch_number: L1BatchNumber,
+    ) -> RpcResult<Option<L1BatchAttestations>> {
+        self.get_l1_batch_attestations_impl(l1_batch_number)
+            .await
+            .map_err(into_rpc_error)
+    }
+}
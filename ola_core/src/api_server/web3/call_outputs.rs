@@ -0,0 +1,149 @@
+//! Full outputs of calls truncated in API responses.
+//!
+//! Outputs are stored in the object store and indexed in the `call_outputs` table, which is used
+//! to keep their total size within the configured quota (the oldest outputs are removed to fit
+//! new ones) and to remove outputs older than the configured retention.
+
+use std::{sync::Arc, time::Duration};
+
+use ola_config::api::Web3JsonRpcConfig;
+use ola_dal::connection::ConnectionPool;
+use ola_types::H256;
+use olaos_object_store::{Bucket, CallOutput, ObjectStore, ObjectStoreError, StoredObject};
+use tokio::sync::watch;
+
+/// Maximum number of expired outputs removed per pruning iteration.
+const PRUNING_CHUNK_SIZE: usize = 1_000;
+const PRUNING_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+pub struct CallOutputStore {
+    object_store: Arc<dyn ObjectStore>,
+    /// Master pool; the output index is written to on each stored output.
+    pool: ConnectionPool,
+    quota_bytes: u64,
+    retention: Duration,
+}
+
+impl CallOutputStore {
+    pub fn new(
+        object_store: Arc<dyn ObjectStore>,
+        pool: ConnectionPool,
+        config: &Web3JsonRpcConfig,
+    ) -> Self {
+        Self {
+            object_store,
+            pool,
+            quota_bytes: config.call_output_quota(),
+            retention: config.call_output_retention(),
+        }
+    }
+
+    /// Stores a full call output, removing the oldest outputs if the quota is exceeded.
+    /// Returns `false` if the output is larger than the entire quota and thus isn't stored.
+    pub async fn put(&self, hash: H256, output: Vec<u8>) -> anyhow::Result<bool> {
+        let size = output.len() as u64;
+        if size > self.quota_bytes {
+            return Ok(false);
+        }
+
+        // The output is indexed before it's stored, so that it's removed by pruning even if
+        // storing fails midway.
+        let mut storage = self.pool.access_storage_tagged("api").await;
+        storage.call_outputs_dal().insert_output(hash, size).await?;
+        let mut evicted = storage
+            .call_outputs_dal()
+            .get_outputs_over_quota(self.quota_bytes)
+            .await?;
+        drop(storage);
+
+        evicted.retain(|&evicted_hash| evicted_hash != hash);
+        if !evicted.is_empty() {
+            self.remove(&evicted).await?;
+            metrics::counter!(
+                "api.web3.call.evicted_outputs",
+                evicted.len() as u64,
+                "reason" => "quota"
+            );
+        }
+        self.object_store.put(hash, &CallOutput(output)).await?;
+        Ok(true)
+    }
+
+    /// Returns the full call output with the specified hash, or `None` if it isn't stored.
+    pub async fn get(&self, hash: H256) -> anyhow::Result<Option<Vec<u8>>> {
+        match self.object_store.get::<CallOutput>(hash).await {
+            Ok(output) => Ok(Some(output.0)),
+            Err(ObjectStoreError::KeyNotFound(_)) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Removes outputs from the object store and then from the index, so that no output
+    /// is left in the store without being indexed.
+    async fn remove(&self, hashes: &[H256]) -> anyhow::Result<()> {
+        for &hash in hashes {
+            let key = CallOutput::encode_key(hash);
+            match self
+                .object_store
+                .remove_raw(Bucket::CallOutputs, &key)
+                .await
+            {
+                Ok(()) | Err(ObjectStoreError::KeyNotFound(_)) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        let mut storage = self.pool.access_storage_tagged("api").await;
+        storage.call_outputs_dal().remove_outputs(hashes).await?;
+        Ok(())
+    }
+
+    /// Removes outputs which exceed the retention. Returns the number of removed outputs.
+    async fn prune_expired(&self) -> anyhow::Result<usize> {
+        let mut storage = self.pool.access_storage_tagged("api").await;
+        let expired = storage
+            .call_outputs_dal()
+            .get_expired_outputs(self.retention, PRUNING_CHUNK_SIZE)
+            .await?;
+        drop(storage);
+
+        if !expired.is_empty() {
+            self.remove(&expired).await?;
+            metrics::counter!(
+                "api.web3.call.evicted_outputs",
+                expired.len() as u64,
+                "reason" => "retention"
+            );
+        }
+        Ok(expired.len())
+    }
+
+    /// Periodically removes outputs which exceed the retention.
+    pub async fn run_pruning(
+        self: Arc<Self>,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        loop {
+            if *stop_receiver.borrow_and_update() {
+                olaos_logs::info!("Stop signal received, call output pruning is shutting down");
+                break;
+            }
+
+            match self.prune_expired().await {
+                // Outputs are removed in chunks, so that the stop signal is checked in between.
+                Ok(PRUNING_CHUNK_SIZE) => continue,
+                Ok(_) => {}
+                // Failures aren't fatal; expired outputs are removed on the next iteration.
+                Err(err) => olaos_logs::warn!("Failed pruning expired call outputs: {err:#}"),
+            }
+            tokio::select! {
+                _ = stop_receiver.changed() => {
+                    olaos_logs::info!("Stop signal received, call output pruning is shutting down");
+                    break;
+                }
+                () = tokio::time::sleep(PRUNING_INTERVAL) => { /* Continue pruning */ }
+            }
+        }
+        Ok(())
+    }
+}
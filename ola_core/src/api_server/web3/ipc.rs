@@ -0,0 +1,224 @@
+//! IPC transport for the JSON-RPC server: a Unix domain socket, or a named pipe on Windows.
+//!
+//! The transport serves the same [`RpcModule`] as the HTTP server. Requests and responses are
+//! newline-delimited JSON; subscription notifications are written to the connection as they
+//! arrive.
+
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use jsonrpsee::RpcModule;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    sync::{mpsc, watch},
+};
+
+/// Capacity of the per-connection queue of outgoing messages.
+const MESSAGE_BUFFER_SIZE: usize = 1_024;
+
+/// IPC endpoint of the JSON-RPC server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IpcEndpoint {
+    /// Path to the Unix domain socket, or the name of the pipe on Windows
+    /// (e.g., `\\.\pipe\olaos`).
+    pub path: PathBuf,
+    /// Permissions of the socket file. Ignored on Windows, where pipes reject remote clients.
+    pub permissions: u32,
+}
+
+#[cfg(unix)]
+pub(super) async fn run_ipc_server(
+    endpoint: IpcEndpoint,
+    rpc: RpcModule<()>,
+    mut stop_receiver: watch::Receiver<bool>,
+) -> anyhow::Result<()> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+    use tokio::net::UnixListener;
+
+    let path = &endpoint.path;
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        // Only a socket left by a previous run is removed; any other file is reported.
+        anyhow::ensure!(
+            metadata.file_type().is_socket(),
+            "IPC path `{}` exists and is not a socket",
+            path.display()
+        );
+        std::fs::remove_file(path)
+            .with_context(|| format!("failed removing stale IPC socket `{}`", path.display()))?;
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed creating directory `{}`", parent.display()))?;
+    }
+    let listener = UnixListener::bind(path)
+        .with_context(|| format!("failed binding IPC socket `{}`", path.display()))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(endpoint.permissions))
+        .with_context(|| format!("failed setting permissions of `{}`", path.display()))?;
+    olaos_logs::info!("Initialized IPC API on {}", path.display());
+
+    loop {
+        tokio::select! {
+            _ = stop_receiver.changed() => break,
+            accepted = listener.accept() => {
+                let (stream, _) = accepted.context("failed accepting IPC connection")?;
+                spawn_connection(stream, rpc.clone(), stop_receiver.clone());
+            }
+        }
+    }
+
+    std::fs::remove_file(path).ok();
+    olaos_logs::info!("IPC JSON-RPC server stopped");
+    Ok(())
+}
+
+#[cfg(windows)]
+pub(super) async fn run_ipc_server(
+    endpoint: IpcEndpoint,
+    rpc: RpcModule<()>,
+    mut stop_receiver: watch::Receiver<bool>,
+) -> anyhow::Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let path = &endpoint.path;
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(path)
+        .with_context(|| format!("failed creating named pipe `{}`", path.display()))?;
+    olaos_logs::info!("Initialized IPC API on {}", path.display());
+
+    loop {
+        tokio::select! {
+            _ = stop_receiver.changed() => break,
+            connected = server.connect() => {
+                connected.context("failed accepting IPC connection")?;
+                // A pipe instance serves a single client, so a new one is created for the next client.
+                let next_server = ServerOptions::new()
+                    .create(path)
+                    .with_context(|| format!("failed creating named pipe `{}`", path.display()))?;
+                let client = std::mem::replace(&mut server, next_server);
+                spawn_connection(client, rpc.clone(), stop_receiver.clone());
+            }
+        }
+    }
+
+    olaos_logs::info!("IPC JSON-RPC server stopped");
+    Ok(())
+}
+
+fn spawn_connection<S>(stream: S, rpc: RpcModule<()>, stop_receiver: watch::Receiver<bool>)
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    tokio::spawn(async move {
+        if let Err(err) = serve_connection(stream, rpc, stop_receiver).await {
+            olaos_logs::warn!("IPC connection terminated with error: {err:#}");
+        }
+    });
+}
+
+async fn serve_connection<S>(
+    stream: S,
+    rpc: RpcModule<()>,
+    mut stop_receiver: watch::Receiver<bool>,
+) -> anyhow::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let (message_sender, mut message_receiver) = mpsc::channel::<String>(MESSAGE_BUFFER_SIZE);
+    let writer_task = tokio::spawn(async move {
+        while let Some(message) = message_receiver.recv().await {
+            writer.write_all(message.as_bytes()).await?;
+            writer.write_all(b"\n").await?;
+            writer.flush().await?;
+        }
+        anyhow::Ok(())
+    });
+
+    let mut lines = BufReader::new(reader).lines();
+    let mut notification_tasks = vec![];
+    loop {
+        let line = tokio::select! {
+            _ = stop_receiver.changed() => break,
+            line = lines.next_line() => line.context("failed reading IPC request")?,
+        };
+        let Some(line) = line else {
+            break; // The client has closed the connection.
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (response, mut notifications) =
+            match rpc.raw_json_request(&line, MESSAGE_BUFFER_SIZE).await {
+                Ok(output) => output,
+                Err(err) => {
+                    olaos_logs::debug!("Malformed IPC request: {err}");
+                    let response = jsonrpsee::types::ErrorObject::from(
+                        jsonrpsee::types::ErrorCode::ParseError,
+                    );
+                    let response = serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": null,
+                        "error": response,
+                    });
+                    (response.to_string(), mpsc::channel(1).1)
+                }
+            };
+        if message_sender.send(response).await.is_err() {
+            break;
+        }
+        notification_tasks.retain(|task: &tokio::task::JoinHandle<()>| !task.is_finished());
+        let notification_sender = message_sender.clone();
+        notification_tasks.push(tokio::spawn(async move {
+            while let Some(notification) = notifications.recv().await {
+                if notification_sender.send(notification).await.is_err() {
+                    break;
+                }
+            }
+        }));
+    }
+
+    // Dropping notification receivers closes subscriptions of the connection.
+    for task in notification_tasks {
+        task.abort();
+    }
+    drop(message_sender);
+    writer_task.await.context("IPC writer panicked")?
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use tokio::net::UnixStream;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn serving_requests_over_ipc() {
+        let mut rpc = RpcModule::new(());
+        rpc.register_method("ola_echo", |params, _| params.one::<u64>())
+            .unwrap();
+        let (_stop_sender, stop_receiver) = watch::channel(false);
+        let (client, server) = UnixStream::pair().unwrap();
+        let server_task = tokio::spawn(serve_connection(server, rpc, stop_receiver));
+
+        let (reader, mut writer) = tokio::io::split(client);
+        let mut lines = BufReader::new(reader).lines();
+        writer
+            .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"ola_echo\",\"params\":[42]}\n")
+            .await
+            .unwrap();
+        let response: serde_json::Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(response["result"], 42);
+
+        writer.write_all(b"not json\n").await.unwrap();
+        let response: serde_json::Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(response["error"]["code"], -32700);
+
+        writer.shutdown().await.unwrap();
+        server_task.await.unwrap().unwrap();
+    }
+}
@@ -1,23 +1,32 @@
-use std::{net::SocketAddr, num::NonZeroU32, time::Duration};
+use std::{net::SocketAddr, num::NonZeroU32, sync::Arc, time::Duration};
 
 use anyhow::{Context, Ok};
 use futures::future;
 use jsonrpsee::{
     server::{BatchRequestConfig, RpcServiceBuilder, ServerBuilder},
-    RpcModule,
+    Methods, RpcModule,
 };
+use ola_config::api::SubscriptionOverflowPolicy;
 use ola_dal::{connection::ConnectionPool, StorageProcessor};
-use ola_types::{api::BlockId, MiniblockNumber};
+use ola_types::{
+    api::{BlockId, BlockIdVariant},
+    MiniblockNumber,
+};
 use ola_web3_decl::{
     error::Web3Error,
     namespaces::{
+        admin::AdminNamespaceServer,
         eth::{EthNamespaceServer, EthPubSubServer},
         net::NetNamespaceServer,
         ola::OlaNamespaceServer,
+        txpool::TxpoolNamespaceServer,
+        verifier::VerifierNamespaceServer,
+        web3::Web3NamespaceServer,
     },
 };
 
 use olaos_health_check::{HealthStatus, HealthUpdater, ReactiveHealthCheck};
+use olaos_object_store::ObjectStore;
 use serde::Deserialize;
 use tokio::{
     sync::{mpsc, oneshot, watch},
@@ -27,15 +36,32 @@ use tower_http::{cors::CorsLayer, metrics::InFlightRequestsLayer};
 
 use crate::{
     api_server::web3::{
-        backend::batch_limiter_middleware::LimitMiddleware, namespaces::net::NetNamespace,
+        backend::{
+            access_log::{AccessLog, AccessLogMiddleware, CallerIpLayer},
+            batch_limiter_middleware::LimitMiddleware,
+            correlation_id::{CorrelationIdLayer, CorrelationIdMiddleware},
+            method_filter::{MethodFilter, MethodFilterMiddleware},
+        },
+        namespaces::{net::NetNamespace, web3::Web3Namespace},
         pubsub::EthSubscriptionIdProvider,
     },
-    utils::wait_for_l1_batch,
+    metadata_calculator::AsyncTreeReader,
+    sequencer::types::{MempoolGuard, PendingStateGuard},
+    utils::{blocking_pool::BlockingPool, wait_for_l1_batch},
+    Component,
 };
 
 use self::{
     backend::error::internal_error,
-    namespaces::{eth::EthNamespace, ola::OlaNamespace},
+    call_outputs::CallOutputStore,
+    ipc::IpcEndpoint,
+    namespaces::{
+        admin::AdminNamespace,
+        eth::EthNamespace,
+        ola::OlaNamespace,
+        txpool::TxpoolNamespace,
+        verifier::{VerifierCommittee, VerifierNamespace},
+    },
     pubsub::{EthSubscribe, PubSubEvent},
     state::{InternalApiConfig, RpcState},
 };
@@ -44,6 +70,8 @@ use super::{execution_sandbox::VmConcurrencyBarrier, tx_sender::TxSender};
 use crate::api_server::execution_sandbox::BlockStartInfo;
 
 pub mod backend;
+pub mod call_outputs;
+pub mod ipc;
 pub mod namespaces;
 pub mod pubsub;
 pub mod state;
@@ -51,6 +79,8 @@ pub mod state;
 pub(crate) mod tests;
 
 const SERVER_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_SUBSCRIPTION_BUFFER_SIZE: usize = 1_024;
+const DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION: u32 = 64;
 
 #[derive(Debug, Clone, Copy)]
 enum ApiTransport {
@@ -58,23 +88,57 @@ enum ApiTransport {
     Http(SocketAddr),
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub enum Namespace {
     Ola,
     Eth,
     Pubsub,
     Net,
+    Web3,
     Debug,
+    /// Node introspection methods, e.g. `ola_getMempoolStatus`. Disabled by default.
+    Admin,
+    /// `txpool_content` for mempool monitoring; enabled together with `Admin`.
+    Txpool,
+    /// Attestations of the offchain verifier committee; served by the offchain verifier server.
+    Verifier,
 }
 
 impl Namespace {
     pub const HTTP: &'static [Namespace] = &[
         Namespace::Eth,
         Namespace::Net,
+        Namespace::Web3,
         Namespace::Ola,
         Namespace::Pubsub,
     ];
+
+    pub const ALL: &'static [Namespace] = &[
+        Namespace::Ola,
+        Namespace::Eth,
+        Namespace::Pubsub,
+        Namespace::Net,
+        Namespace::Web3,
+        Namespace::Debug,
+        Namespace::Admin,
+        Namespace::Txpool,
+        Namespace::Verifier,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Namespace::Ola => "ola",
+            Namespace::Eth => "eth",
+            Namespace::Pubsub => "pubsub",
+            Namespace::Net => "net",
+            Namespace::Web3 => "web3",
+            Namespace::Debug => "debug",
+            Namespace::Admin => "admin",
+            Namespace::Txpool => "txpool",
+            Namespace::Verifier => "verifier",
+        }
+    }
 }
 
 /// Handles to the initialized API server.
@@ -122,12 +186,28 @@ pub struct ApiBuilder {
     vm_barrier: Option<VmConcurrencyBarrier>,
     filters_limit: Option<usize>,
     subscriptions_limit: Option<usize>,
+    subscription_buffer_size: Option<usize>,
+    subscription_overflow_policy: SubscriptionOverflowPolicy,
+    max_subscriptions_per_connection: Option<u32>,
+    subscription_replay_window: Option<u32>,
     batch_request_size_limit: Option<usize>,
     response_body_size_limit: Option<usize>,
     threads: Option<usize>,
     vm_concurrency_limit: Option<usize>,
     polling_interval: Option<Duration>,
     namespaces: Option<Vec<Namespace>>,
+    mempool: Option<MempoolGuard>,
+    pending_state: Option<PendingStateGuard>,
+    tree_reader: Option<watch::Receiver<Option<AsyncTreeReader>>>,
+    tree_read_pool: Option<Arc<BlockingPool>>,
+    archive_store: Option<Arc<dyn ObjectStore>>,
+    call_output_store: Option<Arc<CallOutputStore>>,
+    proof_store: Option<Arc<dyn ObjectStore>>,
+    verifier_committee: Arc<VerifierCommittee>,
+    components: Vec<Component>,
+    ipc: Option<IpcEndpoint>,
+    method_filter: Arc<MethodFilter>,
+    access_log: Option<Arc<AccessLog>>,
 }
 
 impl ApiBuilder {
@@ -144,8 +224,24 @@ impl ApiBuilder {
             filters_limit: None,
             pool,
             subscriptions_limit: None,
+            subscription_buffer_size: None,
+            subscription_overflow_policy: SubscriptionOverflowPolicy::default(),
+            max_subscriptions_per_connection: None,
+            subscription_replay_window: None,
             vm_concurrency_limit: None,
             polling_interval: None,
+            mempool: None,
+            pending_state: None,
+            tree_reader: None,
+            tree_read_pool: None,
+            archive_store: None,
+            call_output_store: None,
+            proof_store: None,
+            verifier_committee: Arc::default(),
+            components: vec![],
+            ipc: None,
+            method_filter: Arc::default(),
+            access_log: None,
         }
     }
 
@@ -157,12 +253,28 @@ impl ApiBuilder {
             vm_barrier: None,
             filters_limit: None,
             subscriptions_limit: None,
+            subscription_buffer_size: None,
+            subscription_overflow_policy: SubscriptionOverflowPolicy::default(),
+            max_subscriptions_per_connection: None,
+            subscription_replay_window: None,
             batch_request_size_limit: None,
             response_body_size_limit: None,
             threads: None,
             vm_concurrency_limit: None,
             polling_interval: None,
             namespaces: None,
+            mempool: None,
+            pending_state: None,
+            tree_reader: None,
+            tree_read_pool: None,
+            archive_store: None,
+            call_output_store: None,
+            proof_store: None,
+            verifier_committee: Arc::default(),
+            components: vec![],
+            ipc: None,
+            method_filter: Arc::default(),
+            access_log: None,
             config,
         }
     }
@@ -187,6 +299,30 @@ impl ApiBuilder {
         self
     }
 
+    /// Sets the number of notifications buffered for a single WS subscription and what happens
+    /// once the buffer is full.
+    pub fn with_subscription_buffer(
+        mut self,
+        buffer_size: usize,
+        overflow_policy: SubscriptionOverflowPolicy,
+    ) -> Self {
+        self.subscription_buffer_size = Some(buffer_size);
+        self.subscription_overflow_policy = overflow_policy;
+        self
+    }
+
+    pub fn with_max_subscriptions_per_connection(mut self, limit: u32) -> Self {
+        self.max_subscriptions_per_connection = Some(limit);
+        self
+    }
+
+    /// Allows subscriptions to replay notifications of up to `window` last miniblocks;
+    /// 0 disables replay.
+    pub fn with_subscription_replay_window(mut self, window: u32) -> Self {
+        self.subscription_replay_window = Some(window);
+        self
+    }
+
     pub fn with_threads(mut self, threads: usize) -> Self {
         self.threads = Some(threads);
         self
@@ -218,6 +354,89 @@ impl ApiBuilder {
         self
     }
 
+    /// Sets the sequencer mempool inspected by the admin namespace, if the sequencer runs
+    /// in the same process.
+    pub fn with_mempool(mut self, mempool: Option<MempoolGuard>) -> Self {
+        self.mempool = mempool;
+        self
+    }
+
+    /// Sets writes of the batch executed by the sequencer, served for `pending` storage reads.
+    pub fn with_pending_state(mut self, pending_state: Option<PendingStateGuard>) -> Self {
+        self.pending_state = pending_state;
+        self
+    }
+
+    /// Sets the read-only Merkle tree handle used to serve storage proofs, if the tree runs
+    /// in the same process.
+    pub fn with_tree_reader(
+        mut self,
+        tree_reader: Option<watch::Receiver<Option<AsyncTreeReader>>>,
+    ) -> Self {
+        self.tree_reader = tree_reader;
+        self
+    }
+
+    /// Sets the thread pool running Merkle tree reads. If not set, reads are run via
+    /// `spawn_blocking()`.
+    pub fn with_tree_read_pool(mut self, pool: Option<Arc<BlockingPool>>) -> Self {
+        self.tree_read_pool = pool;
+        self
+    }
+
+    /// Sets the object store with archived transactions, served if they are missing in Postgres.
+    pub fn with_archive_store(mut self, archive_store: Option<Arc<dyn ObjectStore>>) -> Self {
+        self.archive_store = archive_store;
+        self
+    }
+
+    /// Sets the store for full outputs of calls truncated in responses.
+    pub fn with_call_output_store(
+        mut self,
+        call_output_store: Option<Arc<CallOutputStore>>,
+    ) -> Self {
+        self.call_output_store = call_output_store;
+        self
+    }
+
+    /// Sets the object store with L1 batch proofs, which are pushed to offchain verifiers
+    /// together with batch commitments.
+    pub fn with_proof_store(mut self, proof_store: Option<Arc<dyn ObjectStore>>) -> Self {
+        self.proof_store = proof_store;
+        self
+    }
+
+    /// Sets the committee whose attestations are accepted by the verifier namespace.
+    pub fn with_verifier_committee(mut self, committee: VerifierCommittee) -> Self {
+        self.verifier_committee = Arc::new(committee);
+        self
+    }
+
+    /// Sets the node components reported by `ola_nodeInfo`.
+    pub fn with_components(mut self, components: Vec<Component>) -> Self {
+        self.components = components;
+        self
+    }
+
+    /// Additionally serves the HTTP RPC module over IPC at the specified endpoint.
+    pub fn with_ipc(mut self, ipc: Option<IpcEndpoint>) -> Self {
+        self.ipc = ipc;
+        self
+    }
+
+    /// Sets the filter of served methods. The filter should be shared by all servers of the node,
+    /// so that namespaces toggled via the admin namespace are toggled on every server.
+    pub fn with_method_filter(mut self, method_filter: Arc<MethodFilter>) -> Self {
+        self.method_filter = method_filter;
+        self
+    }
+
+    /// Sets the access log shared by all servers of the node.
+    pub fn with_access_log(mut self, access_log: Option<Arc<AccessLog>>) -> Self {
+        self.access_log = access_log;
+        self
+    }
+
     pub fn enable_api_namespaces(mut self, namespaces: Vec<Namespace>) -> Self {
         self.namespaces = Some(namespaces);
         self
@@ -283,19 +502,39 @@ impl ApiBuilder {
             .response_body_size_limit
             .map(|limit| limit as u32)
             .unwrap_or(u32::MAX);
+        let ipc = self.ipc;
+        let method_filter = self.method_filter;
+        let access_log = self.access_log;
         tokio::task::spawn_blocking(move || {
-            runtime.block_on(Self::run_rpc_server(
-                true,
-                rpc,
-                addr,
-                stop_receiver,
-                health_updater,
-                vm_barrier,
-                batch_request_config,
-                response_body_size_limit,
-            ));
+            let ipc_server = {
+                let rpc = rpc.clone();
+                let stop_receiver = stop_receiver.clone();
+                async move {
+                    match ipc {
+                        Some(endpoint) => ipc::run_ipc_server(endpoint, rpc, stop_receiver).await,
+                        None => Ok(()),
+                    }
+                }
+            };
+            let http_server = async move {
+                Self::run_rpc_server(
+                    true,
+                    rpc,
+                    addr,
+                    stop_receiver,
+                    health_updater,
+                    vm_barrier,
+                    batch_request_config,
+                    response_body_size_limit,
+                    method_filter,
+                    access_log,
+                )
+                .await;
+                Ok(())
+            };
+            let result = runtime.block_on(async { tokio::try_join!(http_server, ipc_server) });
             runtime.shutdown_timeout(SERVER_SHUTDOWN_TIMEOUT);
-            Ok(())
+            result.map(drop)
         })
     }
 
@@ -322,6 +561,8 @@ impl ApiBuilder {
             .response_body_size_limit
             .map(|limit| limit as u32)
             .unwrap_or(u32::MAX);
+        let method_filter = self.method_filter;
+        let access_log = self.access_log;
 
         tokio::task::spawn_blocking(move || {
             runtime.block_on(Self::run_rpc_server(
@@ -333,6 +574,8 @@ impl ApiBuilder {
                 vm_barrier,
                 batch_request_config,
                 response_body_size_limit,
+                method_filter,
+                access_log,
             ));
             runtime.shutdown_timeout(SERVER_SHUTDOWN_TIMEOUT);
             Ok(())
@@ -355,7 +598,17 @@ impl ApiBuilder {
         let pub_sub = if matches!(transport, ApiTransport::WebSocket(_))
             && namespaces.contains(&Namespace::Pubsub)
         {
-            let pub_sub = EthSubscribe::new();
+            let mut pub_sub = EthSubscribe::new(
+                self.subscription_buffer_size
+                    .unwrap_or(DEFAULT_SUBSCRIPTION_BUFFER_SIZE),
+                self.subscription_overflow_policy,
+            );
+            if let Some(window) = self.subscription_replay_window.filter(|&window| window > 0) {
+                pub_sub.set_replay(self.pool.clone(), window);
+            }
+            if namespaces.contains(&Namespace::Verifier) {
+                pub_sub.set_verification_tasks(self.pool.clone(), self.proof_store.clone());
+            }
             // if let Some(sender) = &self.optional.pub_sub_events_sender {
             //     pub_sub.set_events_sender(sender.clone());
             // }
@@ -442,7 +695,9 @@ impl ApiBuilder {
         // Assemble server middleware.
         let middleware = tower::ServiceBuilder::new()
             .layer(in_flight_requests)
-            .option_layer(cors);
+            .option_layer(cors)
+            .layer(CorrelationIdLayer)
+            .layer(CallerIpLayer::new(self.access_log.as_deref()));
 
         // Settings shared by HTTP and WS servers.
         let max_connections = !is_http
@@ -463,10 +718,22 @@ impl ApiBuilder {
             )
             .set_batch_request_config(batch_request_config);
 
+        let method_filter = self.method_filter.clone();
+        let access_log = self.access_log.clone();
         let (local_addr, server_handle) = if is_http {
             // HTTP-specific settings
             let server = server_builder
                 .http_only()
+                .set_rpc_middleware(
+                    RpcServiceBuilder::new()
+                        .layer_fn(CorrelationIdMiddleware::new)
+                        .layer_fn(move |service| {
+                            AccessLogMiddleware::new(service, access_log.clone(), transport_str)
+                        })
+                        .layer_fn(move |service| {
+                            MethodFilterMiddleware::new(service, method_filter.clone())
+                        }),
+                )
                 .build(addr)
                 .await
                 .context("Failed building HTTP JSON-RPC server")?;
@@ -477,9 +744,20 @@ impl ApiBuilder {
             let server = server_builder
                 .set_rpc_middleware(
                     RpcServiceBuilder::new()
+                        .layer_fn(CorrelationIdMiddleware::new)
+                        .layer_fn(move |service| {
+                            AccessLogMiddleware::new(service, access_log.clone(), transport_str)
+                        })
+                        .layer_fn(move |service| {
+                            MethodFilterMiddleware::new(service, method_filter.clone())
+                        })
                         .layer_fn(move |a| LimitMiddleware::new(a, NonZeroU32::new(5))),
                 )
                 .set_id_provider(EthSubscriptionIdProvider)
+                .max_subscriptions_per_connection(
+                    self.max_subscriptions_per_connection
+                        .unwrap_or(DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION),
+                )
                 .build(addr)
                 .await
                 .context("Failed building WS JSON-RPC server")?;
@@ -528,6 +806,8 @@ impl ApiBuilder {
         vm_barrier: VmConcurrencyBarrier,
         batch_request_config: BatchRequestConfig,
         response_body_size_limit: u32,
+        method_filter: Arc<MethodFilter>,
+        access_log: Option<Arc<AccessLog>>,
     ) {
         let transport = if is_http { "HTTP" } else { "WS" };
         let cors = is_http.then(|| {
@@ -543,7 +823,9 @@ impl ApiBuilder {
         }));
         let middleware = tower::ServiceBuilder::new()
             .layer(in_flight_requests)
-            .option_layer(cors);
+            .option_layer(cors)
+            .layer(CorrelationIdLayer)
+            .layer(CallerIpLayer::new(access_log.as_deref()));
 
         let server_builder = if is_http {
             ServerBuilder::default().http_only().max_connections(5000)
@@ -554,6 +836,16 @@ impl ApiBuilder {
         let server = server_builder
             .set_batch_request_config(batch_request_config)
             .set_http_middleware(middleware)
+            .set_rpc_middleware(
+                RpcServiceBuilder::new()
+                    .layer_fn(CorrelationIdMiddleware::new)
+                    .layer_fn(move |service| {
+                        AccessLogMiddleware::new(service, access_log.clone(), transport)
+                    })
+                    .layer_fn(move |service| {
+                        MethodFilterMiddleware::new(service, method_filter.clone())
+                    }),
+            )
             .max_response_body_size(response_body_size_limit)
             .build(addr)
             .await
@@ -577,6 +869,40 @@ impl ApiBuilder {
         Self::wait_for_vm(vm_barrier, transport).await;
     }
 
+    fn admin_namespace(&self) -> AdminNamespace {
+        let vm_concurrency_limiter = self
+            .tx_sender
+            .as_ref()
+            .map(TxSender::vm_concurrency_limiter);
+        let calldata_screener = self
+            .tx_sender
+            .as_ref()
+            .and_then(TxSender::calldata_screener);
+        AdminNamespace::new(
+            self.pool.clone(),
+            self.mempool.clone(),
+            vm_concurrency_limiter,
+            self.method_filter.clone(),
+            self.tree_reader.clone(),
+            self.tree_read_pool.clone(),
+            calldata_screener,
+        )
+    }
+
+    /// Merges methods of `namespace` into `rpc` and registers them in the method filter.
+    fn merge_namespace(
+        &self,
+        rpc: &mut RpcModule<()>,
+        namespace: Namespace,
+        methods: impl Into<Methods>,
+    ) {
+        let methods = methods.into();
+        self.method_filter
+            .register(namespace, methods.method_names());
+        rpc.merge(methods)
+            .unwrap_or_else(|err| panic!("Can't merge {} namespace: {err}", namespace.as_str()));
+    }
+
     async fn build_rpc_module(&self) -> RpcModule<()> {
         let l2_chain_id = self.config.l2_chain_id;
         let rpc_app = self.build_rpc_state().await;
@@ -584,16 +910,54 @@ impl ApiBuilder {
         let mut rpc = RpcModule::new(());
 
         if namespaces.contains(&Namespace::Ola) {
-            rpc.merge(OlaNamespace::new(rpc_app.clone()).into_rpc())
-                .expect("Can't merge ola namespace");
+            self.merge_namespace(
+                &mut rpc,
+                Namespace::Ola,
+                OlaNamespace::new(rpc_app.clone()).into_rpc(),
+            );
         }
         if namespaces.contains(&Namespace::Eth) {
-            rpc.merge(EthNamespace::new(rpc_app.clone()).into_rpc())
-                .expect("Can't merge eth namespace");
+            self.merge_namespace(
+                &mut rpc,
+                Namespace::Eth,
+                EthNamespace::new(rpc_app.clone()).into_rpc(),
+            );
         }
         if namespaces.contains(&Namespace::Net) {
-            rpc.merge(NetNamespace::new(l2_chain_id).into_rpc())
-                .expect("Can't merge net namespace");
+            self.merge_namespace(
+                &mut rpc,
+                Namespace::Net,
+                NetNamespace::new(l2_chain_id).into_rpc(),
+            );
+        }
+        if namespaces.contains(&Namespace::Web3) {
+            self.merge_namespace(&mut rpc, Namespace::Web3, Web3Namespace.into_rpc());
+        }
+        if namespaces.contains(&Namespace::Admin) {
+            self.merge_namespace(
+                &mut rpc,
+                Namespace::Admin,
+                self.admin_namespace().into_rpc(),
+            );
+        }
+        if namespaces.contains(&Namespace::Txpool) {
+            self.merge_namespace(
+                &mut rpc,
+                Namespace::Txpool,
+                TxpoolNamespace::new(self.mempool.clone()).into_rpc(),
+            );
+        }
+        if namespaces.contains(&Namespace::Verifier) {
+            self.merge_namespace(
+                &mut rpc,
+                Namespace::Verifier,
+                VerifierNamespace::new(
+                    self.pool.clone(),
+                    self.verifier_committee.clone(),
+                    self.config.l2_chain_id,
+                )
+                .into_rpc(),
+            );
         }
 
         rpc
@@ -612,16 +976,57 @@ impl ApiBuilder {
 
         let mut rpc = RpcModule::new(());
         if let Some(pub_sub) = pub_sub {
-            rpc.merge(pub_sub.into_rpc())
-                .expect("Can't merge eth pubsub namespace");
+            self.merge_namespace(&mut rpc, Namespace::Pubsub, pub_sub.into_rpc());
         }
         if namespaces.contains(&Namespace::Ola) {
-            rpc.merge(OlaNamespace::new(rpc_state.clone()).into_rpc())
-                .expect("Can't merge ola namespace");
+            self.merge_namespace(
+                &mut rpc,
+                Namespace::Ola,
+                OlaNamespace::new(rpc_state.clone()).into_rpc(),
+            );
         }
         if namespaces.contains(&Namespace::Eth) {
-            rpc.merge(EthNamespace::new(rpc_state.clone()).into_rpc())
-                .expect("Can't merge eth namespace");
+            self.merge_namespace(
+                &mut rpc,
+                Namespace::Eth,
+                EthNamespace::new(rpc_state.clone()).into_rpc(),
+            );
+        }
+        if namespaces.contains(&Namespace::Net) {
+            self.merge_namespace(
+                &mut rpc,
+                Namespace::Net,
+                NetNamespace::new(self.config.l2_chain_id).into_rpc(),
+            );
+        }
+        if namespaces.contains(&Namespace::Web3) {
+            self.merge_namespace(&mut rpc, Namespace::Web3, Web3Namespace.into_rpc());
+        }
+        if namespaces.contains(&Namespace::Admin) {
+            self.merge_namespace(
+                &mut rpc,
+                Namespace::Admin,
+                self.admin_namespace().into_rpc(),
+            );
+        }
+        if namespaces.contains(&Namespace::Txpool) {
+            self.merge_namespace(
+                &mut rpc,
+                Namespace::Txpool,
+                TxpoolNamespace::new(self.mempool.clone()).into_rpc(),
+            );
+        }
+        if namespaces.contains(&Namespace::Verifier) {
+            self.merge_namespace(
+                &mut rpc,
+                Namespace::Verifier,
+                VerifierNamespace::new(
+                    self.pool.clone(),
+                    self.verifier_committee.clone(),
+                    self.config.l2_chain_id,
+                )
+                .into_rpc(),
+            );
         }
 
         Ok(rpc)
@@ -637,6 +1042,12 @@ impl ApiBuilder {
             connection_pool: self.pool.clone(),
             tx_sender: self.tx_sender.clone(),
             start_info,
+            pending_state: self.pending_state.clone(),
+            tree_reader: self.tree_reader.clone(),
+            tree_read_pool: self.tree_read_pool.clone(),
+            archive_store: self.archive_store.clone(),
+            call_output_store: self.call_output_store.clone(),
+            components: self.components.clone(),
         }
     }
 
@@ -666,13 +1077,37 @@ impl ApiBuilder {
     // }
 }
 
+/// Resolves a block referenced as per EIP-1898. Unknown block hashes and (if `requireCanonical`
+/// is set) non-canonical blocks are reported with dedicated errors.
 async fn resolve_block(
     connection: &mut StorageProcessor<'_>,
-    block: BlockId,
+    block: BlockIdVariant,
     method_name: &'static str,
 ) -> Result<MiniblockNumber, Web3Error> {
-    let result = connection.blocks_web3_dal().resolve_block_id(block).await;
-    result
-        .map_err(|err| internal_error(method_name, err))?
-        .ok_or(Web3Error::NoBlock)
+    let block_id = BlockId::from(block);
+    let block_number = connection
+        .blocks_web3_dal()
+        .resolve_block_id(block_id)
+        .await
+        .map_err(|err| internal_error(method_name, err))?;
+
+    match (block_id, block_number) {
+        (BlockId::Hash(hash), None) => Err(Web3Error::UnknownBlockHash(hash)),
+        (BlockId::Number(_), None) => Err(Web3Error::NoBlock),
+        (BlockId::Hash(hash), Some(number)) if block.require_canonical() => {
+            // Postgres is expected to store canonical miniblocks only (reverted ones are removed),
+            // but we double-check that the hash is the one of the miniblock with the same number.
+            let canonical_hash = connection
+                .blocks_web3_dal()
+                .get_miniblock_hash(number)
+                .await
+                .map_err(|err| internal_error(method_name, err))?;
+            if canonical_hash == Some(hash) {
+                Ok(number)
+            } else {
+                Err(Web3Error::NonCanonicalBlock(hash))
+            }
+        }
+        (_, Some(number)) => Ok(number),
+    }
 }
@@ -0,0 +1,348 @@
+use std::{collections::BTreeSet, sync::Arc};
+
+use ola_config::{dump::ConfigDump, node::Config};
+use ola_dal::connection::ConnectionPool;
+use ola_types::{
+    api::{
+        AuditAction, CalldataScreeningRules, ContractResourceKind, ContractResourceUsage,
+        MempoolStatus, StorageProof, StorageSample, VmConcurrencyStatus,
+    },
+    merkle_tree::{tree_key_to_h256, tree_value_to_h256},
+    L1BatchNumber,
+};
+use ola_web3_decl::{
+    error::Web3Error,
+    types::{AuditLogPage, Page, PageCursor},
+};
+use tokio::sync::watch;
+
+use crate::{
+    api_server::{
+        execution_sandbox::VmConcurrencyLimiter,
+        tx_sender::screening::CalldataScreener,
+        web3::{
+            backend::{
+                error::{dal_error, internal_error, tree_error},
+                method_filter::MethodFilter,
+            },
+            Namespace,
+        },
+    },
+    dump_config,
+    metadata_calculator::AsyncTreeReader,
+    sequencer::types::MempoolGuard,
+    utils::blocking_pool::BlockingPool,
+};
+
+/// Default and maximum number of transactions scheduled for inclusion returned by
+/// `ola_getMempoolStatus`.
+const DEFAULT_NEXT_TRANSACTIONS_LIMIT: usize = 20;
+const MAX_NEXT_TRANSACTIONS_LIMIT: usize = 1_000;
+/// Default and maximum number of entries in per-account lists returned by `ola_getMempoolStatus`.
+const DEFAULT_MEMPOOL_ACCOUNTS_LIMIT: usize = 100;
+const MAX_MEMPOOL_ACCOUNTS_LIMIT: usize = 1_000;
+/// Default and maximum number of contracts returned by `ola_getTopContracts`.
+const DEFAULT_TOP_CONTRACTS_LIMIT: usize = 20;
+const MAX_TOP_CONTRACTS_LIMIT: usize = 500;
+/// Default and maximum number of entries returned by `ola_getAuditLog`.
+const DEFAULT_AUDIT_LOG_LIMIT: usize = 100;
+const MAX_AUDIT_LOG_LIMIT: usize = 1_000;
+/// Default and maximum number of storage entries sampled by `ola_sampleStorageProofs`.
+const DEFAULT_STORAGE_SAMPLE_SIZE: usize = 16;
+const MAX_STORAGE_SAMPLE_SIZE: usize = 256;
+/// Number of times keys are resampled if the tree is updated while proofs are read.
+const MAX_STORAGE_SAMPLING_ATTEMPTS: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct AdminNamespace {
+    pool: ConnectionPool,
+    mempool: Option<MempoolGuard>,
+    vm_concurrency_limiter: Option<Arc<VmConcurrencyLimiter>>,
+    method_filter: Arc<MethodFilter>,
+    tree_reader: Option<watch::Receiver<Option<AsyncTreeReader>>>,
+    tree_read_pool: Option<Arc<BlockingPool>>,
+    calldata_screener: Option<Arc<CalldataScreener>>,
+}
+
+impl AdminNamespace {
+    pub fn new(
+        pool: ConnectionPool,
+        mempool: Option<MempoolGuard>,
+        vm_concurrency_limiter: Option<Arc<VmConcurrencyLimiter>>,
+        method_filter: Arc<MethodFilter>,
+        tree_reader: Option<watch::Receiver<Option<AsyncTreeReader>>>,
+        tree_read_pool: Option<Arc<BlockingPool>>,
+        calldata_screener: Option<Arc<CalldataScreener>>,
+    ) -> Self {
+        Self {
+            pool,
+            mempool,
+            vm_concurrency_limiter,
+            method_filter,
+            tree_reader,
+            tree_read_pool,
+            calldata_screener,
+        }
+    }
+
+    #[olaos_logs::instrument(skip(self))]
+    pub fn get_mempool_status_impl(
+        &self,
+        next_transactions_limit: Option<usize>,
+        accounts_limit: Option<usize>,
+    ) -> Result<MempoolStatus, Web3Error> {
+        let mempool = self.mempool.as_ref().ok_or(Web3Error::MempoolUnavailable)?;
+        let limit = next_transactions_limit
+            .unwrap_or(DEFAULT_NEXT_TRANSACTIONS_LIMIT)
+            .min(MAX_NEXT_TRANSACTIONS_LIMIT);
+        let accounts_limit = accounts_limit
+            .unwrap_or(DEFAULT_MEMPOOL_ACCOUNTS_LIMIT)
+            .min(MAX_MEMPOOL_ACCOUNTS_LIMIT);
+        Ok(mempool.status(limit, accounts_limit))
+    }
+
+    #[olaos_logs::instrument(skip(self))]
+    pub fn get_vm_concurrency_impl(&self) -> Result<VmConcurrencyStatus, Web3Error> {
+        let limiter = self
+            .vm_concurrency_limiter
+            .as_ref()
+            .ok_or(Web3Error::VmConcurrencyUnavailable)?;
+        Ok(limiter.status())
+    }
+
+    #[olaos_logs::instrument(skip(self))]
+    pub fn get_config_impl(&self) -> Result<ConfigDump, Web3Error> {
+        const METHOD_NAME: &str = "get_config";
+
+        let config = Config::load().map_err(|err| internal_error(METHOD_NAME, err))?;
+        dump_config(&config).map_err(|err| internal_error(METHOD_NAME, err))
+    }
+
+    #[olaos_logs::instrument(skip(self))]
+    pub async fn get_top_contracts_impl(
+        &self,
+        from_timestamp: u64,
+        to_timestamp: u64,
+        resource: Option<ContractResourceKind>,
+        limit: Option<usize>,
+    ) -> Result<Vec<ContractResourceUsage>, Web3Error> {
+        const METHOD_NAME: &str = "get_top_contracts";
+
+        if from_timestamp > to_timestamp {
+            return Err(Web3Error::InvalidTimeRange(from_timestamp, to_timestamp));
+        }
+        let limit = limit
+            .unwrap_or(DEFAULT_TOP_CONTRACTS_LIMIT)
+            .min(MAX_TOP_CONTRACTS_LIMIT);
+        let mut storage = self.pool.access_storage_tagged("api").await;
+        storage
+            .contract_metrics_dal()
+            .get_top_contracts(
+                from_timestamp,
+                to_timestamp,
+                resource.unwrap_or_default(),
+                limit,
+            )
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))
+    }
+
+    #[olaos_logs::instrument(skip(self))]
+    pub async fn get_audit_log_impl(
+        &self,
+        action: Option<AuditAction>,
+        cursor: Option<PageCursor<u64>>,
+        limit: Option<usize>,
+    ) -> Result<AuditLogPage, Web3Error> {
+        const METHOD_NAME: &str = "get_audit_log";
+
+        let limit = limit
+            .unwrap_or(DEFAULT_AUDIT_LOG_LIMIT)
+            .clamp(1, MAX_AUDIT_LOG_LIMIT);
+        let mut storage = self.pool.access_storage_tagged("api").await;
+        // Fetch an extra entry to find out whether the page is truncated.
+        let entries = storage
+            .audit_log_dal()
+            .get_entries(action, cursor.map(|cursor| *cursor.key()), limit + 1)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+        Ok(Page::new(entries, limit, |entry| entry.id))
+    }
+
+    #[olaos_logs::instrument(skip(self))]
+    pub fn set_namespace_enabled_impl(
+        &self,
+        namespace: String,
+        enabled: bool,
+    ) -> Result<Vec<String>, Web3Error> {
+        // Disabling the admin namespace would make it impossible to re-enable namespaces.
+        let parsed_namespace = Namespace::ALL
+            .iter()
+            .copied()
+            .find(|candidate| candidate.as_str() == namespace)
+            .filter(|&candidate| candidate != Namespace::Admin)
+            .ok_or(Web3Error::InvalidNamespace(namespace))?;
+
+        let disabled = self
+            .method_filter
+            .set_namespace_enabled(parsed_namespace, enabled);
+        olaos_logs::warn!(
+            "Namespace `{}` is {} via the admin API; disabled namespaces: {disabled:?}",
+            parsed_namespace.as_str(),
+            if enabled { "enabled" } else { "disabled" }
+        );
+        Ok(disabled
+            .into_iter()
+            .map(|namespace| namespace.as_str().to_owned())
+            .collect())
+    }
+
+    #[olaos_logs::instrument(skip(self))]
+    pub fn get_calldata_screening_rules_impl(&self) -> Result<CalldataScreeningRules, Web3Error> {
+        let screener = self
+            .calldata_screener
+            .as_ref()
+            .ok_or(Web3Error::CalldataScreeningDisabled)?;
+        Ok(screener.rules())
+    }
+
+    #[olaos_logs::instrument(skip(self))]
+    pub async fn set_calldata_screening_rules_impl(
+        &self,
+        rules: CalldataScreeningRules,
+    ) -> Result<CalldataScreeningRules, Web3Error> {
+        let screener = self
+            .calldata_screener
+            .as_ref()
+            .ok_or(Web3Error::CalldataScreeningDisabled)?;
+        olaos_logs::warn!(
+            "Calldata screening rules are replaced via the admin API: {} forbidden selectors, \
+             {} exploit signatures, max calldata size: {:?}",
+            rules.forbidden_selectors.len(),
+            rules.exploit_signatures.len(),
+            rules.max_calldata_size
+        );
+        screener
+            .set_rules(rules)
+            .await
+            .map_err(|err| internal_error("set_calldata_screening_rules", format!("{err:#}")))
+    }
+
+    #[olaos_logs::instrument(skip(self))]
+    pub async fn sample_storage_proofs_impl(
+        &self,
+        seed: u64,
+        count: Option<usize>,
+    ) -> Result<StorageSample, Web3Error> {
+        const METHOD_NAME: &str = "sample_storage_proofs";
+
+        let tree_reader = self
+            .tree_reader
+            .as_ref()
+            .and_then(|tree_reader| tree_reader.borrow().clone())
+            .map(|tree_reader| tree_reader.with_pool(self.tree_read_pool.clone()))
+            .ok_or(Web3Error::TreeApiUnavailable)?;
+        let count = count
+            .unwrap_or(DEFAULT_STORAGE_SAMPLE_SIZE)
+            .min(MAX_STORAGE_SAMPLE_SIZE);
+        // Keys are sampled among the ones inserted into the tree before the checked L1 batch,
+        // so that the sample of nodes with the same tree state is the same. The tree may
+        // process more L1 batches before the proofs are read, in which case the keys are
+        // resampled for the new tree state.
+        let mut storage = self.pool.access_storage_tagged("api").await;
+        let mut attempt = 0;
+        let (hashed_keys, proofs) = loop {
+            let (_, next_l1_batch) = tree_reader
+                .info()
+                .await
+                .map_err(|err| tree_error(METHOD_NAME, err))?;
+            let max_index = storage
+                .storage_logs_dedup_dal()
+                .max_enumeration_index_before(next_l1_batch)
+                .await
+                .map_err(|err| dal_error(METHOD_NAME, err))?
+                .unwrap_or(0);
+            let indices = sample_leaf_indices(seed, count, max_index);
+            let hashed_keys: Vec<_> = storage
+                .storage_logs_dedup_dal()
+                .get_hashed_keys_by_indices(&indices)
+                .await
+                .map_err(|err| dal_error(METHOD_NAME, err))?
+                .into_iter()
+                .map(|(hashed_key, _)| hashed_key)
+                .collect();
+
+            let proofs = tree_reader
+                .entries_with_proofs(hashed_keys.clone())
+                .await
+                .map_err(|err| tree_error(METHOD_NAME, err))?;
+            if L1BatchNumber(proofs.block_number) == next_l1_batch {
+                break (hashed_keys, proofs);
+            }
+            attempt += 1;
+            if attempt == MAX_STORAGE_SAMPLING_ATTEMPTS {
+                return Err(internal_error(
+                    METHOD_NAME,
+                    format!(
+                        "tree was updated while sampling keys {attempt} times in a row;                          last sampled L1 batch: #{next_l1_batch}"
+                    ),
+                ));
+            }
+        };
+        drop(storage);
+
+        let entries = hashed_keys
+            .into_iter()
+            .zip(proofs.entries)
+            .map(|(key, entry)| StorageProof {
+                key,
+                proof: entry.merkle_path.iter().map(tree_key_to_h256).collect(),
+                value: tree_value_to_h256(&entry.value),
+                index: entry.leaf_index,
+            })
+            .collect();
+        Ok(StorageSample {
+            seed,
+            l1_batch_count: L1BatchNumber(proofs.block_number),
+            root_hash: tree_key_to_h256(&proofs.root_hash),
+            entries,
+        })
+    }
+}
+
+/// Samples up to `count` distinct leaf indices from `1..=max_index`. The sample only depends
+/// on the arguments, so that nodes with the same tree state sample the same keys.
+fn sample_leaf_indices(seed: u64, count: usize, max_index: u64) -> Vec<u64> {
+    let count = usize::try_from(max_index).map_or(count, |max_index| count.min(max_index));
+    let mut indices = BTreeSet::new();
+    let mut state = seed;
+    while indices.len() < count {
+        // SplitMix64: the state advances by the golden ratio increment and is then mixed.
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        indices.insert(1 + mix(state) % max_index);
+    }
+    indices.into_iter().collect()
+}
+
+fn mix(mut value: u64) -> u64 {
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sampling_leaf_indices() {
+        let indices = sample_leaf_indices(42, 16, 1_000);
+        assert_eq!(indices.len(), 16);
+        assert!(indices.iter().all(|index| (1..=1_000).contains(index)));
+        assert_eq!(indices, sample_leaf_indices(42, 16, 1_000));
+        assert_ne!(indices, sample_leaf_indices(43, 16, 1_000));
+
+        assert_eq!(sample_leaf_indices(42, 16, 5), [1, 2, 3, 4, 5]);
+        assert!(sample_leaf_indices(42, 16, 0).is_empty());
+    }
+}
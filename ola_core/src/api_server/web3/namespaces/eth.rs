@@ -1,12 +1,24 @@
-use crate::api_server::web3::{backend::error::internal_error, resolve_block, state::RpcState};
+use crate::api_server::web3::{
+    backend::error::{dal_error, internal_error},
+    resolve_block,
+    state::RpcState,
+};
 use anyhow::Context as _;
-use ola_types::api::{Block, Transaction, TransactionId, TransactionReceipt, TransactionVariant};
+use ola_types::api::{
+    Block, FeeHistory, SyncInfo, SyncState, Transaction, TransactionId, TransactionReceipt,
+    TransactionVariant,
+};
 use ola_types::{
-    api::{BlockId, BlockNumber},
-    Address, MiniblockNumber, H256, U256, U64,
+    api::{BlockHashObject, BlockId, BlockIdVariant, BlockNumber, Log},
+    AccountTreeId, Address, L1BatchNumber, StorageKey, H256, U256, U64,
 };
-use ola_web3_decl::error::Web3Error;
-use web3::types::{Bytes, FeeHistory, SyncInfo, SyncState};
+use ola_utils::convert::u256_to_h256;
+use ola_web3_decl::{error::Web3Error, types::Filter};
+use web3::types::Bytes;
+
+/// Number of sealed L1 batches the Merkle tree may lag behind while the state is considered
+/// consistent: the tree processes each batch shortly after it's sealed.
+const MAX_CONSISTENT_TREE_LAG: u32 = 1;
 
 #[derive(Debug)]
 pub struct EthNamespace {
@@ -39,6 +51,140 @@ impl EthNamespace {
         self.state.api_config.l2_chain_id.0.into()
     }
 
+    /// Compares the local state with the main node (on external nodes) and the Merkle tree
+    /// progress with sealed L1 batches. If the main node is unreachable, the local state
+    /// is assumed to be the latest one.
+    #[tracing::instrument(skip(self))]
+    pub async fn syncing_impl(&self) -> Result<SyncState, Web3Error> {
+        const METHOD_NAME: &str = "syncing";
+
+        let mut storage = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await;
+        let mut blocks_dal = storage.blocks_dal();
+        let current_block = blocks_dal
+            .get_sealed_miniblock_number()
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+        let last_sealed_l1_batch = blocks_dal
+            .get_sealed_l1_batch_number()
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+        let tree_l1_batch = blocks_dal
+            .get_last_l1_batch_number_with_metadata()
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+        drop(storage);
+
+        let proxy = self
+            .state
+            .tx_sender
+            .as_ref()
+            .and_then(|sender| sender.proxy());
+        let highest_block = match proxy {
+            Some(proxy) => match proxy.latest_miniblock().await {
+                Ok(number) => number.max(current_block),
+                Err(err) => {
+                    olaos_logs::warn!("Failed getting latest miniblock from the main node: {err}");
+                    current_block
+                }
+            },
+            None => current_block,
+        };
+
+        let consistent_state = is_consistent_state(last_sealed_l1_batch, tree_l1_batch);
+        if current_block >= highest_block && consistent_state {
+            return Ok(SyncState::NotSyncing);
+        }
+        Ok(SyncState::Syncing(SyncInfo {
+            starting_block: self.state.start_info.first_miniblock.0.into(),
+            current_block: current_block.0.into(),
+            highest_block: highest_block.0.into(),
+            last_sealed_l1_batch,
+            tree_l1_batch,
+            consistent_state,
+        }))
+    }
+
+    /// The node doesn't manage keys; transactions must be signed by the client.
+    pub fn accounts_impl(&self) -> Vec<Address> {
+        Vec::new()
+    }
+
+    /// Ola has no fee model yet, so transactions are free.
+    pub fn gas_price_impl(&self) -> U256 {
+        U256::zero()
+    }
+
+    /// Returns fees of up to `block_count` miniblocks ending with `newest_block`. Without a fee
+    /// model every miniblock has zero base and priority fees, consistent with `eth_gasPrice`.
+    #[tracing::instrument(skip(self))]
+    pub async fn fee_history_impl(
+        &self,
+        block_count: U64,
+        newest_block: BlockNumber,
+        reward_percentiles: Vec<f32>,
+    ) -> Result<FeeHistory, Web3Error> {
+        const METHOD_NAME: &str = "fee_history";
+
+        if reward_percentiles.len() > self.state.api_config.fee_history_max_reward_percentiles {
+            return Err(Web3Error::InvalidRewardPercentiles("too many percentiles"));
+        }
+        if reward_percentiles
+            .iter()
+            .any(|percentile| !(0.0..=100.0).contains(percentile))
+        {
+            return Err(Web3Error::InvalidRewardPercentiles(
+                "percentiles must be in [0, 100]",
+            ));
+        }
+        if reward_percentiles.windows(2).any(|pair| pair[0] > pair[1]) {
+            return Err(Web3Error::InvalidRewardPercentiles(
+                "percentiles must be non-decreasing",
+            ));
+        }
+
+        let block_id = BlockId::Number(newest_block);
+        self.state.start_info.ensure_not_pruned(block_id)?;
+        let mut connection = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await;
+        let newest_block = resolve_block(
+            &mut connection,
+            BlockIdVariant::BlockNumber(newest_block),
+            METHOD_NAME,
+        )
+        .await?;
+
+        let block_count = block_count
+            .as_u64()
+            .min(self.state.api_config.fee_history_limit);
+        let first_block = u64::from(self.state.start_info.first_miniblock.0);
+        let oldest_block = (u64::from(newest_block.0) + 1)
+            .saturating_sub(block_count)
+            .max(first_block);
+        let block_count = (u64::from(newest_block.0) + 1 - oldest_block) as usize;
+        if block_count == 0 {
+            return Ok(FeeHistory {
+                oldest_block: newest_block.0.into(),
+                ..FeeHistory::default()
+            });
+        }
+
+        let gas_price = self.gas_price_impl();
+        Ok(FeeHistory {
+            oldest_block: oldest_block.into(),
+            base_fee_per_gas: vec![gas_price; block_count + 1],
+            gas_used_ratio: vec![0.0; block_count],
+            reward: (!reward_percentiles.is_empty())
+                .then(|| vec![vec![U256::zero(); reward_percentiles.len()]; block_count]),
+        })
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn get_block_impl(
         &self,
@@ -47,24 +193,41 @@ impl EthNamespace {
     ) -> Result<Option<Block<TransactionVariant>>, Web3Error> {
         self.state.start_info.ensure_not_pruned(block_id)?;
 
-        let block = self
+        let mut storage = self
             .state
             .connection_pool
             .access_storage_tagged("api")
-            .await
+            .await;
+        if !full_transactions {
+            return storage
+                .blocks_web3_dal()
+                .get_block_by_web3_block_id(block_id, false, self.state.api_config.l2_chain_id)
+                .await
+                .map_err(|err| dal_error("get_block_by_web3_block_id", err));
+        }
+
+        // Full transactions are only loaded if the block is within the limit, so that heavy blocks
+        // aren't loaded into memory only to be rejected.
+        let max_inline_txs = self.state.api_config.max_inline_block_txs;
+        let block = storage
             .blocks_web3_dal()
-            .get_block_by_web3_block_id(
+            .get_block_with_limited_transactions(
                 block_id,
-                full_transactions,
+                u32::try_from(max_inline_txs).unwrap_or(u32::MAX),
                 self.state.api_config.l2_chain_id,
             )
             .await
-            .map_err(|err| internal_error("get_block_by_web3_block_id", err))?;
-
-        if let Some(block) = &block {
-            let block_number = MiniblockNumber(block.number.as_u32());
+            .map_err(|err| dal_error("get_block_with_limited_transactions", err))?;
+        let Some((block, tx_count)) = block else {
+            return Ok(None);
+        };
+        if tx_count > max_inline_txs as u64 {
+            return Err(Web3Error::TooManyBlockTransactions(
+                tx_count,
+                max_inline_txs,
+            ));
         }
-        Ok(block)
+        Ok(Some(block))
     }
     #[tracing::instrument(skip(self))]
     pub async fn get_block_transaction_count_impl(
@@ -81,7 +244,7 @@ impl EthNamespace {
             .blocks_web3_dal()
             .get_block_tx_count(block_id)
             .await
-            .map_err(|err| internal_error("get_block_tx_count", err))?;
+            .map_err(|err| dal_error("get_block_tx_count", err))?;
 
         Ok(tx_count.map(|(_, count)| count))
     }
@@ -101,7 +264,7 @@ impl EthNamespace {
             .blocks_web3_dal()
             .get_block_by_web3_block_id(block_id, false, self.state.api_config.l2_chain_id)
             .await
-            .map_err(|err| internal_error("get_block_by_web3_block_id", err))?;
+            .map_err(|err| dal_error("get_block_by_web3_block_id", err))?;
 
         // if let Some(block) = &block {
         //     self.set_block_diff(block.number.as_u32().into());
@@ -125,7 +288,7 @@ impl EthNamespace {
             .transactions_web3_dal()
             .get_transaction_receipts(&hashes)
             .await
-            .map_err(|err| internal_error("get_transaction_receipts", err))?;
+            .map_err(|err| dal_error("get_transaction_receipts", err))?;
 
         receipts.sort_unstable_by_key(|receipt| receipt.transaction_index);
         Ok(receipts)
@@ -136,7 +299,7 @@ impl EthNamespace {
         &self,
         id: TransactionId,
     ) -> Result<Option<Transaction>, Web3Error> {
-        let mut transaction = self
+        let transaction = self
             .state
             .connection_pool
             .access_storage_tagged("api")
@@ -144,11 +307,15 @@ impl EthNamespace {
             .transactions_web3_dal()
             .get_transaction(id, self.state.api_config.l2_chain_id)
             .await
-            .map_err(|err| internal_error("get_transaction", err))?;
+            .map_err(|err| dal_error("get_transaction", err))?;
 
-        if transaction.is_none() {
-            // if none, return a empty one.
-            // transaction = self.state.tx_sink().lookup_tx(id).await?;
+        if let (None, TransactionId::Hash(hash)) = (&transaction, id) {
+            let archived = self
+                .state
+                .archived_transaction(hash)
+                .await
+                .map_err(|err| internal_error("get_transaction", err))?;
+            return Ok(archived.and_then(|archived| archived.transaction));
         }
         Ok(transaction)
     }
@@ -166,18 +333,27 @@ impl EthNamespace {
             .transactions_web3_dal()
             .get_transaction_receipts(&[hash])
             .await
-            .map_err(|err| internal_error("get_transaction_receipts", err))?;
+            .map_err(|err| dal_error("get_transaction_receipts", err))?;
 
-        Ok(receipts.into_iter().next())
+        if let Some(receipt) = receipts.into_iter().next() {
+            return Ok(Some(receipt));
+        }
+        let archived = self
+            .state
+            .archived_transaction(hash)
+            .await
+            .map_err(|err| internal_error("get_transaction_receipts", err))?;
+        Ok(archived.and_then(|archived| archived.receipt))
     }
 
-    #[olaos_logs::instrument(skip(self, address, block_id))]
+    #[olaos_logs::instrument(skip(self, address, block))]
     pub async fn get_transaction_count_impl(
         &self,
         address: Address,
-        block_id: Option<BlockId>,
+        block: Option<BlockIdVariant>,
     ) -> anyhow::Result<u32, Web3Error> {
-        let block_id = block_id.unwrap_or(BlockId::Number(BlockNumber::Pending));
+        let block = block.unwrap_or(BlockIdVariant::BlockNumber(BlockNumber::Pending));
+        let block_id = BlockId::from(block);
         let method_name = match block_id {
             BlockId::Number(BlockNumber::Pending) => "get_pending_transaction_count",
             _ => "get_historical_transaction_count",
@@ -195,11 +371,12 @@ impl EthNamespace {
                     .transactions_web3_dal()
                     .next_nonce_by_initiator_account(address)
                     .await
-                    .map_err(|err| internal_error(method_name, err));
+                    .map_err(|err| dal_error(method_name, err));
                 nonce
             }
             _ => {
-                let block_number = resolve_block(&mut connection, block_id, method_name).await?;
+                self.state.start_info.ensure_not_pruned(block_id)?;
+                let block_number = resolve_block(&mut connection, block, method_name).await?;
                 let nonce = connection
                     .storage_web3_dal()
                     .get_address_historical_nonce(address, block_number)
@@ -208,7 +385,7 @@ impl EthNamespace {
                         let U256(ref arr) = nonce_u256;
                         arr[0] as u32
                     })
-                    .map_err(|err| internal_error(method_name, err));
+                    .map_err(|err| dal_error(method_name, err));
                 nonce
             }
         };
@@ -217,6 +394,123 @@ impl EthNamespace {
         account_nonce
     }
 
+    #[olaos_logs::instrument(skip(self, address, idx, block))]
+    pub async fn get_storage_at_impl(
+        &self,
+        address: Address,
+        idx: U256,
+        block: Option<BlockIdVariant>,
+    ) -> anyhow::Result<H256, Web3Error> {
+        const METHOD_NAME: &str = "get_storage_at";
+
+        let block = block.unwrap_or(BlockIdVariant::BlockNumber(BlockNumber::Pending));
+        let block_id = BlockId::from(block);
+        let storage_key = StorageKey::new(AccountTreeId::new(address), u256_to_h256(idx));
+        if let (BlockId::Number(BlockNumber::Pending), Some(pending_state)) =
+            (block_id, &self.state.pending_state)
+        {
+            if let Some(value) = pending_state.get(&storage_key) {
+                return Ok(value);
+            }
+        }
+
+        self.state.start_info.ensure_not_pruned(block_id)?;
+        let mut connection = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await;
+        let block_number = resolve_block(&mut connection, block, METHOD_NAME).await?;
+        connection
+            .storage_web3_dal()
+            .get_historical_value_unchecked(&storage_key, block_number)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))
+    }
+
+    /// Returns logs matching the filter. If more than `filters_limit` logs match, an error
+    /// suggesting a narrower block range is returned instead of a partial result.
+    #[tracing::instrument(skip(self))]
+    pub async fn get_logs_impl(&self, filter: Filter) -> Result<Vec<Log>, Web3Error> {
+        const METHOD_NAME: &str = "get_logs";
+
+        if filter.topics.as_ref().map_or(0, Vec::len) > 4 {
+            return Err(Web3Error::TooManyTopics);
+        }
+        let (from_block, to_block) = match filter.block_hash {
+            Some(_) if filter.from_block.is_some() || filter.to_block.is_some() => {
+                return Err(Web3Error::InvalidFilterBlockHash);
+            }
+            Some(block_hash) => {
+                let block = BlockIdVariant::BlockHashObject(BlockHashObject {
+                    block_hash,
+                    require_canonical: false,
+                });
+                (block, block)
+            }
+            None => (
+                BlockIdVariant::BlockNumber(filter.from_block.unwrap_or(BlockNumber::Latest)),
+                BlockIdVariant::BlockNumber(filter.to_block.unwrap_or(BlockNumber::Latest)),
+            ),
+        };
+
+        for block in [from_block, to_block] {
+            self.state
+                .start_info
+                .ensure_not_pruned(BlockId::from(block))?;
+        }
+        let mut connection = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await;
+        let from = resolve_block(&mut connection, from_block, METHOD_NAME).await?;
+        let to = resolve_block(&mut connection, to_block, METHOD_NAME).await?;
+        if from > to {
+            return Err(Web3Error::InvalidBlockRange(from, to));
+        }
+        let max_block_range = self.state.api_config.max_logs_block_range;
+        if to.0 - from.0 >= max_block_range {
+            let suggested_to = from.0 + max_block_range.saturating_sub(1);
+            return Err(Web3Error::LogsBlockRangeExceeded(
+                max_block_range,
+                from.0,
+                suggested_to,
+            ));
+        }
+
+        // Fetch a single extra log to detect whether the limit is exceeded.
+        let limit = self.state.api_config.filters_limit;
+        let mut logs = connection
+            .events_dal()
+            .get_filtered_logs(
+                from,
+                to,
+                filter.addresses(),
+                &filter.topics_by_position(),
+                limit + 1,
+            )
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+        if logs.len() > limit {
+            // Suggest the range ending before the miniblock with the first log over the limit.
+            // If this miniblock is the first one in the range, no range fits the limit.
+            let overflow_block = logs
+                .pop()
+                .and_then(|log| log.block_number)
+                .map_or(to.0, |number| number.as_u32());
+            if overflow_block <= from.0 {
+                return Err(Web3Error::BlockLogsLimitExceeded(limit, from.0));
+            }
+            return Err(Web3Error::LogsLimitExceeded(
+                limit,
+                from.0,
+                overflow_block - 1,
+            ));
+        }
+        Ok(logs)
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn get_block_number_impl(&self) -> anyhow::Result<U64, Web3Error> {
         let mut storage = self
@@ -224,7 +518,29 @@ impl EthNamespace {
             .connection_pool
             .access_storage_tagged("api")
             .await;
-        let block_number = storage.blocks_dal().get_sealed_miniblock_number().await;
+        let block_number = storage
+            .blocks_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .map_err(|err| dal_error("get_block_number", err))?;
         Ok(block_number.0.into())
     }
 }
+
+/// Checks whether the Merkle tree keeps up with sealed L1 batches, i.e., the state served by
+/// the API is covered by the tree.
+fn is_consistent_state(last_sealed_l1_batch: L1BatchNumber, tree_l1_batch: L1BatchNumber) -> bool {
+    last_sealed_l1_batch.0 <= tree_l1_batch.0 + MAX_CONSISTENT_TREE_LAG
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checking_state_consistency() {
+        assert!(is_consistent_state(L1BatchNumber(5), L1BatchNumber(5)));
+        assert!(is_consistent_state(L1BatchNumber(5), L1BatchNumber(4)));
+        assert!(!is_consistent_state(L1BatchNumber(5), L1BatchNumber(3)));
+    }
+}
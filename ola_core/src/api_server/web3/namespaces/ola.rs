@@ -1,19 +1,42 @@
 use anyhow::Context as _;
 use ola_types::api::proof_offchain_verification::OffChainVerificationResult;
 use ola_types::api::{
-    BlockDetails, L1BatchDetails, ProtocolVersion, TransactionDetails, TransactionReceipt,
+    AccountInfo, BlockDetails, BlockId, BlockIdVariant, BlockNumber, CallResultWithStats,
+    L1BatchDetails, NodeInfo, Proof, ProtocolVersion, ReceiptsRootCommitment, ReorgedOutBlock,
+    ReorgedOutTransaction, StateOverride, StorageProof, SystemContract, TokenBalance, TokenDetails,
+    TransactionDetails, TransactionInclusionProof, TransactionReceipt, TransactionStatusInfo,
+    TruncatedOutput,
 };
-use ola_types::{l2::L2Tx, request::CallRequest, Bytes, L1BatchNumber, MiniblockNumber};
-use ola_types::{H256, U64};
+use ola_types::merkle_tree::{tree_key_to_h256, tree_value_to_h256};
+use ola_types::protocol_version::ProtocolVersionId;
+use ola_types::receipts_tree::ReceiptsTree;
+use ola_types::system_contracts::system_contracts_for_version;
+use ola_types::{
+    get_full_code_key, get_nonce_key, l2::L2Tx, request::CallRequest, AccountTreeId, Address,
+    Bytes, L1BatchNumber, MiniblockNumber, StorageKey,
+};
+use ola_types::{H256, U256, U64};
+use ola_utils::{h256_to_u256, hash::hash_bytes};
 use ola_web3_decl::error::Web3Error;
 
-use crate::api_server::web3::backend::error::internal_error;
-use crate::api_server::web3::state::RpcState;
+use crate::api_server::tx_sender::error::SubmitTxError;
+use crate::api_server::web3::backend::error::{dal_error, internal_error, tree_error};
+use crate::api_server::web3::{resolve_block, state::RpcState};
+use crate::build_info;
+use crate::sequencer::types::PendingStateGuard;
 use anyhow::Context;
 use ola_dal::StorageProcessor;
-use ola_web3_decl::types::Token;
+use ola_web3_decl::types::{
+    BlockTransactionsPage, L1BatchesPage, LogCursorKey, LogsPage, Page, PageCursor,
+    StorageHistoryPage, StorageRangePage, Token, TransactionCursorKey, TransactionsPage,
+};
 use std::time::Instant;
 
+/// Maximum number of addresses accepted by `ola_getAccountsInfo`.
+const MAX_ACCOUNTS_INFO_ADDRESSES: usize = 100;
+/// Maximum (and default) number of items in a page of a paginated list, e.g. `ola_getLogsPage`.
+const MAX_PAGE_LIMIT: usize = 1_000;
+
 #[derive(Debug)]
 pub struct OlaNamespace {
     state: RpcState,
@@ -44,10 +67,38 @@ impl OlaNamespace {
     pub async fn send_raw_transaction_impl(
         &self,
         tx_bytes: Bytes,
+    ) -> anyhow::Result<H256, Web3Error> {
+        self.send_raw_transaction_inner(tx_bytes, None, false).await
+    }
+
+    #[olaos_logs::instrument(skip(self, tx_bytes))]
+    pub async fn send_raw_transaction_with_simulation_impl(
+        &self,
+        tx_bytes: Bytes,
+    ) -> anyhow::Result<H256, Web3Error> {
+        self.send_raw_transaction_inner(tx_bytes, None, true).await
+    }
+
+    #[olaos_logs::instrument(skip(self, tx_bytes))]
+    pub async fn send_scheduled_transaction_impl(
+        &self,
+        tx_bytes: Bytes,
+        execute_after: u64,
+    ) -> anyhow::Result<H256, Web3Error> {
+        self.send_raw_transaction_inner(tx_bytes, Some(execute_after), false)
+            .await
+    }
+
+    async fn send_raw_transaction_inner(
+        &self,
+        tx_bytes: Bytes,
+        execute_after: Option<u64>,
+        record_simulation: bool,
     ) -> anyhow::Result<H256, Web3Error> {
         olaos_logs::info!("received a send transaction: {:?}", Instant::now());
         let (mut tx, hash) = self.state.parse_transaction_bytes(&tx_bytes.0)?;
         tx.set_input(tx_bytes.0, hash);
+        tx.common_data.execute_after = execute_after;
         olaos_logs::info!("parsed transaction, hash: {:?}, initiator_address: {:?}, contract address: {:?}, nonce: {:?}", tx.hash(), tx.initiator_account(), tx.recipient_account(), tx.nonce());
 
         let tx_chain_id = tx.common_data.extract_chain_id().unwrap_or_default();
@@ -56,11 +107,20 @@ impl OlaNamespace {
             return Err(Web3Error::InvalidChainId(tx_chain_id));
         }
 
-        let submit_result = self.state.tx_sender.as_ref().unwrap().submit_tx(tx).await;
+        let tx_sender = self.state.tx_sender.as_ref().unwrap();
+        let submit_result = if record_simulation {
+            tx_sender.submit_tx_with_simulation(tx).await
+        } else {
+            tx_sender.submit_tx(tx).await
+        };
 
         let res = submit_result.map(|_| hash).map_err(|err| {
             olaos_logs::info!("Send raw transaction error: {err}");
-            Web3Error::SubmitTransactionError(err.to_string(), err.data())
+            match err {
+                SubmitTxError::ReadOnlyNode => Web3Error::ReadOnlyNode,
+                SubmitTxError::Invocation(err) => err.into(),
+                _ => Web3Error::SubmitTransactionError(err.to_string(), err.data()),
+            }
         });
 
         olaos_logs::info!("Send raw transaction result: {:?}", res);
@@ -69,9 +129,44 @@ impl OlaNamespace {
     }
 
     #[olaos_logs::instrument(skip(self, request))]
-    pub async fn call_impl(&self, request: CallRequest) -> anyhow::Result<Bytes, Web3Error> {
+    pub async fn call_impl(
+        &self,
+        request: CallRequest,
+        state_override: Option<StateOverride>,
+        block: Option<BlockNumber>,
+    ) -> anyhow::Result<Bytes, Web3Error> {
+        let result = self
+            .call_with_stats_impl(request, state_override, block)
+            .await?;
+        if let Some(truncated) = result.truncated {
+            let limit = self.state.api_config.max_call_output_size;
+            return Err(Web3Error::CallOutputTooLarge(truncated, limit));
+        }
+        Ok(result.output)
+    }
+
+    #[olaos_logs::instrument(skip(self, request))]
+    pub async fn call_with_stats_impl(
+        &self,
+        request: CallRequest,
+        state_override: Option<StateOverride>,
+        block: Option<BlockNumber>,
+    ) -> anyhow::Result<CallResultWithStats, Web3Error> {
         olaos_logs::info!("received a call transaction request: {:?}", request);
 
+        // Calls execute on top of the latest sealed miniblock, so `pending` calls apply writes
+        // of the batch being executed the same way as state overrides.
+        let pending_writes = match block.unwrap_or(BlockNumber::Latest) {
+            BlockNumber::Latest => vec![],
+            BlockNumber::Pending => self
+                .state
+                .pending_state
+                .as_ref()
+                .map(PendingStateGuard::writes)
+                .unwrap_or_default(),
+            _ => return Err(Web3Error::NotImplemented),
+        };
+
         let tx = L2Tx::from_request(request.into(), self.state.api_config.max_tx_size)?;
         olaos_logs::info!("parsed call request transaction: {:?}", tx);
 
@@ -80,16 +175,72 @@ impl OlaNamespace {
             .tx_sender
             .as_ref()
             .unwrap()
-            .call_transaction_impl(tx)
+            .call_transaction_with_stats_impl(
+                tx,
+                state_override.unwrap_or_default(),
+                pending_writes,
+            )
             .await;
-        let res_bytes = call_result.map_err(|err| {
+        let (res_bytes, stats) = call_result.map_err(|err| {
             olaos_logs::info!("Send raw transaction error: {err}");
-            Web3Error::SubmitTransactionError(err.to_string(), err.data())
+            match err {
+                SubmitTxError::Invocation(err) => err.into(),
+                _ => Web3Error::SubmitTransactionError(err.to_string(), err.data()),
+            }
         })?;
 
-        olaos_logs::info!("Call transaction result: {:?}", res_bytes);
+        olaos_logs::info!("Call transaction result: {res_bytes:?}, stats: {stats:?}");
 
-        Ok(res_bytes.into())
+        let (output, truncated) = self.truncate_call_output(res_bytes.0).await;
+        Ok(CallResultWithStats {
+            output,
+            stats,
+            truncated,
+        })
+    }
+
+    /// Truncates `output` to the output size limit. If spilling is enabled, full truncated
+    /// outputs are stored in the object store within the configured quota.
+    async fn truncate_call_output(&self, output: Vec<u8>) -> (Bytes, Option<TruncatedOutput>) {
+        let max_size = self.state.api_config.max_call_output_size;
+        if output.len() <= max_size {
+            return (Bytes(output), None);
+        }
+        let mut truncated = TruncatedOutput {
+            full_size: output.len() as u64,
+            hash: hash_bytes(&output),
+            stored: false,
+        };
+        let truncated_output = output[..max_size].to_vec();
+        if let Some(store) = &self.state.call_output_store {
+            match store.put(truncated.hash, output).await {
+                Ok(stored) => truncated.stored = stored,
+                Err(err) => olaos_logs::warn!(
+                    "Failed storing truncated call output {:?}: {err:#}",
+                    truncated.hash
+                ),
+            }
+        }
+        metrics::counter!(
+            "api.web3.call.truncated_outputs",
+            1,
+            "stored" => if truncated.stored { "true" } else { "false" }
+        );
+        (Bytes(truncated_output), Some(truncated))
+    }
+
+    #[olaos_logs::instrument(skip(self))]
+    pub async fn get_call_output_impl(&self, hash: H256) -> Result<Option<Bytes>, Web3Error> {
+        const METHOD_NAME: &str = "get_call_output";
+
+        let Some(store) = &self.state.call_output_store else {
+            return Ok(None);
+        };
+        let output = store
+            .get(hash)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, format!("{err:#}")))?;
+        Ok(output.map(Bytes))
     }
 
     #[olaos_logs::instrument(skip(self))]
@@ -109,7 +260,16 @@ impl OlaNamespace {
             .transactions_web3_dal()
             .get_transaction_details(hash)
             .await
-            .map_err(|err| internal_error(METHOD_NAME, err));
+            .map_err(|err| dal_error(METHOD_NAME, err));
+        let tx_details = match tx_details {
+            Ok(None) => self
+                .state
+                .archived_transaction(hash)
+                .await
+                .map(|archived| archived.and_then(|archived| archived.details))
+                .map_err(|err| internal_error(METHOD_NAME, err)),
+            other => other,
+        };
 
         olaos_logs::info!("api.web3.call get_transaction_details: {:?}", tx_details);
 
@@ -132,7 +292,16 @@ impl OlaNamespace {
             .transactions_web3_dal()
             .get_transaction_receipt(hash)
             .await
-            .map_err(|err| internal_error(METHOD_NAME, err));
+            .map_err(|err| dal_error(METHOD_NAME, err));
+        let receipt = match receipt {
+            Ok(None) => self
+                .state
+                .archived_transaction(hash)
+                .await
+                .map(|archived| archived.and_then(|archived| archived.receipt))
+                .map_err(|err| internal_error(METHOD_NAME, err)),
+            other => other,
+        };
 
         olaos_logs::info!(
             "api.web3.call get_transaction_receipt: cost {:?}",
@@ -158,7 +327,7 @@ impl OlaNamespace {
             .proof_verification_dal()
             .mark_l1_batch_as_verified(l1_batch_number, verify_result.is_passed)
             .await
-            .map_err(|err| internal_error(METHOD_NAME, err))?;
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
         Ok(true)
     }
 
@@ -167,10 +336,19 @@ impl OlaNamespace {
         U64::from(*self.state.api_config.l1_chain_id)
     }
 
+    /// Same as `eth_gasPrice`: Ola has no fee model yet, so transactions are free.
+    pub fn gas_price_impl(&self) -> U256 {
+        U256::zero()
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn get_l1_batch_number_impl(&self) -> anyhow::Result<U64, Web3Error> {
         let mut storage = self.access_storage().await?;
-        let l1_batch_number = storage.blocks_dal().get_sealed_l1_batch_number().await;
+        let l1_batch_number = storage
+            .blocks_dal()
+            .get_sealed_l1_batch_number()
+            .await
+            .map_err(|err| dal_error("get_l1_batch_number", err))?;
         Ok(l1_batch_number.0.into())
     }
     #[tracing::instrument(skip(self))]
@@ -183,7 +361,8 @@ impl OlaNamespace {
         let range = storage
             .blocks_dal()
             .get_miniblock_range_of_l1_batch(batch)
-            .await;
+            .await
+            .map_err(|err| dal_error("get_miniblock_range", err))?;
         Ok(range.map(|(min, max)| (U64::from(min.0), U64::from(max.0))))
     }
 
@@ -198,7 +377,7 @@ impl OlaNamespace {
             .blocks_web3_dal()
             .get_block_details(block_number)
             .await
-            .map_err(|err| internal_error("get_block_details", err))?)
+            .map_err(|err| dal_error("get_block_details", err))?)
     }
 
     #[tracing::instrument(skip(self))]
@@ -212,7 +391,55 @@ impl OlaNamespace {
             .transactions_web3_dal()
             .get_raw_miniblock_transactions(block_number)
             .await
-            .map_err(|err| internal_error("get_raw_miniblock_transactions", err))?)
+            .map_err(|err| dal_error("get_raw_miniblock_transactions", err))?)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_block_transactions_impl(
+        &self,
+        block: BlockNumber,
+        cursor: Option<PageCursor<TransactionCursorKey>>,
+        limit: Option<U64>,
+    ) -> anyhow::Result<Option<BlockTransactionsPage>, Web3Error> {
+        let block_id = BlockId::Number(block);
+        self.state.start_info.ensure_not_pruned(block_id)?;
+        // Pages are capped by the same limit as full blocks returned inline.
+        let max_limit = self.state.api_config.max_inline_block_txs;
+        let limit = limit.map_or(max_limit, |limit| {
+            limit.as_u64().clamp(1, max_limit as u64) as usize
+        });
+
+        let mut storage = self.access_storage().await?;
+        // Fetch an extra transaction to find out whether the page is truncated.
+        let page = storage
+            .blocks_web3_dal()
+            .get_block_transactions_page(
+                block_id,
+                cursor.map(|cursor| cursor.key().index_in_block),
+                limit as u32 + 1,
+                self.state.api_config.l2_chain_id,
+            )
+            .await
+            .map_err(|err| dal_error("get_block_transactions_page", err))?;
+        let Some((block_number, tx_count, transactions)) = page else {
+            return Ok(None);
+        };
+        if cursor.map_or(false, |cursor| {
+            cursor.key().miniblock_number != block_number
+        }) {
+            return Err(Web3Error::ForeignPageCursor);
+        }
+
+        let page = Page::new(transactions, limit, |tx| TransactionCursorKey {
+            miniblock_number: block_number,
+            index_in_block: tx.transaction_index.unwrap_or_default().as_u32(),
+        });
+        Ok(Some(BlockTransactionsPage {
+            block_number: block_number.0.into(),
+            total_count: tx_count.as_u64().into(),
+            transactions: page.items,
+            next_cursor: page.next_cursor,
+        }))
     }
 
     #[tracing::instrument(skip(self))]
@@ -226,7 +453,7 @@ impl OlaNamespace {
             .blocks_web3_dal()
             .get_l1_batch_details(batch_number)
             .await
-            .map_err(|err| internal_error("get_l1_batch_details", err))?)
+            .map_err(|err| dal_error("get_l1_batch_details", err))?)
     }
 
     #[tracing::instrument(skip(self))]
@@ -251,4 +478,459 @@ impl OlaNamespace {
         };
         Ok(protocol_version)
     }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_system_contracts_impl(
+        &self,
+        version_id: Option<u16>,
+    ) -> anyhow::Result<Vec<SystemContract>, Web3Error> {
+        let version_id = match version_id {
+            Some(id) => id,
+            None => {
+                let mut storage = self.access_storage().await?;
+                storage
+                    .protocol_versions_web3_dal()
+                    .get_latest_protocol_version()
+                    .await
+                    .version_id
+            }
+        };
+        // Unknown protocol versions have no system contracts.
+        let Ok(version) = ProtocolVersionId::try_from(version_id) else {
+            return Ok(vec![]);
+        };
+        Ok(system_contracts_for_version(version)
+            .map(SystemContract::from)
+            .collect())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_token_info_impl(
+        &self,
+        address: Address,
+    ) -> anyhow::Result<Option<TokenDetails>, Web3Error> {
+        const METHOD_NAME: &str = "get_token_info";
+
+        let mut storage = self.access_storage().await?;
+        storage
+            .tokens_dal()
+            .get_token_details(address)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_token_balances_impl(
+        &self,
+        account: Address,
+    ) -> anyhow::Result<Vec<TokenBalance>, Web3Error> {
+        const METHOD_NAME: &str = "get_token_balances";
+
+        let mut storage = self.access_storage().await?;
+        storage
+            .tokens_dal()
+            .get_token_balances(account)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))
+    }
+
+    #[tracing::instrument(skip(self, keys))]
+    pub async fn get_proof_impl(
+        &self,
+        address: Address,
+        keys: Vec<H256>,
+    ) -> anyhow::Result<Proof, Web3Error> {
+        let tree_reader = self.state.tree_reader()?;
+        let hashed_keys = keys
+            .iter()
+            .map(|key| StorageKey::new(AccountTreeId::new(address), *key).hashed_key())
+            .collect();
+        let proofs = tree_reader
+            .entries_with_proofs(hashed_keys)
+            .await
+            .map_err(|err| tree_error("get_proof", err))?;
+
+        let storage_proof = keys
+            .into_iter()
+            .zip(proofs.entries)
+            .map(|(key, entry)| StorageProof {
+                key,
+                proof: entry.merkle_path.iter().map(tree_key_to_h256).collect(),
+                value: tree_value_to_h256(&entry.value),
+                index: entry.leaf_index,
+            })
+            .collect();
+        Ok(Proof {
+            address,
+            storage_proof,
+        })
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn node_info_impl(&self) -> anyhow::Result<NodeInfo, Web3Error> {
+        let mut storage = self.access_storage().await?;
+        let protocol_version = storage
+            .protocol_versions_web3_dal()
+            .get_latest_protocol_version()
+            .await;
+        let genesis_hash = storage
+            .blocks_dal()
+            .get_l1_batch_state_root(L1BatchNumber(0))
+            .await
+            .map_err(|err| dal_error("node_info", err))?;
+        Ok(NodeInfo {
+            version: build_info::VERSION.to_owned(),
+            git_commit: build_info::GIT_COMMIT.to_owned(),
+            build_profile: build_info::BUILD_PROFILE.to_owned(),
+            features: build_info::features(),
+            components: self
+                .state
+                .components
+                .iter()
+                .map(|component| format!("{component:?}"))
+                .collect(),
+            protocol_version: protocol_version.version_id,
+            chain_id: self.state.api_config.l2_chain_id.0.into(),
+            genesis_hash,
+        })
+    }
+
+    #[tracing::instrument(skip(self, addresses))]
+    pub async fn get_accounts_info_impl(
+        &self,
+        addresses: Vec<Address>,
+    ) -> anyhow::Result<Vec<AccountInfo>, Web3Error> {
+        const METHOD_NAME: &str = "get_accounts_info";
+
+        if addresses.len() > MAX_ACCOUNTS_INFO_ADDRESSES {
+            return Err(Web3Error::TooManyAddresses(MAX_ACCOUNTS_INFO_ADDRESSES));
+        }
+        let keys: Vec<_> = addresses
+            .iter()
+            .flat_map(|address| [get_nonce_key(address), get_full_code_key(address)])
+            .collect();
+
+        let mut storage = self.access_storage().await?;
+        let block_number = storage
+            .blocks_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+        let values = storage
+            .storage_web3_dal()
+            .get_historical_values_unchecked(&keys, block_number)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+
+        Ok(addresses
+            .into_iter()
+            .zip(keys.chunks(2))
+            .map(|(address, keys)| AccountInfo {
+                address,
+                nonce: h256_to_u256(values[&keys[0]]),
+                code_hash: values[&keys[1]],
+            })
+            .collect())
+    }
+
+    /// Returns the status of a transaction, including transactions dropped from the mempool,
+    /// so that clients polling for a receipt of a dropped transaction can stop waiting.
+    #[olaos_logs::instrument(skip(self))]
+    pub async fn get_transaction_status_impl(
+        &self,
+        hash: H256,
+    ) -> anyhow::Result<Option<TransactionStatusInfo>, Web3Error> {
+        const METHOD_NAME: &str = "get_transaction_status";
+
+        if let Some(details) = self.get_transaction_details_impl(hash).await? {
+            let simulation = self
+                .access_storage()
+                .await?
+                .transactions_web3_dal()
+                .get_transaction_simulation(hash)
+                .await
+                .map_err(|err| dal_error(METHOD_NAME, err))?;
+            return Ok(Some(TransactionStatusInfo {
+                hash,
+                status: Some(details.status),
+                error: details.revert_reason,
+                dropped: None,
+                simulation,
+            }));
+        }
+        let dropped = self
+            .access_storage()
+            .await?
+            .transactions_web3_dal()
+            .get_dropped_transaction(hash)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+        Ok(dropped.map(|dropped| TransactionStatusInfo {
+            hash,
+            status: None,
+            error: None,
+            dropped: Some(dropped),
+            simulation: None,
+        }))
+    }
+
+    #[olaos_logs::instrument(skip(self))]
+    pub async fn get_transaction_inclusion_proof_impl(
+        &self,
+        hash: H256,
+    ) -> anyhow::Result<Option<TransactionInclusionProof>, Web3Error> {
+        const METHOD_NAME: &str = "get_transaction_inclusion_proof";
+
+        let mut storage = self.access_storage().await?;
+        let l1_batch_number = storage
+            .transactions_web3_dal()
+            .get_transaction_l1_batch_number(hash)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+        let Some(l1_batch_number) = l1_batch_number else {
+            return Ok(None);
+        };
+        self.state.start_info.ensure_not_pruned(l1_batch_number)?;
+
+        let leaves = storage
+            .blocks_web3_dal()
+            .get_l1_batch_receipt_leaves(l1_batch_number)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+        let Some(position) = leaves.iter().position(|leaf| leaf.tx_hash == hash) else {
+            return Ok(None);
+        };
+        let leaf = leaves[position];
+        if leaf.index_in_batch as usize != position {
+            let err = format!("transactions of L1 batch #{l1_batch_number} are not contiguous");
+            return Err(internal_error(METHOD_NAME, err));
+        }
+
+        let tree = ReceiptsTree::new(&leaves);
+        let receipts_root = tree.root();
+        let sealed_receipts_root = storage
+            .blocks_dal()
+            .get_l1_batch_receipts_root(l1_batch_number)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+        if sealed_receipts_root.is_some_and(|root| root != receipts_root) {
+            let err = format!(
+                "receipts root of L1 batch #{l1_batch_number} differs from the one computed on seal"
+            );
+            return Err(internal_error(METHOD_NAME, err));
+        }
+
+        let commitment = storage
+            .blocks_dal()
+            .get_l1_batch_metadata(l1_batch_number)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?
+            .map(|batch| ReceiptsRootCommitment {
+                initial_writes_hash: hash_bytes(&batch.metadata.initial_writes_compressed),
+                repeated_writes_hash: hash_bytes(&batch.metadata.repeated_writes_compressed),
+                pass_through_data_hash: batch.metadata.pass_through_data_hash,
+                meta_parameters_hash: batch.metadata.meta_parameters_hash,
+                commitment: batch.metadata.commitment,
+            });
+
+        Ok(Some(TransactionInclusionProof {
+            tx_hash: hash,
+            l1_batch_number,
+            index_in_batch: leaf.index_in_batch,
+            succeeded: leaf.succeeded,
+            proof: tree.merkle_path(position).unwrap(),
+            receipts_root,
+            commitment,
+        }))
+    }
+
+    #[olaos_logs::instrument(skip(self))]
+    pub async fn get_storage_history_impl(
+        &self,
+        address: Address,
+        key: H256,
+        from_block: MiniblockNumber,
+        to_block: Option<MiniblockNumber>,
+        cursor: Option<PageCursor<MiniblockNumber>>,
+        limit: Option<usize>,
+    ) -> anyhow::Result<StorageHistoryPage, Web3Error> {
+        const METHOD_NAME: &str = "get_storage_history";
+
+        self.state.start_info.ensure_not_pruned(from_block)?;
+        let limit = page_limit(limit);
+        let mut storage = self.access_storage().await?;
+        let to_block = match to_block {
+            Some(number) => number,
+            None => storage
+                .blocks_dal()
+                .get_sealed_miniblock_number()
+                .await
+                .map_err(|err| dal_error(METHOD_NAME, err))?,
+        };
+        if from_block > to_block {
+            return Err(Web3Error::InvalidBlockRange(from_block, to_block));
+        }
+
+        let start = match cursor {
+            Some(cursor) => cursor
+                .key()
+                .0
+                .checked_add(1)
+                .map(|next| from_block.max(MiniblockNumber(next))),
+            None => Some(from_block),
+        };
+        let start = match start {
+            Some(start) if start <= to_block => start,
+            _ => return Ok(Page::new(vec![], limit, |change| change.miniblock_number)),
+        };
+
+        let key = StorageKey::new(AccountTreeId::new(address), key);
+        // Fetch an extra change to find out whether the page is truncated.
+        let changes = storage
+            .storage_web3_dal()
+            .get_storage_history(&key, start..=to_block, limit + 1)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+        Ok(Page::new(changes, limit, |change| change.miniblock_number))
+    }
+
+    #[olaos_logs::instrument(skip(self))]
+    pub async fn get_reorged_out_blocks_impl(
+        &self,
+        number: MiniblockNumber,
+    ) -> anyhow::Result<Vec<ReorgedOutBlock>, Web3Error> {
+        const METHOD_NAME: &str = "get_reorged_out_blocks";
+
+        self.access_storage()
+            .await?
+            .reverts_dal()
+            .get_reorged_out_blocks(number)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))
+    }
+
+    #[olaos_logs::instrument(skip(self))]
+    pub async fn get_reorged_out_transaction_impl(
+        &self,
+        hash: H256,
+    ) -> anyhow::Result<Vec<ReorgedOutTransaction>, Web3Error> {
+        const METHOD_NAME: &str = "get_reorged_out_transaction";
+
+        self.access_storage()
+            .await?
+            .reverts_dal()
+            .get_reorged_out_transaction(hash)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))
+    }
+
+    #[olaos_logs::instrument(skip(self))]
+    pub async fn get_logs_page_impl(
+        &self,
+        address: Option<Address>,
+        cursor: Option<PageCursor<LogCursorKey>>,
+        limit: Option<usize>,
+    ) -> anyhow::Result<LogsPage, Web3Error> {
+        const METHOD_NAME: &str = "get_logs_page";
+
+        let limit = page_limit(limit);
+        let after = cursor.map(|cursor| {
+            let key = cursor.key();
+            (key.miniblock_number, key.event_index_in_block)
+        });
+        // Fetch an extra log to find out whether the page is truncated.
+        let logs = self
+            .access_storage()
+            .await?
+            .events_dal()
+            .get_logs_page(address, after, limit + 1)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+        Ok(Page::new(logs, limit, |log| LogCursorKey {
+            miniblock_number: MiniblockNumber(log.block_number.unwrap_or_default().as_u32()),
+            event_index_in_block: log.log_index.unwrap_or_default().as_u32(),
+        }))
+    }
+
+    #[olaos_logs::instrument(skip(self))]
+    pub async fn get_transactions_by_address_impl(
+        &self,
+        address: Address,
+        cursor: Option<PageCursor<TransactionCursorKey>>,
+        limit: Option<usize>,
+    ) -> anyhow::Result<TransactionsPage, Web3Error> {
+        const METHOD_NAME: &str = "get_transactions_by_address";
+
+        let limit = page_limit(limit);
+        let after = cursor.map(|cursor| {
+            let key = cursor.key();
+            (key.miniblock_number, key.index_in_block)
+        });
+        let mut storage = self.access_storage().await?;
+        let hashes = storage
+            .transactions_web3_dal()
+            .get_sealed_tx_hashes_by_initiator(address, after, limit + 1)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+        let mut receipts = storage
+            .transactions_web3_dal()
+            .get_transaction_receipts(&hashes)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+        receipts.sort_unstable_by_key(|receipt| (receipt.block_number, receipt.transaction_index));
+        Ok(Page::new(receipts, limit, |receipt| TransactionCursorKey {
+            miniblock_number: MiniblockNumber(receipt.block_number.unwrap_or_default().as_u32()),
+            index_in_block: receipt.transaction_index.as_u32(),
+        }))
+    }
+
+    #[olaos_logs::instrument(skip(self))]
+    pub async fn get_l1_batches_impl(
+        &self,
+        cursor: Option<PageCursor<L1BatchNumber>>,
+        limit: Option<usize>,
+    ) -> anyhow::Result<L1BatchesPage, Web3Error> {
+        const METHOD_NAME: &str = "get_l1_batches";
+
+        let limit = page_limit(limit);
+        let after = cursor.map(|cursor| *cursor.key());
+        let batches = self
+            .access_storage()
+            .await?
+            .blocks_web3_dal()
+            .get_l1_batch_details_page(after, limit + 1)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+        Ok(Page::new(batches, limit, |details| details.number))
+    }
+
+    #[olaos_logs::instrument(skip(self))]
+    pub async fn get_account_storage_range_impl(
+        &self,
+        address: Address,
+        block: Option<BlockIdVariant>,
+        cursor: Option<PageCursor<H256>>,
+        limit: Option<usize>,
+    ) -> anyhow::Result<StorageRangePage, Web3Error> {
+        const METHOD_NAME: &str = "get_account_storage_range";
+
+        let block = block.unwrap_or(BlockIdVariant::BlockNumber(BlockNumber::Latest));
+        self.state
+            .start_info
+            .ensure_not_pruned(BlockId::from(block))?;
+        let limit = page_limit(limit);
+        let after = cursor.map(|cursor| *cursor.key());
+        let mut storage = self.access_storage().await?;
+        let block_number = resolve_block(&mut storage, block, METHOD_NAME).await?;
+        let entries = storage
+            .storage_web3_dal()
+            .get_account_storage_range(address, block_number, after, limit + 1)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+        Ok(Page::new(entries, limit, |entry| entry.key))
+    }
+}
+
+fn page_limit(limit: Option<usize>) -> usize {
+    limit.unwrap_or(MAX_PAGE_LIMIT).clamp(1, MAX_PAGE_LIMIT)
 }
@@ -0,0 +1,28 @@
+use ola_types::api::TxpoolContent;
+use ola_web3_decl::error::Web3Error;
+
+use crate::sequencer::types::MempoolGuard;
+
+/// Maximum number of transactions returned by `txpool_content` per account and in total.
+const MAX_CONTENT_TRANSACTIONS_PER_ACCOUNT: usize = 64;
+const MAX_CONTENT_TRANSACTIONS: usize = 4_096;
+
+#[derive(Debug, Clone)]
+pub struct TxpoolNamespace {
+    mempool: Option<MempoolGuard>,
+}
+
+impl TxpoolNamespace {
+    pub fn new(mempool: Option<MempoolGuard>) -> Self {
+        Self { mempool }
+    }
+
+    #[olaos_logs::instrument(skip(self))]
+    pub fn content_impl(&self) -> Result<TxpoolContent, Web3Error> {
+        let mempool = self.mempool.as_ref().ok_or(Web3Error::MempoolUnavailable)?;
+        Ok(mempool.content(
+            MAX_CONTENT_TRANSACTIONS_PER_ACCOUNT,
+            MAX_CONTENT_TRANSACTIONS,
+        ))
+    }
+}
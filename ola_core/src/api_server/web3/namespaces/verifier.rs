@@ -0,0 +1,273 @@
+use std::{collections::HashSet, sync::Arc};
+
+use ola_config::offchain_verifier::OffChainVerifierConfig;
+use ola_dal::{
+    connection::ConnectionPool, proof_offchain_verification_dal::ProofVerificationStatus,
+};
+use ola_types::{
+    api::proof_offchain_verification::{AttestationInfo, L1BatchAttestations, VerifierAttestation},
+    tx::primitives::PackedEthSignature,
+    Address, L1BatchNumber, L2ChainId,
+};
+use ola_web3_decl::error::Web3Error;
+
+use crate::api_server::web3::backend::error::dal_error;
+
+/// Members of the offchain verifier committee and the number of matching attestations
+/// required to decide the outcome of a verification.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VerifierCommittee {
+    members: HashSet<Address>,
+    quorum_threshold: usize,
+}
+
+impl VerifierCommittee {
+    pub fn new(members: impl IntoIterator<Item = Address>, quorum_threshold: usize) -> Self {
+        Self {
+            members: members.into_iter().collect(),
+            quorum_threshold,
+        }
+    }
+
+    pub fn from_config(config: &OffChainVerifierConfig) -> Self {
+        Self::new(config.verifier_addresses(), config.quorum_threshold())
+    }
+
+    fn check_signature(
+        &self,
+        attestation: &VerifierAttestation,
+        chain_id: L2ChainId,
+    ) -> Result<(), Web3Error> {
+        if !self.members.contains(&attestation.verifier) {
+            return Err(Web3Error::InvalidAttestation(format!(
+                "{:?} is not a member of the verifier committee",
+                attestation.verifier
+            )));
+        }
+        let signature = PackedEthSignature::deserialize_packed(&attestation.signature.0)
+            .map_err(|err| Web3Error::InvalidAttestation(err.to_string()))?;
+        let message = attestation.signed_message(chain_id);
+        if !signature.is_signed_by(&message, &attestation.verifier) {
+            return Err(Web3Error::InvalidAttestation(
+                "signature doesn't match the verifier".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns whether members' attestations reach the quorum on the batch being valid or invalid;
+    /// `None` if no outcome has reached the quorum yet.
+    fn quorum_outcome(&self, attestations: &[AttestationInfo]) -> Option<bool> {
+        if self.quorum_threshold == 0 {
+            return None;
+        }
+        let member_attestations = attestations
+            .iter()
+            .filter(|attestation| self.members.contains(&attestation.verifier));
+        let (valid, invalid) = member_attestations.fold((0, 0), |(valid, invalid), attestation| {
+            if attestation.is_valid {
+                (valid + 1, invalid)
+            } else {
+                (valid, invalid + 1)
+            }
+        });
+        if valid >= self.quorum_threshold {
+            Some(true)
+        } else if invalid >= self.quorum_threshold {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VerifierNamespace {
+    connection_pool: ConnectionPool,
+    committee: Arc<VerifierCommittee>,
+    chain_id: L2ChainId,
+}
+
+impl VerifierNamespace {
+    pub fn new(
+        connection_pool: ConnectionPool,
+        committee: Arc<VerifierCommittee>,
+        chain_id: L2ChainId,
+    ) -> Self {
+        Self {
+            connection_pool,
+            committee,
+            chain_id,
+        }
+    }
+
+    #[olaos_logs::instrument(skip(self))]
+    pub async fn submit_attestation_impl(
+        &self,
+        attestation: VerifierAttestation,
+    ) -> Result<L1BatchAttestations, Web3Error> {
+        const METHOD_NAME: &str = "submit_attestation";
+
+        self.committee
+            .check_signature(&attestation, self.chain_id)?;
+        let l1_batch_number = attestation.l1_batch_number;
+        let mut storage = self.connection_pool.access_storage_tagged("api").await;
+        let mut transaction = storage.start_transaction().await;
+        let locked = transaction
+            .proof_verification_dal()
+            .lock_l1_batch_for_attestation(l1_batch_number)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+        let Some((mut status, commitment)) = locked else {
+            return Err(Web3Error::InvalidAttestation(format!(
+                "L1 batch #{l1_batch_number} is not ready to be verified"
+            )));
+        };
+        if commitment != Some(attestation.commitment) {
+            return Err(Web3Error::InvalidAttestation(format!(
+                "commitment doesn't match the commitment of L1 batch #{l1_batch_number}"
+            )));
+        }
+
+        transaction
+            .proof_verification_dal()
+            .insert_attestation(&attestation)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+        let attestations = transaction
+            .proof_verification_dal()
+            .get_attestations(l1_batch_number)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+        metrics::counter!(
+            "api.verifier.attestations",
+            1,
+            "is_valid" => attestation.is_valid.to_string()
+        );
+
+        // Late attestations are recorded, but don't change the decided outcome.
+        if !status.is_final() {
+            if let Some(is_passed) = self.committee.quorum_outcome(&attestations) {
+                transaction
+                    .proof_verification_dal()
+                    .mark_l1_batch_as_verified(l1_batch_number, is_passed)
+                    .await
+                    .map_err(|err| dal_error(METHOD_NAME, err))?;
+                status = if is_passed {
+                    ProofVerificationStatus::OffChainVerifyPassed
+                } else {
+                    ProofVerificationStatus::OffChainVerifyFailed
+                };
+                olaos_logs::info!(
+                    "Verifier committee reached quorum for L1 batch #{l1_batch_number}: {status}"
+                );
+            }
+        }
+        transaction.commit().await;
+
+        Ok(L1BatchAttestations {
+            l1_batch_number,
+            quorum_threshold: self.committee.quorum_threshold,
+            verifier_status: status.to_string(),
+            attestations,
+        })
+    }
+
+    #[olaos_logs::instrument(skip(self))]
+    pub async fn get_l1_batch_attestations_impl(
+        &self,
+        l1_batch_number: L1BatchNumber,
+    ) -> Result<Option<L1BatchAttestations>, Web3Error> {
+        const METHOD_NAME: &str = "get_l1_batch_attestations";
+
+        let mut storage = self.connection_pool.access_storage_tagged("api").await;
+        let status = storage
+            .proof_verification_dal()
+            .get_l1_batch_verification_status(l1_batch_number)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+        if status == ProofVerificationStatus::NotReady {
+            return Ok(None);
+        }
+        let attestations = storage
+            .proof_verification_dal()
+            .get_attestations(l1_batch_number)
+            .await
+            .map_err(|err| dal_error(METHOD_NAME, err))?;
+        Ok(Some(L1BatchAttestations {
+            l1_batch_number,
+            quorum_threshold: self.committee.quorum_threshold,
+            verifier_status: status.to_string(),
+            attestations,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use ola_types::{Bytes, H256};
+
+    use super::*;
+
+    fn attestation_info(verifier: u8, is_valid: bool) -> AttestationInfo {
+        AttestationInfo {
+            verifier: Address::repeat_byte(verifier),
+            is_valid,
+            attested_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn quorum_counts_only_committee_members() {
+        let committee = VerifierCommittee::new((1..=3).map(Address::repeat_byte), 2);
+        assert_eq!(committee.quorum_outcome(&[]), None);
+        let attestations = [attestation_info(1, true), attestation_info(9, true)];
+        assert_eq!(committee.quorum_outcome(&attestations), None);
+
+        let attestations = [attestation_info(1, true), attestation_info(2, true)];
+        assert_eq!(committee.quorum_outcome(&attestations), Some(true));
+        let attestations = [
+            attestation_info(1, true),
+            attestation_info(2, false),
+            attestation_info(3, false),
+        ];
+        assert_eq!(committee.quorum_outcome(&attestations), Some(false));
+
+        let empty_committee = VerifierCommittee::default();
+        assert_eq!(empty_committee.quorum_outcome(&attestations), None);
+    }
+
+    #[test]
+    fn checking_attestation_signatures() {
+        let chain_id = L2ChainId(1027);
+        let private_key = H256::repeat_byte(0x11);
+        let verifier = PackedEthSignature::address_from_private_key(&private_key).unwrap();
+        let committee = VerifierCommittee::new([verifier], 1);
+        let mut attestation = VerifierAttestation {
+            l1_batch_number: L1BatchNumber(5),
+            commitment: H256::repeat_byte(0x22),
+            is_valid: true,
+            verifier,
+            signature: Bytes::default(),
+        };
+        let signature =
+            PackedEthSignature::sign_raw(&private_key, &attestation.signed_message(chain_id))
+                .unwrap();
+        attestation.signature = Bytes(signature.serialize_packed_without_v().to_vec());
+        committee.check_signature(&attestation, chain_id).unwrap();
+
+        // The attestation can't be replayed on another chain.
+        let err = committee
+            .check_signature(&attestation, L2ChainId(1028))
+            .unwrap_err();
+        assert!(matches!(err, Web3Error::InvalidAttestation(_)), "{err:?}");
+
+        // The verdict is covered by the signature.
+        attestation.is_valid = false;
+        let err = committee
+            .check_signature(&attestation, chain_id)
+            .unwrap_err();
+        assert!(matches!(err, Web3Error::InvalidAttestation(_)), "{err:?}");
+    }
+}
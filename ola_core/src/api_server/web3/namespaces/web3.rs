@@ -0,0 +1,8 @@
+#[derive(Debug, Clone)]
+pub struct Web3Namespace;
+
+impl Web3Namespace {
+    pub fn client_version_impl(&self) -> String {
+        format!("ola-os/v{}", env!("CARGO_PKG_VERSION"))
+    }
+}
@@ -1,27 +1,38 @@
 use std::io::Write;
 
-use futures::FutureExt;
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    sync::Arc,
+};
+
+use anyhow::Context as _;
+use futures::{future, FutureExt};
 use jsonrpsee::{
     core::{server::SubscriptionMessage, SubscriptionResult},
     server::IdProvider,
     types::{error::ErrorCode, ErrorObject, SubscriptionId},
-    PendingSubscriptionSink, SendTimeoutError, SubscriptionSink,
+    DisconnectError, PendingSubscriptionSink, SubscriptionSink,
 };
+use ola_config::api::SubscriptionOverflowPolicy;
 use ola_contracts::BaseSystemContractsHashes;
 use ola_dal::{connection::ConnectionPool, StorageProcessor};
 use ola_types::{
+    api::{
+        proof_offchain_verification::L1BatchVerificationTask, L1BatchStatus, L1BatchStatusUpdate,
+    },
     block::L1BatchHeader,
     commitment::{L1BatchMetaParameters, L1BatchMetadata, L1BatchWithMetadata},
     proofs::L1BatchProofForL1,
     protocol_version::ProtocolVersionId,
     prove_batches::ProveBatches,
-    Address, L1BatchNumber, MiniblockNumber, H256,
+    Address, Bytes, L1BatchNumber, MiniblockNumber, H256, U64,
 };
 use ola_utils::time::seconds_since_epoch;
 use ola_web3_decl::{
     namespaces::eth::EthPubSubServer,
     types::{L1BatchProofForVerify, PubSubFilter, PubSubResult},
 };
+use olaos_object_store::ObjectStore;
 use olaos_prover_fri_types::{FriProofWrapper, OlaBaseLayerProof};
 use tokio::{
     sync::{broadcast, mpsc, watch},
@@ -32,6 +43,19 @@ use web3::types::H128;
 
 const BROADCAST_CHANNEL_CAPACITY: usize = 1024;
 const SUBSCRIPTION_SINK_SEND_TIMEOUT: Duration = Duration::from_secs(10);
+/// Maximum number of L1 batches per status reported in a single polling iteration.
+const L1_BATCH_STATUSES_PER_ITERATION: usize = 100;
+/// Number of L1 batches before the last one with a status that are re-checked for the status
+/// on start, so that batches reaching it out of order before the start are still notified.
+const L1_BATCH_STATUSES_STARTUP_LOOKBACK: u32 = 100;
+/// Maximum number of L1 batches per status notified ahead of a batch that hasn't reached
+/// the status yet. If exceeded, the lagging batch is assumed to never reach the status.
+const MAX_L1_BATCH_STATUSES_AHEAD: usize = 1_000;
+/// Number of miniblocks loaded from Postgres at once when replaying notifications.
+const REPLAY_CHUNK_SIZE: u32 = 100;
+/// Maximum number of L1 batches loaded at once for `verificationTasks`; kept small since
+/// each task carries a proof.
+const VERIFICATION_TASKS_PER_ITERATION: usize = 10;
 pub const EVENT_TOPIC_NUMBER_LIMIT: usize = 4;
 
 #[derive(Debug, Clone, Copy)]
@@ -59,6 +83,194 @@ pub(super) enum SubscriptionType {
     Txs,
     Logs,
     L1BatchProofs,
+    L1Batches,
+    VerificationTasks,
+}
+
+impl SubscriptionType {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Blocks => "blocks",
+            Self::Txs => "txs",
+            Self::Logs => "logs",
+            Self::L1BatchProofs => "l1_batch_proofs",
+            Self::L1Batches => "l1_batches",
+            Self::VerificationTasks => "verification_tasks",
+        }
+    }
+}
+
+/// Notifications buffered for a single subscription, so that a slow subscriber doesn't
+/// hold back the broadcast of notifications to the others.
+#[derive(Debug)]
+struct SubscriptionQueue {
+    items: VecDeque<PubSubResult>,
+    capacity: usize,
+    overflow_policy: SubscriptionOverflowPolicy,
+}
+
+impl SubscriptionQueue {
+    fn new(capacity: usize, overflow_policy: SubscriptionOverflowPolicy) -> Self {
+        Self {
+            items: VecDeque::with_capacity(capacity.min(BROADCAST_CHANNEL_CAPACITY)),
+            capacity,
+            overflow_policy,
+        }
+    }
+
+    /// Pushes an item to the queue. Returns the number of dropped items, or `None` if the queue
+    /// is full and the subscription must be closed.
+    fn push(&mut self, item: PubSubResult) -> Option<usize> {
+        let mut dropped = 0;
+        if self.items.len() >= self.capacity {
+            match self.overflow_policy {
+                SubscriptionOverflowPolicy::DropOldest => {
+                    self.items.pop_front();
+                    dropped = 1;
+                }
+                SubscriptionOverflowPolicy::Disconnect => return None,
+            }
+        }
+        self.items.push_back(item);
+        Some(dropped)
+    }
+}
+
+/// L1 batches notified as having reached a certain status. Batches may reach a status out of
+/// order (e.g., a batch may be proven before the previous one), so notified batches are tracked
+/// individually after the first batch which hasn't reached the status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct NotifiedL1Batches {
+    /// First batch which hasn't been notified; all earlier batches have been.
+    next_expected: L1BatchNumber,
+    /// Notified batches after `next_expected`.
+    ahead: BTreeSet<L1BatchNumber>,
+}
+
+impl NotifiedL1Batches {
+    fn new(next_expected: L1BatchNumber) -> Self {
+        Self {
+            next_expected,
+            ahead: BTreeSet::new(),
+        }
+    }
+
+    /// Batches starting from `next_expected` that must not be notified again.
+    fn exclude(&self) -> Vec<L1BatchNumber> {
+        self.ahead.iter().copied().collect()
+    }
+
+    /// Records newly notified batches. Returns the lagging batch if it's skipped because too many
+    /// batches have been notified ahead of it.
+    fn record(&mut self, numbers: &[L1BatchNumber]) -> Option<L1BatchNumber> {
+        self.ahead.extend(
+            numbers
+                .iter()
+                .copied()
+                .filter(|&number| number >= self.next_expected),
+        );
+        let mut skipped = None;
+        if self.ahead.len() > MAX_L1_BATCH_STATUSES_AHEAD {
+            skipped = Some(self.next_expected);
+            self.next_expected = *self.ahead.first().unwrap();
+        }
+        while self.ahead.remove(&self.next_expected) {
+            self.next_expected += 1;
+        }
+        skipped
+    }
+}
+
+/// Miniblocks whose notifications are replayed to a subscription before it switches to
+/// the live notifications.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ReplayRange {
+    from: MiniblockNumber,
+    /// Last sealed miniblock at the time of subscribing; live notifications up to and including it
+    /// are skipped, since they are replayed.
+    to: MiniblockNumber,
+}
+
+impl ReplayRange {
+    /// Returns the range to replay for a subscription requested from `from_block` once `sealed`
+    /// is the last sealed miniblock, or an error message if `from_block` is out of
+    /// the `window` of last miniblocks that can be replayed.
+    fn new(from_block: U64, sealed: MiniblockNumber, window: u32) -> Result<Option<Self>, String> {
+        let from = u32::try_from(from_block.as_u64())
+            .map(MiniblockNumber)
+            .map_err(|_| format!("fromBlock {from_block} is out of range"))?;
+        if from > sealed {
+            return Ok(None); // Nothing to replay
+        }
+        if sealed.0 - from.0 >= window {
+            let earliest = sealed.0 + 1 - window;
+            return Err(format!(
+                "fromBlock {from} is out of the replay window of {window} blocks; \
+                 earliest block that can be replayed is {earliest}"
+            ));
+        }
+        Ok(Some(Self { from, to: sealed }))
+    }
+
+    fn chunks(self) -> impl Iterator<Item = (MiniblockNumber, MiniblockNumber)> {
+        (self.from.0..=self.to.0)
+            .step_by(REPLAY_CHUNK_SIZE as usize)
+            .map(move |start| {
+                let end = start.saturating_add(REPLAY_CHUNK_SIZE - 1).min(self.to.0);
+                (MiniblockNumber(start), MiniblockNumber(end))
+            })
+    }
+}
+
+/// Returns the miniblock a `newHeads` or `logs` notification belongs to.
+fn notification_block_number(item: &PubSubResult) -> Option<MiniblockNumber> {
+    let number = match item {
+        PubSubResult::Header(header) => header.number,
+        PubSubResult::Log(log) => log.block_number?,
+        _ => return None,
+    };
+    Some(MiniblockNumber(number.as_u32()))
+}
+
+/// Loads L1 batches waiting for offchain verification together with their proofs.
+#[derive(Debug, Clone)]
+struct VerificationTaskLoader {
+    connection_pool: ConnectionPool,
+    proof_store: Option<Arc<dyn ObjectStore>>,
+}
+
+impl VerificationTaskLoader {
+    async fn load(
+        &self,
+        after: Option<L1BatchNumber>,
+        with_proofs: bool,
+    ) -> anyhow::Result<Vec<L1BatchVerificationTask>> {
+        let mut storage = self.connection_pool.access_storage_tagged("api").await;
+        let mut tasks = storage
+            .proof_verification_dal()
+            .get_l1_batches_to_be_verified(after, VERIFICATION_TASKS_PER_ITERATION)
+            .await
+            .context("get_l1_batches_to_be_verified()")?;
+        drop(storage);
+
+        let Some(proof_store) = self.proof_store.as_ref().filter(|_| with_proofs) else {
+            return Ok(tasks);
+        };
+        for task in &mut tasks {
+            // A missing proof shouldn't stop the notifications; verifiers can skip such tasks.
+            match proof_store
+                .get::<L1BatchProofForL1>(task.l1_batch_number)
+                .await
+            {
+                Ok(proof) => task.proof = Some(Bytes(proof.proof)),
+                Err(err) => olaos_logs::warn!(
+                    "Failed loading proof for L1 batch #{}: {err}",
+                    task.l1_batch_number
+                ),
+            }
+        }
+        Ok(tasks)
+    }
 }
 
 /// Manager of notifications for a certain type of subscriptions.
@@ -71,26 +283,14 @@ struct PubSubNotifier {
 }
 
 impl PubSubNotifier {
-    // async fn get_starting_miniblock_number(&self) -> anyhow::Result<MiniblockNumber> {
-    //     let mut storage = self
-    //         .connection_pool
-    //         .access_storage_tagged("api")
-    //         .await
-    //         .context("access_storage_tagged")?;
-    //     let sealed_miniblock_number = storage
-    //         .blocks_dal()
-    //         .get_sealed_miniblock_number()
-    //         .await
-    //         .context("get_sealed_miniblock_number()")?;
-    //     Ok(match sealed_miniblock_number {
-    //         Some(number) => number,
-    //         None => {
-    //             // We don't have miniblocks in the storage yet. Use the snapshot miniblock number instead.
-    //             let start_info = BlockStartInfo::new(&mut storage).await?;
-    //             MiniblockNumber(start_info.first_miniblock.saturating_sub(1))
-    //         }
-    //     })
-    // }
+    async fn get_starting_miniblock_number(&self) -> anyhow::Result<MiniblockNumber> {
+        let mut storage = self.connection_pool.access_storage_tagged("api").await;
+        storage
+            .blocks_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .context("get_sealed_miniblock_number()")
+    }
 
     fn emit_event(&self, event: PubSubEvent) {
         if let Some(sender) = &self.events_sender {
@@ -100,35 +300,44 @@ impl PubSubNotifier {
 }
 
 impl PubSubNotifier {
-    // async fn notify_blocks(self, stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
-    //     let mut last_block_number = self.get_starting_miniblock_number().await?;
-    //     let mut timer = Interval(self.polling_interval);
-    //     loop {
-    //         if *stop_receiver.borrow() {
-    //             tracing::info!("Stop signal received, pubsub_block_notifier is shutting down");
-    //             break;
-    //         }
-    //         timer.tick().await;
+    async fn notify_blocks(self, stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        let mut last_block_number = self.get_starting_miniblock_number().await?;
+        let mut timer = interval(self.polling_interval);
+        loop {
+            if *stop_receiver.borrow() {
+                olaos_logs::info!("Stop signal received, pubsub_block_notifier is shutting down");
+                break;
+            }
+            timer.tick().await;
 
-    //         let db_latency = PUB_SUB_METRICS.db_poll_latency[&SubscriptionType::Blocks].start();
-    //         let new_blocks = self.new_blocks(last_block_number).await?;
-    //         db_latency.observe();
-
-    //         if let Some(last_block) = new_blocks.last() {
-    //             last_block_number = MiniblockNumber(last_block.number.unwrap().as_u32());
-    //             let new_blocks = new_blocks.into_iter().map(PubSubResult::Header).collect();
-    //             self.send_pub_sub_results(new_blocks, SubscriptionType::Blocks);
-    //             self.emit_event(PubSubEvent::MiniblockAdvanced(
-    //                 SubscriptionType::Blocks,
-    //                 last_block_number,
-    //             ));
-    //         }
-    //         self.emit_event(PubSubEvent::NotifyIterationFinished(
-    //             SubscriptionType::Blocks,
-    //         ));
-    //     }
-    // Ok(())
-    // }
+            let mut storage = self.connection_pool.access_storage_tagged("api").await;
+            let sealed_block_number = storage
+                .blocks_dal()
+                .get_sealed_miniblock_number()
+                .await
+                .context("get_sealed_miniblock_number()")?;
+            if sealed_block_number > last_block_number {
+                let new_blocks = storage
+                    .blocks_web3_dal()
+                    .get_block_headers(last_block_number + 1, sealed_block_number)
+                    .await
+                    .context("get_block_headers()")?;
+                drop(storage);
+
+                last_block_number = sealed_block_number;
+                let new_blocks = new_blocks.into_iter().map(PubSubResult::Header).collect();
+                self.send_pub_sub_results(new_blocks, SubscriptionType::Blocks);
+                self.emit_event(PubSubEvent::MiniblockAdvanced(
+                    SubscriptionType::Blocks,
+                    last_block_number,
+                ));
+            }
+            self.emit_event(PubSubEvent::NotifyIterationFinished(
+                SubscriptionType::Blocks,
+            ));
+        }
+        Ok(())
+    }
 
     fn send_pub_sub_results(&self, results: Vec<PubSubResult>, sub_type: SubscriptionType) {
         // Errors only on 0 receivers, but we want to go on if we have 0 subscribers so ignore the error.
@@ -136,20 +345,6 @@ impl PubSubNotifier {
         // PUB_SUB_METRICS.broadcast_channel_len[&sub_type].set(self.sender.len());
     }
 
-    // async fn new_blocks(
-    //     &self,
-    //     last_block_number: MiniblockNumber,
-    // ) -> anyhow::Result<Vec<BlockHeader>> {
-    //     self.connection_pool
-    //         .access_storage_tagged("api")
-    //         .await
-    //         .context("access_storage_tagged")?
-    //         .blocks_web3_dal()
-    //         .get_block_headers_after(last_block_number)
-    //         .await
-    //         .with_context(|| format!("get_block_headers_after({last_block_number})"))
-    // }
-
     // async fn notify_txs(self, stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
     //     let mut last_time = chrono::Utc::now().naive_utc();
     //     let mut timer = interval(self.polling_interval);
@@ -188,45 +383,44 @@ impl PubSubNotifier {
     //         .context("get_pending_txs_hashes_after()")
     // }
 
-    // async fn notify_logs(self, stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
-    //     let mut last_block_number = self.get_starting_miniblock_number().await?;
-
-    //     let mut timer = interval(self.polling_interval);
-    //     loop {
-    //         if *stop_receiver.borrow() {
-    //             tracing::info!("Stop signal received, pubsub_logs_notifier is shutting down");
-    //             break;
-    //         }
-    //         timer.tick().await;
+    async fn notify_logs(self, stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        let mut last_block_number = self.get_starting_miniblock_number().await?;
+        let mut timer = interval(self.polling_interval);
+        loop {
+            if *stop_receiver.borrow() {
+                olaos_logs::info!("Stop signal received, pubsub_logs_notifier is shutting down");
+                break;
+            }
+            timer.tick().await;
 
-    //         let db_latency = PUB_SUB_METRICS.db_poll_latency[&SubscriptionType::Logs].start();
-    //         let new_logs = self.new_logs(last_block_number).await?;
-    //         db_latency.observe();
-
-    //         if let Some(last_log) = new_logs.last() {
-    //             last_block_number = MiniblockNumber(last_log.block_number.unwrap().as_u32());
-    //             let new_logs = new_logs.into_iter().map(PubSubResult::Log).collect();
-    //             self.send_pub_sub_results(new_logs, SubscriptionType::Logs);
-    //             self.emit_event(PubSubEvent::MiniblockAdvanced(
-    //                 SubscriptionType::Logs,
-    //                 last_block_number,
-    //             ));
-    //         }
-    //         self.emit_event(PubSubEvent::NotifyIterationFinished(SubscriptionType::Logs));
-    //     }
-    // Ok(())
-    // }
+            let mut storage = self.connection_pool.access_storage_tagged("api").await;
+            let sealed_block_number = storage
+                .blocks_dal()
+                .get_sealed_miniblock_number()
+                .await
+                .context("get_sealed_miniblock_number()")?;
+            if sealed_block_number > last_block_number {
+                let new_logs = storage
+                    .events_dal()
+                    .get_logs_in_range(last_block_number + 1, sealed_block_number)
+                    .await
+                    .context("get_logs_in_range()")?;
+                drop(storage);
 
-    // async fn new_logs(&self, last_block_number: MiniblockNumber) -> anyhow::Result<Vec<Log>> {
-    //     self.connection_pool
-    //         .access_storage_tagged("api")
-    //         .await
-    //         .context("access_storage_tagged")?
-    //         .events_web3_dal()
-    //         .get_all_logs(last_block_number)
-    //         .await
-    //         .context("events_web3_dal().get_all_logs()")
-    // }
+                last_block_number = sealed_block_number;
+                if !new_logs.is_empty() {
+                    let new_logs = new_logs.into_iter().map(PubSubResult::Log).collect();
+                    self.send_pub_sub_results(new_logs, SubscriptionType::Logs);
+                }
+                self.emit_event(PubSubEvent::MiniblockAdvanced(
+                    SubscriptionType::Logs,
+                    last_block_number,
+                ));
+            }
+            self.emit_event(PubSubEvent::NotifyIterationFinished(SubscriptionType::Logs));
+        }
+        Ok(())
+    }
 
     async fn notify_l1_batch_proofs(
         self,
@@ -269,6 +463,130 @@ impl PubSubNotifier {
         Ok(())
     }
 
+    async fn notify_verification_tasks(
+        self,
+        loader: VerificationTaskLoader,
+        stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        let mut last_number = None;
+        let mut timer = interval(self.polling_interval);
+        loop {
+            if *stop_receiver.borrow() {
+                tracing::info!(
+                    "Stop signal received, pubsub_verification_tasks_notifier is shutting down"
+                );
+                break;
+            }
+            timer.tick().await;
+
+            // Proofs are only loaded if there's someone to send them to.
+            let with_proofs = self.sender.receiver_count() > 0;
+            let tasks = loader.load(last_number, with_proofs).await?;
+            if let Some(task) = tasks.last() {
+                last_number = Some(task.l1_batch_number);
+                if with_proofs {
+                    let tasks = tasks.into_iter().map(PubSubResult::VerificationTask);
+                    self.send_pub_sub_results(tasks.collect(), SubscriptionType::VerificationTasks);
+                }
+            }
+            self.emit_event(PubSubEvent::NotifyIterationFinished(
+                SubscriptionType::VerificationTasks,
+            ));
+        }
+        Ok(())
+    }
+
+    async fn notify_l1_batch_statuses(
+        self,
+        stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        let mut notified = self.get_starting_l1_batch_statuses().await?;
+        let mut timer = interval(self.polling_interval);
+        loop {
+            if *stop_receiver.borrow() {
+                tracing::info!("Stop signal received, pubsub_l1_batches_notifier is shutting down");
+                break;
+            }
+            timer.tick().await;
+
+            let mut storage = self.connection_pool.access_storage_tagged("api").await;
+            let mut updates = vec![];
+            for status in L1BatchStatus::ALL {
+                let notified = notified
+                    .get_mut(&status)
+                    .expect("all statuses are initialized");
+                let numbers = storage
+                    .blocks_web3_dal()
+                    .get_l1_batch_numbers_with_status(
+                        status,
+                        notified.next_expected,
+                        &notified.exclude(),
+                        L1_BATCH_STATUSES_PER_ITERATION,
+                    )
+                    .await
+                    .with_context(|| format!("get_l1_batch_numbers_with_status({status:?})"))?;
+                let skipped = notified.record(&numbers);
+                if let Some(skipped) = skipped {
+                    tracing::warn!(
+                        "L1 batch #{skipped} hasn't reached status {status:?} while more than \
+                         {MAX_L1_BATCH_STATUSES_AHEAD} later batches have; it won't be notified"
+                    );
+                }
+                updates.extend(numbers.into_iter().map(|l1_batch_number| {
+                    PubSubResult::L1BatchStatus(L1BatchStatusUpdate {
+                        l1_batch_number,
+                        status,
+                    })
+                }));
+            }
+            drop(storage);
+
+            if !updates.is_empty() {
+                self.send_pub_sub_results(updates, SubscriptionType::L1Batches);
+            }
+            self.emit_event(PubSubEvent::NotifyIterationFinished(
+                SubscriptionType::L1Batches,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Marks batches that have reached each status before the start as notified. Batches within
+    /// the lookback window that haven't reached a status yet will be notified once they reach it.
+    async fn get_starting_l1_batch_statuses(
+        &self,
+    ) -> anyhow::Result<HashMap<L1BatchStatus, NotifiedL1Batches>> {
+        let mut storage = self.connection_pool.access_storage_tagged("api").await;
+        let mut notified = HashMap::with_capacity(L1BatchStatus::ALL.len());
+        for status in L1BatchStatus::ALL {
+            let last_number = storage
+                .blocks_web3_dal()
+                .get_last_l1_batch_number_with_status(status)
+                .await
+                .with_context(|| format!("get_last_l1_batch_number_with_status({status:?})"))?;
+            let Some(last_number) = last_number else {
+                notified.insert(status, NotifiedL1Batches::new(L1BatchNumber(0)));
+                continue;
+            };
+
+            let start = L1BatchNumber(
+                last_number
+                    .0
+                    .saturating_sub(L1_BATCH_STATUSES_STARTUP_LOOKBACK),
+            );
+            let mut status_notified = NotifiedL1Batches::new(start);
+            let window_len = (last_number.0 - start.0) as usize + 1;
+            let numbers = storage
+                .blocks_web3_dal()
+                .get_l1_batch_numbers_with_status(status, start, &[], window_len)
+                .await
+                .with_context(|| format!("get_l1_batch_numbers_with_status({status:?})"))?;
+            status_notified.record(&numbers);
+            notified.insert(status, status_notified);
+        }
+        Ok(notified)
+    }
+
     async fn new_l1_batch_proofs(&self) -> anyhow::Result<Option<ProveBatches>> {
         let mut storage = self.connection_pool.access_storage_tagged("api").await;
         // let blob_store = self.blob_store.clone().expect("blob_store not specified");
@@ -372,29 +690,71 @@ pub(super) struct EthSubscribe {
     transactions: broadcast::Sender<Vec<PubSubResult>>,
     logs: broadcast::Sender<Vec<PubSubResult>>,
     l1_batch_proofs: broadcast::Sender<Vec<PubSubResult>>,
+    l1_batches: broadcast::Sender<Vec<PubSubResult>>,
+    verification_tasks: broadcast::Sender<Vec<PubSubResult>>,
+    /// Set if the server pushes batches to offchain verifiers.
+    verification_task_loader: Option<VerificationTaskLoader>,
+    subscription_buffer_size: usize,
+    overflow_policy: SubscriptionOverflowPolicy,
+    /// Pool used to replay notifications and the number of last miniblocks that can be replayed.
+    replay: Option<(ConnectionPool, u32)>,
     events_sender: Option<mpsc::UnboundedSender<PubSubEvent>>,
 }
 
 impl EthSubscribe {
-    pub fn new() -> Self {
+    pub fn new(
+        subscription_buffer_size: usize,
+        overflow_policy: SubscriptionOverflowPolicy,
+    ) -> Self {
         let (blocks, _) = broadcast::channel(BROADCAST_CHANNEL_CAPACITY);
         let (transactions, _) = broadcast::channel(BROADCAST_CHANNEL_CAPACITY);
         let (logs, _) = broadcast::channel(BROADCAST_CHANNEL_CAPACITY);
         let (l1_batch_proofs, _) = broadcast::channel(BROADCAST_CHANNEL_CAPACITY);
+        let (l1_batches, _) = broadcast::channel(BROADCAST_CHANNEL_CAPACITY);
+        let (verification_tasks, _) = broadcast::channel(BROADCAST_CHANNEL_CAPACITY);
 
         Self {
             blocks,
             transactions,
             logs,
             l1_batch_proofs,
+            l1_batches,
+            verification_tasks,
+            verification_task_loader: None,
+            subscription_buffer_size,
+            overflow_policy,
+            replay: None,
             events_sender: None,
         }
     }
 
+    /// Allows `newHeads` and `logs` subscriptions to replay notifications of up to `window`
+    /// last miniblocks, starting from the `fromBlock` specified in subscription params.
+    pub fn set_replay(&mut self, connection_pool: ConnectionPool, window: u32) {
+        self.replay = Some((connection_pool, window));
+    }
+
+    /// Enables the `verificationTasks` subscription, which pushes L1 batches waiting for
+    /// offchain verification with their commitments and proofs (if `proof_store` is set).
+    pub fn set_verification_tasks(
+        &mut self,
+        connection_pool: ConnectionPool,
+        proof_store: Option<Arc<dyn ObjectStore>>,
+    ) {
+        self.verification_task_loader = Some(VerificationTaskLoader {
+            connection_pool,
+            proof_store,
+        });
+    }
+
     pub fn set_events_sender(&mut self, sender: mpsc::UnboundedSender<PubSubEvent>) {
         self.events_sender = Some(sender);
     }
 
+    fn subscription_queue(&self) -> SubscriptionQueue {
+        SubscriptionQueue::new(self.subscription_buffer_size, self.overflow_policy)
+    }
+
     async fn reject(sink: PendingSubscriptionSink) {
         sink.reject(ErrorObject::borrowed(
             ErrorCode::InvalidParams.code(),
@@ -404,12 +764,138 @@ impl EthSubscribe {
         .await;
     }
 
+    async fn reject_with_message(sink: PendingSubscriptionSink, message: String) {
+        sink.reject(ErrorObject::owned(
+            ErrorCode::InvalidParams.code(),
+            message,
+            None::<()>,
+        ))
+        .await;
+    }
+
+    fn with_replay_pool(
+        &self,
+        range: Option<ReplayRange>,
+    ) -> Option<(ConnectionPool, ReplayRange)> {
+        let (connection_pool, _) = self.replay.as_ref()?;
+        Some((connection_pool.clone(), range?))
+    }
+
+    /// Determines the miniblocks to replay for a subscription with the specified params. Must be
+    /// called after subscribing to live notifications, so that no miniblock is missed in between.
+    async fn replay_range(
+        &self,
+        params: Option<&PubSubFilter>,
+    ) -> Result<Option<ReplayRange>, String> {
+        let Some(from_block) = params.and_then(|params| params.from_block) else {
+            return Ok(None);
+        };
+        let Some((connection_pool, window)) = &self.replay else {
+            return Err("fromBlock is not supported: subscription replay is disabled".to_owned());
+        };
+        let mut storage = connection_pool.access_storage_tagged("api").await;
+        let sealed = storage
+            .blocks_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .map_err(|err| format!("failed loading the latest sealed miniblock: {err}"))?;
+        ReplayRange::new(from_block, sealed, *window)
+    }
+
+    /// Sends notifications of miniblocks in `range` loaded from Postgres.
+    async fn replay(
+        sink: &SubscriptionSink,
+        subscription_type: SubscriptionType,
+        connection_pool: &ConnectionPool,
+        range: ReplayRange,
+        filter: Option<&PubSubFilter>,
+    ) -> anyhow::Result<()> {
+        let mut replayed = 0;
+        for (from, to) in range.chunks() {
+            let mut storage = connection_pool.access_storage_tagged("api").await;
+            let items: Vec<_> = match subscription_type {
+                SubscriptionType::Blocks => storage
+                    .blocks_web3_dal()
+                    .get_block_headers(from, to)
+                    .await
+                    .context("get_block_headers()")?
+                    .into_iter()
+                    .map(PubSubResult::Header)
+                    .collect(),
+                SubscriptionType::Logs => storage
+                    .events_dal()
+                    .get_logs_in_range(from, to)
+                    .await
+                    .context("get_logs_in_range()")?
+                    .into_iter()
+                    .filter(|log| filter.map_or(true, |filter| filter.matches(log)))
+                    .map(PubSubResult::Log)
+                    .collect(),
+                _ => unreachable!("only newHeads and logs subscriptions are replayed"),
+            };
+            drop(storage);
+
+            replayed += items.len();
+            for item in items {
+                Self::send_next(sink, Some(item))
+                    .await
+                    .map_err(|_| anyhow::anyhow!("subscriber disconnected during replay"))?;
+            }
+        }
+        metrics::counter!(
+            "api.web3.pubsub.replayed_notifications",
+            replayed as u64,
+            "subscription_type" => subscription_type.as_str()
+        );
+        Ok(())
+    }
+
+    /// Sends all L1 batches currently waiting for verification, so that a newly connected
+    /// verifier doesn't need to wait for new batches. Batches sealed during the replay may
+    /// be sent twice; attestations for them are idempotent.
+    async fn replay_verification_tasks(
+        sink: &SubscriptionSink,
+        loader: &VerificationTaskLoader,
+    ) -> anyhow::Result<()> {
+        let mut last_number = None;
+        loop {
+            let tasks = loader.load(last_number, true).await?;
+            let Some(task) = tasks.last() else {
+                return Ok(());
+            };
+            last_number = Some(task.l1_batch_number);
+            for task in tasks {
+                Self::send_next(sink, Some(PubSubResult::VerificationTask(task)))
+                    .await
+                    .map_err(|_| anyhow::anyhow!("subscriber disconnected during replay"))?;
+            }
+        }
+    }
+
     async fn run_subscriber(
         sink: SubscriptionSink,
         subscription_type: SubscriptionType,
         mut receiver: broadcast::Receiver<Vec<PubSubResult>>,
         filter: Option<PubSubFilter>,
+        mut queue: SubscriptionQueue,
+        replay: Option<(ConnectionPool, ReplayRange)>,
     ) {
+        let mut replayed_to = None;
+        if let Some((connection_pool, range)) = replay {
+            let replay = Self::replay(
+                &sink,
+                subscription_type,
+                &connection_pool,
+                range,
+                filter.as_ref(),
+            );
+            if let Err(err) = replay.await {
+                olaos_logs::warn!("Failed replaying {subscription_type:?} notifications: {err:#}");
+                return;
+            }
+            replayed_to = Some(range.to);
+        }
+
         // let _guard = PUB_SUB_METRICS.active_subscribers[&subscription_type].inc_guard(1);
         // let lifetime_latency = PUB_SUB_METRICS.subscriber_lifetime[&subscription_type].start();
         let closed = sink.closed().fuse();
@@ -427,23 +913,29 @@ impl EthSubscribe {
                             break;
                         }
                         Err(broadcast::error::RecvError::Lagged(message_count)) => {
-                            olaos_logs::error!("skipped_broadcast_message {:?} count {:?}", subscription_type, message_count);
-                            break;
+                            olaos_logs::warn!("skipped_broadcast_message {:?} count {:?}", subscription_type, message_count);
+                            Self::report_dropped(subscription_type, "lagged", message_count as usize);
+                            if queue.overflow_policy == SubscriptionOverflowPolicy::Disconnect {
+                                Self::report_disconnected(subscription_type);
+                                break;
+                            }
+                            continue;
                         }
                     };
 
-                    let handle_result = Self::handle_new_items(
-                        &sink,
-                        subscription_type,
-                        new_items,
-                        filter.as_ref()
-                    )
-                    .await;
-                    if handle_result.is_err() {
-                        olaos_logs::error!("subscriber_send_timeouts {:?} error {:?}", subscription_type, handle_result);
+                    let filter = filter.as_ref();
+                    if !Self::enqueue_new_items(&mut queue, subscription_type, new_items, filter, replayed_to) {
+                        Self::report_disconnected(subscription_type);
                         break;
                     }
                 }
+                send_result = Self::send_next(&sink, queue.items.front().cloned()) => {
+                    if let Err(err) = send_result {
+                        olaos_logs::info!("subscription {:?} disconnected: {err:?}", subscription_type);
+                        break;
+                    }
+                    queue.items.pop_front();
+                }
                 _ = &mut closed => {
                     break;
                 }
@@ -452,13 +944,24 @@ impl EthSubscribe {
         olaos_logs::info!("run_subscriber {:?} finished", subscription_type);
     }
 
-    async fn handle_new_items(
-        sink: &SubscriptionSink,
+    /// Pushes items matching the filter to the subscription queue, skipping items of miniblocks
+    /// up to `replayed_to`, which were already replayed. Returns `false` if the queue
+    /// overflowed and the subscription must be closed.
+    fn enqueue_new_items(
+        queue: &mut SubscriptionQueue,
         subscription_type: SubscriptionType,
         new_items: Vec<PubSubResult>,
         filter: Option<&PubSubFilter>,
-    ) -> Result<(), SendTimeoutError> {
+        replayed_to: Option<MiniblockNumber>,
+    ) -> bool {
+        let mut dropped = 0;
         for item in new_items {
+            let is_replayed = notification_block_number(&item)
+                .zip(replayed_to)
+                .map_or(false, |(number, replayed_to)| number <= replayed_to);
+            if is_replayed {
+                continue;
+            }
             if let PubSubResult::Log(log) = &item {
                 if let Some(filter) = &filter {
                     if !filter.matches(log) {
@@ -466,19 +969,58 @@ impl EthSubscribe {
                     }
                 }
             }
+            match queue.push(item) {
+                Some(count) => dropped += count,
+                None => {
+                    olaos_logs::warn!(
+                        "Buffer of subscription {subscription_type:?} overflowed; closing the subscription"
+                    );
+                    return false;
+                }
+            }
+        }
+        Self::report_dropped(subscription_type, "overflow", dropped);
+        metrics::histogram!(
+            "api.web3.pubsub.subscription_queue_len",
+            queue.items.len() as f64,
+            "subscription_type" => subscription_type.as_str()
+        );
+        true
+    }
 
-            sink.send_timeout(
-                SubscriptionMessage::from_json(&item)
-                    .expect("PubSubResult always serializable to json;qed"),
-                SUBSCRIPTION_SINK_SEND_TIMEOUT,
-            )
-            .await?;
+    /// Sends the next queued item. If there's none, waits indefinitely, so that the caller
+    /// can wait for new items instead.
+    async fn send_next(
+        sink: &SubscriptionSink,
+        item: Option<PubSubResult>,
+    ) -> Result<(), DisconnectError> {
+        let Some(item) = item else {
+            return future::pending().await;
+        };
+        sink.send(
+            SubscriptionMessage::from_json(&item)
+                .expect("PubSubResult always serializable to json;qed"),
+        )
+        .await
+    }
 
-            olaos_logs::info!("notify {:?}", subscription_type);
+    fn report_dropped(subscription_type: SubscriptionType, reason: &'static str, count: usize) {
+        if count > 0 {
+            metrics::counter!(
+                "api.web3.pubsub.dropped_notifications",
+                count as u64,
+                "subscription_type" => subscription_type.as_str(),
+                "reason" => reason
+            );
         }
+    }
 
-        olaos_logs::info!("notify {:?} new items finished", subscription_type);
-        Ok(())
+    fn report_disconnected(subscription_type: SubscriptionType) {
+        metrics::counter!(
+            "api.web3.pubsub.disconnected_subscribers",
+            1,
+            "subscription_type" => subscription_type.as_str()
+        );
     }
 
     #[olaos_logs::instrument(skip(self, pending_sink))]
@@ -490,18 +1032,27 @@ impl EthSubscribe {
     ) {
         let sub_type = match sub_type.as_str() {
             "newHeads" => {
-                let Ok(sink) = pending_sink.accept().await else {
-                    return;
-                };
                 let blocks_rx = self.blocks.subscribe();
-                tokio::spawn(Self::run_subscriber(
-                    sink,
-                    SubscriptionType::Blocks,
-                    blocks_rx,
-                    None,
-                ));
-
-                Some(SubscriptionType::Blocks)
+                match self.replay_range(params.as_ref()).await {
+                    Ok(replay) => {
+                        let Ok(sink) = pending_sink.accept().await else {
+                            return;
+                        };
+                        tokio::spawn(Self::run_subscriber(
+                            sink,
+                            SubscriptionType::Blocks,
+                            blocks_rx,
+                            None,
+                            self.subscription_queue(),
+                            self.with_replay_pool(replay),
+                        ));
+                        Some(SubscriptionType::Blocks)
+                    }
+                    Err(message) => {
+                        Self::reject_with_message(pending_sink, message).await;
+                        None
+                    }
+                }
             }
             "newPendingTransactions" => {
                 let Ok(sink) = pending_sink.accept().await else {
@@ -513,6 +1064,8 @@ impl EthSubscribe {
                     SubscriptionType::Txs,
                     transactions_rx,
                     None,
+                    self.subscription_queue(),
+                    None,
                 ));
                 Some(SubscriptionType::Txs)
             }
@@ -524,17 +1077,27 @@ impl EthSubscribe {
                     Self::reject(pending_sink).await;
                     None
                 } else {
-                    let Ok(sink) = pending_sink.accept().await else {
-                        return;
-                    };
                     let logs_rx = self.logs.subscribe();
-                    tokio::spawn(Self::run_subscriber(
-                        sink,
-                        SubscriptionType::Logs,
-                        logs_rx,
-                        Some(filter),
-                    ));
-                    Some(SubscriptionType::Logs)
+                    match self.replay_range(Some(&filter)).await {
+                        Ok(replay) => {
+                            let Ok(sink) = pending_sink.accept().await else {
+                                return;
+                            };
+                            tokio::spawn(Self::run_subscriber(
+                                sink,
+                                SubscriptionType::Logs,
+                                logs_rx,
+                                Some(filter),
+                                self.subscription_queue(),
+                                self.with_replay_pool(replay),
+                            ));
+                            Some(SubscriptionType::Logs)
+                        }
+                        Err(message) => {
+                            Self::reject_with_message(pending_sink, message).await;
+                            None
+                        }
+                    }
                 }
             }
             "syncing" => {
@@ -561,10 +1124,57 @@ impl EthSubscribe {
                     SubscriptionType::L1BatchProofs,
                     block_proofs_rx,
                     None,
+                    self.subscription_queue(),
+                    None,
                 ));
 
                 Some(SubscriptionType::L1BatchProofs)
             }
+            "batches" => {
+                let Ok(sink) = pending_sink.accept().await else {
+                    return;
+                };
+                let l1_batches_rx = self.l1_batches.subscribe();
+                tokio::spawn(Self::run_subscriber(
+                    sink,
+                    SubscriptionType::L1Batches,
+                    l1_batches_rx,
+                    None,
+                    self.subscription_queue(),
+                    None,
+                ));
+
+                Some(SubscriptionType::L1Batches)
+            }
+            "verificationTasks" => {
+                if let Some(loader) = self.verification_task_loader.clone() {
+                    let verification_tasks_rx = self.verification_tasks.subscribe();
+                    let Ok(sink) = pending_sink.accept().await else {
+                        return;
+                    };
+                    let queue = self.subscription_queue();
+                    tokio::spawn(async move {
+                        if let Err(err) = Self::replay_verification_tasks(&sink, &loader).await {
+                            olaos_logs::warn!("Failed replaying verification tasks: {err:#}");
+                            return;
+                        }
+                        Self::run_subscriber(
+                            sink,
+                            SubscriptionType::VerificationTasks,
+                            verification_tasks_rx,
+                            None,
+                            queue,
+                            None,
+                        )
+                        .await;
+                    });
+                    Some(SubscriptionType::VerificationTasks)
+                } else {
+                    let message = "verificationTasks subscription is not enabled on this server";
+                    Self::reject_with_message(pending_sink, message.to_owned()).await;
+                    None
+                }
+            }
             _ => {
                 Self::reject(pending_sink).await;
                 None
@@ -585,16 +1195,16 @@ impl EthSubscribe {
         polling_interval: Duration,
         stop_receiver: watch::Receiver<bool>,
     ) -> Vec<JoinHandle<anyhow::Result<()>>> {
-        let mut notifier_tasks = Vec::with_capacity(3);
+        let mut notifier_tasks = Vec::with_capacity(4);
 
-        // let notifier = PubSubNotifier {
-        //     sender: self.blocks.clone(),
-        //     connection_pool: connection_pool.clone(),
-        //     polling_interval,
-        //     events_sender: self.events_sender.clone(),
-        // };
-        // let notifier_task = tokio::spawn(notifier.notify_blocks(stop_receiver.clone()));
-        // notifier_tasks.push(notifier_task);
+        let notifier = PubSubNotifier {
+            sender: self.blocks.clone(),
+            connection_pool: connection_pool.clone(),
+            polling_interval,
+            events_sender: self.events_sender.clone(),
+        };
+        let notifier_task = tokio::spawn(notifier.notify_blocks(stop_receiver.clone()));
+        notifier_tasks.push(notifier_task);
 
         // let notifier = PubSubNotifier {
         //     sender: self.transactions.clone(),
@@ -605,13 +1215,35 @@ impl EthSubscribe {
         // let notifier_task = tokio::spawn(notifier.notify_txs(stop_receiver.clone()));
         // notifier_tasks.push(notifier_task);
 
-        // let notifier = PubSubNotifier {
-        //     sender: self.logs.clone(),
-        //     connection_pool,
-        //     polling_interval,
-        //     events_sender: self.events_sender.clone(),
-        // };
-        // let notifier_task = tokio::spawn(notifier.notify_logs(stop_receiver));
+        let notifier = PubSubNotifier {
+            sender: self.logs.clone(),
+            connection_pool: connection_pool.clone(),
+            polling_interval,
+            events_sender: self.events_sender.clone(),
+        };
+        let notifier_task = tokio::spawn(notifier.notify_logs(stop_receiver.clone()));
+        notifier_tasks.push(notifier_task);
+
+        let notifier = PubSubNotifier {
+            sender: self.l1_batches.clone(),
+            connection_pool: connection_pool.clone(),
+            polling_interval,
+            events_sender: self.events_sender.clone(),
+        };
+        let notifier_task = tokio::spawn(notifier.notify_l1_batch_statuses(stop_receiver.clone()));
+        notifier_tasks.push(notifier_task);
+
+        if let Some(loader) = self.verification_task_loader.clone() {
+            let notifier = PubSubNotifier {
+                sender: self.verification_tasks.clone(),
+                connection_pool: connection_pool.clone(),
+                polling_interval,
+                events_sender: self.events_sender.clone(),
+            };
+            let notifier_task =
+                tokio::spawn(notifier.notify_verification_tasks(loader, stop_receiver.clone()));
+            notifier_tasks.push(notifier_task);
+        }
 
         let notifier = PubSubNotifier {
             sender: self.l1_batch_proofs.clone(),
@@ -638,3 +1270,100 @@ impl EthPubSubServer for EthSubscribe {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ola_web3_decl::types::Block;
+
+    use super::*;
+
+    fn is_syncing(item: &PubSubResult) -> bool {
+        matches!(item, PubSubResult::Syncing(true))
+    }
+
+    #[test]
+    fn subscription_queue_overflow() {
+        let mut queue = SubscriptionQueue::new(2, SubscriptionOverflowPolicy::DropOldest);
+        assert_eq!(queue.push(PubSubResult::Syncing(false)), Some(0));
+        assert_eq!(queue.push(PubSubResult::Syncing(false)), Some(0));
+        assert_eq!(queue.push(PubSubResult::Syncing(true)), Some(1));
+        assert_eq!(queue.items.len(), 2);
+        assert!(is_syncing(queue.items.back().unwrap()));
+
+        let mut queue = SubscriptionQueue::new(1, SubscriptionOverflowPolicy::Disconnect);
+        assert_eq!(queue.push(PubSubResult::Syncing(false)), Some(0));
+        assert_eq!(queue.push(PubSubResult::Syncing(false)), None);
+    }
+
+    #[test]
+    fn replay_range_is_bounded_by_window() {
+        let sealed = MiniblockNumber(1_000);
+        let range = ReplayRange::new(U64::from(901), sealed, 100)
+            .unwrap()
+            .unwrap();
+        assert_eq!(range.from, MiniblockNumber(901));
+        assert_eq!(range.to, sealed);
+        let chunks: Vec<_> = range.chunks().map(|(from, to)| (from.0, to.0)).collect();
+        assert_eq!(chunks, [(901, 1_000)]);
+
+        let err = ReplayRange::new(U64::from(900), sealed, 100).unwrap_err();
+        assert!(
+            err.contains("earliest block that can be replayed is 901"),
+            "{err}"
+        );
+        assert_eq!(ReplayRange::new(U64::from(1_001), sealed, 100), Ok(None));
+        assert!(ReplayRange::new(U64::from(u64::MAX), sealed, 100).is_err());
+
+        let range = ReplayRange::new(U64::from(750), sealed, 1_000)
+            .unwrap()
+            .unwrap();
+        let chunks: Vec<_> = range.chunks().map(|(from, to)| (from.0, to.0)).collect();
+        assert_eq!(chunks, [(750, 849), (850, 949), (950, 1_000)]);
+    }
+
+    #[test]
+    fn l1_batches_reaching_status_out_of_order_are_notified() {
+        let mut notified = NotifiedL1Batches::new(L1BatchNumber(5));
+        assert_eq!(notified.record(&[L1BatchNumber(5), L1BatchNumber(7)]), None);
+        assert_eq!(notified.next_expected, L1BatchNumber(6));
+        assert_eq!(notified.exclude(), [L1BatchNumber(7)]);
+
+        // Batch #6 reaches the status after #7; it must be notified, and #7 not again.
+        assert_eq!(notified.record(&[L1BatchNumber(6)]), None);
+        assert_eq!(notified.next_expected, L1BatchNumber(8));
+        assert!(notified.exclude().is_empty());
+
+        let ahead: Vec<_> = (10..=10 + MAX_L1_BATCH_STATUSES_AHEAD as u32)
+            .map(L1BatchNumber)
+            .collect();
+        assert_eq!(notified.record(&ahead), Some(L1BatchNumber(8)));
+        assert_eq!(
+            notified.next_expected,
+            L1BatchNumber(11 + MAX_L1_BATCH_STATUSES_AHEAD as u32)
+        );
+        assert!(notified.exclude().is_empty());
+    }
+
+    #[test]
+    fn replayed_notifications_are_skipped() {
+        let header = |number: u32| {
+            PubSubResult::Header(Block {
+                number: number.into(),
+                ..Block::default()
+            })
+        };
+        let mut queue = SubscriptionQueue::new(10, SubscriptionOverflowPolicy::DropOldest);
+        let items = vec![header(4), header(5), header(6)];
+        let replayed_to = Some(MiniblockNumber(5));
+        assert!(EthSubscribe::enqueue_new_items(
+            &mut queue,
+            SubscriptionType::Blocks,
+            items,
+            None,
+            replayed_to
+        ));
+        assert_eq!(queue.items.len(), 1);
+        let number = notification_block_number(&queue.items[0]);
+        assert_eq!(number, Some(MiniblockNumber(6)));
+    }
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use crate::api_server::execution_sandbox::BlockStartInfo;
 use anyhow::Context as _;
 use ola_config::contracts::ContractsConfig;
@@ -6,16 +8,32 @@ use ola_dal::connection::ConnectionPool;
 use ola_dal::StorageProcessor;
 use ola_types::l2::L2Tx;
 use ola_types::{api, L1BatchNumber, MiniblockNumber, U64};
-use ola_types::{L1ChainId, L2ChainId, H256};
+use ola_types::{archive::ArchivedTransaction, L1ChainId, L2ChainId, H256};
 use ola_web3_decl::error::Web3Error;
+use olaos_object_store::ObjectStore;
+
+use tokio::sync::watch;
 
-use crate::api_server::tx_sender::TxSender;
+use crate::{
+    api_server::{tx_sender::TxSender, web3::call_outputs::CallOutputStore},
+    archive::read_archived_transaction,
+    metadata_calculator::AsyncTreeReader,
+    sequencer::types::PendingStateGuard,
+    utils::blocking_pool::BlockingPool,
+    Component,
+};
 
 #[derive(Debug, Clone)]
 pub struct InternalApiConfig {
     pub l1_chain_id: L1ChainId,
     pub l2_chain_id: L2ChainId,
     pub max_tx_size: usize,
+    pub max_call_output_size: usize,
+    pub max_inline_block_txs: usize,
+    pub fee_history_limit: u64,
+    pub fee_history_max_reward_percentiles: usize,
+    pub filters_limit: usize,
+    pub max_logs_block_range: u32,
 }
 
 impl InternalApiConfig {
@@ -28,6 +46,12 @@ impl InternalApiConfig {
             l1_chain_id: eth_config.network.chain_id(),
             l2_chain_id: L2ChainId(eth_config.ola_network_id),
             max_tx_size: web3_config.max_tx_size,
+            max_call_output_size: web3_config.max_call_output_size(),
+            max_inline_block_txs: web3_config.max_inline_block_txs(),
+            fee_history_limit: web3_config.fee_history_limit(),
+            fee_history_max_reward_percentiles: web3_config.fee_history_max_reward_percentiles(),
+            filters_limit: web3_config.filters_limit(),
+            max_logs_block_range: web3_config.max_logs_block_range(),
         }
     }
 }
@@ -62,6 +86,17 @@ pub struct RpcState {
     pub tx_sender: Option<TxSender>,
     pub connection_pool: ConnectionPool,
     pub start_info: BlockStartInfo,
+    pub pending_state: Option<PendingStateGuard>,
+    /// Read-only Merkle tree handle; holds `None` until the tree is initialized.
+    pub tree_reader: Option<watch::Receiver<Option<AsyncTreeReader>>>,
+    /// Thread pool running tree reads; if `None`, reads are run via `spawn_blocking()`.
+    pub tree_read_pool: Option<Arc<BlockingPool>>,
+    /// Object store with archived transactions; `None` if transactions are not archived.
+    pub archive_store: Option<Arc<dyn ObjectStore>>,
+    /// Object store with full outputs of truncated calls; `None` if outputs are not stored.
+    pub call_output_store: Option<Arc<CallOutputStore>>,
+    /// Components run by the node, reported by `ola_nodeInfo`.
+    pub components: Vec<Component>,
 }
 
 impl RpcState {
@@ -75,6 +110,28 @@ impl RpcState {
         ))
     }
 
+    /// Returns the Merkle tree handle, or an error if the tree doesn't run in this process
+    /// or isn't initialized yet.
+    pub fn tree_reader(&self) -> Result<AsyncTreeReader, Web3Error> {
+        self.tree_reader
+            .as_ref()
+            .and_then(|tree_reader| tree_reader.borrow().clone())
+            .map(|tree_reader| tree_reader.with_pool(self.tree_read_pool.clone()))
+            .ok_or(Web3Error::TreeApiUnavailable)
+    }
+
+    /// Fetches the transaction from the archive, if archiving is enabled and the transaction
+    /// is archived.
+    pub(crate) async fn archived_transaction(
+        &self,
+        hash: H256,
+    ) -> anyhow::Result<Option<ArchivedTransaction>> {
+        let Some(archive_store) = &self.archive_store else {
+            return Ok(None);
+        };
+        read_archived_transaction(&self.connection_pool, archive_store.as_ref(), hash).await
+    }
+
     pub fn u64_to_block_number(n: U64) -> MiniblockNumber {
         if n.as_u64() > u32::MAX as u64 {
             MiniblockNumber(u32::MAX)
@@ -94,7 +151,7 @@ impl RpcState {
             .resolve_block_id(block)
             .await
             .context("resolve_block_id")?
-            .ok_or(anyhow::bail!(Web3Error::NoBlock))
+            .ok_or_else(|| Web3Error::NoBlock.into())
     }
 
     pub async fn resolve_filter_block_number(
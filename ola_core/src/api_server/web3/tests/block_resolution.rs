@@ -0,0 +1,92 @@
+//! Checks EIP-1898 block references for every method accepting a block by hash.
+
+use jsonrpsee::{
+    core::{client::ClientT, params::ArrayParams, ClientError},
+    http_client::HttpClientBuilder,
+    rpc_params,
+};
+use ola_dal::StorageProcessor;
+use ola_types::{Address, MiniblockNumber, H256};
+use serde_json::{json, Value};
+
+use super::{ensure_genesis, TestHttpServer};
+use crate::api_server::web3::backend::RESOURCE_NOT_FOUND_CODE;
+
+async fn genesis_miniblock_hash() -> H256 {
+    let mut storage = StorageProcessor::establish_connection(true).await;
+    storage
+        .blocks_web3_dal()
+        .get_miniblock_hash(MiniblockNumber(0))
+        .await
+        .unwrap()
+        .expect("no genesis miniblock")
+}
+
+/// Calls `method` with params built by `params` from an EIP-1898 block object. Checks that
+/// an unknown block hash is reported with the dedicated error code, and that the hash
+/// of the genesis miniblock is resolved with and without `requireCanonical`.
+async fn test_block_resolution(method: &str, params: impl Fn(Value) -> ArrayParams) {
+    ensure_genesis().await;
+    let server = TestHttpServer::start().await;
+    let client = HttpClientBuilder::default().build(server.url()).unwrap();
+
+    let unknown_block = json!({ "blockHash": H256::repeat_byte(0xfe) });
+    let err = client
+        .request::<Value, _>(method, params(unknown_block))
+        .await
+        .unwrap_err();
+    match err {
+        ClientError::Call(err) => assert_eq!(err.code(), RESOURCE_NOT_FOUND_CODE, "{err:?}"),
+        other => panic!("unexpected error calling {method}: {other}"),
+    }
+
+    let genesis_hash = genesis_miniblock_hash().await;
+    for block in [
+        json!({ "blockHash": genesis_hash }),
+        json!({ "blockHash": genesis_hash, "requireCanonical": true }),
+    ] {
+        client
+            .request::<Value, _>(method, params(block.clone()))
+            .await
+            .unwrap_or_else(|err| panic!("calling {method} for {block} failed: {err}"));
+    }
+
+    server.stop().await;
+}
+
+#[ignore]
+#[tokio::test]
+async fn get_transaction_count_resolves_block_hash() {
+    test_block_resolution("eth_getTransactionCount", |block| {
+        rpc_params![Address::repeat_byte(1), block]
+    })
+    .await;
+}
+
+#[ignore]
+#[tokio::test]
+async fn get_storage_at_resolves_block_hash() {
+    test_block_resolution("eth_getStorageAt", |block| {
+        rpc_params![Address::repeat_byte(1), "0x0", block]
+    })
+    .await;
+}
+
+#[ignore]
+#[tokio::test]
+async fn get_logs_resolves_block_hash() {
+    // Log filters have no `requireCanonical` field, so only the hash is passed.
+    test_block_resolution("eth_getLogs", |block| {
+        rpc_params![json!({ "blockHash": block["blockHash"] })]
+    })
+    .await;
+}
+
+#[ignore]
+#[tokio::test]
+async fn get_account_storage_range_resolves_block_hash() {
+    test_block_resolution("ola_getAccountStorageRange", |block| {
+        rpc_params![Address::repeat_byte(1), block]
+    })
+    .await;
+}
@@ -0,0 +1,62 @@
+//! In-process harness running the conformance suite against the HTTP API server and
+//! the offchain verifier (WS) server, both started on a migrated test database.
+
+use std::time::Duration;
+
+use conformance::{cases::Suite, runner::run_suites};
+use jsonrpsee::{http_client::HttpClientBuilder, ws_client::WsClientBuilder};
+use tokio::sync::watch;
+
+use super::{api_config, ensure_genesis, free_port, wait_until_ready, TestHttpServer};
+use crate::api_server::{
+    execution_sandbox::VmConcurrencyLimiter,
+    web3::{ApiBuilder, Namespace},
+};
+
+#[ignore]
+#[tokio::test]
+async fn conformance_suite() {
+    ensure_genesis().await;
+    let http_server = TestHttpServer::start().await;
+
+    // Mirrors the offchain verifier server of the node with attestations enabled.
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let ws_port = free_port();
+    let (_, vm_barrier) = VmConcurrencyLimiter::new(1);
+    let ws_handles = ApiBuilder::pubsub_backend(api_config(), http_server.pool.clone())
+        .ws(ws_port)
+        .with_threads(1)
+        .with_polling_interval(Duration::from_millis(50))
+        .with_response_body_size_limit(10 << 20)
+        .with_vm_barrier(vm_barrier)
+        .enable_api_namespaces(vec![Namespace::Pubsub, Namespace::Verifier])
+        .build_ws_new(stop_receiver)
+        .await
+        .unwrap();
+    wait_until_ready(&ws_handles.health_check).await;
+
+    let http_client = HttpClientBuilder::default()
+        .build(http_server.url())
+        .unwrap();
+    let ws_client = WsClientBuilder::default()
+        .build(format!("ws://127.0.0.1:{ws_port}"))
+        .await
+        .unwrap();
+    let suites = [
+        Suite::Eth,
+        Suite::Net,
+        Suite::Web3,
+        Suite::Ola,
+        Suite::Verifier,
+    ];
+    let report = run_suites(&http_client, Some(&ws_client), &suites)
+        .await
+        .unwrap();
+    assert_eq!(report.failed_count(), 0, "{report}");
+
+    stop_sender.send_replace(true);
+    for task in ws_handles.tasks {
+        task.await.unwrap().unwrap();
+    }
+    http_server.stop().await;
+}
@@ -1 +1,121 @@
+//! Tests of the Web3 API servers. Tests starting servers require a migrated Postgres database
+//! and are ignored by default.
+
+use std::{net::TcpListener, time::Duration};
+
+use ola_contracts::BaseSystemContracts;
+use ola_dal::{
+    connection::{ConnectionPool, DbVariant},
+    StorageProcessor,
+};
+use ola_types::{
+    protocol_version::ProtocolVersionId, system_contracts::get_system_smart_contracts, Address,
+    L1ChainId, L2ChainId,
+};
+use olaos_health_check::{CheckHealth, ReactiveHealthCheck};
+use tokio::{sync::watch, task::JoinHandle};
+
+use crate::{
+    api_server::{
+        execution_sandbox::VmConcurrencyLimiter,
+        web3::{state::InternalApiConfig, ApiBuilder, Namespace},
+    },
+    genesis::{ensure_genesis_state, GenesisParams},
+};
+
+mod block_resolution;
+mod conformance;
 mod ws;
+
+const TEST_CHAIN_ID: L2ChainId = L2ChainId(1027);
+const SERVER_START_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn api_config() -> InternalApiConfig {
+    InternalApiConfig {
+        l1_chain_id: L1ChainId(9),
+        l2_chain_id: TEST_CHAIN_ID,
+        max_tx_size: 1_000_000,
+        max_call_output_size: 1_000_000,
+        max_inline_block_txs: 100,
+        fee_history_limit: 1_024,
+        fee_history_max_reward_percentiles: 100,
+        filters_limit: 1_000,
+        max_logs_block_range: 1_000,
+    }
+}
+
+/// Returns a port that is free at the time of the call.
+fn free_port() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().port()
+}
+
+async fn wait_until_ready(health_check: &ReactiveHealthCheck) {
+    let started_at = tokio::time::Instant::now();
+    while !health_check.check_health().await.status().is_ready() {
+        assert!(
+            started_at.elapsed() < SERVER_START_TIMEOUT,
+            "{} server hasn't started in {SERVER_START_TIMEOUT:?}",
+            health_check.name()
+        );
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+/// Creates the genesis L1 batch unless the database already has it.
+async fn ensure_genesis() {
+    let mut storage = StorageProcessor::establish_connection(true).await;
+    let protocol_version = ProtocolVersionId::latest();
+    let genesis_params = GenesisParams {
+        first_validator: Address::zero(),
+        base_system_contracts: BaseSystemContracts::load_from_disk(),
+        system_contracts: get_system_smart_contracts(protocol_version),
+        protocol_version,
+        fork_state: None,
+    };
+    ensure_genesis_state(&mut storage, TEST_CHAIN_ID, &genesis_params)
+        .await
+        .unwrap();
+}
+
+/// HTTP API server started for a test.
+struct TestHttpServer {
+    port: u16,
+    pool: ConnectionPool,
+    stop_sender: watch::Sender<bool>,
+    tasks: Vec<JoinHandle<anyhow::Result<()>>>,
+}
+
+impl TestHttpServer {
+    async fn start() -> Self {
+        let pool = ConnectionPool::builder(DbVariant::Master).build().await;
+        let (stop_sender, stop_receiver) = watch::channel(false);
+        let port = free_port();
+        let (_, vm_barrier) = VmConcurrencyLimiter::new(1);
+        let (tasks, health_check) = ApiBuilder::http_backend(api_config(), pool.clone())
+            .http(port)
+            .with_threads(1)
+            .with_vm_barrier(vm_barrier)
+            .enable_api_namespaces(Namespace::HTTP.to_vec())
+            .build(stop_receiver)
+            .await;
+        wait_until_ready(&health_check).await;
+        Self {
+            port,
+            pool,
+            stop_sender,
+            tasks,
+        }
+    }
+
+    fn url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+
+    async fn stop(self) {
+        self.stop_sender.send_replace(true);
+        for task in self.tasks {
+            task.await.unwrap().unwrap();
+        }
+    }
+}
@@ -0,0 +1,338 @@
+//! Archival of old transactions and events to the object store.
+//!
+//! [`TransactionArchiver`] moves transactions of L1 batches older than the configured retention
+//! into per-batch segments in the object store, together with their events and call traces, and
+//! removes them from Postgres; `transactions_archive_index` maps transaction hashes to segments.
+//! Segments hold both API views of the transactions, served by the API via [`read_archived_transaction()`],
+//! and the raw Postgres rows, which `ola_node restore-archive` inserts back.
+//!
+//! Only lookups by transaction hash (transactions, receipts and details) are served from the archive;
+//! block and log queries over archived L1 batches don't return archived transactions.
+//! Protocol upgrade transactions are never archived. L1 batches are archived only after their proof
+//! generation is finished.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context as _;
+use ola_dal::{connection::ConnectionPool, transactions_archive_dal::ArchivedRows};
+use ola_types::{
+    api::{AuditAction, TransactionId},
+    archive::{ArchivedTransaction, TransactionArchiveSegment},
+    L1BatchNumber, L2ChainId, H256,
+};
+use olaos_object_store::ObjectStore;
+use tokio::sync::watch;
+
+use crate::audit::{AuditLog, SYSTEM_ACTOR};
+
+/// Reads of the archive slower than this are logged as warnings.
+const SLOW_ARCHIVE_READ_THRESHOLD: Duration = Duration::from_millis(500);
+
+/// Background task moving old transactions to the archive.
+#[derive(Debug)]
+pub struct TransactionArchiver {
+    pool: ConnectionPool,
+    object_store: Arc<dyn ObjectStore>,
+    chain_id: L2ChainId,
+    retention_l1_batches: u32,
+    poll_interval: Duration,
+    audit_log: AuditLog,
+}
+
+impl TransactionArchiver {
+    pub fn new(
+        pool: ConnectionPool,
+        object_store: Arc<dyn ObjectStore>,
+        chain_id: L2ChainId,
+        retention_l1_batches: u32,
+        poll_interval: Duration,
+        audit_log: AuditLog,
+    ) -> Self {
+        Self {
+            pool,
+            object_store,
+            chain_id,
+            retention_l1_batches,
+            poll_interval,
+            audit_log,
+        }
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        loop {
+            if *stop_receiver.borrow_and_update() {
+                olaos_logs::info!("Stop signal received, transaction archiver is shutting down");
+                break;
+            }
+
+            // Archive one batch per iteration, so that the stop signal is checked in between.
+            if self.next_l1_batch_to_archive().await?.is_some() {
+                continue;
+            }
+            tokio::select! {
+                _ = stop_receiver.changed() => {
+                    olaos_logs::info!("Stop signal received, transaction archiver is shutting down");
+                    break;
+                }
+                () = tokio::time::sleep(self.poll_interval) => { /* Continue polling */ }
+            }
+        }
+        Ok(())
+    }
+
+    /// Archives the next L1 batch outside the retention window, if any and if its proof generation
+    /// is finished. Returns its number.
+    async fn next_l1_batch_to_archive(&self) -> anyhow::Result<Option<L1BatchNumber>> {
+        let mut storage = self.pool.access_storage_tagged("tx_archiver").await;
+        let last_sealed = storage.blocks_dal().get_sealed_l1_batch_number().await?;
+        let last_archived = storage
+            .transactions_archive_dal()
+            .get_last_archived_l1_batch()
+            .await?;
+
+        // The genesis L1 batch only contains system transactions, which are never archived.
+        let next = last_archived.map_or(L1BatchNumber(1), |number| number + 1);
+        if next.0 + self.retention_l1_batches > last_sealed.0 {
+            return Ok(None);
+        }
+        // Witness and proof generation read transactions of the batch from Postgres, so they must
+        // be done with the batch before its rows are removed.
+        let proven = storage
+            .proof_generation_dal()
+            .is_proof_generation_finished(next)
+            .await?;
+        drop(storage);
+        if !proven {
+            olaos_logs::debug!(
+                "L1 batch #{next} is outside the retention window, but its proof generation \
+                 isn't finished; postponing archival"
+            );
+            return Ok(None);
+        }
+        self.archive_l1_batch(next)
+            .await
+            .with_context(|| format!("failed archiving L1 batch #{next}"))?;
+        Ok(Some(next))
+    }
+
+    async fn archive_l1_batch(&self, l1_batch_number: L1BatchNumber) -> anyhow::Result<()> {
+        let started_at = Instant::now();
+        let mut storage = self.pool.access_storage_tagged("tx_archiver").await;
+        let hashes = storage
+            .transactions_archive_dal()
+            .get_archivable_tx_hashes(l1_batch_number)
+            .await?;
+
+        let mut receipts = storage
+            .transactions_web3_dal()
+            .get_transaction_receipts(&hashes)
+            .await?;
+        let mut transactions = Vec::with_capacity(hashes.len());
+        for &hash in &hashes {
+            let transaction = storage
+                .transactions_web3_dal()
+                .get_transaction(TransactionId::Hash(hash), self.chain_id)
+                .await?;
+            let details = storage
+                .transactions_web3_dal()
+                .get_transaction_details(hash)
+                .await?;
+            let receipt_position = receipts
+                .iter()
+                .position(|receipt| receipt.transaction_hash == hash);
+            transactions.push(ArchivedTransaction {
+                hash,
+                transaction,
+                receipt: receipt_position.map(|position| receipts.swap_remove(position)),
+                details,
+            });
+        }
+        let ArchivedRows {
+            transactions: transaction_rows,
+            events: event_rows,
+            call_traces: call_trace_rows,
+        } = storage.transactions_archive_dal().get_rows(&hashes).await?;
+        let event_count = event_rows.len();
+
+        let segment = TransactionArchiveSegment {
+            l1_batch_number,
+            transactions,
+            transaction_rows,
+            event_rows,
+            call_trace_rows,
+        };
+        // The segment is uploaded before the rows are removed, so an interrupted run only leaves
+        // an orphaned segment, which is overwritten by the next run.
+        let object_key = self
+            .object_store
+            .put(l1_batch_number, &segment)
+            .await
+            .context("failed uploading archive segment")?;
+        storage
+            .transactions_archive_dal()
+            .mark_l1_batch_archived(l1_batch_number, &object_key, &hashes, event_count)
+            .await?;
+        let parameters = serde_json::json!({
+            "l1BatchNumber": l1_batch_number.0,
+            "transactions": hashes.len(),
+            "events": event_count,
+            "objectKey": object_key,
+        });
+        self.audit_log
+            .record(&mut storage, AuditAction::Pruning, SYSTEM_ACTOR, parameters)
+            .await?;
+
+        metrics::counter!("server.tx_archiver.transactions", hashes.len() as u64);
+        metrics::gauge!(
+            "server.tx_archiver.last_archived_l1_batch",
+            l1_batch_number.0 as f64
+        );
+        olaos_logs::info!(
+            "Archived {} transaction(s) and {event_count} event(s) of L1 batch #{l1_batch_number} \
+             to `{object_key}` in {:?}",
+            hashes.len(),
+            started_at.elapsed()
+        );
+        Ok(())
+    }
+}
+
+/// Fetches an archived transaction, if the transaction with `hash` is archived.
+pub async fn read_archived_transaction(
+    pool: &ConnectionPool,
+    object_store: &dyn ObjectStore,
+    hash: H256,
+) -> anyhow::Result<Option<ArchivedTransaction>> {
+    let started_at = Instant::now();
+    let l1_batch_number = pool
+        .access_storage_tagged("api")
+        .await
+        .transactions_archive_dal()
+        .get_archived_l1_batch(hash)
+        .await?;
+    let Some(l1_batch_number) = l1_batch_number else {
+        return Ok(None);
+    };
+
+    let segment: TransactionArchiveSegment =
+        object_store.get(l1_batch_number).await.with_context(|| {
+            format!("failed fetching archive segment of L1 batch #{l1_batch_number}")
+        })?;
+    let elapsed = started_at.elapsed();
+    metrics::histogram!("server.tx_archive.read_latency", elapsed);
+    if elapsed > SLOW_ARCHIVE_READ_THRESHOLD {
+        olaos_logs::warn!(
+            "Transaction {hash:?} was served from the archive of L1 batch #{l1_batch_number} \
+             in {elapsed:?}; archived reads are slower than reads from Postgres"
+        );
+    }
+    Ok(segment.transaction(hash).cloned())
+}
+
+/// Arguments of `ola_node restore-archive`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestoreArchiveArgs {
+    pub from_batch: L1BatchNumber,
+    pub to_batch: L1BatchNumber,
+}
+
+impl RestoreArchiveArgs {
+    pub const USAGE: &'static str =
+        "usage: ola_node restore-archive --from-batch <number> --to-batch <number>";
+
+    /// Parses arguments following the `restore-archive` subcommand.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Self> {
+        let mut from_batch = None;
+        let mut to_batch = None;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .with_context(|| format!("missing value for `{arg}`; {}", Self::USAGE))?;
+            match arg.as_str() {
+                "--from-batch" => from_batch = Some(L1BatchNumber(value.parse()?)),
+                "--to-batch" => to_batch = Some(L1BatchNumber(value.parse()?)),
+                _ => anyhow::bail!("unknown argument `{arg}`; {}", Self::USAGE),
+            }
+        }
+
+        let from_batch = from_batch.context(Self::USAGE)?;
+        let to_batch = to_batch.context(Self::USAGE)?;
+        anyhow::ensure!(
+            from_batch <= to_batch,
+            "--from-batch must not be greater than --to-batch"
+        );
+        Ok(Self {
+            from_batch,
+            to_batch,
+        })
+    }
+}
+
+/// Moves archived transactions of the requested L1 batches back to Postgres. The archiver
+/// should be disabled beforehand, or restored batches within its reach are archived again.
+pub async fn run_restore_archive(
+    pool: &ConnectionPool,
+    object_store: &dyn ObjectStore,
+    args: &RestoreArchiveArgs,
+) -> anyhow::Result<()> {
+    let mut storage = pool.access_storage_tagged("restore_archive").await;
+    let l1_batches = storage
+        .transactions_archive_dal()
+        .get_archived_l1_batches(args.from_batch..=args.to_batch)
+        .await?;
+    olaos_logs::info!(
+        "Restoring {} archived L1 batch(es) in range #{}..=#{}",
+        l1_batches.len(),
+        args.from_batch,
+        args.to_batch
+    );
+
+    for l1_batch_number in l1_batches {
+        let segment: TransactionArchiveSegment =
+            object_store.get(l1_batch_number).await.with_context(|| {
+                format!("failed fetching archive segment of L1 batch #{l1_batch_number}")
+            })?;
+        let tx_count = segment.transaction_rows.len();
+        let rows = ArchivedRows {
+            transactions: segment.transaction_rows,
+            events: segment.event_rows,
+            call_traces: segment.call_trace_rows,
+        };
+        storage
+            .transactions_archive_dal()
+            .restore_l1_batch(l1_batch_number, rows)
+            .await?;
+        olaos_logs::info!("Restored {tx_count} transaction(s) of L1 batch #{l1_batch_number}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> anyhow::Result<RestoreArchiveArgs> {
+        RestoreArchiveArgs::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parsing_restore_archive_args() {
+        let parsed = args(&["--from-batch", "3", "--to-batch", "5"]).unwrap();
+        assert_eq!(
+            parsed,
+            RestoreArchiveArgs {
+                from_batch: L1BatchNumber(3),
+                to_batch: L1BatchNumber(5),
+            }
+        );
+
+        assert!(args(&["--from-batch", "3"]).is_err());
+        assert!(args(&["--from-batch", "5", "--to-batch", "3"]).is_err());
+        assert!(args(&["--batch", "5"]).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+//! Audit log of operator-affecting actions.
+//!
+//! Actions are appended to the `audit_log` Postgres table, which is served by `ola_getAuditLog`
+//! in the admin namespace, and optionally mirrored to a file as JSON lines
+//! (`database.audit_log_path`). Entries are numbered by Postgres, so the file keeps the order
+//! of the table.
+
+use std::{
+    fs::OpenOptions,
+    io::Write as _,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context as _;
+use ola_config::database::DBConfig;
+use ola_dal::StorageProcessor;
+use ola_types::api::{AuditAction, AuditLogEntry};
+
+/// Actor of actions taken by background tasks of the node.
+pub const SYSTEM_ACTOR: &str = "system";
+
+/// Returns the actor of actions taken via `ola_node` subcommands.
+pub fn cli_actor() -> String {
+    let user = std::env::var("USER").unwrap_or_else(|_| "unknown".to_owned());
+    format!("cli:{user}")
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AuditLog {
+    file_path: Option<PathBuf>,
+}
+
+impl AuditLog {
+    pub fn new(file_path: Option<PathBuf>) -> Self {
+        Self { file_path }
+    }
+
+    pub fn from_config(config: &DBConfig) -> Self {
+        Self::new(config.audit_log_path.as_ref().map(PathBuf::from))
+    }
+
+    /// Records `action` taken by `actor`. If the file sink fails, the error is logged;
+    /// the Postgres table remains the source of truth.
+    pub async fn record(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        action: AuditAction,
+        actor: &str,
+        parameters: serde_json::Value,
+    ) -> anyhow::Result<()> {
+        let entry = storage
+            .audit_log_dal()
+            .insert_entry(action, actor, parameters)
+            .await
+            .with_context(|| format!("failed recording `{action}` to the audit log"))?;
+        olaos_logs::info!(
+            "Audit log entry #{}: {action} by {actor} with {}",
+            entry.id,
+            entry.parameters
+        );
+
+        if let Some(path) = &self.file_path {
+            if let Err(err) = append_to_file(path, &entry) {
+                olaos_logs::warn!(
+                    "Failed appending audit log entry #{} to `{}`: {err:#}",
+                    entry.id,
+                    path.display()
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+fn append_to_file(path: &Path, entry: &AuditLogEntry) -> anyhow::Result<()> {
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    #[test]
+    fn appending_entries_to_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("audit.log");
+        let entry = |id| AuditLogEntry {
+            id,
+            timestamp: Utc.timestamp_opt(1_700_000_000, 0).unwrap(),
+            action: AuditAction::OperatorRotation,
+            actor: "cli:ops".to_owned(),
+            parameters: serde_json::json!({ "operatorAddress": "0x01" }),
+        };
+        append_to_file(&path, &entry(1)).unwrap();
+        append_to_file(&path, &entry(2)).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let entries: Vec<AuditLogEntry> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries, [entry(1), entry(2)]);
+        assert!(contents
+            .lines()
+            .next()
+            .unwrap()
+            .contains(r#""action":"operator_rotation""#));
+    }
+}
@@ -0,0 +1,409 @@
+//! Backfill of historical miniblocks from another node (`ola_node backfill`).
+//!
+//! Miniblocks of the requested range are fetched from the remote node's JSON-RPC API together
+//! with their transactions and receipts, validated and written to the local database. Each
+//! miniblock is written in a single database transaction, so an interrupted backfill resumes
+//! from the first miniblock missing locally; miniblocks already present locally are only
+//! checked against the remote node.
+//!
+//! Backfilled miniblocks are assigned to the L1 batch reported by the remote node. The JSON-RPC
+//! API doesn't expose storage logs and L1 batch headers though, so the L1 batches themselves
+//! are not sealed locally and the state must be recovered separately.
+
+use std::num::NonZeroU32;
+
+use anyhow::Context as _;
+use governor::{Quota, RateLimiter};
+use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
+use ola_dal::{connection::ConnectionPool, StorageProcessor};
+use ola_types::{
+    api::{self, BlockNumber, TransactionVariant},
+    block::MiniblockHeader,
+    events::VmEvent,
+    fee::TransactionExecutionMetrics,
+    l2::L2Tx,
+    tx::{
+        tx_execution_info::{ExecutionMetrics, TxExecutionStatus},
+        IncludedTxLocation, TransactionExecutionResult,
+    },
+    ExecuteTransactionCommon, L1BatchNumber, MiniblockNumber, Transaction,
+};
+use ola_utils::misc::miniblock_hash;
+use ola_web3_decl::{
+    error::ClientRpcContext,
+    namespaces::{eth::EthNamespaceClient, ola::OlaNamespaceClient},
+};
+
+/// Arguments of `ola_node backfill`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackfillArgs {
+    pub from_block: MiniblockNumber,
+    pub to_block: MiniblockNumber,
+    /// JSON-RPC URL of the node to fetch miniblocks from.
+    pub remote_url: String,
+    /// Maximum number of requests per second sent to the remote node.
+    pub requests_per_second: NonZeroU32,
+}
+
+impl BackfillArgs {
+    pub const USAGE: &'static str = "usage: ola_node backfill --from-block <number> \
+        --to-block <number> --remote <url> [--rps <requests per second>]";
+
+    const DEFAULT_REQUESTS_PER_SECOND: u32 = 20;
+
+    /// Parses arguments following the `backfill` subcommand.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Self> {
+        let mut from_block = None;
+        let mut to_block = None;
+        let mut remote_url = None;
+        let mut requests_per_second = Self::DEFAULT_REQUESTS_PER_SECOND;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .with_context(|| format!("missing value for `{arg}`; {}", Self::USAGE))?;
+            match arg.as_str() {
+                "--from-block" => from_block = Some(MiniblockNumber(value.parse()?)),
+                "--to-block" => to_block = Some(MiniblockNumber(value.parse()?)),
+                "--remote" => remote_url = Some(value),
+                "--rps" => requests_per_second = value.parse()?,
+                _ => anyhow::bail!("unknown argument `{arg}`; {}", Self::USAGE),
+            }
+        }
+
+        let from_block = from_block.context(Self::USAGE)?;
+        let to_block = to_block.context(Self::USAGE)?;
+        let remote_url = remote_url.context(Self::USAGE)?;
+        anyhow::ensure!(
+            from_block <= to_block,
+            "--from-block must not be greater than --to-block"
+        );
+        let requests_per_second =
+            NonZeroU32::new(requests_per_second).context("--rps must be positive")?;
+        Ok(Self {
+            from_block,
+            to_block,
+            remote_url,
+            requests_per_second,
+        })
+    }
+}
+
+/// Miniblock fetched from the remote node.
+#[derive(Debug)]
+struct RemoteMiniblock {
+    header: MiniblockHeader,
+    l1_batch_number: L1BatchNumber,
+    transactions: Vec<Transaction>,
+    receipts: Vec<api::TransactionReceipt>,
+}
+
+struct RemoteNode {
+    client: HttpClient,
+    rate_limiter: RateLimiter<
+        governor::state::NotKeyed,
+        governor::state::InMemoryState,
+        governor::clock::DefaultClock,
+    >,
+}
+
+impl RemoteNode {
+    fn new(url: &str, requests_per_second: NonZeroU32) -> anyhow::Result<Self> {
+        let client = HttpClientBuilder::default()
+            .build(url)
+            .with_context(|| format!("invalid remote node URL `{url}`"))?;
+        Ok(Self {
+            client,
+            rate_limiter: RateLimiter::direct(Quota::per_second(requests_per_second)),
+        })
+    }
+
+    async fn fetch_miniblock(&self, number: MiniblockNumber) -> anyhow::Result<RemoteMiniblock> {
+        self.rate_limiter.until_ready().await;
+        let block = self
+            .client
+            .get_block_by_number(BlockNumber::Number(number.0.into()), false)
+            .rpc_context("get_block_by_number")
+            .with_arg("number", &number)
+            .await?
+            .with_context(|| format!("miniblock #{number} is missing on the remote node"))?;
+        self.rate_limiter.until_ready().await;
+        let details = self
+            .client
+            .get_block_details(number)
+            .rpc_context("get_block_details")
+            .with_arg("number", &number)
+            .await?
+            .with_context(|| format!("miniblock #{number} is missing on the remote node"))?;
+        self.rate_limiter.until_ready().await;
+        let transactions = self
+            .client
+            .get_raw_block_transactions(number)
+            .rpc_context("get_raw_block_transactions")
+            .with_arg("number", &number)
+            .await?;
+
+        let expected_hash = miniblock_hash(number);
+        anyhow::ensure!(
+            block.hash == expected_hash,
+            "remote node returned hash {:?} for miniblock #{number}, expected {expected_hash:?}",
+            block.hash
+        );
+        let block_tx_hashes: Vec<_> = block
+            .transactions
+            .iter()
+            .map(|tx| match tx {
+                TransactionVariant::Hash(hash) => *hash,
+                TransactionVariant::Full(tx) => tx.hash,
+            })
+            .collect();
+        let tx_hashes: Vec<_> = transactions.iter().map(Transaction::hash).collect();
+        anyhow::ensure!(
+            tx_hashes == block_tx_hashes,
+            "transactions of miniblock #{number} don't match the remote block"
+        );
+
+        let mut receipts = Vec::with_capacity(tx_hashes.len());
+        for &tx_hash in &tx_hashes {
+            self.rate_limiter.until_ready().await;
+            let receipt = EthNamespaceClient::get_transaction_receipt(&self.client, tx_hash)
+                .rpc_context("get_transaction_receipt")
+                .with_arg("tx_hash", &tx_hash)
+                .await?
+                .with_context(|| format!("receipt of tx {tx_hash:?} is missing"))?;
+            anyhow::ensure!(
+                receipt.transaction_hash == tx_hash
+                    && receipt.block_number == Some(number.0.into()),
+                "receipt of tx {tx_hash:?} doesn't belong to miniblock #{number}"
+            );
+            receipts.push(receipt);
+        }
+
+        Ok(RemoteMiniblock {
+            header: MiniblockHeader {
+                number,
+                timestamp: details.base.timestamp,
+                hash: block.hash,
+                l1_tx_count: details.base.l1_tx_count as u16,
+                l2_tx_count: details.base.l2_tx_count as u16,
+                base_system_contracts_hashes: details.base.base_system_contracts_hashes,
+                protocol_version: details.protocol_version,
+            },
+            l1_batch_number: details.l1_batch_number,
+            transactions,
+            receipts,
+        })
+    }
+}
+
+/// Backfills miniblocks from `args.from_block` to `args.to_block` (inclusive).
+pub async fn run_backfill(pool: &ConnectionPool, args: &BackfillArgs) -> anyhow::Result<()> {
+    let remote = RemoteNode::new(&args.remote_url, args.requests_per_second)?;
+    let mut storage = pool.access_storage_tagged("backfill").await;
+    let total = args.to_block.0 - args.from_block.0 + 1;
+
+    for number in args.from_block.0..=args.to_block.0 {
+        let number = MiniblockNumber(number);
+        let miniblock = remote.fetch_miniblock(number).await?;
+        let local_header = storage.blocks_dal().get_miniblock_header(number).await?;
+        if let Some(local_header) = local_header {
+            anyhow::ensure!(
+                local_header.hash == miniblock.header.hash,
+                "local miniblock #{number} has hash {:?}, while the remote one has {:?}",
+                local_header.hash,
+                miniblock.header.hash
+            );
+            olaos_logs::info!("Miniblock #{number} is already present locally, skipping");
+        } else {
+            write_miniblock(&mut storage, &miniblock).await?;
+        }
+
+        let done = number.0 - args.from_block.0 + 1;
+        metrics::gauge!("server.backfill.last_miniblock", number.0 as f64);
+        olaos_logs::info!(
+            "Backfilled miniblock #{number} with {} txs ({done}/{total})",
+            miniblock.transactions.len()
+        );
+    }
+    Ok(())
+}
+
+async fn write_miniblock(
+    storage: &mut StorageProcessor<'_>,
+    miniblock: &RemoteMiniblock,
+) -> anyhow::Result<()> {
+    let number = miniblock.header.number;
+    let mut executed_transactions = Vec::with_capacity(miniblock.transactions.len());
+    let mut events = Vec::with_capacity(miniblock.transactions.len());
+    for (index, (tx, receipt)) in miniblock
+        .transactions
+        .iter()
+        .zip(&miniblock.receipts)
+        .enumerate()
+    {
+        let execution_status = match receipt.status.map(|status| status.as_u64()) {
+            Some(1) => TxExecutionStatus::Success,
+            _ => TxExecutionStatus::Failure,
+        };
+        let l1_batch_tx_index = receipt
+            .l1_batch_tx_index
+            .with_context(|| format!("receipt of tx {:?} has no L1 batch index", tx.hash()))?;
+        let location = IncludedTxLocation {
+            tx_hash: tx.hash(),
+            tx_index_in_miniblock: index as u32,
+            tx_initiator_address: tx.initiator_account(),
+        };
+        let tx_events: Vec<_> = receipt
+            .logs
+            .iter()
+            .map(|log| VmEvent {
+                location: (miniblock.l1_batch_number, l1_batch_tx_index.as_u32()),
+                address: log.address,
+                indexed_topics: log.topics.clone(),
+                value: log.data.0.clone(),
+            })
+            .collect();
+        events.push((location, tx_events));
+        executed_transactions.push(TransactionExecutionResult {
+            transaction: tx.clone(),
+            hash: tx.hash(),
+            execution_info: ExecutionMetrics {
+                vm_events: receipt.logs.len(),
+                ..ExecutionMetrics::default()
+            },
+            execution_status,
+            call_traces: vec![],
+            revert_reason: None,
+        });
+    }
+
+    let mut transaction = storage.start_transaction().await;
+    for tx in &miniblock.transactions {
+        // Executed transactions are updated in place, so they must be known beforehand.
+        if let ExecuteTransactionCommon::L2(common_data) = &tx.common_data {
+            let l2_tx = L2Tx {
+                execute: tx.execute.clone(),
+                common_data: common_data.clone(),
+                received_timestamp_ms: tx.received_timestamp_ms,
+            };
+            transaction
+                .transactions_dal()
+                .insert_transaction_l2(l2_tx, TransactionExecutionMetrics::default())
+                .await?;
+        }
+    }
+    transaction
+        .blocks_dal()
+        .insert_miniblock(&miniblock.header)
+        .await?;
+    transaction
+        .blocks_dal()
+        .set_miniblock_l1_batch_number(number, miniblock.l1_batch_number)
+        .await?;
+    transaction
+        .transactions_dal()
+        .mark_txs_as_executed_in_miniblock(number, &executed_transactions, usize::MAX)
+        .await?;
+    let events: Vec<_> = events
+        .iter()
+        .map(|(location, tx_events)| (location.clone(), tx_events.iter().collect()))
+        .collect();
+    transaction
+        .events_dal()
+        .save_events(number, &events)
+        .await?;
+    transaction.commit().await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ola_types::protocol_version::ProtocolVersionId;
+
+    use super::*;
+
+    fn args(args: &[&str]) -> anyhow::Result<BackfillArgs> {
+        BackfillArgs::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parsing_backfill_args() {
+        let parsed = args(&[
+            "--from-block",
+            "3",
+            "--to-block",
+            "5",
+            "--remote",
+            "http://127.0.0.1:13000",
+        ])
+        .unwrap();
+        assert_eq!(
+            parsed,
+            BackfillArgs {
+                from_block: MiniblockNumber(3),
+                to_block: MiniblockNumber(5),
+                remote_url: "http://127.0.0.1:13000".to_owned(),
+                requests_per_second: NonZeroU32::new(20).unwrap(),
+            }
+        );
+
+        assert!(args(&["--from-block", "3", "--to-block", "5"]).is_err());
+        assert!(args(&[
+            "--from-block",
+            "5",
+            "--to-block",
+            "3",
+            "--remote",
+            "http://127.0.0.1:13000"
+        ])
+        .is_err());
+        assert!(args(&[
+            "--from-block",
+            "3",
+            "--to-block",
+            "5",
+            "--remote",
+            "http://127.0.0.1:13000",
+            "--rps",
+            "0"
+        ])
+        .is_err());
+    }
+
+    #[ignore]
+    #[tokio::test]
+    async fn backfilled_miniblock_is_assigned_to_l1_batch() {
+        let mut storage = StorageProcessor::establish_connection(true).await;
+        // Nothing is committed, so the test leaves the database intact.
+        let mut transaction = storage.start_transaction().await;
+        let number = transaction
+            .blocks_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .unwrap()
+            + 1;
+        let miniblock = RemoteMiniblock {
+            header: MiniblockHeader {
+                number,
+                timestamp: 1_700_000_000,
+                hash: miniblock_hash(number),
+                l1_tx_count: 0,
+                l2_tx_count: 0,
+                base_system_contracts_hashes: Default::default(),
+                protocol_version: Some(ProtocolVersionId::latest()),
+            },
+            l1_batch_number: L1BatchNumber(42),
+            transactions: vec![],
+            receipts: vec![],
+        };
+        write_miniblock(&mut transaction, &miniblock).await.unwrap();
+
+        let resolved = transaction
+            .storage_web3_dal()
+            .resolve_l1_batch_number_of_miniblock(number)
+            .await
+            .unwrap();
+        assert_eq!(resolved.miniblock_l1_batch, Some(L1BatchNumber(42)));
+    }
+}
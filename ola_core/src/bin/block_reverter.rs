@@ -0,0 +1,55 @@
+//! Reverts Postgres chain data to an L1 batch, moving reverted data to the revert log.
+//!
+//! Must be run with the node stopped: `block_reverter <last_kept_l1_batch> <reason>`. Afterwards,
+//! the Merkle tree and the sequencer RocksDB must be rebuilt, since they aren't rolled back.
+
+use anyhow::Context as _;
+use ola_dal::connection::{ConnectionPool, DbVariant};
+use ola_types::L1BatchNumber;
+
+const USAGE: &str = "usage: block_reverter <last_kept_l1_batch> <reason>";
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [last_kept_l1_batch, reason] = args.as_slice() else {
+        anyhow::bail!(USAGE);
+    };
+    let last_kept_l1_batch = L1BatchNumber(last_kept_l1_batch.parse().context(USAGE)?);
+
+    let pool = ConnectionPool::singleton(DbVariant::Master).build().await;
+    let mut storage = pool.access_storage_tagged("block_reverter").await;
+    let sealed_l1_batch = storage.blocks_dal().get_sealed_l1_batch_number().await?;
+    anyhow::ensure!(
+        last_kept_l1_batch <= sealed_l1_batch,
+        "L1 batch #{last_kept_l1_batch} is not sealed; the last sealed one is #{sealed_l1_batch}"
+    );
+    let last_archived_l1_batch = storage
+        .transactions_archive_dal()
+        .get_last_archived_l1_batch()
+        .await?;
+    if let Some(last_archived) = last_archived_l1_batch {
+        anyhow::ensure!(
+            last_archived <= last_kept_l1_batch,
+            "transactions of L1 batches up to #{last_archived} are archived and cannot be reverted"
+        );
+    }
+
+    let reverted = storage
+        .reverts_dal()
+        .revert_to_l1_batch(last_kept_l1_batch, reason)
+        .await?;
+    println!(
+        "Reverted to L1 batch #{last_kept_l1_batch} (revert #{}): {} L1 batches, {} miniblocks, \
+         {} transactions, {} events, {} storage slots, {} tokens",
+        reverted.revert_id,
+        reverted.l1_batches,
+        reverted.miniblocks,
+        reverted.transactions,
+        reverted.events,
+        reverted.storage_slots,
+        reverted.tokens
+    );
+    println!("Rebuild the Merkle tree and the sequencer RocksDB before restarting the node");
+    Ok(())
+}
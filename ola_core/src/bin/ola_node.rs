@@ -1,54 +1,203 @@
-use std::time::Duration;
-
+use anyhow::Context as _;
 use ola_config::{
-    contracts::load_contracts_config, eth_sender::load_eth_sender_config,
+    bootstrap::bootstrap_config, contracts::load_contracts_config, database::load_db_config,
+    eth_sender::load_eth_sender_config, node::Config, object_store::load_object_store_config,
     sequencer::load_network_config,
 };
+use ola_contracts::BaseSystemContracts;
 use ola_core::{
-    genesis_init, initialize_components, is_genesis_needed, setup_sigint_handler, Component,
+    archive::{run_restore_archive, RestoreArchiveArgs},
+    audit::AuditLog,
+    backfill::{run_backfill, BackfillArgs},
+    build_info, dump_config,
+    export::{run_export, ExportArgs},
+    genesis::verify_base_system_contracts,
+    genesis_bundle::{run_export_genesis, ExportGenesisArgs},
+    genesis_init, is_genesis_needed,
+    node::NodeBuilder,
+    replay_bundle::{run_create_replay_bundle, ReplayBundleArgs},
+    sequencer::{
+        operator_rotation::{run_rotate_operator, RotateOperatorArgs},
+        snapshot_sync::{run_create_snapshot, CreateSnapshotArgs},
+        upgrade_dry_run::{run_upgrade_dry_run, UpgradeDryRunArgs},
+    },
+    setup_sigint_handler, validate_config, Component,
 };
-use ola_utils::wait_for_tasks::wait_for_tasks;
+use ola_dal::connection::{ConnectionPool, DbVariant};
+use ola_types::{protocol_version::ProtocolVersionId, L2ChainId};
 use olaos_logs::telemetry::{get_subscriber, init_subscriber, set_panic_hook};
+use olaos_object_store::ObjectStoreFactory;
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
+fn main() -> anyhow::Result<()> {
     let (subscriber, _guard) = get_subscriber("olaos_node".into(), "info".into());
     init_subscriber(subscriber);
     set_panic_hook();
     olaos_logs::info!("init_subscriber finished");
+    olaos_logs::info!(
+        "ola_node {} (commit {}, {} build)",
+        build_info::VERSION,
+        build_info::GIT_COMMIT,
+        build_info::BUILD_PROFILE
+    );
+    // Bootstrapped variables must be set before any config is loaded, and before the runtime
+    // threads which may read the environment concurrently are started.
+    bootstrap_config().context("failed to bootstrap configuration")?;
 
-    if is_genesis_needed().await {
-        let eth_sender = load_eth_sender_config().expect("failed to load eth sender config");
-        let network = load_network_config().expect("failed to load network config");
-        let contracts = load_contracts_config().expect("failed to laod contract config");
-        genesis_init(&eth_sender, &network, &contracts).await;
-        olaos_logs::info!("genesis_init finished");
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .context("failed to build Tokio runtime")?
+        .block_on(run())
+}
+
+async fn run() -> anyhow::Result<()> {
+    if std::env::args().nth(1).as_deref() == Some("export") {
+        let args = ExportArgs::parse(std::env::args().skip(2))?;
+        let network = load_network_config().context("failed to load network config")?;
+        let pool = ConnectionPool::singleton(DbVariant::Replica).build().await;
+        return run_export(&pool, L2ChainId(network.ola_network_id), &args).await;
+    }
+    if std::env::args().nth(1).as_deref() == Some("upgrade-dry-run") {
+        let args = UpgradeDryRunArgs::parse(std::env::args().skip(2))?;
+        let pool = ConnectionPool::singleton(DbVariant::Replica).build().await;
+        // The next protocol version ships its base system contracts with the node binary.
+        let contracts = BaseSystemContracts::load_from_disk();
+        let report =
+            run_upgrade_dry_run(&pool, ProtocolVersionId::next(), contracts, &args).await?;
+        print!("{report}");
+        if report.has_divergences() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    if std::env::args().nth(1).as_deref() == Some("backfill") {
+        let args = BackfillArgs::parse(std::env::args().skip(2))?;
+        let pool = ConnectionPool::singleton(DbVariant::Master).build().await;
+        return run_backfill(&pool, &args).await;
+    }
+    if std::env::args().nth(1).as_deref() == Some("restore-archive") {
+        let args = RestoreArchiveArgs::parse(std::env::args().skip(2))?;
+        let object_store_config =
+            load_object_store_config().context("failed to load object store config")?;
+        let object_store = ObjectStoreFactory::new(object_store_config)
+            .create_store()
+            .await;
+        let pool = ConnectionPool::singleton(DbVariant::Master).build().await;
+        return run_restore_archive(&pool, object_store.as_ref(), &args).await;
+    }
+    if std::env::args().nth(1).as_deref() == Some("rotate-operator") {
+        let args = RotateOperatorArgs::parse(std::env::args().skip(2))?;
+        let db_config = load_db_config().context("failed to load database config")?;
+        let pool = ConnectionPool::singleton(DbVariant::Master).build().await;
+        return run_rotate_operator(&pool, &AuditLog::from_config(&db_config), &args).await;
+    }
+    if std::env::args().nth(1).as_deref() == Some("replay-bundle") {
+        let args = ReplayBundleArgs::parse(std::env::args().skip(2))?;
+        let object_store_config =
+            load_object_store_config().context("failed to load object store config")?;
+        let object_store = ObjectStoreFactory::new(object_store_config)
+            .create_store()
+            .await;
+        let pool = ConnectionPool::singleton(DbVariant::Replica).build().await;
+        return run_create_replay_bundle(&pool, object_store.as_ref(), &args).await;
+    }
+    if std::env::args().nth(1).as_deref() == Some("create-snapshot") {
+        let args = CreateSnapshotArgs::parse(std::env::args().skip(2))?;
+        let object_store_config =
+            load_object_store_config().context("failed to load object store config")?;
+        let object_store = ObjectStoreFactory::new(object_store_config)
+            .create_store()
+            .await;
+        let pool = ConnectionPool::singleton(DbVariant::Replica).build().await;
+        return run_create_snapshot(&pool, object_store.as_ref(), &args).await;
+    }
+
+    if std::env::args().nth(1).as_deref() == Some("export-genesis") {
+        let args = ExportGenesisArgs::parse(std::env::args().skip(2))?;
+        let pool = ConnectionPool::singleton(DbVariant::Replica).build().await;
+        return run_export_genesis(&pool, &args).await;
     }
 
-    let components = vec![
+    let mut components = vec![
         Component::HttpApi,
         Component::PubsubApi,
         Component::Sequencer,
         Component::Tree,
         Component::ProofDataHandler,
+        Component::ConsistencyChecker,
     ];
-    let (core_task_handles, stop_sender, health_check_handle) = initialize_components(components)
-        .await
-        .expect("Unable to start Core actors");
+    let config = Config::load().context("failed to load configuration")?;
+    if config.db.tx_archive_retention_l1_batches.is_some() {
+        components.push(Component::TransactionArchiver);
+    }
+    if config.proof_data_handler.proof_smoke_test_enabled() {
+        components.push(Component::ProofSmokeTest);
+    }
+    let read_only = config.api.web3_json_rpc.read_only();
+    if read_only {
+        components.retain(|component| component.is_read_only());
+    }
+    olaos_logs::info!("Effective configuration:\n{}", dump_config(&config)?);
+    if std::env::args().nth(1).as_deref() == Some("doctor") {
+        run_doctor(&config, &components);
+        return Ok(());
+    }
+    validate_config(&config, &components).context("invalid configuration")?;
 
-    olaos_logs::info!("Running {} core task handlers", core_task_handles.len());
+    if read_only {
+        // Read-only nodes serve data replicated from the main node and never write genesis.
+        anyhow::ensure!(
+            !is_genesis_needed().await?,
+            "read-only node requires a database initialized by the main node"
+        );
+    } else if is_genesis_needed().await? {
+        let eth_sender = load_eth_sender_config().expect("failed to load eth sender config");
+        let network = load_network_config().expect("failed to load network config");
+        let contracts = load_contracts_config().expect("failed to laod contract config");
+        genesis_init(&eth_sender, &network, &contracts)
+            .await
+            .context("genesis_init()")?;
+        olaos_logs::info!("genesis_init finished");
+    }
+    // A replica may lag behind the protocol versions inserted by genesis above.
+    let pool = ConnectionPool::singleton(DbVariant::Master).build().await;
+    verify_base_system_contracts(
+        &mut pool.access_storage().await,
+        &BaseSystemContracts::load_from_disk(),
+    )
+    .await?;
+
+    let node = NodeBuilder::new(config)
+        .with_components(components)
+        .build()
+        .await
+        .context("unable to start core actors")?;
     let sigint_receiver = setup_sigint_handler();
 
-    let graceful_shutdown = None::<futures::future::Ready<()>>;
-    tokio::select! {
-        _ = wait_for_tasks(core_task_handles, graceful_shutdown, false) => {},
-        _ = sigint_receiver => {
-            olaos_logs::info!("Stop signal received, shutting down");
-        },
-    }
-    stop_sender.send(true).ok();
-    tokio::time::sleep(Duration::from_secs(5)).await;
-    health_check_handle.stop().await;
-    olaos_logs::info!("Stopped");
+    // Any component terminating is unexpected: exit with the code of the terminated component,
+    // so that supervisors can tell them apart.
+    let terminated_component = node
+        .run(async {
+            sigint_receiver.await.ok();
+        })
+        .await;
+    if let Some(component) = terminated_component {
+        std::process::exit(component.exit_code());
+    }
     Ok(())
 }
+
+/// Checks the node configuration without starting any components (`ola_node doctor`).
+/// Exits with a non-zero code if there are violations.
+fn run_doctor(config: &Config, components: &[Component]) {
+    match validate_config(config, components) {
+        Ok(()) => println!("Configuration for components {components:?} is valid"),
+        Err(err) => {
+            println!("Configuration for components {components:?} is invalid:");
+            for violation in &err.violations {
+                println!("  - {violation}");
+            }
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Prints storage slots changed between two L1 batches, grouped by account.
+//!
+//! Intended to be run before and after a protocol upgrade to check that no unintended state
+//! changed: `state_diff <from_l1_batch> <to_l1_batch> [--json]`.
+
+use anyhow::Context as _;
+use ola_dal::connection::{ConnectionPool, DbVariant};
+use ola_types::{
+    storage::{diff::StateDiff, labels::StorageKeyLabels},
+    AccountTreeId, L1BatchNumber, MiniblockNumber, StorageKey,
+};
+
+const USAGE: &str = "usage: state_diff <from_l1_batch> <to_l1_batch> [--json]";
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let json = args.iter().any(|arg| arg == "--json");
+    let numbers: Vec<_> = args.iter().filter(|arg| !arg.starts_with("--")).collect();
+    let [from, to] = numbers.as_slice() else {
+        anyhow::bail!(USAGE);
+    };
+    let from = L1BatchNumber(from.parse().context(USAGE)?);
+    let to = L1BatchNumber(to.parse().context(USAGE)?);
+    anyhow::ensure!(from < to, "from_l1_batch must be lower than to_l1_batch");
+
+    let pool = ConnectionPool::singleton(DbVariant::Replica).build().await;
+    let mut storage = pool.access_storage_tagged("state_diff").await;
+    let from_miniblock = last_miniblock(&mut storage, from).await?;
+    let to_miniblock = last_miniblock(&mut storage, to).await?;
+    let changes = storage
+        .storage_logs_dal()
+        .get_storage_diff(from_miniblock, to_miniblock)
+        .await?;
+    let diff = StateDiff::new(changes);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        print_diff(&diff, from, to);
+    }
+    Ok(())
+}
+
+async fn last_miniblock(
+    storage: &mut ola_dal::StorageProcessor<'_>,
+    l1_batch_number: L1BatchNumber,
+) -> anyhow::Result<MiniblockNumber> {
+    let (_, last) = storage
+        .blocks_dal()
+        .get_miniblock_range_of_l1_batch(l1_batch_number)
+        .await?
+        .with_context(|| format!("L1 batch #{l1_batch_number} is not sealed"))?;
+    Ok(last)
+}
+
+fn print_diff(diff: &StateDiff, from: L1BatchNumber, to: L1BatchNumber) {
+    let summary = &diff.summary;
+    println!("State diff between L1 batches #{from} and #{to}");
+    println!(
+        "  {} slots changed in {} accounts: {} created, {} updated, {} cleared",
+        summary.slots_changed,
+        summary.accounts_changed,
+        summary.slots_created,
+        summary.slots_updated,
+        summary.slots_cleared
+    );
+
+    let mut labels = StorageKeyLabels::new();
+    for (address, slots) in &diff.accounts {
+        labels.register_account(address);
+        println!("{address:?}:");
+        for slot in slots {
+            let key = StorageKey::new(AccountTreeId::new(*address), slot.key);
+            println!(
+                "  {} ({:?}): {:?} -> {:?}",
+                labels.label(&key),
+                slot.kind,
+                slot.before,
+                slot.after
+            );
+        }
+    }
+}
@@ -0,0 +1,18 @@
+//! Information about the node build, embedded by the build script.
+
+/// Semantic version of the node.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Git commit the node was built from; `unknown` if it couldn't be determined.
+pub const GIT_COMMIT: &str = env!("OLAOS_GIT_COMMIT");
+/// Cargo profile of the build, e.g. `release`.
+pub const BUILD_PROFILE: &str = env!("OLAOS_BUILD_PROFILE");
+
+/// Cargo features enabled for the build.
+pub fn features() -> Vec<String> {
+    let features = env!("OLAOS_BUILD_FEATURES");
+    features
+        .split(',')
+        .filter(|feature| !feature.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
@@ -0,0 +1,170 @@
+//! Checker of invariants across Postgres tables.
+//!
+//! [`ConsistencyChecker`] checks core invariants on startup and then periodically: miniblocks are
+//! numbered contiguously, no two transactions share a position in a miniblock, events reference
+//! existing transactions, and miniblocks of consecutive L1 batches form consecutive ranges.
+//! Core inconsistencies can't be repaired automatically, so they are only reported. Data derived
+//! from other tables (transaction counts of miniblocks, transaction fields denormalized into
+//! events and transaction L1 batch numbers) is repaired if `database.consistency_repair_enabled`
+//! is set.
+
+use std::time::{Duration, Instant};
+
+use ola_dal::connection::ConnectionPool;
+use ola_types::{L1BatchNumber, MiniblockNumber};
+use tokio::sync::watch;
+
+/// Violations of core invariants found by a check.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConsistencyReport {
+    /// Consecutive miniblock numbers that aren't contiguous.
+    pub miniblock_gaps: Vec<(MiniblockNumber, MiniblockNumber)>,
+    /// Positions in miniblocks occupied by more than one transaction.
+    pub duplicate_tx_indices: Vec<(MiniblockNumber, u32)>,
+    pub orphaned_events: u64,
+    /// L1 batches whose miniblocks don't form a range following the previous L1 batch.
+    pub misaligned_l1_batches: Vec<L1BatchNumber>,
+}
+
+impl ConsistencyReport {
+    pub fn violation_count(&self) -> usize {
+        self.miniblock_gaps.len()
+            + self.duplicate_tx_indices.len()
+            + self.orphaned_events as usize
+            + self.misaligned_l1_batches.len()
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.violation_count() == 0
+    }
+}
+
+/// Numbers of rows with derived data repaired by a check.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RepairStats {
+    pub miniblock_tx_counts: u64,
+    pub event_tx_fields: u64,
+    pub tx_l1_batch_numbers: u64,
+}
+
+impl RepairStats {
+    pub fn total(&self) -> u64 {
+        self.miniblock_tx_counts + self.event_tx_fields + self.tx_l1_batch_numbers
+    }
+}
+
+#[derive(Debug)]
+pub struct ConsistencyChecker {
+    pool: ConnectionPool,
+    interval: Duration,
+    repair_enabled: bool,
+}
+
+impl ConsistencyChecker {
+    pub fn new(pool: ConnectionPool, interval: Duration, repair_enabled: bool) -> Self {
+        Self {
+            pool,
+            interval,
+            repair_enabled,
+        }
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        loop {
+            if *stop_receiver.borrow_and_update() {
+                break;
+            }
+            // The checker only reports problems, so its own failures must not stop the node.
+            if let Err(err) = self.check_and_repair().await {
+                metrics::increment_counter!("server.consistency_checker.failed_checks");
+                olaos_logs::error!("Failed checking consistency of Postgres tables: {err:#}");
+            }
+
+            tokio::select! {
+                _ = stop_receiver.changed() => break,
+                () = tokio::time::sleep(self.interval) => { /* Continue checking */ }
+            }
+        }
+        olaos_logs::info!("Stop signal received, consistency checker is shutting down");
+        Ok(())
+    }
+
+    async fn check_and_repair(&self) -> anyhow::Result<()> {
+        let started_at = Instant::now();
+        let report = self.check().await?;
+        metrics::gauge!(
+            "server.consistency_checker.violations",
+            report.violation_count() as f64
+        );
+        if report.is_consistent() {
+            olaos_logs::info!("Postgres tables are consistent");
+        } else {
+            olaos_logs::error!(
+                "Found {} violations of core invariants in Postgres tables: {report:?}",
+                report.violation_count()
+            );
+        }
+
+        if self.repair_enabled {
+            let stats = self.repair().await?;
+            metrics::counter!("server.consistency_checker.repaired_rows", stats.total());
+            if stats.total() > 0 {
+                olaos_logs::warn!("Repaired derived data in Postgres tables: {stats:?}");
+            }
+        }
+        metrics::histogram!(
+            "server.consistency_checker.check_latency",
+            started_at.elapsed()
+        );
+        Ok(())
+    }
+
+    pub async fn check(&self) -> anyhow::Result<ConsistencyReport> {
+        let mut storage = self.pool.access_storage_tagged("consistency_checker").await;
+        let mut dal = storage.consistency_dal();
+        Ok(ConsistencyReport {
+            miniblock_gaps: dal.get_miniblock_gaps().await?,
+            duplicate_tx_indices: dal.get_duplicate_tx_indices().await?,
+            orphaned_events: dal.count_orphaned_events().await?,
+            misaligned_l1_batches: dal.get_misaligned_l1_batches().await?,
+        })
+    }
+
+    pub async fn repair(&self) -> anyhow::Result<RepairStats> {
+        let mut storage = self.pool.access_storage_tagged("consistency_checker").await;
+        let last_archived_l1_batch = storage
+            .transactions_archive_dal()
+            .get_last_archived_l1_batch()
+            .await?;
+        let mut transaction = storage.start_transaction().await;
+        let mut dal = transaction.consistency_dal();
+        let stats = RepairStats {
+            tx_l1_batch_numbers: dal.repair_tx_l1_batch_numbers().await?,
+            miniblock_tx_counts: dal
+                .repair_miniblock_tx_counts(last_archived_l1_batch)
+                .await?,
+            event_tx_fields: dal.repair_event_tx_fields().await?,
+        };
+        transaction.commit().await;
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counting_violations() {
+        assert!(ConsistencyReport::default().is_consistent());
+
+        let report = ConsistencyReport {
+            miniblock_gaps: vec![(MiniblockNumber(5), MiniblockNumber(7))],
+            orphaned_events: 3,
+            misaligned_l1_batches: vec![L1BatchNumber(2)],
+            ..ConsistencyReport::default()
+        };
+        assert_eq!(report.violation_count(), 5);
+        assert!(!report.is_consistent());
+    }
+}
@@ -0,0 +1,618 @@
+//! Bulk export of historical chain data into CSV or Parquet files (`ola_node export`).
+//!
+//! Each exported L1 batch is written to its own `batch_<number>` directory inside the output
+//! directory. The directory contains the following tables, each in a `<table>.csv` or
+//! `<table>.parquet` file depending on the format. In CSV, all hashes, addresses and byte
+//! strings are `0x`-prefixed hex, numbers are decimal, and missing values are empty. In Parquet,
+//! numeric columns are nullable `UInt64` and the other columns are nullable UTF-8 strings
+//! with the same contents as in CSV.
+//!
+//! - `blocks`: `number,hash,l1_batch_number,timestamp,tx_count`
+//! - `transactions`: `hash,block_number,index_in_block,from,to,contract_address,status`
+//! - `events`: `tx_hash,block_number,log_index,address,topics,data`, where `topics` are
+//!   separated by `;`
+//! - `storage_diffs`: `address,key,before,after` with values of all slots changed
+//!   in the batch (empty for the genesis batch)
+//!
+//! A batch directory is populated in a temporary location and renamed once complete, and the
+//! requested batch range, format and the number of the last exported batch are recorded
+//! in the `checkpoint` file. An interrupted export resumes from the batch following
+//! the checkpoint if it's restarted with the same range and format; otherwise, the checkpoint
+//! is discarded and the export starts over.
+//!
+//! Parquet output requires the `parquet` feature of `ola_core`, which is disabled by default
+//! to keep the `arrow` dependency tree out of node builds which don't need it.
+
+use std::{
+    fmt::{self, Write as _},
+    fs,
+    io::Write as _,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context as _;
+use ola_dal::{connection::ConnectionPool, StorageProcessor};
+use ola_types::{
+    api::{BlockId, BlockNumber, TransactionVariant},
+    L1BatchNumber, L2ChainId, MiniblockNumber,
+};
+use serde::{Deserialize, Serialize};
+
+const CHECKPOINT_FILE: &str = "checkpoint";
+
+/// Format of exported files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            #[cfg(feature = "parquet")]
+            Self::Parquet => "parquet",
+        }
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Self::Csv),
+            #[cfg(feature = "parquet")]
+            "parquet" => Ok(Self::Parquet),
+            #[cfg(not(feature = "parquet"))]
+            "parquet" => anyhow::bail!(
+                "parquet export requires the node to be built with the `parquet` feature"
+            ),
+            _ => anyhow::bail!("unknown export format `{s}`"),
+        }
+    }
+}
+
+/// Arguments of `ola_node export`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportArgs {
+    pub from_batch: L1BatchNumber,
+    pub to_batch: L1BatchNumber,
+    pub format: ExportFormat,
+    pub output_dir: PathBuf,
+}
+
+impl ExportArgs {
+    pub const USAGE: &'static str = "usage: ola_node export --from-batch <number> \
+        --to-batch <number> [--format csv|parquet] [--output <dir>]";
+
+    /// Parses arguments following the `export` subcommand.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Self> {
+        let mut from_batch = None;
+        let mut to_batch = None;
+        let mut format = ExportFormat::Csv;
+        let mut output_dir = PathBuf::from("export");
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .with_context(|| format!("missing value for `{arg}`; {}", Self::USAGE))?;
+            match arg.as_str() {
+                "--from-batch" => from_batch = Some(L1BatchNumber(value.parse()?)),
+                "--to-batch" => to_batch = Some(L1BatchNumber(value.parse()?)),
+                "--format" => format = value.parse()?,
+                "--output" => output_dir = PathBuf::from(value),
+                _ => anyhow::bail!("unknown argument `{arg}`; {}", Self::USAGE),
+            }
+        }
+
+        let from_batch = from_batch.context(Self::USAGE)?;
+        let to_batch = to_batch.context(Self::USAGE)?;
+        anyhow::ensure!(
+            from_batch <= to_batch,
+            "--from-batch must not be greater than --to-batch"
+        );
+        Ok(Self {
+            from_batch,
+            to_batch,
+            format,
+            output_dir,
+        })
+    }
+}
+
+/// Progress of an export recorded in the checkpoint file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Checkpoint {
+    from_batch: L1BatchNumber,
+    to_batch: L1BatchNumber,
+    format: ExportFormat,
+    last_exported_batch: L1BatchNumber,
+}
+
+impl Checkpoint {
+    /// Returns the batch to resume `args` from, or `None` if the checkpoint was recorded
+    /// for another export.
+    fn next_batch(&self, args: &ExportArgs) -> Option<L1BatchNumber> {
+        let is_same_export = self.from_batch == args.from_batch
+            && self.to_batch == args.to_batch
+            && self.format == args.format;
+        is_same_export.then_some(self.last_exported_batch + 1)
+    }
+}
+
+/// Exports L1 batches from `args.from_batch` to `args.to_batch` (inclusive), skipping
+/// batches already recorded in the checkpoint of the same export.
+pub async fn run_export(
+    pool: &ConnectionPool,
+    chain_id: L2ChainId,
+    args: &ExportArgs,
+) -> anyhow::Result<()> {
+    fs::create_dir_all(&args.output_dir)
+        .with_context(|| format!("cannot create {}", args.output_dir.display()))?;
+    let mut next_batch = args.from_batch;
+    if let Some(checkpoint) = read_checkpoint(&args.output_dir)? {
+        if let Some(resumed_batch) = checkpoint.next_batch(args) {
+            next_batch = resumed_batch;
+            olaos_logs::info!(
+                "Resuming export after checkpoint at L1 batch #{}",
+                checkpoint.last_exported_batch
+            );
+        } else {
+            olaos_logs::warn!(
+                "Ignoring checkpoint of another export ({checkpoint:?}); starting over"
+            );
+        }
+    }
+
+    let mut storage = pool.access_storage_tagged("export").await;
+    while next_batch <= args.to_batch {
+        export_batch(
+            &mut storage,
+            chain_id,
+            next_batch,
+            args.format,
+            &args.output_dir,
+        )
+        .await?;
+        let checkpoint = Checkpoint {
+            from_batch: args.from_batch,
+            to_batch: args.to_batch,
+            format: args.format,
+            last_exported_batch: next_batch,
+        };
+        write_checkpoint(&args.output_dir, &checkpoint)?;
+        olaos_logs::info!("Exported L1 batch #{next_batch}");
+        next_batch += 1;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    Number,
+    Text,
+}
+
+/// Value of an exported table cell.
+#[derive(Debug, Clone, PartialEq)]
+enum Cell {
+    Number(u64),
+    Text(String),
+    Null,
+}
+
+impl Cell {
+    fn hex(value: impl fmt::Debug) -> Self {
+        Self::Text(format!("{value:?}"))
+    }
+
+    fn optional_hex(value: Option<impl fmt::Debug>) -> Self {
+        value.map_or(Self::Null, Self::hex)
+    }
+}
+
+impl fmt::Display for Cell {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(number) => write!(formatter, "{number}"),
+            Self::Text(text) => formatter.write_str(text),
+            Self::Null => Ok(()),
+        }
+    }
+}
+
+/// Exported table; see the [module docs](self) for the schemas.
+#[derive(Debug)]
+struct Table {
+    name: &'static str,
+    columns: &'static [(&'static str, ColumnType)],
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    const BLOCKS: &'static [(&'static str, ColumnType)] = &[
+        ("number", ColumnType::Number),
+        ("hash", ColumnType::Text),
+        ("l1_batch_number", ColumnType::Number),
+        ("timestamp", ColumnType::Number),
+        ("tx_count", ColumnType::Number),
+    ];
+    const TRANSACTIONS: &'static [(&'static str, ColumnType)] = &[
+        ("hash", ColumnType::Text),
+        ("block_number", ColumnType::Number),
+        ("index_in_block", ColumnType::Number),
+        ("from", ColumnType::Text),
+        ("to", ColumnType::Text),
+        ("contract_address", ColumnType::Text),
+        ("status", ColumnType::Number),
+    ];
+    const EVENTS: &'static [(&'static str, ColumnType)] = &[
+        ("tx_hash", ColumnType::Text),
+        ("block_number", ColumnType::Number),
+        ("log_index", ColumnType::Number),
+        ("address", ColumnType::Text),
+        ("topics", ColumnType::Text),
+        ("data", ColumnType::Text),
+    ];
+    const STORAGE_DIFFS: &'static [(&'static str, ColumnType)] = &[
+        ("address", ColumnType::Text),
+        ("key", ColumnType::Text),
+        ("before", ColumnType::Text),
+        ("after", ColumnType::Text),
+    ];
+
+    fn new(name: &'static str, columns: &'static [(&'static str, ColumnType)]) -> Self {
+        Self {
+            name,
+            columns,
+            rows: vec![],
+        }
+    }
+
+    fn push(&mut self, row: Vec<Cell>) {
+        assert_eq!(row.len(), self.columns.len(), "malformed {} row", self.name);
+        self.rows.push(row);
+    }
+
+    fn to_csv(&self) -> String {
+        let names: Vec<_> = self.columns.iter().map(|(name, _)| *name).collect();
+        let mut csv = names.join(",");
+        csv.push('\n');
+        for row in &self.rows {
+            let cells: Vec<_> = row.iter().map(Cell::to_string).collect();
+            writeln!(csv, "{}", cells.join(",")).unwrap();
+        }
+        csv
+    }
+
+    fn write(&self, dir: &Path, format: ExportFormat) -> anyhow::Result<()> {
+        let path = dir.join(format!("{}.{}", self.name, format.extension()));
+        match format {
+            ExportFormat::Csv => fs::write(&path, self.to_csv())?,
+            #[cfg(feature = "parquet")]
+            ExportFormat::Parquet => self.write_parquet(&path)?,
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "parquet")]
+    fn write_parquet(&self, path: &Path) -> anyhow::Result<()> {
+        use std::sync::Arc;
+
+        use arrow_array::{ArrayRef, RecordBatch, StringArray, UInt64Array};
+        use arrow_schema::{DataType, Field, Schema};
+        use parquet::arrow::ArrowWriter;
+
+        let fields: Vec<_> = self
+            .columns
+            .iter()
+            .map(|(name, column_type)| {
+                let data_type = match column_type {
+                    ColumnType::Number => DataType::UInt64,
+                    ColumnType::Text => DataType::Utf8,
+                };
+                Field::new(*name, data_type, true)
+            })
+            .collect();
+        let schema = Arc::new(Schema::new(fields));
+        let arrays = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, (_, column_type))| {
+                let cells = self.rows.iter().map(|row| &row[i]);
+                let array: ArrayRef = match column_type {
+                    ColumnType::Number => Arc::new(
+                        cells
+                            .map(|cell| match cell {
+                                Cell::Number(number) => Some(*number),
+                                _ => None,
+                            })
+                            .collect::<UInt64Array>(),
+                    ),
+                    ColumnType::Text => Arc::new(
+                        cells
+                            .map(|cell| match cell {
+                                Cell::Text(text) => Some(text.as_str()),
+                                _ => None,
+                            })
+                            .collect::<StringArray>(),
+                    ),
+                };
+                array
+            });
+        let batch = RecordBatch::try_new(schema.clone(), arrays.collect())?;
+
+        let file = fs::File::create(path)?;
+        let mut writer = ArrowWriter::try_new(file, schema, None)?;
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+}
+
+async fn export_batch(
+    storage: &mut StorageProcessor<'_>,
+    chain_id: L2ChainId,
+    l1_batch_number: L1BatchNumber,
+    format: ExportFormat,
+    output_dir: &Path,
+) -> anyhow::Result<()> {
+    let (first_miniblock, last_miniblock) = storage
+        .blocks_dal()
+        .get_miniblock_range_of_l1_batch(l1_batch_number)
+        .await?
+        .with_context(|| format!("L1 batch #{l1_batch_number} is not sealed"))?;
+
+    let mut blocks = Table::new("blocks", Table::BLOCKS);
+    let mut transactions = Table::new("transactions", Table::TRANSACTIONS);
+    let mut events = Table::new("events", Table::EVENTS);
+    for number in first_miniblock.0..=last_miniblock.0 {
+        let block_id = BlockId::Number(BlockNumber::Number(number.into()));
+        let block = storage
+            .blocks_web3_dal()
+            .get_block_by_web3_block_id(block_id, false, chain_id)
+            .await?
+            .with_context(|| format!("miniblock #{number} is missing"))?;
+        let tx_hashes: Vec<_> = block
+            .transactions
+            .iter()
+            .map(|tx| match tx {
+                TransactionVariant::Hash(hash) => *hash,
+                TransactionVariant::Full(tx) => tx.hash,
+            })
+            .collect();
+        blocks.push(vec![
+            Cell::Number(number.into()),
+            Cell::hex(block.hash),
+            Cell::Number(l1_batch_number.0.into()),
+            Cell::Number(block.timestamp.as_u64()),
+            Cell::Number(tx_hashes.len() as u64),
+        ]);
+
+        let receipts = storage
+            .transactions_web3_dal()
+            .get_transaction_receipts(&tx_hashes)
+            .await?;
+        for receipt in receipts {
+            transactions.push(vec![
+                Cell::hex(receipt.transaction_hash),
+                Cell::Number(number.into()),
+                Cell::Number(receipt.transaction_index.as_u64()),
+                Cell::hex(receipt.from),
+                Cell::optional_hex(receipt.to),
+                Cell::optional_hex(receipt.contract_address),
+                receipt
+                    .status
+                    .map_or(Cell::Null, |status| Cell::Number(status.as_u64())),
+            ]);
+            for log in &receipt.logs {
+                let topics: Vec<_> = log
+                    .topics
+                    .iter()
+                    .map(|topic| format!("{topic:?}"))
+                    .collect();
+                events.push(vec![
+                    Cell::hex(receipt.transaction_hash),
+                    Cell::Number(number.into()),
+                    Cell::Number(log.log_index.unwrap_or_default().as_u64()),
+                    Cell::hex(log.address),
+                    Cell::Text(topics.join(";")),
+                    Cell::Text(format!("0x{}", hex::encode(&log.data.0))),
+                ]);
+            }
+        }
+    }
+
+    let mut storage_diffs = Table::new("storage_diffs", Table::STORAGE_DIFFS);
+    // Diff against the state right before the first miniblock of the batch.
+    let diff_base = first_miniblock.0.checked_sub(1).map(MiniblockNumber);
+    let changes = match diff_base {
+        Some(base) => {
+            storage
+                .storage_logs_dal()
+                .get_storage_diff(base, last_miniblock)
+                .await?
+        }
+        None => vec![],
+    };
+    for change in changes {
+        storage_diffs.push(vec![
+            Cell::hex(change.key.address()),
+            Cell::hex(change.key.key()),
+            Cell::hex(change.before.unwrap_or_default()),
+            Cell::hex(change.after.unwrap_or_default()),
+        ]);
+    }
+
+    let batch_dir = output_dir.join(format!("batch_{l1_batch_number}"));
+    let tmp_dir = output_dir.join(format!(".batch_{l1_batch_number}.tmp"));
+    if tmp_dir.exists() {
+        fs::remove_dir_all(&tmp_dir)?;
+    }
+    fs::create_dir_all(&tmp_dir)?;
+    for table in [blocks, transactions, events, storage_diffs] {
+        table
+            .write(&tmp_dir, format)
+            .with_context(|| format!("cannot write {} table", table.name))?;
+    }
+    if batch_dir.exists() {
+        fs::remove_dir_all(&batch_dir)?;
+    }
+    fs::rename(&tmp_dir, &batch_dir)
+        .with_context(|| format!("cannot move export to {}", batch_dir.display()))
+}
+
+fn read_checkpoint(output_dir: &Path) -> anyhow::Result<Option<Checkpoint>> {
+    let path = output_dir.join(CHECKPOINT_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(&path)?;
+    let checkpoint = serde_json::from_str(&contents)
+        .with_context(|| format!("malformed checkpoint in {}", path.display()))?;
+    Ok(Some(checkpoint))
+}
+
+fn write_checkpoint(output_dir: &Path, checkpoint: &Checkpoint) -> anyhow::Result<()> {
+    let tmp_path = output_dir.join(format!("{CHECKPOINT_FILE}.tmp"));
+    let mut file = fs::File::create(&tmp_path)?;
+    serde_json::to_writer(&mut file, checkpoint)?;
+    writeln!(file)?;
+    file.sync_all()?;
+    fs::rename(tmp_path, output_dir.join(CHECKPOINT_FILE))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> anyhow::Result<ExportArgs> {
+        ExportArgs::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn sample_table() -> Table {
+        let mut table = Table::new("transactions", Table::TRANSACTIONS);
+        table.push(vec![
+            Cell::hex(ola_types::H256::repeat_byte(1)),
+            Cell::Number(3),
+            Cell::Number(0),
+            Cell::hex(ola_types::Address::repeat_byte(2)),
+            Cell::Null,
+            Cell::hex(ola_types::Address::repeat_byte(3)),
+            Cell::Number(1),
+        ]);
+        table
+    }
+
+    #[test]
+    fn parsing_export_args() {
+        let parsed = args(&["--from-batch", "3", "--to-batch", "5", "--output", "out"]).unwrap();
+        assert_eq!(
+            parsed,
+            ExportArgs {
+                from_batch: L1BatchNumber(3),
+                to_batch: L1BatchNumber(5),
+                format: ExportFormat::Csv,
+                output_dir: PathBuf::from("out"),
+            }
+        );
+
+        assert!(args(&["--from-batch", "3"]).is_err());
+        assert!(args(&["--from-batch", "5", "--to-batch", "3"]).is_err());
+        let parquet_args = args(&[
+            "--from-batch",
+            "1",
+            "--to-batch",
+            "2",
+            "--format",
+            "parquet",
+        ]);
+        #[cfg(feature = "parquet")]
+        assert_eq!(parquet_args.unwrap().format, ExportFormat::Parquet);
+        #[cfg(not(feature = "parquet"))]
+        assert!(parquet_args.unwrap_err().to_string().contains("feature"));
+        assert!("xlsx".parse::<ExportFormat>().is_err());
+    }
+
+    #[test]
+    fn checkpoint_roundtrip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert_eq!(read_checkpoint(dir.path()).unwrap(), None);
+        let checkpoint = Checkpoint {
+            from_batch: L1BatchNumber(3),
+            to_batch: L1BatchNumber(10),
+            format: ExportFormat::Csv,
+            last_exported_batch: L1BatchNumber(7),
+        };
+        write_checkpoint(dir.path(), &checkpoint).unwrap();
+        assert_eq!(read_checkpoint(dir.path()).unwrap(), Some(checkpoint));
+    }
+
+    #[test]
+    fn checkpoint_is_only_resumed_for_same_export() {
+        let checkpoint = Checkpoint {
+            from_batch: L1BatchNumber(3),
+            to_batch: L1BatchNumber(10),
+            format: ExportFormat::Csv,
+            last_exported_batch: L1BatchNumber(7),
+        };
+        let mut args = args(&["--from-batch", "3", "--to-batch", "10"]).unwrap();
+        assert_eq!(checkpoint.next_batch(&args), Some(L1BatchNumber(8)));
+        args.to_batch = L1BatchNumber(20);
+        assert_eq!(checkpoint.next_batch(&args), None);
+        args.to_batch = L1BatchNumber(10);
+        args.from_batch = L1BatchNumber(1);
+        assert_eq!(checkpoint.next_batch(&args), None);
+    }
+
+    #[test]
+    fn writing_csv_table() {
+        let csv = sample_table().to_csv();
+        let expected = format!(
+            "hash,block_number,index_in_block,from,to,contract_address,status\n\
+             {:?},3,0,{:?},,{:?},1\n",
+            ola_types::H256::repeat_byte(1),
+            ola_types::Address::repeat_byte(2),
+            ola_types::Address::repeat_byte(3)
+        );
+        assert_eq!(csv, expected);
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn writing_parquet_table() {
+        use arrow_array::{Array, StringArray, UInt64Array};
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        sample_table()
+            .write(dir.path(), ExportFormat::Parquet)
+            .unwrap();
+        let file = fs::File::open(dir.path().join("transactions.parquet")).unwrap();
+        let batches: Vec<_> = ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .build()
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.num_rows(), 1);
+        assert_eq!(batch.num_columns(), Table::TRANSACTIONS.len());
+
+        let block_numbers = batch.column(1).as_any().downcast_ref::<UInt64Array>();
+        assert_eq!(block_numbers.unwrap().value(0), 3);
+        let to = batch.column(4).as_any().downcast_ref::<StringArray>();
+        assert!(to.unwrap().is_null(0));
+        let from = batch.column(3).as_any().downcast_ref::<StringArray>();
+        let expected_from = format!("{:?}", ola_types::Address::repeat_byte(2));
+        assert_eq!(from.unwrap().value(0), expected_from);
+    }
+}
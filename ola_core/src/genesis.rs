@@ -1,4 +1,7 @@
-use ola_contracts::BaseSystemContracts;
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Context as _;
+use ola_contracts::{system_contracts_from_disk, BaseSystemContracts};
 use ola_dal::StorageProcessor;
 use ola_types::{
     block::{DeployedContract, L1BatchHeader, MiniblockHeader},
@@ -6,6 +9,8 @@ use ola_types::{
     get_full_code_key, get_system_context_init_logs,
     log::{LogQuery, StorageLog, StorageLogKind, Timestamp},
     protocol_version::{ProtocolVersion, ProtocolVersionId},
+    receipts_tree::ReceiptsTree,
+    snapshots::GenesisBundle,
     AccountTreeId, Address, L1BatchNumber, L2ChainId, MiniblockNumber, StorageKey, H256,
 };
 use ola_utils::{bytecode::hash_bytecode, h256_to_u256, misc::miniblock_hash, u256_to_h256};
@@ -21,23 +26,27 @@ pub struct GenesisParams {
     pub first_validator: Address,
     pub base_system_contracts: BaseSystemContracts,
     pub system_contracts: Vec<DeployedContract>,
+    /// Protocol version of the genesis batch; `system_contracts` must be the ones of this version.
+    pub protocol_version: ProtocolVersionId,
+    /// State of a forked chain to start from; if set, it replaces `system_contracts`.
+    pub fork_state: Option<GenesisBundle>,
 }
 
 pub async fn ensure_genesis_state(
     storage: &mut StorageProcessor<'_>,
     ola_chain_id: L2ChainId,
     genesis_params: &GenesisParams,
-) -> H256 {
+) -> anyhow::Result<H256> {
     let mut transaction = storage.start_transaction().await;
 
     // return if genesis block was already processed
-    if !transaction.blocks_dal().is_genesis_needed().await {
+    if !transaction.blocks_dal().is_genesis_needed().await? {
         olaos_logs::info!("genesis is not needed!");
         return transaction
             .blocks_dal()
             .get_l1_batch_state_root(L1BatchNumber(0))
-            .await
-            .expect("genesis block hash is empty");
+            .await?
+            .context("genesis block hash is empty");
     }
 
     olaos_logs::info!("running regenesis");
@@ -45,6 +54,8 @@ pub async fn ensure_genesis_state(
         first_validator,
         base_system_contracts,
         system_contracts,
+        protocol_version,
+        fork_state,
     } = genesis_params;
 
     let base_system_contracts_hashes = base_system_contracts.hashes();
@@ -55,15 +66,19 @@ pub async fn ensure_genesis_state(
         ola_chain_id,
         base_system_contracts,
         system_contracts,
+        *protocol_version,
+        fork_state.as_ref(),
     )
-    .await;
+    .await?;
     olaos_logs::info!("chain_schema_genesis is complete");
 
     let storage_logs =
-        crate::metadata_calculator::get_logs_for_l1_batch(&mut transaction, L1BatchNumber(0)).await;
-    let metadata = crate::metadata_calculator::MetadataCalculator::process_genesis_batch(
-        storage_logs.unwrap(),
-    );
+        crate::metadata_calculator::get_logs_for_l1_batch(&mut transaction, L1BatchNumber(0))
+            .await
+            .context("get_logs_for_l1_batch()")?
+            .context("genesis L1 batch has no storage logs")?;
+    let metadata =
+        crate::metadata_calculator::MetadataCalculator::process_genesis_batch(storage_logs);
     let genesis_root_hash = tree_key_to_h256(&metadata.root_hash);
     let rollup_last_leaf_index = metadata.rollup_last_leaf_index;
 
@@ -72,8 +87,11 @@ pub async fn ensure_genesis_state(
         genesis_root_hash,
         vec![],
         vec![],
+        ReceiptsTree::new(&[]).root(),
         base_system_contracts_hashes.entrypoint,
         base_system_contracts_hashes.default_aa,
+        *first_validator,
+        *protocol_version,
     );
 
     save_genesis_l1_batch_metadata(
@@ -82,7 +100,7 @@ pub async fn ensure_genesis_state(
         genesis_root_hash,
         rollup_last_leaf_index,
     )
-    .await;
+    .await?;
     olaos_logs::info!("operations_schema_genesis is complete");
 
     transaction.commit().await;
@@ -106,7 +124,47 @@ pub async fn ensure_genesis_state(
         base_system_contracts_hashes.default_aa
     );
 
-    genesis_root_hash
+    Ok(genesis_root_hash)
+}
+
+/// Checks that base system contracts shipped with the binary belong to a protocol version
+/// registered in Postgres, so that nodes can't silently run different system contracts.
+/// Skipped if system contracts are loaded from disk for development.
+pub async fn verify_base_system_contracts(
+    storage: &mut StorageProcessor<'_>,
+    base_system_contracts: &BaseSystemContracts,
+) -> anyhow::Result<()> {
+    if system_contracts_from_disk() {
+        olaos_logs::warn!(
+            "System contracts are loaded from disk; skipping their verification against protocol versions"
+        );
+        return Ok(());
+    }
+
+    let hashes = base_system_contracts.hashes();
+    let registered = storage
+        .protocol_versions_dal()
+        .all_base_system_contracts_hashes()
+        .await?;
+    let (version, _) = registered
+        .iter()
+        .find(|(_, registered_hashes)| *registered_hashes == hashes)
+        .with_context(|| {
+            format!(
+                "base system contracts of the binary ({hashes:?}) don't belong to any registered \
+                 protocol version"
+            )
+        })?;
+    match registered.first() {
+        Some((latest_version, _)) if latest_version != version => olaos_logs::warn!(
+            "Base system contracts of the binary belong to protocol version {version:?}, \
+             while the latest registered one is {latest_version:?}"
+        ),
+        _ => olaos_logs::info!(
+            "Base system contracts of the binary belong to protocol version {version:?}"
+        ),
+    }
+    Ok(())
 }
 
 pub(crate) async fn create_genesis_l1_batch(
@@ -115,9 +173,11 @@ pub(crate) async fn create_genesis_l1_batch(
     chain_id: L2ChainId,
     base_system_contracts: &BaseSystemContracts,
     system_contracts: &[DeployedContract],
-) {
+    protocol_version: ProtocolVersionId,
+    fork_state: Option<&GenesisBundle>,
+) -> anyhow::Result<()> {
     let version = ProtocolVersion {
-        id: ProtocolVersionId::latest(),
+        id: protocol_version,
         timestamp: 0,
         base_system_contracts_hashes: base_system_contracts.hashes(),
         tx: None,
@@ -128,7 +188,7 @@ pub(crate) async fn create_genesis_l1_batch(
         0,
         first_validator_address,
         base_system_contracts.hashes(),
-        ProtocolVersionId::latest(),
+        protocol_version,
     );
     genesis_l1_batch_header.is_finished = true;
 
@@ -139,7 +199,7 @@ pub(crate) async fn create_genesis_l1_batch(
         l1_tx_count: 0,
         l2_tx_count: 0,
         base_system_contracts_hashes: base_system_contracts.hashes(),
-        protocol_version: Some(ProtocolVersionId::latest()),
+        protocol_version: Some(protocol_version),
     };
 
     let mut transaction = storage.start_transaction().await;
@@ -147,24 +207,28 @@ pub(crate) async fn create_genesis_l1_batch(
     transaction
         .protocol_versions_dal()
         .save_protocol_version(version)
-        .await;
+        .await?;
     transaction
         .blocks_dal()
         .insert_l1_batch(&genesis_l1_batch_header, &[])
-        .await;
+        .await?;
     transaction
         .blocks_dal()
         .insert_miniblock(&genesis_miniblock_header)
-        .await;
+        .await?;
     transaction
         .blocks_dal()
         .mark_miniblocks_as_executed_in_l1_batch(L1BatchNumber(0))
-        .await;
+        .await?;
 
     // insert_base_system_contracts_to_factory_deps(&mut transaction, base_system_contracts).await;
-    insert_system_contracts(&mut transaction, system_contracts, chain_id).await;
+    match fork_state {
+        Some(bundle) => insert_fork_state(&mut transaction, bundle, chain_id).await?,
+        None => insert_system_contracts(&mut transaction, system_contracts, chain_id).await?,
+    }
 
     transaction.commit().await;
+    Ok(())
 }
 
 pub(crate) async fn save_genesis_l1_batch_metadata(
@@ -172,7 +236,7 @@ pub(crate) async fn save_genesis_l1_batch_metadata(
     commitment: &L1BatchCommitment,
     genesis_root_hash: H256,
     rollup_last_leaf_index: u64,
-) {
+) -> anyhow::Result<()> {
     let commitment_hash = commitment.hash();
 
     let metadata = L1BatchMetadata {
@@ -195,13 +259,14 @@ pub(crate) async fn save_genesis_l1_batch_metadata(
     storage
         .blocks_dal()
         .save_genesis_l1_batch_metadata(&metadata)
-        .await;
+        .await?;
+    Ok(())
 }
 
 async fn insert_base_system_contracts_to_factory_deps(
     storage: &mut StorageProcessor<'_>,
     contracts: &BaseSystemContracts,
-) {
+) -> anyhow::Result<()> {
     let factory_deps = [&contracts.entrypoint, &contracts.default_aa]
         .iter()
         .map(|c| (c.hash, c.code.clone()))
@@ -210,14 +275,15 @@ async fn insert_base_system_contracts_to_factory_deps(
     storage
         .storage_dal()
         .insert_factory_deps(MiniblockNumber(0), &factory_deps)
-        .await;
+        .await?;
+    Ok(())
 }
 
 async fn insert_system_contracts(
     storage: &mut StorageProcessor<'_>,
     contracts: &[DeployedContract],
     chain_id: L2ChainId,
-) {
+) -> anyhow::Result<()> {
     let system_context_init_logs = (H256::default(), get_system_context_init_logs(chain_id));
 
     let storage_logs: Vec<(H256, Vec<StorageLog>)> = contracts
@@ -238,13 +304,61 @@ async fn insert_system_contracts(
         })
         .chain(Some(system_context_init_logs))
         .collect();
+    let factory_deps = contracts
+        .iter()
+        .map(|c| (hash_bytecode(&c.raw), c.raw.clone()))
+        .collect();
+    insert_genesis_state(storage, storage_logs, factory_deps).await
+}
+
+/// Recreates the state of a forked chain. The system context is reinitialized, so that the fork
+/// uses its own chain ID.
+async fn insert_fork_state(
+    storage: &mut StorageProcessor<'_>,
+    bundle: &GenesisBundle,
+    chain_id: L2ChainId,
+) -> anyhow::Result<()> {
+    olaos_logs::info!(
+        "Creating genesis from the state of L1 batch #{} (miniblock #{}) of the forked chain: \
+         {} storage entries, {} factory deps",
+        bundle.source_l1_batch_number,
+        bundle.source_miniblock_number,
+        bundle.storage.len(),
+        bundle.factory_deps.len()
+    );
+    let system_context_init_logs = get_system_context_init_logs(chain_id);
+    let system_context_keys: HashSet<_> =
+        system_context_init_logs.iter().map(|log| log.key).collect();
+    let forked_logs = bundle
+        .storage
+        .iter()
+        .filter_map(|entry| {
+            let key = StorageKey::new(AccountTreeId::new(entry.address), entry.key);
+            (!system_context_keys.contains(&key))
+                .then(|| StorageLog::new_write_log(key, entry.value))
+        })
+        .chain(system_context_init_logs)
+        .collect();
+    let storage_logs = vec![(H256::default(), forked_logs)];
+    let factory_deps = bundle
+        .factory_deps
+        .iter()
+        .map(|dep| (dep.bytecode_hash, dep.bytecode.0.clone()))
+        .collect();
+    insert_genesis_state(storage, storage_logs, factory_deps).await
+}
 
+async fn insert_genesis_state(
+    storage: &mut StorageProcessor<'_>,
+    storage_logs: Vec<(H256, Vec<StorageLog>)>,
+    factory_deps: HashMap<H256, Vec<u8>>,
+) -> anyhow::Result<()> {
     let mut transaction = storage.start_transaction().await;
 
     transaction
         .storage_logs_dal()
         .insert_storage_logs(MiniblockNumber(0), &storage_logs)
-        .await;
+        .await?;
 
     // we don't produce proof for the genesis block,
     // but we still need to populate the table
@@ -284,7 +398,7 @@ async fn insert_system_contracts(
     transaction
         .storage_logs_dedup_dal()
         .insert_protective_reads(L1BatchNumber(0), &protective_reads)
-        .await;
+        .await?;
 
     let written_storage_keys: Vec<_> = deduplicated_writes
         .iter()
@@ -293,21 +407,18 @@ async fn insert_system_contracts(
     transaction
         .storage_logs_dedup_dal()
         .insert_initial_writes(L1BatchNumber(0), &written_storage_keys)
-        .await;
+        .await?;
 
     transaction
         .storage_dal()
         .apply_storage_logs(&storage_logs)
-        .await;
+        .await?;
 
-    let factory_deps = contracts
-        .iter()
-        .map(|c| (hash_bytecode(&c.raw), c.raw.clone()))
-        .collect();
     transaction
         .storage_dal()
         .insert_factory_deps(MiniblockNumber(0), &factory_deps)
-        .await;
+        .await?;
 
     transaction.commit().await;
+    Ok(())
 }
@@ -0,0 +1,324 @@
+//! Export of the chain state into a genesis bundle for forked chains (`ola_node export-genesis`).
+//!
+//! The bundle is a JSON file with a [`GenesisBundle`]: all non-zero storage slots, factory deps
+//! and account nonces as of a sealed L1 batch. Storage is read in hashed key chunks in the same
+//! way as when creating snapshots and written to the file chunk by chunk, so that the export
+//! doesn't hold the entire state in memory. All lists are sorted, so exporting the same state
+//! always produces the same file. A node started with `network.fork_genesis_path` pointing to the bundle
+//! creates its genesis L1 batch from the bundle state instead of the system contracts.
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufReader, BufWriter, Write as _},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use anyhow::Context as _;
+use ola_dal::connection::ConnectionPool;
+use ola_types::{
+    get_nonce_key,
+    snapshots::{
+        uniform_hashed_keys_chunk, GenesisAccountNonce, GenesisBundle, GenesisFactoryDep,
+        GenesisStorageEntry,
+    },
+    Bytes, L1BatchNumber, MiniblockNumber, Nonce,
+};
+use ola_utils::h256_to_u32;
+use serde::Serialize;
+
+/// Arguments of `ola_node export-genesis`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportGenesisArgs {
+    pub l1_batch_number: L1BatchNumber,
+    pub output: PathBuf,
+    pub chunk_count: u64,
+}
+
+impl ExportGenesisArgs {
+    pub const USAGE: &'static str = "usage: ola_node export-genesis --l1-batch <number> \
+        [--output <file>] [--chunks <count>]";
+    const DEFAULT_CHUNK_COUNT: u64 = 64;
+
+    /// Parses arguments following the `export-genesis` subcommand.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Self> {
+        let mut l1_batch_number = None;
+        let mut output = PathBuf::from("genesis_bundle.json");
+        let mut chunk_count = Self::DEFAULT_CHUNK_COUNT;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .with_context(|| format!("missing value for `{arg}`; {}", Self::USAGE))?;
+            match arg.as_str() {
+                "--l1-batch" => l1_batch_number = Some(L1BatchNumber(value.parse()?)),
+                "--output" => output = PathBuf::from(value),
+                "--chunks" => chunk_count = value.parse()?,
+                _ => anyhow::bail!("unknown argument `{arg}`; {}", Self::USAGE),
+            }
+        }
+
+        let l1_batch_number = l1_batch_number.context(Self::USAGE)?;
+        anyhow::ensure!(chunk_count > 0, "--chunks must be positive");
+        Ok(Self {
+            l1_batch_number,
+            output,
+            chunk_count,
+        })
+    }
+}
+
+/// Exports the state as of the requested sealed L1 batch into a genesis bundle file.
+pub async fn run_export_genesis(
+    pool: &ConnectionPool,
+    args: &ExportGenesisArgs,
+) -> anyhow::Result<()> {
+    let l1_batch_number = args.l1_batch_number;
+    let mut storage = pool.access_storage_tagged("export_genesis").await;
+    let sealed_l1_batch = storage.blocks_dal().get_sealed_l1_batch_number().await?;
+    anyhow::ensure!(
+        l1_batch_number <= sealed_l1_batch,
+        "L1 batch #{l1_batch_number} is not sealed; the last sealed L1 batch is #{sealed_l1_batch}"
+    );
+    let (_, miniblock_number) = storage
+        .blocks_dal()
+        .get_miniblock_range_of_l1_batch(l1_batch_number)
+        .await?
+        .with_context(|| format!("L1 batch #{l1_batch_number} has no miniblocks"))?;
+
+    let accounts = storage
+        .transactions_dal()
+        .get_initiator_addresses(miniblock_number)
+        .await?;
+    let mut nonce_keys: HashMap<_, _> = accounts
+        .into_iter()
+        .map(|address| {
+            let key = get_nonce_key(&address);
+            ((*key.address(), *key.key()), address)
+        })
+        .collect();
+
+    // Storage is written chunk by chunk, so that only a single chunk is held in memory.
+    let started_at = Instant::now();
+    let mut writer = BundleWriter::new(&args.output, l1_batch_number, miniblock_number)?;
+    let mut nonces = vec![];
+    for chunk_id in 0..args.chunk_count {
+        let key_range = uniform_hashed_keys_chunk(chunk_id, args.chunk_count);
+        let entries = storage
+            .storage_logs_dal()
+            .get_genesis_storage_entries(miniblock_number, key_range)
+            .await?;
+        nonces.extend(entries.iter().filter_map(|entry| {
+            let address = nonce_keys.remove(&(entry.address, entry.key))?;
+            Some(GenesisAccountNonce {
+                address,
+                nonce: Nonce(h256_to_u32(entry.value)),
+            })
+        }));
+        writer.write_storage_entries(&entries)?;
+        olaos_logs::info!(
+            "Exported storage chunk {}/{}, {} storage entries in total, elapsed {:?}",
+            chunk_id + 1,
+            args.chunk_count,
+            writer.storage_entry_count,
+            started_at.elapsed()
+        );
+    }
+    nonces.sort_unstable_by_key(|nonce| nonce.address);
+
+    let mut factory_deps: Vec<_> = storage
+        .storage_dal()
+        .get_snapshot_factory_deps(miniblock_number)
+        .await?
+        .into_iter()
+        .map(|dep| GenesisFactoryDep {
+            bytecode_hash: dep.bytecode_hash,
+            bytecode: Bytes(dep.bytecode),
+        })
+        .collect();
+    drop(storage);
+    factory_deps.sort_unstable_by_key(|dep| dep.bytecode_hash);
+
+    let storage_entry_count = writer.storage_entry_count;
+    writer.finish(&factory_deps, &nonces)?;
+    olaos_logs::info!(
+        "Exported genesis bundle for L1 batch #{l1_batch_number} (miniblock #{miniblock_number}) \
+         with {storage_entry_count} storage entries, {} factory deps and {} nonces to {:?} in {:?}",
+        factory_deps.len(),
+        nonces.len(),
+        args.output,
+        started_at.elapsed()
+    );
+    Ok(())
+}
+
+/// Writes a [`GenesisBundle`] incrementally, so that storage entries don't need to be held
+/// in memory. The bundle is written to a temporary file renamed once complete, so that
+/// an interrupted export doesn't leave a truncated bundle behind.
+struct BundleWriter {
+    path: PathBuf,
+    tmp_path: PathBuf,
+    writer: BufWriter<fs::File>,
+    storage_entry_count: usize,
+}
+
+impl BundleWriter {
+    fn new(
+        path: &Path,
+        l1_batch_number: L1BatchNumber,
+        miniblock_number: MiniblockNumber,
+    ) -> anyhow::Result<Self> {
+        let tmp_path = path.with_extension("tmp");
+        let file =
+            fs::File::create(&tmp_path).with_context(|| format!("failed creating {tmp_path:?}"))?;
+        let mut this = Self {
+            path: path.to_owned(),
+            tmp_path,
+            writer: BufWriter::new(file),
+            storage_entry_count: 0,
+        };
+        // Field names must match the serialization of `GenesisBundle`.
+        this.write_raw("{\"sourceL1BatchNumber\":")?;
+        this.write_value(&l1_batch_number)?;
+        this.write_raw(",\"sourceMiniblockNumber\":")?;
+        this.write_value(&miniblock_number)?;
+        this.write_raw(",\"storage\":[")?;
+        Ok(this)
+    }
+
+    fn write_raw(&mut self, raw: &str) -> anyhow::Result<()> {
+        self.writer
+            .write_all(raw.as_bytes())
+            .with_context(|| format!("failed writing genesis bundle to {:?}", self.tmp_path))
+    }
+
+    fn write_value(&mut self, value: &impl Serialize) -> anyhow::Result<()> {
+        serde_json::to_writer(&mut self.writer, value)
+            .with_context(|| format!("failed writing genesis bundle to {:?}", self.tmp_path))
+    }
+
+    /// Appends storage entries. Entries must be written in the order of their hashed keys.
+    fn write_storage_entries(&mut self, entries: &[GenesisStorageEntry]) -> anyhow::Result<()> {
+        for entry in entries {
+            if self.storage_entry_count > 0 {
+                self.write_raw(",")?;
+            }
+            self.write_value(entry)?;
+            self.storage_entry_count += 1;
+        }
+        Ok(())
+    }
+
+    fn finish(
+        mut self,
+        factory_deps: &[GenesisFactoryDep],
+        nonces: &[GenesisAccountNonce],
+    ) -> anyhow::Result<()> {
+        self.write_raw("],\"factoryDeps\":")?;
+        self.write_value(&factory_deps)?;
+        self.write_raw(",\"nonces\":")?;
+        self.write_value(&nonces)?;
+        self.write_raw("}")?;
+        self.writer
+            .flush()
+            .with_context(|| format!("failed flushing {:?}", self.tmp_path))?;
+        fs::rename(&self.tmp_path, &self.path)
+            .with_context(|| format!("failed renaming {:?}", self.tmp_path))?;
+        Ok(())
+    }
+}
+
+pub fn load_genesis_bundle(path: &Path) -> anyhow::Result<GenesisBundle> {
+    let file = fs::File::open(path).with_context(|| format!("failed opening {path:?}"))?;
+    serde_json::from_reader(BufReader::new(file))
+        .with_context(|| format!("failed parsing genesis bundle {path:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use ola_types::{Address, H256};
+
+    use super::*;
+
+    fn args(args: &[&str]) -> anyhow::Result<ExportGenesisArgs> {
+        ExportGenesisArgs::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parsing_export_genesis_args() {
+        assert_eq!(
+            args(&["--l1-batch", "10"]).unwrap(),
+            ExportGenesisArgs {
+                l1_batch_number: L1BatchNumber(10),
+                output: PathBuf::from("genesis_bundle.json"),
+                chunk_count: ExportGenesisArgs::DEFAULT_CHUNK_COUNT,
+            }
+        );
+        let parsed = args(&[
+            "--output",
+            "/tmp/fork.json",
+            "--l1-batch",
+            "3",
+            "--chunks",
+            "2",
+        ])
+        .unwrap();
+        assert_eq!(parsed.output, PathBuf::from("/tmp/fork.json"));
+        assert_eq!(parsed.chunk_count, 2);
+
+        assert!(args(&[]).is_err());
+        assert!(args(&["--l1-batch", "10", "--chunks", "0"]).is_err());
+        assert!(args(&["--l1-batch"]).is_err());
+        assert!(args(&["--batch", "10"]).is_err());
+    }
+
+    fn write_bundle(path: &Path, bundle: &GenesisBundle) {
+        let mut writer = BundleWriter::new(
+            path,
+            bundle.source_l1_batch_number,
+            bundle.source_miniblock_number,
+        )
+        .unwrap();
+        // Emulate storage exported in several chunks, including an empty one.
+        let (first_chunk, second_chunk) = bundle.storage.split_at(1);
+        writer.write_storage_entries(first_chunk).unwrap();
+        writer.write_storage_entries(&[]).unwrap();
+        writer.write_storage_entries(second_chunk).unwrap();
+        assert_eq!(writer.storage_entry_count, bundle.storage.len());
+        writer.finish(&bundle.factory_deps, &bundle.nonces).unwrap();
+    }
+
+    #[test]
+    fn bundle_file_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("bundle.json");
+        let storage_entry = |byte| GenesisStorageEntry {
+            address: Address::repeat_byte(byte),
+            key: H256::repeat_byte(2),
+            value: H256::from_low_u64_be(3),
+        };
+        let bundle = GenesisBundle {
+            source_l1_batch_number: L1BatchNumber(5),
+            source_miniblock_number: MiniblockNumber(42),
+            storage: vec![storage_entry(1), storage_entry(5), storage_entry(6)],
+            factory_deps: vec![GenesisFactoryDep {
+                bytecode_hash: H256::repeat_byte(4),
+                bytecode: Bytes(vec![0; 16]),
+            }],
+            nonces: vec![GenesisAccountNonce {
+                address: Address::repeat_byte(1),
+                nonce: Nonce(7),
+            }],
+        };
+
+        write_bundle(&path, &bundle);
+        let contents = fs::read(&path).unwrap();
+        assert_eq!(load_genesis_bundle(&path).unwrap(), bundle);
+        // The same bundle is always serialized in the same way.
+        write_bundle(&path, &bundle);
+        assert_eq!(fs::read(&path).unwrap(), contents);
+        assert!(!path.with_extension("tmp").exists());
+    }
+}
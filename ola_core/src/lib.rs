@@ -1,53 +1,74 @@
-use std::{sync::Arc, time::Instant};
+use std::{path::Path, sync::Arc, time::Instant};
 
 use anyhow::{Context, Ok};
 use api_server::{
-    execution_sandbox::{VmConcurrencyBarrier, VmConcurrencyLimiter},
-    healthcheck::HealthCheckHandle,
-    tx_sender::{ApiContracts, TxSender, TxSenderBuilder, TxSenderConfig},
-    web3::{self, state::InternalApiConfig, Namespace},
+    execution_sandbox::{VmConcurrencyAutotuneConfig, VmConcurrencyBarrier, VmConcurrencyLimiter},
+    tx_sender::{
+        screening::CalldataScreener, simulations::SimulationsPruner, ApiContracts, TxSender,
+        TxSenderBuilder, TxSenderConfig,
+    },
+    web3::{
+        self,
+        backend::{access_log::AccessLog, method_filter::MethodFilter},
+        call_outputs::CallOutputStore,
+        ipc::IpcEndpoint,
+        state::InternalApiConfig,
+        Namespace,
+    },
 };
 use futures::channel::oneshot;
+use metadata_calculator::AsyncTreeReader;
+use node::NodePools;
 use ola_config::{
-    api::{
-        load_api_config, load_healthcheck_config, load_web3_json_rpc_config, ApiConfig,
-        Web3JsonRpcConfig,
-    },
-    chain::{
-        load_mempool_config, load_operation_manager_config, MempoolConfig, OperationsManagerConfig,
-    },
-    contracts::{load_contracts_config, ContractsConfig},
-    database::{load_db_config, DBConfig, MerkleTreeMode},
+    api::{ApiConfig, Web3JsonRpcConfig},
+    chain::{MempoolConfig, OperationsManagerConfig},
+    contracts::ContractsConfig,
+    database::{DBConfig, MerkleTreeMode},
+    dump::{ConfigDump, ConfigValueSource},
     eth_sender::ETHSenderConfig,
-    object_store::load_object_store_config,
-    offchain_verifier::load_offchain_verifier_config,
-    proof_data_handler::load_proof_data_handler_config,
-    sequencer::{load_network_config, load_sequencer_config, NetworkConfig, SequencerConfig},
+    node::Config,
+    sequencer::{NetworkConfig, SequencerConfig},
+    validation::{ConfigValidationError, ConfigValidator},
 };
 use ola_contracts::BaseSystemContracts;
-use ola_dal::{
-    connection::{ConnectionPool, DbVariant},
-    healthcheck::ConnectionPoolHealthCheck,
-    StorageProcessor,
-};
+use ola_dal::{connection::ConnectionPool, StorageProcessor};
 use ola_state::postgres::PostgresStorageCaches;
 use ola_types::{
+    network::Network, protocol_version::ProtocolVersionId,
     system_contracts::get_system_smart_contracts, tx::primitives::PackedEthSignature, L2ChainId,
 };
 use olaos_health_check::{CheckHealth, ReactiveHealthCheck};
 use olaos_object_store::{ObjectStore, ObjectStoreFactory};
 use olaos_queued_job_processor::JobProcessor;
 use sequencer::{
-    create_sequencer, io::MiniblockSealer, mempool_actor::MempoolFetcher, types::MempoolGuard,
+    create_sequencer,
+    io::MiniblockSealer,
+    leader_election::{LeaderElection, LeaseFence},
+    mempool_actor::MempoolFetcher,
+    types::{block_builder_policy, MempoolGuard, MiniblockSealEvents, PendingStateGuard},
+    watchdog::SequencerWatchdog,
 };
+use shutdown::{ShutdownPhase, TaskLabel};
 use tokio::{sync::watch, task::JoinHandle};
+use utils::blocking_pool::BlockingPool;
 use witness_input_producer::WitnessInputProducer;
 
 pub mod api_server;
+pub mod archive;
+pub mod audit;
+pub mod backfill;
+pub mod build_info;
+pub mod consistency_checker;
+pub mod export;
 pub mod genesis;
+pub mod genesis_bundle;
 pub mod metadata_calculator;
+pub mod node;
 pub mod proof_data_handler;
+pub mod proof_smoke_test;
+pub mod replay_bundle;
 pub mod sequencer;
+pub mod shutdown;
 pub mod tests;
 pub mod utils;
 pub mod witness_input_producer;
@@ -61,230 +82,265 @@ pub enum Component {
     Tree,
     WitnessInputProducer,
     ProofDataHandler,
+    TransactionArchiver,
+    ConsistencyChecker,
+    /// Proves and verifies each sealed L1 batch in-process. Intended for devnets only.
+    ProofSmokeTest,
 }
 
-pub async fn initialize_components(
-    components: Vec<Component>,
-) -> anyhow::Result<(
-    Vec<JoinHandle<anyhow::Result<()>>>,
-    watch::Sender<bool>,
-    HealthCheckHandle,
-)> {
-    olaos_logs::info!("Starting the components: {components:?}");
-    let db_config = load_db_config().expect("failed to load database config");
-    let connection_pool = ConnectionPool::builder(DbVariant::Master).build().await;
-    let replica_connection_pool = ConnectionPool::builder(DbVariant::Replica)
-        .set_statement_timeout(db_config.statement_timeout())
-        .build()
-        .await;
-
-    let mut healthchecks: Vec<Box<dyn CheckHealth>> = Vec::new();
-
-    let contracts_config = load_contracts_config().expect("failed to load contract config");
-
-    let (stop_sender, stop_receiver) = watch::channel(false);
-
-    let mut task_futures: Vec<JoinHandle<anyhow::Result<()>>> = vec![];
-
-    if components.contains(&Component::HttpApi) || components.contains(&Component::PubsubApi) {
-        let api_config = load_api_config().expect("failed to load api config");
-        let sequencer_config = load_sequencer_config().expect("failed to load sequencer config");
-        let network_config = load_network_config().expect("failed to load network config");
-        let tx_sender_config = TxSenderConfig::new(&sequencer_config, &api_config.web3_json_rpc);
-        let internal_api_config = InternalApiConfig::new(
-            &network_config,
-            &api_config.web3_json_rpc,
-            &contracts_config,
-        );
-
-        let mut storage_caches = None;
-
-        if components.contains(&Component::HttpApi) {
-            storage_caches = Some(build_storage_caches(
-                &replica_connection_pool,
-                &mut task_futures,
-            ));
-
-            let started_at = Instant::now();
-            olaos_logs::info!("initializing HTTP API");
-            let (futures, health_check) = run_http_api(
-                &api_config,
-                &sequencer_config,
-                &internal_api_config,
-                &tx_sender_config,
-                connection_pool.clone(),
-                replica_connection_pool.clone(),
-                stop_receiver.clone(),
-                storage_caches.clone().unwrap(),
-            )
-            .await;
-            task_futures.extend(futures);
-            healthchecks.push(Box::new(health_check));
-            olaos_logs::info!("initialized HTTP API in {:?}", started_at.elapsed());
-        }
-
-        if components.contains(&Component::PubsubApi) {
-            let started_at = Instant::now();
-            olaos_logs::info!("initializing PubsubApi API");
-            let offchain_verifier_config =
-                load_offchain_verifier_config().expect("failed to load offchain verifier config");
-            let max_concurrency = api_config.web3_json_rpc.vm_concurrency_limit();
-            let (_, vm_barrier) = VmConcurrencyLimiter::new(max_concurrency);
-
-            let server_handles = web3::ApiBuilder::pubsub_backend(
-                internal_api_config,
-                replica_connection_pool.clone(),
-            )
-            .ws(offchain_verifier_config.port)
-            .with_filters_limit(api_config.web3_json_rpc.filters_limit())
-            .with_subscriptions_limit(api_config.web3_json_rpc.subscriptions_limit())
-            .with_batch_request_size_limit(api_config.web3_json_rpc.max_batch_request_size())
-            .with_response_body_size_limit(api_config.web3_json_rpc.max_response_body_size())
-            .with_polling_interval(api_config.web3_json_rpc.pubsub_interval())
-            .with_threads(api_config.web3_json_rpc.ws_server_threads())
-            .with_vm_barrier(vm_barrier)
-            .enable_api_namespaces(vec![Namespace::Pubsub])
-            .build_ws_new(stop_receiver.clone())
-            .await
-            .context("run_pubsub_api")?;
-
-            task_futures.extend(server_handles.tasks);
-            healthchecks.push(Box::new(server_handles.health_check));
-            olaos_logs::info!("initialized PubsubApi API in {:?}", started_at.elapsed());
+impl Component {
+    /// Process exit code used when a task of this component terminates unexpectedly.
+    /// Components of the same class share the code.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::HttpApi | Self::WsApi | Self::PubsubApi => 10,
+            Self::Sequencer => 11,
+            Self::Tree => 12,
+            Self::WitnessInputProducer => 13,
+            Self::ProofDataHandler => 14,
+            Self::TransactionArchiver => 15,
+            Self::ConsistencyChecker => 16,
+            Self::ProofSmokeTest => 17,
         }
     }
 
-    let object_store_config =
-        load_object_store_config().expect("failed to load object store config");
-    let store_factory = ObjectStoreFactory::new(object_store_config);
-
-    if components.contains(&Component::Sequencer) {
-        let started_at = Instant::now();
-        olaos_logs::info!("initializing Sequencer");
-        let sequencer_config = load_sequencer_config().expect("failed to load sequencer config");
-        let mempool_config = load_mempool_config().expect("failed to load mempool config");
-        add_sequencer_to_task_futures(
-            &mut task_futures,
-            &contracts_config,
-            sequencer_config,
-            &db_config,
-            &mempool_config,
-            &store_factory,
-            stop_receiver.clone(),
-        )
-        .await;
-        olaos_logs::info!("initialized Sequencer in {:?}", started_at.elapsed());
+    /// Phase of the ordered shutdown in which the component is stopped.
+    pub fn shutdown_phase(self) -> ShutdownPhase {
+        match self {
+            Self::HttpApi | Self::WsApi | Self::PubsubApi | Self::ProofDataHandler => {
+                ShutdownPhase::Ingress
+            }
+            Self::Sequencer => ShutdownPhase::Sequencer,
+            Self::Tree
+            | Self::WitnessInputProducer
+            | Self::TransactionArchiver
+            | Self::ConsistencyChecker
+            | Self::ProofSmokeTest => ShutdownPhase::Tree,
+        }
     }
 
-    if components.contains(&Component::Tree) {
-        let started_at = Instant::now();
-        olaos_logs::info!("initializing Merkle Tree");
-        add_trees_to_task_futures(
-            &mut task_futures,
-            &mut healthchecks,
-            &components,
-            &store_factory,
-            stop_receiver.clone(),
+    /// Whether the component can run on a read-only node. On such nodes, the tree is only read.
+    pub fn is_read_only(self) -> bool {
+        matches!(
+            self,
+            Self::HttpApi | Self::WsApi | Self::PubsubApi | Self::Tree
         )
-        .await;
-        olaos_logs::info!("initialized Merkle Tree in {:?}", started_at.elapsed());
     }
+}
 
+/// Validates the node configuration for the specified set of components, reporting all
+/// violations at once.
+pub fn validate_config(
+    config: &Config,
+    components: &[Component],
+) -> Result<(), ConfigValidationError> {
+    let mut validator = ConfigValidator::new();
+    config.validate_into(&mut validator);
     if components.contains(&Component::WitnessInputProducer) {
-        let started_at = Instant::now();
-        olaos_logs::info!("initializing Merkle Tree");
-        let pool_builder = ConnectionPool::singleton(DbVariant::Master);
-        let connection_pool = pool_builder.build().await;
-        let network_config = load_network_config().expect("failed to load network config");
-        let _ = add_witness_input_producer_to_task_futures(
-            &mut task_futures,
-            &connection_pool,
-            &store_factory,
-            L2ChainId(network_config.ola_network_id),
-            stop_receiver.clone(),
-        )
-        .await
-        .context("add_witness_input_producer_to_task_futures");
-        olaos_logs::info!(
-            "initialized WitnessInputProducer in {:?}",
-            started_at.elapsed()
+        validator.check(
+            config.db.merkle_tree.mode == MerkleTreeMode::Full,
+            "merkle_tree.mode",
+            "must be `Full` if WitnessInputProducer is enabled",
         );
     }
-
-    if components.contains(&Component::ProofDataHandler) {
-        let proof_data_handler_config =
-            load_proof_data_handler_config().expect("failed to load proof data handler config");
-        task_futures.push(tokio::spawn(proof_data_handler::run_server(
-            proof_data_handler_config,
-            store_factory.create_store().await,
-            connection_pool.clone(),
-            stop_receiver.clone(),
-        )));
+    if components.contains(&Component::TransactionArchiver) {
+        validator.check(
+            config.db.tx_archive_retention_l1_batches.is_some(),
+            "database.tx_archive_retention_l1_batches",
+            "must be set if TransactionArchiver is enabled",
+        );
     }
+    if components.contains(&Component::ProofSmokeTest) {
+        validator.check(
+            config.network.network != Network::Mainnet,
+            "proof_data_handler.proof_smoke_test_enabled",
+            "ProofSmokeTest must not run on mainnet",
+        );
+    }
+    if config.api.web3_json_rpc.read_only() {
+        validator.check(
+            components.iter().all(|component| component.is_read_only()),
+            "web3_json_rpc.read_only",
+            format!("read-only node cannot run components {components:?}"),
+        );
+    }
+    validator.finish()
+}
 
-    healthchecks.push(Box::new(ConnectionPoolHealthCheck::new(
-        replica_connection_pool,
-    )));
+/// Dumps the effective node configuration with secrets redacted. Besides [`Config`] values,
+/// includes database URLs resolved by the DAL.
+pub fn dump_config(config: &Config) -> anyhow::Result<ConfigDump> {
+    let mut dump = config.dump().context("failed to dump configuration")?;
+    for (path, url) in [
+        ("database.master_url", ola_dal::get_master_database_url()),
+        ("database.replica_url", ola_dal::get_replica_database_url()),
+        ("database.prover_url", ola_dal::get_prover_database_url()),
+    ] {
+        dump.push(path.to_owned(), url.into(), ConfigValueSource::Default);
+    }
+    Ok(dump)
+}
 
-    let healtcheck_api_config =
-        load_healthcheck_config().expect("failed to load health_check config");
-    let health_check_handle =
-        HealthCheckHandle::spawn_server(healtcheck_api_config.bind_addr(), healthchecks);
-    Ok((task_futures, stop_sender, health_check_handle))
+/// Attributes the tasks spawned since the last call to `label`.
+fn label_new_tasks(
+    task_labels: &mut Vec<TaskLabel>,
+    task_futures: &[JoinHandle<anyhow::Result<()>>],
+    label: impl Into<TaskLabel>,
+) {
+    let new_task_count = task_futures.len() - task_labels.len();
+    task_labels.extend(std::iter::repeat(label.into()).take(new_task_count));
 }
 
 async fn run_http_api(
     api_config: &ApiConfig,
     sequencer_config: &SequencerConfig,
+    db_config: &DBConfig,
     internal_api: &InternalApiConfig,
     tx_sender_config: &TxSenderConfig,
     master_connection_pool: ConnectionPool,
     replica_connection_pool: ConnectionPool,
     stop_receiver: watch::Receiver<bool>,
     storage_caches: PostgresStorageCaches,
+    mempool: Option<MempoolGuard>,
+    pending_state: Option<PendingStateGuard>,
+    seal_events: Option<MiniblockSealEvents>,
+    tree_reader: Option<watch::Receiver<Option<AsyncTreeReader>>>,
+    archive_store: Option<Arc<dyn ObjectStore>>,
+    call_output_store: Option<Arc<CallOutputStore>>,
+    method_filter: Arc<MethodFilter>,
+    access_log: Option<Arc<AccessLog>>,
+    components: &[Component],
 ) -> (Vec<JoinHandle<anyhow::Result<()>>>, ReactiveHealthCheck) {
+    // Simulations are only recorded for transactions inserted into the mempool by this node.
+    let simulations_pruner = (!api_config.web3_json_rpc.read_only()).then(|| {
+        SimulationsPruner::new(
+            master_connection_pool.clone(),
+            api_config.web3_json_rpc.simulation_retention(),
+        )
+    });
     let (tx_sender, vm_barrier) = build_tx_sender(
         tx_sender_config,
         &api_config.web3_json_rpc,
         sequencer_config,
+        db_config,
         master_connection_pool,
         replica_connection_pool.clone(),
         storage_caches,
+        seal_events,
     )
     .await;
 
-    let namespaces = Namespace::HTTP.to_vec();
-
-    web3::ApiBuilder::http_backend(internal_api.clone(), replica_connection_pool)
-        .http(api_config.web3_json_rpc.http_port)
-        .with_filters_limit(api_config.web3_json_rpc.filters_limit())
-        .with_threads(api_config.web3_json_rpc.http_server_threads())
-        .with_batch_request_size_limit(api_config.web3_json_rpc.max_batch_request_size())
-        .with_response_body_size_limit(api_config.web3_json_rpc.max_response_body_size())
-        .with_tx_sender(tx_sender, vm_barrier)
-        .enable_api_namespaces(namespaces)
-        .build(stop_receiver.clone())
-        .await
+    let mut namespaces = Namespace::HTTP.to_vec();
+    if api_config.web3_json_rpc.admin_namespace_enabled() {
+        namespaces.extend([Namespace::Admin, Namespace::Txpool]);
+    }
+    let vm_concurrency_autotuner = tx_sender.vm_concurrency_autotuner();
+    let call_storage_pool = tx_sender.call_storage_pool();
+    let calldata_screener = tx_sender.calldata_screener();
+    let tree_read_pool = tree_reader.as_ref().map(|_| {
+        let pool = BlockingPool::new(
+            "tree_reads",
+            api_config.web3_json_rpc.tree_read_threads(),
+            api_config.web3_json_rpc.tree_read_queue_capacity(),
+        )
+        .expect("failed creating tree read thread pool");
+        Arc::new(pool)
+    });
+
+    let (mut tasks, health_check) =
+        web3::ApiBuilder::http_backend(internal_api.clone(), replica_connection_pool.clone())
+            .http(api_config.web3_json_rpc.http_port)
+            .with_filters_limit(api_config.web3_json_rpc.filters_limit())
+            .with_threads(api_config.web3_json_rpc.http_server_threads())
+            .with_batch_request_size_limit(api_config.web3_json_rpc.max_batch_request_size())
+            .with_response_body_size_limit(api_config.web3_json_rpc.max_response_body_size())
+            .with_tx_sender(tx_sender, vm_barrier)
+            .with_mempool(mempool)
+            .with_pending_state(pending_state)
+            .with_tree_reader(tree_reader)
+            .with_tree_read_pool(tree_read_pool)
+            .with_archive_store(archive_store)
+            .with_call_output_store(call_output_store)
+            .with_components(components.to_vec())
+            .with_ipc(ipc_endpoint(&api_config.web3_json_rpc))
+            .with_method_filter(method_filter)
+            .with_access_log(access_log)
+            .enable_api_namespaces(namespaces)
+            .build(stop_receiver.clone())
+            .await;
+    if let Some(pool) = call_storage_pool {
+        tasks.push(tokio::spawn(
+            pool.run(replica_connection_pool, stop_receiver.clone()),
+        ));
+    }
+    if let Some(pruner) = simulations_pruner {
+        tasks.push(tokio::spawn(pruner.run(stop_receiver.clone())));
+    }
+    if let Some(screener) = calldata_screener {
+        tasks.push(tokio::spawn(screener.run_reloading(stop_receiver.clone())));
+    }
+    if let Some(autotuner) = vm_concurrency_autotuner {
+        tasks.push(tokio::spawn(autotuner.run(stop_receiver)));
+    }
+    (tasks, health_check)
+}
+
+fn ipc_endpoint(web3_json_config: &Web3JsonRpcConfig) -> Option<IpcEndpoint> {
+    let path = web3_json_config.ipc_path.as_ref()?;
+    Some(IpcEndpoint {
+        path: path.into(),
+        permissions: web3_json_config
+            .ipc_permissions()
+            .expect("invalid IPC permissions"),
+    })
 }
 
 async fn build_tx_sender(
     tx_sender_config: &TxSenderConfig,
     web3_json_config: &Web3JsonRpcConfig,
     sequencer_config: &SequencerConfig,
+    db_config: &DBConfig,
     master_pool: ConnectionPool,
     replica_pool: ConnectionPool,
     storage_caches: PostgresStorageCaches,
+    seal_events: Option<MiniblockSealEvents>,
 ) -> (TxSender, VmConcurrencyBarrier) {
     let mut tx_sender_builder = TxSenderBuilder::new(tx_sender_config.clone(), replica_pool)
-        .with_main_connection_pool(master_pool)
-        .with_sequencer_config(sequencer_config.clone());
+        .with_sequencer_config(sequencer_config.clone())
+        .with_miniblock_seal_events(seal_events);
+    if let Some(rules_path) = &web3_json_config.calldata_screening_rules_path {
+        let screener = CalldataScreener::load(master_pool.clone(), Path::new(rules_path))
+            .await
+            .expect("failed loading calldata screening rules");
+        tx_sender_builder = tx_sender_builder.with_calldata_screener(screener);
+    }
+    // Read-only nodes don't insert transactions into the mempool; they either proxy transactions
+    // to the main node, or reject them.
+    if !web3_json_config.read_only() {
+        tx_sender_builder = tx_sender_builder.with_main_connection_pool(master_pool);
+    } else if let Some(main_node_url) = &web3_json_config.main_node_url {
+        tx_sender_builder = tx_sender_builder.with_tx_proxy(main_node_url);
+    }
 
+    if web3_json_config.call_storage_pool_size() > 0 {
+        tx_sender_builder =
+            tx_sender_builder.with_call_storage_pool(db_config.sequencer_db_path.clone());
+    }
     if let Some(transactions_per_sec_limit) = web3_json_config.transactions_per_sec_limit {
         tx_sender_builder = tx_sender_builder.with_rate_limiter(transactions_per_sec_limit);
     }
 
     let max_concurrency = web3_json_config.vm_concurrency_limit();
-    let (vm_concurrency_limiter, vm_barrier) = VmConcurrencyLimiter::new(max_concurrency);
+    let (vm_concurrency_limiter, vm_barrier) = match web3_json_config.vm_concurrency_min_limit {
+        Some(min_limit) => VmConcurrencyLimiter::autotuned(VmConcurrencyAutotuneConfig {
+            min_limit,
+            max_limit: max_concurrency,
+            target_p95: web3_json_config.vm_concurrency_target_p95(),
+            max_cache_miss_ratio: web3_json_config.vm_concurrency_max_cache_miss_ratio(),
+            interval: web3_json_config.vm_concurrency_autotune_interval(),
+        }),
+        None => VmConcurrencyLimiter::new(max_concurrency),
+    };
 
     let tx_sender = tx_sender_builder
         .build(
@@ -297,10 +353,10 @@ async fn build_tx_sender(
 }
 
 fn build_storage_caches(
+    rpc_config: &Web3JsonRpcConfig,
     replica_connection_pool: &ConnectionPool,
     task_futures: &mut Vec<JoinHandle<anyhow::Result<()>>>,
 ) -> PostgresStorageCaches {
-    let rpc_config = load_web3_json_rpc_config().expect("failed to load web3_json_rpc_config");
     let factory_deps_capacity = rpc_config.factory_deps_cache_size() as u64;
     let initial_writes_capacity = rpc_config.initial_writes_cache_size() as u64;
     let values_capacity = rpc_config.latest_values_cache_size() as u64;
@@ -317,6 +373,25 @@ fn build_storage_caches(
     storage_caches
 }
 
+async fn create_mempool(
+    connection_pool: &ConnectionPool,
+    mempool_config: &MempoolConfig,
+    sequencer_config: &SequencerConfig,
+) -> anyhow::Result<MempoolGuard> {
+    let next_priority_id = connection_pool
+        .access_storage()
+        .await
+        .transactions_dal()
+        .next_priority_id()
+        .await
+        .context("next_priority_id()")?;
+    Ok(MempoolGuard::new(
+        next_priority_id,
+        mempool_config.capacity,
+        block_builder_policy(sequencer_config),
+    ))
+}
+
 pub fn setup_sigint_handler() -> oneshot::Receiver<()> {
     let (sigint_sender, sigint_receiver) = oneshot::channel();
     let mut sigint_sender = Some(sigint_sender);
@@ -335,47 +410,108 @@ pub fn setup_sigint_handler() -> oneshot::Receiver<()> {
 
 async fn add_sequencer_to_task_futures(
     task_futures: &mut Vec<JoinHandle<anyhow::Result<()>>>,
+    task_labels: &mut Vec<TaskLabel>,
+    healthchecks: &mut Vec<Box<dyn CheckHealth>>,
     contracts_config: &ContractsConfig,
+    pools: &NodePools,
     sequencer_config: SequencerConfig,
     db_config: &DBConfig,
     mempool_config: &MempoolConfig,
+    mempool: MempoolGuard,
+    pending_state: Option<PendingStateGuard>,
+    seal_events: Option<MiniblockSealEvents>,
     store_factory: &ObjectStoreFactory,
     stop_receiver: watch::Receiver<bool>,
 ) {
-    let pool_builder = ConnectionPool::singleton(DbVariant::Master);
-    let sequencer_pool = pool_builder.build().await;
-    let next_priority_id = sequencer_pool
-        .access_storage()
-        .await
-        .transactions_dal()
-        .next_priority_id()
-        .await;
-    let mempool = MempoolGuard::new(next_priority_id, mempool_config.capacity);
+    let sequencer_pool = pools.master(Some(1)).await;
 
-    let miniblock_sealer_pool = pool_builder.build().await;
+    let leader_election = if sequencer_config.leader_election_enabled() {
+        let election_pool = pools.master(Some(1)).await;
+        Some(LeaderElection::new(&sequencer_config, election_pool))
+    } else {
+        None
+    };
+    let fence = leader_election
+        .as_ref()
+        .map_or_else(LeaseFence::default, LeaderElection::fence);
+
+    let miniblock_sealer_pool = pools.master(Some(1)).await;
     let (miniblock_sealer, miniblock_sealer_handle) = MiniblockSealer::new(
         miniblock_sealer_pool,
         sequencer_config.miniblock_seal_queue_capacity,
+        fence.clone(),
+        seal_events,
     );
     task_futures.push(tokio::spawn(miniblock_sealer.run()));
+    // The sealer has no stop signal: it stops once the sequencer drops its handle.
+    label_new_tasks(
+        task_labels,
+        task_futures,
+        TaskLabel {
+            component: Component::Sequencer,
+            phase: ShutdownPhase::Sealers,
+        },
+    );
 
     let object_store = store_factory.create_store().await;
 
-    let sequencer = create_sequencer(
-        contracts_config,
-        sequencer_config,
-        db_config,
-        mempool_config,
-        sequencer_pool,
+    let watchdog_pool = pools.master(Some(1)).await;
+    let (watchdog, watchdog_health_check) = SequencerWatchdog::new(
+        &sequencer_config,
+        watchdog_pool,
         mempool.clone(),
-        miniblock_sealer_handle,
-        object_store,
-        stop_receiver.clone(),
-    )
-    .await;
-    task_futures.push(tokio::spawn(sequencer.run()));
+        miniblock_sealer_handle.in_flight_counter(),
+    );
+    task_futures.push(tokio::spawn(watchdog.run(stop_receiver.clone())));
+    healthchecks.push(Box::new(watchdog_health_check));
+
+    let sequencer_task = {
+        let contracts_config = contracts_config.clone();
+        let db_config = db_config.clone();
+        let mempool_config = mempool_config.clone();
+        let mempool = mempool.clone();
+        let stop_receiver = stop_receiver.clone();
+        async move {
+            // A standby instance only creates the sequencer once it becomes the leader, so that
+            // the pending state and the sequencer cache are loaded after the takeover.
+            let lease = match &leader_election {
+                Some(election) => {
+                    let lease = election
+                        .wait_for_leadership(&db_config, stop_receiver.clone())
+                        .await?;
+                    let Some(lease) = lease else {
+                        return Ok(());
+                    };
+                    Some(lease)
+                }
+                None => None,
+            };
+            let run_sequencer = async {
+                let sequencer = create_sequencer(
+                    &contracts_config,
+                    sequencer_config,
+                    &db_config,
+                    &mempool_config,
+                    sequencer_pool,
+                    mempool,
+                    miniblock_sealer_handle,
+                    object_store,
+                    pending_state,
+                    fence,
+                    stop_receiver,
+                )
+                .await;
+                sequencer.run().await
+            };
+            match (leader_election, lease) {
+                (Some(election), Some(lease)) => election.run_as_leader(lease, run_sequencer).await,
+                _ => run_sequencer.await,
+            }
+        }
+    };
+    task_futures.push(tokio::spawn(sequencer_task));
 
-    let mempool_fetcher_pool = pool_builder.build().await;
+    let mempool_fetcher_pool = pools.master(Some(1)).await;
     let mempool_fetcher = MempoolFetcher::new(mempool, mempool_config);
     let mempool_fetcher_handle = tokio::spawn(mempool_fetcher.run(
         mempool_fetcher_pool,
@@ -390,7 +526,7 @@ pub async fn genesis_init(
     eth_sender: &ETHSenderConfig,
     network_config: &NetworkConfig,
     _contracts_config: &ContractsConfig,
-) {
+) -> anyhow::Result<()> {
     let mut storage: StorageProcessor<'_> = StorageProcessor::establish_connection(true).await;
     // TODO: check operator_address is correct
     let operator_address = PackedEthSignature::address_from_private_key(
@@ -401,49 +537,71 @@ pub async fn genesis_init(
     )
     .expect("Failed to restore operator address from private key");
 
+    let fork_state = network_config.fork_genesis_path.as_ref().map(|path| {
+        genesis_bundle::load_genesis_bundle(Path::new(path))
+            .expect("failed to load fork genesis bundle")
+    });
+
+    // System contracts must match the protocol version recorded for the genesis batch.
+    let protocol_version = ProtocolVersionId::latest();
     genesis::ensure_genesis_state(
         &mut storage,
         L2ChainId(network_config.ola_network_id),
         &genesis::GenesisParams {
             first_validator: operator_address,
             base_system_contracts: BaseSystemContracts::load_from_disk(),
-            system_contracts: get_system_smart_contracts(),
+            system_contracts: get_system_smart_contracts(protocol_version),
+            protocol_version,
+            fork_state,
         },
     )
-    .await;
+    .await?;
+    Ok(())
 }
 
-pub async fn is_genesis_needed() -> bool {
+pub async fn is_genesis_needed() -> anyhow::Result<bool> {
     let mut storage = StorageProcessor::establish_connection(true).await;
-    storage.blocks_dal().is_genesis_needed().await
+    Ok(storage.blocks_dal().is_genesis_needed().await?)
 }
 
 async fn add_trees_to_task_futures(
     task_futures: &mut Vec<JoinHandle<anyhow::Result<()>>>,
     healthchecks: &mut Vec<Box<dyn CheckHealth>>,
-    _components: &[Component],
+    pools: &NodePools,
+    db_config: &DBConfig,
+    operation_config: &OperationsManagerConfig,
     store_factory: &ObjectStoreFactory,
     stop_receiver: watch::Receiver<bool>,
-) {
-    let db_config = DBConfig::from_env();
-    let operation_config =
-        load_operation_manager_config().expect("failed to load operation config");
+) -> watch::Receiver<Option<AsyncTreeReader>> {
     let object_store = match db_config.merkle_tree.mode {
         MerkleTreeMode::Lightweight => None,
         MerkleTreeMode::Full => Some(store_factory.create_store().await),
     };
-    let (future, tree_health_check) =
-        run_tree(&db_config, &operation_config, object_store, stop_receiver).await;
+    let pool = pools.master(Some(1)).await;
+    let (future, tree_health_check, tree_reader) = run_tree(
+        db_config,
+        operation_config,
+        object_store,
+        pool,
+        stop_receiver,
+    )
+    .await;
     task_futures.push(future);
     healthchecks.push(Box::new(tree_health_check));
+    tree_reader
 }
 
 async fn run_tree(
     config: &DBConfig,
     operation_manager: &OperationsManagerConfig,
     object_store: Option<Arc<dyn ObjectStore>>,
+    pool: ConnectionPool,
     stop_receiver: watch::Receiver<bool>,
-) -> (JoinHandle<anyhow::Result<()>>, ReactiveHealthCheck) {
+) -> (
+    JoinHandle<anyhow::Result<()>>,
+    ReactiveHealthCheck,
+    watch::Receiver<Option<AsyncTreeReader>>,
+) {
     let started_at = Instant::now();
     let config = metadata_calculator::MetadataCalculatorConfig::for_main_node(
         &config.merkle_tree,
@@ -452,10 +610,10 @@ async fn run_tree(
     let metadata_calculator =
         metadata_calculator::MetadataCalculator::new(config, object_store).await;
     let tree_health_check = metadata_calculator.tree_health_check();
-    let pool = ConnectionPool::singleton(DbVariant::Master).build().await;
+    let tree_reader = metadata_calculator.tree_reader();
     let future = tokio::spawn(metadata_calculator.run(pool, stop_receiver));
     olaos_logs::info!("Initialized merkle tree in {:?}", started_at.elapsed());
-    (future, tree_health_check)
+    (future, tree_health_check, tree_reader)
 }
 
 async fn add_witness_input_producer_to_task_futures(
@@ -2,24 +2,31 @@ use std::{
     collections::BTreeMap,
     future::Future,
     path::{Path, PathBuf},
+    sync::Arc,
     time::Duration,
 };
 
 use ola_config::database::MerkleTreeMode;
-use ola_dal::StorageProcessor;
+use ola_dal::{DalResult, StorageProcessor};
 use ola_types::{
     block::WitnessBlockWithLogs,
     log::{StorageLog, StorageLogKind, WitnessStorageLog},
-    merkle_tree::{tree_key_to_h256, TreeMetadata},
+    merkle_tree::{h256_to_tree_key, tree_key_to_h256, TreeMetadata},
     L1BatchNumber, StorageKey, H256,
 };
 use olaos_health_check::{Health, HealthStatus};
-use olaos_merkle_tree::{storage::MerkleTreeColumnFamily, tree::AccountTree};
+use olaos_merkle_tree::{
+    reader::{TreeEntriesWithProofs, TreeReader},
+    storage::MerkleTreeColumnFamily,
+    tree::AccountTree,
+};
 use olaos_storage::{RocksDB, RocksDBOptions, StalledWritesRetries};
 use serde::{Deserialize, Serialize};
 #[cfg(test)]
 use tokio::sync::mpsc;
 
+use crate::utils::blocking_pool::BlockingPool;
+
 #[derive(Debug, Serialize)]
 pub(super) struct TreeHealthCheckDetails {
     pub next_l1_batch_to_seal: L1BatchNumber,
@@ -51,6 +58,7 @@ pub(super) async fn create_db(
     block_cache_capacity: usize,
     memtable_capacity: usize,
     stalled_writes_timeout: Duration,
+    io_stats_interval: Option<Duration>,
     multi_get_chunk_size: usize,
 ) -> RocksDB<MerkleTreeColumnFamily> {
     tokio::task::spawn_blocking(move || {
@@ -59,6 +67,7 @@ pub(super) async fn create_db(
             block_cache_capacity,
             memtable_capacity,
             stalled_writes_timeout,
+            io_stats_interval,
             multi_get_chunk_size,
         )
     })
@@ -71,6 +80,7 @@ fn create_db_sync(
     block_cache_capacity: usize,
     memtable_capacity: usize,
     stalled_writes_timeout: Duration,
+    io_stats_interval: Option<Duration>,
     _multi_get_chunk_size: usize,
 ) -> RocksDB<MerkleTreeColumnFamily> {
     olaos_logs::info!(
@@ -86,6 +96,7 @@ fn create_db_sync(
             block_cache_capacity: Some(block_cache_capacity),
             large_memtable_capacity: Some(memtable_capacity),
             stalled_writes_retries: StalledWritesRetries::new(stalled_writes_timeout),
+            io_stats_interval,
         },
     );
     if cfg!(test) {
@@ -140,6 +151,23 @@ impl AsyncTree {
         metadata
     }
 
+    /// Rewrites up to `max_nodes` tree nodes stored in an outdated format.
+    pub async fn migrate_nodes(&mut self, max_nodes: usize) -> usize {
+        let mut tree = self.inner.take().expect(Self::INCONSISTENT_MSG);
+        let (tree, migrated_count) = tokio::task::spawn_blocking(move || {
+            let migrated_count = tree
+                .storage
+                .migrate_nodes(max_nodes)
+                .expect("failed migrating tree nodes");
+            (tree, migrated_count)
+        })
+        .await
+        .unwrap();
+
+        self.inner = Some(tree);
+        migrated_count
+    }
+
     pub async fn save(&mut self) {
         let mut tree = self.inner.take().expect(Self::INCONSISTENT_MSG);
         self.inner = Some(
@@ -153,6 +181,81 @@ impl AsyncTree {
     }
 }
 
+/// Read-only handle to the Merkle tree. Unlike [`AsyncTree`], it can be cloned and used concurrently
+/// with tree updates; see [`TreeReader`] for the consistency guarantees.
+#[derive(Debug, Clone)]
+pub struct AsyncTreeReader {
+    inner: TreeReader,
+    /// Pool running reads; if not set, reads are run via `spawn_blocking()`.
+    pool: Option<Arc<BlockingPool>>,
+}
+
+impl AsyncTreeReader {
+    pub(super) fn new(db: RocksDB<MerkleTreeColumnFamily>) -> Self {
+        Self {
+            inner: TreeReader::new(db),
+            pool: None,
+        }
+    }
+
+    /// Runs reads on the specified thread pool. If the pool is saturated, reads fail with
+    /// a [`PoolSaturated`](crate::utils::blocking_pool::PoolSaturated) error.
+    pub fn with_pool(mut self, pool: Option<Arc<BlockingPool>>) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    async fn run_read<T: Send + 'static>(
+        &self,
+        read: impl FnOnce(TreeReader) -> T + Send + 'static,
+    ) -> anyhow::Result<T> {
+        let reader = self.inner.clone();
+        Ok(match &self.pool {
+            Some(pool) => pool.run(move || read(reader)).await?,
+            None => tokio::task::spawn_blocking(move || read(reader))
+                .await
+                .unwrap(),
+        })
+    }
+
+    /// Returns the root hash of the tree and the number of the next L1 batch to be processed.
+    pub async fn info(&self) -> anyhow::Result<(H256, L1BatchNumber)> {
+        let (root_hash, block_number) = self.run_read(|reader| reader.metadata()).await??;
+        Ok((tree_key_to_h256(&root_hash), L1BatchNumber(block_number)))
+    }
+
+    /// Reads values of the specified hashed storage keys together with Merkle paths to them.
+    pub async fn entries_with_proofs(
+        &self,
+        hashed_keys: Vec<H256>,
+    ) -> anyhow::Result<TreeEntriesWithProofs> {
+        let proofs = self
+            .run_read(move |reader| {
+                let keys: Vec<_> = hashed_keys.iter().map(h256_to_tree_key).collect();
+                reader.entries_with_proofs(&keys)
+            })
+            .await??;
+        Ok(proofs)
+    }
+
+    /// Reads the tree nodes on the paths to the specified hashed storage keys into the RocksDB
+    /// block cache. Returns the number of read DB keys.
+    pub(super) async fn prefetch(
+        &self,
+        hashed_keys: Vec<H256>,
+        chunk_size: usize,
+    ) -> anyhow::Result<usize> {
+        let reader = self.inner.clone();
+        let read_count = tokio::task::spawn_blocking(move || {
+            let keys: Vec<_> = hashed_keys.iter().map(h256_to_tree_key).collect();
+            reader.prefetch(&keys, chunk_size)
+        })
+        .await
+        .unwrap()?;
+        Ok(read_count)
+    }
+}
+
 /// Component implementing the delay policy in [`MetadataCalculator`] when there are no
 /// L1 batches to seal.
 #[derive(Debug, Clone)]
@@ -187,11 +290,14 @@ impl Delayer {
 pub(crate) async fn get_logs_for_l1_batch(
     storage: &mut StorageProcessor<'_>,
     l1_batch_number: L1BatchNumber,
-) -> Option<WitnessBlockWithLogs> {
-    let header = storage
+) -> DalResult<Option<WitnessBlockWithLogs>> {
+    let Some(header) = storage
         .blocks_dal()
         .get_l1_batch_header(l1_batch_number)
-        .await?;
+        .await?
+    else {
+        return Ok(None);
+    };
 
     // `BTreeMap` is used because tree needs to process slots in lexicographical order.
     let mut storage_logs: BTreeMap<StorageKey, WitnessStorageLog> = BTreeMap::new();
@@ -199,11 +305,11 @@ pub(crate) async fn get_logs_for_l1_batch(
     let protective_reads = storage
         .storage_logs_dedup_dal()
         .get_protective_reads_for_l1_batch(l1_batch_number)
-        .await;
+        .await?;
     let touched_slots = storage
         .storage_logs_dal()
         .get_touched_slots_for_l1_batch(l1_batch_number)
-        .await;
+        .await?;
 
     let hashed_keys: Vec<_> = protective_reads
         .iter()
@@ -213,7 +319,7 @@ pub(crate) async fn get_logs_for_l1_batch(
     let previous_values = storage
         .storage_logs_dal()
         .get_previous_storage_values(&hashed_keys, l1_batch_number)
-        .await;
+        .await?;
 
     for storage_key in protective_reads {
         let previous_value = previous_values[&storage_key.hashed_key()].unwrap_or_default();
@@ -247,10 +353,10 @@ pub(crate) async fn get_logs_for_l1_batch(
         }
     }
 
-    Some(WitnessBlockWithLogs {
+    Ok(Some(WitnessBlockWithLogs {
         header,
         storage_logs: storage_logs.into_values().collect(),
-    })
+    }))
 }
 
 pub fn filter_block_logs(logs: &[WitnessStorageLog]) -> Vec<WitnessStorageLog> {
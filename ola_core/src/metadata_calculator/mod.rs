@@ -20,9 +20,10 @@ use tokio::sync::watch;
 use tempfile::TempDir;
 
 pub(crate) use self::helpers::get_logs_for_l1_batch;
+pub use self::helpers::AsyncTreeReader;
 use self::{
     helpers::{create_db, AsyncTree, Delayer},
-    updater::TreeUpdater,
+    updater::{TreePrefetcher, TreeUpdater},
 };
 
 mod helpers;
@@ -49,6 +50,10 @@ pub struct MetadataCalculatorConfig {
     pub memtable_capacity: usize,
     /// Timeout to wait for the Merkle tree database to run compaction on stalled writes.
     pub stalled_writes_timeout: Duration,
+    /// Interval between reports of RocksDB IO statistics; if not set, statistics are not collected.
+    pub io_stats_interval: Option<Duration>,
+    /// Whether to prefetch tree nodes touched by the next L1 batch into the RocksDB block cache.
+    pub prefetch_enabled: bool,
 }
 
 impl MetadataCalculatorConfig {
@@ -65,17 +70,38 @@ impl MetadataCalculatorConfig {
             block_cache_capacity: merkle_tree_config.block_cache_size(),
             memtable_capacity: merkle_tree_config.memtable_capacity(),
             stalled_writes_timeout: merkle_tree_config.stalled_writes_timeout(),
+            io_stats_interval: merkle_tree_config.io_stats_interval(),
+            prefetch_enabled: merkle_tree_config.prefetch_enabled(),
         }
     }
 }
 
+/// Opens the tree for reading only. Unlike [`MetadataCalculator`], nothing updates the tree,
+/// so it's used by read-only nodes, whose tree is provisioned externally (e.g., restored
+/// from a backup of the main node tree) and which must not write to Postgres.
+pub async fn open_tree_reader(config: &MetadataCalculatorConfig) -> AsyncTreeReader {
+    let db = create_db(
+        config.db_path.clone().into(),
+        config.block_cache_capacity,
+        config.memtable_capacity,
+        config.stalled_writes_timeout,
+        config.io_stats_interval,
+        config.multi_get_chunk_size,
+    )
+    .await;
+    AsyncTreeReader::new(db)
+}
+
 #[derive(Debug)]
 pub struct MetadataCalculator {
     tree: AsyncTree,
+    tree_reader: AsyncTreeReader,
+    tree_reader_sender: watch::Sender<Option<AsyncTreeReader>>,
     object_store: Option<Arc<dyn ObjectStore>>,
     delayer: Delayer,
     health_updater: HealthUpdater,
     max_l1_batches_per_iter: usize,
+    prefetcher: Option<TreePrefetcher>,
 }
 
 impl MetadataCalculator {
@@ -94,18 +120,28 @@ impl MetadataCalculator {
             config.block_cache_capacity,
             config.memtable_capacity,
             config.stalled_writes_timeout,
+            config.io_stats_interval,
             config.multi_get_chunk_size,
         )
         .await;
+        // The reader shares the RocksDB instance with the tree, since RocksDB cannot be opened
+        // by several instances at once.
+        let tree_reader = AsyncTreeReader::new(db.clone());
         let tree = AsyncTree::new(db);
+        let prefetcher = config
+            .prefetch_enabled
+            .then(|| TreePrefetcher::new(tree_reader.clone(), config.multi_get_chunk_size));
 
         let (_, health_updater) = ReactiveHealthCheck::new("tree");
         Self {
             tree,
+            tree_reader,
+            tree_reader_sender: watch::channel(None).0,
             object_store,
             delayer: Delayer::new(config.delay_interval),
             health_updater,
             max_l1_batches_per_iter: config.max_l1_batches_per_iter,
+            prefetcher,
         }
     }
 
@@ -114,16 +150,32 @@ impl MetadataCalculator {
         self.health_updater.subscribe()
     }
 
+    /// Returns a receiver for the read-only tree handle. The handle is published once the tree
+    /// is initialized, i.e. after the genesis L1 batch is processed; until then, the receiver holds `None`.
+    pub fn tree_reader(&self) -> watch::Receiver<Option<AsyncTreeReader>> {
+        self.tree_reader_sender.subscribe()
+    }
+
     pub async fn run(
         self,
         pool: ConnectionPool,
         stop_receiver: watch::Receiver<bool>,
     ) -> anyhow::Result<()> {
-        let updater = TreeUpdater::new(self.tree, self.max_l1_batches_per_iter, self.object_store);
+        let updater = TreeUpdater::new(
+            self.tree,
+            self.max_l1_batches_per_iter,
+            self.object_store,
+            self.prefetcher,
+        );
         updater
-            .loop_updating_tree(self.delayer, &pool, stop_receiver, self.health_updater)
-            .await;
-        Ok(())
+            .loop_updating_tree(
+                self.delayer,
+                &pool,
+                stop_receiver,
+                self.health_updater,
+                (self.tree_reader, self.tree_reader_sender),
+            )
+            .await
     }
 
     // TODO: gas
@@ -152,6 +204,7 @@ impl MetadataCalculator {
     fn build_l1_batch_metadata(
         tree_metadata: TreeMetadata,
         header: &L1BatchHeader,
+        receipts_root: H256,
     ) -> L1BatchMetadata {
         let merkle_root_hash = tree_key_to_h256(&tree_metadata.root_hash);
         let commitment = L1BatchCommitment::new(
@@ -159,8 +212,11 @@ impl MetadataCalculator {
             merkle_root_hash,
             tree_metadata.initial_writes,
             tree_metadata.repeated_writes,
+            receipts_root,
             header.base_system_contracts_hashes.entrypoint,
             header.base_system_contracts_hashes.default_aa,
+            header.fee_account_address,
+            header.protocol_version.unwrap_or_default(),
         );
         let commitment_hash = commitment.hash();
         olaos_logs::trace!("L1 batch commitment: {commitment:?}");
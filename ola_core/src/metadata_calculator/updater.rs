@@ -1,28 +1,82 @@
 //! Tree updater trait and its implementations.
 
+use anyhow::Context as _;
 use futures::{future::join as future_join, future::ready as future_ready, FutureExt};
 use ola_dal::{connection::ConnectionPool, StorageProcessor};
 use ola_types::{
     block::{L1BatchHeader, WitnessBlockWithLogs},
     merkle_tree::{h256_to_tree_value, TreeMetadata},
     proofs::PrepareBasicCircuitsJob,
-    L1BatchNumber,
+    receipts_tree::ReceiptsTree,
+    L1BatchNumber, H256,
 };
 use olaos_health_check::HealthUpdater;
 use olaos_object_store::ObjectStore;
-use std::{ops, sync::Arc};
+use std::{ops, sync::Arc, time::Instant};
 use tokio::sync::watch;
 
 use super::{
-    helpers::{get_logs_for_l1_batch, AsyncTree, Delayer, TreeHealthCheckDetails},
+    helpers::{get_logs_for_l1_batch, AsyncTree, AsyncTreeReader, Delayer, TreeHealthCheckDetails},
     MetadataCalculator,
 };
 
+/// Max number of outdated tree nodes rewritten per idle iteration of the updater.
+const NODE_MIGRATION_CHUNK_SIZE: usize = 10_000;
+
+/// Reads the tree nodes touched by an L1 batch into the RocksDB block cache before the batch
+/// is processed, so that the tree doesn't load them one by one on a cold cache.
+#[derive(Debug, Clone)]
+pub(super) struct TreePrefetcher {
+    reader: AsyncTreeReader,
+    multi_get_chunk_size: usize,
+}
+
+impl TreePrefetcher {
+    pub fn new(reader: AsyncTreeReader, multi_get_chunk_size: usize) -> Self {
+        Self {
+            reader,
+            multi_get_chunk_size,
+        }
+    }
+
+    async fn prefetch(&self, l1_batch: &WitnessBlockWithLogs) {
+        let started_at = Instant::now();
+        let hashed_keys = l1_batch
+            .storage_logs
+            .iter()
+            .map(|log| log.storage_log.key.hashed_key())
+            .collect();
+        let l1_batch_number = l1_batch.header.number;
+        match self
+            .reader
+            .prefetch(hashed_keys, self.multi_get_chunk_size)
+            .await
+        {
+            Ok(read_count) => {
+                let elapsed = started_at.elapsed();
+                metrics::histogram!("server.metadata_calculator.prefetch.latency", elapsed);
+                metrics::histogram!(
+                    "server.metadata_calculator.prefetch.read_keys",
+                    read_count as f64
+                );
+                olaos_logs::debug!(
+                    "Prefetched {read_count} tree DB keys for L1 batch #{l1_batch_number} in {elapsed:?}"
+                );
+            }
+            // Prefetching only warms up the cache, so the tree can proceed without it.
+            Err(err) => olaos_logs::warn!(
+                "Failed prefetching tree nodes for L1 batch #{l1_batch_number}: {err:#}"
+            ),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub(super) struct TreeUpdater {
     tree: AsyncTree,
     max_l1_batches_per_iter: usize,
     object_store: Option<Arc<dyn ObjectStore>>,
+    prefetcher: Option<TreePrefetcher>,
 }
 
 impl TreeUpdater {
@@ -30,11 +84,13 @@ impl TreeUpdater {
         tree: AsyncTree,
         max_l1_batches_per_iter: usize,
         object_store: Option<Arc<dyn ObjectStore>>,
+        prefetcher: Option<TreePrefetcher>,
     ) -> Self {
         Self {
             tree,
             max_l1_batches_per_iter,
             object_store,
+            prefetcher,
         }
     }
 
@@ -43,7 +99,19 @@ impl TreeUpdater {
         l1_batch: WitnessBlockWithLogs,
     ) -> (L1BatchHeader, TreeMetadata, Option<String>) {
         let pre_root_hash = h256_to_tree_value(&self.tree.root_hash());
+        let started_at = Instant::now();
         let mut metadata = self.tree.process_l1_batch(l1_batch.storage_logs).await;
+        // Labeled with the prefetching mode, so that its effect can be compared across nodes.
+        let prefetched = if self.prefetcher.is_some() {
+            "true"
+        } else {
+            "false"
+        };
+        metrics::histogram!(
+            "server.metadata_calculator.tree_update.latency",
+            started_at.elapsed(),
+            "prefetched" => prefetched
+        );
         let root_hash = h256_to_tree_value(&self.tree.root_hash());
 
         let witness_input = metadata.witness.take();
@@ -84,37 +152,73 @@ impl TreeUpdater {
         (l1_batch.header, metadata, object_key)
     }
 
+    /// Loads the receipts root saved when the L1 batch was sealed. Batches sealed before
+    /// receipts roots were introduced have it recomputed from their transactions.
+    async fn load_receipts_root(
+        storage: &mut StorageProcessor<'_>,
+        l1_batch_number: L1BatchNumber,
+    ) -> H256 {
+        let receipts_root = storage
+            .blocks_dal()
+            .get_l1_batch_receipts_root(l1_batch_number)
+            .await
+            .expect("failed loading receipts root of L1 batch");
+        if let Some(receipts_root) = receipts_root {
+            return receipts_root;
+        }
+        let leaves = storage
+            .blocks_web3_dal()
+            .get_l1_batch_receipt_leaves(l1_batch_number)
+            .await
+            .expect("failed loading receipts of L1 batch");
+        ReceiptsTree::new(&leaves).root()
+    }
+
     async fn process_multiple_batches(
         &mut self,
         storage: &mut StorageProcessor<'_>,
         l1_batch_numbers: ops::RangeInclusive<u32>,
-    ) -> L1BatchNumber {
+    ) -> anyhow::Result<L1BatchNumber> {
         olaos_logs::info!("Processing L1 batches #{l1_batch_numbers:?}");
         let first_l1_batch_number = *l1_batch_numbers.start();
         let last_l1_batch_number = L1BatchNumber(*l1_batch_numbers.end());
         let mut l1_batch_data =
-            get_logs_for_l1_batch(storage, L1BatchNumber(first_l1_batch_number)).await;
+            get_logs_for_l1_batch(storage, L1BatchNumber(first_l1_batch_number))
+                .await
+                .context("get_logs_for_l1_batch()")?;
+        let prefetcher = self.prefetcher.clone();
+        if let (Some(prefetcher), Some(l1_batch)) = (&prefetcher, &l1_batch_data) {
+            prefetcher.prefetch(l1_batch).await;
+        }
 
         let mut previous_root_hash = self.tree.root_hash();
         let mut updated_headers = vec![];
         for l1_batch_number in l1_batch_numbers {
             let l1_batch_number = L1BatchNumber(l1_batch_number);
             let Some(current_l1_batch_data) = l1_batch_data else {
-                return l1_batch_number;
+                return Ok(l1_batch_number);
             };
 
             let process_l1_batch_task = self.process_l1_batch(current_l1_batch_data);
             let load_next_l1_batch_task = async {
                 if l1_batch_number < last_l1_batch_number {
-                    get_logs_for_l1_batch(storage, l1_batch_number + 1).await
+                    let next_l1_batch = get_logs_for_l1_batch(storage, l1_batch_number + 1).await;
+                    // Warm up the cache for the next L1 batch while the current one is processed.
+                    if let (Some(prefetcher), Ok(Some(l1_batch))) = (&prefetcher, &next_l1_batch) {
+                        prefetcher.prefetch(l1_batch).await;
+                    }
+                    next_l1_batch
                 } else {
-                    None // Don't need to load the next L1 batch after the last one we're processing.
+                    Ok(None) // Don't need to load the next L1 batch after the last one we're processing.
                 }
             };
             let ((header, metadata, object_key), next_l1_batch_data) =
                 future_join(process_l1_batch_task, load_next_l1_batch_task).await;
+            let next_l1_batch_data = next_l1_batch_data.context("get_logs_for_l1_batch()")?;
 
-            let metadata = MetadataCalculator::build_l1_batch_metadata(metadata, &header);
+            let receipts_root = Self::load_receipts_root(storage, l1_batch_number).await;
+            let metadata =
+                MetadataCalculator::build_l1_batch_metadata(metadata, &header, receipts_root);
 
             // TODO: gas
             // MetadataCalculator::reestimate_l1_batch_commit_gas(storage, &header, &metadata).await;
@@ -137,7 +241,8 @@ impl TreeUpdater {
                 storage
                     .proof_generation_dal()
                     .insert_proof_generation_details(l1_batch_number, object_key)
-                    .await;
+                    .await
+                    .expect("failed to insert proof generation details");
             }
             olaos_logs::info!("Updated metadata for L1 batch #{l1_batch_number} in Postgres");
 
@@ -148,15 +253,19 @@ impl TreeUpdater {
 
         self.tree.save().await;
 
-        last_l1_batch_number + 1
+        Ok(last_l1_batch_number + 1)
     }
 
     async fn step(
         &mut self,
         mut storage: StorageProcessor<'_>,
         next_l1_batch_to_seal: &mut L1BatchNumber,
-    ) {
-        let last_sealed_l1_batch = storage.blocks_dal().get_sealed_l1_batch_number().await;
+    ) -> anyhow::Result<()> {
+        let last_sealed_l1_batch = storage
+            .blocks_dal()
+            .get_sealed_l1_batch_number()
+            .await
+            .context("get_sealed_l1_batch_number()")?;
         let last_requested_l1_batch =
             next_l1_batch_to_seal.0 + self.max_l1_batches_per_iter as u32 - 1;
         let last_requested_l1_batch = last_requested_l1_batch.min(last_sealed_l1_batch.0);
@@ -169,8 +278,9 @@ impl TreeUpdater {
             olaos_logs::info!("Updating Merkle tree with L1 batches #{l1_batch_numbers:?}");
             *next_l1_batch_to_seal = self
                 .process_multiple_batches(&mut storage, l1_batch_numbers)
-                .await;
+                .await?;
         }
+        Ok(())
     }
 
     /// The processing loop for this updater.
@@ -180,25 +290,37 @@ impl TreeUpdater {
         pool: &ConnectionPool,
         mut stop_receiver: watch::Receiver<bool>,
         health_updater: HealthUpdater,
-    ) {
+        (tree_reader, tree_reader_sender): (
+            AsyncTreeReader,
+            watch::Sender<Option<AsyncTreeReader>>,
+        ),
+    ) -> anyhow::Result<()> {
         let mut storage = pool.access_storage_tagged("metadata_calculator").await;
 
         // Ensure genesis creation
         let tree = &mut self.tree;
         if tree.is_empty() {
-            let Some(logs) = get_logs_for_l1_batch(&mut storage, L1BatchNumber(0)).await else {
-                panic!("Missing storage logs for the genesis block");
-            };
+            let logs = get_logs_for_l1_batch(&mut storage, L1BatchNumber(0))
+                .await
+                .context("get_logs_for_l1_batch()")?
+                .context("Missing storage logs for the genesis block")?;
             let _ = tree.process_l1_batch(logs.storage_logs).await;
             tree.save().await;
         }
         let mut next_l1_batch_to_seal = tree.next_l1_batch_number();
+        tree_reader_sender.send_replace(Some(tree_reader));
 
-        let current_db_batch = storage.blocks_dal().get_sealed_l1_batch_number().await + 1;
+        let current_db_batch = storage
+            .blocks_dal()
+            .get_sealed_l1_batch_number()
+            .await
+            .context("get_sealed_l1_batch_number()")?
+            + 1;
         let last_l1_batch_with_metadata = storage
             .blocks_dal()
             .get_last_l1_batch_number_with_metadata()
             .await
+            .context("get_last_l1_batch_number_with_metadata()")?
             + 1;
         drop(storage);
 
@@ -226,9 +348,19 @@ impl TreeUpdater {
             let storage = pool.access_storage_tagged("metadata_calculator").await;
 
             let next_block_snapshot = *next_l1_batch_to_seal;
-            self.step(storage, &mut next_l1_batch_to_seal).await;
+            self.step(storage, &mut next_l1_batch_to_seal).await?;
             let delay = if next_block_snapshot == *next_l1_batch_to_seal {
-                // We didn't make any progress.
+                // We didn't make any progress; use the idle time to migrate outdated tree nodes.
+                let migrated_count = self.tree.migrate_nodes(NODE_MIGRATION_CHUNK_SIZE).await;
+                if migrated_count > 0 {
+                    olaos_logs::info!(
+                        "Migrated {migrated_count} Merkle tree nodes to the current format"
+                    );
+                    metrics::counter!(
+                        "server.metadata_calculator.migrated_nodes",
+                        migrated_count as u64
+                    );
+                }
                 delayer.wait(&self.tree).left_future()
             } else {
                 let health = TreeHealthCheckDetails {
@@ -253,5 +385,6 @@ impl TreeUpdater {
             }
         }
         drop(health_updater); // Explicitly mark where the updater should be dropped
+        Ok(())
     }
 }
@@ -0,0 +1,570 @@
+//! Embedding the node as a library.
+//!
+//! [`NodeBuilder`] starts the specified components from a [`Config`] and returns a [`Node`] owning
+//! their tasks, the DB pools and the shutdown handle, so that integration tests and downstream
+//! projects can run and stop a node without the `ola_node` binary. Configs not included
+//! in [`Config`] (contracts and the object store) are loaded from the environment unless provided
+//! to the builder.
+
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context as _;
+use futures::Future;
+use ola_config::{
+    contracts::{load_contracts_config, ContractsConfig},
+    node::Config,
+    object_store::{load_object_store_config, ObjectStoreConfig},
+};
+use ola_dal::{
+    connection::{ConnectionPool, DbVariant},
+    healthcheck::ConnectionPoolHealthCheck,
+};
+use ola_types::L2ChainId;
+use ola_utils::wait_for_tasks::wait_for_first_task;
+use olaos_health_check::CheckHealth;
+use olaos_object_store::ObjectStoreFactory;
+use tokio::{sync::watch, task::JoinHandle};
+
+use crate::{
+    add_sequencer_to_task_futures, add_trees_to_task_futures,
+    add_witness_input_producer_to_task_futures,
+    api_server::{
+        execution_sandbox::VmConcurrencyLimiter,
+        healthcheck::HealthCheckHandle,
+        tx_sender::TxSenderConfig,
+        web3::{
+            self,
+            backend::{access_log::AccessLog, method_filter::MethodFilter},
+            call_outputs::CallOutputStore,
+            namespaces::verifier::VerifierCommittee,
+            state::InternalApiConfig,
+            Namespace,
+        },
+    },
+    archive::TransactionArchiver,
+    audit::AuditLog,
+    build_storage_caches,
+    consistency_checker::ConsistencyChecker,
+    create_mempool, label_new_tasks, metadata_calculator, proof_data_handler,
+    proof_smoke_test::ProofSmokeTest,
+    run_http_api,
+    sequencer::types::{MiniblockSealEvents, PendingStateGuard},
+    shutdown::{Shutdown, StopSignals, TaskLabel},
+    Component,
+};
+
+/// Creates the connection pools of the node's components.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NodePools {
+    provided: Option<(ConnectionPool, ConnectionPool)>,
+}
+
+impl NodePools {
+    /// Returns a master pool with at most `max_size` connections. If the pools are provided
+    /// to [`NodeBuilder`], the provided master pool is shared instead.
+    pub(crate) async fn master(&self, max_size: Option<u32>) -> ConnectionPool {
+        match &self.provided {
+            Some((master, _)) => master.clone(),
+            None => {
+                ConnectionPool::builder(DbVariant::Master)
+                    .set_max_size(max_size)
+                    .build()
+                    .await
+            }
+        }
+    }
+
+    /// Returns a replica pool; see [`Self::master()`].
+    async fn replica(&self, statement_timeout: Option<Duration>) -> ConnectionPool {
+        match &self.provided {
+            Some((_, replica)) => replica.clone(),
+            None => {
+                ConnectionPool::builder(DbVariant::Replica)
+                    .set_statement_timeout(statement_timeout)
+                    .build()
+                    .await
+            }
+        }
+    }
+}
+
+/// Builder of a [`Node`].
+#[derive(Debug)]
+pub struct NodeBuilder {
+    config: Config,
+    components: Vec<Component>,
+    contracts_config: Option<ContractsConfig>,
+    object_store_config: Option<ObjectStoreConfig>,
+    pools: Option<(ConnectionPool, ConnectionPool)>,
+}
+
+impl NodeBuilder {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            components: Vec::new(),
+            contracts_config: None,
+            object_store_config: None,
+            pools: None,
+        }
+    }
+
+    pub fn with_component(mut self, component: Component) -> Self {
+        if !self.components.contains(&component) {
+            self.components.push(component);
+        }
+        self
+    }
+
+    pub fn with_components(self, components: impl IntoIterator<Item = Component>) -> Self {
+        components.into_iter().fold(self, Self::with_component)
+    }
+
+    pub fn with_contracts_config(mut self, config: ContractsConfig) -> Self {
+        self.contracts_config = Some(config);
+        self
+    }
+
+    pub fn with_object_store_config(mut self, config: ObjectStoreConfig) -> Self {
+        self.object_store_config = Some(config);
+        self
+    }
+
+    /// Sets the master and replica pools shared by all components. By default, the pools
+    /// are created from the database URLs in the environment, and some components (e.g.,
+    /// the sequencer) get dedicated pools.
+    pub fn with_connection_pools(
+        mut self,
+        master: ConnectionPool,
+        replica: ConnectionPool,
+    ) -> Self {
+        self.pools = Some((master, replica));
+        self
+    }
+
+    pub fn components(&self) -> &[Component] {
+        &self.components
+    }
+
+    /// Starts the components. The node runs until it's stopped with [`Node::run()`]
+    /// or [`Node::shutdown()`].
+    pub async fn build(self) -> anyhow::Result<Node> {
+        let Self {
+            config,
+            components,
+            contracts_config,
+            object_store_config,
+            pools,
+        } = self;
+        olaos_logs::info!("Starting the components: {components:?}");
+
+        let db_config = &config.db;
+        let pools = NodePools { provided: pools };
+        let connection_pool = pools.master(None).await;
+        let replica_connection_pool = pools.replica(db_config.statement_timeout()).await;
+        if db_config.schema_check_enabled() {
+            ola_dal::schema::check_schema(&connection_pool).await?;
+        }
+
+        let mut healthchecks: Vec<Box<dyn CheckHealth>> = Vec::new();
+
+        let api_config = &config.api;
+        let read_only = api_config.web3_json_rpc.read_only();
+        let contracts_config = match contracts_config {
+            Some(config) => config,
+            None => load_contracts_config().context("failed to load contracts config")?,
+        };
+
+        let stop_signals = StopSignals::new();
+
+        let mut task_futures: Vec<JoinHandle<anyhow::Result<()>>> = vec![];
+        let mut task_labels: Vec<TaskLabel> = vec![];
+
+        // The mempool is created upfront, so that it can be shared with the admin API.
+        let mempool = if components.contains(&Component::Sequencer) {
+            Some(create_mempool(&connection_pool, &config.mempool, &config.sequencer).await?)
+        } else {
+            None
+        };
+        // Writes of the open batch can only be shared with the API if both run in this process.
+        let pending_state = if components.contains(&Component::Sequencer)
+            && components.contains(&Component::HttpApi)
+        {
+            api_config
+                .web3_json_rpc
+                .pending_state_overlay_enabled()
+                .then(PendingStateGuard::default)
+        } else {
+            None
+        };
+        // Seal events let the API invalidate cached nonces as soon as miniblocks are persisted.
+        let seal_events = (components.contains(&Component::Sequencer)
+            && components.contains(&Component::HttpApi))
+        .then(MiniblockSealEvents::default);
+
+        let object_store_config = match object_store_config {
+            Some(config) => config,
+            None => load_object_store_config().context("failed to load object store config")?,
+        };
+        let store_factory = ObjectStoreFactory::new(object_store_config);
+        // Archived transactions are only served if archiving is configured.
+        let archive_store = if db_config.tx_archive_retention_l1_batches.is_some() {
+            Some(store_factory.create_store().await)
+        } else {
+            None
+        };
+
+        // The tree is initialized before the API, so that its reader can be shared with the API.
+        let mut tree_reader = None;
+        if components.contains(&Component::Tree) && read_only {
+            // Read-only nodes serve the tree as is, since updating it requires writing to Postgres.
+            let tree_config = metadata_calculator::MetadataCalculatorConfig::for_main_node(
+                &db_config.merkle_tree,
+                &config.operations_manager,
+            );
+            let reader = metadata_calculator::open_tree_reader(&tree_config).await;
+            tree_reader = Some(watch::channel(Some(reader)).1);
+            olaos_logs::info!("opened Merkle Tree for reading only");
+        } else if components.contains(&Component::Tree) {
+            let started_at = Instant::now();
+            olaos_logs::info!("initializing Merkle Tree");
+            tree_reader = Some(
+                add_trees_to_task_futures(
+                    &mut task_futures,
+                    &mut healthchecks,
+                    &pools,
+                    db_config,
+                    &config.operations_manager,
+                    &store_factory,
+                    stop_signals.receiver(Component::Tree.shutdown_phase()),
+                )
+                .await,
+            );
+            label_new_tasks(&mut task_labels, &task_futures, Component::Tree);
+            olaos_logs::info!("initialized Merkle Tree in {:?}", started_at.elapsed());
+        }
+
+        if components.contains(&Component::HttpApi) || components.contains(&Component::PubsubApi) {
+            let tx_sender_config =
+                TxSenderConfig::new(&config.sequencer, &api_config.web3_json_rpc);
+            let internal_api_config = InternalApiConfig::new(
+                &config.network,
+                &api_config.web3_json_rpc,
+                &contracts_config,
+            );
+            // Shared by the HTTP and WS servers, so that namespaces toggled via the admin API
+            // are toggled on both of them.
+            let method_filter = Arc::new(MethodFilter::new(
+                api_config.web3_json_rpc.methods_allowlist(),
+                api_config.web3_json_rpc.methods_denylist(),
+            ));
+            let web3_config = &api_config.web3_json_rpc;
+            let access_log = web3_config
+                .access_log_sample_rate
+                .map(|sample_rate| {
+                    AccessLog::new(
+                        sample_rate,
+                        web3_config
+                            .access_log_error_sample_rate
+                            .unwrap_or(sample_rate),
+                        web3_config.access_log_trusted_proxies(),
+                        web3_config.access_log_path.as_ref().map(PathBuf::from),
+                        web3_config.access_log_max_file_size(),
+                        web3_config.access_log_redact_ips(),
+                    )
+                })
+                .transpose()
+                .context("failed initializing RPC access log")?
+                .map(Arc::new);
+
+            if components.contains(&Component::HttpApi) {
+                let storage_caches = build_storage_caches(
+                    &api_config.web3_json_rpc,
+                    &replica_connection_pool,
+                    &mut task_futures,
+                );
+
+                let call_output_store =
+                    if web3_config.spill_call_outputs() {
+                        let store = Arc::new(CallOutputStore::new(
+                            store_factory.create_store().await,
+                            connection_pool.clone(),
+                            web3_config,
+                        ));
+                        task_futures.push(tokio::spawn(store.clone().run_pruning(
+                            stop_signals.receiver(Component::HttpApi.shutdown_phase()),
+                        )));
+                        Some(store)
+                    } else {
+                        None
+                    };
+
+                let started_at = Instant::now();
+                olaos_logs::info!("initializing HTTP API");
+                let (futures, health_check) = run_http_api(
+                    api_config,
+                    &config.sequencer,
+                    db_config,
+                    &internal_api_config,
+                    &tx_sender_config,
+                    connection_pool.clone(),
+                    replica_connection_pool.clone(),
+                    stop_signals.receiver(Component::HttpApi.shutdown_phase()),
+                    storage_caches,
+                    mempool.clone(),
+                    pending_state.clone(),
+                    seal_events.clone(),
+                    tree_reader.clone(),
+                    archive_store.clone(),
+                    call_output_store,
+                    method_filter.clone(),
+                    access_log.clone(),
+                    &components,
+                )
+                .await;
+                task_futures.extend(futures);
+                label_new_tasks(&mut task_labels, &task_futures, Component::HttpApi);
+                healthchecks.push(Box::new(health_check));
+                olaos_logs::info!("initialized HTTP API in {:?}", started_at.elapsed());
+            }
+
+            if components.contains(&Component::PubsubApi) {
+                let started_at = Instant::now();
+                olaos_logs::info!("initializing PubsubApi API");
+                let max_concurrency = web3_config.vm_concurrency_limit();
+                let (_, vm_barrier) = VmConcurrencyLimiter::new(max_concurrency);
+
+                // Verification tasks are only streamed to the committee if attestations are enabled.
+                let mut namespaces = vec![Namespace::Pubsub];
+                let mut proof_store = None;
+                if config.offchain_verifier.attestations_enabled() {
+                    namespaces.push(Namespace::Verifier);
+                    proof_store = Some(store_factory.create_store().await);
+                }
+                let server_handles = web3::ApiBuilder::pubsub_backend(
+                    internal_api_config,
+                    replica_connection_pool.clone(),
+                )
+                .ws(config.offchain_verifier.port)
+                .with_filters_limit(web3_config.filters_limit())
+                .with_subscriptions_limit(web3_config.subscriptions_limit())
+                .with_subscription_buffer(
+                    web3_config.subscription_buffer_size(),
+                    web3_config.subscription_overflow_policy(),
+                )
+                .with_max_subscriptions_per_connection(
+                    web3_config.max_subscriptions_per_connection(),
+                )
+                .with_subscription_replay_window(web3_config.subscription_replay_window())
+                .with_batch_request_size_limit(web3_config.max_batch_request_size())
+                .with_response_body_size_limit(web3_config.max_response_body_size())
+                .with_polling_interval(web3_config.pubsub_interval())
+                .with_threads(web3_config.ws_server_threads())
+                .with_vm_barrier(vm_barrier)
+                .with_method_filter(method_filter)
+                .with_access_log(access_log)
+                .with_proof_store(proof_store)
+                .with_verifier_committee(VerifierCommittee::from_config(&config.offchain_verifier))
+                .enable_api_namespaces(namespaces)
+                .build_ws_new(stop_signals.receiver(Component::PubsubApi.shutdown_phase()))
+                .await
+                .context("run_pubsub_api")?;
+
+                task_futures.extend(server_handles.tasks);
+                label_new_tasks(&mut task_labels, &task_futures, Component::PubsubApi);
+                healthchecks.push(Box::new(server_handles.health_check));
+                olaos_logs::info!("initialized PubsubApi API in {:?}", started_at.elapsed());
+            }
+        }
+
+        if components.contains(&Component::Sequencer) {
+            let started_at = Instant::now();
+            olaos_logs::info!("initializing Sequencer");
+            add_sequencer_to_task_futures(
+                &mut task_futures,
+                &mut task_labels,
+                &mut healthchecks,
+                &contracts_config,
+                &pools,
+                config.sequencer.clone(),
+                db_config,
+                &config.mempool,
+                mempool.clone().expect("mempool is created for sequencer"),
+                pending_state.clone(),
+                seal_events.clone(),
+                &store_factory,
+                stop_signals.receiver(Component::Sequencer.shutdown_phase()),
+            )
+            .await;
+            label_new_tasks(&mut task_labels, &task_futures, Component::Sequencer);
+            olaos_logs::info!("initialized Sequencer in {:?}", started_at.elapsed());
+        }
+
+        if components.contains(&Component::WitnessInputProducer) {
+            let started_at = Instant::now();
+            olaos_logs::info!("initializing Merkle Tree");
+            let connection_pool = pools.master(Some(1)).await;
+            let _ = add_witness_input_producer_to_task_futures(
+                &mut task_futures,
+                &connection_pool,
+                &store_factory,
+                L2ChainId(config.network.ola_network_id),
+                stop_signals.receiver(Component::WitnessInputProducer.shutdown_phase()),
+            )
+            .await
+            .context("add_witness_input_producer_to_task_futures");
+            label_new_tasks(
+                &mut task_labels,
+                &task_futures,
+                Component::WitnessInputProducer,
+            );
+            olaos_logs::info!(
+                "initialized WitnessInputProducer in {:?}",
+                started_at.elapsed()
+            );
+        }
+
+        if components.contains(&Component::ProofDataHandler) {
+            task_futures.push(tokio::spawn(proof_data_handler::run_server(
+                config.proof_data_handler.clone(),
+                store_factory.create_store().await,
+                connection_pool.clone(),
+                stop_signals.receiver(Component::ProofDataHandler.shutdown_phase()),
+            )));
+            label_new_tasks(&mut task_labels, &task_futures, Component::ProofDataHandler);
+        }
+
+        if components.contains(&Component::TransactionArchiver) {
+            let retention_l1_batches = db_config
+                .tx_archive_retention_l1_batches
+                .context("tx_archive_retention_l1_batches is not set")?;
+            let archiver = TransactionArchiver::new(
+                connection_pool.clone(),
+                archive_store
+                    .clone()
+                    .expect("archive store is created for archiver"),
+                L2ChainId(config.network.ola_network_id),
+                retention_l1_batches,
+                db_config.tx_archive_interval(),
+                AuditLog::from_config(db_config),
+            );
+            task_futures.push(tokio::spawn(archiver.run(
+                stop_signals.receiver(Component::TransactionArchiver.shutdown_phase()),
+            )));
+            label_new_tasks(
+                &mut task_labels,
+                &task_futures,
+                Component::TransactionArchiver,
+            );
+        }
+
+        if components.contains(&Component::ConsistencyChecker) {
+            let checker = ConsistencyChecker::new(
+                connection_pool.clone(),
+                db_config.consistency_check_interval(),
+                db_config.consistency_repair_enabled(),
+            );
+            task_futures.push(tokio::spawn(checker.run(
+                stop_signals.receiver(Component::ConsistencyChecker.shutdown_phase()),
+            )));
+            label_new_tasks(
+                &mut task_labels,
+                &task_futures,
+                Component::ConsistencyChecker,
+            );
+        }
+
+        if components.contains(&Component::ProofSmokeTest) {
+            let (smoke_test, smoke_test_health_check) = ProofSmokeTest::new(
+                &config.proof_data_handler,
+                connection_pool.clone(),
+                store_factory.create_store().await,
+            );
+            task_futures.push(tokio::spawn(
+                smoke_test.run(stop_signals.receiver(Component::ProofSmokeTest.shutdown_phase())),
+            ));
+            label_new_tasks(&mut task_labels, &task_futures, Component::ProofSmokeTest);
+            healthchecks.push(Box::new(smoke_test_health_check));
+        }
+
+        healthchecks.push(Box::new(ConnectionPoolHealthCheck::new(
+            replica_connection_pool.clone(),
+        )));
+
+        let health_check_handle =
+            HealthCheckHandle::spawn_server(api_config.healthcheck.bind_addr(), healthchecks);
+        let tasks = task_labels.into_iter().zip(task_futures).collect();
+        let shutdown = Shutdown::new(
+            stop_signals,
+            vec![connection_pool.clone(), replica_connection_pool.clone()],
+            health_check_handle,
+        );
+        Ok(Node {
+            components,
+            connection_pool,
+            replica_connection_pool,
+            tasks,
+            shutdown,
+        })
+    }
+}
+
+/// Running node built by [`NodeBuilder`].
+#[derive(Debug)]
+pub struct Node {
+    components: Vec<Component>,
+    connection_pool: ConnectionPool,
+    replica_connection_pool: ConnectionPool,
+    tasks: Vec<(TaskLabel, JoinHandle<anyhow::Result<()>>)>,
+    shutdown: Shutdown,
+}
+
+impl Node {
+    pub fn components(&self) -> &[Component] {
+        &self.components
+    }
+
+    pub fn connection_pool(&self) -> &ConnectionPool {
+        &self.connection_pool
+    }
+
+    pub fn replica_connection_pool(&self) -> &ConnectionPool {
+        &self.replica_connection_pool
+    }
+
+    /// Runs the node until `stop_signal` resolves or any of its tasks terminates, then shuts
+    /// the node down. Returns the component whose task has terminated, if any; termination
+    /// of a task is unexpected, so callers should treat it as a failure.
+    pub async fn run(mut self, stop_signal: impl Future<Output = ()>) -> Option<Component> {
+        olaos_logs::info!("Running {} core task handlers", self.tasks.len());
+        let terminated_component = if self.tasks.is_empty() {
+            stop_signal.await;
+            None
+        } else {
+            tokio::select! {
+                (TaskLabel { component, .. }, _) = wait_for_first_task(&mut self.tasks) => {
+                    olaos_logs::info!(
+                        "Component {component:?} terminated, shutting down other components"
+                    );
+                    Some(component)
+                }
+                () = stop_signal => {
+                    olaos_logs::info!("Stop signal received, shutting down");
+                    None
+                }
+            }
+        };
+        self.shutdown.run(self.tasks).await;
+        olaos_logs::info!("Stopped");
+        terminated_component
+    }
+
+    /// Shuts the node down right away.
+    pub async fn shutdown(self) {
+        self.shutdown.run(self.tasks).await;
+    }
+}
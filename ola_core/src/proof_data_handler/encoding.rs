@@ -0,0 +1,136 @@
+//! Content negotiation for the proof data handler API. Requests with `Content-Type: application/x-protobuf`
+//! are decoded with protobuf, and responses are encoded with protobuf if the client accepts it
+//! (or, if there's no preference in `Accept`, if the request is encoded with protobuf).
+//! JSON is used otherwise, so existing clients are unaffected.
+
+use std::fmt;
+
+use axum::{
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+    Json,
+};
+use ola_types::prover_server_api::proto::PROTOBUF_CONTENT_TYPE;
+use prost::Message;
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::request_processor::RequestProcessorError;
+
+const JSON_CONTENT_TYPE: &str = "application/json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WireFormat {
+    Json,
+    Protobuf,
+}
+
+impl WireFormat {
+    fn lists(value: &HeaderValue, media_type: &str) -> bool {
+        value.to_str().map_or(false, |value| {
+            value
+                .split(',')
+                .any(|item| item.trim().starts_with(media_type))
+        })
+    }
+
+    fn of_request(headers: &HeaderMap) -> Self {
+        match headers.get(header::CONTENT_TYPE) {
+            Some(value) if Self::lists(value, PROTOBUF_CONTENT_TYPE) => Self::Protobuf,
+            _ => Self::Json,
+        }
+    }
+
+    fn of_response(headers: &HeaderMap) -> Self {
+        match headers.get(header::ACCEPT) {
+            Some(value) if Self::lists(value, PROTOBUF_CONTENT_TYPE) => Self::Protobuf,
+            Some(value) if Self::lists(value, JSON_CONTENT_TYPE) => Self::Json,
+            _ => Self::of_request(headers),
+        }
+    }
+}
+
+/// Decodes the request body of type `T`, which is represented by `P` in protobuf.
+pub(super) fn decode_request<T, P>(
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<T, RequestProcessorError>
+where
+    T: DeserializeOwned + TryFrom<P>,
+    T::Error: fmt::Display,
+    P: Message + Default,
+{
+    match WireFormat::of_request(headers) {
+        WireFormat::Json => serde_json::from_slice(body)
+            .map_err(|err| RequestProcessorError::InvalidRequest(err.to_string())),
+        WireFormat::Protobuf => {
+            let message = P::decode(body)
+                .map_err(|err| RequestProcessorError::InvalidRequest(err.to_string()))?;
+            T::try_from(message)
+                .map_err(|err| RequestProcessorError::InvalidRequest(err.to_string()))
+        }
+    }
+}
+
+/// Encodes the `response` of type `T`, which is represented by `P` in protobuf.
+pub(super) fn encode_response<T, P>(
+    headers: &HeaderMap,
+    response: T,
+) -> Result<Response, RequestProcessorError>
+where
+    T: Serialize,
+    P: Message + TryFrom<T>,
+    P::Error: fmt::Display,
+{
+    Ok(match WireFormat::of_response(headers) {
+        WireFormat::Json => Json(response).into_response(),
+        WireFormat::Protobuf => {
+            let message = P::try_from(response)
+                .map_err(|err| RequestProcessorError::Encoding(err.to_string()))?;
+            (
+                [(header::CONTENT_TYPE, PROTOBUF_CONTENT_TYPE)],
+                message.encode_to_vec(),
+            )
+                .into_response()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(header::HeaderName, &'static str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.clone(), HeaderValue::from_static(value)))
+            .collect()
+    }
+
+    #[test]
+    fn negotiating_wire_format() {
+        assert_eq!(WireFormat::of_response(&HeaderMap::new()), WireFormat::Json);
+
+        let protobuf_request = headers(&[(header::CONTENT_TYPE, PROTOBUF_CONTENT_TYPE)]);
+        assert_eq!(
+            WireFormat::of_request(&protobuf_request),
+            WireFormat::Protobuf
+        );
+        assert_eq!(
+            WireFormat::of_response(&protobuf_request),
+            WireFormat::Protobuf
+        );
+
+        let json_accepted = headers(&[
+            (header::CONTENT_TYPE, PROTOBUF_CONTENT_TYPE),
+            (header::ACCEPT, "application/json"),
+        ]);
+        assert_eq!(WireFormat::of_response(&json_accepted), WireFormat::Json);
+
+        let protobuf_accepted = headers(&[(header::ACCEPT, "text/plain, application/x-protobuf")]);
+        assert_eq!(WireFormat::of_request(&protobuf_accepted), WireFormat::Json);
+        assert_eq!(
+            WireFormat::of_response(&protobuf_accepted),
+            WireFormat::Protobuf
+        );
+    }
+}
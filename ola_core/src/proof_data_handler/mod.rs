@@ -1,18 +1,25 @@
 use std::{net::SocketAddr, sync::Arc};
 
 use anyhow::{Context, Ok};
-use axum::{extract::Path, routing::post, Json, Router};
+use axum::{body::Bytes, extract::Path, http::HeaderMap, routing::post, Router};
 use ola_config::proof_data_handler::ProofDataHandlerConfig;
 use ola_dal::connection::ConnectionPool;
 use ola_types::{
     protocol_version::L1VerifierConfig,
-    prover_server_api::{ProofGenerationDataRequest, SubmitProofRequest},
+    prover_server_api::{
+        proto, ProofGenerationDataRequest, ProofGenerationDataResponse, SubmitProofRequest,
+        SubmitProofResponse, PROVER_ID_HEADER,
+    },
 };
 use olaos_object_store::ObjectStore;
 use tokio::sync::watch;
 
-use crate::proof_data_handler::request_processor::RequestProcessor;
+use crate::proof_data_handler::{
+    encoding::{decode_request, encode_response},
+    request_processor::RequestProcessor,
+};
 
+mod encoding;
 mod request_processor;
 
 pub(crate) async fn run_server(
@@ -34,20 +41,37 @@ pub(crate) async fn run_server(
             post(
                 // we use post method because the returned data is not idempotent,
                 // i.e we return different result on each call.
-                |payload: Json<ProofGenerationDataRequest>| async move {
-                    get_proof_gen_processor
-                        .get_proof_generation_data(payload)
-                        .await
+                |headers: HeaderMap, body: Bytes| async move {
+                    let request = decode_request::<
+                        ProofGenerationDataRequest,
+                        proto::ProofGenerationDataRequest,
+                    >(&headers, &body)?;
+                    let response = get_proof_gen_processor
+                        .get_proof_generation_data(request)
+                        .await?;
+                    encode_response::<ProofGenerationDataResponse, proto::ProofGenerationDataResponse>(
+                        &headers, response,
+                    )
                 },
             ),
         )
         .route(
             "/submit_proof/:l1_batch_number",
             post(
-                move |l1_batch_number: Path<u32>, payload: Json<SubmitProofRequest>| async move {
-                    submit_proof_processor
-                        .submit_proof(l1_batch_number, payload)
-                        .await
+                move |Path(l1_batch_number): Path<u32>, headers: HeaderMap, body: Bytes| async move {
+                    let request = decode_request::<SubmitProofRequest, proto::SubmitProofRequest>(
+                        &headers, &body,
+                    )?;
+                    let submitter = headers
+                        .get(PROVER_ID_HEADER)
+                        .and_then(|value| value.to_str().ok())
+                        .map(str::to_owned);
+                    let response = submit_proof_processor
+                        .submit_proof(l1_batch_number, request, submitter)
+                        .await?;
+                    encode_response::<SubmitProofResponse, proto::SubmitProofResponse>(
+                        &headers, response,
+                    )
                 },
             ),
         );
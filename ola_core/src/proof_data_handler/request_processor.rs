@@ -1,14 +1,14 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::Path,
     http::StatusCode,
     response::{IntoResponse, Response},
     Json,
 };
 use ola_config::proof_data_handler::{ProofDataHandlerConfig, ProtocolVersionLoadingMode};
-use ola_dal::{connection::ConnectionPool, SqlxError};
+use ola_dal::{connection::ConnectionPool, proof_generation_dal::ProofSubmission, DalError};
 use ola_types::{
+    proofs::L1BatchProofForL1,
     protocol_version::{FriProtocolVersionId, L1VerifierConfig},
     prover_server_api::{
         ProofGenerationData, ProofGenerationDataRequest, ProofGenerationDataResponse,
@@ -16,16 +16,40 @@ use ola_types::{
     },
     L1BatchNumber,
 };
+use ola_utils::hash::hash_bytes;
 use olaos_object_store::{ObjectStore, ObjectStoreError};
 
 pub(crate) enum RequestProcessorError {
     ObjectStore(ObjectStoreError),
-    Sqlx(SqlxError),
+    Dal(DalError),
+    InvalidRequest(String),
+    Encoding(String),
+    /// A different proof was already submitted for the L1 batch, or its proof generation
+    /// was skipped.
+    ProofConflict {
+        l1_batch_number: L1BatchNumber,
+        submitter: Option<String>,
+    },
 }
 
 impl IntoResponse for RequestProcessorError {
     fn into_response(self) -> Response {
         let (status_code, message) = match self {
+            RequestProcessorError::ProofConflict {
+                l1_batch_number,
+                submitter,
+            } => {
+                olaos_logs::warn!(
+                    "Rejected conflicting proof for L1 batch #{l1_batch_number}; \
+                     the stored one was submitted by {submitter:?}"
+                );
+                let body = serde_json::json!({
+                    "error": "proofConflict",
+                    "l1BatchNumber": l1_batch_number.0,
+                    "submitter": submitter,
+                });
+                return (StatusCode::CONFLICT, Json(body)).into_response();
+            }
             RequestProcessorError::ObjectStore(err) => {
                 olaos_logs::error!("GCS error: {:?}", err);
                 (
@@ -33,18 +57,32 @@ impl IntoResponse for RequestProcessorError {
                     "Failed fetching/saving from GCS".to_owned(),
                 )
             }
-            RequestProcessorError::Sqlx(err) => {
-                olaos_logs::error!("Sqlx error: {:?}", err);
+            RequestProcessorError::Dal(err) => {
+                olaos_logs::error!("DAL error: {:?}", err);
                 match err {
-                    SqlxError::RowNotFound => {
+                    DalError::NotFound => {
                         (StatusCode::NOT_FOUND, "Non existing L1 batch".to_owned())
                     }
+                    err if err.is_retriable() => (
+                        StatusCode::SERVICE_UNAVAILABLE,
+                        "Database is temporarily unavailable".to_owned(),
+                    ),
                     _ => (
                         StatusCode::BAD_GATEWAY,
                         "Failed fetching/saving from db".to_owned(),
                     ),
                 }
             }
+            RequestProcessorError::InvalidRequest(err) => {
+                (StatusCode::BAD_REQUEST, format!("Invalid request: {err}"))
+            }
+            RequestProcessorError::Encoding(err) => {
+                olaos_logs::error!("Failed encoding response: {err}");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed encoding response".to_owned(),
+                )
+            }
         };
         (status_code, message).into_response()
     }
@@ -75,8 +113,8 @@ impl RequestProcessor {
 
     pub(crate) async fn get_proof_generation_data(
         &self,
-        request: Json<ProofGenerationDataRequest>,
-    ) -> Result<Json<ProofGenerationDataResponse>, RequestProcessorError> {
+        request: ProofGenerationDataRequest,
+    ) -> Result<ProofGenerationDataResponse, RequestProcessorError> {
         olaos_logs::info!("Received request for proof generation data: {:?}", request);
 
         let l1_batch_number_result = self
@@ -85,11 +123,12 @@ impl RequestProcessor {
             .await
             .proof_generation_dal()
             .get_next_block_to_be_proven(self.config.proof_generation_timeout())
-            .await;
+            .await
+            .map_err(RequestProcessorError::Dal)?;
 
         let l1_batch_number = match l1_batch_number_result {
             Some(number) => number,
-            None => return Ok(Json(ProofGenerationDataResponse::Success(None))), // no batches pending to be proven
+            None => return Ok(ProofGenerationDataResponse::Success(None)), // no batches pending to be proven
         };
 
         let blob = self
@@ -119,44 +158,124 @@ impl RequestProcessor {
             l1_verifier_config,
         };
 
-        Ok(Json(ProofGenerationDataResponse::Success(Some(
-            proof_gen_data,
-        ))))
+        Ok(ProofGenerationDataResponse::Success(Some(proof_gen_data)))
     }
 
+    /// Saves the submitted proof. Resubmitting the stored proof succeeds without changes, while
+    /// submitting a different one results in [`RequestProcessorError::ProofConflict`].
     pub(crate) async fn submit_proof(
         &self,
-        Path(l1_batch_number): Path<u32>,
-        Json(payload): Json<SubmitProofRequest>,
-    ) -> Result<Json<SubmitProofResponse>, RequestProcessorError> {
-        olaos_logs::info!("Received proof for block number: {:?}", l1_batch_number);
+        l1_batch_number: u32,
+        payload: SubmitProofRequest,
+        submitter: Option<String>,
+    ) -> Result<SubmitProofResponse, RequestProcessorError> {
+        olaos_logs::info!(
+            "Received proof for block number: {:?} from {:?}",
+            l1_batch_number,
+            submitter
+        );
         let l1_batch_number = L1BatchNumber(l1_batch_number);
+        let mut storage = self.pool.access_storage().await;
+        // The row of the L1 batch stays locked until the proof is saved, so that concurrent
+        // submissions can't overwrite each other's blobs.
+        let mut transaction = storage.start_transaction().await;
+        let submission = transaction
+            .proof_generation_dal()
+            .lock_proof_submission(l1_batch_number)
+            .await
+            .map_err(RequestProcessorError::Dal)?;
+
         match payload {
             SubmitProofRequest::Proof(proof) => {
+                let proof_hash = hash_bytes(&proof.proof);
+                match submission {
+                    ProofSubmission::Pending => {}
+                    ProofSubmission::Generated {
+                        proof_hash: Some(stored_hash),
+                        ..
+                    } if stored_hash == proof_hash => {
+                        olaos_logs::info!("Proof for L1 batch #{l1_batch_number} is already saved");
+                        return Ok(SubmitProofResponse::Success);
+                    }
+                    ProofSubmission::Generated {
+                        proof_hash: None,
+                        submitter: stored_submitter,
+                    } => {
+                        // The proof was saved before proof hashes were recorded.
+                        let stored_proof: L1BatchProofForL1 = self
+                            .blob_store
+                            .get(l1_batch_number)
+                            .await
+                            .map_err(RequestProcessorError::ObjectStore)?;
+                        if stored_proof.proof == proof.proof {
+                            return Ok(SubmitProofResponse::Success);
+                        }
+                        return Err(RequestProcessorError::ProofConflict {
+                            l1_batch_number,
+                            submitter: stored_submitter,
+                        });
+                    }
+                    ProofSubmission::Generated {
+                        submitter: stored_submitter,
+                        ..
+                    }
+                    | ProofSubmission::Skipped {
+                        submitter: stored_submitter,
+                    } => {
+                        return Err(RequestProcessorError::ProofConflict {
+                            l1_batch_number,
+                            submitter: stored_submitter,
+                        });
+                    }
+                }
+
                 let blob_url = self
                     .blob_store
                     .put(l1_batch_number, &*proof)
                     .await
                     .map_err(RequestProcessorError::ObjectStore)?;
-
-                let mut storage = self.pool.access_storage().await;
-
-                storage
+                transaction
                     .proof_generation_dal()
-                    .save_proof_artifacts_metadata(l1_batch_number, &blob_url)
+                    .save_proof_artifacts_metadata(
+                        l1_batch_number,
+                        &blob_url,
+                        proof_hash,
+                        submitter.as_deref(),
+                    )
                     .await
-                    .map_err(RequestProcessorError::Sqlx)?;
-            }
-            SubmitProofRequest::SkippedProofGeneration => {
-                self.pool
-                    .access_storage()
-                    .await
-                    .proof_generation_dal()
-                    .mark_proof_generation_job_as_skipped(l1_batch_number)
+                    .map_err(RequestProcessorError::Dal)?;
+                // Makes the batch available to offchain verifiers; batches that are already
+                // registered are left as is.
+                match transaction
+                    .proof_verification_dal()
+                    .insert_l1_batch_to_be_verified(l1_batch_number)
                     .await
-                    .map_err(RequestProcessorError::Sqlx)?;
+                {
+                    Ok(()) | Err(DalError::NotFound) => {}
+                    Err(err) => return Err(RequestProcessorError::Dal(err)),
+                }
             }
+            SubmitProofRequest::SkippedProofGeneration => match submission {
+                ProofSubmission::Pending => {
+                    transaction
+                        .proof_generation_dal()
+                        .mark_proof_generation_job_as_skipped(l1_batch_number, submitter.as_deref())
+                        .await
+                        .map_err(RequestProcessorError::Dal)?;
+                }
+                ProofSubmission::Skipped { .. } => return Ok(SubmitProofResponse::Success),
+                ProofSubmission::Generated {
+                    submitter: stored_submitter,
+                    ..
+                } => {
+                    return Err(RequestProcessorError::ProofConflict {
+                        l1_batch_number,
+                        submitter: stored_submitter,
+                    });
+                }
+            },
         }
-        Ok(Json(SubmitProofResponse::Success))
+        transaction.commit().await;
+        Ok(SubmitProofResponse::Success)
     }
 }
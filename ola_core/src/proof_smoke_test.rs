@@ -0,0 +1,316 @@
+//! End-to-end smoke test of the proving pipeline for devnets.
+//!
+//! [`ProofSmokeTest`] waits for newly sealed L1 batches and checks each of them in-process once its
+//! witness inputs are ready (i.e., the Merkle tree has processed the batch and the witness input
+//! producer has saved its block state): it generates the basic circuits witness the same way as
+//! the witness generator does and, if the base layer circuit of the batch is available in
+//! the object store, proves it with a reduced number of FRI query rounds and verifies the proof.
+//!
+//! Failures don't terminate the node: they are reported in metrics and in the health details of
+//! the component, so that witness or circuit regressions are noticed right away rather than after
+//! deploying provers.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context as _;
+use ola_config::proof_data_handler::ProofDataHandlerConfig;
+use ola_dal::connection::ConnectionPool;
+use ola_types::{
+    proofs::{AggregationRound, PrepareBasicCircuitsJob},
+    witness_block_state::WitnessBlockState,
+    L1BatchNumber,
+};
+use ola_utils::panic_extractor::try_extract_panic_message;
+use olaos_health_check::{Health, HealthStatus, HealthUpdater, ReactiveHealthCheck};
+use olaos_object_store::{FriCircuitKey, ObjectStore, ObjectStoreError};
+use olaos_prover_fri_types::{
+    prove_with_traces, verifier, CircuitWrapper, OlaBaseLayerCircuit, StarkConfig, TimingTree, C,
+    D, F,
+};
+use olaos_witness_generator::basic_circuits::generate_basic_circuits_witness;
+use serde::Serialize;
+use tokio::sync::watch;
+
+/// Result of a check of an L1 batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmokeTestOutcome {
+    /// Witness inputs of the batch aren't ready yet; the batch is checked again later.
+    NotReady,
+    /// The witness was generated, and the batch proof was generated and verified.
+    Proven,
+    /// The witness was generated, but the base layer circuit isn't available.
+    WitnessOnly,
+}
+
+impl SmokeTestOutcome {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::NotReady => "not_ready",
+            Self::Proven => "proven",
+            Self::WitnessOnly => "witness_only",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct SmokeTestFailure {
+    l1_batch: L1BatchNumber,
+    error: String,
+}
+
+/// Progress of the smoke test, reported in the health details.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+struct SmokeTestStatus {
+    last_checked_l1_batch: Option<L1BatchNumber>,
+    failed_l1_batches: u64,
+    last_failure: Option<SmokeTestFailure>,
+}
+
+impl SmokeTestStatus {
+    fn record(&mut self, l1_batch: L1BatchNumber, result: &anyhow::Result<SmokeTestOutcome>) {
+        self.last_checked_l1_batch = Some(l1_batch);
+        if let Err(err) = result {
+            self.failed_l1_batches += 1;
+            self.last_failure = Some(SmokeTestFailure {
+                l1_batch,
+                error: format!("{err:#}"),
+            });
+        }
+    }
+}
+
+impl From<SmokeTestStatus> for Health {
+    fn from(status: SmokeTestStatus) -> Self {
+        // Failed checks are reported in details only; they don't affect the readiness of the node.
+        Self::from(HealthStatus::Ready).with_details(status)
+    }
+}
+
+#[derive(Debug)]
+pub struct ProofSmokeTest {
+    pool: ConnectionPool,
+    object_store: Arc<dyn ObjectStore>,
+    interval: Duration,
+    fri_query_rounds: usize,
+    health_updater: HealthUpdater,
+}
+
+impl ProofSmokeTest {
+    pub fn new(
+        config: &ProofDataHandlerConfig,
+        pool: ConnectionPool,
+        object_store: Arc<dyn ObjectStore>,
+    ) -> (Self, ReactiveHealthCheck) {
+        let (health_check, health_updater) = ReactiveHealthCheck::new("proof_smoke_test");
+        let this = Self {
+            pool,
+            object_store,
+            interval: config.proof_smoke_test_interval(),
+            fri_query_rounds: config.proof_smoke_test_fri_query_rounds,
+            health_updater,
+        };
+        (this, health_check)
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        // Batches sealed before the start are not checked; the smoke test is about new batches.
+        let mut last_checked_l1_batch = self
+            .pool
+            .access_storage_tagged("proof_smoke_test")
+            .await
+            .blocks_dal()
+            .get_sealed_l1_batch_number()
+            .await
+            .context("get_sealed_l1_batch_number()")?;
+        olaos_logs::info!(
+            "Starting proof smoke test after L1 batch #{last_checked_l1_batch}, \
+             using {} FRI query rounds",
+            self.fri_query_rounds
+        );
+        let mut status = SmokeTestStatus::default();
+        self.health_updater.update(status.clone().into());
+
+        loop {
+            if *stop_receiver.borrow_and_update() {
+                break;
+            }
+            let sealed_l1_batch = self
+                .pool
+                .access_storage_tagged("proof_smoke_test")
+                .await
+                .blocks_dal()
+                .get_sealed_l1_batch_number()
+                .await
+                .context("get_sealed_l1_batch_number()")?;
+            while last_checked_l1_batch < sealed_l1_batch {
+                let l1_batch_number = last_checked_l1_batch + 1;
+                let result = self.check_l1_batch(l1_batch_number).await;
+                if let Ok(SmokeTestOutcome::NotReady) = result {
+                    break;
+                }
+                let outcome = match &result {
+                    Ok(outcome) => outcome.as_str(),
+                    Err(err) => {
+                        olaos_logs::error!(
+                            "Proof smoke test failed for L1 batch #{l1_batch_number}: {err:#}"
+                        );
+                        "failed"
+                    }
+                };
+                metrics::counter!("server.proof_smoke_test.l1_batches", 1, "outcome" => outcome);
+                status.record(l1_batch_number, &result);
+                self.health_updater.update(status.clone().into());
+                last_checked_l1_batch = l1_batch_number;
+            }
+
+            tokio::select! {
+                _ = stop_receiver.changed() => break,
+                () = tokio::time::sleep(self.interval) => { /* Continue checking */ }
+            }
+        }
+        olaos_logs::info!("Stop signal received, proof smoke test is shutting down");
+        Ok(())
+    }
+
+    pub async fn check_l1_batch(
+        &self,
+        l1_batch_number: L1BatchNumber,
+    ) -> anyhow::Result<SmokeTestOutcome> {
+        let Some(job) = self.load_witness_inputs(l1_batch_number).await? else {
+            return Ok(SmokeTestOutcome::NotReady);
+        };
+
+        let started_at = Instant::now();
+        let object_store = self.object_store.clone();
+        let pool = self.pool.clone();
+        // Witness generation panics on some malformed inputs; the panic is reported as a failure.
+        tokio::spawn(async move {
+            generate_basic_circuits_witness(&*object_store, pool, l1_batch_number, job).await
+        })
+        .await
+        .map_err(|err| {
+            anyhow::anyhow!(
+                "witness generation panicked: {}",
+                try_extract_panic_message(err)
+            )
+        })?
+        .context("failed generating witness")?;
+        report_stage_time("witness", started_at.elapsed());
+
+        let key = FriCircuitKey {
+            block_number: l1_batch_number,
+            sequence_number: 0,
+            circuit_id: 0,
+            aggregation_round: AggregationRound::BasicCircuits,
+            depth: 0,
+        };
+        let circuit = match self.object_store.get::<CircuitWrapper>(key).await {
+            Ok(CircuitWrapper::Base(circuit)) => circuit,
+            Err(ObjectStoreError::KeyNotFound(_)) => {
+                olaos_logs::info!(
+                    "Generated witness for L1 batch #{l1_batch_number}; \
+                     base layer circuit is not available, skipping the proof"
+                );
+                return Ok(SmokeTestOutcome::WitnessOnly);
+            }
+            Err(err) => {
+                return Err(err).context("failed loading base layer circuit");
+            }
+        };
+
+        let started_at = Instant::now();
+        let fri_query_rounds = self.fri_query_rounds;
+        tokio::task::spawn_blocking(move || prove_and_verify(circuit, fri_query_rounds))
+            .await
+            .map_err(|err| {
+                anyhow::anyhow!("proving panicked: {}", try_extract_panic_message(err))
+            })??;
+        report_stage_time("proof", started_at.elapsed());
+        olaos_logs::info!(
+            "Generated and verified reduced-size proof of L1 batch #{l1_batch_number}"
+        );
+        Ok(SmokeTestOutcome::Proven)
+    }
+
+    /// Loads the witness generator job for the L1 batch. Returns `None` if the Merkle tree or
+    /// the witness input producer hasn't processed the batch yet.
+    async fn load_witness_inputs(
+        &self,
+        l1_batch_number: L1BatchNumber,
+    ) -> anyhow::Result<Option<PrepareBasicCircuitsJob>> {
+        let job = match self
+            .object_store
+            .get::<PrepareBasicCircuitsJob>(l1_batch_number)
+            .await
+        {
+            // The sequencer saves the job with transaction traces only; the tree adds storage.
+            Ok(job) if job.storage.is_some() => job,
+            Ok(_) | Err(ObjectStoreError::KeyNotFound(_)) => return Ok(None),
+            Err(err) => return Err(err).context("failed loading witness generator job"),
+        };
+        match self
+            .object_store
+            .get::<WitnessBlockState>(l1_batch_number)
+            .await
+        {
+            Ok(_) => Ok(Some(job)),
+            Err(ObjectStoreError::KeyNotFound(_)) => Ok(None),
+            Err(err) => Err(err).context("failed loading witness block state"),
+        }
+    }
+}
+
+fn reduced_config(config: &StarkConfig, fri_query_rounds: usize) -> StarkConfig {
+    let mut config = config.clone();
+    config.fri_config.num_query_rounds = config.fri_config.num_query_rounds.min(fri_query_rounds);
+    config
+}
+
+fn prove_and_verify(circuit: OlaBaseLayerCircuit, fri_query_rounds: usize) -> anyhow::Result<()> {
+    let config = reduced_config(&circuit.config, fri_query_rounds);
+    let proof = prove_with_traces::<F, C, D>(
+        &circuit.ola_stark,
+        &config,
+        circuit.witness,
+        circuit.public_values,
+        &mut TimingTree::default(),
+    )
+    .context("failed generating proof")?;
+    verifier::verify_proof::<F, C, D>(circuit.ola_stark, proof, &config)
+        .context("failed verifying proof")
+}
+
+fn report_stage_time(stage: &'static str, elapsed: Duration) {
+    metrics::histogram!("server.proof_smoke_test.stage_time", elapsed, "stage" => stage);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failures_are_reported_in_status() {
+        let mut status = SmokeTestStatus::default();
+        status.record(L1BatchNumber(1), &Ok(SmokeTestOutcome::Proven));
+        assert_eq!(status.last_checked_l1_batch, Some(L1BatchNumber(1)));
+        assert_eq!(status.failed_l1_batches, 0);
+
+        status.record(L1BatchNumber(2), &Err(anyhow::anyhow!("bogus proof")));
+        status.record(L1BatchNumber(3), &Ok(SmokeTestOutcome::WitnessOnly));
+        assert_eq!(status.last_checked_l1_batch, Some(L1BatchNumber(3)));
+        assert_eq!(status.failed_l1_batches, 1);
+        assert_eq!(
+            status.last_failure,
+            Some(SmokeTestFailure {
+                l1_batch: L1BatchNumber(2),
+                error: "bogus proof".to_owned(),
+            })
+        );
+
+        let health = Health::from(status);
+        assert_eq!(health.status(), HealthStatus::Ready);
+    }
+}
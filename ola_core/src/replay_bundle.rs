@@ -0,0 +1,231 @@
+//! Replay bundles of L1 batches (`ola_node replay-bundle`).
+//!
+//! A [`ReplayBundle`] is a JSON file with everything a third party needs to re-execute an L1 batch
+//! and check its state root without access to the node databases: the transactions grouped by
+//! miniblock, the bytecodes they use, the storage trace with Merkle paths against the root of
+//! the previous batch (produced by the Merkle tree while processing the batch), and the expected
+//! post-state. The storage trace is read from the witness inputs in the object store, so bundles
+//! can only be created for batches already processed by the tree.
+
+use std::{fs, path::PathBuf};
+
+use anyhow::Context as _;
+use ola_dal::connection::ConnectionPool;
+use ola_types::{
+    merkle_tree::tree_value_to_h256,
+    proofs::PrepareBasicCircuitsJob,
+    replay_bundle::{ReplayBundle, ReplayFactoryDep, ReplayMiniblock, ReplayStorageWrite},
+    L1BatchNumber, MiniblockNumber, H256,
+};
+use olaos_object_store::ObjectStore;
+
+use crate::witness_input_producer::WitnessInputProducer;
+
+/// Arguments of `ola_node replay-bundle`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayBundleArgs {
+    pub l1_batch_number: L1BatchNumber,
+    pub output_path: PathBuf,
+}
+
+impl ReplayBundleArgs {
+    pub const USAGE: &'static str =
+        "usage: ola_node replay-bundle --l1-batch <number> [--output <file>]";
+
+    /// Parses arguments following the `replay-bundle` subcommand.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Self> {
+        let mut l1_batch_number = None;
+        let mut output_path = None;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .with_context(|| format!("missing value for `{arg}`; {}", Self::USAGE))?;
+            match arg.as_str() {
+                "--l1-batch" => l1_batch_number = Some(L1BatchNumber(value.parse()?)),
+                "--output" => output_path = Some(PathBuf::from(value)),
+                _ => anyhow::bail!("unknown argument `{arg}`; {}", Self::USAGE),
+            }
+        }
+
+        let l1_batch_number: L1BatchNumber = l1_batch_number.context(Self::USAGE)?;
+        anyhow::ensure!(
+            l1_batch_number.0 > 0,
+            "replay bundles cannot be created for the genesis L1 batch"
+        );
+        let output_path = output_path
+            .unwrap_or_else(|| PathBuf::from(format!("replay_bundle_{l1_batch_number}.json")));
+        Ok(Self {
+            l1_batch_number,
+            output_path,
+        })
+    }
+}
+
+/// Collects the replay bundle for `l1_batch_number` from Postgres and the object store.
+pub async fn create_replay_bundle(
+    pool: &ConnectionPool,
+    object_store: &dyn ObjectStore,
+    l1_batch_number: L1BatchNumber,
+) -> anyhow::Result<ReplayBundle> {
+    let mut storage = pool.access_storage_tagged("replay_bundle").await;
+    let header = storage
+        .blocks_dal()
+        .get_l1_batch_header(l1_batch_number)
+        .await?
+        .with_context(|| format!("L1 batch #{l1_batch_number} is not sealed"))?;
+    let pre_root_hash = storage
+        .blocks_dal()
+        .get_l1_batch_state_root(l1_batch_number - 1)
+        .await?
+        .with_context(|| format!("L1 batch #{} has no state root", l1_batch_number - 1))?;
+    let post_root_hash = storage
+        .blocks_dal()
+        .get_l1_batch_state_root(l1_batch_number)
+        .await?
+        .with_context(|| {
+            format!("L1 batch #{l1_batch_number} is not processed by the Merkle tree yet")
+        })?;
+
+    let witness: PrepareBasicCircuitsJob =
+        object_store.get(l1_batch_number).await.with_context(|| {
+            format!("failed fetching witness inputs for L1 batch #{l1_batch_number}")
+        })?;
+    let storage_trace = witness.storage.with_context(|| {
+        format!("witness inputs for L1 batch #{l1_batch_number} have no storage trace")
+    })?;
+    check_root_hash(
+        "pre",
+        witness.pre_root_hash.map(|hash| tree_value_to_h256(&hash)),
+        pre_root_hash,
+    )?;
+    check_root_hash(
+        "post",
+        witness.root_hash.map(|hash| tree_value_to_h256(&hash)),
+        post_root_hash,
+    )?;
+
+    let miniblocks: Vec<_> = storage
+        .transactions_dal()
+        .get_l1_batch_miniblocks_to_reexecute(l1_batch_number)
+        .await?
+        .into_iter()
+        .map(|miniblock| ReplayMiniblock {
+            number: miniblock.number,
+            timestamp: miniblock.timestamp,
+            transactions: miniblock.txs,
+        })
+        .collect();
+
+    let (first_miniblock, last_miniblock) = storage
+        .blocks_dal()
+        .get_miniblock_range_of_l1_batch(l1_batch_number)
+        .await?
+        .with_context(|| format!("L1 batch #{l1_batch_number} has no miniblocks"))?;
+    let storage_writes = storage
+        .storage_logs_dal()
+        .get_storage_diff(MiniblockNumber(first_miniblock.0 - 1), last_miniblock)
+        .await?
+        .into_iter()
+        .map(|diff| ReplayStorageWrite {
+            address: *diff.key.address(),
+            key: *diff.key.key(),
+            before: diff.before.unwrap_or_default(),
+            after: diff.after.unwrap_or_default(),
+        })
+        .collect();
+    drop(storage);
+
+    let mut factory_deps: Vec<_> =
+        WitnessInputProducer::load_used_factory_deps(pool, l1_batch_number)
+            .await?
+            .into_iter()
+            .map(|(bytecode_hash, bytecode)| ReplayFactoryDep {
+                bytecode_hash,
+                bytecode,
+            })
+            .collect();
+    factory_deps.sort_unstable_by_key(|dep| dep.bytecode_hash);
+
+    Ok(ReplayBundle {
+        version: ReplayBundle::VERSION,
+        l1_batch_number,
+        header,
+        pre_root_hash,
+        post_root_hash,
+        miniblocks,
+        factory_deps,
+        storage: storage_trace,
+        storage_writes,
+    })
+}
+
+/// Checks that the root hash recorded in the witness inputs matches the one in Postgres.
+fn check_root_hash(kind: &str, witness_hash: Option<H256>, expected: H256) -> anyhow::Result<()> {
+    let witness_hash =
+        witness_hash.with_context(|| format!("witness inputs have no {kind}-state root hash"))?;
+    anyhow::ensure!(
+        witness_hash == expected,
+        "{kind}-state root hash in witness inputs ({witness_hash:?}) differs from the one \
+         in Postgres ({expected:?})"
+    );
+    Ok(())
+}
+
+/// Writes the replay bundle for the requested L1 batch to `args.output_path`.
+pub async fn run_create_replay_bundle(
+    pool: &ConnectionPool,
+    object_store: &dyn ObjectStore,
+    args: &ReplayBundleArgs,
+) -> anyhow::Result<()> {
+    let bundle = create_replay_bundle(pool, object_store, args.l1_batch_number).await?;
+    let json = serde_json::to_vec_pretty(&bundle)?;
+    fs::write(&args.output_path, json)
+        .with_context(|| format!("cannot write {}", args.output_path.display()))?;
+    olaos_logs::info!(
+        "Wrote replay bundle for L1 batch #{} with {} transactions to {}",
+        args.l1_batch_number,
+        bundle.transaction_count(),
+        args.output_path.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> anyhow::Result<ReplayBundleArgs> {
+        ReplayBundleArgs::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parsing_replay_bundle_args() {
+        assert_eq!(
+            args(&["--l1-batch", "5"]).unwrap(),
+            ReplayBundleArgs {
+                l1_batch_number: L1BatchNumber(5),
+                output_path: PathBuf::from("replay_bundle_5.json"),
+            }
+        );
+        assert_eq!(
+            args(&["--l1-batch", "5", "--output", "out.json"])
+                .unwrap()
+                .output_path,
+            PathBuf::from("out.json")
+        );
+
+        assert!(args(&[]).is_err());
+        assert!(args(&["--l1-batch", "0"]).is_err());
+        assert!(args(&["--batch", "5"]).is_err());
+    }
+
+    #[test]
+    fn checking_root_hashes() {
+        let hash = H256::repeat_byte(1);
+        assert!(check_root_hash("pre", Some(hash), hash).is_ok());
+        assert!(check_root_hash("pre", None, hash).is_err());
+        assert!(check_root_hash("post", Some(H256::zero()), hash).is_err());
+    }
+}
@@ -1,23 +1,30 @@
 use std::path::Path;
+use std::sync::{Arc, Mutex};
 use std::{fmt, time::Instant};
 
+use anyhow::Context as _;
 use async_trait::async_trait;
-use ola_config::sequencer::load_network_config;
+use ola_config::{
+    constants::contracts::{DEFAULT_ACCOUNT_ADDRESS, ENTRYPOINT_ADDRESS},
+    sequencer::load_network_config,
+};
+use ola_contracts::BaseSystemContracts;
 use ola_dal::connection::ConnectionPool;
 use ola_executor::batch_exe_manager::BlockExeManager;
 use ola_executor::tx_exe_manager::OlaTapeInitInfo;
 use ola_state::rocksdb::RocksdbStorage;
 use ola_types::{
-    block::TxExeTraces, events::VmEvent, log::StorageLogQuery, ExecuteTransactionCommon,
-    Transaction,
+    block::TxExeTraces, events::VmEvent, get_full_code_key, log::StorageLogQuery,
+    tx::tx_execution_info::ExecutionMetrics, ExecuteTransactionCommon, Transaction,
 };
 use ola_utils::{bytes_to_u64s, h256_to_u64_array};
 use ola_vm::errors::VmRevertReason;
 use ola_vm::vm::VmTxExeResult;
 use ola_vm::{
     errors::TxRevertReason,
-    vm::{VmBlockResult, VmExecutionResult, VmPartialExecutionResult},
+    vm::{cycles_in_trace, VmBlockResult, VmExecutionResult, VmPartialExecutionResult},
 };
+use olaos_storage::RocksDBOptions;
 use olavm_core::util::converts::u8_arr_to_address;
 use tokio::{
     sync::{mpsc, oneshot},
@@ -34,12 +41,32 @@ pub struct BatchExecutorHandle {
     commands: mpsc::Sender<Command>,
 }
 
+/// Estimate of the resources used by the block tip, i.e. the entrypoint logic run when a batch
+/// is finished.
+///
+/// The executor can't run the block tip without applying it to the batch, so the estimate
+/// is the block tip of the most recently finished batch. It's shared by the executors of
+/// consecutive batches, and is empty until the first batch is finished.
+#[derive(Debug, Clone, Default)]
+pub struct BlockTipEstimate(Arc<Mutex<ExecutionMetrics>>);
+
+impl BlockTipEstimate {
+    fn get(&self) -> ExecutionMetrics {
+        *self.0.lock().unwrap()
+    }
+
+    fn update(&self, metrics: ExecutionMetrics) {
+        *self.0.lock().unwrap() = metrics;
+    }
+}
+
 impl BatchExecutorHandle {
     pub(super) fn new(
         save_call_traces: bool,
         secondary_storage_path: &Path,
         merkle_tree_path: &Path,
         l1_batch_params: L1BatchParams,
+        block_tip_estimate: BlockTipEstimate,
     ) -> Self {
         // Since we process `BatchExecutor` commands one-by-one (the next command is never enqueued
         // until a previous command is processed), capacity 1 is enough for the commands channel.
@@ -47,6 +74,7 @@ impl BatchExecutorHandle {
         let executor = BatchExecutor {
             save_call_traces,
             commands: commands_receiver,
+            block_tip_estimate,
         };
 
         let db_path = secondary_storage_path.to_str().unwrap().to_string();
@@ -116,7 +144,6 @@ pub(crate) enum TxExecutionResult {
         tx_result: Box<VmTxExeResult>,
         tx_metrics: ExecutionMetricsForCriteria,
         entrypoint_dry_run_metrics: ExecutionMetricsForCriteria,
-        entrypoint_dry_run_result: Box<VmPartialExecutionResult>,
     },
     /// The VM rejected the tx for some reason.
     RejectedByVm { rejection_reason: TxRevertReason },
@@ -141,35 +168,46 @@ impl TxExecutionResult {
 
 #[async_trait]
 pub trait L1BatchExecutorBuilder: 'static + Send + Sync + fmt::Debug {
-    async fn init_batch(&self, l1_batch_params: L1BatchParams) -> BatchExecutorHandle;
+    async fn init_batch(
+        &self,
+        l1_batch_params: L1BatchParams,
+    ) -> anyhow::Result<BatchExecutorHandle>;
 }
 
 #[derive(Debug, Clone)]
 pub struct MainBatchExecutorBuilder {
     sequencer_db_path: String,
+    sequencer_db_options: RocksDBOptions,
     merkle_db_path: String,
     pool: ConnectionPool,
     save_call_traces: bool,
+    block_tip_estimate: BlockTipEstimate,
 }
 
 impl MainBatchExecutorBuilder {
     pub fn new(
         sequencer_db_path: String,
+        sequencer_db_options: RocksDBOptions,
         merkle_db_path: String,
         pool: ConnectionPool,
         save_call_traces: bool,
     ) -> Self {
         Self {
             sequencer_db_path,
+            sequencer_db_options,
             merkle_db_path,
             pool,
             save_call_traces,
+            block_tip_estimate: BlockTipEstimate::default(),
         }
     }
 
     #[allow(unused)]
     fn init_batch_mock(&self, l1_batch_params: L1BatchParams) -> BatchExecutorHandle {
-        let secondary_storage = RocksdbStorage::new(self.sequencer_db_path.as_ref());
+        let secondary_storage = RocksdbStorage::with_options(
+            self.sequencer_db_path.as_ref(),
+            self.sequencer_db_options,
+        );
 
         let batch_number = l1_batch_params
             .context_mode
@@ -186,6 +224,7 @@ impl MainBatchExecutorBuilder {
             self.sequencer_db_path.as_ref(),
             self.merkle_db_path.as_ref(),
             l1_batch_params,
+            self.block_tip_estimate.clone(),
         )
     }
 }
@@ -193,10 +232,19 @@ impl MainBatchExecutorBuilder {
 #[async_trait]
 impl L1BatchExecutorBuilder for MainBatchExecutorBuilder {
     #[olaos_logs::instrument(skip(self, l1_batch_params), fields(block_number = l1_batch_params.block_number()))]
-    async fn init_batch(&self, l1_batch_params: L1BatchParams) -> BatchExecutorHandle {
-        let mut secondary_storage = RocksdbStorage::new(self.sequencer_db_path.as_ref());
+    async fn init_batch(
+        &self,
+        l1_batch_params: L1BatchParams,
+    ) -> anyhow::Result<BatchExecutorHandle> {
+        let mut secondary_storage = RocksdbStorage::with_options(
+            self.sequencer_db_path.as_ref(),
+            self.sequencer_db_options,
+        );
         let mut conn = self.pool.access_storage_tagged("sequencer").await;
-        secondary_storage.update_from_postgres(&mut conn).await;
+        secondary_storage
+            .update_from_postgres(&mut conn)
+            .await
+            .expect("failed loading sequencer state from Postgres");
         drop(conn);
 
         let batch_number = l1_batch_params
@@ -209,19 +257,58 @@ impl L1BatchExecutorBuilder for MainBatchExecutorBuilder {
             "Secondary storage for batch {batch_number} initialized, size is {}",
             secondary_storage.estimated_map_size()
         );
-        BatchExecutorHandle::new(
+        prepare_base_system_contracts(&secondary_storage, &l1_batch_params.base_system_contracts)
+            .with_context(|| format!("cannot execute L1 batch #{batch_number}"))?;
+        drop(secondary_storage);
+
+        Ok(BatchExecutorHandle::new(
             self.save_call_traces,
             self.sequencer_db_path.as_ref(),
             self.merkle_db_path.as_ref(),
             l1_batch_params,
-        )
+            self.block_tip_estimate.clone(),
+        ))
     }
 }
 
+/// Checks that the base system contracts of the batch protocol version match the contracts
+/// deployed in the state the batch is executed against, and makes them available to the VM.
+/// Nothing is persisted if the check fails.
+fn prepare_base_system_contracts(
+    storage: &RocksdbStorage,
+    contracts: &BaseSystemContracts,
+) -> anyhow::Result<()> {
+    for (name, address, expected_hash) in [
+        ("entrypoint", ENTRYPOINT_ADDRESS, contracts.entrypoint.hash),
+        (
+            "default AA",
+            DEFAULT_ACCOUNT_ADDRESS,
+            contracts.default_aa.hash,
+        ),
+    ] {
+        // Absent before genesis is applied to the secondary storage.
+        let Some(deployed_hash) = storage.read_value_inner(&get_full_code_key(&address)) else {
+            continue;
+        };
+        anyhow::ensure!(
+            deployed_hash == expected_hash,
+            "Deployed {name} code hash {deployed_hash:?} doesn't match the one of the batch \
+             protocol version ({expected_hash:?})"
+        );
+    }
+
+    storage.persist_factory_deps([
+        (contracts.entrypoint.hash, contracts.entrypoint.code.clone()),
+        (contracts.default_aa.hash, contracts.default_aa.code.clone()),
+    ]);
+    Ok(())
+}
+
 #[derive(Debug)]
 pub(super) struct BatchExecutor {
     save_call_traces: bool,
     commands: mpsc::Receiver<Command>,
+    block_tip_estimate: BlockTipEstimate,
 }
 
 impl BatchExecutor {
@@ -333,26 +420,36 @@ impl BatchExecutor {
                     tx_hash: Some(h256_to_u64_array(&hash)),
                 };
                 let tx_result = block_exe_manager.invoke(tape_init_info);
+                let cycles_used = tx_result
+                    .as_ref()
+                    .map_or(0, |result| cycles_in_trace(&result.trace));
                 match tx_result {
                     Ok(result) => TxExecutionResult::Success {
+                        tx_metrics: ExecutionMetricsForCriteria {
+                            execution_metrics: ExecutionMetrics {
+                                storage_logs: result.storage_access_logs.len(),
+                                vm_events: result.events.len(),
+                                total_log_queries: result.storage_access_logs.len()
+                                    + result.events.len(),
+                                cycles_used,
+                                ..ExecutionMetrics::default()
+                            },
+                        },
+                        entrypoint_dry_run_metrics: ExecutionMetricsForCriteria {
+                            execution_metrics: self.block_tip_estimate.get(),
+                        },
                         tx_result: Box::new(VmTxExeResult {
                             status: TxExecutionStatus::Success,
                             result: VmPartialExecutionResult::from_storage_events(
                                 &result.storage_access_logs,
                                 &result.events,
                                 tx_index_in_l1_batch,
+                                cycles_used,
                             ),
                             trace: result.trace,
                             gas_refunded: 0,
                             operator_suggested_refund: 0,
                         }),
-                        tx_metrics: ExecutionMetricsForCriteria {
-                            execution_metrics: Default::default(),
-                        },
-                        entrypoint_dry_run_metrics: ExecutionMetricsForCriteria {
-                            execution_metrics: Default::default(),
-                        },
-                        entrypoint_dry_run_result: Box::default(),
                     },
                     Err(e) => {
                         let revert_reason = VmRevertReason::General {
@@ -386,14 +483,26 @@ impl BatchExecutor {
         let events: Vec<VmEvent> = result.events.iter().map(|event| event.into()).collect();
         full_result.storage_log_queries = storage_logs;
         full_result.events = events;
+        full_result.cycles_used = result
+            .tx_traces
+            .iter()
+            .map(cycles_in_trace)
+            .fold(0, u32::saturating_add);
+        self.block_tip_estimate.update(ExecutionMetrics {
+            storage_logs: result.block_tip_queries.len(),
+            total_log_queries: result.block_tip_queries.len(),
+            ..ExecutionMetrics::default()
+        });
 
         (
             VmBlockResult {
                 full_result,
+                // The executor doesn't report a trace for the block tip, so its cycles are unknown.
                 block_tip_result: VmPartialExecutionResult::from_storage_events(
                     &result.block_tip_queries,
                     &vec![],
                     tx_index_in_l1_batch,
+                    0,
                 ),
             },
             result.tx_traces,
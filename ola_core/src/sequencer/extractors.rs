@@ -1,4 +1,4 @@
-use ola_dal::StorageProcessor;
+use ola_dal::{DalResult, StorageProcessor};
 use ola_types::{L1BatchNumber, Transaction, U256};
 use ola_utils::h256_to_u256;
 use ola_vm::transaction_data::TransactionData;
@@ -40,9 +40,9 @@ pub(super) fn display_timestamp(timestamp: u64) -> impl fmt::Display {
 pub(crate) async fn wait_for_prev_l1_batch_params(
     storage: &mut StorageProcessor<'_>,
     number: L1BatchNumber,
-) -> (U256, u64) {
+) -> DalResult<(U256, u64)> {
     if number == L1BatchNumber(0) {
-        return (U256::default(), 0);
+        return Ok((U256::default(), 0));
     }
     wait_for_l1_batch_params_unchecked(storage, number - 1).await
 }
@@ -50,7 +50,7 @@ pub(crate) async fn wait_for_prev_l1_batch_params(
 async fn wait_for_l1_batch_params_unchecked(
     storage: &mut StorageProcessor<'_>,
     number: L1BatchNumber,
-) -> (U256, u64) {
+) -> DalResult<(U256, u64)> {
     // If the state root is not known yet, this duration will be used to back off in the while loops
     const SAFE_STATE_ROOT_INTERVAL: Duration = Duration::from_millis(100);
 
@@ -59,13 +59,13 @@ async fn wait_for_l1_batch_params_unchecked(
         let data = storage
             .blocks_dal()
             .get_l1_batch_state_root_and_timestamp(number)
-            .await;
+            .await?;
         if let Some((root_hash, timestamp)) = data {
             olaos_logs::info!(
                 "Waiting for hash of L1 batch #{number} took {:?}",
                 stage_started_at.elapsed()
             );
-            return (h256_to_u256(root_hash), timestamp);
+            return Ok((h256_to_u256(root_hash), timestamp));
         }
 
         tokio::time::sleep(SAFE_STATE_ROOT_INTERVAL).await;
@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use ola_contracts::BaseSystemContracts;
-use ola_dal::StorageProcessor;
+use ola_dal::{DalError, DalResult, StorageProcessor};
 use ola_types::{protocol_version::ProtocolVersionId, Address, L1BatchNumber, U256};
 use ola_utils::h256_to_u256;
 use ola_vm::vm_with_bootloader::{BlockContext, BlockContextMode, BlockProperties};
@@ -43,25 +43,28 @@ pub(crate) async fn load_pending_batch(
     storage: &mut StorageProcessor<'_>,
     current_l1_batch_number: L1BatchNumber,
     fee_account: Address,
-) -> Option<PendingBatchData> {
+) -> DalResult<Option<PendingBatchData>> {
     // If pending miniblock doesn't exist, it means that there is no unsynced state (i.e. no transaction
     // were executed after the last sealed batch).
     let pending_miniblock_number = {
         let (_, last_miniblock_number_included_in_l1_batch) = storage
             .blocks_dal()
             .get_miniblock_range_of_l1_batch(current_l1_batch_number - 1)
-            .await
-            .unwrap();
+            .await?
+            .ok_or(DalError::NotFound)?;
         last_miniblock_number_included_in_l1_batch + 1
     };
-    let pending_miniblock_header = storage
+    let Some(pending_miniblock_header) = storage
         .blocks_dal()
         .get_miniblock_header(pending_miniblock_number)
-        .await?;
+        .await?
+    else {
+        return Ok(None);
+    };
 
     olaos_logs::info!("Getting previous batch hash");
     let (previous_l1_batch_hash, _) =
-        extractors::wait_for_prev_l1_batch_params(storage, current_l1_batch_number).await;
+        extractors::wait_for_prev_l1_batch_params(storage, current_l1_batch_number).await?;
 
     let base_system_contracts = storage
         .storage_dal()
@@ -73,7 +76,7 @@ pub(crate) async fn load_pending_batch(
                 .base_system_contracts_hashes
                 .default_aa,
         )
-        .await;
+        .await?;
 
     olaos_logs::info!("Previous l1_batch_hash: {}", previous_l1_batch_hash);
     let params = l1_batch_params(
@@ -90,12 +93,12 @@ pub(crate) async fn load_pending_batch(
     let pending_miniblocks = storage
         .transactions_dal()
         .get_miniblocks_to_reexecute()
-        .await;
+        .await?;
 
-    Some(PendingBatchData {
+    Ok(Some(PendingBatchData {
         params,
         pending_miniblocks,
-    })
+    }))
 }
 
 pub(crate) fn poll_iters(delay_interval: Duration, max_wait: Duration) -> usize {
@@ -106,19 +109,21 @@ pub(crate) fn poll_iters(delay_interval: Duration, max_wait: Duration) -> usize
     ((max_wait_millis + delay_interval_millis - 1) / delay_interval_millis).max(1) as usize
 }
 
-pub async fn set_missing_initial_writes_indices(storage: &mut StorageProcessor<'_>) {
+pub async fn set_missing_initial_writes_indices(
+    storage: &mut StorageProcessor<'_>,
+) -> DalResult<()> {
     // Indices should start from 1, that's why default is (1, 0).
     let (mut next_index, start_from_batch) = storage
         .storage_logs_dedup_dal()
         .max_set_enumeration_index()
-        .await
+        .await?
         .map(|(index, l1_batch_number)| (index + 1, l1_batch_number + 1))
         .unwrap_or((1, L1BatchNumber(0)));
 
-    let sealed_batch = storage.blocks_dal().get_sealed_l1_batch_number().await;
+    let sealed_batch = storage.blocks_dal().get_sealed_l1_batch_number().await?;
     if start_from_batch > sealed_batch {
         olaos_logs::info!("All indices for initial writes are already set, no action is needed");
-        return;
+        return Ok(());
     } else {
         let batches_count = sealed_batch.0 - start_from_batch.0 + 1;
         if batches_count > 100 {
@@ -132,7 +137,7 @@ pub async fn set_missing_initial_writes_indices(storage: &mut StorageProcessor<'
     );
     let mut current_l1_batch = start_from_batch;
     loop {
-        if current_l1_batch > storage.blocks_dal().get_sealed_l1_batch_number().await {
+        if current_l1_batch > storage.blocks_dal().get_sealed_l1_batch_number().await? {
             break;
         }
         olaos_logs::info!("Setting indices for batch {current_l1_batch}");
@@ -140,13 +145,13 @@ pub async fn set_missing_initial_writes_indices(storage: &mut StorageProcessor<'
         let (hashed_keys, _): (Vec<_>, Vec<_>) = storage
             .storage_logs_dedup_dal()
             .initial_writes_for_batch(current_l1_batch)
-            .await
+            .await?
             .into_iter()
             .unzip();
         let storage_keys = storage
             .storage_logs_dal()
             .resolve_hashed_keys(&hashed_keys)
-            .await;
+            .await?;
 
         // Sort storage key alphanumerically and assign indices.
         let indexed_keys: Vec<_> = storage_keys
@@ -158,9 +163,10 @@ pub async fn set_missing_initial_writes_indices(storage: &mut StorageProcessor<'
         storage
             .storage_logs_dedup_dal()
             .set_indices_for_initial_writes(&indexed_keys)
-            .await;
+            .await?;
 
         next_index += indexed_keys.len() as u64;
         current_l1_batch += 1;
     }
+    Ok(())
 }
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use ola_utils::time::millis_since_epoch;
+use ola_utils::time::{millis_since_epoch, seconds_since_epoch};
 use ola_vm::{vm::VmBlockResult, vm_with_bootloader::DerivedBlockContext};
 
 use std::{
@@ -11,11 +11,17 @@ use std::{
 use ola_config::sequencer::SequencerConfig;
 use ola_dal::connection::ConnectionPool;
 use ola_types::{
+    api::AuditAction,
     protocol_version::{ProtocolUpgradeTx, ProtocolVersionId},
     Address, L1BatchNumber, MiniblockNumber, Transaction, U256,
 };
 
-use crate::sequencer::{extractors, types::MempoolGuard, updates::UpdatesManager};
+use crate::{
+    audit::{AuditLog, SYSTEM_ACTOR},
+    sequencer::{
+        extractors, leader_election::LeaseFence, types::MempoolGuard, updates::UpdatesManager,
+    },
+};
 
 use super::{
     common::{l1_batch_params, load_pending_batch, poll_iters},
@@ -29,8 +35,12 @@ pub(crate) struct MempoolIO {
     current_miniblock_number: MiniblockNumber,
     miniblock_sealer_handle: MiniblockSealerHandle,
     current_l1_batch_number: L1BatchNumber,
+    /// Operator from the config, used until the operator is rotated for the first time.
     fee_account: Address,
     delay_interval: Duration,
+    max_call_output_size: usize,
+    fence: LeaseFence,
+    audit_log: AuditLog,
 }
 
 impl MempoolIO {
@@ -40,10 +50,20 @@ impl MempoolIO {
         pool: ConnectionPool,
         config: &SequencerConfig,
         delay_interval: Duration,
+        fence: LeaseFence,
+        audit_log: AuditLog,
     ) -> Self {
         let mut storage = pool.access_storage_tagged("sequencer").await;
-        let last_sealed_l1_batch_header = storage.blocks_dal().get_newest_l1_batch_header().await;
-        let last_miniblock_number = storage.blocks_dal().get_sealed_miniblock_number().await;
+        let last_sealed_l1_batch_header = storage
+            .blocks_dal()
+            .get_newest_l1_batch_header()
+            .await
+            .expect("failed loading the last sealed L1 batch");
+        let last_miniblock_number = storage
+            .blocks_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .expect("failed loading the last sealed miniblock");
         drop(storage);
 
         Self {
@@ -54,6 +74,9 @@ impl MempoolIO {
             current_miniblock_number: last_miniblock_number + 1,
             fee_account: config.fee_account_addr,
             delay_interval,
+            max_call_output_size: config.max_stored_call_output_size(),
+            fence,
+            audit_log,
         }
     }
 }
@@ -70,13 +93,17 @@ impl SequencerIO for MempoolIO {
 
     #[olaos_logs::instrument(skip_all)]
     async fn load_pending_batch(&mut self) -> Option<PendingBatchData> {
+        // The operator of the pending batch is already fixed; rotations requested since then
+        // take effect on the next batch.
+        let operator_address = self.load_operator_address(false).await;
         let mut storage = self.pool.access_storage_tagged("sequencer").await;
 
         let PendingBatchData {
             params,
             pending_miniblocks,
-        } = load_pending_batch(&mut storage, self.current_l1_batch_number, self.fee_account)
-            .await?;
+        } = load_pending_batch(&mut storage, self.current_l1_batch_number, operator_address)
+            .await
+            .expect("failed loading pending L1 batch")?;
 
         Some(PendingBatchData {
             params,
@@ -91,7 +118,8 @@ impl SequencerIO for MempoolIO {
         // This is needed to ensure that block timestamp is not too old.
         for _ in 0..poll_iters(self.delay_interval, max_wait) {
             // We only need to get the root hash when we're certain that we have a new transaction.
-            if !self.mempool.has_next() {
+            // Scheduled transactions are only considered once their timestamp has passed.
+            if !self.mempool.has_next(seconds_since_epoch()) {
                 tokio::time::sleep(self.delay_interval).await;
                 continue;
             }
@@ -108,16 +136,18 @@ impl SequencerIO for MempoolIO {
                 sleep_past(prev_miniblock_timestamp, self.current_miniblock_number),
             );
             let current_timestamp = current_timestamp.await.ok()?;
+            let operator_address = self.load_operator_address(true).await;
 
             let mut storage = self.pool.access_storage().await;
             let (base_system_contracts, protocol_version) = storage
                 .protocol_versions_dal()
                 .base_system_contracts_by_timestamp(current_timestamp as i64)
-                .await;
+                .await
+                .expect("failed loading base system contracts");
 
             let l1_batch_params = l1_batch_params(
                 self.current_l1_batch_number,
-                self.fee_account,
+                operator_address,
                 current_timestamp,
                 prev_l1_batch_hash,
                 base_system_contracts,
@@ -150,9 +180,13 @@ impl SequencerIO for MempoolIO {
     }
 
     #[olaos_logs::instrument(skip_all)]
-    async fn wait_for_next_tx(&mut self, max_wait: Duration) -> Option<Transaction> {
+    async fn wait_for_next_tx(
+        &mut self,
+        max_wait: Duration,
+        miniblock_timestamp: u64,
+    ) -> Option<Transaction> {
         for _ in 0..poll_iters(self.delay_interval, max_wait) {
-            let res = self.mempool.next_transaction();
+            let res = self.mempool.next_transaction(miniblock_timestamp);
             if let Some(res) = res {
                 return Some(res);
             } else {
@@ -180,26 +214,23 @@ impl SequencerIO for MempoolIO {
         //     error
         // );
 
-        // Reset the nonces in the mempool, but don't insert the transaction back.
-        self.mempool.rollback(rejected);
-
-        // Mark tx as rejected in the storage.
-        let mut storage = self.pool.access_storage_tagged("sequencer").await;
+        // Reset the nonces in the mempool, but don't insert the transaction back. The mempool
+        // skips the transaction when syncing with Postgres until its rejection is persisted.
+        self.mempool.reject(rejected);
         olaos_logs::warn!(
             "transaction {} is rejected with error {}",
             rejected.hash(),
             error
         );
-        storage
-            .transactions_dal()
-            .mark_tx_as_rejected(rejected.hash(), &format!("rejected: {}", error))
-            .await;
     }
 
     #[olaos_logs::instrument(skip_all)]
     async fn seal_miniblock(&mut self, updates_manager: &UpdatesManager) {
-        let command = updates_manager
-            .seal_miniblock_command(self.current_l1_batch_number, self.current_miniblock_number);
+        let command = updates_manager.seal_miniblock_command(
+            self.current_l1_batch_number,
+            self.current_miniblock_number,
+            self.max_call_output_size,
+        );
         self.miniblock_sealer_handle.submit(command).await;
         self.current_miniblock_number += 1;
     }
@@ -230,8 +261,12 @@ impl SequencerIO for MempoolIO {
                 self.current_l1_batch_number,
                 block_result,
                 block_context,
+                &self.fence,
             )
-            .await;
+            .await
+            .expect("failed sealing L1 batch");
+        // All miniblocks of the batch are sealed, so rejections recorded in them are persisted.
+        self.mempool.clear_persisted_rejections();
         self.current_miniblock_number += 1; // Due to fictive miniblock being sealed.
         self.current_l1_batch_number += 1;
     }
@@ -243,6 +278,7 @@ impl SequencerIO for MempoolIO {
             .blocks_dal()
             .get_batch_protocol_version_id(self.current_l1_batch_number - 1)
             .await
+            .expect("failed loading protocol version of the previous L1 batch")
     }
 
     #[olaos_logs::instrument(skip_all)]
@@ -251,10 +287,29 @@ impl SequencerIO for MempoolIO {
         version_id: ProtocolVersionId,
     ) -> Option<ProtocolUpgradeTx> {
         let mut storage = self.pool.access_storage().await;
+        // Called when the sequencer opens the first L1 batch of a new protocol version.
+        let parameters = serde_json::json!({
+            "protocolVersion": version_id as u16,
+            "l1BatchNumber": self.current_l1_batch_number.0,
+        });
+        let recorded = self
+            .audit_log
+            .record(
+                &mut storage,
+                AuditAction::ProtocolUpgrade,
+                SYSTEM_ACTOR,
+                parameters,
+            )
+            .await;
+        if let Err(err) = recorded {
+            olaos_logs::error!("{err:#}");
+        }
+
         storage
             .protocol_versions_dal()
             .get_protocol_upgrade_tx(version_id)
             .await
+            .expect("failed loading protocol upgrade transaction")
     }
 }
 
@@ -270,7 +325,8 @@ impl MempoolIO {
         let mut storage = self.pool.access_storage_tagged("sequencer").await;
         let (batch_hash, _) =
             extractors::wait_for_prev_l1_batch_params(&mut storage, self.current_l1_batch_number)
-                .await;
+                .await
+                .expect("failed loading previous L1 batch hash");
 
         olaos_logs::info!(
             "Got previous L1 batch hash: {batch_hash:0>64x} for L1 batch #{}",
@@ -279,6 +335,30 @@ impl MempoolIO {
         batch_hash
     }
 
+    /// Returns the operator of the current L1 batch. If `apply_pending_rotation` is set,
+    /// the pending operator rotation, if any, takes effect starting from this batch.
+    async fn load_operator_address(&self, apply_pending_rotation: bool) -> Address {
+        let mut storage = self.pool.access_storage_tagged("sequencer").await;
+        let mut operators_dal = storage.operators_dal();
+        if apply_pending_rotation {
+            let rotated = operators_dal
+                .apply_pending_rotation(self.current_l1_batch_number)
+                .await
+                .expect("failed applying operator rotation");
+            if let Some(operator_address) = rotated {
+                olaos_logs::info!(
+                    "Operator is rotated to {operator_address:?} starting from L1 batch #{}",
+                    self.current_l1_batch_number
+                );
+            }
+        }
+        operators_dal
+            .get_operator_for_l1_batch(self.current_l1_batch_number)
+            .await
+            .expect("failed loading operator address")
+            .unwrap_or(self.fee_account)
+    }
+
     async fn load_previous_miniblock_timestamp(&self) -> u64 {
         let mut storage = self.pool.access_storage_tagged("sequencer").await;
 
@@ -286,6 +366,7 @@ impl MempoolIO {
             .blocks_dal()
             .get_miniblock_timestamp(self.current_miniblock_number - 1)
             .await
+            .expect("failed loading previous miniblock timestamp")
             .expect("Previous miniblock must be sealed and header saved to DB")
     }
 }
@@ -12,19 +12,43 @@ use ola_vm::{
 };
 use std::{
     fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
     time::{Duration, Instant},
 };
 
-use ola_dal::connection::ConnectionPool;
+use anyhow::Context as _;
+use ola_dal::{connection::ConnectionPool, facade::DalFacade};
+use ola_utils::retry::RetryPolicy;
 use tokio::sync::{mpsc, oneshot};
 
-use super::updates::{MiniblockSealCommand, UpdatesManager};
+use super::{
+    leader_election::LeaseFence,
+    types::MiniblockSealEvents,
+    updates::{MiniblockSealCommand, UpdatesManager},
+};
 
 pub mod common;
 pub mod mempool;
 pub mod seal_logic;
 pub mod sort_storage_access;
 
+/// Retries of persisting a miniblock if the database is temporarily unavailable.
+fn seal_retry_policy() -> RetryPolicy {
+    RetryPolicy::new(Duration::from_millis(10))
+        .with_exponential_backoff(2.0, Duration::from_secs(5))
+        .with_jitter(0.2)
+        .with_max_attempts(20)
+}
+
+/// Persists sealed miniblocks in the background, so that the sequencer can execute transactions
+/// of the next miniblock while the previous ones are being written to Postgres.
+///
+/// The pipeline is bounded by the command queue capacity. The batch executor state is
+/// authoritative: the sequencer never reads back data of miniblocks which may still be in
+/// the queue, and it waits for the queue to drain before sealing an L1 batch.
 #[derive(Debug)]
 pub(crate) struct MiniblockSealer {
     pool: ConnectionPool,
@@ -32,29 +56,41 @@ pub(crate) struct MiniblockSealer {
     // Weak sender handle to get queue capacity stats.
     commands_sender: mpsc::WeakSender<Completable<MiniblockSealCommand>>,
     commands_receiver: mpsc::Receiver<Completable<MiniblockSealCommand>>,
+    // Number of submitted commands which are not persisted yet.
+    in_flight: Arc<AtomicUsize>,
+    fence: LeaseFence,
+    seal_events: Option<MiniblockSealEvents>,
 }
 
 impl MiniblockSealer {
-    /// Creates a sealer that will use the provided Postgres connection and will have the specified
+    /// Creates a sealer that will use a connection from the provided pool (or persist miniblocks
+    /// through [`DalFacade`] if the pool is a mock) and will have the specified
     /// `command_capacity` for unprocessed sealing commands.
     pub(crate) fn new(
         pool: ConnectionPool,
         mut command_capacity: usize,
+        fence: LeaseFence,
+        seal_events: Option<MiniblockSealEvents>,
     ) -> (Self, MiniblockSealerHandle) {
         let is_sync = command_capacity == 0;
         command_capacity = command_capacity.max(1);
 
         let (commands_sender, commands_receiver) = mpsc::channel(command_capacity);
+        let in_flight = Arc::new(AtomicUsize::new(0));
         let this = Self {
             pool,
             is_sync,
             commands_sender: commands_sender.downgrade(),
             commands_receiver,
+            in_flight: in_flight.clone(),
+            fence,
+            seal_events,
         };
         let handle = MiniblockSealerHandle {
             commands_sender,
             latest_completion_receiver: None,
             is_sync,
+            in_flight,
         };
         (this, handle)
     }
@@ -75,9 +111,30 @@ impl MiniblockSealer {
         // an earlier one.
         while let Some(completable) = self.next_command().await {
             olaos_logs::info!("Miniblock sealer get a new command");
-            let mut conn = self.pool.access_storage_tagged("sequencer").await;
-            completable.command.seal(&mut conn).await;
+            let started_at = Instant::now();
+            let (command, pool, fence) = (&completable.command, &self.pool, &self.fence);
+            seal_retry_policy()
+                .retry("miniblock_sealer", || {
+                    let seal = Box::pin(command.seal(pool, fence));
+                    pool.seal_miniblock(command.miniblock_number, seal)
+                })
+                .await
+                .with_context(|| {
+                    format!("failed sealing miniblock #{}", command.miniblock_number)
+                })?;
+            metrics::histogram!(
+                "server.sequencer.miniblock_sealer.seal_latency",
+                started_at.elapsed()
+            );
+            let in_flight = self.in_flight.fetch_sub(1, Ordering::Relaxed) - 1;
+            metrics::gauge!(
+                "server.sequencer.miniblock_sealer.in_flight",
+                in_flight as f64
+            );
             olaos_logs::info!("Miniblock sealer sealed successfully");
+            if let Some(seal_events) = &self.seal_events {
+                seal_events.notify(completable.command.miniblock_number);
+            }
             completable.completion_sender.send(()).ok();
             // ^ We don't care whether anyone listens to the processing progress
             olaos_logs::info!("Miniblock sealer send ok to sender");
@@ -115,11 +172,17 @@ pub(crate) struct MiniblockSealerHandle {
     latest_completion_receiver: Option<oneshot::Receiver<()>>,
     // If true, `submit()` will wait for the operation to complete.
     is_sync: bool,
+    in_flight: Arc<AtomicUsize>,
 }
 
 impl MiniblockSealerHandle {
     const SHUTDOWN_MSG: &'static str = "miniblock sealer unexpectedly shut down";
 
+    /// Returns the counter of submitted commands which are not persisted yet.
+    pub(crate) fn in_flight_counter(&self) -> Arc<AtomicUsize> {
+        self.in_flight.clone()
+    }
+
     #[olaos_logs::instrument(skip_all)]
     pub async fn submit(&mut self, command: MiniblockSealCommand) {
         let miniblock_number = command.miniblock_number;
@@ -139,12 +202,25 @@ impl MiniblockSealerHandle {
 
         olaos_logs::info!("Sending a command to miniblock sealer");
 
+        // Count the command before sending it, so that the sealer never decrements the counter
+        // below zero.
+        let pending_before = self.in_flight.fetch_add(1, Ordering::Relaxed);
+        if pending_before > 0 {
+            // Execution of the next miniblock overlaps with persisting the previous ones.
+            metrics::counter!("server.sequencer.miniblock_sealer.pipelined", 1);
+        }
         self.commands_sender
             .send(command)
             .await
             .expect(Self::SHUTDOWN_MSG);
 
         let elapsed = start.elapsed();
+        // Non-zero latency means that the queue is full and the sequencer is stalled by sealing.
+        metrics::histogram!("server.sequencer.miniblock_sealer.submit_latency", elapsed);
+        metrics::gauge!(
+            "server.sequencer.miniblock_sealer.in_flight",
+            self.in_flight.load(Ordering::Relaxed) as f64
+        );
         let queue_capacity = self.commands_sender.capacity();
         olaos_logs::info!(
             "Enqueued sealing command for miniblock #{miniblock_number} (took {elapsed:?}; \
@@ -170,6 +246,7 @@ impl MiniblockSealerHandle {
         }
 
         let elapsed = start.elapsed();
+        metrics::histogram!("server.sequencer.miniblock_sealer.drain_latency", elapsed);
         olaos_logs::info!("Miniblock seal queue is emptied (took {elapsed:?})");
     }
 }
@@ -193,12 +270,18 @@ pub trait SequencerIO: 'static + Send {
         max_wait: Duration,
         prev_miniblock_timestamp: u64,
     ) -> Option<u64>;
-    /// Blocks for up to `max_wait` until the next transaction is available for execution.
-    /// Returns `None` if no transaction became available until the timeout.
-    async fn wait_for_next_tx(&mut self, max_wait: Duration) -> Option<Transaction>;
+    /// Blocks for up to `max_wait` until the next transaction is available for execution
+    /// in a miniblock with the specified timestamp. Returns `None` if no transaction became
+    /// available until the timeout.
+    async fn wait_for_next_tx(
+        &mut self,
+        max_wait: Duration,
+        miniblock_timestamp: u64,
+    ) -> Option<Transaction>;
     /// Marks the transaction as "not executed", so it can be retrieved from the IO again.
     async fn rollback(&mut self, tx: Transaction);
     /// Marks the transaction as "rejected", e.g. one that is not correct and can't be executed.
+    /// The rejection is persisted by the sequencer when the current miniblock is sealed.
     async fn reject(&mut self, tx: &Transaction, error: &str);
     /// Marks the miniblock (aka L2 block) as sealed.
     /// Returns the timestamp for the next miniblock.
@@ -249,3 +332,91 @@ impl L1BatchParams {
         self.context_mode.inner_block_context().context.block_number
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ola_contracts::BaseSystemContractsHashes;
+    use ola_dal::connection::mock::MockDb;
+    use ola_types::{
+        l2::L2Tx,
+        request::PaymasterParams,
+        tx::{
+            tx_execution_info::{ExecutionMetrics, TxExecutionStatus},
+            TransactionExecutionResult,
+        },
+        Address, Nonce, H256,
+    };
+    use ola_utils::misc::miniblock_hash;
+
+    use super::*;
+    use crate::sequencer::updates::miniblock_updates::MiniblockUpdates;
+
+    fn executed_tx(nonce: u32) -> TransactionExecutionResult {
+        let mut tx = L2Tx::new(
+            Address::repeat_byte(0xff),
+            vec![],
+            Nonce(nonce),
+            Address::repeat_byte(1),
+            None,
+            PaymasterParams::default(),
+        );
+        tx.set_input(vec![], H256::from_low_u64_be(nonce.into()));
+        let tx = Transaction::from(tx);
+        TransactionExecutionResult {
+            hash: tx.hash(),
+            transaction: tx,
+            execution_info: ExecutionMetrics::default(),
+            execution_status: TxExecutionStatus::Success,
+            call_traces: vec![],
+            revert_reason: None,
+        }
+    }
+
+    fn seal_command(number: u32) -> MiniblockSealCommand {
+        let mut miniblock = MiniblockUpdates::new(u64::from(number));
+        miniblock.executed_transactions.push(executed_tx(number));
+        MiniblockSealCommand {
+            l1_batch_number: L1BatchNumber(1),
+            miniblock_number: MiniblockNumber(number),
+            miniblock,
+            first_tx_index: 0,
+            base_system_contracts_hashes: BaseSystemContractsHashes::default(),
+            protocol_version: ProtocolVersionId::latest(),
+            max_call_output_size: 1_024,
+        }
+    }
+
+    #[tokio::test]
+    async fn sealer_retries_failed_seals() {
+        let db = Arc::new(MockDb::new().with_error_rate(0.5).with_seed(11));
+        let pool = ConnectionPool::Mock(db.clone());
+        let (sealer, mut handle) =
+            MiniblockSealer::new(pool.clone(), 2, LeaseFence::default(), None);
+        let sealer_task = tokio::spawn(sealer.run());
+
+        for number in 1..=5 {
+            handle.submit(seal_command(number)).await;
+        }
+        handle.wait_for_all_commands().await;
+        assert_eq!(db.current_sealed_miniblock_number(), MiniblockNumber(5));
+        // Each of 5 seals is a single simulated query, so some of them were retried.
+        assert!(db.calls() > 5, "calls: {}", db.calls());
+        for number in 1..=5 {
+            let header = loop {
+                // Reads are subject to the injected errors as well.
+                if let Ok(header) = pool.miniblock_header(MiniblockNumber(number)).await {
+                    break header.expect("miniblock is not persisted");
+                }
+            };
+            assert_eq!(header.timestamp, u64::from(number));
+            assert_eq!(header.l2_tx_count, 1);
+            assert_eq!(header.hash, miniblock_hash(MiniblockNumber(number)));
+        }
+        assert_eq!(handle.in_flight_counter().load(Ordering::Relaxed), 0);
+
+        drop(handle);
+        sealer_task.await.unwrap().unwrap();
+    }
+}
@@ -10,12 +10,20 @@ use std::{
     time::{Duration, Instant},
 };
 
-use ola_dal::StorageProcessor;
+use ola_dal::{connection::ConnectionPool, DalResult, StorageProcessor};
 use ola_types::{
     block::{L1BatchHeader, MiniblockHeader},
     events::VmEvent,
+    get_full_code_key,
     log::{LogQuery, StorageLog, StorageLogQuery},
-    tx::{IncludedTxLocation, TransactionExecutionResult},
+    receipts_tree::{ReceiptLeaf, ReceiptsTree},
+    storage::labels::StorageKeyLabels,
+    tokens::{decode_token_transfer, extract_deployed_tokens, TokenTransfer},
+    tx::{
+        execution_digest::ExecutionDigest,
+        tx_execution_info::{ContractExecutionMetrics, TxExecutionStatus},
+        IncludedTxLocation, TransactionExecutionResult,
+    },
     AccountTreeId, Address, L1BatchNumber, MiniblockNumber, StorageKey, StorageValue, Transaction,
     H256, U256,
 };
@@ -27,6 +35,7 @@ use crate::sequencer::{
         common::set_missing_initial_writes_indices,
         sort_storage_access::sort_storage_access_queries,
     },
+    leader_election::LeaseFence,
     updates::{l1_batch_updates::L1BatchUpdates, MiniblockSealCommand, UpdatesManager},
 };
 
@@ -79,45 +88,63 @@ impl SealProgress {
     }
 }
 
+/// Data of a miniblock persisted when sealing it.
+#[derive(Debug)]
+struct MiniblockSealData<'a> {
+    write_logs: Vec<(H256, Vec<StorageLog>)>,
+    events: Vec<(IncludedTxLocation, Vec<&'a VmEvent>)>,
+    token_transfers: Vec<TokenTransfer>,
+}
+
+impl MiniblockSealData<'_> {
+    fn write_log_count(&self) -> usize {
+        self.write_logs.iter().map(|(_, logs)| logs.len()).sum()
+    }
+
+    fn event_count(&self) -> usize {
+        self.events.iter().map(|(_, events)| events.len()).sum()
+    }
+}
+
 impl MiniblockSealCommand {
-    pub async fn seal(&self, storage: &mut StorageProcessor<'_>) {
-        self.seal_inner(storage, false).await;
+    /// Persists the miniblock in a single transaction using a connection from `pool`.
+    /// Mock pools only record the miniblock header.
+    pub(crate) async fn seal(&self, pool: &ConnectionPool, fence: &LeaseFence) -> DalResult<()> {
+        if let ConnectionPool::Mock(db) = pool {
+            // Mock pools don't serve seal queries, so only the miniblock header is recorded.
+            self.assert_valid_miniblock(false);
+            self.log_seal_start();
+            db.insert_miniblock(self.miniblock_header());
+            return Ok(());
+        }
+
+        let mut storage = pool.access_storage_tagged("sequencer").await;
+        self.seal_inner(&mut storage, false, fence).await
     }
 
-    async fn seal_inner(&self, storage: &mut StorageProcessor<'_>, is_fictive: bool) {
+    async fn seal_inner(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        is_fictive: bool,
+        fence: &LeaseFence,
+    ) -> DalResult<()> {
         self.assert_valid_miniblock(is_fictive);
-
-        let l1_batch_number = self.l1_batch_number;
         let miniblock_number = self.miniblock_number;
         let started_at = Instant::now();
         let mut progress = SealProgress::for_miniblock();
+        self.log_seal_start();
 
-        let (l1_tx_count, l2_tx_count) = l1_l2_tx_count(&self.miniblock.executed_transactions);
-        let (writes_count, reads_count) =
-            storage_log_query_write_read_counts(&self.miniblock.storage_logs);
-        olaos_logs::info!(
-            "Sealing miniblock {miniblock_number} (L1 batch {l1_batch_number}) \
-             with {total_tx_count} ({l2_tx_count} L2 + {l1_tx_count} L1) txs, \
-             {event_count} events, {reads_count} reads, {writes_count} writes",
-            total_tx_count = l1_tx_count + l2_tx_count,
-            event_count = self.miniblock.events.len()
-        );
+        let data = self.extract_seal_data(is_fictive);
+        let write_log_count = data.write_log_count();
+        let event_count = data.event_count();
+        progress.end_stage("extract_seal_data", Some(write_log_count + event_count));
 
         let mut transaction = storage.start_transaction().await;
-        let miniblock_header = MiniblockHeader {
-            number: miniblock_number,
-            timestamp: self.miniblock.timestamp,
-            hash: miniblock_hash(miniblock_number),
-            l1_tx_count: l1_tx_count as u16,
-            l2_tx_count: l2_tx_count as u16,
-            base_system_contracts_hashes: self.base_system_contracts_hashes,
-            protocol_version: Some(self.protocol_version),
-        };
-
+        fence.check(&mut transaction).await;
         transaction
             .blocks_dal()
-            .insert_miniblock(&miniblock_header)
-            .await;
+            .insert_miniblock(&self.miniblock_header())
+            .await?;
         progress.end_stage("insert_miniblock_header", None);
 
         transaction
@@ -125,65 +152,149 @@ impl MiniblockSealCommand {
             .mark_txs_as_executed_in_miniblock(
                 miniblock_number,
                 &self.miniblock.executed_transactions,
+                self.max_call_output_size,
             )
-            .await;
+            .await?;
         progress.end_stage(
             "mark_transactions_in_miniblock",
             Some(self.miniblock.executed_transactions.len()),
         );
 
-        let write_logs = self.extract_write_logs(is_fictive);
-        // let write_log_count = write_logs.iter().map(|(_, logs)| logs.len()).sum();
+        transaction
+            .transactions_dal()
+            .mark_txs_as_rejected(&self.miniblock.rejected_transactions)
+            .await?;
+        progress.end_stage(
+            "mark_rejected_transactions",
+            Some(self.miniblock.rejected_transactions.len()),
+        );
 
         transaction
             .storage_logs_dal()
-            .insert_storage_logs(miniblock_number, &write_logs)
-            .await;
-        // progress.end_stage("insert_storage_logs", Some(write_log_count));
+            .insert_storage_logs(miniblock_number, &data.write_logs)
+            .await?;
+        progress.end_stage("insert_storage_logs", Some(write_log_count));
 
         let unique_updates = transaction
             .storage_dal()
-            .apply_storage_logs(&write_logs)
-            .await;
-        // progress.end_stage("apply_storage_logs", Some(write_log_count));
+            .apply_storage_logs(&data.write_logs)
+            .await?;
+        progress.end_stage("apply_storage_logs", Some(unique_updates.len()));
 
         let new_factory_deps = &self.miniblock.new_factory_deps;
-        let new_factory_deps_count = new_factory_deps.len();
         if !new_factory_deps.is_empty() {
             transaction
                 .storage_dal()
                 .insert_factory_deps(miniblock_number, new_factory_deps)
-                .await;
+                .await?;
         }
-        progress.end_stage("insert_factory_deps", Some(new_factory_deps_count));
+        progress.end_stage("insert_factory_deps", Some(new_factory_deps.len()));
 
         // Factory deps should be inserted before using `count_deployed_contracts`.
         let deployed_contract_count = Self::count_deployed_contracts(&unique_updates);
         progress.end_stage("extract_contracts_deployed", Some(deployed_contract_count));
 
-        // TODO: do not process L1 & L2 bridge & tokens
-        // let added_tokens = extract_added_tokens(self.l2_erc20_bridge_addr, &self.miniblock.events);
-        // progress.end_stage("extract_added_tokens", Some(added_tokens.len()));
-        // let added_tokens_len = added_tokens.len();
-        // if !added_tokens.is_empty() {
-        //     transaction.tokens_dal().add_tokens(added_tokens).await;
-        // }
-        // progress.end_stage("insert_tokens", Some(added_tokens_len));
-
-        let miniblock_events = self.extract_events(is_fictive);
-        let miniblock_event_count = miniblock_events
-            .iter()
-            .map(|(_, events)| events.len())
-            .sum();
-        progress.end_stage("extract_events", Some(miniblock_event_count));
+        let added_tokens = extract_deployed_tokens(&self.miniblock.events, |address| {
+            unique_updates
+                .get(&get_full_code_key(address))
+                .map_or(false, |(_, bytecode_hash)| !bytecode_hash.is_zero())
+        });
+        progress.end_stage("extract_added_tokens", Some(added_tokens.len()));
+        let added_tokens_len = added_tokens.len();
+        if !added_tokens.is_empty() {
+            transaction
+                .tokens_dal()
+                .add_tokens(miniblock_number, &added_tokens)
+                .await?;
+        }
+        progress.end_stage("insert_tokens", Some(added_tokens_len));
+
+        // Tokens must be inserted before transfers, so that transfers in the deployment
+        // miniblock (e.g., initial mints) are accounted for.
+        transaction
+            .tokens_dal()
+            .apply_transfers(miniblock_number, &data.token_transfers)
+            .await?;
+        progress.end_stage("apply_token_transfers", Some(data.token_transfers.len()));
+
         transaction
             .events_dal()
-            .save_events(miniblock_number, &miniblock_events)
-            .await;
-        progress.end_stage("insert_events", Some(miniblock_event_count));
+            .save_events(miniblock_number, &data.events)
+            .await?;
+        progress.end_stage("insert_events", Some(event_count));
+
+        if !is_fictive {
+            let digest_count = self
+                .record_execution_digests(&mut transaction, &data.write_logs, &data.events)
+                .await?;
+            progress.end_stage("record_execution_digests", Some(digest_count));
+        }
 
         transaction.commit().await;
         progress.end_stage("commit_miniblock", None);
+        olaos_logs::info!(
+            "Sealed miniblock {miniblock_number} with {write_log_count} storage writes \
+             and {event_count} events in {:?}",
+            started_at.elapsed()
+        );
+        Ok(())
+    }
+
+    fn log_seal_start(&self) {
+        let (l1_tx_count, l2_tx_count) = l1_l2_tx_count(&self.miniblock.executed_transactions);
+        let (writes_count, reads_count) =
+            storage_log_query_write_read_counts(&self.miniblock.storage_logs);
+        olaos_logs::info!(
+            "Sealing miniblock {miniblock_number} (L1 batch {l1_batch_number}) \
+             with {total_tx_count} ({l2_tx_count} L2 + {l1_tx_count} L1) txs, \
+             {event_count} events, {reads_count} reads, {writes_count} writes",
+            miniblock_number = self.miniblock_number,
+            l1_batch_number = self.l1_batch_number,
+            total_tx_count = l1_tx_count + l2_tx_count,
+            event_count = self.miniblock.events.len()
+        );
+    }
+
+    /// Extracts the data to persist before the seal transaction is started. Write logs, events
+    /// and token transfers are extracted in parallel on the rayon thread pool, so that
+    /// the transaction isn't held open while a large miniblock is processed.
+    fn extract_seal_data(&self, is_fictive: bool) -> MiniblockSealData<'_> {
+        let (write_logs, (events, token_transfers)) = rayon::join(
+            || {
+                let write_logs = self.extract_write_logs(is_fictive);
+                if tracing::enabled!(tracing::Level::DEBUG) {
+                    self.log_labeled_write_logs(&write_logs);
+                }
+                write_logs
+            },
+            || {
+                rayon::join(
+                    || self.extract_events(is_fictive),
+                    || {
+                        let events = self.miniblock.events.iter();
+                        events.filter_map(decode_token_transfer).collect()
+                    },
+                )
+            },
+        );
+        MiniblockSealData {
+            write_logs,
+            events,
+            token_transfers,
+        }
+    }
+
+    fn miniblock_header(&self) -> MiniblockHeader {
+        let (l1_tx_count, l2_tx_count) = l1_l2_tx_count(&self.miniblock.executed_transactions);
+        MiniblockHeader {
+            number: self.miniblock_number,
+            timestamp: self.miniblock.timestamp,
+            hash: miniblock_hash(self.miniblock_number),
+            l1_tx_count: l1_tx_count as u16,
+            l2_tx_count: l2_tx_count as u16,
+            base_system_contracts_hashes: self.base_system_contracts_hashes,
+            protocol_version: Some(self.protocol_version),
+        }
     }
 
     fn assert_valid_miniblock(&self, is_fictive: bool) {
@@ -226,6 +337,68 @@ impl MiniblockSealCommand {
         grouped_logs.collect()
     }
 
+    /// Records digests of the actual execution for transactions submitted with simulation
+    /// recording. Returns the number of recorded digests.
+    async fn record_execution_digests(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        write_logs: &[(H256, Vec<StorageLog>)],
+        events: &[(IncludedTxLocation, Vec<&VmEvent>)],
+    ) -> DalResult<usize> {
+        let tx_hashes: Vec<_> = self
+            .miniblock
+            .executed_transactions
+            .iter()
+            .map(|tx| tx.hash)
+            .collect();
+        let simulated_tx_hashes = storage
+            .transactions_dal()
+            .get_simulated_tx_hashes(&tx_hashes)
+            .await?;
+        if simulated_tx_hashes.is_empty() {
+            return Ok(0);
+        }
+
+        let digests: Vec<_> = simulated_tx_hashes
+            .into_iter()
+            .map(|tx_hash| {
+                let tx_writes = write_logs
+                    .iter()
+                    .filter(|(hash, _)| *hash == tx_hash)
+                    .flat_map(|(_, logs)| logs);
+                let tx_events = events
+                    .iter()
+                    .filter(|(location, _)| location.tx_hash == tx_hash)
+                    .flat_map(|(_, events)| events.iter().copied());
+                (tx_hash, ExecutionDigest::new(tx_writes, tx_events))
+            })
+            .collect();
+        storage
+            .transactions_dal()
+            .set_actual_execution_digests(&digests)
+            .await?;
+        Ok(digests.len())
+    }
+
+    /// Logs storage writes with human-readable slot labels. Only used for debugging, since
+    /// building the label registry requires hashing keys for every transaction initiator.
+    fn log_labeled_write_logs(&self, write_logs: &[(H256, Vec<StorageLog>)]) {
+        let mut labels = StorageKeyLabels::new();
+        for tx_result in &self.miniblock.executed_transactions {
+            labels.register_account(&tx_result.transaction.initiator_account());
+        }
+        for (tx_hash, logs) in write_logs {
+            for log in logs {
+                olaos_logs::debug!(
+                    "Miniblock {} tx {tx_hash:?} wrote {} = {:?}",
+                    self.miniblock_number,
+                    labels.label(&log.key),
+                    log.value
+                );
+            }
+        }
+    }
+
     fn transaction(&self, index: usize) -> &Transaction {
         let tx_result = &self.miniblock.executed_transactions[index - self.first_tx_index];
         &tx_result.transaction
@@ -294,9 +467,11 @@ impl UpdatesManager {
         current_l1_batch_number: L1BatchNumber,
         block_result: VmBlockResult,
         block_context: DerivedBlockContext,
-    ) {
+        fence: &LeaseFence,
+    ) -> DalResult<()> {
         let mut progress = SealProgress::for_l1_batch();
         let mut transaction = storage.start_transaction().await;
+        fence.check(&mut transaction).await;
 
         // The vm execution was paused right after the last transaction was executed.
         // There is some post-processing work that the VM needs to do before the block is fully processed.
@@ -313,9 +488,15 @@ impl UpdatesManager {
 
         self.extend_from_fictive_transaction(block_tip_result.logs);
         // Seal fictive miniblock with last events and storage logs.
-        let miniblock_command =
-            self.seal_miniblock_command(current_l1_batch_number, current_miniblock_number);
-        miniblock_command.seal_inner(&mut transaction, true).await;
+        // The fictive miniblock has no transactions, so there are no call outputs to truncate.
+        let miniblock_command = self.seal_miniblock_command(
+            current_l1_batch_number,
+            current_miniblock_number,
+            usize::MAX,
+        );
+        miniblock_command
+            .seal_inner(&mut transaction, true, fence)
+            .await?;
         progress.end_stage("fictive_miniblock", None);
 
         let (_, deduped_log_queries) = sort_storage_access_queries(
@@ -343,7 +524,7 @@ impl UpdatesManager {
 
         let (prev_hash, prev_timestamp) =
             extractors::wait_for_prev_l1_batch_params(&mut transaction, current_l1_batch_number)
-                .await;
+                .await?;
         let timestamp = block_context.context.block_timestamp;
         assert!(
             prev_timestamp < timestamp,
@@ -377,13 +558,30 @@ impl UpdatesManager {
         transaction
             .blocks_dal()
             .insert_l1_batch(&l1_batch, &initial_bootloader_contents)
-            .await;
+            .await?;
         progress.end_stage("insert_l1_batch_header", None);
 
+        let contract_metrics = ContractExecutionMetrics::aggregate(
+            self.l1_batch
+                .executed_transactions
+                .iter()
+                .map(|tx| (tx.transaction.execute.contract_address, &tx.execution_info)),
+            full_result
+                .storage_log_queries
+                .iter()
+                .map(|log| &log.log_query),
+            &full_result.events,
+        );
+        transaction
+            .contract_metrics_dal()
+            .insert_l1_batch_metrics(current_l1_batch_number, &contract_metrics)
+            .await?;
+        progress.end_stage("insert_contract_metrics", Some(contract_metrics.len()));
+
         transaction
             .blocks_dal()
             .mark_miniblocks_as_executed_in_l1_batch(current_l1_batch_number)
-            .await;
+            .await?;
         progress.end_stage("set_l1_batch_number_for_miniblocks", None);
 
         transaction
@@ -392,16 +590,36 @@ impl UpdatesManager {
                 current_l1_batch_number,
                 &self.l1_batch.executed_transactions,
             )
-            .await;
+            .await?;
         progress.end_stage("mark_txs_as_executed_in_l1_batch", None);
 
+        // Leaves must match `BlocksWeb3Dal::get_l1_batch_receipt_leaves()`, which rebuilds
+        // the tree from `l1_batch_tx_index` and `error` to serve inclusion proofs.
+        let receipt_leaves: Vec<_> = self
+            .l1_batch
+            .executed_transactions
+            .iter()
+            .enumerate()
+            .map(|(index, tx)| ReceiptLeaf {
+                tx_hash: tx.hash,
+                index_in_batch: index as u32,
+                succeeded: tx.execution_status == TxExecutionStatus::Success,
+            })
+            .collect();
+        let receipts_root = ReceiptsTree::new(&receipt_leaves).root();
+        transaction
+            .blocks_dal()
+            .set_l1_batch_receipts_root(current_l1_batch_number, receipts_root)
+            .await?;
+        progress.end_stage("set_l1_batch_receipts_root", Some(receipt_leaves.len()));
+
         let (deduplicated_writes, protective_reads): (Vec<_>, Vec<_>) = deduped_log_queries
             .into_iter()
             .partition(|log_query| log_query.rw_flag);
         transaction
             .storage_logs_dedup_dal()
             .insert_protective_reads(current_l1_batch_number, &protective_reads)
-            .await;
+            .await?;
         progress.end_stage("insert_protective_reads", Some(protective_reads.len()));
 
         let deduplicated_writes_hashed_keys: Vec<_> = deduplicated_writes
@@ -416,7 +634,7 @@ impl UpdatesManager {
         let non_initial_writes = transaction
             .storage_logs_dedup_dal()
             .filter_written_slots(&deduplicated_writes_hashed_keys)
-            .await;
+            .await?;
         progress.end_stage("filter_written_slots", Some(deduplicated_writes.len()));
 
         let written_storage_keys: Vec<_> = deduplicated_writes
@@ -428,13 +646,13 @@ impl UpdatesManager {
             .collect();
 
         // One-time migration completion for initial writes' indices.
-        set_missing_initial_writes_indices(&mut transaction).await;
+        set_missing_initial_writes_indices(&mut transaction).await?;
         progress.end_stage("set_missing_initial_writes_indices", None);
 
         transaction
             .storage_logs_dedup_dal()
             .insert_initial_writes(current_l1_batch_number, &written_storage_keys)
-            .await;
+            .await?;
         progress.end_stage("insert_initial_writes", Some(written_storage_keys.len()));
 
         transaction.commit().await;
@@ -447,6 +665,7 @@ impl UpdatesManager {
             writes_metrics.initial_storage_writes + writes_metrics.repeated_storage_writes,
             "Results of in-flight and common deduplications are mismatched"
         );
+        Ok(())
     }
 
     pub(crate) fn initial_bootloader_memory(
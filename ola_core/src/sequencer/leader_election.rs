@@ -0,0 +1,193 @@
+//! Leader election for running several sequencer instances with exactly one active.
+//!
+//! The leader holds a lease in Postgres and renews it periodically. Standby instances keep their
+//! RocksDB cache in sync with sealed L1 batches and try to acquire the lease; once the lease of
+//! an unresponsive leader expires, one of them takes over. Each acquisition increments the fencing
+//! token of the lease, and all sealing transactions check the lease, so a former leader can't
+//! write after it lost the lease.
+
+use std::{
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+
+use ola_config::{database::DBConfig, sequencer::SequencerConfig};
+use ola_dal::{connection::ConnectionPool, sequencer_leases_dal::SequencerLease, StorageProcessor};
+use ola_state::rocksdb::RocksdbStorage;
+use olaos_storage::RocksDBOptions;
+use tokio::sync::watch;
+
+/// Lease of the current instance checked before writing sealed data. Does nothing if leader
+/// election is disabled.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LeaseFence(Arc<OnceLock<SequencerLease>>);
+
+impl LeaseFence {
+    /// Checks that the current instance is still the leader. Must be called within the sealing
+    /// transaction, so that the lease can't be taken over until the transaction is committed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lease is lost, refusing to write sealed data.
+    pub(crate) async fn check(&self, storage: &mut StorageProcessor<'_>) {
+        let Some(lease) = self.0.get() else {
+            return;
+        };
+        let is_valid = storage
+            .sequencer_leases_dal()
+            .check(lease)
+            .await
+            .expect("failed checking sequencer lease");
+        assert!(
+            is_valid,
+            "Sequencer instance `{}` lost its leadership lease (fencing token {}); refusing to write",
+            lease.holder,
+            lease.fencing_token
+        );
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct LeaderElection {
+    pool: ConnectionPool,
+    instance_id: String,
+    lease_ttl: Duration,
+    renew_interval: Duration,
+    fence: LeaseFence,
+}
+
+impl LeaderElection {
+    pub fn new(config: &SequencerConfig, pool: ConnectionPool) -> Self {
+        Self {
+            pool,
+            instance_id: config.instance_id(),
+            lease_ttl: config.leader_lease_ttl(),
+            renew_interval: config.leader_lease_renew_interval(),
+            fence: LeaseFence::default(),
+        }
+    }
+
+    pub fn fence(&self) -> LeaseFence {
+        self.fence.clone()
+    }
+
+    /// Waits until the current instance acquires the lease, keeping the sequencer RocksDB cache
+    /// warm in the meantime. Returns `None` if a stop signal is received first.
+    pub async fn wait_for_leadership(
+        &self,
+        db_config: &DBConfig,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<Option<SequencerLease>> {
+        let mut cache = RocksdbStorage::with_options(
+            db_config.sequencer_db_path.as_ref(),
+            RocksDBOptions {
+                io_stats_interval: db_config.sequencer_io_stats_interval(),
+                ..RocksDBOptions::default()
+            },
+        );
+        olaos_logs::info!(
+            "Sequencer instance `{}` is waiting for the leadership lease",
+            self.instance_id
+        );
+        metrics::gauge!("server.sequencer.is_leader", 0.0);
+
+        loop {
+            let mut storage = self.pool.access_storage_tagged("sequencer").await;
+            let lease = match storage
+                .sequencer_leases_dal()
+                .try_acquire(&self.instance_id, self.lease_ttl)
+                .await
+            {
+                Ok(lease) => lease,
+                // Acquisition is retried on the next iteration anyway.
+                Err(err) if err.is_retriable() => {
+                    olaos_logs::warn!("Failed acquiring the sequencer leadership lease: {err}");
+                    None
+                }
+                Err(err) => return Err(err.into()),
+            };
+            if let Some(lease) = lease {
+                drop(storage);
+                // The sequencer opens the cache on its own.
+                drop(cache);
+                olaos_logs::info!(
+                    "Sequencer instance `{}` acquired the leadership lease (fencing token {})",
+                    lease.holder,
+                    lease.fencing_token
+                );
+                metrics::gauge!("server.sequencer.is_leader", 1.0);
+                metrics::counter!("server.sequencer.leader_takeovers", 1);
+                self.fence
+                    .0
+                    .set(lease.clone())
+                    .expect("leadership lease is acquired twice");
+                return Ok(Some(lease));
+            }
+
+            match cache.update_from_postgres(&mut storage).await {
+                Ok(()) => {}
+                // The cache catches up on the next iteration anyway.
+                Err(err) if err.is_retriable() => {
+                    olaos_logs::warn!("Failed updating the standby sequencer cache: {err}");
+                }
+                Err(err) => return Err(err.into()),
+            }
+            drop(storage);
+
+            if tokio::time::timeout(self.renew_interval, stop_receiver.changed())
+                .await
+                .is_ok()
+            {
+                olaos_logs::info!("Stop signal received, standby sequencer is shutting down");
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Runs `task` while renewing the lease. Fails if the lease is lost; releases the lease
+    /// once `task` completes, so that a standby can take over immediately.
+    pub async fn run_as_leader(
+        self,
+        lease: SequencerLease,
+        task: impl std::future::Future<Output = anyhow::Result<()>>,
+    ) -> anyhow::Result<()> {
+        tokio::pin!(task);
+        let mut interval = tokio::time::interval(self.renew_interval);
+        loop {
+            tokio::select! {
+                result = &mut task => {
+                    let mut storage = self.pool.access_storage_tagged("sequencer").await;
+                    storage.sequencer_leases_dal().release(&lease).await?;
+                    metrics::gauge!("server.sequencer.is_leader", 0.0);
+                    olaos_logs::info!("Released the sequencer leadership lease");
+                    return result;
+                }
+                _ = interval.tick() => {
+                    let mut storage = self.pool.access_storage_tagged("sequencer").await;
+                    let renewed = match storage
+                        .sequencer_leases_dal()
+                        .renew(&lease, self.lease_ttl)
+                        .await
+                    {
+                        Ok(renewed) => renewed,
+                        // The lease outlives several renewal intervals, so a transient error
+                        // is retried on the next tick rather than giving up the leadership.
+                        Err(err) if err.is_retriable() => {
+                            olaos_logs::warn!(
+                                "Failed renewing the sequencer leadership lease: {err}; retrying"
+                            );
+                            continue;
+                        }
+                        Err(err) => return Err(err.into()),
+                    };
+                    anyhow::ensure!(
+                        renewed,
+                        "sequencer instance `{}` lost its leadership lease (fencing token {})",
+                        lease.holder,
+                        lease.fencing_token
+                    );
+                }
+            }
+        }
+    }
+}
@@ -1,17 +1,32 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use anyhow::Ok;
+use anyhow::{Context as _, Ok};
+use chrono::NaiveDateTime;
 use ola_config::chain::MempoolConfig;
 use ola_dal::connection::ConnectionPool;
+use ola_utils::time::seconds_since_epoch;
 use tokio::sync::watch;
 
 use super::types::MempoolGuard;
 
+/// Transactions are received by the API before they are inserted into Postgres, so a transaction
+/// committed after a sync may have been received before the latest synced one. The sync cursor
+/// lags behind by this much to pick such transactions up.
+const SYNC_CURSOR_LOOKBACK: Duration = Duration::from_secs(60);
+/// Interval between syncs ignoring the cursor, which pick up transactions returned to the pool
+/// of pending transactions behind the cursor.
+const FULL_SYNC_INTERVAL: Duration = Duration::from_secs(300);
+
 #[derive(Debug)]
 pub struct MempoolFetcher {
     mempool: MempoolGuard,
     sync_interval: Duration,
     sync_batch_size: usize,
+    max_scheduled_transactions: usize,
+    /// Latest receipt time of the L2 transactions loaded into the mempool. Transactions
+    /// received before it (minus [`SYNC_CURSOR_LOOKBACK`]) are not rescanned.
+    last_received_at: Option<NaiveDateTime>,
+    last_full_sync: Instant,
 }
 
 impl MempoolFetcher {
@@ -20,6 +35,9 @@ impl MempoolFetcher {
             mempool,
             sync_interval: config.sync_interval(),
             sync_batch_size: config.sync_batch_size,
+            max_scheduled_transactions: config.max_scheduled_transactions() as usize,
+            last_received_at: None,
+            last_full_sync: Instant::now(),
         }
     }
 
@@ -36,10 +54,15 @@ impl MempoolFetcher {
                 let removed_txs = storage
                     .transactions_dal()
                     .remove_stuck_txs(stuck_tx_timeout)
-                    .await;
+                    .await
+                    .context("remove_stuck_txs()")?;
                 olaos_logs::info!("Number of stuck txs was removed: {}", removed_txs);
             }
-            storage.transactions_dal().reset_mempool().await;
+            storage
+                .transactions_dal()
+                .reset_mempool()
+                .await
+                .context("reset_mempool()")?;
         }
 
         loop {
@@ -58,14 +81,40 @@ impl MempoolFetcher {
                 olaos_logs::info!("purged accounts {:?}", mempool_info.purged_accounts);
             }
 
-            let (transactions, nonces) = storage
+            // Transactions of stashed accounts are returned to the pool of pending transactions,
+            // so they must be rescanned regardless of their receipt time.
+            if !mempool_info.stashed_accounts.is_empty()
+                || self.last_full_sync.elapsed() >= FULL_SYNC_INTERVAL
+            {
+                self.last_received_at = None;
+                self.last_full_sync = Instant::now();
+            }
+            let received_after = self.last_received_at.map(|received_at| {
+                received_at - chrono::Duration::from_std(SYNC_CURSOR_LOOKBACK).unwrap()
+            });
+
+            // Scheduled transactions are capped separately, so that they can't fill the mempool
+            // while they cannot be executed.
+            let scheduled_limit = self
+                .max_scheduled_transactions
+                .saturating_sub(self.mempool.scheduled_len(seconds_since_epoch()));
+
+            let mut transaction = storage.start_transaction().await;
+            let (transactions, nonces, last_received_at) = transaction
                 .transactions_dal()
                 .sync_mempool(
                     mempool_info.stashed_accounts,
                     mempool_info.purged_accounts,
                     self.sync_batch_size,
+                    scheduled_limit,
+                    received_after,
                 )
-                .await;
+                .await
+                .context("sync_mempool()")?;
+            transaction.commit().await;
+            // The cursor only advances once the transactions are marked as loaded.
+            self.last_received_at = self.last_received_at.max(last_received_at);
+
             if transactions.len() > 0 {
                 olaos_logs::info!("Sync {:?} transactions from mempool", transactions.len());
                 for tx in transactions.iter() {
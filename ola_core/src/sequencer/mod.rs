@@ -3,25 +3,42 @@ use ola_config::{
     database::DBConfig, sequencer::SequencerConfig,
 };
 use ola_dal::connection::ConnectionPool;
+use ola_state::rocksdb::RocksdbStorage;
 use ola_types::tx::tx_execution_info::{DeduplicatedWritesMetrics, ExecutionMetrics};
+use ola_types::L1BatchNumber;
 use tokio::sync::watch;
 
-use crate::sequencer::{
-    batch_executor::MainBatchExecutorBuilder, io::mempool::MempoolIO, seal_criteria::SealManager,
+use crate::{
+    audit::AuditLog,
+    sequencer::{
+        batch_executor::MainBatchExecutorBuilder, io::mempool::MempoolIO,
+        seal_criteria::SealManager,
+    },
 };
 use olaos_object_store::ObjectStore;
+use olaos_storage::RocksDBOptions;
 use std::sync::Arc;
 
-use self::{io::MiniblockSealerHandle, sequencer::OlaSequencer, types::MempoolGuard};
+use self::{
+    io::MiniblockSealerHandle,
+    leader_election::LeaseFence,
+    sequencer::OlaSequencer,
+    types::{MempoolGuard, PendingStateGuard},
+};
 
 pub mod batch_executor;
 pub mod extractors;
 pub mod io;
+pub mod leader_election;
 pub mod mempool_actor;
+pub mod operator_rotation;
 pub mod seal_criteria;
 pub mod sequencer;
+pub mod snapshot_sync;
 pub mod types;
 pub mod updates;
+pub mod upgrade_dry_run;
+pub mod watchdog;
 
 #[derive(Debug, Default)]
 pub struct SealData {
@@ -40,6 +57,8 @@ pub(crate) async fn create_sequencer(
     mempool: MempoolGuard,
     miniblock_sealer_handle: MiniblockSealerHandle,
     object_store: Arc<dyn ObjectStore>,
+    pending_state: Option<PendingStateGuard>,
+    fence: LeaseFence,
     stop_receiver: watch::Receiver<bool>,
 ) -> OlaSequencer {
     assert!(
@@ -49,8 +68,27 @@ pub(crate) async fn create_sequencer(
         MAX_TXS_IN_BLOCK
     );
 
+    let sequencer_db_options = RocksDBOptions {
+        io_stats_interval: db_config.sequencer_io_stats_interval(),
+        ..RocksDBOptions::default()
+    };
+    if let Some(l1_batch_number) = db_config.sequencer_snapshot_l1_batch {
+        let mut storage = RocksdbStorage::with_options(
+            db_config.sequencer_db_path.as_ref(),
+            sequencer_db_options,
+        );
+        snapshot_sync::recover_from_snapshot(
+            &mut storage,
+            object_store.as_ref(),
+            L1BatchNumber(l1_batch_number),
+        )
+        .await
+        .expect("failed recovering sequencer DB from snapshot");
+    }
+
     let batch_executor_base = MainBatchExecutorBuilder::new(
         db_config.sequencer_db_path.clone(),
+        sequencer_db_options,
         db_config.merkle_tree.path.clone(),
         pool.clone(),
         sequencer_config.save_call_traces,
@@ -62,6 +100,8 @@ pub(crate) async fn create_sequencer(
         pool,
         &sequencer_config,
         mempool_config.delay_interval(),
+        fence,
+        AuditLog::from_config(db_config),
     )
     .await;
 
@@ -72,5 +112,6 @@ pub(crate) async fn create_sequencer(
         Box::new(batch_executor_base),
         sealer,
         object_store,
+        pending_state,
     )
 }
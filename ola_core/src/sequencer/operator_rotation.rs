@@ -0,0 +1,112 @@
+//! Rotation of the operator address (`ola_node rotate-operator`).
+//!
+//! The rotation is recorded as pending and takes effect when the sequencer opens the next L1 batch;
+//! the L1 batch being executed keeps its operator. The operator of each L1 batch is stored in its
+//! header and is a part of the L1 batch commitment, so L1 verifies the rotation.
+
+use anyhow::Context as _;
+use ola_dal::connection::ConnectionPool;
+use ola_types::{api::AuditAction, Address};
+
+use crate::audit::{cli_actor, AuditLog};
+
+/// Arguments of `ola_node rotate-operator`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RotateOperatorArgs {
+    pub operator_address: Address,
+}
+
+impl RotateOperatorArgs {
+    pub const USAGE: &'static str = "usage: ola_node rotate-operator --address <address>";
+
+    /// Parses arguments following the `rotate-operator` subcommand.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Self> {
+        let mut operator_address = None;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .with_context(|| format!("missing value for `{arg}`; {}", Self::USAGE))?;
+            match arg.as_str() {
+                "--address" => {
+                    let address = value
+                        .parse()
+                        .with_context(|| format!("invalid operator address `{value}`"))?;
+                    operator_address = Some(address);
+                }
+                _ => anyhow::bail!("unknown argument `{arg}`; {}", Self::USAGE),
+            }
+        }
+
+        let operator_address: Address = operator_address.context(Self::USAGE)?;
+        anyhow::ensure!(
+            !operator_address.is_zero(),
+            "operator address must not be zero"
+        );
+        Ok(Self { operator_address })
+    }
+}
+
+pub async fn run_rotate_operator(
+    pool: &ConnectionPool,
+    audit_log: &AuditLog,
+    args: &RotateOperatorArgs,
+) -> anyhow::Result<()> {
+    let mut storage = pool.access_storage_tagged("rotate_operator").await;
+    let replaced = storage.operators_dal().get_pending_rotation().await?;
+    storage
+        .operators_dal()
+        .request_rotation(args.operator_address)
+        .await?;
+    let next_l1_batch = storage.blocks_dal().get_sealed_l1_batch_number().await? + 1;
+    let parameters = serde_json::json!({
+        "operatorAddress": args.operator_address,
+        "replacedPendingAddress": replaced,
+    });
+    audit_log
+        .record(
+            &mut storage,
+            AuditAction::OperatorRotation,
+            &cli_actor(),
+            parameters,
+        )
+        .await?;
+
+    if let Some(replaced) = replaced {
+        olaos_logs::info!("Replaced pending rotation to {replaced:?}");
+    }
+    olaos_logs::info!(
+        "Operator rotation to {:?} is requested; it takes effect on the next L1 batch opened \
+         by the sequencer (L1 batch #{next_l1_batch} at the earliest)",
+        args.operator_address
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> anyhow::Result<RotateOperatorArgs> {
+        RotateOperatorArgs::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parsing_rotate_operator_args() {
+        let address = "0x0000000000000000000000000000000000000000000000000000000000000001";
+        assert_eq!(
+            args(&["--address", address]).unwrap().operator_address,
+            Address::from_low_u64_be(1)
+        );
+
+        assert!(args(&[]).is_err());
+        assert!(args(&["--address"]).is_err());
+        assert!(args(&["--address", "0x01"]).is_err());
+        assert!(args(&[
+            "--address",
+            "0x0000000000000000000000000000000000000000000000000000000000000000"
+        ])
+        .is_err());
+        assert!(args(&["--operator", address]).is_err());
+    }
+}
@@ -61,12 +61,11 @@ impl ConditionalSealer {
     }
 
     fn default_sealers() -> Vec<Box<dyn SealCriterion>> {
-        // TODO: MaxCyclesCriterion? limit_cpu_cycle_per_block(main trace table size)
         vec![
             Box::new(criteria::SlotsCriterion),
             // Box::new(criteria::InitialWritesCriterion),
             // Box::new(criteria::RepeatedWritesCriterion),
-            // Box::new(criteria::MaxCyclesCriterion),
+            Box::new(criteria::MaxCyclesCriterion),
             Box::new(criteria::TxEncodingSizeCriterion),
         ]
     }
@@ -13,6 +13,8 @@ use crate::sequencer::{
 pub struct RepeatedWritesCriterion;
 #[derive(Debug, Default)]
 pub struct InitialWritesCriterion;
+#[derive(Debug, Default)]
+pub struct MaxCyclesCriterion;
 
 trait MetricExtractor {
     const PROM_METRIC_CRITERION_NAME: &'static str;
@@ -80,3 +82,63 @@ impl MetricExtractor for InitialWritesCriterion {
         writes.initial_storage_writes
     }
 }
+
+impl MetricExtractor for MaxCyclesCriterion {
+    const PROM_METRIC_CRITERION_NAME: &'static str = "max_cycles";
+
+    fn limit_per_block() -> usize {
+        GEOMETRY_CONFIG.limit_for_cpu_cycles as usize
+    }
+
+    fn extract(metrics: &ExecutionMetrics, _writes: &DeduplicatedWritesMetrics) -> usize {
+        metrics.cycles_used as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seal_data(cycles_used: u32) -> SealData {
+        SealData {
+            execution_metrics: ExecutionMetrics {
+                cycles_used,
+                ..ExecutionMetrics::default()
+            },
+            ..SealData::default()
+        }
+    }
+
+    #[test]
+    fn max_cycles_criterion() {
+        let config = SequencerConfig {
+            reject_tx_at_geometry_percentage: 0.3,
+            close_block_at_geometry_percentage: 0.5,
+            ..SequencerConfig::default()
+        };
+        let limit = GEOMETRY_CONFIG.limit_for_cpu_cycles;
+        let should_seal = |block_cycles, tx_cycles| {
+            MaxCyclesCriterion.should_seal(
+                &config,
+                0,
+                1,
+                &seal_data(block_cycles),
+                &seal_data(tx_cycles),
+            )
+        };
+
+        assert_eq!(should_seal(limit / 4, limit / 10), SealResolution::NoSeal);
+        assert_eq!(
+            should_seal(limit / 5 * 3, limit / 10),
+            SealResolution::IncludeAndSeal
+        );
+        assert_eq!(
+            should_seal(limit, limit / 10),
+            SealResolution::ExcludeAndSeal
+        );
+        assert!(matches!(
+            should_seal(limit / 2, limit / 2),
+            SealResolution::Unexecutable(_)
+        ));
+    }
+}
@@ -2,6 +2,8 @@ pub mod slots;
 
 pub use slots::SlotsCriterion;
 pub mod geometry_seal_criteria;
-pub use geometry_seal_criteria::{InitialWritesCriterion, RepeatedWritesCriterion};
+pub use geometry_seal_criteria::{
+    InitialWritesCriterion, MaxCyclesCriterion, RepeatedWritesCriterion,
+};
 pub mod tx_encoding_size;
 pub use tx_encoding_size::TxEncodingSizeCriterion;
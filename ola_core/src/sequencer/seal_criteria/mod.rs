@@ -109,9 +109,10 @@ impl fmt::Debug for SealManager {
 impl SealManager {
     /// Creates a default pre-configured seal manager for the main node.
     pub(super) fn new(config: SequencerConfig) -> Self {
-        let timeout_batch_sealer = Self::timeout_batch_sealer(config.block_commit_deadline_ms);
+        let timeout_batch_sealer =
+            Self::timeout_batch_sealer(config.block_commit_deadline().as_millis() as u64);
         let timeout_miniblock_sealer =
-            Self::timeout_miniblock_sealer(config.miniblock_commit_deadline_ms);
+            Self::timeout_miniblock_sealer(config.miniblock_commit_deadline().as_millis() as u64);
         // Currently, it's assumed that timeout is the only criterion for miniblock sealing.
         // If this doesn't hold and some miniblocks are sealed in less than 1 second,
         // then sequencer will be blocked waiting for the miniblock timestamp to be changed.
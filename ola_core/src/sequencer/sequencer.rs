@@ -7,12 +7,16 @@ use ola_types::{
     tx::tx_execution_info::TxExecutionStatus, Transaction,
 };
 
-use ola_vm::errors::TxRevertReason;
+use ola_vm::{errors::TxRevertReason, vm::VmTxExeResult};
 use tokio::sync::watch;
 
 use crate::sequencer::{
-    batch_executor::TxExecutionResult, extractors, io::PendingBatchData,
-    types::ExecutionMetricsForCriteria, updates::UpdatesManager, SealData,
+    batch_executor::TxExecutionResult,
+    extractors,
+    io::PendingBatchData,
+    types::{ExecutionMetricsForCriteria, PendingStateGuard},
+    updates::UpdatesManager,
+    SealData,
 };
 use olaos_object_store::ObjectStore;
 use std::sync::Arc;
@@ -50,6 +54,7 @@ pub struct OlaSequencer {
     batch_executor_base: Box<dyn L1BatchExecutorBuilder>,
     sealer: SealManager,
     object_store: Arc<dyn ObjectStore>,
+    pending_state: Option<PendingStateGuard>,
 }
 
 impl OlaSequencer {
@@ -59,6 +64,7 @@ impl OlaSequencer {
         batch_executor_base: Box<dyn L1BatchExecutorBuilder>,
         sealer: SealManager,
         object_store: Arc<dyn ObjectStore>,
+        pending_state: Option<PendingStateGuard>,
     ) -> Self {
         OlaSequencer {
             stop_receiver,
@@ -66,6 +72,7 @@ impl OlaSequencer {
             batch_executor_base,
             sealer,
             object_store,
+            pending_state,
         }
     }
 
@@ -139,7 +146,7 @@ impl OlaSequencer {
         let mut batch_executor = self
             .batch_executor_base
             .init_batch(l1_batch_params.clone())
-            .await;
+            .await?;
         self.restore_state(&batch_executor, &mut updates_manager, pending_miniblocks)
             .await?;
 
@@ -199,6 +206,8 @@ impl OlaSequencer {
                     l1_batch_params.context_mode.inner_block_context(),
                 )
                 .await;
+            // The batch is persisted now, so the API can read its state from Postgres.
+            self.reset_pending_state();
 
             // Start the new batch.
             l1_batch_params = self.wait_for_new_batch_params().await?;
@@ -213,7 +222,7 @@ impl OlaSequencer {
             batch_executor = self
                 .batch_executor_base
                 .init_batch(l1_batch_params.clone())
-                .await;
+                .await?;
 
             let version_changed = l1_batch_params.protocol_version != sealed_batch_protocol_version;
             protocol_upgrade_tx = if version_changed {
@@ -226,6 +235,19 @@ impl OlaSequencer {
         }
     }
 
+    /// Drops writes of the sealed batch from the state published for the API.
+    fn reset_pending_state(&self) {
+        if let Some(pending_state) = &self.pending_state {
+            pending_state.clear();
+        }
+    }
+
+    fn publish_pending_writes(&self, tx_result: &VmTxExeResult) {
+        if let Some(pending_state) = &self.pending_state {
+            pending_state.apply(&tx_result.result.logs.storage_logs);
+        }
+    }
+
     fn check_if_cancelled(&self) -> Result<(), Error> {
         if *self.stop_receiver.borrow() {
             return Err(Error::Canceled);
@@ -311,6 +333,7 @@ impl OlaSequencer {
 
                 let tx_hash = tx.hash();
                 let initiator_account = tx.initiator_account();
+                self.publish_pending_writes(&tx_result);
                 updates_manager.extend_from_executed_transaction(
                     tx,
                     *tx_result,
@@ -384,7 +407,12 @@ impl OlaSequencer {
                 updates_manager.push_miniblock(new_timestamp);
             }
 
-            let Some(tx) = self.io.wait_for_next_tx(POLL_WAIT_DURATION).await else {
+            let miniblock_timestamp = updates_manager.miniblock.timestamp;
+            let Some(tx) = self
+                .io
+                .wait_for_next_tx(POLL_WAIT_DURATION, miniblock_timestamp)
+                .await
+            else {
                 olaos_logs::info!("No new transactions. Waiting!");
                 continue;
             };
@@ -409,6 +437,7 @@ impl OlaSequencer {
                     let ExecutionMetricsForCriteria {
                         execution_metrics: tx_execution_metrics,
                     } = tx_metrics;
+                    self.publish_pending_writes(&tx_result);
                     updates_manager.extend_from_executed_transaction(
                         tx,
                         *tx_result,
@@ -420,6 +449,7 @@ impl OlaSequencer {
                 }
                 SealResolution::Unexecutable(reason) => {
                     self.io.reject(&tx, reason).await;
+                    updates_manager.extend_from_rejected_transaction(tx_hash, reason);
                 }
             };
 
@@ -472,6 +502,7 @@ impl OlaSequencer {
                     execution_metrics: tx_execution_metrics,
                     ..
                 } = tx_metrics;
+                self.publish_pending_writes(&tx_result);
                 updates_manager.extend_from_executed_transaction(
                     tx,
                     *tx_result,
@@ -518,7 +549,6 @@ impl OlaSequencer {
                 tx_result,
                 tx_metrics,
                 entrypoint_dry_run_metrics,
-                entrypoint_dry_run_result,
                 ..
             } => {
                 let tx_execution_status = tx_result.status;
@@ -548,9 +578,9 @@ impl OlaSequencer {
 
                 let encoding_len = extractors::encoded_transaction_size(tx);
 
+                // Storage keys written by the block tip aren't known before it's run, so only
+                // the writes of the transaction are deduplicated against the writes of the batch.
                 let logs_to_apply = tx_result.result.logs.storage_logs.iter();
-                let logs_to_apply =
-                    logs_to_apply.chain(&entrypoint_dry_run_result.logs.storage_logs);
                 let block_writes_metrics = updates_manager
                     .storage_writes_deduplicator
                     .apply_and_rollback(logs_to_apply.clone());
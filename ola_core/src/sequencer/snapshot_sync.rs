@@ -0,0 +1,247 @@
+//! Fast sync of the sequencer RocksDB cache from a state snapshot in the object store.
+//!
+//! A snapshot for an L1 batch consists of a [`SnapshotHeader`], storage logs split into chunks by
+//! hashed key ranges, and factory deps. It is created with `ola_node create-snapshot`. An empty
+//! sequencer cache is initialized from the snapshot chunk by chunk (an interrupted recovery resumes
+//! from the next chunk), after which only the L1 batches following the snapshot are loaded
+//! from Postgres.
+
+use std::time::Instant;
+
+use anyhow::Context as _;
+use ola_dal::connection::ConnectionPool;
+use ola_state::rocksdb::RocksdbStorage;
+use ola_types::{
+    snapshots::{
+        uniform_hashed_keys_chunk, SnapshotFactoryDependencies, SnapshotHeader,
+        SnapshotStorageLogsChunk, SnapshotStorageLogsStorageKey,
+    },
+    L1BatchNumber,
+};
+use olaos_object_store::ObjectStore;
+
+/// Initializes an empty `storage` from the snapshot for `l1_batch_number`, or resumes
+/// an interrupted recovery. Does nothing if `storage` already contains state.
+pub async fn recover_from_snapshot(
+    storage: &mut RocksdbStorage,
+    object_store: &dyn ObjectStore,
+    l1_batch_number: L1BatchNumber,
+) -> anyhow::Result<()> {
+    let first_chunk_id = match storage.snapshot_recovery_progress() {
+        Some((recovered_l1_batch, next_chunk_id)) => {
+            anyhow::ensure!(
+                recovered_l1_batch == l1_batch_number,
+                "sequencer cache is being recovered from the snapshot for L1 batch \
+                 #{recovered_l1_batch}, but the snapshot for L1 batch #{l1_batch_number} \
+                 is configured"
+            );
+            next_chunk_id
+        }
+        None if storage.is_empty() => 0,
+        None => {
+            olaos_logs::info!("Sequencer cache is not empty, skipping snapshot recovery");
+            return Ok(());
+        }
+    };
+
+    let started_at = Instant::now();
+    let header: SnapshotHeader = object_store.get(l1_batch_number).await.with_context(|| {
+        format!("failed fetching header of snapshot for L1 batch #{l1_batch_number}")
+    })?;
+    let chunk_count = header.storage_logs_chunk_count;
+    olaos_logs::info!(
+        "Recovering sequencer cache from snapshot for L1 batch #{l1_batch_number} \
+         (miniblock #{}), starting from chunk {first_chunk_id}/{chunk_count}",
+        header.miniblock_number
+    );
+
+    for chunk_id in first_chunk_id..chunk_count {
+        let key = SnapshotStorageLogsStorageKey {
+            l1_batch_number,
+            chunk_id,
+        };
+        let chunk: SnapshotStorageLogsChunk = object_store
+            .get(key)
+            .await
+            .with_context(|| format!("failed fetching snapshot storage logs chunk {chunk_id}"))?;
+        let log_count = chunk.storage_logs.len();
+        storage
+            .apply_snapshot_chunk(l1_batch_number, chunk_id, chunk.storage_logs)
+            .await;
+
+        metrics::gauge!(
+            "server.sequencer.snapshot_recovery.chunks_applied",
+            (chunk_id + 1) as f64
+        );
+        metrics::counter!(
+            "server.sequencer.snapshot_recovery.storage_logs",
+            log_count as u64
+        );
+        olaos_logs::info!(
+            "Applied snapshot chunk {}/{chunk_count} with {log_count} storage logs, elapsed {:?}",
+            chunk_id + 1,
+            started_at.elapsed()
+        );
+    }
+
+    let factory_deps: SnapshotFactoryDependencies = object_store
+        .get(l1_batch_number)
+        .await
+        .context("failed fetching snapshot factory deps")?;
+    let factory_dep_count = factory_deps.factory_deps.len();
+    storage
+        .finish_snapshot_recovery(l1_batch_number, factory_deps.factory_deps)
+        .await;
+
+    let elapsed = started_at.elapsed();
+    metrics::histogram!("server.sequencer.snapshot_recovery.latency", elapsed);
+    olaos_logs::info!(
+        "Recovered sequencer cache from snapshot for L1 batch #{l1_batch_number} \
+         ({factory_dep_count} factory deps) in {elapsed:?}; following L1 batches are loaded \
+         from Postgres"
+    );
+    Ok(())
+}
+
+/// Arguments of `ola_node create-snapshot`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateSnapshotArgs {
+    pub l1_batch_number: L1BatchNumber,
+    pub chunk_count: u64,
+}
+
+impl CreateSnapshotArgs {
+    pub const USAGE: &'static str =
+        "usage: ola_node create-snapshot --l1-batch <number> [--chunks <count>]";
+    const DEFAULT_CHUNK_COUNT: u64 = 64;
+
+    /// Parses arguments following the `create-snapshot` subcommand.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Self> {
+        let mut l1_batch_number = None;
+        let mut chunk_count = Self::DEFAULT_CHUNK_COUNT;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .with_context(|| format!("missing value for `{arg}`; {}", Self::USAGE))?;
+            match arg.as_str() {
+                "--l1-batch" => l1_batch_number = Some(L1BatchNumber(value.parse()?)),
+                "--chunks" => chunk_count = value.parse()?,
+                _ => anyhow::bail!("unknown argument `{arg}`; {}", Self::USAGE),
+            }
+        }
+
+        let l1_batch_number = l1_batch_number.context(Self::USAGE)?;
+        anyhow::ensure!(chunk_count > 0, "--chunks must be positive");
+        Ok(Self {
+            l1_batch_number,
+            chunk_count,
+        })
+    }
+}
+
+/// Writes the snapshot of the storage state as of the requested sealed L1 batch
+/// to the object store.
+pub async fn run_create_snapshot(
+    pool: &ConnectionPool,
+    object_store: &dyn ObjectStore,
+    args: &CreateSnapshotArgs,
+) -> anyhow::Result<()> {
+    let l1_batch_number = args.l1_batch_number;
+    let mut storage = pool.access_storage_tagged("create_snapshot").await;
+    let sealed_l1_batch = storage.blocks_dal().get_sealed_l1_batch_number().await?;
+    anyhow::ensure!(
+        l1_batch_number <= sealed_l1_batch,
+        "L1 batch #{l1_batch_number} is not sealed; the last sealed L1 batch is #{sealed_l1_batch}"
+    );
+    let (_, miniblock_number) = storage
+        .blocks_dal()
+        .get_miniblock_range_of_l1_batch(l1_batch_number)
+        .await?
+        .with_context(|| format!("L1 batch #{l1_batch_number} has no miniblocks"))?;
+
+    let started_at = Instant::now();
+    for chunk_id in 0..args.chunk_count {
+        let key_range = uniform_hashed_keys_chunk(chunk_id, args.chunk_count);
+        let storage_logs = storage
+            .storage_logs_dal()
+            .get_snapshot_storage_logs(miniblock_number, key_range)
+            .await?;
+        let log_count = storage_logs.len();
+        let key = SnapshotStorageLogsStorageKey {
+            l1_batch_number,
+            chunk_id,
+        };
+        object_store
+            .put(key, &SnapshotStorageLogsChunk { storage_logs })
+            .await
+            .with_context(|| format!("failed saving snapshot storage logs chunk {chunk_id}"))?;
+        olaos_logs::info!(
+            "Saved snapshot chunk {}/{} with {log_count} storage logs, elapsed {:?}",
+            chunk_id + 1,
+            args.chunk_count,
+            started_at.elapsed()
+        );
+    }
+
+    let factory_deps = storage
+        .storage_dal()
+        .get_snapshot_factory_deps(miniblock_number)
+        .await?;
+    object_store
+        .put(
+            l1_batch_number,
+            &SnapshotFactoryDependencies { factory_deps },
+        )
+        .await
+        .context("failed saving snapshot factory deps")?;
+
+    // The header is written last, so that a snapshot is only visible once it's complete.
+    let header = SnapshotHeader {
+        l1_batch_number,
+        miniblock_number,
+        storage_logs_chunk_count: args.chunk_count,
+    };
+    object_store
+        .put(l1_batch_number, &header)
+        .await
+        .context("failed saving snapshot header")?;
+    olaos_logs::info!(
+        "Created snapshot for L1 batch #{l1_batch_number} (miniblock #{miniblock_number}) in {:?}",
+        started_at.elapsed()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> anyhow::Result<CreateSnapshotArgs> {
+        CreateSnapshotArgs::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parsing_create_snapshot_args() {
+        assert_eq!(
+            args(&["--l1-batch", "10"]).unwrap(),
+            CreateSnapshotArgs {
+                l1_batch_number: L1BatchNumber(10),
+                chunk_count: CreateSnapshotArgs::DEFAULT_CHUNK_COUNT,
+            }
+        );
+        assert_eq!(
+            args(&["--l1-batch", "10", "--chunks", "4"])
+                .unwrap()
+                .chunk_count,
+            4
+        );
+
+        assert!(args(&[]).is_err());
+        assert!(args(&["--chunks", "4"]).is_err());
+        assert!(args(&["--l1-batch", "10", "--chunks", "0"]).is_err());
+        assert!(args(&["--l1-batch"]).is_err());
+        assert!(args(&["--batch", "10"]).is_err());
+    }
+}
@@ -1,19 +1,45 @@
 use std::{
     collections::HashMap,
-    sync::{Arc, Mutex},
+    sync::{Arc, Mutex, RwLock},
 };
 
+use ola_config::sequencer::{SequencerConfig, TxOrderingPolicy};
 use ola_types::{
-    tx::tx_execution_info::ExecutionMetrics, Address, Nonce, PriorityOpId, Transaction,
+    api::{MempoolStatus, TxpoolContent},
+    log::{StorageLog, StorageLogQuery},
+    tx::tx_execution_info::ExecutionMetrics,
+    utils::unix_timestamp_ms,
+    Address, MiniblockNumber, Nonce, PriorityOpId, StorageKey, Transaction, H256,
 };
-use olaos_mempool::mempool_store::{MempoolInfo, MempoolStore};
+use olaos_mempool::{
+    mempool_store::{MempoolInfo, MempoolStore},
+    policy::{BlockBuilderPolicy, FifoPolicy, SeededShufflePolicy},
+};
+use tokio::sync::watch;
+
+/// Creates the policy ordering transactions of different accounts in blocks.
+pub fn block_builder_policy(config: &SequencerConfig) -> Box<dyn BlockBuilderPolicy> {
+    match config.tx_ordering_policy() {
+        TxOrderingPolicy::Fifo => Box::new(FifoPolicy),
+        TxOrderingPolicy::SeededShuffle => {
+            let seed = config
+                .tx_ordering_seed
+                .expect("`tx_ordering_seed` must be set for the SeededShuffle policy");
+            Box::new(SeededShufflePolicy::new(seed))
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct MempoolGuard(Arc<Mutex<MempoolStore>>);
 
 impl MempoolGuard {
-    pub fn new(next_priority_id: PriorityOpId, capacity: u64) -> Self {
-        let store = MempoolStore::new(next_priority_id, capacity);
+    pub fn new(
+        next_priority_id: PriorityOpId,
+        capacity: u64,
+        policy: Box<dyn BlockBuilderPolicy>,
+    ) -> Self {
+        let store = MempoolStore::with_policy(next_priority_id, capacity, policy);
         Self(Arc::new(Mutex::new(store)))
     }
 
@@ -24,18 +50,28 @@ impl MempoolGuard {
             .insert(transactions, nonces);
     }
 
-    pub fn has_next(&self) -> bool {
+    /// Checks whether a transaction can be executed in a block with the specified timestamp.
+    pub fn has_next(&self, timestamp: u64) -> bool {
+        self.0
+            .lock()
+            .expect("failed to acquire mempool lock")
+            .has_next(timestamp)
+    }
+
+    pub fn next_transaction(&mut self, timestamp: u64) -> Option<Transaction> {
         self.0
             .lock()
             .expect("failed to acquire mempool lock")
-            .has_next()
+            .next_transaction(timestamp)
     }
 
-    pub fn next_transaction(&mut self) -> Option<Transaction> {
+    /// Returns the number of transactions which cannot be executed in a block with the specified
+    /// timestamp yet.
+    pub fn scheduled_len(&self, timestamp: u64) -> usize {
         self.0
             .lock()
             .expect("failed to acquire mempool lock")
-            .next_transaction()
+            .scheduled_len(timestamp)
     }
 
     pub fn rollback(&mut self, rejected: &Transaction) {
@@ -45,15 +81,339 @@ impl MempoolGuard {
             .rollback(rejected);
     }
 
+    pub fn reject(&mut self, rejected: &Transaction) {
+        self.0
+            .lock()
+            .expect("failed to acquire mempool lock")
+            .reject(rejected);
+    }
+
+    pub fn clear_persisted_rejections(&mut self) {
+        self.0
+            .lock()
+            .expect("failed to acquire mempool lock")
+            .clear_persisted_rejections();
+    }
+
     pub fn get_mempool_info(&mut self) -> MempoolInfo {
         self.0
             .lock()
             .expect("failed to acquire mempool lock")
             .get_mempool_info()
     }
+
+    pub fn status(&self, next_transactions_limit: usize, accounts_limit: usize) -> MempoolStatus {
+        self.0
+            .lock()
+            .expect("failed to acquire mempool lock")
+            .status(next_transactions_limit, accounts_limit, unix_timestamp_ms())
+    }
+
+    pub fn content(&self, account_limit: usize, total_limit: usize) -> TxpoolContent {
+        self.0
+            .lock()
+            .expect("failed to acquire mempool lock")
+            .content(account_limit, total_limit)
+    }
+}
+
+/// Storage writes of the L1 batch currently executed by the sequencer, published for the API
+/// so that `pending` reads see state which is not yet persisted to Postgres.
+#[derive(Debug, Clone, Default)]
+pub struct PendingStateGuard(Arc<RwLock<HashMap<StorageKey, H256>>>);
+
+impl PendingStateGuard {
+    /// Applies storage logs of an executed transaction. Reads are ignored, and rolled back
+    /// writes restore the value read before the write.
+    pub fn apply(&self, logs: &[StorageLogQuery]) {
+        let mut values = self
+            .0
+            .write()
+            .expect("failed to acquire pending state lock");
+        for log in logs.iter().filter(|log| log.log_query.rw_flag) {
+            let log = StorageLog::from_log_query(log);
+            values.insert(log.key, log.value);
+        }
+    }
+
+    /// Forgets all writes, e.g. when the pending batch is sealed or a new one is started.
+    pub fn clear(&self) {
+        self.0
+            .write()
+            .expect("failed to acquire pending state lock")
+            .clear();
+    }
+
+    /// Returns the value of `key` written in the pending batch, if any.
+    pub fn get(&self, key: &StorageKey) -> Option<H256> {
+        self.0
+            .read()
+            .expect("failed to acquire pending state lock")
+            .get(key)
+            .copied()
+    }
+
+    /// Returns all writes of the pending batch, so that they can be applied to a call storage.
+    pub fn writes(&self) -> Vec<(StorageKey, H256)> {
+        self.0
+            .read()
+            .expect("failed to acquire pending state lock")
+            .iter()
+            .map(|(key, value)| (*key, *value))
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct ExecutionMetricsForCriteria {
     pub execution_metrics: ExecutionMetrics,
 }
+
+/// Notifies in-process subscribers (e.g., the API nonce cache) about miniblocks persisted
+/// to Postgres by the miniblock sealer.
+#[derive(Debug, Clone)]
+pub struct MiniblockSealEvents(Arc<watch::Sender<MiniblockNumber>>);
+
+impl Default for MiniblockSealEvents {
+    fn default() -> Self {
+        Self(Arc::new(watch::channel(MiniblockNumber(0)).0))
+    }
+}
+
+impl MiniblockSealEvents {
+    pub(crate) fn notify(&self, number: MiniblockNumber) {
+        self.0.send_replace(number);
+    }
+
+    /// Returns a receiver of the number of the latest persisted miniblock.
+    pub fn subscribe(&self) -> watch::Receiver<MiniblockNumber> {
+        self.0.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use ola_types::{l2::L2Tx, request::PaymasterParams, ExecuteTransactionCommon};
+
+    use super::*;
+
+    fn l2_tx(account: u64, nonce: u32, received_timestamp_ms: u64) -> Transaction {
+        raw_l2_tx(account, nonce, received_timestamp_ms).into()
+    }
+
+    fn raw_l2_tx(account: u64, nonce: u32, received_timestamp_ms: u64) -> L2Tx {
+        let initiator = Address::from_low_u64_be(account);
+        let mut tx = L2Tx::new(
+            Address::repeat_byte(0xff),
+            vec![],
+            Nonce(nonce),
+            initiator,
+            None,
+            PaymasterParams::default(),
+        );
+        tx.received_timestamp_ms = received_timestamp_ms;
+        let hash = H256::from_low_u64_be((account << 32) | u64::from(nonce));
+        tx.set_input(vec![], hash);
+        tx
+    }
+
+    /// Inserts transactions of 8 accounts with 3 transactions each, and returns the order
+    /// they are pulled from the mempool in as `(account, nonce)` pairs.
+    fn simulate(policy: Box<dyn BlockBuilderPolicy>) -> Vec<(Address, Nonce)> {
+        let mut mempool = MempoolGuard::new(PriorityOpId(0), 100, policy);
+        let transactions = (0..8)
+            .flat_map(|account| {
+                (0..3).map(move |nonce| l2_tx(account, nonce, 1_000 * u64::from(nonce) + account))
+            })
+            .collect();
+        mempool.insert(transactions, HashMap::new());
+
+        let mut order = vec![];
+        while let Some(tx) = mempool.next_transaction(u64::MAX) {
+            order.push((tx.initiator_account(), tx.nonce().unwrap()));
+        }
+        order
+    }
+
+    fn assert_nonce_order(order: &[(Address, Nonce)]) {
+        let mut next_nonces = HashMap::new();
+        for &(account, nonce) in order {
+            let expected = next_nonces.entry(account).or_insert(Nonce(0));
+            assert_eq!(nonce, *expected, "{order:?}");
+            *expected += 1;
+        }
+    }
+
+    #[test]
+    fn fifo_policy_orders_by_receive_time() {
+        let order = simulate(block_builder_policy(&SequencerConfig::default()));
+        assert_eq!(order.len(), 24);
+        assert_nonce_order(&order);
+        let expected: Vec<_> = (0..3)
+            .flat_map(|nonce| {
+                (0..8).map(move |account| (Address::from_low_u64_be(account), Nonce(nonce)))
+            })
+            .collect();
+        assert_eq!(order, expected);
+    }
+
+    #[test]
+    fn shuffle_policy_is_deterministic() {
+        let config = |seed| SequencerConfig {
+            tx_ordering_policy: Some(TxOrderingPolicy::SeededShuffle),
+            tx_ordering_seed: Some(seed),
+            ..SequencerConfig::default()
+        };
+        let order = simulate(block_builder_policy(&config(1)));
+        assert_eq!(order.len(), 24);
+        assert_nonce_order(&order);
+        assert_eq!(order, simulate(block_builder_policy(&config(1))));
+
+        let fifo_order = simulate(Box::new(FifoPolicy));
+        assert_ne!(order, fifo_order);
+        assert_ne!(order, simulate(block_builder_policy(&config(2))));
+    }
+
+    #[test]
+    fn shuffle_policy_ignores_transaction_contents() {
+        let policy = SeededShufflePolicy::new(1);
+        let tx = raw_l2_tx(1, 0, 1_000);
+        let mut ground_tx = tx.clone();
+        ground_tx.set_input(vec![1], H256::repeat_byte(0xaa));
+        assert_eq!(policy.ordering_key(&tx), policy.ordering_key(&ground_tx));
+
+        let later_tx = raw_l2_tx(1, 0, 1_001);
+        assert_ne!(policy.ordering_key(&tx), policy.ordering_key(&later_tx));
+    }
+
+    #[test]
+    fn mempool_status_lists_are_capped() {
+        let mut mempool = MempoolGuard::new(PriorityOpId(0), 100, Box::new(FifoPolicy));
+        let transactions = vec![
+            l2_tx(1, 0, 0),
+            l2_tx(1, 1, 1),
+            l2_tx(1, 2, 2),
+            l2_tx(2, 0, 3),
+            // Account 3 has a nonce gap.
+            l2_tx(3, 5, 4),
+            l2_tx(3, 6, 5),
+        ];
+        mempool.insert(transactions, HashMap::new());
+
+        let status = mempool.status(10, 2);
+        assert_eq!(status.total_transactions, 6);
+        let accounts: Vec<_> = status
+            .accounts
+            .iter()
+            .map(|account| (account.address, account.pending_transactions))
+            .collect();
+        assert_eq!(
+            accounts,
+            [
+                (Address::from_low_u64_be(1), 3),
+                (Address::from_low_u64_be(3), 2)
+            ]
+        );
+        assert!(status.accounts_truncated);
+        assert_eq!(status.skipped_accounts.len(), 1);
+        assert_eq!(
+            status.skipped_accounts[0].address,
+            Address::from_low_u64_be(3)
+        );
+        assert!(!status.skipped_accounts_truncated);
+
+        let status = mempool.status(0, 0);
+        assert_eq!(status.total_transactions, 6);
+        assert!(status.accounts.is_empty() && status.accounts_truncated);
+        assert!(status.skipped_accounts.is_empty() && status.skipped_accounts_truncated);
+    }
+
+    #[test]
+    fn txpool_content_is_capped() {
+        let mut mempool = MempoolGuard::new(PriorityOpId(0), 100, Box::new(FifoPolicy));
+        let transactions = vec![
+            l2_tx(1, 0, 0),
+            l2_tx(1, 1, 1),
+            l2_tx(1, 2, 2),
+            // Account 2 has a nonce gap.
+            l2_tx(2, 0, 3),
+            l2_tx(2, 5, 4),
+            l2_tx(3, 0, 5),
+        ];
+        mempool.insert(transactions, HashMap::new());
+
+        let content = mempool.content(10, 10);
+        assert!(!content.truncated);
+        assert_eq!(
+            content.pending.values().map(BTreeMap::len).sum::<usize>(),
+            5
+        );
+        assert_eq!(content.queued.len(), 1);
+
+        let content = mempool.content(2, 10);
+        assert!(content.truncated);
+        let account = |number| Address::from_low_u64_be(number);
+        let nonces = |txs: Option<&BTreeMap<Nonce, _>>| -> Vec<_> {
+            txs.map_or(vec![], |txs| txs.keys().map(|nonce| nonce.0).collect())
+        };
+        assert_eq!(nonces(content.pending.get(&account(1))), [0, 1]);
+        assert_eq!(nonces(content.pending.get(&account(2))), [0]);
+        assert_eq!(nonces(content.queued.get(&account(2))), [5]);
+        assert_eq!(nonces(content.pending.get(&account(3))), [0]);
+
+        // Accounts are listed in address order until the total limit is reached.
+        let content = mempool.content(10, 4);
+        assert!(content.truncated);
+        assert_eq!(nonces(content.pending.get(&account(1))), [0, 1, 2]);
+        assert_eq!(nonces(content.pending.get(&account(2))), [0]);
+        assert!(content.queued.is_empty());
+        assert!(!content.pending.contains_key(&account(3)));
+    }
+
+    #[test]
+    fn scheduled_transactions_are_held_until_due() {
+        let mut mempool = MempoolGuard::new(PriorityOpId(0), 100, Box::new(FifoPolicy));
+        let mut scheduled = l2_tx(0, 0, 0);
+        if let ExecuteTransactionCommon::L2(data) = &mut scheduled.common_data {
+            data.execute_after = Some(1_000);
+        }
+        mempool.insert(vec![scheduled, l2_tx(1, 0, 1)], HashMap::new());
+
+        assert!(mempool.has_next(999));
+        let tx = mempool.next_transaction(999).unwrap();
+        assert_eq!(tx.initiator_account(), Address::from_low_u64_be(1));
+        assert!(!mempool.has_next(999));
+        assert!(mempool.next_transaction(999).is_none());
+        assert_eq!(mempool.scheduled_len(999), 1);
+        assert_eq!(mempool.scheduled_len(1_000), 0);
+
+        // Status is reported for the current time, at which the transaction is already due.
+        let status = mempool.status(10, 10);
+        assert_eq!(status.total_transactions, 1);
+        assert_eq!(status.scheduled_transactions, 0);
+        assert!(status.skipped_accounts.is_empty());
+
+        let tx = mempool.next_transaction(1_000).unwrap();
+        assert_eq!(tx.initiator_account(), Address::from_low_u64_be(0));
+        assert!(!mempool.has_next(u64::MAX));
+    }
+
+    #[test]
+    fn rejected_transactions_are_skipped_until_persisted() {
+        let mut mempool = MempoolGuard::new(PriorityOpId(0), 100, Box::new(FifoPolicy));
+        mempool.insert(vec![l2_tx(1, 0, 0)], HashMap::new());
+        let tx = mempool.next_transaction(u64::MAX).unwrap();
+        mempool.reject(&tx);
+        assert!(!mempool.has_next(u64::MAX));
+
+        // The mempool sync loads the transaction again until its rejection is persisted.
+        mempool.insert(vec![l2_tx(1, 0, 0)], HashMap::new());
+        assert!(!mempool.has_next(u64::MAX));
+
+        mempool.clear_persisted_rejections();
+        mempool.insert(vec![l2_tx(1, 0, 0)], HashMap::new());
+        assert!(mempool.has_next(u64::MAX));
+    }
+}
@@ -17,6 +17,9 @@ use crate::sequencer::extractors;
 #[derive(Debug, Clone, PartialEq)]
 pub struct MiniblockUpdates {
     pub executed_transactions: Vec<TransactionExecutionResult>,
+    /// Hashes and errors of transactions rejected while the miniblock was open. Rejections are
+    /// persisted together when the miniblock is sealed.
+    pub rejected_transactions: Vec<(H256, String)>,
     pub events: Vec<VmEvent>,
     pub storage_logs: Vec<StorageLogQuery>,
     pub new_factory_deps: HashMap<H256, Vec<u8>>,
@@ -29,6 +32,7 @@ impl MiniblockUpdates {
     pub(crate) fn new(timestamp: u64) -> Self {
         Self {
             executed_transactions: vec![],
+            rejected_transactions: vec![],
             events: vec![],
             storage_logs: vec![],
             new_factory_deps: HashMap::new(),
@@ -92,6 +96,11 @@ impl MiniblockUpdates {
         });
     }
 
+    pub(crate) fn extend_from_rejected_transaction(&mut self, tx_hash: H256, error: &str) {
+        self.rejected_transactions
+            .push((tx_hash, format!("rejected: {error}")));
+    }
+
     pub(crate) fn extend_from_fictive_transaction(&mut self, vm_execution_logs: VmExecutionLogs) {
         // TODO: check events
         self.events.extend(vm_execution_logs.events);
@@ -3,7 +3,7 @@ use ola_types::{
     protocol_version::ProtocolVersionId,
     storage_writes_deduplicator::StorageWritesDeduplicator,
     tx::tx_execution_info::{ExecutionMetrics, VmExecutionLogs},
-    L1BatchNumber, MiniblockNumber, Transaction,
+    L1BatchNumber, MiniblockNumber, Transaction, H256,
 };
 use ola_vm::{vm::VmTxExeResult, vm_with_bootloader::BlockContextMode};
 
@@ -20,6 +20,8 @@ pub(crate) struct MiniblockSealCommand {
     pub first_tx_index: usize,
     pub base_system_contracts_hashes: BaseSystemContractsHashes,
     pub protocol_version: ProtocolVersionId,
+    /// Maximum size of call outputs persisted with call traces.
+    pub max_call_output_size: usize,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -72,6 +74,11 @@ impl UpdatesManager {
             .extend_from_executed_transaction(tx, tx_execution_result, execution_metrics);
     }
 
+    pub(crate) fn extend_from_rejected_transaction(&mut self, tx_hash: H256, error: &str) {
+        self.miniblock
+            .extend_from_rejected_transaction(tx_hash, error);
+    }
+
     #[olaos_logs::instrument(skip_all)]
     pub(crate) fn extend_from_fictive_transaction(&mut self, vm_execution_logs: VmExecutionLogs) {
         self.storage_writes_deduplicator
@@ -85,6 +92,7 @@ impl UpdatesManager {
         &self,
         l1_batch_number: L1BatchNumber,
         miniblock_number: MiniblockNumber,
+        max_call_output_size: usize,
     ) -> MiniblockSealCommand {
         MiniblockSealCommand {
             l1_batch_number,
@@ -93,6 +101,7 @@ impl UpdatesManager {
             first_tx_index: self.l1_batch.executed_transactions.len(),
             base_system_contracts_hashes: self.base_system_contract_hashes,
             protocol_version: self.protocol_version,
+            max_call_output_size,
         }
     }
 
@@ -0,0 +1,329 @@
+//! Dry run of a protocol upgrade (`ola_node upgrade-dry-run`).
+//!
+//! The last sealed L1 batches are replayed in a shadow executor with the base system contracts
+//! of the next protocol version, and the results are compared with the ones recorded in Postgres.
+//! The shadow executor works on its own RocksDB state in a temporary directory, which is populated
+//! from Postgres and removed afterwards, so nothing is persisted.
+//!
+//! The protocol upgrade transaction is not executed: the new contracts are deployed into the shadow
+//! state directly before each replayed batch.
+
+use std::{collections::HashMap, fmt, path::Path};
+
+use anyhow::Context as _;
+use ola_config::constants::contracts::{DEFAULT_ACCOUNT_ADDRESS, ENTRYPOINT_ADDRESS};
+use ola_contracts::{BaseSystemContracts, BaseSystemContractsHashes};
+use ola_dal::{connection::ConnectionPool, StorageProcessor};
+use ola_state::rocksdb::RocksdbStorage;
+use ola_types::{
+    get_full_code_key, get_known_code_key, protocol_version::ProtocolVersionId, AccountTreeId,
+    L1BatchNumber, StorageKey, H256,
+};
+use ola_utils::{h256_to_u256, u256_to_h256};
+
+use crate::sequencer::{
+    batch_executor::{BatchExecutorHandle, BlockTipEstimate},
+    io::{common::l1_batch_params, sort_storage_access::sort_storage_access_queries},
+};
+
+/// Arguments of `ola_node upgrade-dry-run`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpgradeDryRunArgs {
+    /// Number of the last sealed L1 batches to replay.
+    pub batch_count: u32,
+}
+
+impl UpgradeDryRunArgs {
+    pub const USAGE: &'static str = "usage: ola_node upgrade-dry-run [--batches <count>]";
+
+    const DEFAULT_BATCH_COUNT: u32 = 10;
+
+    /// Parses arguments following the `upgrade-dry-run` subcommand.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Self> {
+        let mut batch_count = Self::DEFAULT_BATCH_COUNT;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .with_context(|| format!("missing value for `{arg}`; {}", Self::USAGE))?;
+            match arg.as_str() {
+                "--batches" => batch_count = value.parse()?,
+                _ => anyhow::bail!("unknown argument `{arg}`; {}", Self::USAGE),
+            }
+        }
+        anyhow::ensure!(batch_count > 0, "--batches must be positive");
+        Ok(Self { batch_count })
+    }
+}
+
+/// Storage slot whose value after the replayed batch differs from the recorded one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotDivergence {
+    pub key: StorageKey,
+    /// Value recorded in Postgres; `None` if the slot wasn't written by the batch.
+    pub expected: Option<H256>,
+    /// Value produced by the shadow executor; `None` if the slot wasn't written.
+    pub actual: Option<H256>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchDivergences {
+    pub l1_batch_number: L1BatchNumber,
+    /// Transactions included into the batch, but rejected by the shadow executor.
+    pub rejected_txs: Vec<(H256, String)>,
+    pub slots: Vec<SlotDivergence>,
+}
+
+impl BatchDivergences {
+    pub fn is_empty(&self) -> bool {
+        self.rejected_txs.is_empty() && self.slots.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UpgradeDryRunReport {
+    pub protocol_version: ProtocolVersionId,
+    pub contracts: BaseSystemContractsHashes,
+    pub replayed_batches: Vec<L1BatchNumber>,
+    /// Batches with at least one divergence.
+    pub divergences: Vec<BatchDivergences>,
+}
+
+impl UpgradeDryRunReport {
+    pub fn has_divergences(&self) -> bool {
+        !self.divergences.is_empty()
+    }
+}
+
+impl fmt::Display for UpgradeDryRunReport {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            formatter,
+            "Replayed {} L1 batch(es) with protocol version {:?} (entrypoint {:?}, default AA {:?})",
+            self.replayed_batches.len(),
+            self.protocol_version,
+            self.contracts.entrypoint,
+            self.contracts.default_aa
+        )?;
+        if self.divergences.is_empty() {
+            return writeln!(formatter, "No divergences found");
+        }
+        for batch in &self.divergences {
+            writeln!(formatter, "L1 batch #{}:", batch.l1_batch_number)?;
+            for (hash, reason) in &batch.rejected_txs {
+                writeln!(formatter, "  tx {hash:?} rejected: {reason}")?;
+            }
+            for slot in &batch.slots {
+                writeln!(
+                    formatter,
+                    "  slot {:?}/{:?}: expected {:?}, got {:?}",
+                    slot.key.address(),
+                    slot.key.key(),
+                    slot.expected,
+                    slot.actual
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Replays the last `args.batch_count` sealed L1 batches with the `contracts` of `protocol_version`.
+pub async fn run_upgrade_dry_run(
+    pool: &ConnectionPool,
+    protocol_version: ProtocolVersionId,
+    contracts: BaseSystemContracts,
+    args: &UpgradeDryRunArgs,
+) -> anyhow::Result<UpgradeDryRunReport> {
+    let mut storage = pool.access_storage_tagged("upgrade_dry_run").await;
+    let last_batch = storage.blocks_dal().get_sealed_l1_batch_number().await?;
+    anyhow::ensure!(last_batch.0 > 0, "there are no sealed L1 batches to replay");
+    let first_batch = L1BatchNumber(last_batch.0.saturating_sub(args.batch_count - 1).max(1));
+
+    let shadow_dir = tempfile::TempDir::new().context("cannot create shadow state directory")?;
+    let shadow_path = shadow_dir.path();
+    olaos_logs::info!("Loading shadow state up to L1 batch #{}", first_batch - 1);
+    RocksdbStorage::new(shadow_path)
+        .update_from_postgres_until(&mut storage, first_batch - 1)
+        .await?;
+
+    let mut report = UpgradeDryRunReport {
+        protocol_version,
+        contracts: contracts.hashes(),
+        replayed_batches: vec![],
+        divergences: vec![],
+    };
+    for number in first_batch.0..=last_batch.0 {
+        let number = L1BatchNumber(number);
+        deploy_contracts(&RocksdbStorage::new(shadow_path), &contracts);
+        let divergences = replay_batch(
+            &mut storage,
+            shadow_path,
+            number,
+            protocol_version,
+            contracts.clone(),
+        )
+        .await?;
+        if !divergences.is_empty() {
+            olaos_logs::warn!("Replaying L1 batch #{number} diverged: {divergences:?}");
+            report.divergences.push(divergences);
+        }
+        report.replayed_batches.push(number);
+
+        // Continue from the recorded state, so that divergences don't accumulate across batches.
+        RocksdbStorage::new(shadow_path)
+            .update_from_postgres_until(&mut storage, number)
+            .await?;
+    }
+    Ok(report)
+}
+
+/// Deploys base system contracts into the shadow state, like the protocol upgrade tx would.
+fn deploy_contracts(shadow: &RocksdbStorage, contracts: &BaseSystemContracts) {
+    shadow.persist_factory_deps([
+        (contracts.entrypoint.hash, contracts.entrypoint.code.clone()),
+        (contracts.default_aa.hash, contracts.default_aa.code.clone()),
+    ]);
+    let known_code_marker = H256::from_low_u64_be(1);
+    shadow.persist_values([
+        (
+            get_full_code_key(&ENTRYPOINT_ADDRESS),
+            contracts.entrypoint.hash,
+        ),
+        (
+            get_known_code_key(&contracts.entrypoint.hash),
+            known_code_marker,
+        ),
+        (
+            get_full_code_key(&DEFAULT_ACCOUNT_ADDRESS),
+            contracts.default_aa.hash,
+        ),
+        (
+            get_known_code_key(&contracts.default_aa.hash),
+            known_code_marker,
+        ),
+    ]);
+}
+
+async fn replay_batch(
+    storage: &mut StorageProcessor<'_>,
+    shadow_path: &Path,
+    l1_batch_number: L1BatchNumber,
+    protocol_version: ProtocolVersionId,
+    contracts: BaseSystemContracts,
+) -> anyhow::Result<BatchDivergences> {
+    let header = storage
+        .blocks_dal()
+        .get_l1_batch_header(l1_batch_number)
+        .await?
+        .with_context(|| format!("L1 batch #{l1_batch_number} is not sealed"))?;
+    let previous_batch_hash = storage
+        .blocks_dal()
+        .get_l1_batch_state_root(l1_batch_number - 1)
+        .await?
+        .with_context(|| {
+            format!(
+                "state root of L1 batch #{} is not computed yet",
+                l1_batch_number - 1
+            )
+        })?;
+    let params = l1_batch_params(
+        l1_batch_number,
+        header.fee_account_address,
+        header.timestamp,
+        h256_to_u256(previous_batch_hash),
+        contracts,
+        protocol_version,
+    );
+    let miniblocks = storage
+        .transactions_dal()
+        .get_l1_batch_miniblocks_to_reexecute(l1_batch_number)
+        .await?;
+
+    olaos_logs::info!("Replaying L1 batch #{l1_batch_number}");
+    let mut divergences = BatchDivergences {
+        l1_batch_number,
+        rejected_txs: vec![],
+        slots: vec![],
+    };
+    let executor = BatchExecutorHandle::new(
+        false,
+        shadow_path,
+        shadow_path,
+        params,
+        BlockTipEstimate::default(),
+    );
+    let mut tx_index_in_l1_batch = 0;
+    for tx in miniblocks.into_iter().flat_map(|miniblock| miniblock.txs) {
+        let tx_hash = tx.hash();
+        let result = executor.execute_tx(tx, tx_index_in_l1_batch).await;
+        if let Some(reason) = result.err() {
+            divergences.rejected_txs.push((tx_hash, reason.to_string()));
+        }
+        tx_index_in_l1_batch += 1;
+    }
+    let (block_result, _) = executor.finish_batch(tx_index_in_l1_batch).await;
+
+    let (_, deduped_log_queries) = sort_storage_access_queries(
+        block_result
+            .full_result
+            .storage_log_queries
+            .iter()
+            .map(|log| &log.log_query),
+    );
+    let actual_writes: HashMap<_, _> = deduped_log_queries
+        .into_iter()
+        .filter(|log_query| log_query.rw_flag)
+        .map(|log_query| {
+            let key = StorageKey::new(
+                AccountTreeId::new(log_query.address),
+                u256_to_h256(log_query.key),
+            );
+            (key, u256_to_h256(log_query.written_value))
+        })
+        .collect();
+    let expected_writes = storage
+        .storage_logs_dal()
+        .get_touched_slots_for_l1_batch(l1_batch_number)
+        .await?;
+
+    for (key, expected) in &expected_writes {
+        let actual = actual_writes.get(key);
+        if actual != Some(expected) {
+            divergences.slots.push(SlotDivergence {
+                key: *key,
+                expected: Some(*expected),
+                actual: actual.copied(),
+            });
+        }
+    }
+    for (key, actual) in &actual_writes {
+        if !expected_writes.contains_key(key) {
+            divergences.slots.push(SlotDivergence {
+                key: *key,
+                expected: None,
+                actual: Some(*actual),
+            });
+        }
+    }
+    divergences.slots.sort_by_key(|slot| slot.key.hashed_key());
+    Ok(divergences)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> anyhow::Result<UpgradeDryRunArgs> {
+        UpgradeDryRunArgs::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parsing_upgrade_dry_run_args() {
+        assert_eq!(args(&[]).unwrap().batch_count, 10);
+        assert_eq!(args(&["--batches", "3"]).unwrap().batch_count, 3);
+        assert!(args(&["--batches", "0"]).is_err());
+        assert!(args(&["--batches"]).is_err());
+        assert!(args(&["--from", "1"]).is_err());
+    }
+}
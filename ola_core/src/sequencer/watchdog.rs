@@ -0,0 +1,245 @@
+//! Watchdog detecting a stalled sequencer.
+//!
+//! The sequencer is considered stalled if no miniblock is sealed for the configured threshold while
+//! the mempool has transactions, or if no L1 batch is sealed for the configured threshold while there
+//! are miniblocks outside sealed batches. Stall durations are measured from the moment the watchdog
+//! observes the last progress, so a node restarted after downtime isn't reported as stalled.
+//!
+//! While the sequencer is stalled, its health is `NotReady` with diagnostics in the health details.
+//! If restarts are enabled, the watchdog terminates instead; like with any other sequencer task,
+//! the node then stops its components and exits with the sequencer exit code, so that the supervisor
+//! restarts it. The sequencer cannot be restarted in-process, since the mempool (including the
+//! `in_mempool` flags in Postgres) has to be rebuilt together with it.
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::Context as _;
+use ola_config::sequencer::SequencerConfig;
+use ola_dal::connection::ConnectionPool;
+use ola_types::{L1BatchNumber, MiniblockNumber};
+use olaos_health_check::{Health, HealthStatus, HealthUpdater, ReactiveHealthCheck};
+use serde::Serialize;
+use tokio::sync::watch;
+
+use super::types::MempoolGuard;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StalledStage {
+    Miniblock,
+    L1Batch,
+}
+
+/// Snapshot of the sequencer state, reported while it's stalled.
+#[derive(Debug, Clone, Serialize)]
+pub struct StallDiagnostics {
+    pub stage: Option<StalledStage>,
+    pub stalled_for_sec: u64,
+    pub last_sealed_miniblock: MiniblockNumber,
+    pub last_sealed_l1_batch: L1BatchNumber,
+    pub mempool_transactions: u64,
+    /// Miniblocks submitted to the miniblock sealer, but not persisted yet.
+    pub miniblock_seal_queue_depth: usize,
+    /// Latency of a simple Postgres query.
+    pub db_latency_ms: u64,
+}
+
+impl From<StallDiagnostics> for Health {
+    fn from(diagnostics: StallDiagnostics) -> Self {
+        let status = if diagnostics.stage.is_some() {
+            HealthStatus::NotReady
+        } else {
+            HealthStatus::Ready
+        };
+        Self::from(status).with_details(diagnostics)
+    }
+}
+
+/// Last progress of a sealed entity (miniblock or L1 batch) observed by the watchdog.
+#[derive(Debug)]
+struct Progress {
+    last_number: u32,
+    observed_at: Instant,
+}
+
+impl Progress {
+    fn new(last_number: u32, now: Instant) -> Self {
+        Self {
+            last_number,
+            observed_at: now,
+        }
+    }
+
+    /// Returns the time since the last progress. `is_expected` tells whether progress is expected
+    /// at all; if not, the stall timer is reset.
+    fn stalled_for(&mut self, number: u32, is_expected: bool, now: Instant) -> Duration {
+        if number != self.last_number || !is_expected {
+            self.last_number = number;
+            self.observed_at = now;
+        }
+        now - self.observed_at
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct SequencerWatchdog {
+    pool: ConnectionPool,
+    mempool: MempoolGuard,
+    miniblock_seal_queue_depth: Arc<AtomicUsize>,
+    miniblock_stall_threshold: Duration,
+    l1_batch_stall_threshold: Duration,
+    restart_on_stall: bool,
+    health_updater: HealthUpdater,
+}
+
+impl SequencerWatchdog {
+    pub fn new(
+        config: &SequencerConfig,
+        pool: ConnectionPool,
+        mempool: MempoolGuard,
+        miniblock_seal_queue_depth: Arc<AtomicUsize>,
+    ) -> (Self, ReactiveHealthCheck) {
+        let (health_check, health_updater) = ReactiveHealthCheck::new("sequencer");
+        let this = Self {
+            pool,
+            mempool,
+            miniblock_seal_queue_depth,
+            miniblock_stall_threshold: config.watchdog_miniblock_stall_threshold(),
+            l1_batch_stall_threshold: config.watchdog_l1_batch_stall_threshold(),
+            restart_on_stall: config.watchdog_restart_on_stall(),
+            health_updater,
+        };
+        (this, health_check)
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        let now = Instant::now();
+        let mut miniblock_progress = Progress::new(0, now);
+        let mut l1_batch_progress = Progress::new(0, now);
+        let mut was_stalled = false;
+
+        loop {
+            if *stop_receiver.borrow_and_update() {
+                olaos_logs::info!("Stop signal received, sequencer watchdog is shutting down");
+                break;
+            }
+
+            let mut storage = self.pool.access_storage_tagged("sequencer_watchdog").await;
+            let started_at = Instant::now();
+            let last_sealed_miniblock = storage
+                .blocks_dal()
+                .get_sealed_miniblock_number()
+                .await
+                .context("get_sealed_miniblock_number()")?;
+            let db_latency = started_at.elapsed();
+            let last_sealed_l1_batch = storage
+                .blocks_dal()
+                .get_sealed_l1_batch_number()
+                .await
+                .context("get_sealed_l1_batch_number()")?;
+            let last_batched_miniblock = storage
+                .blocks_dal()
+                .get_miniblock_range_of_l1_batch(last_sealed_l1_batch)
+                .await
+                .context("get_miniblock_range_of_l1_batch()")?
+                .map_or(MiniblockNumber(0), |(_, last)| last);
+            drop(storage);
+
+            let now = Instant::now();
+            let mempool_status = self.mempool.status(0, 0);
+            let mempool_transactions = mempool_status.total_transactions;
+            // Scheduled transactions aren't expected to be executed yet.
+            let executable_transactions =
+                mempool_transactions.saturating_sub(mempool_status.scheduled_transactions);
+            let miniblock_stall = miniblock_progress.stalled_for(
+                last_sealed_miniblock.0,
+                executable_transactions > 0,
+                now,
+            );
+            let l1_batch_stall = l1_batch_progress.stalled_for(
+                last_sealed_l1_batch.0,
+                last_batched_miniblock < last_sealed_miniblock,
+                now,
+            );
+            let (stage, stalled_for) = if miniblock_stall > self.miniblock_stall_threshold {
+                (Some(StalledStage::Miniblock), miniblock_stall)
+            } else if l1_batch_stall > self.l1_batch_stall_threshold {
+                (Some(StalledStage::L1Batch), l1_batch_stall)
+            } else {
+                (None, Duration::ZERO)
+            };
+
+            let diagnostics = StallDiagnostics {
+                stage,
+                stalled_for_sec: stalled_for.as_secs(),
+                last_sealed_miniblock,
+                last_sealed_l1_batch,
+                mempool_transactions,
+                miniblock_seal_queue_depth: self.miniblock_seal_queue_depth.load(Ordering::Relaxed),
+                db_latency_ms: db_latency.as_millis() as u64,
+            };
+            let is_stalled = stage.is_some();
+            metrics::gauge!(
+                "server.sequencer.watchdog.stalled",
+                if is_stalled { 1.0 } else { 0.0 }
+            );
+            if is_stalled && !was_stalled {
+                olaos_logs::warn!("Sequencer is stalled: {diagnostics:?}");
+                metrics::counter!("server.sequencer.watchdog.stalls", 1);
+            } else if !is_stalled && was_stalled {
+                olaos_logs::info!("Sequencer recovered from stall: {diagnostics:?}");
+            }
+            was_stalled = is_stalled;
+            self.health_updater.update(diagnostics.into());
+
+            if is_stalled && self.restart_on_stall {
+                anyhow::bail!(
+                    "sequencer is stalled at {stage:?} stage for {stalled_for:?}; restarting"
+                );
+            }
+
+            tokio::select! {
+                _ = stop_receiver.changed() => {
+                    olaos_logs::info!("Stop signal received, sequencer watchdog is shutting down");
+                    break;
+                }
+                () = tokio::time::sleep(POLL_INTERVAL) => { /* Continue polling */ }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stall_timer_is_reset_on_progress() {
+        let start = Instant::now();
+        let mut progress = Progress::new(1, start);
+        let later = start + Duration::from_secs(30);
+        assert_eq!(
+            progress.stalled_for(1, true, later),
+            Duration::from_secs(30)
+        );
+        assert_eq!(progress.stalled_for(2, true, later), Duration::ZERO);
+
+        // No progress is expected, e.g. because the mempool is empty.
+        let even_later = later + Duration::from_secs(60);
+        assert_eq!(progress.stalled_for(2, false, even_later), Duration::ZERO);
+        let latest = even_later + Duration::from_secs(10);
+        assert_eq!(
+            progress.stalled_for(2, true, latest),
+            Duration::from_secs(10)
+        );
+    }
+}
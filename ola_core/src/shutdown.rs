@@ -0,0 +1,204 @@
+//! Ordered shutdown of the node components.
+//!
+//! Components are stopped in phases, so that each component stops only after the components that
+//! feed it: first the ingress (API servers and the proof data handler), then the sequencer, then
+//! the miniblock sealer persisting miniblocks queued by the sequencer, then the tree, and finally
+//! the DB pools. Each phase has its own stop signal and timeout; tasks not finished within the timeout
+//! are aborted, and the shutdown proceeds with the next phase.
+
+use std::time::{Duration, Instant};
+
+use futures::future;
+use ola_dal::connection::ConnectionPool;
+use ola_utils::panic_extractor::try_extract_panic_message;
+use tokio::{sync::watch, task::JoinHandle};
+
+use crate::{api_server::healthcheck::HealthCheckHandle, Component};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ShutdownPhase {
+    /// Components accepting requests, e.g. transaction submissions.
+    Ingress,
+    Sequencer,
+    /// Miniblock sealer; it stops on its own once the sequencer drops its handle, after persisting
+    /// all queued miniblocks.
+    Sealers,
+    Tree,
+    /// DB pools and the health check server.
+    Database,
+}
+
+impl ShutdownPhase {
+    /// All phases in the order they are executed.
+    pub const ALL: [Self; 5] = [
+        Self::Ingress,
+        Self::Sequencer,
+        Self::Sealers,
+        Self::Tree,
+        Self::Database,
+    ];
+
+    pub fn timeout(self) -> Duration {
+        match self {
+            Self::Ingress => Duration::from_secs(10),
+            Self::Sequencer => Duration::from_secs(30),
+            Self::Sealers => Duration::from_secs(30),
+            Self::Tree => Duration::from_secs(60),
+            Self::Database => Duration::from_secs(10),
+        }
+    }
+}
+
+/// Label of a node task: the component it belongs to and the phase in which it's stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskLabel {
+    pub component: Component,
+    pub phase: ShutdownPhase,
+}
+
+impl From<Component> for TaskLabel {
+    fn from(component: Component) -> Self {
+        Self {
+            component,
+            phase: component.shutdown_phase(),
+        }
+    }
+}
+
+/// Stop signals for each shutdown phase.
+#[derive(Debug)]
+pub(crate) struct StopSignals {
+    senders: Vec<watch::Sender<bool>>,
+}
+
+impl StopSignals {
+    pub fn new() -> Self {
+        Self {
+            senders: ShutdownPhase::ALL
+                .iter()
+                .map(|_| watch::channel(false).0)
+                .collect(),
+        }
+    }
+
+    pub fn receiver(&self, phase: ShutdownPhase) -> watch::Receiver<bool> {
+        self.senders[phase as usize].subscribe()
+    }
+
+    fn send(&self, phase: ShutdownPhase) {
+        self.senders[phase as usize].send_replace(true);
+    }
+}
+
+/// Handle to shut down the node components started by [`NodeBuilder`](crate::node::NodeBuilder).
+#[derive(Debug)]
+pub struct Shutdown {
+    stop_signals: StopSignals,
+    pools: Vec<ConnectionPool>,
+    health_check_handle: HealthCheckHandle,
+}
+
+impl Shutdown {
+    pub(crate) fn new(
+        stop_signals: StopSignals,
+        pools: Vec<ConnectionPool>,
+        health_check_handle: HealthCheckHandle,
+    ) -> Self {
+        Self {
+            stop_signals,
+            pools,
+            health_check_handle,
+        }
+    }
+
+    /// Stops `tasks` phase by phase, then closes the DB pools.
+    pub async fn run(self, mut tasks: Vec<(TaskLabel, JoinHandle<anyhow::Result<()>>)>) {
+        for phase in ShutdownPhase::ALL {
+            let started_at = Instant::now();
+            let (phase_tasks, other_tasks) = tasks
+                .into_iter()
+                .partition::<Vec<_>, _>(|(label, _)| label.phase == phase);
+            tasks = other_tasks;
+
+            olaos_logs::info!(
+                "Shutdown phase {phase:?}: stopping {} task(s)",
+                phase_tasks.len()
+            );
+            self.stop_signals.send(phase);
+            let stopped = stop_tasks(phase_tasks, phase.timeout()).await;
+            if phase == ShutdownPhase::Database {
+                self.health_check_handle.stop().await;
+                for pool in &self.pools {
+                    pool.close().await;
+                }
+                olaos_logs::info!("Shutdown phase {phase:?}: closed DB pools");
+                return;
+            }
+            if stopped {
+                olaos_logs::info!(
+                    "Shutdown phase {phase:?} finished in {:?}",
+                    started_at.elapsed()
+                );
+            }
+        }
+    }
+}
+
+/// Waits for `tasks` to finish for at most `timeout`, aborting them afterwards.
+/// Returns `false` if the timeout is exceeded.
+async fn stop_tasks(
+    tasks: Vec<(TaskLabel, JoinHandle<anyhow::Result<()>>)>,
+    timeout: Duration,
+) -> bool {
+    let (labels, mut handles): (Vec<_>, Vec<_>) = tasks.into_iter().unzip();
+    let results = tokio::time::timeout(timeout, future::join_all(handles.iter_mut())).await;
+    let Ok(results) = results else {
+        olaos_logs::warn!(
+            "Tasks {labels:?} did not stop in {timeout:?}; aborting them and proceeding with shutdown"
+        );
+        for handle in &handles {
+            handle.abort();
+        }
+        return false;
+    };
+
+    for (label, result) in labels.iter().zip(results) {
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => olaos_logs::warn!("Task of {label:?} failed on shutdown: {err:#}"),
+            Err(err) => {
+                let panic_message = try_extract_panic_message(err);
+                olaos_logs::warn!("Task of {label:?} panicked on shutdown: {panic_message}");
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn tasks_are_stopped_after_their_phase() {
+        let stop_signals = StopSignals::new();
+        let mut tree_stop_receiver = stop_signals.receiver(ShutdownPhase::Tree);
+        let ingress_stop_receiver = stop_signals.receiver(ShutdownPhase::Ingress);
+
+        stop_signals.send(ShutdownPhase::Ingress);
+        assert!(*ingress_stop_receiver.borrow());
+        assert!(!*tree_stop_receiver.borrow_and_update());
+
+        let tree_task = tokio::spawn(async move {
+            tree_stop_receiver.changed().await?;
+            Ok::<_, anyhow::Error>(())
+        });
+        stop_signals.send(ShutdownPhase::Tree);
+        let tasks = vec![(TaskLabel::from(Component::Tree), tree_task)];
+        assert!(stop_tasks(tasks, Duration::from_secs(10)).await);
+
+        let stuck_task = tokio::spawn(future::pending::<anyhow::Result<()>>());
+        let tasks = vec![(TaskLabel::from(Component::Sequencer), stuck_task)];
+        assert!(!stop_tasks(tasks, Duration::from_millis(10)).await);
+    }
+}
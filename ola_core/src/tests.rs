@@ -54,7 +54,8 @@ mod tests {
         transaction
             .storage_logs_dal()
             .insert_storage_logs(MiniblockNumber(0), &storage_logs)
-            .await;
+            .await
+            .unwrap();
 
         let log_queries: Vec<LogQuery> = storage_logs
             .iter()
@@ -91,7 +92,8 @@ mod tests {
         transaction
             .storage_logs_dedup_dal()
             .insert_protective_reads(L1BatchNumber(0), &protective_reads)
-            .await;
+            .await
+            .unwrap();
 
         let written_storage_keys: Vec<_> = deduplicated_writes
             .iter()
@@ -100,19 +102,22 @@ mod tests {
         transaction
             .storage_logs_dedup_dal()
             .insert_initial_writes(L1BatchNumber(0), &written_storage_keys)
-            .await;
+            .await
+            .unwrap();
 
         transaction
             .storage_dal()
             .apply_storage_logs(&storage_logs)
-            .await;
+            .await
+            .unwrap();
 
         let mut factory_deps = HashMap::new();
         factory_deps.insert(program_hash, program_bytes);
         transaction
             .storage_dal()
             .insert_factory_deps(MiniblockNumber(0), &factory_deps)
-            .await;
+            .await
+            .unwrap();
 
         transaction.commit().await;
     }
@@ -125,7 +130,7 @@ mod tests {
         let path = "../db/main/sequencer".to_string();
         let mut db = RocksdbStorage::new(path.as_ref());
 
-        db.update_from_postgres(&mut storage).await;
+        db.update_from_postgres(&mut storage).await.unwrap();
 
         let l1_number = db.l1_batch_number();
         println!("l1_number: {:?}", l1_number);
@@ -0,0 +1,220 @@
+//! Bounded thread pools isolating heavy blocking work of the API from the rest of the node.
+//!
+//! `tokio::task::spawn_blocking` uses a single blocking pool shared by all components, so a burst
+//! of VM invocations or RocksDB reads can starve e.g. the sequencer of blocking threads.
+//! A [`BlockingPool`] has a fixed number of dedicated threads and a bounded queue of tasks waiting
+//! for a thread; tasks submitted to a saturated pool are rejected instead of being queued.
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use anyhow::Context as _;
+use ola_web3_decl::error::Web3Error;
+use thiserror::Error;
+use tokio::sync::oneshot;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("`{pool}` thread pool is saturated: {capacity} tasks are already queued")]
+pub struct PoolSaturated {
+    pub pool: &'static str,
+    pub capacity: usize,
+}
+
+impl From<PoolSaturated> for Web3Error {
+    fn from(err: PoolSaturated) -> Self {
+        Self::ThreadPoolSaturated(err.pool)
+    }
+}
+
+#[derive(Debug)]
+pub struct BlockingPool {
+    name: &'static str,
+    threads: rayon::ThreadPool,
+    queue_capacity: usize,
+    /// Number of submitted tasks not yet picked up by a thread.
+    queued: Arc<AtomicUsize>,
+}
+
+impl BlockingPool {
+    pub fn new(
+        name: &'static str,
+        thread_count: usize,
+        queue_capacity: usize,
+    ) -> anyhow::Result<Self> {
+        let threads = rayon::ThreadPoolBuilder::new()
+            .num_threads(thread_count)
+            .thread_name(move |index| format!("{name}-{index}"))
+            // A panicking task drops its result sender, so the panic is reported to the caller
+            // by `Self::run()`; without a handler, rayon would abort the process.
+            .panic_handler(move |_| olaos_logs::error!("Task in `{name}` thread pool panicked"))
+            .build()
+            .with_context(|| format!("failed building `{name}` thread pool"))?;
+        Ok(Self {
+            name,
+            threads,
+            queue_capacity,
+            queued: Arc::default(),
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn queue_depth(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    /// Runs `task` on a thread of the pool. Fails immediately if the queue is full.
+    ///
+    /// # Panics
+    ///
+    /// Resumes a panic of the task.
+    pub async fn run<T: Send + 'static>(
+        &self,
+        task: impl FnOnce() -> T + Send + 'static,
+    ) -> Result<T, PoolSaturated> {
+        let output = self.spawn(task)?;
+        Ok(output
+            .await
+            .unwrap_or_else(|_| panic!("task in `{}` thread pool panicked", self.name)))
+    }
+
+    fn spawn<T: Send + 'static>(
+        &self,
+        task: impl FnOnce() -> T + Send + 'static,
+    ) -> Result<oneshot::Receiver<T>, PoolSaturated> {
+        let name = self.name;
+        let reserved = self
+            .queued
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |queued| {
+                (queued < self.queue_capacity).then_some(queued + 1)
+            });
+        let queue_depth = match reserved {
+            Ok(queued) => queued + 1,
+            Err(_) => {
+                metrics::increment_counter!("api.blocking_pool.rejected", "pool" => name);
+                return Err(PoolSaturated {
+                    pool: name,
+                    capacity: self.queue_capacity,
+                });
+            }
+        };
+        metrics::gauge!("api.blocking_pool.queue_depth", queue_depth as f64, "pool" => name);
+
+        let queued = Arc::clone(&self.queued);
+        let submitted_at = Instant::now();
+        let (output_sender, output) = oneshot::channel();
+        self.threads.spawn(move || {
+            let queue_depth = queued.fetch_sub(1, Ordering::SeqCst) - 1;
+            metrics::gauge!("api.blocking_pool.queue_depth", queue_depth as f64, "pool" => name);
+            metrics::histogram!(
+                "api.blocking_pool.queue_wait",
+                submitted_at.elapsed(),
+                "pool" => name
+            );
+            // The caller may no longer wait for the output, e.g. if it has timed out
+            // while the task was queued; such tasks are skipped to free the thread.
+            if output_sender.is_closed() {
+                metrics::increment_counter!("api.blocking_pool.abandoned", "pool" => name);
+                return;
+            }
+            output_sender.send(task()).ok();
+        });
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::mpsc, time::Duration};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn running_tasks() {
+        let pool = BlockingPool::new("test", 2, 4).unwrap();
+        assert_eq!(pool.run(|| 42).await, Ok(42));
+        let thread_name = pool
+            .run(|| std::thread::current().name().map(str::to_owned))
+            .await
+            .unwrap();
+        assert!(thread_name.unwrap().starts_with("test-"));
+    }
+
+    #[tokio::test]
+    async fn saturated_pool_rejects_tasks() {
+        let pool = BlockingPool::new("test", 1, 1).unwrap();
+        let (unblock_sender, unblock_receiver) = mpsc::channel::<()>();
+        let (started_sender, started_receiver) = mpsc::channel();
+        let blocking_task = pool
+            .spawn(move || {
+                started_sender.send(()).unwrap();
+                unblock_receiver.recv().ok();
+            })
+            .unwrap();
+        started_receiver
+            .recv_timeout(Duration::from_secs(5))
+            .unwrap();
+
+        // The only thread is busy, so the next task is queued, and the one after is rejected.
+        let queued_task = pool.spawn(|| 1).unwrap();
+        assert_eq!(pool.queue_depth(), 1);
+        let err = pool.run(|| 2).await.unwrap_err();
+        assert_eq!(
+            err,
+            PoolSaturated {
+                pool: "test",
+                capacity: 1
+            }
+        );
+
+        unblock_sender.send(()).unwrap();
+        blocking_task.await.unwrap();
+        assert_eq!(queued_task.await.unwrap(), 1);
+        assert_eq!(pool.queue_depth(), 0);
+        assert_eq!(pool.run(|| 3).await, Ok(3));
+    }
+
+    #[tokio::test]
+    async fn abandoned_tasks_are_skipped() {
+        let pool = BlockingPool::new("test", 1, 2).unwrap();
+        let (unblock_sender, unblock_receiver) = mpsc::channel::<()>();
+        let (started_sender, started_receiver) = mpsc::channel();
+        let blocking_task = pool
+            .spawn(move || {
+                started_sender.send(()).unwrap();
+                unblock_receiver.recv().ok();
+            })
+            .unwrap();
+        started_receiver
+            .recv_timeout(Duration::from_secs(5))
+            .unwrap();
+
+        let executed = Arc::new(AtomicUsize::new(0));
+        let executed_in_task = Arc::clone(&executed);
+        let abandoned_task = pool
+            .spawn(move || executed_in_task.fetch_add(1, Ordering::SeqCst))
+            .unwrap();
+        drop(abandoned_task);
+
+        unblock_sender.send(()).unwrap();
+        blocking_task.await.unwrap();
+        // Tasks are run in order on the single thread, so the abandoned one is already handled.
+        assert_eq!(pool.run(|| 1).await, Ok(1));
+        assert_eq!(executed.load(Ordering::SeqCst), 0);
+        assert_eq!(pool.queue_depth(), 0);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "task in `test` thread pool panicked")]
+    async fn task_panics_are_propagated() {
+        let pool = BlockingPool::new("test", 1, 1).unwrap();
+        pool.run(|| panic!("oops")).await.ok();
+    }
+}
@@ -1,3 +1,5 @@
+pub mod blocking_pool;
+
 use std::time::Duration;
 
 use ola_dal::connection::ConnectionPool;
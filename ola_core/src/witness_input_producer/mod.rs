@@ -1,13 +1,28 @@
-use std::{sync::Arc, time::Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use anyhow::Ok;
+use anyhow::{Context as _, Ok};
 use async_trait::async_trait;
-use ola_dal::connection::ConnectionPool;
-use ola_types::{witness_block_state::WitnessBlockState, L1BatchNumber, L2ChainId};
+use ola_dal::{basic_witness_input_producer_dal::JOB_MAX_ATTEMPT, connection::ConnectionPool};
+use ola_types::{
+    proofs::WitnessInputStageTimings, witness_block_state::WitnessBlockState, L1BatchNumber,
+    L2ChainId, H256,
+};
+use ola_utils::u256_to_h256;
 use olaos_object_store::{ObjectStore, ObjectStoreFactory};
 use olaos_queued_job_processor::JobProcessor;
 use tokio::{runtime::Handle, task::JoinHandle};
 
+/// Witness inputs of an L1 batch together with the time spent producing them.
+#[derive(Debug)]
+pub struct WitnessInputArtifacts {
+    block_state: WitnessBlockState,
+    timings: WitnessInputStageTimings,
+}
+
 #[derive(Debug)]
 pub struct WitnessInputProducer {
     connection_pool: ConnectionPool,
@@ -29,14 +44,63 @@ impl WitnessInputProducer {
     }
 
     fn process_job_impl(
-        _rt_handle: Handle,
-        _l1_batch_number: L1BatchNumber,
+        rt_handle: Handle,
+        l1_batch_number: L1BatchNumber,
         _started_at: Instant,
-        _connection_pool: ConnectionPool,
+        connection_pool: ConnectionPool,
         _l2_chain_id: L2ChainId,
-    ) -> anyhow::Result<WitnessBlockState> {
-        // TODO:
-        Ok(WitnessBlockState::default())
+    ) -> anyhow::Result<WitnessInputArtifacts> {
+        let mut timings = WitnessInputStageTimings::default();
+        let stage_started_at = Instant::now();
+        let used_factory_deps = rt_handle.block_on(Self::load_used_factory_deps(
+            &connection_pool,
+            l1_batch_number,
+        ))?;
+        timings.db_read_ms = report_stage_time("db_read", stage_started_at.elapsed());
+
+        // TODO: collect storage reads and initial writes by re-executing the batch.
+        let block_state = WitnessBlockState {
+            used_factory_deps,
+            ..WitnessBlockState::default()
+        };
+        Ok(WitnessInputArtifacts {
+            block_state,
+            timings,
+        })
+    }
+
+    /// Loads bytecodes of the contracts used in the batch, so that the witness generator
+    /// doesn't need to access Postgres to resolve code hashes.
+    pub(crate) async fn load_used_factory_deps(
+        connection_pool: &ConnectionPool,
+        l1_batch_number: L1BatchNumber,
+    ) -> anyhow::Result<HashMap<H256, Vec<u8>>> {
+        let mut connection = connection_pool.access_storage().await;
+        let header = connection
+            .blocks_dal()
+            .get_l1_batch_header(l1_batch_number)
+            .await?
+            .with_context(|| format!("L1 batch #{l1_batch_number} is not in Postgres"))?;
+
+        let mut hashes: HashSet<H256> = header
+            .used_contract_hashes
+            .iter()
+            .map(|hash| u256_to_h256(*hash))
+            .collect();
+        hashes.insert(header.base_system_contracts_hashes.entrypoint);
+        hashes.insert(header.base_system_contracts_hashes.default_aa);
+
+        let factory_deps = connection
+            .storage_dal()
+            .get_factory_dep_bytecodes(&hashes)
+            .await
+            .context("failed loading factory deps")?;
+        anyhow::ensure!(
+            factory_deps.len() == hashes.len(),
+            "{} factory deps used in L1 batch #{l1_batch_number} are not found in Postgres",
+            hashes.len() - factory_deps.len()
+        );
+        Ok(factory_deps)
     }
 }
 
@@ -44,17 +108,44 @@ impl WitnessInputProducer {
 impl JobProcessor for WitnessInputProducer {
     type Job = L1BatchNumber;
     type JobId = L1BatchNumber;
-    type JobArtifacts = WitnessBlockState;
+    type JobArtifacts = WitnessInputArtifacts;
     const SERVICE_NAME: &'static str = "witness_input_producer";
 
     async fn get_next_job(&self) -> anyhow::Result<Option<(Self::JobId, Self::Job)>> {
-        let _connection = self.connection_pool.access_storage().await;
-        // TODO:
-        Ok(Some((L1BatchNumber(0), L1BatchNumber(0))))
+        let mut connection = self.connection_pool.access_storage().await;
+        let l1_batch_number = connection
+            .basic_witness_input_producer_dal()
+            .get_next_basic_witness_input_producer_job()
+            .await
+            .context("failed getting next basic witness input producer job")?;
+        Ok(l1_batch_number.map(|number| (number, number)))
     }
 
-    async fn save_failure(&self, _job_id: Self::JobId, _started_at: Instant, _error: String) {
-        todo!()
+    async fn save_failure(&self, job_id: Self::JobId, started_at: Instant, error: String) {
+        let attempts = self
+            .connection_pool
+            .access_storage()
+            .await
+            .basic_witness_input_producer_dal()
+            .mark_job_as_failed(job_id, started_at, error)
+            .await;
+        match attempts {
+            Ok(Some(attempts)) => {
+                olaos_logs::warn!(
+                    "Witness input producer failed on L1 batch #{job_id} (attempt {attempts})"
+                );
+            }
+            Ok(None) => {
+                olaos_logs::warn!(
+                    "Witness input producer job for L1 batch #{job_id} is not in progress anymore"
+                );
+            }
+            Err(err) => {
+                olaos_logs::error!(
+                    "Failed marking witness input producer job for L1 batch #{job_id} as failed: {err}"
+                );
+            }
+        }
     }
 
     async fn process_job(
@@ -78,21 +169,51 @@ impl JobProcessor for WitnessInputProducer {
 
     async fn save_result(
         &self,
-        _job_id: Self::JobId,
-        _started_at: Instant,
-        _artifacts: Self::JobArtifacts,
+        job_id: Self::JobId,
+        started_at: Instant,
+        artifacts: Self::JobArtifacts,
     ) -> anyhow::Result<()> {
-        // TODO:
+        let mut timings = artifacts.timings;
+        let stage_started_at = Instant::now();
+        // The block state is serialized inside `ObjectStore::put()`, so the upload stage
+        // includes serialization.
+        let object_path = self
+            .object_store
+            .put(job_id, &artifacts.block_state)
+            .await
+            .context("failed saving witness block state to the object store")?;
+        timings.upload_ms = report_stage_time("upload", stage_started_at.elapsed());
+        olaos_logs::info!("Produced witness inputs for L1 batch #{job_id}: {timings:?}");
+
+        self.connection_pool
+            .access_storage()
+            .await
+            .basic_witness_input_producer_dal()
+            .mark_job_as_successful(job_id, started_at, &object_path, &timings)
+            .await
+            .context("failed marking basic witness input producer job as successful")?;
         Ok(())
     }
 
     fn max_attempts(&self) -> u32 {
-        // TODO:
-        0 as u32
+        JOB_MAX_ATTEMPT as u32
     }
 
-    async fn get_job_attempts(&self, _job_id: &L1BatchNumber) -> anyhow::Result<u32> {
-        // TODO:
-        Ok(0)
+    async fn get_job_attempts(&self, job_id: &L1BatchNumber) -> anyhow::Result<u32> {
+        let attempts = self
+            .connection_pool
+            .access_storage()
+            .await
+            .basic_witness_input_producer_dal()
+            .get_basic_witness_input_producer_job_attempts(*job_id)
+            .await
+            .context("failed getting basic witness input producer job attempts")?;
+        Ok(attempts.unwrap_or(0))
     }
 }
+
+/// Reports the duration of a witness input production stage and returns it in milliseconds.
+fn report_stage_time(stage: &'static str, elapsed: Duration) -> u64 {
+    metrics::histogram!("server.witness_input_producer.stage_time", elapsed, "stage" => stage);
+    elapsed.as_millis() as u64
+}
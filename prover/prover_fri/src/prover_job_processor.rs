@@ -10,7 +10,7 @@ use olaos_prover_fri_types::{
     circuits::OlaBaseLayerCircuit, prove_with_traces, CircuitWrapper, FriProofWrapper,
     OlaBaseLayerProof, ProverJob, TimingTree, C, D, F,
 };
-use olaos_prover_fri_utils::fetch_next_circuit;
+use olaos_prover_fri_utils::{fetch_next_circuit, prefetch::CircuitPrefetcher};
 use olaos_queued_job_processor::JobProcessor;
 use tokio::task::JoinHandle;
 
@@ -24,6 +24,7 @@ pub struct Prover {
     // Only pick jobs for the configured circuit id and aggregation rounds.
     // Empty means all jobs are picked.
     circuit_ids_for_round_to_be_proven: Vec<CircuitIdRoundTuple>,
+    prefetcher: Option<CircuitPrefetcher>,
 }
 
 impl Prover {
@@ -34,12 +35,17 @@ impl Prover {
         prover_connection_pool: ConnectionPool,
         circuit_ids_for_round_to_be_proven: Vec<CircuitIdRoundTuple>,
     ) -> Self {
+        let prefetcher = match config.prefetch_queue_capacity() {
+            0 => None,
+            capacity => Some(CircuitPrefetcher::new(blob_store.clone(), capacity)),
+        };
         Prover {
             blob_store,
             public_blob_store,
             config: Arc::new(config),
             prover_connection_pool,
             circuit_ids_for_round_to_be_proven,
+            prefetcher,
         }
     }
 
@@ -97,9 +103,11 @@ impl JobProcessor for Prover {
             &mut storage,
             &*self.blob_store,
             &self.circuit_ids_for_round_to_be_proven,
+            self.prefetcher.as_ref(),
             // &self.vk_commitments,
         )
         .await
+        .context("failed fetching next prover job")?
         else {
             return Ok(None);
         };
@@ -107,12 +115,16 @@ impl JobProcessor for Prover {
     }
 
     async fn save_failure(&self, job_id: Self::JobId, _started_at: Instant, error: String) {
-        self.prover_connection_pool
+        let result = self
+            .prover_connection_pool
             .access_storage()
             .await
             .fri_prover_jobs_dal()
             .save_proof_error(job_id, error)
             .await;
+        if let Err(err) = result {
+            olaos_logs::error!("Failed marking prover job {job_id} as failed: {err}");
+        }
     }
 
     async fn process_job(
@@ -148,8 +160,8 @@ impl JobProcessor for Prover {
             self.config.shall_save_to_public_bucket,
             &mut storage_processor,
         )
-        .await;
-        Ok(())
+        .await
+        .context("save_proof()")
     }
 
     fn max_attempts(&self) -> u32 {
@@ -1,6 +1,6 @@
 use std::time::Instant;
 
-use ola_dal::StorageProcessor;
+use ola_dal::{DalResult, StorageProcessor};
 use ola_types::L1BatchNumber;
 use olaos_object_store::ObjectStore;
 use olaos_prover_fri_types::{verifier, AllProof, CircuitWrapper, FriProofWrapper, C, D, F};
@@ -27,7 +27,7 @@ pub async fn save_proof(
     _public_blob_store: Option<&dyn ObjectStore>,
     _shall_save_to_public_bucket: bool,
     storage_processor: &mut StorageProcessor<'_>,
-) {
+) -> DalResult<()> {
     olaos_logs::info!(
         "Successfully proven job: {}, total time taken: {:?}",
         job_id,
@@ -46,8 +46,9 @@ pub async fn save_proof(
     let _job_metadata = transaction
         .fri_prover_jobs_dal()
         .save_proof(job_id, started_at.elapsed(), &blob_url)
-        .await;
+        .await?;
     transaction.commit().await;
+    Ok(())
 }
 
 pub fn verify_proof(circuit_wrapper: CircuitWrapper, proof: AllProof<F, C, D>, job_id: u32) {
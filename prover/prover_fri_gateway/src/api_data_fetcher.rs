@@ -2,6 +2,7 @@ use std::{sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 use ola_dal::connection::ConnectionPool;
+use ola_utils::retry::RetryPolicy;
 use olaos_object_store::ObjectStore;
 use reqwest::Client;
 use serde::{de::DeserializeOwned, Serialize};
@@ -13,6 +14,9 @@ pub(crate) const PROOF_GENERATION_DATA_PATH: &str = "/proof_generation_data";
 /// The path to the API endpoint that submits the proof.
 pub(crate) const SUBMIT_PROOF_PATH: &str = "/submit_proof";
 
+/// Maximum delay after failed requests, relative to the polling interval.
+const MAX_ERROR_BACKOFF_MULTIPLIER: f64 = 8.0;
+
 pub(crate) struct PeriodicApiStruct {
     pub(crate) blob_store: Arc<dyn ObjectStore>,
     pub(crate) pool: ConnectionPool,
@@ -57,19 +61,31 @@ impl PeriodicApiStruct {
             self.poll_duration
         );
 
+        // Failed requests are retried with a growing delay, so that an unavailable API
+        // isn't hammered by all provers at once.
+        let mut error_backoff = RetryPolicy::new(self.poll_duration)
+            .with_exponential_backoff(
+                MAX_ERROR_BACKOFF_MULTIPLIER,
+                self.poll_duration.mul_f64(MAX_ERROR_BACKOFF_MULTIPLIER),
+            )
+            .with_jitter(0.2)
+            .backoff(Self::SERVICE_NAME);
         loop {
             if *stop_receiver.borrow() {
                 olaos_logs::warn!("Stop signal received, shutting down {}", Self::SERVICE_NAME);
                 return Ok(());
             }
 
+            let mut delay = self.poll_duration;
             if let Some((job_id, request)) = self.get_next_request().await {
                 match self.send_request(job_id, request).await {
                     Ok(response) => {
+                        error_backoff.reset();
                         self.handle_response(job_id, response).await;
                     }
                     Err(err) => {
                         olaos_logs::error!("HTTP request failed due to error: {}", err);
+                        delay = error_backoff.next_delay().unwrap_or(delay);
                     }
                 }
             }
@@ -78,7 +94,7 @@ impl PeriodicApiStruct {
                     olaos_logs::warn!("Stop signal received, shutting down {}", Self::SERVICE_NAME);
                     return Ok(());
                 }
-                _ = sleep(self.poll_duration) => {}
+                _ = sleep(delay) => {}
             }
         }
     }
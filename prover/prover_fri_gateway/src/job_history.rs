@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use anyhow::Context as _;
+use ola_dal::connection::ConnectionPool;
+use tokio::{sync::watch, time::sleep};
+
+/// Periodically moves finished prover jobs into the archive table and exports
+/// aggregated proving statistics as metrics.
+pub(crate) struct ProverJobHistoryPruner {
+    pub(crate) pool: ConnectionPool,
+    pub(crate) retention: Duration,
+    pub(crate) interval: Duration,
+}
+
+impl ProverJobHistoryPruner {
+    const SERVICE_NAME: &'static str = "ProverJobHistoryPruner";
+
+    pub(crate) async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        olaos_logs::info!(
+            "Starting periodic job: {} with frequency: {:?}, retention: {:?}",
+            Self::SERVICE_NAME,
+            self.interval,
+            self.retention
+        );
+
+        loop {
+            if *stop_receiver.borrow() {
+                olaos_logs::warn!("Stop signal received, shutting down {}", Self::SERVICE_NAME);
+                return Ok(());
+            }
+
+            self.archive_and_report().await?;
+
+            tokio::select! {
+                _ = stop_receiver.changed() => {
+                    olaos_logs::warn!("Stop signal received, shutting down {}", Self::SERVICE_NAME);
+                    return Ok(());
+                }
+                _ = sleep(self.interval) => {}
+            }
+        }
+    }
+
+    async fn archive_and_report(&self) -> anyhow::Result<()> {
+        let mut storage = self.pool.access_storage_tagged("prover_job_history").await;
+        let mut prover_dal = storage.fri_prover_jobs_dal();
+
+        let archived = prover_dal
+            .archive_old_jobs(self.retention)
+            .await
+            .context("archive_old_jobs()")?;
+        if archived > 0 {
+            olaos_logs::info!("Archived {} finished prover jobs", archived);
+        }
+        metrics::counter!("prover_fri.job_history.archived_jobs", archived as u64);
+
+        let stats = prover_dal
+            .get_prover_jobs_stats()
+            .await
+            .context("get_prover_jobs_stats()")?;
+        for stats in stats {
+            let circuit_id = stats.circuit_id.to_string();
+            let round = stats.aggregation_round.to_string();
+            let labels = [("circuit_id", circuit_id), ("aggregation_round", round)];
+            metrics::gauge!("prover_fri.jobs.total", stats.total_jobs as f64, &labels);
+            metrics::gauge!(
+                "prover_fri.jobs.success_rate",
+                stats.success_rate(),
+                &labels
+            );
+            if let Some(avg_proving_time) = stats.avg_proving_time {
+                metrics::gauge!(
+                    "prover_fri.jobs.avg_proving_time",
+                    avg_proving_time.as_secs_f64(),
+                    &labels
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,7 @@
-use crate::api_data_fetcher::{PeriodicApiStruct, PROOF_GENERATION_DATA_PATH, SUBMIT_PROOF_PATH};
+use crate::{
+    api_data_fetcher::{PeriodicApiStruct, PROOF_GENERATION_DATA_PATH, SUBMIT_PROOF_PATH},
+    job_history::ProverJobHistoryPruner,
+};
 use anyhow::Context as _;
 use ola_config::{
     fri_prover_gateway::load_prover_fri_gateway_config, object_store::load_object_store_config,
@@ -12,6 +15,7 @@ use reqwest::Client;
 use tokio::sync::{oneshot, watch};
 
 mod api_data_fetcher;
+mod job_history;
 mod proof_gen_data_fetcher;
 mod proof_submitter;
 
@@ -37,11 +41,16 @@ async fn main() -> anyhow::Result<()> {
     };
     let proof_gen_data_fetcher = PeriodicApiStruct {
         blob_store: store_factory.create_store().await,
-        pool,
+        pool: pool.clone(),
         api_url: format!("{}{PROOF_GENERATION_DATA_PATH}", config.api_url),
         poll_duration: config.api_poll_duration(),
         client: Client::new(),
     };
+    let job_history_pruner = ProverJobHistoryPruner {
+        pool,
+        retention: config.job_history_retention(),
+        interval: config.job_history_pruning_interval(),
+    };
 
     let (stop_sender, stop_receiver) = watch::channel(false);
 
@@ -60,7 +69,8 @@ async fn main() -> anyhow::Result<()> {
         tokio::spawn(
             proof_gen_data_fetcher.run::<ProofGenerationDataRequest>(stop_receiver.clone()),
         ),
-        tokio::spawn(proof_submitter.run::<SubmitProofRequest>(stop_receiver)),
+        tokio::spawn(proof_submitter.run::<SubmitProofRequest>(stop_receiver.clone())),
+        tokio::spawn(job_history_pruner.run(stop_receiver)),
     ];
 
     let graceful_shutdown = None::<futures::future::Ready<()>>;
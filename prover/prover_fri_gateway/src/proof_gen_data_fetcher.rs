@@ -17,7 +17,7 @@ impl PeriodicApiStruct {
             .fri_protocol_versions_dal()
             .save_prover_protocol_version(data.fri_protocol_version_id)
             .await;
-        connection
+        let result = connection
             .fri_witness_generator_dal()
             .save_witness_inputs(
                 data.l1_batch_number,
@@ -25,6 +25,12 @@ impl PeriodicApiStruct {
                 data.fri_protocol_version_id,
             )
             .await;
+        if let Err(err) = result {
+            olaos_logs::error!(
+                "Failed saving witness inputs for L1 batch #{}: {err}",
+                data.l1_batch_number
+            );
+        }
     }
 }
 
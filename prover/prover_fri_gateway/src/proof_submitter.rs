@@ -2,22 +2,38 @@ use async_trait::async_trait;
 use ola_dal::fri_prover_dal::FriProofJobStatus;
 use ola_types::{
     proofs::L1BatchProofForL1,
-    prover_server_api::{SubmitProofRequest, SubmitProofResponse},
+    prover_server_api::{SubmitProofRequest, SubmitProofResponse, PROVER_ID_HEADER},
     L1BatchNumber,
 };
 use olaos_prover_fri_types::FriProofWrapper;
+use reqwest::StatusCode;
 
 use crate::api_data_fetcher::{PeriodicApi, PeriodicApiStruct};
 
+/// Identifies this gateway to the server: the pod name if running in Kubernetes,
+/// or the host name otherwise.
+fn prover_id() -> String {
+    std::env::var("POD_NAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| "unknown".to_owned())
+}
+
 impl PeriodicApiStruct {
     async fn next_submit_proof_request(&self) -> Option<(L1BatchNumber, SubmitProofRequest)> {
-        let (l1_batch_number, status) = self
+        let proven_block = self
             .pool
             .access_storage()
             .await
             .fri_prover_jobs_dal()
             .get_least_proven_block_number_not_sent_to_server()
-            .await?;
+            .await;
+        let (l1_batch_number, status) = match proven_block {
+            Ok(proven_block) => proven_block?,
+            Err(err) => {
+                olaos_logs::error!("Failed getting the next proven L1 batch: {err}");
+                return None;
+            }
+        };
 
         let request = match status {
             FriProofJobStatus::Successful => {
@@ -41,12 +57,18 @@ impl PeriodicApiStruct {
     }
 
     async fn save_successful_sent_proof(&self, l1_batch_number: L1BatchNumber) {
-        self.pool
+        let result = self
+            .pool
             .access_storage()
             .await
             .fri_prover_jobs_dal()
             .mark_proof_sent_to_server(l1_batch_number)
             .await;
+        if let Err(err) = result {
+            olaos_logs::error!(
+                "Failed marking proof for L1 batch {l1_batch_number} as sent: {err}"
+            );
+        }
     }
 }
 
@@ -67,11 +89,31 @@ impl PeriodicApi<SubmitProofRequest> for PeriodicApiStruct {
         request: SubmitProofRequest,
     ) -> reqwest::Result<Self::Response> {
         let endpoint = format!("{}/{job_id}", self.api_url);
-        self.send_http_request(request, &endpoint).await
+        olaos_logs::info!("Sending request to {}", endpoint);
+        let response = self
+            .client
+            .post(&endpoint)
+            .header(PROVER_ID_HEADER, prover_id())
+            .json(&request)
+            .send()
+            .await?;
+        // A different proof is already saved by the server, so resending this one won't help.
+        if response.status() == StatusCode::CONFLICT {
+            let body = response.text().await?;
+            return Ok(SubmitProofResponse::Error(body));
+        }
+        response.error_for_status()?.json().await
     }
 
     async fn handle_response(&self, job_id: L1BatchNumber, response: Self::Response) {
-        olaos_logs::info!("Received response: {:?}", response);
+        match &response {
+            SubmitProofResponse::Success => {
+                olaos_logs::info!("Received response: {:?}", response);
+            }
+            SubmitProofResponse::Error(err) => {
+                olaos_logs::error!("Server rejected proof for L1 batch #{job_id}: {err}");
+            }
+        }
         self.save_successful_sent_proof(job_id).await;
     }
 }
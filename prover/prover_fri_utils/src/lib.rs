@@ -1,16 +1,21 @@
 use std::time::Instant;
 
-use ola_dal::StorageProcessor;
+use ola_dal::{DalResult, StorageProcessor};
 use ola_types::{basic_fri_types::CircuitIdRoundTuple, protocol_version::FriProtocolVersionId};
 use olaos_object_store::{FriCircuitKey, ObjectStore};
 use olaos_prover_fri_types::{get_current_pod_name, ProverJob, ProverServiceDataKey};
 
+use self::prefetch::CircuitPrefetcher;
+
+pub mod prefetch;
+
 pub async fn fetch_next_circuit(
     storage: &mut StorageProcessor<'_>,
     blob_store: &dyn ObjectStore,
     circuit_ids_for_round_to_be_proven: &Vec<CircuitIdRoundTuple>,
+    prefetcher: Option<&CircuitPrefetcher>,
     // vk_commitments: &L1VerifierConfig,
-) -> Option<ProverJob> {
+) -> DalResult<Option<ProverJob>> {
     // TODO:
     let protocol_versions = vec![FriProtocolVersionId::latest()];
     // let protocol_versions = storage
@@ -38,6 +43,9 @@ pub async fn fetch_next_circuit(
                 .await
         }
     }?;
+    let Some(prover_job) = prover_job else {
+        return Ok(None);
+    };
     olaos_logs::info!("Started processing prover job: {:?}", prover_job);
 
     let circuit_key = FriCircuitKey {
@@ -48,21 +56,37 @@ pub async fn fetch_next_circuit(
         depth: prover_job.depth,
     };
     let started_at = Instant::now();
-    let input = blob_store
-        .get(circuit_key)
-        .await
-        .unwrap_or_else(|err| panic!("{err:?}"));
+    let prefetched = match prefetcher {
+        Some(prefetcher) => prefetcher.take(prover_job.id).await,
+        None => None,
+    };
+    let input = match prefetched {
+        Some(input) => input,
+        None => blob_store
+            .get(circuit_key)
+            .await
+            .unwrap_or_else(|err| panic!("{err:?}")),
+    };
 
     olaos_logs::info!("blob_fetch_time {:?}", started_at.elapsed());
 
+    // Start fetching blobs of the next jobs while this one is being proven.
+    if let Some(prefetcher) = prefetcher {
+        let candidates = storage
+            .fri_prover_jobs_dal()
+            .peek_next_jobs(&protocol_versions, prefetcher.capacity())
+            .await?;
+        prefetcher.prefetch(&candidates, circuit_ids_for_round_to_be_proven);
+    }
+
     let setup_data_key = ProverServiceDataKey {
         circuit_id: prover_job.circuit_id,
         round: prover_job.aggregation_round,
     };
-    Some(ProverJob::new(
+    Ok(Some(ProverJob::new(
         prover_job.block_number,
         prover_job.id,
         input,
         setup_data_key,
-    ))
+    )))
 }
@@ -0,0 +1,111 @@
+use std::{collections::VecDeque, sync::Arc, sync::Mutex};
+
+use ola_types::{basic_fri_types::CircuitIdRoundTuple, proofs::FriProverJobMetadata};
+use olaos_object_store::{FriCircuitKey, ObjectStore, ObjectStoreError};
+use olaos_prover_fri_types::CircuitWrapper;
+use tokio::task::JoinHandle;
+
+type PrefetchHandle = JoinHandle<Result<CircuitWrapper, ObjectStoreError>>;
+
+/// Fetches circuit blobs of upcoming prover jobs in the background, so that the prover doesn't
+/// wait for object store round trips after claiming a job.
+///
+/// At most `capacity` blobs are fetched or held at once. Prefetched jobs may still be claimed
+/// by other provers; such blobs are dropped once the job is no longer among the candidates.
+#[derive(Debug)]
+pub struct CircuitPrefetcher {
+    blob_store: Arc<dyn ObjectStore>,
+    capacity: usize,
+    in_flight: Mutex<VecDeque<(u32, PrefetchHandle)>>,
+}
+
+impl CircuitPrefetcher {
+    pub fn new(blob_store: Arc<dyn ObjectStore>, capacity: usize) -> Self {
+        Self {
+            blob_store,
+            capacity,
+            in_flight: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the prefetched circuit for `job_id`, if it was prefetched successfully.
+    pub async fn take(&self, job_id: u32) -> Option<CircuitWrapper> {
+        let handle = {
+            let mut in_flight = self.in_flight.lock().expect("prefetcher lock poisoned");
+            let position = in_flight.iter().position(|(id, _)| *id == job_id);
+            position.and_then(|position| in_flight.remove(position))
+        };
+        let Some((_, handle)) = handle else {
+            metrics::counter!("prover_fri.prefetch.misses", 1);
+            return None;
+        };
+
+        match handle.await {
+            Ok(Ok(circuit)) => {
+                metrics::counter!("prover_fri.prefetch.hits", 1);
+                Some(circuit)
+            }
+            Ok(Err(err)) => {
+                olaos_logs::warn!("Failed prefetching circuit for prover job {job_id}: {err}");
+                metrics::counter!("prover_fri.prefetch.misses", 1);
+                None
+            }
+            Err(err) => {
+                olaos_logs::warn!("Prefetch task for prover job {job_id} panicked: {err}");
+                metrics::counter!("prover_fri.prefetch.misses", 1);
+                None
+            }
+        }
+    }
+
+    /// Starts fetching circuits of `candidates` (in the order they are going to be picked),
+    /// dropping prefetched circuits of jobs which are no longer candidates.
+    pub fn prefetch(
+        &self,
+        candidates: &[FriProverJobMetadata],
+        circuit_ids_for_round_to_be_proven: &[CircuitIdRoundTuple],
+    ) {
+        let candidates: Vec<_> = candidates
+            .iter()
+            .filter(|job| {
+                circuit_ids_for_round_to_be_proven.is_empty()
+                    || circuit_ids_for_round_to_be_proven.iter().any(|tuple| {
+                        tuple.circuit_id == job.circuit_id
+                            && tuple.aggregation_round == job.aggregation_round as u8
+                    })
+            })
+            .take(self.capacity)
+            .collect();
+
+        let mut in_flight = self.in_flight.lock().expect("prefetcher lock poisoned");
+        in_flight.retain(|(id, handle)| {
+            let is_candidate = candidates.iter().any(|job| job.id == *id);
+            if !is_candidate {
+                handle.abort();
+                metrics::counter!("prover_fri.prefetch.wasted", 1);
+            }
+            is_candidate
+        });
+
+        for job in candidates {
+            if in_flight.iter().any(|(id, _)| *id == job.id) {
+                continue;
+            }
+            let blob_store = self.blob_store.clone();
+            let circuit_key = FriCircuitKey {
+                block_number: job.block_number,
+                sequence_number: job.sequence_number,
+                circuit_id: job.circuit_id,
+                aggregation_round: job.aggregation_round,
+                depth: job.depth,
+            };
+            let handle = tokio::spawn(async move { blob_store.get(circuit_key).await });
+            in_flight.push_back((job.id, handle));
+        }
+        metrics::gauge!("prover_fri.prefetch.queue_size", in_flight.len() as f64);
+    }
+}
@@ -1,12 +1,20 @@
-use std::{collections::HashSet, sync::Arc, time::Instant};
+use std::{
+    collections::HashSet,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use anyhow::Context;
 use async_trait::async_trait;
 use ola_config::fri_witness_generator::FriWitnessGeneratorConfig;
 use ola_dal::{connection::ConnectionPool, fri_witness_generator_dal::FriWitnessJobStatus};
 use ola_types::{
-    proofs::{AggregationRound, BasicCircuitWitnessGeneratorInput, PrepareBasicCircuitsJob},
+    proofs::{
+        AggregationRound, BasicCircuitWitnessGeneratorInput, PrepareBasicCircuitsJob,
+        WitnessStageTimings,
+    },
     protocol_version::FriProtocolVersionId,
+    witness_block_state::WitnessBlockState,
     L1BatchNumber, H256,
 };
 use ola_utils::{bytes_to_chunks, h256_to_u256, u256_to_h256};
@@ -15,15 +23,22 @@ use olaos_prover_fri_types::{get_current_pod_name, OlaBaseLayerCircuit};
 use olaos_queued_job_processor::JobProcessor;
 use rand::Rng;
 
-use crate::precalculated_merkle_paths_provider::PrecalculatedMerklePathsProvider;
+use crate::{
+    memory_budget::{MemoryBudget, MemoryReservation},
+    precalculated_merkle_paths_provider::PrecalculatedMerklePathsProvider,
+};
 
-#[derive(Clone)]
 pub struct BasicWitnessGeneratorJob {
     block_number: L1BatchNumber,
     job: PrepareBasicCircuitsJob,
+    /// Time spent downloading `job` from the object store.
+    blob_fetch_ms: u64,
+    /// Held until the witness is generated.
+    memory_reservation: Option<MemoryReservation>,
 }
 
 pub struct BasicCircuitArtifacts {
+    timings: WitnessStageTimings,
     // basic_circuits: BlockBasicCircuits<GoldilocksField, ZkSyncDefaultRoundFunction>,
     // basic_circuits_inputs: BlockBasicCircuitsPublicInputs<GoldilocksField>,
     // per_circuit_closed_form_inputs: BlockBasicCircuitsPublicCompactFormsWitnesses<GoldilocksField>,
@@ -43,7 +58,9 @@ struct BlobUrls {
     // scheduler_witness_url: String,
 }
 
-#[derive(Debug)]
+/// Cloned generators share the memory budget, so that several of them can process
+/// L1 batches concurrently.
+#[derive(Debug, Clone)]
 pub struct BasicWitnessGenerator {
     config: Arc<FriWitnessGeneratorConfig>,
     object_store: Arc<dyn ObjectStore>,
@@ -51,6 +68,7 @@ pub struct BasicWitnessGenerator {
     connection_pool: ConnectionPool,
     prover_connection_pool: ConnectionPool,
     protocol_versions: Vec<FriProtocolVersionId>,
+    memory_budget: Option<MemoryBudget>,
 }
 
 impl BasicWitnessGenerator {
@@ -62,6 +80,7 @@ impl BasicWitnessGenerator {
         prover_connection_pool: ConnectionPool,
         protocol_versions: Vec<FriProtocolVersionId>,
     ) -> Self {
+        let memory_budget = config.memory_budget_bytes().map(MemoryBudget::new);
         Self {
             config: Arc::new(config),
             object_store: store_factory.create_store().await,
@@ -69,18 +88,52 @@ impl BasicWitnessGenerator {
             connection_pool,
             prover_connection_pool,
             protocol_versions,
+            memory_budget,
         }
     }
 
+    /// Waits until the memory estimated for the L1 batch fits into the budget.
+    async fn reserve_memory(&self, block_number: L1BatchNumber) -> Option<MemoryReservation> {
+        let memory_budget = self.memory_budget.as_ref()?;
+        let storage_log_count = self
+            .connection_pool
+            .access_storage()
+            .await
+            .storage_logs_dal()
+            .count_l1_batch_storage_logs(block_number)
+            .await
+            .unwrap();
+        let estimated_bytes = self.config.estimated_job_memory_bytes(storage_log_count);
+
+        let started_at = Instant::now();
+        let reservation = memory_budget.reserve(estimated_bytes).await;
+        olaos_logs::info!(
+            "Reserved {} MB for block {} with {} storage logs in {:?}",
+            estimated_bytes >> 20,
+            block_number,
+            storage_log_count,
+            started_at.elapsed()
+        );
+        metrics::histogram!(
+            "prover_fri.witness_generator.memory_reservation_wait_time",
+            started_at.elapsed()
+        );
+        Some(reservation)
+    }
+
     async fn process_job_impl(
-        object_store: Arc<dyn ObjectStore>,
         connection_pool: ConnectionPool,
         prover_connection_pool: ConnectionPool,
         basic_job: BasicWitnessGeneratorJob,
         started_at: Instant,
         config: Arc<FriWitnessGeneratorConfig>,
-    ) -> Option<BasicCircuitArtifacts> {
-        let BasicWitnessGeneratorJob { block_number, job } = basic_job;
+    ) -> anyhow::Result<Option<BasicCircuitArtifacts>> {
+        let BasicWitnessGeneratorJob {
+            block_number,
+            job,
+            blob_fetch_ms,
+            memory_reservation: _memory_reservation,
+        } = basic_job;
         let shall_force_process_block = config
             .force_process_block
             .map_or(false, |block| block == block_number.0);
@@ -107,9 +160,10 @@ impl BasicWitnessGenerator {
                 transaction
                     .fri_witness_generator_dal()
                     .mark_witness_job(FriWitnessJobStatus::Skipped, block_number)
-                    .await;
+                    .await
+                    .context("mark_witness_job()")?;
                 transaction.commit().await;
-                return None;
+                return Ok(None);
             }
         }
 
@@ -119,17 +173,15 @@ impl BasicWitnessGenerator {
             block_number.0
         );
 
-        Some(
-            process_basic_circuits_job(
-                &*object_store,
-                config,
-                connection_pool,
-                started_at,
-                block_number,
-                job,
-            )
-            .await,
+        let artifacts = process_basic_circuits_job(
+            connection_pool,
+            started_at,
+            block_number,
+            job,
+            blob_fetch_ms,
         )
+        .await?;
+        Ok(Some(artifacts))
     }
 }
 
@@ -154,19 +206,27 @@ impl JobProcessor for BasicWitnessGenerator {
                 &pod_name,
             )
             .await
+            .context("get_next_basic_circuit_witness_job()")?
         {
             Some(block_number) => {
                 olaos_logs::info!(
                     "Processing FRI basic witness-gen for block {}",
                     block_number
                 );
+                let memory_reservation = self.reserve_memory(block_number).await;
                 let started_at = Instant::now();
-                let job = get_artifacts(block_number, &*self.object_store).await;
+                let mut job = get_artifacts(block_number, &*self.object_store).await;
+                job.memory_reservation = memory_reservation;
 
                 olaos_logs::info!(
                     "WITNESS_GENERATOR_METRICS AggregationRound::BasicCircuits blob_fetch_time {:?}",
                     started_at.elapsed()
                 );
+                metrics::histogram!(
+                    "prover_fri.witness_generator.blob_fetch_time",
+                    started_at.elapsed()
+                );
+                job.blob_fetch_ms = report_stage_time("blob_fetch", started_at.elapsed());
 
                 Ok(Some((block_number, job)))
             }
@@ -175,12 +235,18 @@ impl JobProcessor for BasicWitnessGenerator {
     }
 
     async fn save_failure(&self, job_id: L1BatchNumber, _started_at: Instant, error: String) -> () {
-        self.prover_connection_pool
+        let result = self
+            .prover_connection_pool
             .access_storage()
             .await
             .fri_witness_generator_dal()
             .mark_witness_job_failed(&error, job_id)
             .await;
+        if let Err(err) = result {
+            olaos_logs::error!(
+                "Failed marking witness job for L1 batch #{job_id} as failed: {err}"
+            );
+        }
     }
 
     #[allow(clippy::async_yields_async)]
@@ -190,20 +256,15 @@ impl JobProcessor for BasicWitnessGenerator {
         started_at: Instant,
     ) -> tokio::task::JoinHandle<anyhow::Result<Option<BasicCircuitArtifacts>>> {
         let config = Arc::clone(&self.config);
-        let object_store = Arc::clone(&self.object_store);
         let connection_pool = self.connection_pool.clone();
         let prover_connection_pool = self.prover_connection_pool.clone();
-        tokio::spawn(async move {
-            Ok(Self::process_job_impl(
-                object_store,
-                connection_pool,
-                prover_connection_pool,
-                job,
-                started_at,
-                config,
-            )
-            .await)
-        })
+        tokio::spawn(Self::process_job_impl(
+            connection_pool,
+            prover_connection_pool,
+            job,
+            started_at,
+            config,
+        ))
     }
 
     async fn save_result(
@@ -215,6 +276,7 @@ impl JobProcessor for BasicWitnessGenerator {
         match optional_artifacts {
             None => Ok(()),
             Some(artifacts) => {
+                let mut timings = artifacts.timings;
                 let blob_started_at = Instant::now();
                 let blob_urls = save_artifacts(
                     job_id,
@@ -224,15 +286,25 @@ impl JobProcessor for BasicWitnessGenerator {
                     self.config.shall_save_to_public_bucket,
                 )
                 .await;
+                // Artifacts are serialized inside `ObjectStore::put()`, so the upload stage
+                // includes serialization.
+                let blob_save_time = blob_started_at.elapsed();
+                timings.upload_ms = report_stage_time("upload", blob_save_time);
 
                 olaos_logs::info!(
                     "BasicCircuits job_id {}, blob_save_time {:?}",
                     job_id,
-                    blob_started_at.elapsed()
+                    blob_save_time
                 );
 
-                update_database(&self.prover_connection_pool, started_at, job_id, blob_urls).await;
-                Ok(())
+                update_database(
+                    &self.prover_connection_pool,
+                    started_at,
+                    job_id,
+                    blob_urls,
+                    &timings,
+                )
+                .await
             }
         }
     }
@@ -253,22 +325,27 @@ impl JobProcessor for BasicWitnessGenerator {
 }
 
 async fn process_basic_circuits_job(
-    object_store: &dyn ObjectStore,
-    config: Arc<FriWitnessGeneratorConfig>,
     connection_pool: ConnectionPool,
     started_at: Instant,
     block_number: L1BatchNumber,
     job: PrepareBasicCircuitsJob,
-) -> BasicCircuitArtifacts {
-    let witness_gen_input =
-        build_basic_circuits_witness_generator_input(&connection_pool, job, block_number).await;
+    blob_fetch_ms: u64,
+) -> anyhow::Result<BasicCircuitArtifacts> {
+    let mut timings = WitnessStageTimings {
+        blob_fetch_ms,
+        ..WitnessStageTimings::default()
+    };
+    let stage_started_at = Instant::now();
+    let _witness_gen_input =
+        build_basic_circuits_witness_generator_input(&connection_pool, job, block_number).await?;
+    timings.db_read_ms = report_stage_time("db_read", stage_started_at.elapsed());
     // let (
     //     basic_circuits,
     //     basic_circuits_inputs,
     //     per_circuit_closed_form_inputs,
     //     scheduler_witness,
     //     aux_output_witness,
-    // ) = generate_witness(object_store, config, connection_pool, witness_gen_input).await;
+    // ) = generate_witness(object_store, connection_pool, witness_gen_input).await?;
 
     olaos_logs::info!(
         "Witness generation for block {} is complete in {:?}",
@@ -276,13 +353,29 @@ async fn process_basic_circuits_job(
         started_at.elapsed()
     );
 
-    BasicCircuitArtifacts {
+    Ok(BasicCircuitArtifacts {
+        timings,
         // basic_circuits,
         // basic_circuits_inputs,
         // per_circuit_closed_form_inputs,
         // scheduler_witness,
         // aux_output_witness,
-    }
+    })
+}
+
+/// Generates the basic circuits witness of an L1 batch from the job prepared by the sequencer and
+/// the Merkle tree by re-executing the batch. Prover jobs don't run it yet, since the prover
+/// doesn't consume its artifacts; the proof smoke test of the node uses it to catch witness
+/// regressions. Doesn't touch the prover DB.
+pub async fn generate_basic_circuits_witness(
+    object_store: &dyn ObjectStore,
+    connection_pool: ConnectionPool,
+    block_number: L1BatchNumber,
+    job: PrepareBasicCircuitsJob,
+) -> anyhow::Result<()> {
+    let witness_gen_input =
+        build_basic_circuits_witness_generator_input(&connection_pool, job, block_number).await?;
+    generate_witness(object_store, connection_pool, witness_gen_input).await
 }
 
 async fn update_database(
@@ -290,12 +383,14 @@ async fn update_database(
     started_at: Instant,
     block_number: L1BatchNumber,
     blob_urls: BlobUrls,
-) {
+    stage_timings: &WitnessStageTimings,
+) -> anyhow::Result<()> {
     let mut prover_connection = prover_connection_pool.access_storage().await;
     let protocol_version_id = prover_connection
         .fri_witness_generator_dal()
         .protocol_version_for_l1_batch(block_number)
-        .await;
+        .await
+        .context("protocol_version_for_l1_batch()")?;
     // prover_connection
     //     .fri_prover_jobs_dal()
     //     .insert_prover_jobs(
@@ -318,8 +413,15 @@ async fn update_database(
     //     .await;
     prover_connection
         .fri_witness_generator_dal()
-        .mark_witness_job_as_successful(block_number, started_at.elapsed())
-        .await;
+        .mark_witness_job_as_successful(block_number, started_at.elapsed(), stage_timings)
+        .await
+        .context("mark_witness_job_as_successful()")
+}
+
+/// Reports the duration of a witness generation stage and returns it in milliseconds.
+fn report_stage_time(stage: &'static str, elapsed: Duration) -> u64 {
+    metrics::histogram!("prover_fri.witness_generator.stage_time", elapsed, "stage" => stage);
+    elapsed.as_millis() as u64
 }
 
 async fn get_artifacts(
@@ -327,7 +429,12 @@ async fn get_artifacts(
     object_store: &dyn ObjectStore,
 ) -> BasicWitnessGeneratorJob {
     let job = object_store.get(block_number).await.unwrap();
-    BasicWitnessGeneratorJob { block_number, job }
+    BasicWitnessGeneratorJob {
+        block_number,
+        job,
+        blob_fetch_ms: 0,
+        memory_reservation: None,
+    }
 }
 
 async fn save_artifacts(
@@ -402,13 +509,13 @@ async fn build_basic_circuits_witness_generator_input(
     connection_pool: &ConnectionPool,
     witness_merkle_input: PrepareBasicCircuitsJob,
     l1_batch_number: L1BatchNumber,
-) -> BasicCircuitWitnessGeneratorInput {
+) -> anyhow::Result<BasicCircuitWitnessGeneratorInput> {
     let mut connection = connection_pool.access_storage().await;
     let block_header = connection
         .blocks_dal()
         .get_l1_batch_header(l1_batch_number)
-        .await
-        .unwrap();
+        .await?
+        .with_context(|| format!("L1 batch #{l1_batch_number} is not in Postgres"))?;
     // let initial_heap_content = connection
     //     .blocks_dal()
     //     .get_initial_bootloader_heap(l1_batch_number)
@@ -418,14 +525,14 @@ async fn build_basic_circuits_witness_generator_input(
     let (_, previous_block_timestamp) = connection
         .blocks_dal()
         .get_l1_batch_state_root_and_timestamp(l1_batch_number - 1)
-        .await
-        .unwrap();
+        .await?
+        .context("cannot generate witness before the root hash is computed")?;
     let previous_block_hash = connection
         .blocks_dal()
         .get_l1_batch_state_root(l1_batch_number - 1)
-        .await
-        .expect("cannot generate witness before the root hash is computed");
-    BasicCircuitWitnessGeneratorInput {
+        .await?
+        .context("cannot generate witness before the root hash is computed")?;
+    Ok(BasicCircuitWitnessGeneratorInput {
         block_number: l1_batch_number,
         previous_block_timestamp,
         previous_block_hash,
@@ -433,21 +540,20 @@ async fn build_basic_circuits_witness_generator_input(
         used_bytecodes_hashes: block_header.used_contract_hashes,
         // initial_heap_content,
         merkle_paths_input: witness_merkle_input,
-    }
+    })
 }
 
 async fn generate_witness(
     object_store: &dyn ObjectStore,
-    config: Arc<FriWitnessGeneratorConfig>,
     connection_pool: ConnectionPool,
     input: BasicCircuitWitnessGeneratorInput,
-) -> () {
+) -> anyhow::Result<()> {
     let mut connection = connection_pool.access_storage().await;
     let header = connection
         .blocks_dal()
         .get_l1_batch_header(input.block_number)
-        .await
-        .unwrap();
+        .await?
+        .with_context(|| format!("L1 batch #{} is not in Postgres", input.block_number))?;
 
     let previous_batch_with_metadata = connection
         .blocks_dal()
@@ -456,18 +562,27 @@ async fn generate_witness(
         .unwrap()
         .unwrap();
 
-    let entrypoint_code_bytes = connection
-        .storage_dal()
-        .get_factory_dep(header.base_system_contracts_hashes.entrypoint)
+    // Factory deps used in the batch are collected by the witness input producer, which may not
+    // have processed the batch yet; the job is retried in this case.
+    let block_state: WitnessBlockState = object_store
+        .get(input.block_number)
         .await
-        .expect("Bootloader bytecode should exist");
-    let bootloader_code = bytes_to_chunks(&entrypoint_code_bytes);
-    let account_bytecode_bytes = connection
-        .storage_dal()
-        .get_factory_dep(header.base_system_contracts_hashes.default_aa)
-        .await
-        .expect("Default aa bytecode should exist");
-    let account_bytecode = bytes_to_chunks(&account_bytecode_bytes);
+        .with_context(|| {
+            format!(
+                "witness block state for L1 batch #{} is not saved by the witness input producer yet",
+                input.block_number
+            )
+        })?;
+    let entrypoint_code_bytes = block_state
+        .used_factory_deps
+        .get(&header.base_system_contracts_hashes.entrypoint)
+        .context("bootloader bytecode is missing in the witness block state")?;
+    let bootloader_code = bytes_to_chunks(entrypoint_code_bytes);
+    let account_bytecode_bytes = block_state
+        .used_factory_deps
+        .get(&header.base_system_contracts_hashes.default_aa)
+        .context("default AA bytecode is missing in the witness block state")?;
+    let account_bytecode = bytes_to_chunks(account_bytecode_bytes);
     let account_code_hash = h256_to_u256(header.base_system_contracts_hashes.default_aa);
 
     let hashes: HashSet<H256> = input
@@ -486,7 +601,8 @@ async fn generate_witness(
     //     .unwrap()
     //     .unwrap();
 
-    let mut used_bytecodes = connection.storage_dal().get_factory_deps(&hashes).await;
+    let mut used_bytecodes = block_state.used_bytecodes();
+    used_bytecodes.retain(|hash, _| hashes.contains(&u256_to_h256(*hash)));
     if input.used_bytecodes_hashes.contains(&account_code_hash) {
         used_bytecodes.insert(account_code_hash, account_bytecode);
     }
@@ -494,7 +610,7 @@ async fn generate_witness(
     assert_eq!(
         hashes.len(),
         used_bytecodes.len(),
-        "{} factory deps are not found in DB",
+        "{} factory deps are not found in the witness block state",
         hashes.len() - used_bytecodes.len()
     );
 
@@ -503,8 +619,8 @@ async fn generate_witness(
     let (_, last_miniblock_number) = connection
         .blocks_dal()
         .get_miniblock_range_of_l1_batch(input.block_number - 1)
-        .await
-        .expect("L1 batch should contain at least one miniblock");
+        .await?
+        .context("L1 batch should contain at least one miniblock")?;
     drop(connection);
 
     let mut tree = PrecalculatedMerklePathsProvider::new(
@@ -594,4 +710,5 @@ async fn generate_witness(
     //         scheduler_witness,
     //         block_aux_witness,
     //     )
+    Ok(())
 }
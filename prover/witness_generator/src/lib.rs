@@ -1,5 +1,7 @@
 #![feature(generic_const_exprs)]
 
 pub mod basic_circuits;
+pub mod memory_budget;
 pub mod precalculated_merkle_paths_provider;
+pub mod runners;
 pub mod utils;
@@ -1,20 +1,22 @@
 use std::time::Instant;
 
-use anyhow::{anyhow, Ok};
-use futures::{channel::mpsc, executor::block_on, SinkExt, StreamExt};
+use anyhow::anyhow;
+use futures::{channel::mpsc, executor::block_on, SinkExt};
 use ola_config::{
     fri_witness_generator::load_fri_witness_generator_config,
     object_store::load_object_store_config,
 };
 use ola_dal::connection::{ConnectionPool, DbVariant};
 use ola_types::proofs::AggregationRound;
-use ola_utils::wait_for_tasks::wait_for_tasks;
 use olaos_logs::telemetry::{get_subscriber, init_subscriber};
 use olaos_object_store::ObjectStoreFactory;
 use olaos_queued_job_processor::JobProcessor;
-use olaos_witness_generator::basic_circuits::BasicWitnessGenerator;
+use olaos_witness_generator::{
+    basic_circuits::BasicWitnessGenerator,
+    runners::{split_iterations, wait_for_runners},
+};
 use structopt::StructOpt;
-use tokio::sync::watch;
+use tokio::{sync::watch, task::JoinSet};
 
 #[derive(Debug, StructOpt)]
 #[structopt(
@@ -83,7 +85,7 @@ async fn main() -> anyhow::Result<()> {
             ));
         }
     };
-    let mut tasks = Vec::new();
+    let mut runners = JoinSet::new();
 
     for (i, round) in rounds.iter().enumerate() {
         olaos_logs::info!(
@@ -93,7 +95,7 @@ async fn main() -> anyhow::Result<()> {
             &protocol_versions
         );
 
-        let witness_generator_task = match round {
+        match round {
             AggregationRound::BasicCircuits => {
                 let public_blob_store = match config.shall_save_to_public_bucket {
                     false => None,
@@ -112,11 +114,13 @@ async fn main() -> anyhow::Result<()> {
                     protocol_versions.clone(),
                 )
                 .await;
-                generator.run(stop_receiver.clone(), opt.batch_size)
+                // Jobs are claimed with row locks, so runners never pick the same L1 batch.
+                let runner_count = config.max_concurrent_jobs();
+                for iterations in split_iterations(opt.batch_size, runner_count) {
+                    runners.spawn(generator.clone().run(stop_receiver.clone(), iterations));
+                }
             }
-        };
-
-        tasks.push(tokio::spawn(witness_generator_task));
+        }
 
         olaos_logs::info!(
             "initialized {:?} witness generator in {:?}",
@@ -125,21 +129,12 @@ async fn main() -> anyhow::Result<()> {
         );
     }
 
-    let (mut stop_signal_sender, mut stop_signal_receiver) = mpsc::channel(256);
+    let (mut stop_signal_sender, stop_signal_receiver) = mpsc::channel(256);
     ctrlc::set_handler(move || {
         block_on(stop_signal_sender.send(true)).expect("Ctrl+C signal send");
     })
     .expect("Error setting Ctrl+C handler");
-    let graceful_shutdown = None::<futures::future::Ready<()>>;
-    let tasks_allowed_to_finish = true;
-    tokio::select! {
-        _ = wait_for_tasks(tasks, graceful_shutdown, tasks_allowed_to_finish) => {},
-        _ = stop_signal_receiver.next() => {
-            olaos_logs::info!("Stop signal received, shutting down");
-        }
-    }
-
-    stop_sender.send(true).ok();
+    let result = wait_for_runners(runners, &stop_sender, stop_signal_receiver).await;
     olaos_logs::info!("Finished witness generation");
-    Ok(())
+    result
 }
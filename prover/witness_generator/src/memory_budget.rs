@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+const BYTES_PER_PERMIT: u64 = 1 << 20;
+
+/// Memory budget shared by witness generation jobs processed concurrently. Jobs reserve
+/// their estimated memory before processing and wait while the budget is exhausted.
+#[derive(Debug, Clone)]
+pub struct MemoryBudget {
+    semaphore: Arc<Semaphore>,
+    total_permits: u32,
+}
+
+/// Memory reserved for a job; released on drop.
+#[derive(Debug)]
+pub struct MemoryReservation {
+    _permit: OwnedSemaphorePermit,
+}
+
+impl MemoryBudget {
+    pub fn new(total_bytes: u64) -> Self {
+        let total_permits = u32::try_from(total_bytes / BYTES_PER_PERMIT)
+            .unwrap_or(u32::MAX)
+            .max(1);
+        Self {
+            semaphore: Arc::new(Semaphore::new(total_permits as usize)),
+            total_permits,
+        }
+    }
+
+    /// Reserves `bytes` of the budget. Jobs estimated to exceed the whole budget reserve
+    /// all of it, so that they are processed alone rather than never.
+    pub async fn reserve(&self, bytes: u64) -> MemoryReservation {
+        let permits = u32::try_from(bytes.div_ceil(BYTES_PER_PERMIT))
+            .unwrap_or(u32::MAX)
+            .clamp(1, self.total_permits);
+        let permit = Arc::clone(&self.semaphore)
+            .acquire_many_owned(permits)
+            .await
+            .expect("memory budget semaphore is never closed");
+        metrics::gauge!(
+            "prover_fri.witness_generator.memory_budget_available_bytes",
+            self.available_bytes() as f64
+        );
+        MemoryReservation { _permit: permit }
+    }
+
+    pub fn available_bytes(&self) -> u64 {
+        self.semaphore.available_permits() as u64 * BYTES_PER_PERMIT
+    }
+}
@@ -0,0 +1,151 @@
+use futures::{Stream, StreamExt};
+use ola_utils::panic_extractor::try_extract_panic_message;
+use tokio::{sync::watch, task::JoinSet};
+
+/// Distributes `batch_size` iterations among `runners`, skipping runners with nothing to do.
+pub fn split_iterations(batch_size: Option<usize>, runners: usize) -> Vec<Option<usize>> {
+    match batch_size {
+        None => vec![None; runners],
+        Some(batch_size) => (0..runners)
+            .map(|i| batch_size / runners + usize::from(i < batch_size % runners))
+            .filter(|&iterations| iterations > 0)
+            .map(Some)
+            .collect(),
+    }
+}
+
+/// Waits until all `runners` terminate. Runners only check the stop signal between jobs, so once
+/// a stop signal is received or a runner fails, the remaining runners are asked to stop and
+/// finish their current jobs. Returns an error if any of the runners failed or panicked.
+pub async fn wait_for_runners(
+    mut runners: JoinSet<anyhow::Result<()>>,
+    stop_sender: &watch::Sender<bool>,
+    stop_signal: impl Stream<Item = bool> + Unpin,
+) -> anyhow::Result<()> {
+    let mut stop_signal = stop_signal.fuse();
+    let mut failed_runners = 0;
+    loop {
+        tokio::select! {
+            result = runners.join_next() => {
+                let Some(result) = result else {
+                    break;
+                };
+                let error = match result {
+                    Ok(Ok(())) => continue,
+                    Ok(Err(err)) => format!("{err:#}"),
+                    Err(err) => try_extract_panic_message(err),
+                };
+                olaos_logs::error!("Witness generator runner failed: {error}");
+                failed_runners += 1;
+                stop_sender.send_replace(true);
+            }
+            Some(_) = stop_signal.next() => {
+                olaos_logs::info!(
+                    "Stop signal received, waiting for {} runners to finish their jobs",
+                    runners.len()
+                );
+                stop_sender.send_replace(true);
+            }
+        }
+    }
+    anyhow::ensure!(
+        failed_runners == 0,
+        "{failed_runners} witness generator runners failed"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use futures::{channel::mpsc, SinkExt};
+
+    use super::*;
+
+    #[test]
+    fn splitting_iterations() {
+        assert_eq!(split_iterations(None, 2), [None::<usize>, None]);
+        assert_eq!(split_iterations(Some(5), 2), [Some(3), Some(2)]);
+        assert_eq!(split_iterations(Some(1), 3), [Some(1)]);
+        assert!(split_iterations(Some(0), 3).is_empty());
+    }
+
+    /// Emulates `JobProcessor::run()`: the stop signal is only checked between jobs.
+    async fn run_jobs(
+        stop_receiver: watch::Receiver<bool>,
+        finished_jobs: Arc<AtomicUsize>,
+        fail: bool,
+    ) -> anyhow::Result<()> {
+        while !*stop_receiver.borrow() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            anyhow::ensure!(!fail, "job failed");
+            finished_jobs.fetch_add(1, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn runners_finish_jobs_after_stop_signal() {
+        let (stop_sender, stop_receiver) = watch::channel(false);
+        let finished_jobs = Arc::new(AtomicUsize::new(0));
+        let mut runners = JoinSet::new();
+        for _ in 0..3 {
+            runners.spawn(run_jobs(
+                stop_receiver.clone(),
+                finished_jobs.clone(),
+                false,
+            ));
+        }
+        let (mut signal_sender, signal_receiver) = mpsc::channel(1);
+        signal_sender.send(true).await.unwrap();
+
+        wait_for_runners(runners, &stop_sender, signal_receiver)
+            .await
+            .unwrap();
+        assert!(*stop_sender.borrow());
+        // Each runner has completed the job it was processing when the signal arrived.
+        assert!(finished_jobs.load(Ordering::SeqCst) >= 3);
+    }
+
+    #[tokio::test]
+    async fn failed_runner_stops_other_runners() {
+        let (stop_sender, stop_receiver) = watch::channel(false);
+        let finished_jobs = Arc::new(AtomicUsize::new(0));
+        let mut runners = JoinSet::new();
+        runners.spawn(run_jobs(stop_receiver.clone(), finished_jobs.clone(), true));
+        runners.spawn(run_jobs(stop_receiver, finished_jobs, false));
+        let (_signal_sender, signal_receiver) = mpsc::channel::<bool>(1);
+
+        let err = wait_for_runners(runners, &stop_sender, signal_receiver)
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("1 witness generator runners failed"));
+    }
+
+    #[tokio::test]
+    async fn finished_runners_are_awaited() {
+        let (stop_sender, _) = watch::channel(false);
+        let mut runners = JoinSet::new();
+        for delay in [10, 50] {
+            runners.spawn(async move {
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                Ok(())
+            });
+        }
+        let (_signal_sender, signal_receiver) = mpsc::channel::<bool>(1);
+
+        wait_for_runners(runners, &stop_sender, signal_receiver)
+            .await
+            .unwrap();
+        assert!(!*stop_sender.borrow());
+    }
+}
@@ -5,7 +5,7 @@ use std::{
 
 use anyhow::Context as _;
 pub use async_trait::async_trait;
-use ola_utils::panic_extractor::try_extract_panic_message;
+use ola_utils::{panic_extractor::try_extract_panic_message, retry::RetryPolicy};
 use tokio::{sync::watch, task::JoinHandle, time::sleep};
 
 #[async_trait]
@@ -47,7 +47,13 @@ pub trait JobProcessor: Sync + Send {
     where
         Self: Sized,
     {
-        let mut backoff: u64 = Self::POLLING_INTERVAL_MS;
+        let mut backoff = RetryPolicy::new(Duration::from_millis(Self::POLLING_INTERVAL_MS))
+            .with_exponential_backoff(
+                Self::BACKOFF_MULTIPLIER as f64,
+                Duration::from_millis(Self::MAX_BACKOFF_MS),
+            )
+            .with_jitter(0.1)
+            .backoff(Self::SERVICE_NAME);
         while iterations_left.map_or(true, |i| i > 0) {
             if *stop_receiver.borrow() {
                 olaos_logs::warn!(
@@ -60,7 +66,7 @@ pub trait JobProcessor: Sync + Send {
                 Self::get_next_job(&self).await.context("get_next_job()")?
             {
                 let started_at = Instant::now();
-                backoff = Self::POLLING_INTERVAL_MS;
+                backoff.reset();
                 iterations_left = iterations_left.map(|i| i - 1);
 
                 olaos_logs::info!(
@@ -77,9 +83,10 @@ pub trait JobProcessor: Sync + Send {
                 olaos_logs::info!("No more jobs to process. Server can stop now.");
                 return Ok(());
             } else {
-                olaos_logs::info!("Backing off for {} ms", backoff);
-                sleep(Duration::from_millis(backoff)).await;
-                backoff = (backoff * Self::BACKOFF_MULTIPLIER).min(Self::MAX_BACKOFF_MS);
+                // The backoff is unbounded, so it always yields a delay.
+                let delay = backoff.next_delay().unwrap_or_default();
+                olaos_logs::info!("Backing off for {delay:?}");
+                sleep(delay).await;
             }
         }
         olaos_logs::info!("Requested number of jobs is processed. Server can stop now.");
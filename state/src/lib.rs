@@ -4,6 +4,7 @@ use ola_types::{get_known_code_key, StorageKey, StorageValue, H256};
 
 pub mod cache;
 pub mod in_memory;
+pub mod overlay;
 pub mod postgres;
 pub mod rocksdb;
 pub mod storage_view;
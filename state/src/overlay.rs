@@ -0,0 +1,92 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anyhow::Context as _;
+use ola_types::{
+    api::{OverrideAccount, StateOverride},
+    get_full_code_key, get_known_code_key, get_nonce_key, AccountTreeId, Address, StorageKey,
+    StorageValue, H256,
+};
+use ola_utils::{bytecode::hash_bytecode, convert::u256_to_h256};
+
+/// Call state overrides translated into storage slots of the corresponding system contracts:
+/// the nonce goes to `NonceHolder`, the code hash to `AccountCodeStorage` and `KnownCodesStorage`.
+///
+/// The call executor keeps its own storage, so overrides are applied by writing
+/// [`Self::resolve_writes()`] into the storage cache before the call.
+#[derive(Debug, Clone, Default)]
+pub struct StorageOverrides {
+    slots: HashMap<StorageKey, StorageValue>,
+    /// Accounts whose storage is replaced entirely; slots not in `slots` read as zero.
+    replaced_accounts: HashSet<Address>,
+    factory_deps: HashMap<H256, Vec<u8>>,
+}
+
+impl StorageOverrides {
+    pub fn new(state_override: &StateOverride) -> anyhow::Result<Self> {
+        let mut this = Self::default();
+        for (address, account) in state_override {
+            this.apply(address, account)
+                .with_context(|| format!("invalid state override for account {address:?}"))?;
+        }
+        Ok(this)
+    }
+
+    /// Returns accounts whose storage is replaced entirely by the `state` override.
+    pub fn replaced_accounts(&self) -> impl Iterator<Item = &Address> + '_ {
+        self.replaced_accounts.iter()
+    }
+
+    /// Returns hashes of bytecodes set by `code` overrides.
+    pub fn factory_dep_hashes(&self) -> impl Iterator<Item = &H256> + '_ {
+        self.factory_deps.keys()
+    }
+
+    /// Returns slot values to write so that the storage reflects the overrides, ordered by key.
+    /// `existing_slots` must contain all non-zero slots of [`Self::replaced_accounts()`];
+    /// the slots not listed in the `state` override are reset to zero.
+    pub fn resolve_writes(
+        &self,
+        existing_slots: impl IntoIterator<Item = StorageKey>,
+    ) -> Vec<(StorageKey, StorageValue)> {
+        let mut writes: BTreeMap<_, _> = existing_slots
+            .into_iter()
+            .filter(|key| self.replaced_accounts.contains(key.address()))
+            .map(|key| (key, H256::zero()))
+            .collect();
+        writes.extend(self.slots.iter().map(|(key, value)| (*key, *value)));
+        writes.into_iter().collect()
+    }
+
+    fn apply(&mut self, address: &Address, account: &OverrideAccount) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            account.balance.is_none(),
+            "balance overrides are not supported, since Ola has no native balances"
+        );
+        if let Some(nonce) = account.nonce {
+            self.slots
+                .insert(get_nonce_key(address), u256_to_h256(nonce));
+        }
+        if let Some(code) = &account.code {
+            let hash = hash_bytecode(&code.0);
+            self.slots.insert(get_full_code_key(address), hash);
+            self.slots
+                .insert(get_known_code_key(&hash), H256::from_low_u64_be(1));
+            self.factory_deps.insert(hash, code.0.clone());
+        }
+
+        let slots = match (&account.state, &account.state_diff) {
+            (Some(_), Some(_)) => anyhow::bail!("`state` and `stateDiff` are mutually exclusive"),
+            (Some(state), None) => {
+                self.replaced_accounts.insert(*address);
+                state
+            }
+            (None, Some(state_diff)) => state_diff,
+            (None, None) => return Ok(()),
+        };
+        let account_id = AccountTreeId::new(*address);
+        for (key, value) in slots {
+            self.slots.insert(StorageKey::new(account_id, *key), *value);
+        }
+        Ok(())
+    }
+}
@@ -1,8 +1,14 @@
 use std::{collections::HashMap, path::Path, sync::Arc, time::Instant};
 
-use ola_dal::StorageProcessor;
-use ola_types::{L1BatchNumber, StorageKey, StorageValue, H256};
-use olaos_storage::db::{NamedColumnFamily, RocksDB};
+use ola_dal::{DalResult, StorageProcessor};
+use ola_types::{
+    snapshots::{SnapshotFactoryDependency, SnapshotStorageLog},
+    L1BatchNumber, StorageKey, StorageValue, H256,
+};
+use olaos_storage::{
+    db::{NamedColumnFamily, RocksDB},
+    RocksDBOptions,
+};
 
 use crate::{in_memory::InMemoryStorage, ReadStorage};
 
@@ -15,6 +21,24 @@ fn deserialize_block_number(bytes: &[u8]) -> u32 {
     u32::from_le_bytes(bytes)
 }
 
+fn serialize_recovery_progress(l1_batch_number: L1BatchNumber, next_chunk_id: u64) -> [u8; 12] {
+    let mut bytes = [0_u8; 12];
+    bytes[..4].copy_from_slice(&serialize_block_number(l1_batch_number.0));
+    bytes[4..].copy_from_slice(&next_chunk_id.to_le_bytes());
+    bytes
+}
+
+fn deserialize_recovery_progress(bytes: &[u8]) -> (L1BatchNumber, u64) {
+    assert_eq!(
+        bytes.len(),
+        12,
+        "incorrect snapshot recovery progress format"
+    );
+    let l1_batch_number = deserialize_block_number(&bytes[..4]);
+    let next_chunk_id = u64::from_le_bytes(bytes[4..].try_into().unwrap());
+    (L1BatchNumber(l1_batch_number), next_chunk_id)
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum SequencerColumnFamily {
     State,
@@ -44,19 +68,36 @@ pub struct RocksdbStorage {
 
 impl RocksdbStorage {
     const BLOCK_NUMBER_KEY: &'static [u8] = b"block_number";
+    const SNAPSHOT_RECOVERY_KEY: &'static [u8] = b"snapshot_recovery";
 
     /// Creates a new storage with the provided RocksDB `path`.
     pub fn new(path: &Path) -> Self {
-        let db = RocksDB::new(path);
+        Self::with_options(path, RocksDBOptions::default())
+    }
+
+    /// Creates a new storage with the provided RocksDB `path` and `options`.
+    pub fn with_options(path: &Path, options: RocksDBOptions) -> Self {
+        let db = RocksDB::with_options(path, options);
         Self {
             db: Arc::new(db),
             pending_patch: InMemoryStorage::default(),
         }
     }
 
-    pub async fn update_from_postgres(&mut self, conn: &mut StorageProcessor<'_>) {
-        let _stage_started_at: Instant = Instant::now();
-        let latest_l1_batch_number = conn.blocks_dal().get_sealed_l1_batch_number().await;
+    pub async fn update_from_postgres(&mut self, conn: &mut StorageProcessor<'_>) -> DalResult<()> {
+        let latest_l1_batch_number = conn.blocks_dal().get_sealed_l1_batch_number().await?;
+        self.update_from_postgres_until(conn, latest_l1_batch_number)
+            .await
+    }
+
+    /// Same as [`Self::update_from_postgres()`], but only loads state changes up to and including
+    /// `latest_l1_batch_number`, which must be sealed.
+    pub async fn update_from_postgres_until(
+        &mut self,
+        conn: &mut StorageProcessor<'_>,
+        latest_l1_batch_number: L1BatchNumber,
+    ) -> DalResult<()> {
+        let stage_started_at = Instant::now();
         olaos_logs::info!(
             "loading storage for l1 batch number {}",
             latest_l1_batch_number.0
@@ -68,20 +109,25 @@ impl RocksdbStorage {
             "L1 batch number in sequencer cache ({current_l1_batch_number}) is greater than \
              the last sealed L1 batch number in Postgres ({latest_l1_batch_number})"
         );
+        let first_l1_batch_number = current_l1_batch_number;
+        let total_l1_batches = latest_l1_batch_number.0 + 1 - first_l1_batch_number;
 
         while current_l1_batch_number <= latest_l1_batch_number.0 {
-            olaos_logs::info!("loading state changes for l1 batch {current_l1_batch_number}");
+            olaos_logs::info!(
+                "loading state changes for l1 batch {current_l1_batch_number} ({}/{total_l1_batches})",
+                current_l1_batch_number - first_l1_batch_number + 1
+            );
             let storage_logs = conn
                 .storage_logs_dal()
                 .get_touched_slots_for_l1_batch(L1BatchNumber(current_l1_batch_number))
-                .await;
+                .await?;
             self.process_transaction_logs(&storage_logs);
 
             olaos_logs::info!("loading factory deps for l1 batch {current_l1_batch_number}");
             let factory_deps = conn
                 .blocks_dal()
                 .get_l1_batch_factory_deps(L1BatchNumber(current_l1_batch_number))
-                .await;
+                .await?;
             for (hash, bytecode) in factory_deps {
                 self.store_factory_dep(hash, bytecode);
             }
@@ -89,6 +135,91 @@ impl RocksdbStorage {
             current_l1_batch_number += 1;
             self.save(L1BatchNumber(current_l1_batch_number)).await;
         }
+
+        if total_l1_batches > 0 {
+            olaos_logs::info!(
+                "loaded {total_l1_batches} l1 batches from Postgres in {:?}",
+                stage_started_at.elapsed()
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if the storage contains no state, i.e. it was neither synced from Postgres
+    /// nor (partially) recovered from a snapshot.
+    pub fn is_empty(&self) -> bool {
+        let cf = SequencerColumnFamily::State;
+        let block_number = self
+            .db
+            .get_cf(cf, Self::BLOCK_NUMBER_KEY)
+            .expect("failed to fetch block number");
+        block_number.is_none() && self.snapshot_recovery_progress().is_none()
+    }
+
+    /// Returns the L1 batch of the snapshot being recovered and the ID of the next storage logs
+    /// chunk to apply, or `None` if snapshot recovery is not in progress.
+    pub fn snapshot_recovery_progress(&self) -> Option<(L1BatchNumber, u64)> {
+        let cf = SequencerColumnFamily::State;
+        let progress = self
+            .db
+            .get_cf(cf, Self::SNAPSHOT_RECOVERY_KEY)
+            .expect("failed to fetch snapshot recovery progress")?;
+        Some(deserialize_recovery_progress(&progress))
+    }
+
+    /// Writes a chunk of snapshot storage logs together with the recovery progress, so that
+    /// an interrupted recovery resumes from the next chunk.
+    pub async fn apply_snapshot_chunk(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        chunk_id: u64,
+        storage_logs: Vec<SnapshotStorageLog>,
+    ) {
+        let db = Arc::clone(&self.db);
+        let save_task = tokio::task::spawn_blocking(move || {
+            let mut batch = db.new_write_batch();
+            let cf = SequencerColumnFamily::State;
+            batch.put_cf(
+                cf,
+                Self::SNAPSHOT_RECOVERY_KEY,
+                &serialize_recovery_progress(l1_batch_number, chunk_id + 1),
+            );
+            for log in storage_logs {
+                batch.put_cf(cf, log.hashed_key.as_bytes(), log.value.as_bytes());
+            }
+            db.write(batch)
+                .expect("failed to save snapshot chunk into rocksdb");
+        });
+        save_task.await.unwrap();
+    }
+
+    /// Completes snapshot recovery: writes factory deps and marks the storage as synced up to and
+    /// including `l1_batch_number`, after which [`Self::update_from_postgres()`] only loads
+    /// the following L1 batches.
+    pub async fn finish_snapshot_recovery(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        factory_deps: Vec<SnapshotFactoryDependency>,
+    ) {
+        let db = Arc::clone(&self.db);
+        let save_task = tokio::task::spawn_blocking(move || {
+            let mut batch = db.new_write_batch();
+            let cf = SequencerColumnFamily::FactoryDeps;
+            for dep in factory_deps {
+                batch.put_cf(cf, dep.bytecode_hash.as_bytes(), &dep.bytecode);
+            }
+
+            let cf = SequencerColumnFamily::State;
+            batch.delete_cf(cf, Self::SNAPSHOT_RECOVERY_KEY);
+            batch.put_cf(
+                cf,
+                Self::BLOCK_NUMBER_KEY,
+                &serialize_block_number(l1_batch_number.0 + 1),
+            );
+            db.write(batch)
+                .expect("failed to finish snapshot recovery in rocksdb");
+        });
+        save_task.await.unwrap();
     }
 
     async fn save(&mut self, l1_batch_number: L1BatchNumber) {
@@ -147,6 +278,33 @@ impl RocksdbStorage {
         self.pending_patch.factory_deps.insert(hash, bytecode);
     }
 
+    /// Writes factory deps directly to RocksDB, without waiting for the next L1 batch to be saved.
+    /// Used to make bytecodes not originating from Postgres storage logs (e.g., base system
+    /// contracts of the current protocol version) available to the VM.
+    pub fn persist_factory_deps(&self, factory_deps: impl IntoIterator<Item = (H256, Vec<u8>)>) {
+        let mut batch = self.db.new_write_batch();
+        let cf = SequencerColumnFamily::FactoryDeps;
+        for (hash, bytecode) in factory_deps {
+            batch.put_cf(cf, &hash.to_fixed_bytes(), &bytecode);
+        }
+        self.db
+            .write(batch)
+            .expect("failed to save factory deps into rocksdb");
+    }
+
+    /// Writes storage values directly to RocksDB, bypassing the L1 batch number tracking.
+    /// Used to set up state which doesn't originate from Postgres, e.g. in shadow executions.
+    pub fn persist_values(&self, values: impl IntoIterator<Item = (StorageKey, StorageValue)>) {
+        let mut batch = self.db.new_write_batch();
+        let cf = SequencerColumnFamily::State;
+        for (key, value) in values {
+            batch.put_cf(cf, &Self::serialize_state_key(&key), value.as_ref());
+        }
+        self.db
+            .write(batch)
+            .expect("failed to save state values into rocksdb");
+    }
+
     fn serialize_state_key(key: &StorageKey) -> [u8; 32] {
         key.hashed_key().to_fixed_bytes()
     }
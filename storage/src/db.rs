@@ -5,7 +5,7 @@ use std::{
     marker::PhantomData,
     ops,
     path::Path,
-    sync::{Arc, Condvar, Mutex},
+    sync::{Arc, Condvar, Mutex, Weak},
     thread,
     time::{Duration, Instant},
 };
@@ -15,6 +15,8 @@ use rocksdb::{
     Direction, IteratorMode, Options, PrefixRange, ReadOptions, WriteOptions, DB,
 };
 
+use crate::metrics::RocksDBIoStats;
+
 /// Number of active RocksDB instances used to determine if it's safe to exit current process.
 /// Not properly dropped RocksDB instances can lead to DB corruption.
 static ROCKSDB_INSTANCE_COUNTER: (Mutex<usize>, Condvar) = (Mutex::new(0), Condvar::new());
@@ -68,6 +70,37 @@ impl<CF: NamedColumnFamily> WriteBatch<'_, CF> {
     }
 }
 
+/// Read-only snapshot of a [`RocksDB`] instance.
+pub struct RocksDBSnapshot<'a, CF> {
+    inner: rocksdb::Snapshot<'a>,
+    db: &'a RocksDB<CF>,
+}
+
+impl<CF: fmt::Debug> fmt::Debug for RocksDBSnapshot<'_, CF> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("RocksDBSnapshot")
+            .field("db", self.db)
+            .finish()
+    }
+}
+
+impl<CF: NamedColumnFamily> RocksDBSnapshot<'_, CF> {
+    pub fn get_cf(&self, cf: CF, key: &[u8]) -> Result<Option<Vec<u8>>, rocksdb::Error> {
+        let cf = self.db.column_family(cf);
+        self.inner.get_cf(cf, key)
+    }
+
+    pub fn multi_get_cf(
+        &self,
+        cf: CF,
+        keys: impl Iterator<Item = Vec<u8>>,
+    ) -> Vec<Result<Option<Vec<u8>>, rocksdb::Error>> {
+        let cf = self.db.column_family(cf);
+        self.inner.multi_get_cf(keys.map(|key| (cf, key)))
+    }
+}
+
 struct RocksDBCaches {
     /// LRU block cache shared among all column families.
     shared: Option<Cache>,
@@ -88,11 +121,47 @@ impl RocksDBCaches {
     }
 }
 
+/// Statistics collected for a RocksDB instance if IO statistics are enabled.
+struct RocksDBStatistics {
+    /// Options the DB was opened with; they own the RocksDB statistics object.
+    db_options: Options,
+    /// Statistics as of the last report.
+    reported: Mutex<RocksDBIoStats>,
+}
+
+impl RocksDBStatistics {
+    fn new(db_options: Options) -> Self {
+        Self {
+            db_options,
+            reported: Mutex::default(),
+        }
+    }
+
+    fn report(&self, db_name: &'static str) {
+        let Some(statistics) = self.db_options.get_statistics() else {
+            return;
+        };
+        let stats = RocksDBIoStats::parse(&statistics);
+        let mut reported = self.reported.lock().unwrap();
+        stats.report(&reported, db_name);
+        *reported = stats;
+    }
+}
+
+impl fmt::Debug for RocksDBStatistics {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("RocksDBStatistics")
+            .finish_non_exhaustive()
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct RocksDBInner {
     db: DB,
     db_name: &'static str,
     cf_names: HashSet<&'static str>,
+    statistics: Option<RocksDBStatistics>,
     _registry_entry: RegistryEntry,
     // Importantly, `Cache`s must be dropped after `DB`, so we place them as the last field
     // (fields in a struct are dropped in the declaration order).
@@ -114,6 +183,25 @@ impl RocksDBInner {
         property
     }
 
+    /// Reports IO statistics every `interval` until the instance is dropped. The remaining
+    /// statistics are reported on drop, so short-lived instances are accounted for as well.
+    fn spawn_io_stats_reporter(this: Weak<Self>, db_name: &'static str, interval: Duration) {
+        let spawn_result = thread::Builder::new()
+            .name(format!("{db_name}_io_stats"))
+            .spawn(move || loop {
+                thread::sleep(interval);
+                let Some(inner) = this.upgrade() else {
+                    break;
+                };
+                if let Some(statistics) = &inner.statistics {
+                    statistics.report(db_name);
+                }
+            });
+        if let Err(err) = spawn_result {
+            olaos_logs::warn!(%err, "Failed spawning IO stats reporter for DB `{db_name}`");
+        }
+    }
+
     /// Waits until writes are not stopped for any of the CFs. Writes can stop immediately on DB initialization
     /// if there are too many level-0 SST files; in this case, it may help waiting several seconds until
     /// these files are compacted.
@@ -142,6 +230,9 @@ impl RocksDBInner {
 impl Drop for RocksDBInner {
     fn drop(&mut self) {
         self.db.cancel_all_background_work(true);
+        if let Some(statistics) = &self.statistics {
+            statistics.report(self.db_name);
+        }
     }
 }
 
@@ -206,6 +297,10 @@ pub struct RocksDBOptions {
     /// Timeout to wait for the database to run compaction on stalled writes during startup or
     /// when the corresponding RocksDB error is encountered.
     pub stalled_writes_retries: StalledWritesRetries,
+    /// Interval between reports of IO statistics (bytes read / written, compaction IO and time)
+    /// of the instance. If not set, statistics are not collected, since they come with a small
+    /// performance penalty.
+    pub io_stats_interval: Option<Duration>,
 }
 
 impl Default for RocksDBOptions {
@@ -214,6 +309,7 @@ impl Default for RocksDBOptions {
             block_cache_capacity: None,
             large_memtable_capacity: None,
             stalled_writes_retries: StalledWritesRetries::new(Duration::from_secs(10)),
+            io_stats_interval: None,
         }
     }
 }
@@ -235,8 +331,12 @@ impl<CF: NamedColumnFamily> RocksDB<CF> {
     }
 
     pub fn with_options(path: &Path, options: RocksDBOptions) -> Self {
+        crate::metrics::describe_metrics();
         let caches = RocksDBCaches::new(options.block_cache_capacity);
-        let db_options = Self::rocksdb_options(None, None);
+        let mut db_options = Self::rocksdb_options(None, None);
+        if options.io_stats_interval.is_some() {
+            db_options.enable_statistics();
+        }
         let existing_cfs = DB::list_cf(&db_options, path).unwrap_or_else(|_err| vec![]);
 
         let cfs_and_options: HashMap<_, _> = CF::ALL
@@ -272,13 +372,20 @@ impl<CF: NamedColumnFamily> RocksDB<CF> {
         });
 
         let db = DB::open_cf_descriptors(&db_options, path, cfs).expect("failed to init rocksdb");
+        let statistics = options
+            .io_stats_interval
+            .map(|_| RocksDBStatistics::new(db_options));
         let inner = Arc::new(RocksDBInner {
             db,
             db_name: CF::DB_NAME,
             cf_names,
+            statistics,
             _registry_entry: RegistryEntry::new(),
             _caches: caches,
         });
+        if let Some(interval) = options.io_stats_interval {
+            RocksDBInner::spawn_io_stats_reporter(Arc::downgrade(&inner), CF::DB_NAME, interval);
+        }
 
         inner.wait_for_writes_to_resume(&options.stalled_writes_retries);
         Self {
@@ -349,6 +456,15 @@ impl<CF: NamedColumnFamily> RocksDB<CF> {
         self.inner.db.batched_multi_get_cf(cf, keys, false)
     }
 
+    /// Creates a consistent point-in-time view of the database. Writes made after the snapshot
+    /// is taken are not visible through it.
+    pub fn snapshot(&self) -> RocksDBSnapshot<'_, CF> {
+        RocksDBSnapshot {
+            inner: self.inner.db.snapshot(),
+            db: self,
+        }
+    }
+
     pub fn new_write_batch(&self) -> WriteBatch<'_, CF> {
         WriteBatch {
             inner: rocksdb::WriteBatch::default(),
@@ -1,5 +1,5 @@
 pub mod db;
 pub mod metrics;
 
-pub use db::{RocksDB, RocksDBOptions, StalledWritesRetries};
+pub use db::{RocksDB, RocksDBOptions, RocksDBSnapshot, StalledWritesRetries};
 pub use rocksdb;
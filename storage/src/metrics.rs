@@ -8,6 +8,7 @@ pub(crate) fn describe_metrics() {
     INITIALIZER.call_once(|| {
         WriteMetrics::describe();
         RocksDBSizeStats::describe();
+        RocksDBIoStats::describe();
     });
 }
 
@@ -72,3 +73,111 @@ impl RocksDBSizeStats {
         );
     }
 }
+
+/// Cumulative IO statistics of a RocksDB instance, parsed from the RocksDB statistics dump.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct RocksDBIoStats {
+    pub bytes_read: u64,
+    pub bytes_written: u64,
+    pub compaction_bytes_read: u64,
+    pub compaction_bytes_written: u64,
+    pub compaction_time_micros: u64,
+}
+
+impl RocksDBIoStats {
+    const BYTES_READ: &'static str = "rocksdb.io.bytes_read";
+    const BYTES_WRITTEN: &'static str = "rocksdb.io.bytes_written";
+    const COMPACTION_BYTES_READ: &'static str = "rocksdb.io.compaction_bytes_read";
+    const COMPACTION_BYTES_WRITTEN: &'static str = "rocksdb.io.compaction_bytes_written";
+    const COMPACTION_TIME: &'static str = "rocksdb.io.compaction_time";
+
+    fn describe() {
+        metrics::describe_counter!(
+            Self::BYTES_READ,
+            Unit::Bytes,
+            "Bytes read by user operations from a RocksDB instance"
+        );
+        metrics::describe_counter!(
+            Self::BYTES_WRITTEN,
+            Unit::Bytes,
+            "Bytes written by user operations to a RocksDB instance"
+        );
+        metrics::describe_counter!(
+            Self::COMPACTION_BYTES_READ,
+            Unit::Bytes,
+            "Bytes read by compactions of a RocksDB instance"
+        );
+        metrics::describe_counter!(
+            Self::COMPACTION_BYTES_WRITTEN,
+            Unit::Bytes,
+            "Bytes written by compactions of a RocksDB instance"
+        );
+        metrics::describe_counter!(
+            Self::COMPACTION_TIME,
+            Unit::Microseconds,
+            "Time spent on compactions of a RocksDB instance"
+        );
+    }
+
+    /// Parses statistics returned by `Options::get_statistics()`. Tickers are formatted
+    /// as `<name> COUNT : <value>`, and histograms as `<name> P50 : <value> ... COUNT : <value> SUM : <value>`.
+    pub fn parse(statistics: &str) -> Self {
+        let mut stats = Self::default();
+        for line in statistics.lines() {
+            let mut tokens = line.split_whitespace();
+            let Some(name) = tokens.next() else {
+                continue;
+            };
+            let field = match name {
+                "rocksdb.bytes.read" => &mut stats.bytes_read,
+                "rocksdb.bytes.written" => &mut stats.bytes_written,
+                "rocksdb.compact.read.bytes" => &mut stats.compaction_bytes_read,
+                "rocksdb.compact.write.bytes" => &mut stats.compaction_bytes_written,
+                "rocksdb.compaction.times.micros" => &mut stats.compaction_time_micros,
+                _ => continue,
+            };
+            // Histograms report the total in `SUM`, tickers in `COUNT`.
+            let total_key = if name.contains(".times.") {
+                "SUM"
+            } else {
+                "COUNT"
+            };
+            let tokens: Vec<_> = tokens.collect();
+            let value = tokens
+                .windows(3)
+                .find(|window| window[0] == total_key && window[1] == ":")
+                .and_then(|window| window[2].parse::<f64>().ok());
+            if let Some(value) = value {
+                *field = value as u64;
+            }
+        }
+        stats
+    }
+
+    /// Reports the increase of statistics since `prev`, so that counters of all instances
+    /// of the same DB add up, even if the instances are reopened.
+    pub fn report(&self, prev: &Self, db_name: &'static str) {
+        let deltas = [
+            (Self::BYTES_READ, self.bytes_read, prev.bytes_read),
+            (Self::BYTES_WRITTEN, self.bytes_written, prev.bytes_written),
+            (
+                Self::COMPACTION_BYTES_READ,
+                self.compaction_bytes_read,
+                prev.compaction_bytes_read,
+            ),
+            (
+                Self::COMPACTION_BYTES_WRITTEN,
+                self.compaction_bytes_written,
+                prev.compaction_bytes_written,
+            ),
+            (
+                Self::COMPACTION_TIME,
+                self.compaction_time_micros,
+                prev.compaction_time_micros,
+            ),
+        ];
+        for (name, value, prev_value) in deltas {
+            metrics::counter!(name, value.saturating_sub(prev_value), "db" => db_name);
+        }
+    }
+}
@@ -0,0 +1,10 @@
+//! Generates Rust types for the protobuf schemas in `proto/`.
+
+fn main() {
+    println!("cargo:rerun-if-changed=proto");
+    // Use the vendored `protoc`, so that building doesn't require protobuf to be installed.
+    let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc is unavailable");
+    std::env::set_var("PROTOC", protoc);
+    prost_build::compile_protos(&["proto/prover_server_api.proto"], &["proto"])
+        .expect("failed compiling protobuf schemas");
+}
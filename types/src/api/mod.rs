@@ -1,12 +1,16 @@
 pub use crate::request::{SerializationTransactionError, TransactionRequest};
+use std::collections::{BTreeMap, HashMap};
+
 use chrono::{DateTime, Utc};
-use ola_basic_types::{Address, Bytes, Index, L1BatchNumber};
+use ola_basic_types::{Address, Bytes, Index, L1BatchNumber, Nonce};
 use ola_contracts::BaseSystemContractsHashes;
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
-use strum::Display;
+use strum::{Display, EnumString};
 use web3::types::{AccessList, H2048, H256, H64, U256, U64};
 
+use crate::proofs::{WitnessInputStageTimings, WitnessStageTimings};
 use crate::protocol_version::ProtocolVersionId;
+use crate::tx::execution_digest::ExecutionDigest;
 use crate::MiniblockNumber;
 
 pub mod en;
@@ -118,6 +122,9 @@ pub struct BlockNumberObject {
 #[serde(rename_all = "camelCase")]
 pub struct BlockHashObject {
     pub block_hash: H256,
+    /// If set, the request must fail if the block is not in the canonical chain.
+    #[serde(default)]
+    pub require_canonical: bool,
 }
 
 /// Helper enum for EIP-1898.
@@ -130,6 +137,17 @@ pub enum BlockIdVariant {
     BlockHashObject(BlockHashObject),
 }
 
+impl BlockIdVariant {
+    /// Returns whether the referenced block must be canonical (`requireCanonical` in EIP-1898).
+    /// Always `false` for blocks referenced by number.
+    pub fn require_canonical(&self) -> bool {
+        match self {
+            Self::BlockHashObject(hash_object) => hash_object.require_canonical,
+            Self::BlockNumber(_) | Self::BlockNumberObject(_) => false,
+        }
+    }
+}
+
 impl From<BlockIdVariant> for BlockId {
     fn from(value: BlockIdVariant) -> BlockId {
         match value {
@@ -181,6 +199,38 @@ pub struct L2ToL1LogProof {
     pub root: H256,
 }
 
+/// Proof that a transaction and its execution status are included in an L1 batch.
+///
+/// The leaf is a [`ReceiptLeaf`](crate::receipts_tree::ReceiptLeaf) built from `txHash`,
+/// `indexInBatch` and `succeeded`; `proof` leads from it to `receiptsRoot`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionInclusionProof {
+    pub tx_hash: H256,
+    pub l1_batch_number: L1BatchNumber,
+    pub index_in_batch: u32,
+    pub succeeded: bool,
+    /// Sibling hashes from the leaf level up to the root.
+    pub proof: Vec<H256>,
+    pub receipts_root: H256,
+    /// Binding of the receipts root into the L1 batch commitment; absent until the batch
+    /// metadata is calculated.
+    pub commitment: Option<ReceiptsRootCommitment>,
+}
+
+/// Hashes linking a receipts root to an L1 batch commitment:
+/// `commitment = hash(passThroughDataHash ‖ metaParametersHash ‖ auxDataHash)`, where
+/// `auxDataHash = hash(initialWritesHash ‖ repeatedWritesHash ‖ receiptsRoot)`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptsRootCommitment {
+    pub initial_writes_hash: H256,
+    pub repeated_writes_hash: H256,
+    pub pass_through_data_hash: H256,
+    pub meta_parameters_hash: H256,
+    pub commitment: H256,
+}
+
 /// A struct with the two default bridge contracts.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -233,8 +283,8 @@ pub struct TransactionReceipt {
     /// Logs generated within this transaction.
     pub logs: Vec<Log>,
     /// L2 to L1 logs generated within this transaction.
-    // #[serde(rename = "l2ToL1Logs")]
-    // pub l2_to_l1_logs: Vec<L2ToL1Log>,
+    #[serde(rename = "l2ToL1Logs", default)]
+    pub l2_to_l1_logs: Vec<L2ToL1Log>,
     /// Status: either 1 (success) or 0 (failure).
     pub status: Option<U64>,
     /// State root.
@@ -250,6 +300,37 @@ pub struct TransactionReceipt {
     pub effective_gas_price: Option<U256>,
 }
 
+impl TransactionReceipt {
+    /// Sets the logs of the receipt together with the logs bloom derived from them.
+    pub fn set_logs(&mut self, logs: Vec<Log>) {
+        self.logs_bloom = logs_bloom(&logs);
+        self.logs = logs;
+    }
+}
+
+/// Computes an Ethereum-compatible bloom filter over addresses and topics of the `logs`.
+pub fn logs_bloom<'a>(logs: impl IntoIterator<Item = &'a Log>) -> H2048 {
+    let mut bloom = H2048::zero();
+    for log in logs {
+        accrue_bloom(&mut bloom, log.address.as_bytes());
+        for topic in &log.topics {
+            accrue_bloom(&mut bloom, topic.as_bytes());
+        }
+    }
+    bloom
+}
+
+fn accrue_bloom(bloom: &mut H2048, input: &[u8]) {
+    const BLOOM_BITS: usize = 2048;
+
+    let hash = web3::signing::keccak256(input);
+    for chunk in hash[..6].chunks(2) {
+        let bit = (usize::from(chunk[0]) << 8 | usize::from(chunk[1])) % BLOOM_BITS;
+        // Bits are numbered from the least significant bit of the big-endian representation.
+        bloom.0[BLOOM_BITS / 8 - 1 - bit / 8] |= 1 << (bit % 8);
+    }
+}
+
 /// The block type returned from RPC calls.
 /// This is generic over a `TX` type.
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -361,6 +442,77 @@ where
     Ok(option.unwrap_or_default())
 }
 
+/// State overrides applied for the duration of a call, keyed by account address.
+/// Follows the format of the optional third parameter of Geth's `eth_call`.
+pub type StateOverride = HashMap<Address, OverrideAccount>;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverrideAccount {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// Replaces the entire account storage; slots not listed read as zero.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<HashMap<H256, H256>>,
+    /// Overrides individual storage slots, keeping the rest of the account storage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_diff: Option<HashMap<H256, H256>>,
+}
+
+/// Output of a call together with its execution statistics, as returned by
+/// `ola_callTransactionWithStats`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallResultWithStats {
+    pub output: Bytes,
+    pub stats: CallStats,
+    /// Set if `output` is truncated to the output size limit of the node.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncated: Option<TruncatedOutput>,
+}
+
+/// Marker of a call output truncated to the output size limit of the node.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TruncatedOutput {
+    /// Size of the full output in bytes.
+    pub full_size: u64,
+    /// Hash of the full output.
+    pub hash: H256,
+    /// Whether the full output can be fetched by `hash` via `ola_getCallOutput`.
+    pub stored: bool,
+}
+
+/// Execution statistics of a call.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallStats {
+    /// Time spent executing the call, excluding waiting for a VM permit.
+    pub time_spent_ms: u64,
+    /// Number of storage slot reads.
+    pub storage_reads: u64,
+    /// Number of reads which weren't served from the storage cache.
+    pub missed_storage_invocations: u64,
+    /// Number of VM cycles taken by the call.
+    pub cycles: u64,
+}
+
+/// Fee history of a range of miniblocks, as returned by `eth_feeHistory`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeHistory {
+    pub oldest_block: U64,
+    /// Base fees of the miniblocks in the range, followed by the base fee of the next miniblock.
+    pub base_fee_per_gas: Vec<U256>,
+    pub gas_used_ratio: Vec<f64>,
+    /// Priority fees at the requested percentiles, for each miniblock in the range.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reward: Option<Vec<Vec<U256>>>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TransactionDetails {
@@ -373,6 +525,9 @@ pub struct TransactionDetails {
     pub eth_commit_tx_hash: Option<H256>,
     pub eth_prove_tx_hash: Option<H256>,
     pub eth_execute_tx_hash: Option<H256>,
+    /// Error the transaction was rejected or reverted with, if any.
+    #[serde(default)]
+    pub revert_reason: Option<String>,
 }
 
 /// A log produced by a transaction.
@@ -531,11 +686,458 @@ pub struct Transaction {
 #[serde(rename_all = "camelCase")]
 pub enum TransactionStatus {
     Pending,
+    /// The transaction is held in the mempool until its `execute_after` timestamp.
+    Scheduled,
     Included,
     Verified,
     Failed,
 }
 
+/// Reason a transaction was removed from the mempool without being executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DropReason {
+    /// The transaction stayed in the mempool for longer than the stuck transaction timeout.
+    Stuck,
+    /// The transaction was replaced by another transaction with the same initiator and nonce.
+    Replaced,
+}
+
+impl DropReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Stuck => "stuck",
+            Self::Replaced => "replaced",
+        }
+    }
+}
+
+impl std::str::FromStr for DropReason {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stuck" => Ok(Self::Stuck),
+            "replaced" => Ok(Self::Replaced),
+            other => Err(format!("{other} is not a valid drop reason")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DroppedTransaction {
+    pub reason: DropReason,
+    /// Hash of the replacing transaction, if the transaction was replaced.
+    pub replaced_by: Option<H256>,
+    pub dropped_at: DateTime<Utc>,
+}
+
+/// Status of a transaction returned by `ola_getTransactionStatus`. Exactly one of `status`
+/// and `dropped` is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionStatusInfo {
+    pub hash: H256,
+    /// Status of a transaction that isn't dropped.
+    pub status: Option<TransactionStatus>,
+    /// Error the transaction was rejected or reverted with, if any.
+    pub error: Option<String>,
+    pub dropped: Option<DroppedTransaction>,
+    /// Comparison of the simulated and actual execution; only set for transactions submitted
+    /// via `ola_sendRawTransactionWithSimulation`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub simulation: Option<TransactionSimulationDiff>,
+}
+
+/// Effects of a transaction simulated at submission compared to its actual execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionSimulationDiff {
+    pub simulated: ExecutionDigest,
+    /// `None` until the miniblock including the transaction is sealed.
+    pub actual: Option<ExecutionDigest>,
+    /// Whether storage writes or events of the actual execution differ from the simulated ones.
+    pub diverged: Option<bool>,
+}
+
+impl TransactionSimulationDiff {
+    pub fn new(simulated: ExecutionDigest, actual: Option<ExecutionDigest>) -> Self {
+        Self {
+            simulated,
+            actual,
+            diverged: actual.map(|actual| actual != simulated),
+        }
+    }
+}
+
+/// Value of a storage slot as of the end of a miniblock that changed it, returned
+/// by `ola_getStorageHistory`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageValueChange {
+    pub miniblock_number: MiniblockNumber,
+    /// Last transaction in the miniblock that wrote the slot.
+    pub tx_hash: H256,
+    pub value: H256,
+}
+
+/// Non-zero storage slot of an account, returned by `ola_getAccountStorageRange`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StorageEntry {
+    pub key: H256,
+    pub value: H256,
+}
+
+/// Revert of the chain to an earlier L1 batch. Entities removed by a revert are kept in the revert
+/// log, so that they can still be displayed as reorged out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevertInfo {
+    pub id: u64,
+    pub last_kept_l1_batch: L1BatchNumber,
+    pub last_kept_miniblock: MiniblockNumber,
+    pub reason: String,
+    pub reverted_at: DateTime<Utc>,
+}
+
+/// Miniblock removed from the chain by a revert, as returned by `ola_getReorgedOutBlocks`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReorgedOutBlock {
+    pub number: MiniblockNumber,
+    pub hash: H256,
+    pub l1_batch_number: Option<L1BatchNumber>,
+    pub timestamp: u64,
+    /// Hashes of the transactions the miniblock contained, ordered by their index in the block.
+    pub transactions: Vec<H256>,
+    pub revert: RevertInfo,
+}
+
+/// Inclusion of a transaction removed from the chain by a revert, as returned by
+/// `ola_getReorgedOutTransaction`. Reverted transactions are returned to the mempool, so the
+/// transaction may be included again afterwards.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReorgedOutTransaction {
+    pub hash: H256,
+    pub miniblock_number: MiniblockNumber,
+    pub l1_batch_number: Option<L1BatchNumber>,
+    pub index_in_block: Option<u32>,
+    pub error: Option<String>,
+    pub revert: RevertInfo,
+}
+
+/// Processing stage of an L1 batch, as reported by the `batches` subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum L1BatchStatus {
+    /// The batch is sealed by the sequencer.
+    Sealed,
+    /// The batch metadata (state root and commitment) is calculated.
+    Committed,
+    /// The batch proof is generated.
+    Proven,
+    /// The batch proof passed off-chain verification.
+    Executed,
+}
+
+impl L1BatchStatus {
+    pub const ALL: [Self; 4] = [Self::Sealed, Self::Committed, Self::Proven, Self::Executed];
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1BatchStatusUpdate {
+    pub l1_batch_number: L1BatchNumber,
+    pub status: L1BatchStatus,
+}
+
+/// State of the API VM concurrency limit, as returned by `ola_getVmConcurrency`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VmConcurrencyStatus {
+    /// Current effective limit.
+    pub limit: usize,
+    pub min_limit: usize,
+    pub max_limit: usize,
+    pub autotune_enabled: bool,
+    /// Number of VM invocations currently waiting for a permit.
+    pub queue_depth: usize,
+    /// Recent adjustments of the limit, oldest first.
+    pub history: Vec<VmConcurrencyAdjustment>,
+}
+
+/// Adjustment of the VM concurrency limit made by the autotuner.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VmConcurrencyAdjustment {
+    /// Unix timestamp of the adjustment, in seconds.
+    pub timestamp: u64,
+    pub from: usize,
+    pub to: usize,
+    pub reason: VmConcurrencyAdjustmentReason,
+    pub p95_execution_ms: Option<u64>,
+    pub cache_miss_ratio: Option<f64>,
+    pub peak_queue_depth: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum VmConcurrencyAdjustmentReason {
+    /// p95 execution time exceeded the target.
+    SlowExecution,
+    /// Storage cache miss ratio exceeded the limit.
+    CacheMisses,
+    /// Invocations queued for permits while execution was fast enough.
+    QueueBacklog,
+}
+
+/// Rules checked against transaction calldata before mempool admission, as returned by
+/// `ola_getCalldataScreeningRules`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalldataScreeningRules {
+    /// Calldata size cap stricter than the one from the node configuration, in bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_calldata_size: Option<usize>,
+    #[serde(default)]
+    pub forbidden_selectors: Vec<ForbiddenSelector>,
+    #[serde(default)]
+    pub exploit_signatures: Vec<ExploitSignature>,
+}
+
+/// Function selector that must not be called, either on any contract or on a single one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForbiddenSelector {
+    pub selector: U64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract: Option<Address>,
+}
+
+/// Byte pattern of a known exploit; calldata containing it is rejected.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExploitSignature {
+    /// Name reported in rejection errors and metrics.
+    pub name: String,
+    pub pattern: Bytes,
+}
+
+impl VmConcurrencyAdjustmentReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::SlowExecution => "slow_execution",
+            Self::CacheMisses => "cache_misses",
+            Self::QueueBacklog => "queue_backlog",
+        }
+    }
+}
+
+/// Snapshot of the sequencer mempool, as returned by `ola_getMempoolStatus`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MempoolStatus {
+    /// Number of L2 transactions currently held in the mempool.
+    pub total_transactions: u64,
+    /// Number of held transactions which cannot be executed before their `execute_after` timestamp.
+    #[serde(default)]
+    pub scheduled_transactions: u64,
+    pub capacity: u64,
+    /// Age of the oldest transaction in the mempool, in milliseconds.
+    pub oldest_transaction_age_ms: Option<u64>,
+    /// Accounts with the most pending transactions, up to the requested limit.
+    pub accounts: Vec<MempoolAccountStatus>,
+    /// Whether some accounts were left out of `accounts` because of the limit.
+    #[serde(default)]
+    pub accounts_truncated: bool,
+    /// Transactions that will be handed to the sequencer next, in inclusion order.
+    pub next_transactions: Vec<MempoolTransactionInfo>,
+    /// Skipped accounts ordered by address, up to the requested limit.
+    pub skipped_accounts: Vec<MempoolSkippedAccount>,
+    /// Whether some accounts were left out of `skipped_accounts` because of the limit.
+    #[serde(default)]
+    pub skipped_accounts_truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MempoolAccountStatus {
+    pub address: Address,
+    /// Nonce of the next transaction of the account the sequencer expects.
+    pub next_nonce: Nonce,
+    pub pending_transactions: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MempoolTransactionInfo {
+    pub hash: H256,
+    pub initiator_address: Address,
+    pub nonce: Nonce,
+    pub received_at_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MempoolSkippedAccount {
+    pub address: Address,
+    pub reason: MempoolSkipReason,
+    /// Nonce the sequencer expects; unknown for accounts stashed by the policy filter.
+    pub next_nonce: Option<Nonce>,
+    /// Lowest nonce among the pending transactions of the account.
+    pub lowest_pending_nonce: Option<Nonce>,
+    /// Timestamp after which the next transaction of the account can be executed; only set
+    /// for scheduled transactions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execute_after: Option<u64>,
+}
+
+/// Reason why transactions of an account are currently not considered for inclusion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MempoolSkipReason {
+    /// None of the pending transactions has the next expected nonce.
+    NonceGap,
+    /// Transactions were stashed by the mempool filter and await reloading from Postgres.
+    FilteredByPolicy,
+    /// The next transaction of the account is scheduled for a later timestamp.
+    Scheduled,
+}
+
+/// Mempool transactions grouped by sender and nonce, as returned by `txpool_content`.
+/// Follows the layout of the Ethereum method, so that existing dashboards can consume it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TxpoolContent {
+    /// Transactions executable one after another starting from the expected account nonce.
+    pub pending: BTreeMap<Address, BTreeMap<Nonce, TxpoolTransaction>>,
+    /// Transactions waiting for a missing nonce.
+    pub queued: BTreeMap<Address, BTreeMap<Nonce, TxpoolTransaction>>,
+    /// Whether some transactions were left out because the response size is capped.
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxpoolTransaction {
+    pub hash: H256,
+    pub nonce: Nonce,
+    pub from: Address,
+    pub to: Address,
+    pub input: Bytes,
+    pub received_at_ms: u64,
+}
+
+/// Operator-affecting action recorded in the audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum AuditAction {
+    ConfigReload,
+    Rollback,
+    /// Transactions of an L1 batch are moved from Postgres to the archive.
+    Pruning,
+    /// The sequencer opens the first L1 batch of a new protocol version.
+    ProtocolUpgrade,
+    /// Rotation of the operator address is requested.
+    OperatorRotation,
+}
+
+/// Entry of the audit log, as returned by `ola_getAuditLog`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogEntry {
+    /// Position of the entry; entries are numbered in the order they are recorded.
+    pub id: u64,
+    pub timestamp: DateTime<Utc>,
+    pub action: AuditAction,
+    /// Who triggered the action, e.g. `cli:<user>`, or `system` for background tasks.
+    pub actor: String,
+    pub parameters: serde_json::Value,
+}
+
+/// System contract deployed in a protocol version, as returned by `ola_getSystemContracts`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemContract {
+    pub name: String,
+    pub address: Address,
+    pub since_version: u16,
+    pub until_version: Option<u16>,
+}
+
+impl From<&crate::system_contracts::SystemContractInfo> for SystemContract {
+    fn from(info: &crate::system_contracts::SystemContractInfo) -> Self {
+        Self {
+            name: info.name.to_owned(),
+            address: info.address,
+            since_version: info.since_version as u16,
+            until_version: info.until_version.map(|version| version as u16),
+        }
+    }
+}
+
+/// Token registered on deployment, as returned by `ola_getTokenInfo`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenDetails {
+    pub address: Address,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    /// Miniblock the token contract was deployed in.
+    pub deployed_in_block: MiniblockNumber,
+}
+
+/// Balance of an account in a token, as returned by `ola_getTokenBalances`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBalance {
+    pub token: Address,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub balance: U256,
+}
+
+/// Resource consumption of a contract over a time range, as returned by `ola_getTopContracts`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractResourceUsage {
+    pub address: Address,
+    /// Number of transactions calling the contract.
+    pub tx_count: u64,
+    pub cycles_used: u64,
+    pub storage_reads: u64,
+    pub storage_writes: u64,
+    pub events: u64,
+}
+
+/// Resource by which `ola_getTopContracts` ranks contracts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ContractResourceKind {
+    Transactions,
+    Cycles,
+    /// Storage reads and writes combined.
+    #[default]
+    StorageOps,
+    Events,
+}
+
+impl ContractResourceKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Transactions => "transactions",
+            Self::Cycles => "cycles",
+            Self::StorageOps => "storage_ops",
+            Self::Events => "events",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GetLogsFilter {
     pub from_block: MiniblockNumber,
@@ -674,6 +1276,10 @@ pub struct L1BatchDetails {
     pub number: L1BatchNumber,
     #[serde(flatten)]
     pub base: BlockDetailsBase,
+    /// Per-stage timings of producing witness inputs; `None` until the inputs are produced.
+    pub witness_input_stage_timings: Option<WitnessInputStageTimings>,
+    /// Per-stage timings of basic witness generation; `None` until the witness is generated.
+    pub witness_stage_timings: Option<WitnessStageTimings>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -691,3 +1297,253 @@ pub struct Proof {
     pub address: Address,
     pub storage_proof: Vec<StorageProof>,
 }
+
+/// Storage entries sampled by `ola_sampleStorageProofs`. Entries are proven against `root_hash`;
+/// their `key`s are hashed storage keys.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageSample {
+    pub seed: u64,
+    /// Number of L1 batches processed by the tree at the time of reading.
+    pub l1_batch_count: L1BatchNumber,
+    pub root_hash: H256,
+    pub entries: Vec<StorageProof>,
+}
+
+/// Identity of a node and its build, returned by `ola_nodeInfo`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeInfo {
+    /// Semantic version of the node.
+    pub version: String,
+    /// Git commit the node was built from, or `unknown`.
+    pub git_commit: String,
+    /// Cargo profile of the build, e.g. `release`.
+    pub build_profile: String,
+    /// Cargo features enabled for the build.
+    pub features: Vec<String>,
+    /// Components run by the node, e.g. `HttpApi` or `Sequencer`.
+    pub components: Vec<String>,
+    /// ID of the latest protocol version known to the node.
+    pub protocol_version: u16,
+    /// L2 chain ID.
+    pub chain_id: U64,
+    /// Hash of the genesis L1 batch; `None` if it isn't computed yet.
+    pub genesis_hash: Option<H256>,
+}
+
+/// State of an account, returned by `ola_getAccountsInfo`. Ola has no native balances,
+/// so there's no balance field.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountInfo {
+    pub address: Address,
+    pub nonce: U256,
+    /// Hash of the contract bytecode deployed at the address; zero if the address has no code.
+    pub code_hash: H256,
+}
+
+/// Sync progress of the node. Besides the standard fields, it reports the progress of
+/// the Merkle tree, since state proofs and L1 batch commitments lag behind sealed batches.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncInfo {
+    /// First locally available miniblock.
+    pub starting_block: U64,
+    /// Latest sealed local miniblock.
+    pub current_block: U64,
+    /// Latest miniblock known to the node: the one of the main node for external nodes,
+    /// or the local one otherwise.
+    pub highest_block: U64,
+    pub last_sealed_l1_batch: L1BatchNumber,
+    /// Latest L1 batch processed by the Merkle tree.
+    pub tree_l1_batch: L1BatchNumber,
+    /// Whether the Merkle tree keeps up with sealed L1 batches, i.e., state proofs and L1 batch
+    /// commitments are available for the served state. The node may still lag behind
+    /// the main node.
+    pub consistent_state: bool,
+}
+
+/// Result of `eth_syncing`: `false` if the node has all known miniblocks and serves
+/// a consistent state, or the sync progress otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyncState {
+    Syncing(SyncInfo),
+    NotSyncing,
+}
+
+impl Serialize for SyncState {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            SyncState::Syncing(info) => info.serialize(serializer),
+            SyncState::NotSyncing => serializer.serialize_bool(false),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SyncState {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum SerializedSyncState {
+            Syncing(SyncInfo),
+            NotSyncing(bool),
+        }
+
+        match SerializedSyncState::deserialize(deserializer)? {
+            SerializedSyncState::Syncing(info) => Ok(SyncState::Syncing(info)),
+            SerializedSyncState::NotSyncing(false) => Ok(SyncState::NotSyncing),
+            SerializedSyncState::NotSyncing(true) => Err(de::Error::custom(
+                "`true` is not a valid sync state; expected `false` or sync info",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_log(address: H256, topics: Vec<H256>) -> Log {
+        Log {
+            address,
+            topics,
+            data: Bytes::default(),
+            block_hash: None,
+            block_number: None,
+            l1_batch_number: None,
+            transaction_hash: None,
+            transaction_index: None,
+            log_index: None,
+            transaction_log_index: None,
+            log_type: None,
+            removed: None,
+        }
+    }
+
+    #[test]
+    fn computing_logs_bloom() {
+        assert_eq!(logs_bloom(&[]), H2048::zero());
+
+        let log = test_log(H256::repeat_byte(1), vec![H256::repeat_byte(2)]);
+        let bloom = logs_bloom([&log]);
+        let set_bits: u32 = bloom.0.iter().map(|byte| byte.count_ones()).sum();
+        assert!(set_bits > 0 && set_bits <= 6);
+        // Duplicate logs do not change the filter.
+        assert_eq!(logs_bloom([&log, &log]), bloom);
+
+        let other_log = test_log(H256::repeat_byte(3), vec![]);
+        let combined = logs_bloom([&log, &other_log]);
+        for (combined_byte, byte) in combined.0.iter().zip(&bloom.0) {
+            assert_eq!(combined_byte & byte, *byte);
+        }
+    }
+
+    #[test]
+    fn deserializing_block_id_variant() {
+        let variant: BlockIdVariant = serde_json::from_str(r#""latest""#).unwrap();
+        assert_eq!(variant, BlockIdVariant::BlockNumber(BlockNumber::Latest));
+        assert!(!variant.require_canonical());
+
+        let variant: BlockIdVariant = serde_json::from_str(r#""0x10""#).unwrap();
+        assert_eq!(variant, BlockIdVariant::BlockNumber(16_u64.into()));
+
+        let variant: BlockIdVariant = serde_json::from_str(r#"{ "blockNumber": "0x10" }"#).unwrap();
+        assert_eq!(BlockId::from(variant), BlockId::Number(16_u64.into()));
+        assert!(!variant.require_canonical());
+
+        let hash = H256::repeat_byte(0x42);
+        let json = format!(r#"{{ "blockHash": "{hash:?}" }}"#);
+        let variant: BlockIdVariant = serde_json::from_str(&json).unwrap();
+        assert_eq!(BlockId::from(variant), BlockId::Hash(hash));
+        assert!(!variant.require_canonical());
+
+        let json = format!(r#"{{ "blockHash": "{hash:?}", "requireCanonical": true }}"#);
+        let variant: BlockIdVariant = serde_json::from_str(&json).unwrap();
+        assert_eq!(BlockId::from(variant), BlockId::Hash(hash));
+        assert!(variant.require_canonical());
+    }
+
+    #[test]
+    fn serializing_call_stats() {
+        let stats = CallStats {
+            time_spent_ms: 12,
+            storage_reads: 5,
+            missed_storage_invocations: 2,
+            cycles: 1_000,
+        };
+        assert_eq!(
+            serde_json::to_value(&stats).unwrap(),
+            serde_json::json!({
+                "timeSpentMs": 12,
+                "storageReads": 5,
+                "missedStorageInvocations": 2,
+                "cycles": 1_000,
+            })
+        );
+    }
+
+    #[test]
+    fn serializing_fee_history() {
+        let history = FeeHistory {
+            oldest_block: 5.into(),
+            base_fee_per_gas: vec![U256::zero(); 3],
+            gas_used_ratio: vec![0.0; 2],
+            reward: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&history).unwrap(),
+            serde_json::json!({
+                "oldestBlock": "0x5",
+                "baseFeePerGas": ["0x0", "0x0", "0x0"],
+                "gasUsedRatio": [0.0, 0.0],
+            })
+        );
+    }
+
+    #[test]
+    fn audit_action_names() {
+        let action = AuditAction::ProtocolUpgrade;
+        assert_eq!(action.to_string(), "protocol_upgrade");
+        assert_eq!("protocol_upgrade".parse::<AuditAction>().unwrap(), action);
+        assert_eq!(
+            serde_json::to_string(&action).unwrap(),
+            r#""protocol_upgrade""#
+        );
+        assert!("unknown".parse::<AuditAction>().is_err());
+    }
+
+    #[test]
+    fn serializing_sync_state() {
+        let state = SyncState::NotSyncing;
+        assert_eq!(
+            serde_json::to_value(&state).unwrap(),
+            serde_json::json!(false)
+        );
+        assert_eq!(
+            serde_json::from_value::<SyncState>(serde_json::json!(false)).unwrap(),
+            state
+        );
+        assert!(serde_json::from_value::<SyncState>(serde_json::json!(true)).is_err());
+
+        let state = SyncState::Syncing(SyncInfo {
+            starting_block: 0.into(),
+            current_block: 10.into(),
+            highest_block: 12.into(),
+            last_sealed_l1_batch: L1BatchNumber(3),
+            tree_l1_batch: L1BatchNumber(3),
+            consistent_state: false,
+        });
+        let json = serde_json::to_value(&state).unwrap();
+        assert_eq!(json["currentBlock"], "0xa");
+        assert_eq!(json["highestBlock"], "0xc");
+        assert_eq!(json["consistentState"], false);
+        assert_eq!(serde_json::from_value::<SyncState>(json).unwrap(), state);
+    }
+}
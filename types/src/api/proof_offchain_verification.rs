@@ -1,6 +1,7 @@
 use super::BlockDetailsBase;
 use chrono::{DateTime, Utc};
-use ola_basic_types::L1BatchNumber;
+use ola_basic_types::{Address, Bytes, L1BatchNumber, L2ChainId, H256};
+use ola_utils::hash::hash_bytes;
 use serde::{Deserialize, Serialize};
 
 #[derive(Default, Serialize, Deserialize, Clone, Debug)]
@@ -28,3 +29,63 @@ pub struct L1BatchDetailsWithOffchainVerification {
     #[serde(flatten)]
     pub offchain_verification: OffChainVerificationDetails,
 }
+
+/// L1 batch pushed to offchain verifiers by the `verificationTasks` subscription.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1BatchVerificationTask {
+    pub l1_batch_number: L1BatchNumber,
+    pub commitment: H256,
+    pub root_hash: H256,
+    /// Serialized proof of the batch; `None` if the node has no access to the proof store.
+    pub proof: Option<Bytes>,
+}
+
+/// Signed verdict of a committee member on the proof of an L1 batch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifierAttestation {
+    pub l1_batch_number: L1BatchNumber,
+    pub commitment: H256,
+    pub is_valid: bool,
+    pub verifier: Address,
+    /// Packed 64-byte signature of [`Self::signed_message()`] for the chain ID of the node.
+    pub signature: Bytes,
+}
+
+impl VerifierAttestation {
+    /// Domain separator of attestation messages, so that an attestation signature can't be
+    /// reused as a signature of any other message signed with the verifier key.
+    pub const DOMAIN_SEPARATOR: &'static [u8] = b"OlaOS offchain verifier attestation v1";
+
+    /// Returns the hash signed by the verifier. The chain ID is included so that an attestation
+    /// can't be replayed on another chain (e.g., a fork sharing the committee), and the commitment
+    /// is included so that it can't be replayed for a reverted and re-sealed batch.
+    pub fn signed_message(&self, chain_id: L2ChainId) -> H256 {
+        let mut bytes = Vec::with_capacity(Self::DOMAIN_SEPARATOR.len() + 2 + 4 + 32 + 1);
+        bytes.extend_from_slice(Self::DOMAIN_SEPARATOR);
+        bytes.extend_from_slice(&chain_id.0.to_be_bytes());
+        bytes.extend_from_slice(&self.l1_batch_number.0.to_be_bytes());
+        bytes.extend_from_slice(self.commitment.as_bytes());
+        bytes.push(self.is_valid as u8);
+        hash_bytes(&bytes)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationInfo {
+    pub verifier: Address,
+    pub is_valid: bool,
+    pub attested_at: DateTime<Utc>,
+}
+
+/// Attestations collected for an L1 batch, as returned by `verifier_getL1BatchAttestations`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1BatchAttestations {
+    pub l1_batch_number: L1BatchNumber,
+    pub quorum_threshold: usize,
+    pub verifier_status: String,
+    pub attestations: Vec<AttestationInfo>,
+}
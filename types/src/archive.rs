@@ -0,0 +1,36 @@
+//! Transactions and events of an L1 batch moved from Postgres to the object store.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::{Transaction, TransactionDetails, TransactionReceipt},
+    L1BatchNumber, H256,
+};
+
+/// API views of an archived transaction, so that the API can serve it without Postgres.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedTransaction {
+    pub hash: H256,
+    pub transaction: Option<Transaction>,
+    pub receipt: Option<TransactionReceipt>,
+    pub details: Option<TransactionDetails>,
+}
+
+/// Archive segment holding all transactions and events of an L1 batch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionArchiveSegment {
+    pub l1_batch_number: L1BatchNumber,
+    pub transactions: Vec<ArchivedTransaction>,
+    /// Rows of the `transactions` table in the Postgres JSON representation, used to restore them.
+    pub transaction_rows: Vec<serde_json::Value>,
+    /// Rows of the `events` table in the Postgres JSON representation.
+    pub event_rows: Vec<serde_json::Value>,
+    /// Rows of the `call_traces` table in the Postgres JSON representation.
+    pub call_trace_rows: Vec<serde_json::Value>,
+}
+
+impl TransactionArchiveSegment {
+    pub fn transaction(&self, hash: H256) -> Option<&ArchivedTransaction> {
+        self.transactions.iter().find(|tx| tx.hash == hash)
+    }
+}
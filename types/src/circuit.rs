@@ -20,6 +20,8 @@ pub struct GeometryConfig {
     // pub limit_for_l1_messages_merklizer: u32,
     pub limit_for_initial_writes_pubdata_hasher: u32,
     pub limit_for_repeated_writes_pubdata_hasher: u32,
+    /// Maximum number of VM cycles in a batch, bounded by the main trace table of the batch proof.
+    pub limit_for_cpu_cycles: u32,
 }
 
 pub const fn get_geometry_config() -> GeometryConfig {
@@ -35,6 +37,7 @@ pub const fn get_geometry_config() -> GeometryConfig {
         // cycles_per_storage_application: 118,
         limit_for_initial_writes_pubdata_hasher: 4765,
         limit_for_repeated_writes_pubdata_hasher: 7564,
+        limit_for_cpu_cycles: 1 << 22,
         // cycles_per_keccak256_circuit: 2141,
         // cycles_per_sha256_circuit: 11812,
         // cycles_per_ecrecover_circuit: 72,
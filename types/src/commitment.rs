@@ -1,9 +1,9 @@
 use std::collections::HashMap;
 
-use crate::block::L1BatchHeader;
+use crate::{block::L1BatchHeader, protocol_version::ProtocolVersionId};
 
 use super::storage::writes::{InitialStorageWrite, RepeatedStorageWrite};
-use ola_basic_types::H256;
+use ola_basic_types::{Address, H256};
 use ola_config::constants::contracts::KNOWN_CODES_STORAGE_ADDRESS;
 use ola_utils::hash::hash_bytes;
 use serde::{Deserialize, Serialize};
@@ -164,12 +164,16 @@ struct L1BatchAuxiliaryOutput {
     initial_writes_hash: H256,
     repeated_writes_compressed: Vec<u8>,
     repeated_writes_hash: H256,
+    /// Root of the [receipts tree](crate::receipts_tree) of the batch. Only committed to since
+    /// [`ProtocolVersionId::Version1`].
+    receipts_root: H256,
 }
 
 impl L1BatchAuxiliaryOutput {
     fn new(
         initial_writes: Vec<InitialStorageWrite>,
         repeated_writes: Vec<RepeatedStorageWrite>,
+        receipts_root: H256,
     ) -> Self {
         let initial_writes_compressed = serialize_commitments(&initial_writes);
         let repeated_writes_compressed = serialize_commitments(&repeated_writes);
@@ -184,20 +188,24 @@ impl L1BatchAuxiliaryOutput {
             repeated_writes,
             initial_writes_hash,
             repeated_writes_hash,
+            receipts_root,
         }
     }
 
-    pub fn to_bytes(&self) -> Vec<u8> {
-        // 2 H256 values
-        const SERIALIZED_SIZE: usize = 64;
+    pub fn to_bytes(&self, protocol_version: ProtocolVersionId) -> Vec<u8> {
+        // 3 H256 values
+        const SERIALIZED_SIZE: usize = 96;
         let mut result = Vec::with_capacity(SERIALIZED_SIZE);
         result.extend(self.initial_writes_hash.as_bytes());
         result.extend(self.repeated_writes_hash.as_bytes());
+        if protocol_version.commits_to_receipts_root() {
+            result.extend(self.receipts_root.as_bytes());
+        }
         result
     }
 
-    pub fn hash(&self) -> H256 {
-        hash_bytes(&self.to_bytes())
+    pub fn hash(&self, protocol_version: ProtocolVersionId) -> H256 {
+        hash_bytes(&self.to_bytes(protocol_version))
     }
 }
 
@@ -205,19 +213,26 @@ impl L1BatchAuxiliaryOutput {
 pub struct L1BatchMetaParameters {
     pub bootloader_code_hash: H256,
     pub default_aa_code_hash: H256,
+    /// Operator (fee account) of the L1 batch; it can be rotated between L1 batches,
+    /// so L1 verifies it as a part of the commitment. Only committed to since
+    /// [`ProtocolVersionId::Version1`].
+    pub operator_address: Address,
 }
 
 impl L1BatchMetaParameters {
-    pub fn to_bytes(&self) -> Vec<u8> {
-        const SERIALIZED_SIZE: usize = 4 + 32 + 32;
+    pub fn to_bytes(&self, protocol_version: ProtocolVersionId) -> Vec<u8> {
+        const SERIALIZED_SIZE: usize = 32 + 32 + 32;
         let mut result = Vec::with_capacity(SERIALIZED_SIZE);
         result.extend(self.bootloader_code_hash.as_bytes());
         result.extend(self.default_aa_code_hash.as_bytes());
+        if protocol_version.commits_to_operator_address() {
+            result.extend(self.operator_address.as_bytes());
+        }
         result
     }
 
-    pub fn hash(&self) -> H256 {
-        hash_bytes(&self.to_bytes())
+    pub fn hash(&self, protocol_version: ProtocolVersionId) -> H256 {
+        hash_bytes(&self.to_bytes(protocol_version))
     }
 }
 
@@ -226,6 +241,7 @@ pub struct L1BatchCommitment {
     pass_through_data: L1BatchPassThroughData,
     auxiliary_output: L1BatchAuxiliaryOutput,
     meta_parameters: L1BatchMetaParameters,
+    protocol_version: ProtocolVersionId,
 }
 
 #[derive(Debug, Clone)]
@@ -242,12 +258,16 @@ impl L1BatchCommitment {
         rollup_root_hash: H256,
         initial_writes: Vec<InitialStorageWrite>,
         repeated_writes: Vec<RepeatedStorageWrite>,
+        receipts_root: H256,
         bootloader_code_hash: H256,
         default_aa_code_hash: H256,
+        operator_address: Address,
+        protocol_version: ProtocolVersionId,
     ) -> Self {
         let meta_parameters = L1BatchMetaParameters {
             bootloader_code_hash,
             default_aa_code_hash,
+            operator_address,
         };
 
         Self {
@@ -257,8 +277,13 @@ impl L1BatchCommitment {
                     root_hash: rollup_root_hash,
                 }],
             },
-            auxiliary_output: L1BatchAuxiliaryOutput::new(initial_writes, repeated_writes),
+            auxiliary_output: L1BatchAuxiliaryOutput::new(
+                initial_writes,
+                repeated_writes,
+                receipts_root,
+            ),
             meta_parameters,
+            protocol_version,
         }
     }
 
@@ -266,9 +291,9 @@ impl L1BatchCommitment {
         let mut result = vec![];
         let pass_through_data_hash = self.pass_through_data.hash();
         result.extend_from_slice(pass_through_data_hash.as_bytes());
-        let metadata_hash = self.meta_parameters.hash();
+        let metadata_hash = self.meta_parameters.hash(self.protocol_version);
         result.extend_from_slice(metadata_hash.as_bytes());
-        let auxiliary_output_hash = self.auxiliary_output.hash();
+        let auxiliary_output_hash = self.auxiliary_output.hash(self.protocol_version);
         result.extend_from_slice(auxiliary_output_hash.as_bytes());
         let commitment = hash_bytes(&result);
         L1BatchCommitmentHash {
@@ -290,4 +315,46 @@ impl L1BatchCommitment {
     pub fn repeated_writes_compressed(&self) -> &[u8] {
         &self.auxiliary_output.repeated_writes_compressed
     }
+
+    pub fn receipts_root(&self) -> H256 {
+        self.auxiliary_output.receipts_root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operator_address_is_committed_to_since_version1() {
+        let meta_parameters = L1BatchMetaParameters {
+            bootloader_code_hash: H256::repeat_byte(1),
+            default_aa_code_hash: H256::repeat_byte(2),
+            operator_address: Address::repeat_byte(3),
+        };
+
+        // Batches of older protocol versions must keep their commitments.
+        let old_bytes = meta_parameters.to_bytes(ProtocolVersionId::Version0);
+        assert_eq!(old_bytes, [[1_u8; 32], [2; 32]].concat());
+        let new_bytes = meta_parameters.to_bytes(ProtocolVersionId::Version1);
+        assert_eq!(new_bytes, [[1_u8; 32], [2; 32], [3; 32]].concat());
+    }
+
+    #[test]
+    fn receipts_root_is_committed_to_since_version1() {
+        let aux_output = L1BatchAuxiliaryOutput::new(vec![], vec![], H256::repeat_byte(3));
+        // Hashes of the serialized empty write lists, i.e. `[0_u8; 4]`.
+        let empty_writes_hash = hash_bytes(&[0_u8; 4]);
+        let old_format = [empty_writes_hash.0, empty_writes_hash.0].concat();
+
+        // Batches of older protocol versions must keep their commitments.
+        let old_bytes = aux_output.to_bytes(ProtocolVersionId::Version0);
+        assert_eq!(old_bytes, old_format);
+        assert_eq!(
+            aux_output.hash(ProtocolVersionId::Version0),
+            hash_bytes(&old_format)
+        );
+        let new_bytes = aux_output.to_bytes(ProtocolVersionId::Version1);
+        assert_eq!(new_bytes, [old_format, vec![3; 32]].concat());
+    }
 }
@@ -53,6 +53,7 @@ impl L2Tx {
                 signature: Default::default(),
                 transaction_type: TransactionType::OlaRawTransaction,
                 input: None,
+                execute_after: None,
             },
             received_timestamp_ms: unix_timestamp_ms(),
         }
@@ -99,6 +100,10 @@ pub struct L2TxCommonData {
     pub signature: Vec<u8>,
     pub transaction_type: TransactionType,
     pub input: Option<InputData>,
+    /// UNIX timestamp (in seconds) before which the transaction is held in the mempool. Unlike
+    /// other fields, it is not covered by the signature and is set when the transaction is submitted.
+    #[serde(default)]
+    pub execute_after: Option<u64>,
 }
 
 impl L2TxCommonData {
@@ -117,9 +122,16 @@ impl L2TxCommonData {
             signature,
             transaction_type,
             input,
+            execute_after: None,
         }
     }
 
+    /// Checks whether the transaction can be executed in a block with the specified timestamp.
+    pub fn is_executable_at(&self, timestamp: u64) -> bool {
+        self.execute_after
+            .map_or(true, |execute_after| execute_after <= timestamp)
+    }
+
     pub fn set_input(&mut self, input: Vec<u8>, hash: H256) {
         self.input = Some(InputData { hash, data: input })
     }
@@ -160,6 +172,7 @@ impl Default for L2TxCommonData {
             signature: Default::default(),
             transaction_type: TransactionType::EIP712Transaction,
             input: Default::default(),
+            execute_after: None,
         }
     }
 }
@@ -15,6 +15,7 @@ pub use storage::*;
 use tx::execute::Execute;
 
 pub mod api;
+pub mod archive;
 pub mod block;
 pub mod circuit;
 pub mod commitment;
@@ -29,6 +30,8 @@ pub mod proofs;
 pub mod protocol_version;
 pub mod prove_batches;
 pub mod prover_server_api;
+pub mod receipts_tree;
+pub mod replay_bundle;
 pub mod request;
 pub mod snapshots;
 pub mod storage;
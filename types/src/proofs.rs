@@ -145,6 +145,28 @@ pub struct PrepareBasicCircuitsJob {
 //     }
 // }
 
+/// Wall-clock time spent in each stage of basic witness generation for an L1 batch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct WitnessStageTimings {
+    /// Downloading the job prepared by the sequencer from the object store.
+    pub blob_fetch_ms: u64,
+    /// Loading batch data from Postgres.
+    pub db_read_ms: u64,
+    /// Serializing and uploading the produced artifacts to the object store.
+    pub upload_ms: u64,
+}
+
+/// Wall-clock time spent in each stage of producing witness inputs for an L1 batch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct WitnessInputStageTimings {
+    /// Loading the batch header and the used factory deps from Postgres.
+    pub db_read_ms: u64,
+    /// Serializing and uploading the witness block state to the object store.
+    pub upload_ms: u64,
+}
+
 /// Enriched `PrepareBasicCircuitsJob`. All the other fields are taken from the `l1_batches` table.
 #[derive(Debug, Clone)]
 pub struct BasicCircuitWitnessGeneratorInput {
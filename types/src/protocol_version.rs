@@ -49,6 +49,16 @@ impl ProtocolVersionId {
     pub fn next() -> Self {
         Self::Version1
     }
+
+    /// Checks whether L1 batch commitments include the operator address in the meta parameters.
+    pub fn commits_to_operator_address(self) -> bool {
+        self >= Self::Version1
+    }
+
+    /// Checks whether L1 batch commitments include the receipts root in the auxiliary output.
+    pub fn commits_to_receipts_root(self) -> bool {
+        self >= Self::Version1
+    }
 }
 
 impl Default for ProtocolVersionId {
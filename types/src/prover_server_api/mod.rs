@@ -8,6 +8,12 @@ use crate::{
     protocol_version::{FriProtocolVersionId, L1VerifierConfig},
 };
 
+pub mod proto;
+
+/// Header identifying the submitter of a proof (e.g., the name of the prover gateway pod),
+/// recorded for audit.
+pub const PROVER_ID_HEADER: &str = "x-prover-id";
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProofGenerationDataRequest {}
 
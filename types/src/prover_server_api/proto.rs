@@ -0,0 +1,290 @@
+//! Protobuf representation of the proof data handler API messages, generated from
+//! `proto/prover_server_api.proto`, and conversions from / to the native types.
+
+use ola_basic_types::{L1BatchNumber, H256};
+
+use crate::{
+    proofs::L1BatchProofForL1,
+    protocol_version::{self, FriProtocolVersionId},
+};
+
+#[allow(clippy::all)]
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/ola.prover_server_api.rs"));
+}
+
+pub use self::generated::*;
+
+/// Media type of protobuf-encoded requests and responses.
+pub const PROTOBUF_CONTENT_TYPE: &str = "application/x-protobuf";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProtoConversionError {
+    #[error("required field `{0}` is missing")]
+    MissingField(&'static str),
+    #[error("field `{field}` is invalid: {message}")]
+    InvalidField {
+        field: &'static str,
+        message: String,
+    },
+    #[error("failed (de)serializing proof generation data: {0}")]
+    Bincode(#[from] bincode::Error),
+}
+
+fn parse_h256(field: &'static str, bytes: &[u8]) -> Result<H256, ProtoConversionError> {
+    if bytes.len() != 32 {
+        return Err(ProtoConversionError::InvalidField {
+            field,
+            message: format!("expected 32 bytes, got {}", bytes.len()),
+        });
+    }
+    Ok(H256::from_slice(bytes))
+}
+
+impl From<super::ProofGenerationDataRequest> for ProofGenerationDataRequest {
+    fn from(_: super::ProofGenerationDataRequest) -> Self {
+        Self {}
+    }
+}
+
+impl From<ProofGenerationDataRequest> for super::ProofGenerationDataRequest {
+    fn from(_: ProofGenerationDataRequest) -> Self {
+        Self {}
+    }
+}
+
+impl From<protocol_version::L1VerifierConfig> for L1VerifierConfig {
+    fn from(config: protocol_version::L1VerifierConfig) -> Self {
+        let params = config.params;
+        Self {
+            params: Some(VerifierParams {
+                recursion_node_level_vk_hash: params.recursion_node_level_vk_hash.0.to_vec(),
+                recursion_leaf_level_vk_hash: params.recursion_leaf_level_vk_hash.0.to_vec(),
+                recursion_circuits_set_vks_hash: params.recursion_circuits_set_vks_hash.0.to_vec(),
+            }),
+            recursion_scheduler_level_vk_hash: config.recursion_scheduler_level_vk_hash.0.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<L1VerifierConfig> for protocol_version::L1VerifierConfig {
+    type Error = ProtoConversionError;
+
+    fn try_from(config: L1VerifierConfig) -> Result<Self, Self::Error> {
+        let params = config.params.ok_or(ProtoConversionError::MissingField(
+            "l1_verifier_config.params",
+        ))?;
+        Ok(Self {
+            params: protocol_version::VerifierParams {
+                recursion_node_level_vk_hash: parse_h256(
+                    "recursion_node_level_vk_hash",
+                    &params.recursion_node_level_vk_hash,
+                )?,
+                recursion_leaf_level_vk_hash: parse_h256(
+                    "recursion_leaf_level_vk_hash",
+                    &params.recursion_leaf_level_vk_hash,
+                )?,
+                recursion_circuits_set_vks_hash: parse_h256(
+                    "recursion_circuits_set_vks_hash",
+                    &params.recursion_circuits_set_vks_hash,
+                )?,
+            },
+            recursion_scheduler_level_vk_hash: parse_h256(
+                "recursion_scheduler_level_vk_hash",
+                &config.recursion_scheduler_level_vk_hash,
+            )?,
+        })
+    }
+}
+
+impl TryFrom<super::ProofGenerationData> for ProofGenerationData {
+    type Error = ProtoConversionError;
+
+    fn try_from(data: super::ProofGenerationData) -> Result<Self, Self::Error> {
+        Ok(Self {
+            l1_batch_number: data.l1_batch_number.0,
+            data: bincode::serialize(&data.data)?,
+            fri_protocol_version_id: data.fri_protocol_version_id as u32,
+            l1_verifier_config: Some(data.l1_verifier_config.into()),
+        })
+    }
+}
+
+impl TryFrom<ProofGenerationData> for super::ProofGenerationData {
+    type Error = ProtoConversionError;
+
+    fn try_from(data: ProofGenerationData) -> Result<Self, Self::Error> {
+        let fri_protocol_version_id = u16::try_from(data.fri_protocol_version_id)
+            .ok()
+            .and_then(|id| FriProtocolVersionId::try_from(id).ok())
+            .ok_or_else(|| ProtoConversionError::InvalidField {
+                field: "fri_protocol_version_id",
+                message: format!("unknown version {}", data.fri_protocol_version_id),
+            })?;
+        let l1_verifier_config = data
+            .l1_verifier_config
+            .ok_or(ProtoConversionError::MissingField("l1_verifier_config"))?;
+        Ok(Self {
+            l1_batch_number: L1BatchNumber(data.l1_batch_number),
+            data: bincode::deserialize(&data.data)?,
+            fri_protocol_version_id,
+            l1_verifier_config: l1_verifier_config.try_into()?,
+        })
+    }
+}
+
+impl TryFrom<super::ProofGenerationDataResponse> for ProofGenerationDataResponse {
+    type Error = ProtoConversionError;
+
+    fn try_from(response: super::ProofGenerationDataResponse) -> Result<Self, Self::Error> {
+        let result = match response {
+            super::ProofGenerationDataResponse::Success(data) => {
+                proof_generation_data_response::Result::Success(
+                    proof_generation_data_response::Success {
+                        data: data.map(TryInto::try_into).transpose()?,
+                    },
+                )
+            }
+            super::ProofGenerationDataResponse::Error(err) => {
+                proof_generation_data_response::Result::Error(err)
+            }
+        };
+        Ok(Self {
+            result: Some(result),
+        })
+    }
+}
+
+impl TryFrom<ProofGenerationDataResponse> for super::ProofGenerationDataResponse {
+    type Error = ProtoConversionError;
+
+    fn try_from(response: ProofGenerationDataResponse) -> Result<Self, Self::Error> {
+        Ok(
+            match response
+                .result
+                .ok_or(ProtoConversionError::MissingField("result"))?
+            {
+                proof_generation_data_response::Result::Success(success) => {
+                    Self::Success(success.data.map(TryInto::try_into).transpose()?)
+                }
+                proof_generation_data_response::Result::Error(err) => Self::Error(err),
+            },
+        )
+    }
+}
+
+impl From<super::SubmitProofRequest> for SubmitProofRequest {
+    fn from(request: super::SubmitProofRequest) -> Self {
+        let request = match request {
+            super::SubmitProofRequest::Proof(proof) => {
+                submit_proof_request::Request::Proof(proof.proof)
+            }
+            super::SubmitProofRequest::SkippedProofGeneration => {
+                submit_proof_request::Request::SkippedProofGeneration(
+                    submit_proof_request::SkippedProofGeneration {},
+                )
+            }
+        };
+        Self {
+            request: Some(request),
+        }
+    }
+}
+
+impl TryFrom<SubmitProofRequest> for super::SubmitProofRequest {
+    type Error = ProtoConversionError;
+
+    fn try_from(request: SubmitProofRequest) -> Result<Self, Self::Error> {
+        Ok(
+            match request
+                .request
+                .ok_or(ProtoConversionError::MissingField("request"))?
+            {
+                submit_proof_request::Request::Proof(proof) => {
+                    Self::Proof(Box::new(L1BatchProofForL1 { proof }))
+                }
+                submit_proof_request::Request::SkippedProofGeneration(_) => {
+                    Self::SkippedProofGeneration
+                }
+            },
+        )
+    }
+}
+
+impl From<super::SubmitProofResponse> for SubmitProofResponse {
+    fn from(response: super::SubmitProofResponse) -> Self {
+        let result = match response {
+            super::SubmitProofResponse::Success => {
+                submit_proof_response::Result::Success(submit_proof_response::Success {})
+            }
+            super::SubmitProofResponse::Error(err) => submit_proof_response::Result::Error(err),
+        };
+        Self {
+            result: Some(result),
+        }
+    }
+}
+
+impl TryFrom<SubmitProofResponse> for super::SubmitProofResponse {
+    type Error = ProtoConversionError;
+
+    fn try_from(response: SubmitProofResponse) -> Result<Self, Self::Error> {
+        Ok(
+            match response
+                .result
+                .ok_or(ProtoConversionError::MissingField("result"))?
+            {
+                submit_proof_response::Result::Success(_) => Self::Success,
+                submit_proof_response::Result::Error(err) => Self::Error(err),
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prost::Message;
+
+    use super::*;
+
+    #[test]
+    fn submit_proof_request_roundtrip() {
+        let request = super::super::SubmitProofRequest::Proof(Box::new(L1BatchProofForL1 {
+            proof: vec![1, 2, 3],
+        }));
+        let encoded = SubmitProofRequest::from(request).encode_to_vec();
+        let decoded = SubmitProofRequest::decode(encoded.as_slice()).unwrap();
+        let decoded = super::super::SubmitProofRequest::try_from(decoded).unwrap();
+        assert!(matches!(
+            decoded,
+            super::super::SubmitProofRequest::Proof(proof) if proof.proof == [1, 2, 3]
+        ));
+
+        let err =
+            super::super::SubmitProofRequest::try_from(SubmitProofRequest::default()).unwrap_err();
+        assert!(matches!(err, ProtoConversionError::MissingField("request")));
+    }
+
+    #[test]
+    fn l1_verifier_config_roundtrip() {
+        let config = protocol_version::L1VerifierConfig {
+            params: protocol_version::VerifierParams {
+                recursion_node_level_vk_hash: H256::repeat_byte(1),
+                recursion_leaf_level_vk_hash: H256::repeat_byte(2),
+                recursion_circuits_set_vks_hash: H256::repeat_byte(3),
+            },
+            recursion_scheduler_level_vk_hash: H256::repeat_byte(4),
+        };
+        let proto = L1VerifierConfig::from(config);
+        assert_eq!(
+            protocol_version::L1VerifierConfig::try_from(proto.clone()).unwrap(),
+            config
+        );
+
+        let truncated = L1VerifierConfig {
+            recursion_scheduler_level_vk_hash: vec![0; 20],
+            ..proto
+        };
+        assert!(protocol_version::L1VerifierConfig::try_from(truncated).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! Binary Merkle tree over the transactions of an L1 batch and their execution status.
+//!
+//! The root of the tree is computed when the L1 batch is sealed and is bound into the L1 batch
+//! commitment, so that a transaction can be proven to be included in a committed batch (and to
+//! have succeeded or failed) without trusting the node serving the proof.
+
+use ola_basic_types::H256;
+use ola_utils::hash::hash_bytes;
+use serde::{Deserialize, Serialize};
+
+/// Leaf of the receipts tree: a transaction executed in an L1 batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiptLeaf {
+    pub tx_hash: H256,
+    /// Index of the transaction in the L1 batch.
+    pub index_in_batch: u32,
+    pub succeeded: bool,
+}
+
+impl ReceiptLeaf {
+    const SERIALIZED_SIZE: usize = 32 + 4 + 1;
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(Self::SERIALIZED_SIZE);
+        result.extend_from_slice(self.tx_hash.as_bytes());
+        result.extend_from_slice(&self.index_in_batch.to_be_bytes());
+        result.push(self.succeeded as u8);
+        result
+    }
+
+    pub fn hash(&self) -> H256 {
+        hash_bytes(&self.to_bytes())
+    }
+}
+
+/// Receipts tree of an L1 batch. Leaves are padded with zero hashes up to the next power of two;
+/// the root of a batch without transactions is the zero hash.
+#[derive(Debug, Clone)]
+pub struct ReceiptsTree {
+    /// Tree levels from the (padded) leaf hashes up to the root.
+    levels: Vec<Vec<H256>>,
+}
+
+impl ReceiptsTree {
+    pub fn new(leaves: &[ReceiptLeaf]) -> Self {
+        let mut level: Vec<_> = leaves.iter().map(ReceiptLeaf::hash).collect();
+        level.resize(leaves.len().max(1).next_power_of_two(), H256::zero());
+
+        let mut levels = vec![level];
+        while levels.last().unwrap().len() > 1 {
+            let next_level = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| hash_pair(&pair[0], &pair[1]))
+                .collect();
+            levels.push(next_level);
+        }
+        Self { levels }
+    }
+
+    pub fn root(&self) -> H256 {
+        self.levels.last().unwrap()[0]
+    }
+
+    /// Returns sibling hashes on the path from the leaf with the specified index to the root,
+    /// starting from the leaf level, or `None` if the index is out of bounds.
+    pub fn merkle_path(&self, index: usize) -> Option<Vec<H256>> {
+        if index >= self.levels[0].len() {
+            return None;
+        }
+        let path = self.levels[..self.levels.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(depth, level)| level[(index >> depth) ^ 1])
+            .collect();
+        Some(path)
+    }
+}
+
+/// Checks that `leaf` is included in the tree with the specified `root` at `leaf.index_in_batch`.
+pub fn verify_receipt_proof(leaf: &ReceiptLeaf, merkle_path: &[H256], root: H256) -> bool {
+    let mut index = leaf.index_in_batch as usize;
+    let mut hash = leaf.hash();
+    for sibling in merkle_path {
+        hash = if index % 2 == 0 {
+            hash_pair(&hash, sibling)
+        } else {
+            hash_pair(sibling, &hash)
+        };
+        index /= 2;
+    }
+    index == 0 && hash == root
+}
+
+fn hash_pair(left: &H256, right: &H256) -> H256 {
+    let mut bytes = [0_u8; 64];
+    bytes[..32].copy_from_slice(left.as_bytes());
+    bytes[32..].copy_from_slice(right.as_bytes());
+    hash_bytes(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(count: u32) -> Vec<ReceiptLeaf> {
+        (0..count)
+            .map(|index_in_batch| ReceiptLeaf {
+                tx_hash: H256::repeat_byte(index_in_batch as u8 + 1),
+                index_in_batch,
+                succeeded: index_in_batch % 3 != 0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn empty_receipts_tree() {
+        let tree = ReceiptsTree::new(&[]);
+        assert_eq!(tree.root(), H256::zero());
+        assert_eq!(tree.merkle_path(0), Some(vec![]));
+    }
+
+    #[test]
+    fn proving_receipt_inclusion() {
+        for count in [1, 2, 5, 8] {
+            let leaves = leaves(count);
+            let tree = ReceiptsTree::new(&leaves);
+            for leaf in &leaves {
+                let path = tree.merkle_path(leaf.index_in_batch as usize).unwrap();
+                assert_eq!(
+                    path.len(),
+                    count.next_power_of_two().trailing_zeros() as usize
+                );
+                assert!(verify_receipt_proof(leaf, &path, tree.root()));
+
+                let flipped = ReceiptLeaf {
+                    succeeded: !leaf.succeeded,
+                    ..*leaf
+                };
+                assert!(!verify_receipt_proof(&flipped, &path, tree.root()));
+            }
+        }
+    }
+}
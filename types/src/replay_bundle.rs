@@ -0,0 +1,62 @@
+//! Self-contained bundle for independent re-execution of an L1 batch.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    block::L1BatchHeader, storage::StorageUpdateTrace, Address, L1BatchNumber, MiniblockNumber,
+    Transaction, H256,
+};
+
+/// Transactions of a miniblock in the execution order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayMiniblock {
+    pub number: MiniblockNumber,
+    pub timestamp: u64,
+    pub transactions: Vec<Transaction>,
+}
+
+/// Storage slot written in the batch, with its values before and after the batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayStorageWrite {
+    pub address: Address,
+    pub key: H256,
+    pub before: H256,
+    pub after: H256,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayFactoryDep {
+    pub bytecode_hash: H256,
+    pub bytecode: Vec<u8>,
+}
+
+/// Everything needed to re-execute an L1 batch and check the resulting state root without
+/// access to the node databases.
+///
+/// `storage` is the trace produced by the Merkle tree while applying the batch: every storage
+/// access with the Merkle path of the slot, starting from `pre_root_hash`. Re-executing
+/// `miniblocks` on top of the reads must produce `storage_writes` and `post_root_hash`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayBundle {
+    /// Version of the bundle format.
+    pub version: u32,
+    pub l1_batch_number: L1BatchNumber,
+    pub header: L1BatchHeader,
+    pub pre_root_hash: H256,
+    pub post_root_hash: H256,
+    pub miniblocks: Vec<ReplayMiniblock>,
+    pub factory_deps: Vec<ReplayFactoryDep>,
+    pub storage: StorageUpdateTrace,
+    pub storage_writes: Vec<ReplayStorageWrite>,
+}
+
+impl ReplayBundle {
+    pub const VERSION: u32 = 1;
+
+    pub fn transaction_count(&self) -> usize {
+        self.miniblocks
+            .iter()
+            .map(|miniblock| miniblock.transactions.len())
+            .sum()
+    }
+}
@@ -37,6 +37,8 @@ pub enum SerializationTransactionError {
     WrongChainId(Option<u16>),
     #[error("oversized data. max: {0}; actual: {1}")]
     OversizedData(usize, usize),
+    #[error("malformed calldata: length {0} is not a multiple of 8 bytes")]
+    MalformedCalldata(usize),
 }
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -464,6 +466,7 @@ impl TransactionRequest {
         if let Some(deps) = factory_deps_ref {
             validate_factory_deps(deps)?;
         }
+        validate_calldata(&tx.input.0)?;
         tx.raw = Some(Bytes(bytes.to_vec()));
         let default_signed_message = tx.get_default_signed_message(chain_id);
         tx.from = match tx.from {
@@ -621,6 +624,7 @@ impl L2Tx {
         if let Some(deps) = factory_deps.as_ref() {
             validate_factory_deps(deps)?;
         }
+        validate_calldata(&request.input.0)?;
 
         let contrace_address = request
             .to
@@ -670,6 +674,16 @@ pub fn validate_factory_deps(
     Ok(())
 }
 
+/// Checks that calldata can be converted into field elements, i.e. consists of whole `u64` words.
+pub fn validate_calldata(calldata: &[u8]) -> Result<(), SerializationTransactionError> {
+    if calldata.len() % 8 != 0 {
+        return Err(SerializationTransactionError::MalformedCalldata(
+            calldata.len(),
+        ));
+    }
+    Ok(())
+}
+
 fn rlp_opt<T: rlp::Encodable>(rlp: &mut RlpStream, opt: &Option<T>) {
     if let Some(inner) = opt {
         rlp.append(inner);
@@ -700,7 +714,7 @@ mod tests {
 
     use crate::{
         api::TransactionRequest,
-        request::{Eip712Meta, PaymasterParams},
+        request::{Eip712Meta, PaymasterParams, SerializationTransactionError},
         tx::primitives::{Eip712Domain, PackedEthSignature},
         EIP_712_TX_TYPE, OLA_RAW_TX_TYPE,
     };
@@ -792,4 +806,35 @@ mod tests {
 
         assert_eq!(tx, tx2);
     }
+
+    #[test]
+    fn reject_unaligned_calldata() {
+        let private_key = H256::random();
+        let address = PackedEthSignature::address_from_private_key(&private_key).unwrap();
+
+        let tx = TransactionRequest {
+            nonce: U256::from(0u32),
+            to: Some(Address::random()),
+            from: Some(address),
+            input: Bytes::from(vec![1u8; 9]),
+            transaction_type: Some(U64::from(OLA_RAW_TX_TYPE)),
+            eip712_meta: Some(Eip712Meta {
+                factory_deps: Some(vec![]),
+                custom_signature: Some(vec![1; 32]),
+                paymaster_params: None,
+            }),
+            chain_id: Some(1027),
+            ..Default::default()
+        };
+
+        let msg = tx.get_default_signed_message(1027);
+        let signature = PackedEthSignature::sign_raw(&private_key, &msg).unwrap();
+        let data = tx.get_signed_bytes(&signature, 1027);
+
+        let err = TransactionRequest::from_bytes(&data, 1027).unwrap_err();
+        assert!(matches!(
+            err,
+            SerializationTransactionError::MalformedCalldata(9)
+        ));
+    }
 }
@@ -1,4 +1,7 @@
-use ola_basic_types::{L1BatchNumber, MiniblockNumber, H256};
+use std::ops;
+
+use ola_basic_types::{Address, Bytes, L1BatchNumber, MiniblockNumber, Nonce, H256, U256};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, PartialEq)]
 pub struct SnapshotRecoveryStatus {
@@ -9,3 +12,131 @@ pub struct SnapshotRecoveryStatus {
     pub last_finished_chunk_id: Option<u64>,
     pub total_chunk_count: u64,
 }
+
+/// Header of a state snapshot stored in the object store. The snapshot captures the storage state
+/// after the last miniblock of `l1_batch_number`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotHeader {
+    pub l1_batch_number: L1BatchNumber,
+    pub miniblock_number: MiniblockNumber,
+    pub storage_logs_chunk_count: u64,
+}
+
+/// Latest value of a storage slot, identified by its hashed key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotStorageLog {
+    pub hashed_key: H256,
+    pub value: H256,
+}
+
+/// Storage logs for a contiguous range of hashed keys, see [`uniform_hashed_keys_chunk()`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotStorageLogsChunk {
+    pub storage_logs: Vec<SnapshotStorageLog>,
+}
+
+/// Object store key for a [`SnapshotStorageLogsChunk`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapshotStorageLogsStorageKey {
+    pub l1_batch_number: L1BatchNumber,
+    pub chunk_id: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotFactoryDependency {
+    pub bytecode_hash: H256,
+    pub bytecode: Vec<u8>,
+}
+
+/// All factory dependencies known as of the snapshot L1 batch.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotFactoryDependencies {
+    pub factory_deps: Vec<SnapshotFactoryDependency>,
+}
+
+/// Full state of a chain as of an L1 batch, used as the genesis state of a forked chain.
+/// Created with `ola_node export-genesis`. All lists are sorted, so that the same state
+/// is always exported to the same bundle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenesisBundle {
+    pub source_l1_batch_number: L1BatchNumber,
+    pub source_miniblock_number: MiniblockNumber,
+    /// Storage slots with non-zero values, sorted by hashed key.
+    pub storage: Vec<GenesisStorageEntry>,
+    /// Factory deps sorted by bytecode hash.
+    pub factory_deps: Vec<GenesisFactoryDep>,
+    /// Nonces of accounts that have sent transactions, sorted by address. Nonces are also
+    /// contained in `storage`; they are listed separately for inspection of the bundle.
+    pub nonces: Vec<GenesisAccountNonce>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenesisStorageEntry {
+    pub address: Address,
+    pub key: H256,
+    pub value: H256,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenesisFactoryDep {
+    pub bytecode_hash: H256,
+    pub bytecode: Bytes,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenesisAccountNonce {
+    pub address: Address,
+    pub nonce: Nonce,
+}
+
+/// Returns the range of hashed keys covered by the chunk `chunk_id` if the key space is split
+/// into `chunk_count` chunks of (almost) equal size. Keys are compared as big-endian numbers,
+/// which matches the byte-wise ordering used by Postgres.
+pub fn uniform_hashed_keys_chunk(chunk_id: u64, chunk_count: u64) -> ops::RangeInclusive<H256> {
+    assert!(chunk_count > 0, "`chunk_count` must be positive");
+    assert!(
+        chunk_id < chunk_count,
+        "Chunk ID {chunk_id} is out of range for {chunk_count} chunks"
+    );
+
+    let stride = U256::MAX / chunk_count;
+    let start = stride * chunk_id;
+    let end = if chunk_id == chunk_count - 1 {
+        U256::MAX
+    } else {
+        start + stride - 1
+    };
+    u256_to_h256(start)..=u256_to_h256(end)
+}
+
+fn u256_to_h256(value: U256) -> H256 {
+    let mut bytes = [0_u8; 32];
+    value.to_big_endian(&mut bytes);
+    H256(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uniform_chunks_cover_key_space() {
+        for chunk_count in [1, 2, 3, 10] {
+            let chunks: Vec<_> = (0..chunk_count)
+                .map(|chunk_id| uniform_hashed_keys_chunk(chunk_id, chunk_count))
+                .collect();
+            assert_eq!(*chunks[0].start(), H256::zero());
+            assert_eq!(*chunks.last().unwrap().end(), H256::repeat_byte(0xff));
+
+            for window in chunks.windows(2) {
+                let prev_end = U256::from_big_endian(window[0].end().as_bytes());
+                let next_start = U256::from_big_endian(window[1].start().as_bytes());
+                assert_eq!(prev_end + 1, next_start);
+            }
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! State diff between two versions of the storage, e.g. before and after a protocol upgrade.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use super::{Address, StorageKey, H256};
+
+/// Change of a single storage slot. `None` means the slot was never written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StorageValueDiff {
+    pub key: StorageKey,
+    pub before: Option<H256>,
+    pub after: Option<H256>,
+}
+
+impl StorageValueDiff {
+    fn kind(&self) -> StorageChangeKind {
+        let before = self.before.unwrap_or_else(H256::zero);
+        let after = self.after.unwrap_or_else(H256::zero);
+        if before.is_zero() {
+            StorageChangeKind::Created
+        } else if after.is_zero() {
+            StorageChangeKind::Cleared
+        } else {
+            StorageChangeKind::Updated
+        }
+    }
+
+    fn is_noop(&self) -> bool {
+        self.before.unwrap_or_else(H256::zero) == self.after.unwrap_or_else(H256::zero)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageChangeKind {
+    Created,
+    Updated,
+    Cleared,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotChange {
+    pub key: H256,
+    pub kind: StorageChangeKind,
+    pub before: H256,
+    pub after: H256,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateDiffSummary {
+    pub accounts_changed: usize,
+    pub slots_changed: usize,
+    pub slots_created: usize,
+    pub slots_updated: usize,
+    pub slots_cleared: usize,
+}
+
+/// Changed slots grouped by account. Slots whose value didn't change are omitted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateDiff {
+    pub summary: StateDiffSummary,
+    pub accounts: BTreeMap<Address, Vec<SlotChange>>,
+}
+
+impl StateDiff {
+    pub fn new(changes: impl IntoIterator<Item = StorageValueDiff>) -> Self {
+        let mut diff = Self::default();
+        for change in changes.into_iter().filter(|change| !change.is_noop()) {
+            let kind = change.kind();
+            match kind {
+                StorageChangeKind::Created => diff.summary.slots_created += 1,
+                StorageChangeKind::Updated => diff.summary.slots_updated += 1,
+                StorageChangeKind::Cleared => diff.summary.slots_cleared += 1,
+            }
+            diff.summary.slots_changed += 1;
+            diff.accounts
+                .entry(*change.key.address())
+                .or_default()
+                .push(SlotChange {
+                    key: *change.key.key(),
+                    kind,
+                    before: change.before.unwrap_or_else(H256::zero),
+                    after: change.after.unwrap_or_else(H256::zero),
+                });
+        }
+        for slots in diff.accounts.values_mut() {
+            slots.sort_by_key(|slot| slot.key);
+        }
+        diff.summary.accounts_changed = diff.accounts.len();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AccountTreeId;
+
+    fn change(address: u8, key: u8, before: Option<u8>, after: Option<u8>) -> StorageValueDiff {
+        let key = StorageKey::new(
+            AccountTreeId::new(Address::repeat_byte(address)),
+            H256::repeat_byte(key),
+        );
+        StorageValueDiff {
+            key,
+            before: before.map(H256::repeat_byte),
+            after: after.map(H256::repeat_byte),
+        }
+    }
+
+    #[test]
+    fn state_diff_groups_changes_by_account() {
+        let diff = StateDiff::new([
+            change(1, 2, None, Some(1)),
+            change(1, 1, Some(1), Some(2)),
+            change(2, 1, Some(1), Some(0)),
+            change(2, 2, Some(3), Some(3)),
+            change(3, 1, None, Some(0)),
+        ]);
+
+        assert_eq!(
+            diff.summary,
+            StateDiffSummary {
+                accounts_changed: 2,
+                slots_changed: 3,
+                slots_created: 1,
+                slots_updated: 1,
+                slots_cleared: 1,
+            }
+        );
+        let first_account = &diff.accounts[&Address::repeat_byte(1)];
+        assert_eq!(first_account.len(), 2);
+        assert_eq!(first_account[0].key, H256::repeat_byte(1));
+        assert_eq!(first_account[0].kind, StorageChangeKind::Updated);
+        assert_eq!(first_account[1].kind, StorageChangeKind::Created);
+        let second_account = &diff.accounts[&Address::repeat_byte(2)];
+        assert_eq!(second_account.len(), 1);
+        assert_eq!(second_account[0].kind, StorageChangeKind::Cleared);
+        assert!(!diff.accounts.contains_key(&Address::repeat_byte(3)));
+    }
+}
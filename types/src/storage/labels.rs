@@ -0,0 +1,101 @@
+//! Human-readable labels for well-known storage keys, used for debugging output only.
+
+use std::collections::HashMap;
+
+use ola_config::constants::{
+    contracts::{
+        ACCOUNT_CODE_STORAGE_ADDRESS, CONTRACT_DEPLOYER_ADDRESS, DEFAULT_ACCOUNT_ADDRESS,
+        ENTRYPOINT_ADDRESS, KNOWN_CODES_STORAGE_ADDRESS, NONCE_HOLDER_ADDRESS,
+        SYSTEM_CONTEXT_ADDRESS,
+    },
+    system_context::{
+        SYSTEM_CONTEXT_CHAIN_ID_POSITION, SYSTEM_CONTEXT_COINBASE_POSITION,
+        SYSTEM_CONTEXT_DIFFICULTY_POSITION,
+    },
+};
+
+use super::{get_full_code_key, get_nonce_key, get_system_context_key, Address, StorageKey};
+
+const SYSTEM_CONTRACT_NAMES: [(Address, &str); 7] = [
+    (ENTRYPOINT_ADDRESS, "Entrypoint"),
+    (ACCOUNT_CODE_STORAGE_ADDRESS, "AccountCodeStorage"),
+    (NONCE_HOLDER_ADDRESS, "NonceHolder"),
+    (KNOWN_CODES_STORAGE_ADDRESS, "KnownCodesStorage"),
+    (CONTRACT_DEPLOYER_ADDRESS, "ContractDeployer"),
+    (DEFAULT_ACCOUNT_ADDRESS, "DefaultAccount"),
+    (SYSTEM_CONTEXT_ADDRESS, "SystemContext"),
+];
+
+/// Registry mapping storage keys to human-readable labels.
+///
+/// System context slots are registered on creation. Account-specific slots (nonces and deployed
+/// code hashes) are derived from hashed keys and thus have to be registered per account
+/// with [`Self::register_account()`]. Keys without an exact label fall back to
+/// `<contract>[<key>]`, where `<contract>` is the system contract name if known.
+#[derive(Debug, Clone)]
+pub struct StorageKeyLabels {
+    labels: HashMap<StorageKey, String>,
+}
+
+impl Default for StorageKeyLabels {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StorageKeyLabels {
+    pub fn new() -> Self {
+        let labels = [
+            (SYSTEM_CONTEXT_CHAIN_ID_POSITION, "SystemContext.chainId"),
+            (SYSTEM_CONTEXT_COINBASE_POSITION, "SystemContext.coinbase"),
+            (
+                SYSTEM_CONTEXT_DIFFICULTY_POSITION,
+                "SystemContext.difficulty",
+            ),
+        ]
+        .into_iter()
+        .map(|(position, label)| (get_system_context_key(position), label.to_owned()))
+        .collect();
+        Self { labels }
+    }
+
+    /// Registers the nonce and deployed code slots of `account`.
+    pub fn register_account(&mut self, account: &Address) {
+        self.labels.insert(
+            get_nonce_key(account),
+            format!("NonceHolder.nonce[{account:?}]"),
+        );
+        self.labels.insert(
+            get_full_code_key(account),
+            format!("AccountCodeStorage.codeHash[{account:?}]"),
+        );
+    }
+
+    /// Registers an arbitrary label, e.g. for a slot of a user contract being debugged.
+    pub fn register(&mut self, key: StorageKey, label: impl Into<String>) {
+        self.labels.insert(key, label.into());
+    }
+
+    /// Returns the exact label registered for `key`, if any.
+    pub fn get(&self, key: &StorageKey) -> Option<&str> {
+        self.labels.get(key).map(String::as_str)
+    }
+
+    /// Returns a label for `key`, falling back to the contract name (or address) and raw key.
+    pub fn label(&self, key: &StorageKey) -> String {
+        if let Some(label) = self.get(key) {
+            return label.to_owned();
+        }
+        if *key.address() == KNOWN_CODES_STORAGE_ADDRESS {
+            return format!("KnownCodesStorage.known[{:?}]", key.key());
+        }
+        let contract_name = SYSTEM_CONTRACT_NAMES
+            .iter()
+            .find(|(address, _)| address == key.address())
+            .map(|(_, name)| *name);
+        match contract_name {
+            Some(name) => format!("{name}[{:?}]", key.key()),
+            None => format!("{:?}[{:?}]", key.address(), key.key()),
+        }
+    }
+}
@@ -14,6 +14,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::log::StorageLog;
 
+pub mod diff;
+pub mod labels;
 pub mod log;
 pub mod witness_block_state;
 pub mod writes;
@@ -1,12 +1,26 @@
 use std::collections::HashMap;
 
+use ola_utils::{bytes_to_chunks, h256_to_u256};
 use serde::{Deserialize, Serialize};
 
-use crate::{StorageKey, StorageValue};
+use crate::{StorageKey, StorageValue, H256, U256};
 
 /// Storage data used during Witness Generation.
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct WitnessBlockState {
     pub read_storage_key: HashMap<StorageKey, StorageValue>,
     pub is_write_initial: HashMap<StorageKey, bool>,
+    /// Bytecodes of factory deps loaded during the batch (including the entrypoint and
+    /// the default account), keyed by their hashes.
+    pub used_factory_deps: HashMap<H256, Vec<u8>>,
+}
+
+impl WitnessBlockState {
+    /// Returns used factory deps in the form expected by the witness generator.
+    pub fn used_bytecodes(&self) -> HashMap<U256, Vec<[u8; 32]>> {
+        self.used_factory_deps
+            .iter()
+            .map(|(hash, bytecode)| (h256_to_u256(*hash), bytes_to_chunks(bytecode)))
+            .collect()
+    }
 }
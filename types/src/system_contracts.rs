@@ -4,32 +4,87 @@ use ola_config::constants::contracts::{
     ENTRYPOINT_ADDRESS, KNOWN_CODES_STORAGE_ADDRESS, NONCE_HOLDER_ADDRESS, SYSTEM_CONTEXT_ADDRESS,
 };
 use ola_contracts::read_sys_contract_bytecode;
-use once_cell::sync::Lazy;
 
-use crate::block::DeployedContract;
+use crate::{block::DeployedContract, protocol_version::ProtocolVersionId};
 
 pub const TX_NONCE_INCREMENT: U256 = U256([1, 0, 0, 0]); // 1
 pub const DEPLOYMENT_NONCE_INCREMENT: U256 = U256([0, 0, 1, 0]); // 2^128
 
-static SYSTEM_CONTRACTS: Lazy<Vec<DeployedContract>> = Lazy::new(|| {
-    let mut deployed_system_contracts = [
-        ("", "Entrypoint", ENTRYPOINT_ADDRESS),
-        ("", "AccountCodeStorage", ACCOUNT_CODE_STORAGE_ADDRESS),
-        ("", "NonceHolder", NONCE_HOLDER_ADDRESS),
-        ("", "KnownCodesStorage", KNOWN_CODES_STORAGE_ADDRESS),
-        ("", "ContractDeployer", CONTRACT_DEPLOYER_ADDRESS),
-        ("", "DefaultAccount", DEFAULT_ACCOUNT_ADDRESS),
-        ("", "SystemContext", SYSTEM_CONTEXT_ADDRESS),
-    ]
-    .map(|(path, name, address)| {
-        let (raw, bytecode) = read_sys_contract_bytecode(path, name);
-        DeployedContract {
-            account_id: AccountTreeId::new(address),
-            raw: raw.clone(),
-            bytecode: bytecode.clone(),
+/// System contract deployed at a fixed address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemContractInfo {
+    /// Contract name; also the name of the contract artifact.
+    pub name: &'static str,
+    pub address: Address,
+    /// First protocol version the contract is deployed in.
+    pub since_version: ProtocolVersionId,
+    /// Last protocol version the contract is deployed in, if it's removed by a later version.
+    pub until_version: Option<ProtocolVersionId>,
+}
+
+impl SystemContractInfo {
+    const fn new(name: &'static str, address: Address) -> Self {
+        Self {
+            name,
+            address,
+            since_version: ProtocolVersionId::Version0,
+            until_version: None,
         }
-    })
-    .to_vec();
+    }
+
+    pub fn is_supported_by(&self, version: ProtocolVersionId) -> bool {
+        version >= self.since_version && self.until_version.map_or(true, |until| version <= until)
+    }
+}
+
+/// Registry of all system contracts. OlaVM precompiles are invoked as VM builtins rather than
+/// through contract addresses, so they have no entries here.
+pub const SYSTEM_CONTRACT_REGISTRY: &[SystemContractInfo] = &[
+    SystemContractInfo::new("Entrypoint", ENTRYPOINT_ADDRESS),
+    SystemContractInfo::new("AccountCodeStorage", ACCOUNT_CODE_STORAGE_ADDRESS),
+    SystemContractInfo::new("NonceHolder", NONCE_HOLDER_ADDRESS),
+    SystemContractInfo::new("KnownCodesStorage", KNOWN_CODES_STORAGE_ADDRESS),
+    SystemContractInfo::new("ContractDeployer", CONTRACT_DEPLOYER_ADDRESS),
+    SystemContractInfo::new("DefaultAccount", DEFAULT_ACCOUNT_ADDRESS),
+    SystemContractInfo::new("SystemContext", SYSTEM_CONTEXT_ADDRESS),
+];
+
+/// Returns system contracts deployed in the specified protocol version.
+pub fn system_contracts_for_version(
+    version: ProtocolVersionId,
+) -> impl Iterator<Item = &'static SystemContractInfo> {
+    SYSTEM_CONTRACT_REGISTRY
+        .iter()
+        .filter(move |contract| contract.is_supported_by(version))
+}
+
+/// Looks up a system contract by its address.
+pub fn find_system_contract(address: &Address) -> Option<&'static SystemContractInfo> {
+    SYSTEM_CONTRACT_REGISTRY
+        .iter()
+        .find(|contract| contract.address == *address)
+}
+
+/// Looks up a system contract by its name.
+pub fn find_system_contract_by_name(name: &str) -> Option<&'static SystemContractInfo> {
+    SYSTEM_CONTRACT_REGISTRY
+        .iter()
+        .find(|contract| contract.name == name)
+}
+
+/// Returns system contracts deployed at genesis of a chain starting at the specified protocol
+/// version.
+pub fn get_system_smart_contracts(version: ProtocolVersionId) -> Vec<DeployedContract> {
+    let mut deployed_system_contracts: Vec<_> = system_contracts_for_version(version)
+        .map(|contract| {
+            let (raw, bytecode) = read_sys_contract_bytecode("", contract.name);
+            DeployedContract {
+                account_id: AccountTreeId::new(contract.address),
+                raw,
+                bytecode,
+            }
+        })
+        .collect();
 
     let (empty_raw, empty_bytecode) = read_sys_contract_bytecode("", "EmptyContract");
     // For now, only zero address and the bootloader address have empty bytecode at the init
@@ -42,8 +97,44 @@ static SYSTEM_CONTRACTS: Lazy<Vec<DeployedContract>> = Lazy::new(|| {
 
     deployed_system_contracts.extend(empty_system_contracts);
     deployed_system_contracts
-});
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn system_contract_registry_is_consistent() {
+        let addresses: HashSet<_> = SYSTEM_CONTRACT_REGISTRY
+            .iter()
+            .map(|contract| contract.address)
+            .collect();
+        assert_eq!(addresses.len(), SYSTEM_CONTRACT_REGISTRY.len());
+        let names: HashSet<_> = SYSTEM_CONTRACT_REGISTRY
+            .iter()
+            .map(|contract| contract.name)
+            .collect();
+        assert_eq!(names.len(), SYSTEM_CONTRACT_REGISTRY.len());
+
+        let entrypoint = find_system_contract(&ENTRYPOINT_ADDRESS).unwrap();
+        assert_eq!(entrypoint.name, "Entrypoint");
+        assert_eq!(find_system_contract_by_name("Entrypoint"), Some(entrypoint));
+        assert_eq!(find_system_contract(&Address::repeat_byte(0xff)), None);
+        assert_eq!(
+            system_contracts_for_version(ProtocolVersionId::latest()).count(),
+            SYSTEM_CONTRACT_REGISTRY.len()
+        );
+    }
 
-pub fn get_system_smart_contracts() -> Vec<DeployedContract> {
-    SYSTEM_CONTRACTS.clone()
+    #[test]
+    fn checking_protocol_version_support() {
+        let contract = SystemContractInfo {
+            until_version: Some(ProtocolVersionId::Version0),
+            ..SystemContractInfo::new("Test", Address::repeat_byte(1))
+        };
+        assert!(contract.is_supported_by(ProtocolVersionId::Version0));
+        assert!(!contract.is_supported_by(ProtocolVersionId::Version1));
+    }
 }
@@ -1,6 +1,10 @@
-use ola_basic_types::Address;
+use ola_basic_types::{Address, H256, U256};
+use ola_utils::{convert::bytes_to_u64s, hash::hash_bytes};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 
+use crate::events::VmEvent;
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct TokenInfo {
     pub l1_address: Address,
@@ -17,3 +21,180 @@ pub struct TokenMetadata {
     /// Token precision (e.g. 18 for "ETH" so "1.0" ETH = 10e18 as U256 number)
     pub decimals: u8,
 }
+
+/// Emitted by a token contract in its constructor; `name`, `symbol` and `decimals` are
+/// in the event data.
+pub static TOKEN_INITIALIZED_EVENT_SIGNATURE: Lazy<H256> =
+    Lazy::new(|| hash_bytes(b"TokenInitialized(string,string,u32)"));
+
+/// Emitted by a token contract on each transfer, with indexed `from` and `to` and `amount`
+/// in the event data. Mints are transfers from the zero address, burns are transfers to it.
+pub static TRANSFER_EVENT_SIGNATURE: Lazy<H256> =
+    Lazy::new(|| hash_bytes(b"Transfer(address,address,u32)"));
+
+/// Token transfer decoded from a [`TRANSFER_EVENT_SIGNATURE`] event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenTransfer {
+    pub token: Address,
+    pub from: Address,
+    pub to: Address,
+    pub amount: U256,
+}
+
+/// Decodes token metadata from a [`TOKEN_INITIALIZED_EVENT_SIGNATURE`] event. Returns `None`
+/// for other events or malformed data.
+pub fn decode_token_metadata(event: &VmEvent) -> Option<TokenMetadata> {
+    if event.indexed_topics.first() != Some(&TOKEN_INITIALIZED_EVENT_SIGNATURE)
+        || event.value.len() % 8 != 0
+    {
+        return None;
+    }
+    let fields = bytes_to_u64s(event.value.clone());
+    let mut fields = fields.as_slice();
+    let name = decode_string(&mut fields)?;
+    let symbol = decode_string(&mut fields)?;
+    let (&decimals, rest) = fields.split_first()?;
+    if !rest.is_empty() {
+        return None;
+    }
+    Some(TokenMetadata {
+        name,
+        symbol,
+        decimals: u8::try_from(decimals).ok()?,
+    })
+}
+
+/// Decodes a string encoded as its length followed by one field per byte.
+fn decode_string(fields: &mut &[u64]) -> Option<String> {
+    let (&len, rest) = fields.split_first()?;
+    let len = usize::try_from(len).ok()?;
+    if rest.len() < len {
+        return None;
+    }
+    let (chars, rest) = rest.split_at(len);
+    let bytes = chars
+        .iter()
+        .map(|&ch| u8::try_from(ch).ok())
+        .collect::<Option<Vec<_>>>()?;
+    *fields = rest;
+    String::from_utf8(bytes).ok()
+}
+
+/// Decodes a [`TRANSFER_EVENT_SIGNATURE`] event. Returns `None` for other events or
+/// malformed data.
+pub fn decode_token_transfer(event: &VmEvent) -> Option<TokenTransfer> {
+    let [signature, from, to] = event.indexed_topics.as_slice() else {
+        return None;
+    };
+    if *signature != *TRANSFER_EVENT_SIGNATURE || event.value.len() != 8 {
+        return None;
+    }
+    let amount = bytes_to_u64s(event.value.clone())[0];
+    Some(TokenTransfer {
+        token: event.address,
+        from: *from,
+        to: *to,
+        amount: amount.into(),
+    })
+}
+
+/// Returns tokens initialized by `events` of contracts for which `is_deployed` holds,
+/// i.e. which are deployed in the same miniblock.
+pub fn extract_deployed_tokens(
+    events: &[VmEvent],
+    is_deployed: impl Fn(&Address) -> bool,
+) -> Vec<(Address, TokenMetadata)> {
+    events
+        .iter()
+        .filter(|event| is_deployed(&event.address))
+        .filter_map(|event| Some((event.address, decode_token_metadata(event)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ola_utils::convert::u64s_to_bytes;
+
+    use super::*;
+
+    fn encode_string(s: &str) -> Vec<u64> {
+        let mut fields = vec![s.len() as u64];
+        fields.extend(s.bytes().map(u64::from));
+        fields
+    }
+
+    fn metadata_event(address: Address, fields: &[u64]) -> VmEvent {
+        VmEvent {
+            address,
+            indexed_topics: vec![*TOKEN_INITIALIZED_EVENT_SIGNATURE],
+            value: u64s_to_bytes(fields),
+            ..VmEvent::default()
+        }
+    }
+
+    #[test]
+    fn decoding_token_metadata() {
+        let mut fields = encode_string("Ola Token");
+        fields.extend(encode_string("OLA"));
+        fields.push(6);
+        let event = metadata_event(Address::repeat_byte(1), &fields);
+        assert_eq!(
+            decode_token_metadata(&event),
+            Some(TokenMetadata {
+                name: "Ola Token".to_owned(),
+                symbol: "OLA".to_owned(),
+                decimals: 6,
+            })
+        );
+
+        let truncated = metadata_event(Address::repeat_byte(1), &fields[..fields.len() - 1]);
+        assert_eq!(decode_token_metadata(&truncated), None);
+        let mut other_event = event.clone();
+        other_event.indexed_topics[0] = H256::repeat_byte(0xff);
+        assert_eq!(decode_token_metadata(&other_event), None);
+    }
+
+    #[test]
+    fn decoding_token_transfers() {
+        let event = VmEvent {
+            address: Address::repeat_byte(1),
+            indexed_topics: vec![
+                *TRANSFER_EVENT_SIGNATURE,
+                Address::zero(),
+                Address::repeat_byte(2),
+            ],
+            value: u64s_to_bytes(&[100]),
+            ..VmEvent::default()
+        };
+        assert_eq!(
+            decode_token_transfer(&event),
+            Some(TokenTransfer {
+                token: Address::repeat_byte(1),
+                from: Address::zero(),
+                to: Address::repeat_byte(2),
+                amount: 100.into(),
+            })
+        );
+
+        let mut malformed = event.clone();
+        malformed.indexed_topics.pop();
+        assert_eq!(decode_token_transfer(&malformed), None);
+    }
+
+    #[test]
+    fn extracting_deployed_tokens() {
+        let mut fields = encode_string("Token");
+        fields.extend(encode_string("TKN"));
+        fields.push(18);
+        let deployed = Address::repeat_byte(1);
+        let events = [
+            metadata_event(deployed, &fields),
+            metadata_event(Address::repeat_byte(2), &fields),
+        ];
+
+        let tokens = extract_deployed_tokens(&events, |address| *address == deployed);
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].0, deployed);
+        assert_eq!(tokens[0].1.symbol, "TKN");
+    }
+}
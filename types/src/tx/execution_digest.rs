@@ -0,0 +1,97 @@
+//! Digests of transaction effects, used to compare the simulation of a transaction at submission
+//! with its actual execution.
+
+use std::collections::BTreeMap;
+
+use ola_utils::hash::hash_bytes;
+use serde::{Deserialize, Serialize};
+
+use crate::{events::VmEvent, StorageLog, H256};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionDigest {
+    /// Hash of the final values of slots written by the transaction, ordered by hashed key.
+    pub write_set_hash: H256,
+    /// Hash of events emitted by the transaction in the emission order.
+    pub events_hash: H256,
+}
+
+impl ExecutionDigest {
+    /// Computes the digest from the storage writes of a transaction in the execution order
+    /// and its events. If a slot is written several times, only the last value is accounted for.
+    pub fn new<'a>(
+        writes: impl IntoIterator<Item = &'a StorageLog>,
+        events: impl IntoIterator<Item = &'a VmEvent>,
+    ) -> Self {
+        let write_set: BTreeMap<_, _> = writes
+            .into_iter()
+            .map(|log| (log.key.hashed_key(), log))
+            .collect();
+        let mut write_set_bytes = Vec::with_capacity(write_set.len() * 96);
+        for log in write_set.values() {
+            write_set_bytes.extend_from_slice(log.key.address().as_bytes());
+            write_set_bytes.extend_from_slice(log.key.key().as_bytes());
+            write_set_bytes.extend_from_slice(log.value.as_bytes());
+        }
+
+        let mut events_bytes = vec![];
+        for event in events {
+            events_bytes.extend_from_slice(event.address.as_bytes());
+            events_bytes.extend_from_slice(&(event.indexed_topics.len() as u64).to_be_bytes());
+            for topic in &event.indexed_topics {
+                events_bytes.extend_from_slice(topic.as_bytes());
+            }
+            events_bytes.extend_from_slice(&(event.value.len() as u64).to_be_bytes());
+            events_bytes.extend_from_slice(&event.value);
+        }
+
+        Self {
+            write_set_hash: hash_bytes(&write_set_bytes),
+            events_hash: hash_bytes(&events_bytes),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AccountTreeId, Address, L1BatchNumber, StorageKey};
+
+    fn write(slot: u8, value: u8) -> StorageLog {
+        let key = StorageKey::new(
+            AccountTreeId::new(Address::repeat_byte(1)),
+            H256::repeat_byte(slot),
+        );
+        StorageLog::new_write_log(key, H256::repeat_byte(value))
+    }
+
+    fn event(value: u8) -> VmEvent {
+        VmEvent {
+            location: (L1BatchNumber(1), 0),
+            address: Address::repeat_byte(2),
+            indexed_topics: vec![H256::repeat_byte(3)],
+            value: vec![value],
+        }
+    }
+
+    #[test]
+    fn write_set_hash_depends_on_final_values_only() {
+        let events = [event(1)];
+        let digest = ExecutionDigest::new(&[write(1, 1), write(2, 2)], &events);
+        let reordered = ExecutionDigest::new(&[write(2, 2), write(1, 0), write(1, 1)], &events);
+        assert_eq!(digest, reordered);
+
+        let changed = ExecutionDigest::new(&[write(1, 1), write(2, 3)], &events);
+        assert_ne!(changed.write_set_hash, digest.write_set_hash);
+        assert_eq!(changed.events_hash, digest.events_hash);
+    }
+
+    #[test]
+    fn events_hash_depends_on_order() {
+        let digest = ExecutionDigest::new(&[], &[event(1), event(2)]);
+        let reordered = ExecutionDigest::new(&[], &[event(2), event(1)]);
+        assert_ne!(digest.events_hash, reordered.events_hash);
+        assert_eq!(digest.write_set_hash, reordered.write_set_hash);
+    }
+}
@@ -5,6 +5,7 @@ use crate::{vm_trace::Call, Transaction};
 use self::tx_execution_info::{ExecutionMetrics, TxExecutionStatus};
 
 pub mod execute;
+pub mod execution_digest;
 pub mod primitives;
 pub mod tx_execution_info;
 
@@ -5,7 +5,7 @@ use ola_utils::{
     hash::{hash_bytes, PoseidonBytes},
 };
 use parity_crypto::publickey::{
-    sign, Error as ParityCryptoError, KeyPair, Signature as ETHSignature,
+    recover, sign, Error as ParityCryptoError, KeyPair, Signature as ETHSignature,
 };
 use thiserror::Error;
 
@@ -105,6 +105,19 @@ impl PackedEthSignature {
         PackedEthSignature::sign_raw(private_key, &signed_bytes)
     }
 
+    /// Checks whether `signed_bytes` were signed by `signer`. Packed signatures don't retain `v`,
+    /// so both recovery IDs are tried.
+    pub fn is_signed_by(&self, signed_bytes: &H256, signer: &Address) -> bool {
+        let message = ParityCryptoH256::from_slice(&signed_bytes.0);
+        let mut signature = [0u8; 65];
+        signature[..64].copy_from_slice(&self.serialize_packed_without_v());
+        (0..2).any(|recovery_id| {
+            signature[64] = recovery_id;
+            recover(&ETHSignature::from(signature), &message)
+                .map_or(false, |public| H256(public.hash_bytes()) == *signer)
+        })
+    }
+
     pub fn address_from_private_key(private_key: &H256) -> Result<Address, ParityCryptoError> {
         let private_key = ParityCryptoH256::from_slice(&private_key.0);
         let pair = KeyPair::from_secret(private_key.into())?;
@@ -119,3 +132,23 @@ pub enum DeserializeError {
     #[error("Eth signature length should be 65 bytes")]
     IncorrectSignatureLength,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checking_signer_of_packed_signature() {
+        let private_key = H256::repeat_byte(0x11);
+        let signer = PackedEthSignature::address_from_private_key(&private_key).unwrap();
+        let message = H256::repeat_byte(0x22);
+        let signature = PackedEthSignature::sign_raw(&private_key, &message).unwrap();
+        let packed =
+            PackedEthSignature::deserialize_packed(&signature.serialize_packed_without_v())
+                .unwrap();
+
+        assert!(packed.is_signed_by(&message, &signer));
+        assert!(!packed.is_signed_by(&H256::repeat_byte(0x23), &signer));
+        assert!(!packed.is_signed_by(&message, &Address::repeat_byte(1)));
+    }
+}
@@ -1,6 +1,15 @@
-use std::ops::{Add, AddAssign};
+use std::{
+    collections::BTreeMap,
+    ops::{Add, AddAssign},
+};
 
-use crate::{events::VmEvent, fee::TransactionExecutionMetrics, log::StorageLogQuery};
+use ola_basic_types::Address;
+
+use crate::{
+    events::VmEvent,
+    fee::TransactionExecutionMetrics,
+    log::{LogQuery, StorageLogQuery},
+};
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize)]
 pub struct ExecutionMetrics {
@@ -50,6 +59,54 @@ impl AddAssign for ExecutionMetrics {
     }
 }
 
+/// Resources consumed by a single contract within an L1 batch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ContractExecutionMetrics {
+    pub address: Address,
+    pub tx_count: u32,
+    pub cycles_used: u64,
+    pub storage_reads: u32,
+    pub storage_writes: u32,
+    pub events: u32,
+}
+
+impl ContractExecutionMetrics {
+    /// Aggregates resource consumption per contract. Storage accesses and events are attributed
+    /// to the contract performing them; transactions and their cycles are attributed
+    /// to the called contract. The output is sorted by address.
+    pub fn aggregate<'a>(
+        transactions: impl IntoIterator<Item = (Address, &'a ExecutionMetrics)>,
+        storage_logs: impl IntoIterator<Item = &'a LogQuery>,
+        events: &[VmEvent],
+    ) -> Vec<Self> {
+        let mut metrics = BTreeMap::<Address, Self>::new();
+        let mut entry = |address: Address| {
+            metrics.entry(address).or_insert_with(|| Self {
+                address,
+                ..Self::default()
+            })
+        };
+
+        for (contract_address, execution_metrics) in transactions {
+            let contract = entry(contract_address);
+            contract.tx_count += 1;
+            contract.cycles_used += u64::from(execution_metrics.cycles_used);
+        }
+        for log in storage_logs {
+            let contract = entry(log.address);
+            if log.rw_flag {
+                contract.storage_writes += 1;
+            } else {
+                contract.storage_reads += 1;
+            }
+        }
+        for event in events {
+            entry(event.address).events += 1;
+        }
+        metrics.into_values().collect()
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct DeduplicatedWritesMetrics {
     pub initial_storage_writes: usize,
@@ -87,3 +144,70 @@ pub struct VmExecutionLogs {
     pub events: Vec<VmEvent>,
     pub total_log_queries_count: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use ola_basic_types::U256;
+
+    use super::*;
+    use crate::log::Timestamp;
+
+    fn log_query(address: Address, rw_flag: bool) -> LogQuery {
+        LogQuery {
+            timestamp: Timestamp(0),
+            tx_number_in_block: 0,
+            aux_byte: 0,
+            shard_id: 0,
+            address,
+            key: U256::zero(),
+            read_value: U256::zero(),
+            written_value: U256::zero(),
+            rw_flag,
+            rollback: false,
+            is_service: false,
+        }
+    }
+
+    #[test]
+    fn aggregating_contract_execution_metrics() {
+        let first = Address::repeat_byte(1);
+        let second = Address::repeat_byte(2);
+        let tx_metrics = ExecutionMetrics {
+            cycles_used: 100,
+            ..ExecutionMetrics::default()
+        };
+        let logs = [
+            log_query(second, false),
+            log_query(second, true),
+            log_query(second, true),
+        ];
+        let events = [VmEvent {
+            address: first,
+            ..VmEvent::default()
+        }];
+
+        let metrics = ContractExecutionMetrics::aggregate(
+            [(first, &tx_metrics), (first, &tx_metrics)],
+            &logs,
+            &events,
+        );
+        assert_eq!(
+            metrics,
+            [
+                ContractExecutionMetrics {
+                    address: first,
+                    tx_count: 2,
+                    cycles_used: 200,
+                    events: 1,
+                    ..ContractExecutionMetrics::default()
+                },
+                ContractExecutionMetrics {
+                    address: second,
+                    storage_reads: 1,
+                    storage_writes: 2,
+                    ..ContractExecutionMetrics::default()
+                },
+            ]
+        );
+    }
+}
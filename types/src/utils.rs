@@ -1,9 +1,13 @@
 use std::time::SystemTime;
 
-use ola_basic_types::U256;
+use ola_basic_types::{Address, H256, U256};
+use ola_utils::{convert::h256_to_u64_array, hash::hash_bytes, u64s_to_bytes};
 
 use crate::system_contracts::DEPLOYMENT_NONCE_INCREMENT;
 
+/// Domain separator of [`compute_create2_address()`], encoded as one `u64` limb per character.
+pub const CREATE2_PREFIX: &str = "OlaCreate2";
+
 pub fn unix_timestamp_ms() -> u64 {
     SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
@@ -17,3 +21,22 @@ pub fn decompose_full_nonce(full_nonce: U256) -> (U256, U256) {
         full_nonce / DEPLOYMENT_NONCE_INCREMENT,
     )
 }
+
+/// Computes the address of a contract deployed by `deployer` via `ContractDeployer.create2`:
+///
+/// ```text
+/// poseidon(poseidon(limbs(CREATE2_PREFIX)) ‖ deployer ‖ limbs(salt) ‖ bytecode_hash)
+/// ```
+///
+/// where `limbs` encodes a sequence of `u64` limbs as big-endian bytes and `‖` is concatenation.
+/// This mirrors the derivation in the deployer system contract, so the address is known before
+/// the deployment transaction is sent.
+pub fn compute_create2_address(deployer: &Address, salt: &H256, bytecode_hash: &H256) -> Address {
+    let prefix: Vec<u64> = CREATE2_PREFIX.chars().map(|c| c as u64).collect();
+    let mut input = Vec::with_capacity(4 * 32);
+    input.extend_from_slice(hash_bytes(&u64s_to_bytes(&prefix)).as_bytes());
+    input.extend_from_slice(deployer.as_bytes());
+    input.extend_from_slice(&u64s_to_bytes(&h256_to_u64_array(salt)));
+    input.extend_from_slice(bytecode_hash.as_bytes());
+    hash_bytes(&input)
+}
@@ -21,6 +21,10 @@ pub enum CallType {
     NearCall,
 }
 
+/// Version of the `bincode` layout of [`Call`] persisted in call traces. Bump it on any change
+/// of the `Call` fields and keep a legacy layout to decode the previous versions.
+pub const CALL_TRACE_FORMAT_VERSION: i16 = 1;
+
 // TODO: @Pierre
 #[derive(Clone, Serialize, Deserialize)]
 /// Represents a call in the VM trace.
@@ -35,6 +39,9 @@ pub struct Call {
     pub input: Vec<u8>,
     /// Output data.
     pub output: Vec<u8>,
+    /// Size of the full output if `output` was truncated before storing.
+    #[serde(default)]
+    pub truncated_output_size: Option<u64>,
     /// Error message provided by vm or some unexpected errors.
     pub error: Option<String>,
     /// Revert reason.
@@ -52,6 +59,7 @@ impl PartialEq for Call {
             && self.r#type == other.r#type
             && self.error == other.error
             && self.output == other.output
+            && self.truncated_output_size == other.truncated_output_size
             && self.calls == other.calls
     }
 }
@@ -64,6 +72,7 @@ impl Default for Call {
             to: Default::default(),
             input: vec![],
             output: vec![],
+            truncated_output_size: None,
             error: None,
             revert_reason: None,
             calls: vec![],
@@ -79,6 +88,7 @@ impl fmt::Debug for Call {
             .field("from", &self.from)
             .field("input", &format_args!("{:?}", self.input))
             .field("output", &format_args!("{:?}", self.output))
+            .field("truncated_output_size", &self.truncated_output_size)
             .field("error", &self.error)
             .field("revert_reason", &format_args!("{:?}", self.revert_reason))
             .field("call_traces", &self.calls)
@@ -99,11 +109,72 @@ impl Call {
             to: ENTRYPOINT_ADDRESS,
             input,
             output,
+            truncated_output_size: None,
             error: None,
             revert_reason,
             calls,
         }
     }
+
+    /// Truncates outputs of this call and all its subcalls to `max_size` bytes, recording
+    /// the full sizes of truncated outputs. Returns the number of truncated outputs.
+    pub fn truncate_outputs(&mut self, max_size: usize) -> usize {
+        let mut truncated_count = 0;
+        if self.output.len() > max_size {
+            self.truncated_output_size = Some(self.output.len() as u64);
+            self.output.truncate(max_size);
+            truncated_count += 1;
+        }
+        for call in &mut self.calls {
+            truncated_count += call.truncate_outputs(max_size);
+        }
+        truncated_count
+    }
+
+    /// Serializes the call to be persisted with [`CALL_TRACE_FORMAT_VERSION`].
+    pub fn encode_stored(&self) -> bincode::Result<Vec<u8>> {
+        bincode::serialize(self)
+    }
+
+    /// Deserializes a call persisted with the specified format version.
+    pub fn decode_stored(format_version: i16, bytes: &[u8]) -> bincode::Result<Self> {
+        match format_version {
+            0 => bincode::deserialize::<CallV0>(bytes).map(Self::from),
+            CALL_TRACE_FORMAT_VERSION => bincode::deserialize(bytes),
+            _ => Err(Box::new(bincode::ErrorKind::Custom(format!(
+                "unknown call trace format version {format_version}"
+            )))),
+        }
+    }
+}
+
+/// Layout of [`Call`] persisted before truncated outputs were recorded (format version 0).
+#[derive(Serialize, Deserialize)]
+struct CallV0 {
+    r#type: CallType,
+    from: Address,
+    to: Address,
+    input: Vec<u8>,
+    output: Vec<u8>,
+    error: Option<String>,
+    revert_reason: Option<String>,
+    calls: Vec<CallV0>,
+}
+
+impl From<CallV0> for Call {
+    fn from(call: CallV0) -> Self {
+        Self {
+            r#type: call.r#type,
+            from: call.from,
+            to: call.to,
+            input: call.input,
+            output: call.output,
+            truncated_output_size: None,
+            error: call.error,
+            revert_reason: call.revert_reason,
+            calls: call.calls.into_iter().map(Self::from).collect(),
+        }
+    }
 }
 
 fn far_call_type_from_u8<'de, D>(deserializer: D) -> Result<FarCallOpcode, D::Error>
@@ -125,3 +196,42 @@ where
 {
     s.serialize_u8(*far_call_type as u8)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decoding_stored_calls() {
+        let legacy = CallV0 {
+            r#type: CallType::Call(FarCallOpcode::Normal),
+            from: Address::repeat_byte(1),
+            to: Address::repeat_byte(2),
+            input: vec![1, 2],
+            output: vec![3; 8],
+            error: None,
+            revert_reason: Some("reverted".to_owned()),
+            calls: vec![CallV0 {
+                r#type: CallType::Create,
+                from: Address::repeat_byte(2),
+                to: Address::repeat_byte(3),
+                input: vec![],
+                output: vec![4],
+                error: Some("failed".to_owned()),
+                revert_reason: None,
+                calls: vec![],
+            }],
+        };
+        let bytes = bincode::serialize(&legacy).unwrap();
+        let mut call = Call::decode_stored(0, &bytes).unwrap();
+        assert_eq!(call.output, [3; 8]);
+        assert_eq!(call.calls[0].error.as_deref(), Some("failed"));
+
+        assert_eq!(call.truncate_outputs(4), 1);
+        let bytes = call.encode_stored().unwrap();
+        let decoded = Call::decode_stored(CALL_TRACE_FORMAT_VERSION, &bytes).unwrap();
+        assert_eq!(decoded, call);
+        assert_eq!(decoded.truncated_output_size, Some(8));
+        assert!(Call::decode_stored(CALL_TRACE_FORMAT_VERSION + 1, &bytes).is_err());
+    }
+}
@@ -6,5 +6,6 @@ pub mod env_tools;
 pub mod hash;
 pub mod misc;
 pub mod panic_extractor;
+pub mod retry;
 pub mod time;
 pub mod wait_for_tasks;
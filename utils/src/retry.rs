@@ -0,0 +1,206 @@
+//! Retries with exponential backoff and jitter.
+//!
+//! [`RetryPolicy`] describes how an operation is retried; it can either drive retries of a fallible
+//! future itself ([`RetryPolicy::retry()`]), or produce a [`Backoff`] for polling loops that sleep
+//! between iterations. Each retry is counted in the `retry.attempts` metric labeled with the retry site.
+
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use rand::Rng;
+
+const RETRY_ATTEMPTS_METRIC: &str = "retry.attempts";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    initial_interval: Duration,
+    max_interval: Duration,
+    multiplier: f64,
+    jitter: f64,
+    max_attempts: Option<u32>,
+    max_elapsed: Option<Duration>,
+}
+
+impl RetryPolicy {
+    /// Creates a policy retrying indefinitely with the constant `interval` between attempts.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            initial_interval: interval,
+            max_interval: interval,
+            multiplier: 1.0,
+            jitter: 0.0,
+            max_attempts: None,
+            max_elapsed: None,
+        }
+    }
+
+    /// Multiplies the interval by `multiplier` after each attempt, up to `max_interval`.
+    #[must_use]
+    pub fn with_exponential_backoff(mut self, multiplier: f64, max_interval: Duration) -> Self {
+        assert!(multiplier >= 1.0, "backoff multiplier must be at least 1");
+        self.multiplier = multiplier;
+        self.max_interval = max_interval.max(self.initial_interval);
+        self
+    }
+
+    /// Randomizes each interval by up to `jitter` of its value in either direction, so that
+    /// processes retrying the same resource don't do it in lockstep.
+    #[must_use]
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        assert!((0.0..=1.0).contains(&jitter), "jitter must be in [0, 1]");
+        self.jitter = jitter;
+        self
+    }
+
+    /// Limits the number of attempts, including the first one.
+    #[must_use]
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        assert!(max_attempts > 0, "number of attempts must be positive");
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// Stops retrying once `max_elapsed` has passed since the first attempt.
+    #[must_use]
+    pub fn with_max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = Some(max_elapsed);
+        self
+    }
+
+    /// Starts a backoff for a retry site, e.g. a polling loop.
+    pub fn backoff(&self, site: &'static str) -> Backoff {
+        Backoff {
+            policy: *self,
+            site,
+            attempts: 0,
+            interval: self.initial_interval,
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Calls `operation` until it succeeds or the policy is exhausted, returning the last error
+    /// in the latter case.
+    pub async fn retry<T, E, F, Fut>(&self, site: &'static str, mut operation: F) -> Result<T, E>
+    where
+        E: std::fmt::Display,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut backoff = self.backoff(site);
+        loop {
+            let err = match operation().await {
+                Ok(value) => return Ok(value),
+                Err(err) => err,
+            };
+            let Some(delay) = backoff.next_delay() else {
+                return Err(err);
+            };
+            olaos_logs::warn!(
+                "Attempt #{} at `{site}` failed: {err}; retrying in {delay:?}",
+                backoff.attempts
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Backoff state of a single retry site.
+#[derive(Debug)]
+pub struct Backoff {
+    policy: RetryPolicy,
+    site: &'static str,
+    attempts: u32,
+    interval: Duration,
+    started_at: Instant,
+}
+
+impl Backoff {
+    /// Returns the delay before the next attempt, or `None` if the policy is exhausted.
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        self.attempts += 1;
+        let policy = &self.policy;
+        if policy
+            .max_attempts
+            .map_or(false, |max_attempts| self.attempts >= max_attempts)
+        {
+            return None;
+        }
+        if policy.max_elapsed.map_or(false, |max_elapsed| {
+            self.started_at.elapsed() >= max_elapsed
+        }) {
+            return None;
+        }
+
+        let interval = self.interval;
+        self.interval = interval.mul_f64(policy.multiplier).min(policy.max_interval);
+        metrics::counter!(RETRY_ATTEMPTS_METRIC, 1, "site" => self.site);
+        Some(Self::jittered(interval, policy.jitter))
+    }
+
+    /// Resets the backoff after a successful attempt.
+    pub fn reset(&mut self) {
+        self.attempts = 0;
+        self.interval = self.policy.initial_interval;
+        self.started_at = Instant::now();
+    }
+
+    fn jittered(interval: Duration, jitter: f64) -> Duration {
+        if jitter == 0.0 {
+            return interval;
+        }
+        let factor = rand::thread_rng().gen_range(1.0 - jitter..=1.0 + jitter);
+        interval.mul_f64(factor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_grows_up_to_max_interval() {
+        let policy = RetryPolicy::new(Duration::from_millis(100))
+            .with_exponential_backoff(2.0, Duration::from_millis(300));
+        let mut backoff = policy.backoff("test");
+        let delays: Vec<_> = (0..4).map(|_| backoff.next_delay().unwrap()).collect();
+        assert_eq!(
+            delays,
+            [100, 200, 300, 300].map(Duration::from_millis).to_vec()
+        );
+
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn backoff_respects_max_attempts_and_jitter() {
+        let policy = RetryPolicy::new(Duration::from_millis(100))
+            .with_jitter(0.5)
+            .with_max_attempts(3);
+        let mut backoff = policy.backoff("test");
+        for _ in 0..2 {
+            let delay = backoff.next_delay().unwrap();
+            assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(150));
+        }
+        assert_eq!(backoff.next_delay(), None);
+    }
+
+    #[tokio::test]
+    async fn retrying_fallible_operation() {
+        let policy = RetryPolicy::new(Duration::from_millis(1)).with_max_attempts(3);
+        let mut calls = 0;
+        let result = policy
+            .retry("test", || {
+                calls += 1;
+                let result = if calls < 3 { Err("failure") } else { Ok(calls) };
+                async move { result }
+            })
+            .await;
+        assert_eq!(result, Ok(3));
+
+        let result: Result<(), _> = policy.retry("test", || async { Err("failure") }).await;
+        assert_eq!(result, Err("failure"));
+    }
+}
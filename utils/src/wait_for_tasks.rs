@@ -1,3 +1,5 @@
+use std::fmt;
+
 use futures::{future, Future};
 use tokio::task::JoinHandle;
 
@@ -11,7 +13,10 @@ pub async fn wait_for_tasks<Fut>(
     Fut: Future<Output = ()>,
 {
     match future::select_all(task_futures).await.0 {
-        Ok(_) => {
+        Ok(result) => {
+            if let Err(err) = result {
+                olaos_logs::error!("One of the actors failed: {err:#}");
+            }
             if tasks_allowed_to_finish {
                 olaos_logs::error!("One of the actors finished its run. Finishing execution.");
             } else {
@@ -36,3 +41,57 @@ pub async fn wait_for_tasks<Fut>(
         }
     }
 }
+
+/// How the first finished task among the supervised ones has terminated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskOutcome {
+    /// The task returned `Ok(())`.
+    Finished,
+    /// The task returned an error; contains the full error chain.
+    Failed(String),
+    /// The task panicked or was cancelled.
+    Panicked(String),
+}
+
+impl TaskOutcome {
+    pub fn is_failure(&self) -> bool {
+        !matches!(self, Self::Finished)
+    }
+}
+
+/// Waits until any of the labeled tasks terminates and reports which one it was and how it
+/// terminated. The terminated task is removed from `tasks`; the remaining tasks are not aborted,
+/// the caller is expected to signal them to stop.
+///
+/// # Panics
+///
+/// Panics if `tasks` is empty.
+pub async fn wait_for_first_task<L>(
+    tasks: &mut Vec<(L, JoinHandle<anyhow::Result<()>>)>,
+) -> (L, TaskOutcome)
+where
+    L: fmt::Debug,
+{
+    let (result, index, _) = future::select_all(tasks.iter_mut().map(|(_, handle)| handle)).await;
+    let (label, _) = tasks.swap_remove(index);
+
+    let outcome = match result {
+        Ok(Ok(())) => TaskOutcome::Finished,
+        Ok(Err(err)) => TaskOutcome::Failed(format!("{err:#}")),
+        Err(err) => TaskOutcome::Panicked(try_extract_panic_message(err)),
+    };
+    match &outcome {
+        TaskOutcome::Finished => {
+            olaos_logs::error!(
+                "Task of {label:?} finished its run, while it wasn't expected to do it"
+            );
+        }
+        TaskOutcome::Failed(err) => {
+            olaos_logs::error!("Task of {label:?} failed: {err}");
+        }
+        TaskOutcome::Panicked(panic_message) => {
+            olaos_logs::error!("Task of {label:?} panicked: {panic_message}");
+        }
+    }
+    (label, outcome)
+}
@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-use ola_types::{Address, U256};
+use ola_types::{system_contracts::find_system_contract, Address, U256};
 
 #[derive(Debug, Clone)]
 pub struct ValidationTracerParams {
@@ -14,3 +14,11 @@ pub struct ValidationTracerParams {
     /// stored in the beacon.
     pub trusted_address_slots: HashSet<(Address, U256)>,
 }
+
+impl ValidationTracerParams {
+    /// Checks whether the validation may access any slot of `address`. System contracts are
+    /// always trusted.
+    pub fn is_trusted_address(&self, address: &Address) -> bool {
+        self.trusted_addresses.contains(address) || find_system_contract(address).is_some()
+    }
+}
@@ -43,6 +43,7 @@ impl VmPartialExecutionResult {
         storage_access_logs: &Vec<StorageAccessLog>,
         events: &Vec<Event>,
         tx_index_in_l1_batch: u32,
+        cycles_used: u32,
     ) -> Self {
         let storage_logs: Vec<StorageLogQuery> = storage_access_logs
             .iter()
@@ -66,11 +67,18 @@ impl VmPartialExecutionResult {
             logs,
             revert_reason: None,
             contracts_used: 0,
-            cycles_used: 0,
+            cycles_used,
         }
     }
 }
 
+/// Returns the number of VM cycles in the execution trace of a transaction, i.e. the number
+/// of CPU steps across all contract calls of the transaction.
+pub fn cycles_in_trace(trace: &TxExeTrace) -> u32 {
+    let cycles: usize = trace.cpu.iter().map(|(_, _, _, steps)| steps.len()).sum();
+    cycles.try_into().unwrap_or(u32::MAX)
+}
+
 #[derive(Debug, Clone)]
 pub struct VmTxExeResult {
     pub status: TxExecutionStatus,
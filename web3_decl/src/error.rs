@@ -8,8 +8,8 @@ use std::{
 };
 
 use jsonrpsee::core::ClientError;
-use ola_types::api::SerializationTransactionError;
-use ola_types::{L1BatchNumber, MiniblockNumber};
+use ola_types::api::{SerializationTransactionError, TruncatedOutput};
+use ola_types::{L1BatchNumber, MiniblockNumber, H256};
 use pin_project_lite::pin_project;
 use thiserror::Error;
 
@@ -17,6 +17,10 @@ use thiserror::Error;
 pub enum Web3Error {
     #[error("Block with such an ID doesn't exist yet")]
     NoBlock,
+    #[error("Block with hash {0:?} is not found")]
+    UnknownBlockHash(H256),
+    #[error("Block with hash {0:?} is not canonical")]
+    NonCanonicalBlock(H256),
     #[error("Block with such an ID is pruned; the first retained block is {0}")]
     PrunedBlock(MiniblockNumber),
     #[error("L1 batch with such an ID is pruned; the first retained L1 batch is {0}")]
@@ -33,16 +37,59 @@ pub enum Web3Error {
     FilterNotFound,
     #[error("Query returned more than {0} results. Try with this block range [{1:#x}, {2:#x}].")]
     LogsLimitExceeded(usize, u32, u32),
+    #[error(
+        "Block {1:#x} alone has more than {0} logs matching the filter; narrow down addresses or topics"
+    )]
+    BlockLogsLimitExceeded(usize, u32),
+    #[error("Query spans more than {0} blocks. Try with this block range [{1:#x}, {2:#x}].")]
+    LogsBlockRangeExceeded(u32, u32, u32),
     #[error("invalid filter: if blockHash is supplied fromBlock and toBlock must not be")]
     InvalidFilterBlockHash,
     #[error("Not implemented")]
     NotImplemented,
+    #[error("Too many addresses requested; at most {0} are allowed")]
+    TooManyAddresses(usize),
     #[error("Tree API is not available")]
     TreeApiUnavailable,
+    #[error("Mempool is not available on this node")]
+    MempoolUnavailable,
+    #[error("VM execution is not available on this node")]
+    VmConcurrencyUnavailable,
+    #[error("Calldata screening is not enabled on this node")]
+    CalldataScreeningDisabled,
+    #[error("Transaction submission is disabled on read-only nodes")]
+    ReadOnlyNode,
     #[error("Internal error")]
     InternalError,
+    #[error("Storage is temporarily unavailable; retry later")]
+    StorageUnavailable,
+    #[error("Node is overloaded: the `{0}` thread pool is saturated; retry later")]
+    ThreadPoolSaturated(&'static str),
     #[error("Invalid l2 chainId `{0}`")]
     InvalidChainId(u16),
+    #[error("Invalid time range: start {0} is greater than end {1}")]
+    InvalidTimeRange(u64, u64),
+    #[error("Invalid block range: start {0} is greater than end {1}")]
+    InvalidBlockRange(MiniblockNumber, MiniblockNumber),
+    #[error("Pagination cursor was returned for another list")]
+    ForeignPageCursor,
+    #[error("Invalid reward percentiles: {0}")]
+    InvalidRewardPercentiles(&'static str),
+    #[error("Invalid attestation: {0}")]
+    InvalidAttestation(String),
+    #[error("Namespace `{0}` is unknown or can't be toggled")]
+    InvalidNamespace(String),
+    #[error("Execution ran out of the {0} budget of {1}ms")]
+    ExecutionBudgetExceeded(&'static str, u64),
+    #[error("Execution ran out of the {0} budget of {1} VM cycles")]
+    ExecutionCyclesExceeded(&'static str, u64),
+    #[error("Call output of {} bytes exceeds the limit of {1} bytes", .0.full_size)]
+    CallOutputTooLarge(TruncatedOutput, usize),
+    #[error(
+        "Block has {0} transactions, more than {1} allowed to be returned inline; \
+         use `ola_getBlockTransactions` to fetch them in pages"
+    )]
+    TooManyBlockTransactions(u64, usize),
 }
 
 /// Client RPC error with additional details: the method name and arguments of the called method.
@@ -0,0 +1,93 @@
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use ola_config::dump::ConfigDump;
+use ola_types::api::{
+    AuditAction, CalldataScreeningRules, ContractResourceKind, ContractResourceUsage,
+    MempoolStatus, StorageSample, VmConcurrencyStatus,
+};
+
+use crate::types::{AuditLogPage, PageCursor};
+
+/// Debugging methods exposing node internals. Only served if the admin namespace is enabled
+/// in the node configuration.
+#[cfg_attr(
+    all(feature = "client", feature = "server"),
+    rpc(server, client, namespace = "ola")
+)]
+#[cfg_attr(
+    all(feature = "client", not(feature = "server")),
+    rpc(client, namespace = "ola")
+)]
+#[cfg_attr(
+    all(not(feature = "client"), feature = "server"),
+    rpc(server, namespace = "ola")
+)]
+pub trait AdminNamespace {
+    /// Returns a snapshot of the sequencer mempool. Lists of accounts are capped
+    /// at `accounts_limit` entries; truncated lists are flagged in the response.
+    #[method(name = "getMempoolStatus")]
+    async fn get_mempool_status(
+        &self,
+        next_transactions_limit: Option<usize>,
+        accounts_limit: Option<usize>,
+    ) -> RpcResult<MempoolStatus>;
+
+    /// Returns the current VM concurrency limit of the API and its recent autotuner adjustments.
+    #[method(name = "getVmConcurrency")]
+    async fn get_vm_concurrency(&self) -> RpcResult<VmConcurrencyStatus>;
+
+    /// Returns the effective node configuration with secrets redacted.
+    #[method(name = "getConfig")]
+    async fn get_config(&self) -> RpcResult<ConfigDump>;
+
+    /// Returns contracts with the highest consumption of `resource` (storage ops by default)
+    /// in L1 batches sealed within `from_timestamp..=to_timestamp` (Unix seconds).
+    #[method(name = "getTopContracts")]
+    async fn get_top_contracts(
+        &self,
+        from_timestamp: u64,
+        to_timestamp: u64,
+        resource: Option<ContractResourceKind>,
+        limit: Option<usize>,
+    ) -> RpcResult<Vec<ContractResourceUsage>>;
+
+    /// Returns a page of audit log entries, optionally only for `action`, in the order
+    /// they were recorded. The page starts after `cursor` returned with the previous page.
+    #[method(name = "getAuditLog")]
+    async fn get_audit_log(
+        &self,
+        action: Option<AuditAction>,
+        cursor: Option<PageCursor<u64>>,
+        limit: Option<usize>,
+    ) -> RpcResult<AuditLogPage>;
+
+    /// Enables or disables all methods of the namespace (e.g., `eth` or `debug`) on every API
+    /// server of the node until it is restarted. Returns the namespaces that are disabled.
+    #[method(name = "setNamespaceEnabled")]
+    async fn set_namespace_enabled(
+        &self,
+        namespace: String,
+        enabled: bool,
+    ) -> RpcResult<Vec<String>>;
+
+    /// Samples `count` storage entries pseudorandomly derived from `seed` and returns them
+    /// with Merkle proofs against the latest root of the tree. Nodes with the same tree state
+    /// sample the same entries, so replicas can be cross-checked against the main node.
+    #[method(name = "sampleStorageProofs")]
+    async fn sample_storage_proofs(
+        &self,
+        seed: u64,
+        count: Option<usize>,
+    ) -> RpcResult<StorageSample>;
+
+    /// Returns calldata screening rules checked before mempool admission.
+    #[method(name = "getCalldataScreeningRules")]
+    async fn get_calldata_screening_rules(&self) -> RpcResult<CalldataScreeningRules>;
+
+    /// Replaces calldata screening rules until the node is restarted; returns the previous rules.
+    /// Only available if screening is enabled in the node configuration.
+    #[method(name = "setCalldataScreeningRules")]
+    async fn set_calldata_screening_rules(
+        &self,
+        rules: CalldataScreeningRules,
+    ) -> RpcResult<CalldataScreeningRules>;
+}
@@ -1,12 +1,12 @@
-use crate::types::PubSubFilter;
+use crate::types::{Filter, PubSubFilter};
 use jsonrpsee::{
     core::{RpcResult, SubscriptionResult},
     proc_macros::rpc,
 };
 use ola_types::{
     api::{
-        Block, BlockId, BlockIdVariant, BlockNumber, Transaction, TransactionReceipt,
-        TransactionVariant,
+        Block, BlockId, BlockIdVariant, BlockNumber, FeeHistory, Log, SyncState, Transaction,
+        TransactionReceipt, TransactionVariant,
     },
     Address, Index, H256, U256, U64,
 };
@@ -71,6 +71,17 @@ pub trait EthNamespace {
         block: Option<BlockIdVariant>,
     ) -> RpcResult<u32>;
 
+    #[method(name = "getStorageAt")]
+    async fn get_storage_at(
+        &self,
+        address: Address,
+        idx: U256,
+        block: Option<BlockIdVariant>,
+    ) -> RpcResult<H256>;
+
+    #[method(name = "getLogs")]
+    async fn get_logs(&self, filter: Filter) -> RpcResult<Vec<Log>>;
+
     #[method(name = "getTransactionByHash")]
     async fn get_transaction_by_hash(&self, hash: H256) -> RpcResult<Option<Transaction>>;
 
@@ -93,9 +104,37 @@ pub trait EthNamespace {
 
     #[method(name = "protocolVersion")]
     async fn protocol_version(&self) -> RpcResult<String>;
+
+    #[method(name = "syncing")]
+    async fn syncing(&self) -> RpcResult<SyncState>;
+
+    #[method(name = "accounts")]
+    async fn accounts(&self) -> RpcResult<Vec<Address>>;
+
+    #[method(name = "gasPrice")]
+    async fn gas_price(&self) -> RpcResult<U256>;
+
+    #[method(name = "feeHistory")]
+    async fn fee_history(
+        &self,
+        block_count: U64,
+        newest_block: BlockNumber,
+        reward_percentiles: Vec<f32>,
+    ) -> RpcResult<FeeHistory>;
 }
 
-#[rpc(server, namespace = "ola")]
+#[cfg_attr(
+    all(feature = "client", feature = "server"),
+    rpc(server, client, namespace = "ola")
+)]
+#[cfg_attr(
+    all(feature = "client", not(feature = "server")),
+    rpc(client, namespace = "ola")
+)]
+#[cfg_attr(
+    all(not(feature = "client"), feature = "server"),
+    rpc(server, namespace = "ola")
+)]
 pub trait EthPubSub {
     #[subscription(name = "subscribe" => "subscription", unsubscribe = "unsubscribe", item = PubSubResult)]
     async fn subscribe(&self, sub_type: String, filter: Option<PubSubFilter>)
@@ -1,5 +1,9 @@
 pub use jsonrpsee::core::RpcResult;
 
+pub mod admin;
 pub mod eth;
 pub mod net;
 pub mod ola;
+pub mod txpool;
+pub mod verifier;
+pub mod web3;
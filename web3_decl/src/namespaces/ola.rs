@@ -1,12 +1,18 @@
-use crate::types::Token;
+use crate::types::{
+    BlockTransactionsPage, L1BatchesPage, LogCursorKey, LogsPage, PageCursor, StorageHistoryPage,
+    StorageRangePage, Token, TransactionCursorKey, TransactionsPage,
+};
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 use ola_types::{
     api::{
         proof_offchain_verification::{
             L1BatchDetailsWithOffchainVerification, OffChainVerificationResult,
         },
-        BlockDetails, BridgeAddresses, L1BatchDetails, L2ToL1LogProof, Proof, ProtocolVersion,
-        TransactionDetails, TransactionReceipt,
+        AccountInfo, BlockDetails, BlockIdVariant, BlockNumber, BridgeAddresses,
+        CallResultWithStats, L1BatchDetails, L2ToL1LogProof, NodeInfo, Proof, ProtocolVersion,
+        ReorgedOutBlock, ReorgedOutTransaction, StateOverride, SystemContract, TokenBalance,
+        TokenDetails, TransactionDetails, TransactionInclusionProof, TransactionReceipt,
+        TransactionStatusInfo,
     },
     // fee::Fee,
     // fee_model::FeeParams,
@@ -38,8 +44,50 @@ pub trait OlaNamespace {
     #[method(name = "sendRawTransaction")]
     async fn send_raw_transaction(&self, tx_bytes: Bytes) -> RpcResult<H256>;
 
+    /// Same as `sendRawTransaction`, but simulates the transaction on top of the pending state
+    /// before admitting it, rejecting it if the simulation fails. Digests of the simulated storage
+    /// writes and events are compared with the actual ones once the transaction is sealed, and
+    /// the comparison is returned by `getTransactionStatus`. Nodes proxying transactions to
+    /// the main node don't record simulations.
+    #[method(name = "sendRawTransactionWithSimulation")]
+    async fn send_raw_transaction_with_simulation(&self, tx_bytes: Bytes) -> RpcResult<H256>;
+
+    /// Same as `sendRawTransaction`, but the transaction is held in the mempool until the chain
+    /// timestamp reaches `execute_after` (UNIX seconds). Until then, the transaction status is
+    /// reported as `scheduled`. `execute_after` isn't signed, so resubmitting the same pending
+    /// transaction with another schedule is rejected as a duplicate.
+    #[method(name = "sendScheduledTransaction")]
+    async fn send_scheduled_transaction(
+        &self,
+        tx_bytes: Bytes,
+        execute_after: u64,
+    ) -> RpcResult<H256>;
+
+    /// Executes a call on top of the `latest` (default) or `pending` block. Other blocks
+    /// aren't supported.
     #[method(name = "callTransaction")]
-    async fn call_transaction(&self, call_request: CallRequest) -> RpcResult<Bytes>;
+    async fn call_transaction(
+        &self,
+        call_request: CallRequest,
+        state_override: Option<StateOverride>,
+        block: Option<BlockNumber>,
+    ) -> RpcResult<Bytes>;
+
+    /// Same as `callTransaction`, but also returns execution statistics of the call. Unlike
+    /// `callTransaction`, which fails on outputs exceeding the output size limit, returns
+    /// such outputs truncated and marked as such.
+    #[method(name = "callTransactionWithStats")]
+    async fn call_transaction_with_stats(
+        &self,
+        call_request: CallRequest,
+        state_override: Option<StateOverride>,
+        block: Option<BlockNumber>,
+    ) -> RpcResult<CallResultWithStats>;
+
+    /// Returns the full output of a truncated call by its hash, or `null` if the output
+    /// isn't stored.
+    #[method(name = "getCallOutput")]
+    async fn get_call_output(&self, hash: H256) -> RpcResult<Option<Bytes>>;
 
     #[method(name = "getTransactionReceipt")]
     async fn get_transaction_receipt(&self, hash: H256) -> RpcResult<Option<TransactionReceipt>>;
@@ -53,6 +101,9 @@ pub trait OlaNamespace {
     #[method(name = "L1ChainId")]
     async fn l1_chain_id(&self) -> RpcResult<U64>;
 
+    #[method(name = "gasPrice")]
+    async fn gas_price(&self) -> RpcResult<U256>;
+
     #[method(name = "getL1BatchNumber")]
     async fn get_l1_batch_number(&self) -> RpcResult<U64>;
 
@@ -74,6 +125,18 @@ pub trait OlaNamespace {
         block_number: MiniblockNumber,
     ) -> RpcResult<Vec<ola_types::Transaction>>;
 
+    /// Returns a page of full transactions of the block ordered by the position in the block,
+    /// or `None` if the block doesn't exist. The page starts after `cursor` returned with
+    /// the previous page of the same block. Unlike `eth_getBlockByNumber`, works for blocks
+    /// with any number of transactions.
+    #[method(name = "getBlockTransactions")]
+    async fn get_block_transactions(
+        &self,
+        block: BlockNumber,
+        cursor: Option<PageCursor<TransactionCursorKey>>,
+        limit: Option<U64>,
+    ) -> RpcResult<Option<BlockTransactionsPage>>;
+
     #[method(name = "getL1BatchDetails")]
     async fn get_l1_batch_details(&self, batch: L1BatchNumber)
         -> RpcResult<Option<L1BatchDetails>>;
@@ -89,4 +152,109 @@ pub trait OlaNamespace {
         &self,
         batch: L1BatchNumber,
     ) -> RpcResult<Option<L1BatchDetailsWithOffchainVerification>>;
+
+    #[method(name = "getProof")]
+    async fn get_proof(&self, address: Address, keys: Vec<H256>) -> RpcResult<Proof>;
+
+    #[method(name = "nodeInfo")]
+    async fn node_info(&self) -> RpcResult<NodeInfo>;
+
+    #[method(name = "getAccountsInfo")]
+    async fn get_accounts_info(&self, addresses: Vec<Address>) -> RpcResult<Vec<AccountInfo>>;
+
+    #[method(name = "getTransactionStatus")]
+    async fn get_transaction_status(&self, hash: H256) -> RpcResult<Option<TransactionStatusInfo>>;
+
+    /// Returns system contracts deployed in the specified protocol version (by default,
+    /// the latest one).
+    #[method(name = "getSystemContracts")]
+    async fn get_system_contracts(&self, version_id: Option<u16>)
+        -> RpcResult<Vec<SystemContract>>;
+
+    /// Returns metadata of a token registered on deployment, or `None` if `address` is not
+    /// a known token.
+    #[method(name = "getTokenInfo")]
+    async fn get_token_info(&self, address: Address) -> RpcResult<Option<TokenDetails>>;
+
+    /// Returns non-zero balances of `account` in known tokens.
+    #[method(name = "getTokenBalances")]
+    async fn get_token_balances(&self, account: Address) -> RpcResult<Vec<TokenBalance>>;
+
+    /// Returns a Merkle proof that the transaction and its execution status are included in
+    /// the receipts root of its L1 batch, or `None` if the transaction isn't in a sealed batch.
+    #[method(name = "getTransactionInclusionProof")]
+    async fn get_transaction_inclusion_proof(
+        &self,
+        hash: H256,
+    ) -> RpcResult<Option<TransactionInclusionProof>>;
+
+    /// Returns a page of values of a storage slot at the end of each miniblock
+    /// in `[fromBlock, toBlock]` that changed it, ordered by the miniblock number. `toBlock`
+    /// defaults to the latest sealed miniblock. The page starts after `cursor` returned with
+    /// the previous page.
+    #[method(name = "getStorageHistory")]
+    async fn get_storage_history(
+        &self,
+        address: Address,
+        key: H256,
+        from_block: MiniblockNumber,
+        to_block: Option<MiniblockNumber>,
+        cursor: Option<PageCursor<MiniblockNumber>>,
+        limit: Option<usize>,
+    ) -> RpcResult<StorageHistoryPage>;
+
+    /// Returns miniblocks with the specified number that were removed from the chain by reverts.
+    #[method(name = "getReorgedOutBlocks")]
+    async fn get_reorged_out_blocks(
+        &self,
+        number: MiniblockNumber,
+    ) -> RpcResult<Vec<ReorgedOutBlock>>;
+
+    /// Returns inclusions of the transaction that were removed from the chain by reverts.
+    #[method(name = "getReorgedOutTransaction")]
+    async fn get_reorged_out_transaction(
+        &self,
+        hash: H256,
+    ) -> RpcResult<Vec<ReorgedOutTransaction>>;
+
+    /// Returns a page of logs of sealed miniblocks, optionally only emitted by `address`,
+    /// ordered by the miniblock number and the log index. The page starts after `cursor`
+    /// returned with the previous page, or from the first log if the cursor is not set.
+    #[method(name = "getLogsPage")]
+    async fn get_logs_page(
+        &self,
+        address: Option<Address>,
+        cursor: Option<PageCursor<LogCursorKey>>,
+        limit: Option<usize>,
+    ) -> RpcResult<LogsPage>;
+
+    /// Returns a page of receipts of sealed transactions initiated by `address`, ordered by
+    /// the miniblock number and the index in the miniblock.
+    #[method(name = "getTransactionsByAddress")]
+    async fn get_transactions_by_address(
+        &self,
+        address: Address,
+        cursor: Option<PageCursor<TransactionCursorKey>>,
+        limit: Option<usize>,
+    ) -> RpcResult<TransactionsPage>;
+
+    /// Returns a page of details of sealed L1 batches ordered by the number.
+    #[method(name = "getL1Batches")]
+    async fn get_l1_batches(
+        &self,
+        cursor: Option<PageCursor<L1BatchNumber>>,
+        limit: Option<usize>,
+    ) -> RpcResult<L1BatchesPage>;
+
+    /// Returns a page of non-zero storage slots of `address` as of the end of `block`
+    /// (the latest sealed miniblock if not set), ordered by the slot key. To page through
+    /// a consistent state, `block` should be set to a specific number or hash.
+    #[method(name = "getAccountStorageRange")]
+    async fn get_account_storage_range(
+        &self,
+        address: Address,
+        block: Option<BlockIdVariant>,
+        cursor: Option<PageCursor<H256>>,
+        limit: Option<usize>,
+    ) -> RpcResult<StorageRangePage>;
 }
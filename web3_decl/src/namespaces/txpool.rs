@@ -0,0 +1,24 @@
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use ola_types::api::TxpoolContent;
+
+/// Subset of the Ethereum `txpool` namespace for mempool monitoring. Served together with
+/// the admin namespace.
+#[cfg_attr(
+    all(feature = "client", feature = "server"),
+    rpc(server, client, namespace = "txpool")
+)]
+#[cfg_attr(
+    all(feature = "client", not(feature = "server")),
+    rpc(client, namespace = "txpool")
+)]
+#[cfg_attr(
+    all(not(feature = "client"), feature = "server"),
+    rpc(server, namespace = "txpool")
+)]
+pub trait TxpoolNamespace {
+    /// Returns mempool transactions grouped by sender and nonce, split into pending ones
+    /// and ones queued behind a nonce gap. The number of returned transactions is capped per
+    /// account and in total; `truncated` is set if some transactions were left out.
+    #[method(name = "content")]
+    async fn content(&self) -> RpcResult<TxpoolContent>;
+}
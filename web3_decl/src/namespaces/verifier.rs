@@ -0,0 +1,37 @@
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use ola_types::{
+    api::proof_offchain_verification::{L1BatchAttestations, VerifierAttestation},
+    L1BatchNumber,
+};
+
+/// Methods of the offchain verifier committee. Verifiers receive batches to verify via
+/// the `verificationTasks` subscription and reply with signed attestations; a batch is marked
+/// as verified once the quorum of the committee agrees on the outcome.
+#[cfg_attr(
+    all(feature = "client", feature = "server"),
+    rpc(server, client, namespace = "verifier")
+)]
+#[cfg_attr(
+    all(feature = "client", not(feature = "server")),
+    rpc(client, namespace = "verifier")
+)]
+#[cfg_attr(
+    all(not(feature = "client"), feature = "server"),
+    rpc(server, namespace = "verifier")
+)]
+pub trait VerifierNamespace {
+    /// Records an attestation of a committee member and returns the attestations collected
+    /// for the batch so far.
+    #[method(name = "submitAttestation")]
+    async fn submit_attestation(
+        &self,
+        attestation: VerifierAttestation,
+    ) -> RpcResult<L1BatchAttestations>;
+
+    /// Returns attestations for an L1 batch, or `null` if the batch isn't ready to be verified.
+    #[method(name = "getL1BatchAttestations")]
+    async fn get_l1_batch_attestations(
+        &self,
+        l1_batch_number: L1BatchNumber,
+    ) -> RpcResult<Option<L1BatchAttestations>>;
+}
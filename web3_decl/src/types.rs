@@ -13,7 +13,10 @@ use core::{
 
 use itertools::unfold;
 pub use ola_types::api::*;
-use ola_types::{Address, L1BatchNumber, H256};
+use ola_types::{
+    api::proof_offchain_verification::L1BatchVerificationTask, Address, Bytes, L1BatchNumber,
+    MiniblockNumber, H256, U64,
+};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 /// Token in the zkSync network
@@ -42,10 +45,13 @@ pub struct EIP2718TransactionCallData(TransactionCalldata);
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum PubSubResult {
+    Header(Block<H256>),
     Log(Log),
     TxHash(H256),
     Syncing(bool),
     L1BatchProof(L1BatchProofForVerify),
+    L1BatchStatus(L1BatchStatusUpdate),
+    VerificationTask(L1BatchVerificationTask),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -123,6 +129,10 @@ pub struct PubSubFilter {
     pub address: Option<ValueOrArray<H256>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub topics: Option<Vec<Option<ValueOrArray<H256>>>>,
+    /// Miniblock to replay `newHeads` or `logs` notifications from before streaming new ones,
+    /// e.g. the block after the last one received before a reconnect.
+    #[serde(rename = "fromBlock", skip_serializing_if = "Option::is_none")]
+    pub from_block: Option<U64>,
 }
 
 impl PubSubFilter {
@@ -148,3 +158,354 @@ impl PubSubFilter {
         true
     }
 }
+
+/// Filter of `eth_getLogs`. Either a block range or a single `blockHash` may be specified;
+/// an omitted range bound defaults to the latest sealed miniblock.
+#[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Filter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_block: Option<BlockNumber>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_block: Option<BlockNumber>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<ValueOrArray<Address>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topics: Option<Vec<Option<ValueOrArray<H256>>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<H256>,
+}
+
+impl Filter {
+    /// Returns addresses the filter is restricted to; empty if logs of any address match.
+    pub fn addresses(&self) -> &[Address] {
+        self.address
+            .as_ref()
+            .map_or(&[], |addresses| addresses.0.as_slice())
+    }
+
+    /// Returns topics to match by their position, skipping positions matching any topic.
+    pub fn topics_by_position(&self) -> Vec<(u32, Vec<H256>)> {
+        let Some(topics) = &self.topics else {
+            return vec![];
+        };
+        topics
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, topics)| {
+                let topics = topics.as_ref()?;
+                Some((idx as u32 + 1, topics.0.clone()))
+            })
+            .collect()
+    }
+}
+
+/// Key of an item in a paginated list. Lists are ordered by their keys in the ascending order,
+/// and pages continue strictly after the key of the last returned item (keyset pagination),
+/// so items added while a client pages through a list never shift it. All paginated lists
+/// only contain sealed data, which is appended after the existing keys.
+pub trait CursorKey: Sized {
+    /// Tag distinguishing cursors of different lists, so that a cursor of one list
+    /// is rejected by methods returning another one.
+    const KIND: u8;
+
+    fn to_words(&self) -> Vec<u64>;
+
+    fn from_words(words: &[u64]) -> Option<Self>;
+}
+
+/// Position of an event: ordered by the miniblock number and the index in the miniblock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LogCursorKey {
+    pub miniblock_number: MiniblockNumber,
+    pub event_index_in_block: u32,
+}
+
+impl CursorKey for LogCursorKey {
+    const KIND: u8 = 1;
+
+    fn to_words(&self) -> Vec<u64> {
+        vec![
+            self.miniblock_number.0.into(),
+            self.event_index_in_block.into(),
+        ]
+    }
+
+    fn from_words(words: &[u64]) -> Option<Self> {
+        let [miniblock_number, event_index_in_block] = *words else {
+            return None;
+        };
+        Some(Self {
+            miniblock_number: MiniblockNumber(miniblock_number.try_into().ok()?),
+            event_index_in_block: event_index_in_block.try_into().ok()?,
+        })
+    }
+}
+
+/// Position of a sealed transaction: ordered by the miniblock number and the index
+/// in the miniblock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TransactionCursorKey {
+    pub miniblock_number: MiniblockNumber,
+    pub index_in_block: u32,
+}
+
+impl CursorKey for TransactionCursorKey {
+    const KIND: u8 = 2;
+
+    fn to_words(&self) -> Vec<u64> {
+        vec![self.miniblock_number.0.into(), self.index_in_block.into()]
+    }
+
+    fn from_words(words: &[u64]) -> Option<Self> {
+        let [miniblock_number, index_in_block] = *words else {
+            return None;
+        };
+        Some(Self {
+            miniblock_number: MiniblockNumber(miniblock_number.try_into().ok()?),
+            index_in_block: index_in_block.try_into().ok()?,
+        })
+    }
+}
+
+impl CursorKey for L1BatchNumber {
+    const KIND: u8 = 3;
+
+    fn to_words(&self) -> Vec<u64> {
+        vec![self.0.into()]
+    }
+
+    fn from_words(words: &[u64]) -> Option<Self> {
+        let [number] = *words else {
+            return None;
+        };
+        Some(Self(number.try_into().ok()?))
+    }
+}
+
+/// Storage key of an account slot. Keys are ordered as big-endian integers.
+impl CursorKey for H256 {
+    const KIND: u8 = 4;
+
+    fn to_words(&self) -> Vec<u64> {
+        self.as_bytes()
+            .chunks_exact(8)
+            .map(|word| u64::from_be_bytes(word.try_into().unwrap()))
+            .collect()
+    }
+
+    fn from_words(words: &[u64]) -> Option<Self> {
+        if words.len() != 4 {
+            return None;
+        }
+        let bytes: Vec<_> = words.iter().flat_map(|word| word.to_be_bytes()).collect();
+        Some(Self::from_slice(&bytes))
+    }
+}
+
+/// Miniblock changing a storage slot.
+impl CursorKey for MiniblockNumber {
+    const KIND: u8 = 5;
+
+    fn to_words(&self) -> Vec<u64> {
+        vec![self.0.into()]
+    }
+
+    fn from_words(words: &[u64]) -> Option<Self> {
+        let [number] = *words else {
+            return None;
+        };
+        Some(Self(number.try_into().ok()?))
+    }
+}
+
+/// ID of an audit log entry.
+impl CursorKey for u64 {
+    const KIND: u8 = 6;
+
+    fn to_words(&self) -> Vec<u64> {
+        vec![*self]
+    }
+
+    fn from_words(words: &[u64]) -> Option<Self> {
+        let [id] = *words else {
+            return None;
+        };
+        Some(id)
+    }
+}
+
+/// Opaque pagination cursor. On the wire, it's a hex string encoding the key of the last item
+/// of a page; clients should only pass it back to the method that returned it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageCursor<K> {
+    key: K,
+}
+
+impl<K: CursorKey> PageCursor<K> {
+    pub fn new(key: K) -> Self {
+        Self { key }
+    }
+
+    /// Key of the last item of the previous page.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    fn encode(&self) -> Bytes {
+        let mut bytes = vec![K::KIND];
+        for word in self.key.to_words() {
+            bytes.extend_from_slice(&word.to_be_bytes());
+        }
+        Bytes(bytes)
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let (&kind, words) = bytes.split_first()?;
+        if kind != K::KIND || words.len() % 8 != 0 {
+            return None;
+        }
+        let words: Vec<_> = words
+            .chunks_exact(8)
+            .map(|word| u64::from_be_bytes(word.try_into().unwrap()))
+            .collect();
+        K::from_words(&words).map(Self::new)
+    }
+}
+
+impl<K: CursorKey> Serialize for PageCursor<K> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.encode().serialize(serializer)
+    }
+}
+
+impl<'de, K: CursorKey> Deserialize<'de> for PageCursor<K> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let bytes = Bytes::deserialize(deserializer)?;
+        Self::decode(&bytes.0)
+            .ok_or_else(|| de::Error::custom("invalid or foreign pagination cursor"))
+    }
+}
+
+/// Page of a list ordered by keys of type `K`. `next_cursor` is `None` on the last page.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    rename_all = "camelCase",
+    bound(
+        serialize = "T: Serialize, K: CursorKey",
+        deserialize = "T: Deserialize<'de>, K: CursorKey"
+    )
+)]
+pub struct Page<T, K> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<PageCursor<K>>,
+}
+
+impl<T, K: CursorKey> Page<T, K> {
+    /// Creates a page from up to `limit + 1` items, the extra item only signalling
+    /// that the list continues.
+    pub fn new(mut items: Vec<T>, limit: usize, key: impl Fn(&T) -> K) -> Self {
+        let next_cursor = if items.len() > limit {
+            items.truncate(limit);
+            items.last().map(|item| PageCursor::new(key(item)))
+        } else {
+            None
+        };
+        Self { items, next_cursor }
+    }
+}
+
+pub type LogsPage = Page<Log, LogCursorKey>;
+pub type TransactionsPage = Page<TransactionReceipt, TransactionCursorKey>;
+pub type L1BatchesPage = Page<L1BatchDetails, L1BatchNumber>;
+pub type StorageRangePage = Page<StorageEntry, H256>;
+pub type StorageHistoryPage = Page<StorageValueChange, MiniblockNumber>;
+pub type AuditLogPage = Page<AuditLogEntry, u64>;
+
+/// Page of transactions of a block returned by `ola_getBlockTransactions`. Transactions are
+/// ordered by the position in the block; `next_cursor` is `None` on the last page.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockTransactionsPage {
+    pub block_number: U64,
+    /// Total number of transactions in the block.
+    pub total_count: U64,
+    pub transactions: Vec<Transaction>,
+    pub next_cursor: Option<PageCursor<TransactionCursorKey>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_cursor_round_trip() {
+        let cursor = PageCursor::new(LogCursorKey {
+            miniblock_number: MiniblockNumber(42),
+            event_index_in_block: 3,
+        });
+        let json = serde_json::to_value(cursor).unwrap();
+        assert!(json.as_str().unwrap().starts_with("0x01"), "{json}");
+        let parsed: PageCursor<LogCursorKey> = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(parsed, cursor);
+
+        // Cursors of other lists and malformed cursors are rejected.
+        assert!(serde_json::from_value::<PageCursor<TransactionCursorKey>>(json).is_err());
+        let truncated = serde_json::json!("0x01000000000000002a");
+        assert!(serde_json::from_value::<PageCursor<LogCursorKey>>(truncated).is_err());
+        let batch_cursor = serde_json::to_value(PageCursor::new(L1BatchNumber(7))).unwrap();
+        assert_eq!(batch_cursor, "0x030000000000000007");
+
+        let key = H256(std::array::from_fn(|i| i as u8));
+        let key_cursor = serde_json::to_value(PageCursor::new(key)).unwrap();
+        assert_eq!(key_cursor.as_str().unwrap().len(), 2 + 2 * 33);
+        let parsed: PageCursor<H256> = serde_json::from_value(key_cursor).unwrap();
+        assert_eq!(*parsed.key(), key);
+
+        let history_cursor = serde_json::to_value(PageCursor::new(MiniblockNumber(7))).unwrap();
+        assert_eq!(history_cursor, "0x050000000000000007");
+        let audit_cursor = serde_json::to_value(PageCursor::new(7_u64)).unwrap();
+        assert_eq!(audit_cursor, "0x060000000000000007");
+        assert!(serde_json::from_value::<PageCursor<u64>>(history_cursor).is_err());
+    }
+
+    #[test]
+    fn parsing_logs_filter() {
+        let topic = H256::repeat_byte(2);
+        let filter: Filter = serde_json::from_value(serde_json::json!({
+            "fromBlock": "0x10",
+            "toBlock": "latest",
+            "address": H256::repeat_byte(1),
+            "topics": [null, [topic, H256::repeat_byte(3)], topic],
+        }))
+        .unwrap();
+        assert_eq!(filter.from_block, Some(BlockNumber::Number(16.into())));
+        assert_eq!(filter.to_block, Some(BlockNumber::Latest));
+        assert_eq!(filter.addresses(), [H256::repeat_byte(1)]);
+        assert_eq!(
+            filter.topics_by_position(),
+            [(2, vec![topic, H256::repeat_byte(3)]), (3, vec![topic])]
+        );
+
+        let filter: Filter = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert!(filter.addresses().is_empty());
+        assert!(filter.topics_by_position().is_empty());
+    }
+
+    #[test]
+    fn creating_pages() {
+        let page = Page::new(vec![1_u32, 2, 3], 2, |&item| L1BatchNumber(item));
+        assert_eq!(page.items, [1, 2]);
+        assert_eq!(page.next_cursor.unwrap().key(), &L1BatchNumber(2));
+
+        let page = Page::new(vec![1_u32, 2], 2, |&item| L1BatchNumber(item));
+        assert_eq!(page.items, [1, 2]);
+        assert!(page.next_cursor.is_none());
+    }
+}